@@ -13,7 +13,6 @@ mod inner {
 
     use clap::Parser;
     use graphitude::dot::parser::ParseError;
-    use graphitude::edge_multiplicity::EdgeMultiplicity;
     use graphitude::end_pair::EndPair as _;
     use graphitude::{
         bag_graph::BagGraph,
@@ -78,7 +77,7 @@ mod inner {
         D: Directedness,
     {
         match BagGraph::from_dot_string(data, builder) {
-            Ok(graph) => return Some(graph),
+            Ok(graph) => Some(graph),
             Err(ParseError::UnsupportedDirectedness) => None,
             Err(err) => {
                 eprintln!("Invalid DOT input: {err}");
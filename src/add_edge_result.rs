@@ -0,0 +1,207 @@
+//! The result of adding an edge to a graph.
+
+/// The result of [`GraphMut::add_edge`](crate::GraphMut::add_edge): either
+/// a new edge was added, or the graph does not allow parallel edges and the
+/// data of an existing edge was replaced.
+///
+/// This can be converted to and from a tuple of the edge's ID and the ID and
+/// data of any replaced edge with [`From`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddEdgeResult<E, D> {
+    /// A new edge was added.
+    Added(E),
+    /// An existing edge was replaced.
+    Updated {
+        /// The ID of the edge, which may differ from `old_id` if the graph
+        /// does not reuse edge IDs when replacing edges.
+        id: E,
+        /// The ID of the replaced edge.
+        old_id: E,
+        /// The data of the replaced edge.
+        old_data: D,
+    },
+}
+
+impl<E, D> AddEdgeResult<E, D> {
+    /// Returns the ID of the edge which now connects the two nodes.
+    pub fn edge_id(&self) -> &E {
+        match self {
+            Self::Added(id) | Self::Updated { id, .. } => id,
+        }
+    }
+
+    /// Returns the ID of the edge which now connects the two nodes,
+    /// discarding the rest of the result.
+    pub fn into_edge_id(self) -> E {
+        match self {
+            Self::Added(id) | Self::Updated { id, .. } => id,
+        }
+    }
+
+    /// Returns true if a new edge was added.
+    pub fn is_added(&self) -> bool {
+        matches!(self, Self::Added(_))
+    }
+
+    /// Returns true if an existing edge was replaced.
+    pub fn is_updated(&self) -> bool {
+        matches!(self, Self::Updated { .. })
+    }
+
+    /// Returns the ID of the new edge, or `None` if an existing edge was
+    /// replaced.
+    pub fn added(self) -> Option<E> {
+        match self {
+            Self::Added(id) => Some(id),
+            Self::Updated { .. } => None,
+        }
+    }
+
+    /// Returns the ID and data of the replaced edge, or `None` if a new edge
+    /// was added.
+    pub fn updated(self) -> Option<(E, D)> {
+        match self {
+            Self::Added(_) => None,
+            Self::Updated {
+                old_id, old_data, ..
+            } => Some((old_id, old_data)),
+        }
+    }
+
+    /// Converts the result into `Ok` with the ID of a new edge, or `Err`
+    /// with the ID and data of a replaced edge.  This allows the `?`
+    /// operator to be used where replacing an edge is unexpected.
+    pub fn into_result(self) -> Result<E, (E, D)> {
+        match self {
+            Self::Added(id) => Ok(id),
+            Self::Updated {
+                old_id, old_data, ..
+            } => Err((old_id, old_data)),
+        }
+    }
+
+    /// Returns the ID of the new edge.  Panics if an existing edge was
+    /// replaced.
+    pub fn unwrap(self) -> E {
+        self.expect("an existing edge was replaced")
+    }
+
+    /// Returns the ID of the new edge.  Panics with the given message if an
+    /// existing edge was replaced.
+    pub fn expect(self, message: &str) -> E {
+        match self {
+            Self::Added(id) => id,
+            Self::Updated { .. } => panic!("{}", message),
+        }
+    }
+}
+
+impl<E, D> From<(E, Option<(E, D)>)> for AddEdgeResult<E, D> {
+    fn from((id, replaced): (E, Option<(E, D)>)) -> Self {
+        match replaced {
+            None => Self::Added(id),
+            Some((old_id, old_data)) => Self::Updated {
+                id,
+                old_id,
+                old_data,
+            },
+        }
+    }
+}
+
+impl<E, D> From<AddEdgeResult<E, D>> for (E, Option<(E, D)>) {
+    fn from(result: AddEdgeResult<E, D>) -> Self {
+        match result {
+            AddEdgeResult::Added(id) => (id, None),
+            AddEdgeResult::Updated {
+                id,
+                old_id,
+                old_data,
+            } => (id, Some((old_id, old_data))),
+        }
+    }
+}
+
+impl<E, D> From<AddEdgeResult<E, D>> for Result<E, (E, D)> {
+    fn from(result: AddEdgeResult<E, D>) -> Self {
+        result.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_added() {
+        let mut graph = BagGraph::<(), i32, Directed>::default();
+        let a = graph.add_node(());
+        let result = graph.add_edge(&a, &a, 1);
+        assert!(result.is_added());
+        let id = result.edge_id().clone();
+        assert_eq!(result.clone().added(), Some(id.clone()));
+        assert_eq!(result.clone().updated(), None);
+        assert_eq!(result.clone().into_result(), Ok(id.clone()));
+        assert_eq!(result.unwrap(), id);
+    }
+
+    #[test]
+    fn test_updated() {
+        let mut graph = LinkedGraph::<(), i32, Undirected, SingleEdge>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let old_id = graph.add_edge(&a, &b, 1).unwrap();
+        let result = graph.add_edge(&b, &a, 2);
+        assert!(result.is_updated());
+        assert_eq!(result.clone().added(), None);
+        assert_eq!(result.clone().updated(), Some((old_id.clone(), 1)));
+        assert_eq!(result.clone().into_result(), Err((old_id.clone(), 1)));
+        let (id, replaced): (_, Option<_>) = result.into();
+        assert_eq!(replaced, Some((old_id, 1)));
+        assert_eq!(*graph.edge_data(&id), 2);
+    }
+
+    #[test]
+    fn test_question_mark() {
+        type TestGraph = LinkedGraph<(), i32, Directed, SingleEdge>;
+
+        // Adds edges between the given pairs of nodes, returning the number
+        // of edges added, or the data of the first edge to be replaced.
+        fn add_all(
+            graph: &mut TestGraph,
+            pairs: &[(<TestGraph as Graph>::NodeId, <TestGraph as Graph>::NodeId)],
+        ) -> Result<usize, i32> {
+            for (data, (from, into)) in pairs.iter().enumerate() {
+                graph
+                    .add_edge(from, into, data as i32)
+                    .into_result()
+                    .map_err(|(_, old_data)| old_data)?;
+            }
+            Ok(pairs.len())
+        }
+
+        let mut graph = TestGraph::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        assert_eq!(
+            add_all(
+                &mut graph,
+                &[(a.clone(), b.clone()), (b.clone(), a.clone())]
+            ),
+            Ok(2)
+        );
+        assert_eq!(
+            add_all(&mut graph, &[(a.clone(), a.clone()), (b, a)]),
+            Err(1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "an existing edge was replaced")]
+    fn test_unwrap_updated() {
+        let result = AddEdgeResult::from((1, Some((0, "old"))));
+        result.unwrap();
+    }
+}
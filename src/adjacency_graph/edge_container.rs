@@ -75,7 +75,8 @@ impl<T> EdgeContainer<T> for SingleItem<T> {
         std::iter::once(((), &self.0))
     }
 }
-/// A multi-item edge container
+/// A multi-item edge container, which iterates over its items in the order
+/// they were added.
 #[derive(Debug, Clone)]
 pub struct MultipleItems<T> {
     inner: Bag<T>,
@@ -119,7 +120,7 @@ impl<T> EdgeContainer<T> for MultipleItems<T> {
     where
         T: 'a,
     {
-        self.inner.pairs()
+        self.inner.ordered_pairs()
     }
 }
 
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
 use derivative::Derivative;
 
@@ -22,7 +22,8 @@ use crate::bag::{Bag, BagKey};
 ///
 /// This graph stores nodes in a contiguous vector and uses an adjacency matrix
 /// to track edges. The matrix implementation can be selected via the `S` (storage)
-/// type parameter, supporting either hash-based or bitvec-based storage.
+/// type parameter, supporting hash-based, bitvec-based, or compressed sparse
+/// row storage.
 ///
 /// Multiple edges between the same pair of nodes are not supported; adding an edge
 /// between two nodes that already have an edge will replace the existing edge's data.
@@ -31,7 +32,8 @@ use crate::bag::{Bag, BagKey};
 /// * `N` - The type of data stored in nodes
 /// * `E` - The type of data stored in edges
 /// * `D` - The directedness ([`Directed`] or [`Undirected`](crate::Undirected))
-/// * `S` - The storage type ([`HashStorage`] or [`BitvecStorage`](crate::adjacency_matrix::BitvecStorage))
+/// * `S` - The storage type ([`HashStorage`], [`BitvecStorage`](crate::adjacency_matrix::BitvecStorage),
+///   or [`CsrStorage`](crate::adjacency_matrix::CsrStorage))
 #[derive(Derivative)]
 #[derivative(Default(bound = ""))]
 pub struct AdjacencyGraph<N, E, D = Directed, M = SingleEdge, S = HashStorage>
@@ -52,6 +54,70 @@ where
     M: EdgeContainerSelector,
     S: Storage,
 {
+    /// Creates a graph from node data and `(from, into, data)` edges, where
+    /// `from` and `into` are positions in `nodes`.  The node IDs are returned
+    /// by [`node_ids`](Graph::node_ids) in the same order as `nodes`.
+    ///
+    /// This is much faster than adding the edges one at a time, because the
+    /// node storage and the adjacency matrix are each allocated once and the
+    /// edges are not looked up individually.  Edges between the same nodes
+    /// are handled as by [`add_edge`](GraphMut::add_edge): with
+    /// [`SingleEdge`], a later edge replaces an earlier one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an edge refers to a position past the end of `nodes`.
+    pub fn from_edges(
+        nodes: impl IntoIterator<Item = N>,
+        edges: impl IntoIterator<Item = (usize, usize, E)>,
+    ) -> Self {
+        let nodes = nodes.into_iter();
+        let mut bag = Bag::new();
+        bag.reserve(nodes.size_hint().0);
+        for data in nodes {
+            bag.insert(data);
+        }
+        let size = bag.len();
+
+        let mut entries: Vec<_> = edges
+            .into_iter()
+            .map(|(from, into, data)| {
+                assert!(
+                    from < size && into < size,
+                    "edge ({from}, {into}) refers to a missing node"
+                );
+                let (row, col) = D::sort_pair((from, into));
+                (row, col, data)
+            })
+            .collect();
+        // A stable sort keeps parallel edges in the order they were given.
+        entries.sort_by_key(|&(row, col, _)| (row, col));
+
+        let mut num_edges = 0;
+        let mut containers: Vec<(usize, usize, M::Container<E>)> =
+            Vec::with_capacity(entries.len());
+        for (row, col, data) in entries {
+            let old = match containers.last() {
+                Some(&(last_row, last_col, _)) if (last_row, last_col) == (row, col) => {
+                    containers.pop().map(|(_, _, container)| container)
+                }
+                _ => None,
+            };
+            let (container, _, replaced) = EdgeContainer::append(old, data);
+            if replaced.is_none() {
+                num_edges += 1;
+            }
+            containers.push((row, col, container));
+        }
+
+        Self {
+            nodes: bag,
+            adjacency: AdjacencyMatrix::from_sorted_entries(size, containers),
+            num_edges,
+            compaction_count: Default::default(),
+        }
+    }
+
     /// Creates a `NodeId` for the given `BagKey`.
     fn node_id(&self, key: BagKey) -> AdjacencyGraphNodeId<S> {
         AdjacencyGraphNodeId::new(key, self.compaction_count)
@@ -85,10 +151,28 @@ where
         self.nodes.get(id.key()).expect("no such node")
     }
 
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        if id.compaction_count() != self.compaction_count {
+            Err(GraphError::StaleId)
+        } else if self.nodes.get(id.key()).is_none() {
+            Err(GraphError::InvalidNodeId)
+        } else {
+            Ok(())
+        }
+    }
+
     fn node_ids(&self) -> impl Iterator<Item = <Self as Graph>::NodeId> {
         self.nodes.keys().map(|key| self.node_id(key))
     }
 
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.num_nodes() == 0
+    }
+
     fn edge_data(&self, eid: &Self::EdgeId) -> &Self::EdgeData {
         let (from, to) = eid.ends().into_values();
         self.adjacency
@@ -98,6 +182,18 @@ where
             .expect("no such edge index")
     }
 
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        if id.compaction_count() != self.compaction_count {
+            return Err(GraphError::StaleId);
+        }
+        let (from, to) = id.ends().into_values();
+        self.adjacency
+            .get(from.to_index(), to.to_index())
+            .and_then(|container| container.get(id.index()))
+            .map(|_| ())
+            .ok_or(GraphError::InvalidEdgeId)
+    }
+
     fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
         self.adjacency
             .iter()
@@ -137,6 +233,30 @@ where
             })
     }
 
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        let col = into.key().to_index();
+        if M::ALLOWS_PARALLEL_EDGES {
+            self.adjacency
+                .entries_in_col(col)
+                .map(|(_, container)| container.len())
+                .sum()
+        } else {
+            self.adjacency.col_len(col)
+        }
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        let row = from.key().to_index();
+        if M::ALLOWS_PARALLEL_EDGES {
+            self.adjacency
+                .entries_in_row(row)
+                .map(|(_, container)| container.len())
+                .sum()
+        } else {
+            self.adjacency.row_len(row)
+        }
+    }
+
     fn edges_into<'a, 'b: 'a>(
         &'a self,
         into: &'b Self::NodeId,
@@ -166,6 +286,47 @@ where
                     .map(move |(index, _)| self.edge_id(from_key, into_key, index))
             })
     }
+
+    fn fold_edges_by_source<A>(
+        &self,
+        mut init_fn: impl FnMut(&Self::NodeId) -> A,
+        mut fold_fn: impl FnMut(&mut A, &Self::EdgeId, &Self::EdgeData),
+    ) -> HashMap<Self::NodeId, A> {
+        // Accumulators are indexed by row, so a single pass over the matrix
+        // needs no per-node lookups.
+        let mut accs: Vec<Option<A>> = Vec::new();
+        for key in self.nodes.keys() {
+            let index = key.to_index();
+            if accs.len() <= index {
+                accs.resize_with(index + 1, || None);
+            }
+            accs[index] = Some(init_fn(&self.node_id(key)));
+        }
+        for (row, col, container) in self.adjacency.iter() {
+            let from_key = BagKey::from_index(row);
+            let into_key = BagKey::from_index(col);
+            for (index, data) in container.iter() {
+                let eid = self.edge_id(from_key, into_key, index);
+                if let Some(acc) = &mut accs[row] {
+                    fold_fn(acc, &eid, data);
+                }
+                // An undirected edge is also an outgoing edge of its other
+                // end.
+                if !D::IS_DIRECTED
+                    && row != col
+                    && let Some(acc) = &mut accs[col]
+                {
+                    fold_fn(acc, &eid, data);
+                }
+            }
+        }
+        accs.into_iter()
+            .enumerate()
+            .filter_map(|(index, acc)| {
+                acc.map(|acc| (self.node_id(BagKey::from_index(index)), acc))
+            })
+            .collect()
+    }
 }
 
 impl<N, E, D, M, S> Clone for AdjacencyGraph<N, E, D, M, S>
@@ -210,7 +371,7 @@ where
         from: &Self::NodeId,
         into: &Self::NodeId,
         data: Self::EdgeData,
-    ) -> (Self::EdgeId, Option<(Self::EdgeId, Self::EdgeData)>) {
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
         let from_index = from.key().to_index();
         let into_index = into.key().to_index();
         let old_data = self.adjacency.remove(from_index, into_index);
@@ -218,10 +379,14 @@ where
         self.adjacency.insert(from_index, into_index, new_data);
         let edge_id = self.edge_id(from.key(), into.key(), index);
         match replaced {
-            Some(replaced) => (edge_id.clone(), Some((edge_id, replaced))),
+            Some(old_data) => AddEdgeResult::Updated {
+                id: edge_id.clone(),
+                old_id: edge_id,
+                old_data,
+            },
             None => {
                 self.num_edges += 1;
-                (edge_id, None)
+                AddEdgeResult::Added(edge_id)
             }
         }
     }
@@ -291,3 +456,73 @@ where
         format_debug(self, f, "AdjacencyGraph")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::adjacency_matrix::CsrStorage;
+
+    use super::*;
+
+    #[test]
+    fn test_from_edges_matches_add_edge() {
+        let edges = [
+            (0, 1, 'a'),
+            (2, 1, 'b'),
+            (1, 2, 'c'),
+            (0, 1, 'd'),
+            (3, 3, 'e'),
+        ];
+        let bulk = AdjacencyGraph::<u32, char, Undirected, MultipleEdges, CsrStorage>::from_edges(
+            0..4,
+            edges,
+        );
+        let mut incremental = AdjacencyGraph::<u32, char, Undirected, MultipleEdges>::default();
+        let nids: Vec<_> = (0..4).map(|i| incremental.add_node(i)).collect();
+        for (from, into, data) in edges {
+            incremental.add_edge(&nids[from], &nids[into], data);
+        }
+
+        assert_eq!(bulk.num_nodes(), 4);
+        assert_eq!(bulk.num_edges(), 5);
+        let bulk_nids: Vec<_> = bulk.node_ids().collect();
+        for (i, nid) in bulk_nids.iter().enumerate() {
+            assert_eq!(bulk.node_data(nid), &(i as u32));
+        }
+        for from in 0..4 {
+            for into in 0..4 {
+                let bulk_data: Vec<_> = bulk
+                    .edges_from_into(&bulk_nids[from], &bulk_nids[into])
+                    .map(|eid| *bulk.edge_data(&eid))
+                    .collect();
+                let incremental_data: Vec<_> = incremental
+                    .edges_from_into(&nids[from], &nids[into])
+                    .map(|eid| *incremental.edge_data(&eid))
+                    .collect();
+                assert_eq!(bulk_data, incremental_data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_edges_replaces_single_edges() {
+        let mut graph = AdjacencyGraph::<(), u32, Directed>::from_edges(
+            [(); 3],
+            [(0, 1, 1), (1, 0, 2), (0, 1, 3)],
+        );
+        let nids: Vec<_> = graph.node_ids().collect();
+        assert_eq!(graph.num_edges(), 2);
+        let eid = graph.edges_from_into(&nids[0], &nids[1]).next().unwrap();
+        assert_eq!(graph.edge_data(&eid), &3);
+
+        // The graph can be changed as usual afterwards.
+        graph.add_edge(&nids[2], &nids[0], 4);
+        graph.remove_node(&nids[1]);
+        assert_eq!(graph.num_edges(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing node")]
+    fn test_from_edges_missing_node() {
+        AdjacencyGraph::<(), (), Directed>::from_edges([(); 2], [(0, 2, ())]);
+    }
+}
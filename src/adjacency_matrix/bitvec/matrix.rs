@@ -1,6 +1,6 @@
 use std::{fmt::Debug, mem::MaybeUninit, ops::Range};
 
-use bitvec::{slice::BitSlice, vec::BitVec};
+use bitvec::{field::BitField, slice::BitSlice, vec::BitVec};
 
 use crate::{
     Directedness,
@@ -82,6 +82,49 @@ where
         &mut self.liveness[range]
     }
 
+    /// Returns the liveness bits of the given row, or `None` if the row is
+    /// out of bounds.
+    fn row_bits(&self, row: usize) -> Option<&BitSlice> {
+        (row < self.size_bound()).then(|| {
+            let row_start = self.indexing.unchecked_liveness_index(row, 0);
+            let row_end = self.indexing.unchecked_liveness_index(row + 1, 0);
+            &self.liveness_bits()[row_start.0..row_end.0]
+        })
+    }
+
+    /// Returns the reflected liveness bits of the given column, or `None` if
+    /// the column is out of bounds.
+    fn col_bits(&self, col: usize) -> Option<&BitSlice> {
+        (col < self.size_bound()).then(|| {
+            let col_start = self.indexing.unchecked_liveness_index(col, 0);
+            let col_end = self.indexing.unchecked_liveness_index(col + 1, 0);
+            &self.reflected_liveness_bits()[col_start.0..col_end.0]
+        })
+    }
+
+    /// Iterates over the bits of a row as 64-bit words, in the layout used by
+    /// [`AdjacencyMatrix::row_or_into`].  Rows don't start on word
+    /// boundaries, so each word is assembled from at most two storage words.
+    fn row_words(&self, row: usize) -> impl Iterator<Item = u64> + '_ {
+        self.row_bits(row)
+            .into_iter()
+            .flat_map(|bits| bits.chunks(64).map(|chunk| chunk.load_le::<u64>()))
+    }
+
+    /// Returns the columns which are set in the words of `row` of `self` and
+    /// not set in `mask`.  Bits past the end of `mask` count as unset.
+    fn cols_not_in(&self, row: usize, mask: &[u64]) -> Vec<usize> {
+        let mut cols = Vec::new();
+        for (i, word) in self.row_words(row).enumerate() {
+            let mut bits = word & !mask.get(i).copied().unwrap_or(0);
+            while bits != 0 {
+                cols.push(i * 64 + bits.trailing_zeros() as usize);
+                bits &= bits - 1;
+            }
+        }
+        cols
+    }
+
     fn get_data_ref(&self, index: LivenessIndex) -> Option<&V> {
         self.liveness_bits()[index]
             .then(|| self.unchecked_get_data_ref(self.indexing.liveness_index_to_data_index(index)))
@@ -108,6 +151,70 @@ where
     }
 }
 
+/// Bulk operations on whole rows and matrices, which find the entries to
+/// change a word at a time rather than visiting every entry.  For undirected
+/// matrices, rows and columns are the same, so changing a row also changes
+/// the corresponding column.
+impl<V, D> BitvecAdjacencyMatrix<V, D>
+where
+    D: Directedness + Default,
+{
+    /// Returns the number of entries in the given row.  The same as
+    /// [`AdjacencyMatrix::row_len`].
+    pub fn count_in_row(&self, row: usize) -> usize {
+        self.row_len(row)
+    }
+
+    /// Adds an entry to row `dst` for each entry of row `src` in a column
+    /// where `dst` has none, with a copy of the data of the entry in `src`.
+    /// This is the step of a transitive closure where `dst` inherits the
+    /// successors of `src`.
+    pub fn row_or(&mut self, dst: usize, src: usize)
+    where
+        V: Clone,
+    {
+        let dst_words: Vec<_> = self.row_words(dst).collect();
+        for col in self.cols_not_in(src, &dst_words) {
+            let data = self.get(src, col).expect("entry is live").clone();
+            self.insert(dst, col, data);
+        }
+    }
+
+    /// Removes the entries of row `dst` in columns where row `src` has no
+    /// entry.
+    pub fn row_and(&mut self, dst: usize, src: usize) {
+        let src_words: Vec<_> = self.row_words(src).collect();
+        for col in self.cols_not_in(dst, &src_words) {
+            self.remove(dst, col);
+        }
+    }
+
+    /// Adds a copy of each entry of `other` at a position where `self` has
+    /// no entry.  Entries already in `self` keep their data.
+    pub fn union_with(&mut self, other: &Self)
+    where
+        V: Clone,
+    {
+        for row in 0..other.size_bound() {
+            let words: Vec<_> = self.row_words(row).collect();
+            for col in other.cols_not_in(row, &words) {
+                let data = other.get(row, col).expect("entry is live").clone();
+                self.insert(row, col, data);
+            }
+        }
+    }
+
+    /// Removes the entries of `self` at positions where `other` has no entry.
+    pub fn intersect_with(&mut self, other: &Self) {
+        for row in 0..self.size_bound() {
+            let words: Vec<_> = other.row_words(row).collect();
+            for col in self.cols_not_in(row, &words) {
+                self.remove(row, col);
+            }
+        }
+    }
+}
+
 impl<V, D> Drop for BitvecAdjacencyMatrix<V, D>
 where
     D: Directedness + Default,
@@ -282,6 +389,31 @@ where
         })
     }
 
+    fn row_len(&self, row: usize) -> usize {
+        self.row_bits(row).map_or(0, BitSlice::count_ones)
+    }
+
+    fn col_len(&self, col: usize) -> usize {
+        self.col_bits(col).map_or(0, BitSlice::count_ones)
+    }
+
+    fn row_or_into(&self, row: usize, target: &mut Vec<u64>) {
+        let num_words = self.size_bound().div_ceil(64);
+        if row < self.size_bound() && target.len() < num_words {
+            target.resize(num_words, 0);
+        }
+        for (word, bits) in target.iter_mut().zip(self.row_words(row)) {
+            *word |= bits;
+        }
+    }
+
+    fn row_and_count(&self, row: usize, mask: &[u64]) -> usize {
+        self.row_words(row)
+            .zip(mask)
+            .map(|(bits, mask)| (bits & mask).count_ones() as usize)
+            .sum()
+    }
+
     fn clear(&mut self) {
         // Drop all initialized values before clearing
         for index in self.liveness[self.liveness_range()]
@@ -459,6 +591,78 @@ where
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::{Directed, Undirected};
+
+    use super::*;
+
+    type Reachability = BitvecAdjacencyMatrix<(), Directed>;
+
+    #[test]
+    fn test_transitive_closure() {
+        // 0 -> 1 -> 2 -> 3, and 4 -> 0.
+        let mut matrix = Reachability::with_size(5);
+        for (row, col) in [(0, 1), (1, 2), (2, 3), (4, 0)] {
+            matrix.insert(row, col, ());
+        }
+        // Warshall's algorithm, a row at a time.
+        for k in 0..5 {
+            for row in 0..5 {
+                if matrix.get(row, k).is_some() {
+                    matrix.row_or(row, k);
+                }
+            }
+        }
+        let counts: Vec<_> = (0..5).map(|row| matrix.count_in_row(row)).collect();
+        assert_eq!(counts, vec![3, 2, 1, 0, 4]);
+        assert_eq!(matrix.len(), 10);
+    }
+
+    #[test]
+    fn test_row_and() {
+        let mut matrix = BitvecAdjacencyMatrix::<u32, Undirected>::with_size(0);
+        for col in [1, 2, 3, 100] {
+            matrix.insert(0, col, col as u32);
+        }
+        matrix.insert(5, 2, 0);
+        matrix.insert(5, 100, 0);
+        matrix.row_and(0, 5);
+        let mut row: Vec<_> = matrix
+            .entries_in_row(0)
+            .map(|(col, data)| (col, *data))
+            .collect();
+        row.sort();
+        assert_eq!(row, vec![(2, 2), (100, 100)]);
+        // The matrix is symmetric, so the columns were cleared too.
+        assert_eq!(matrix.get(1, 0), None);
+        assert_eq!(matrix.len(), 4);
+    }
+
+    #[test]
+    fn test_union_and_intersection() {
+        let mut a = BitvecAdjacencyMatrix::<&str, Directed>::with_size(0);
+        a.insert(0, 1, "a01");
+        a.insert(1, 2, "a12");
+        let mut b = BitvecAdjacencyMatrix::<&str, Directed>::with_size(0);
+        b.insert(0, 1, "b01");
+        b.insert(70, 3, "b70");
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.len(), 3);
+        assert_eq!(union.get(0, 1), Some(&"a01"));
+        assert_eq!(union.get(70, 3), Some(&"b70"));
+
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        assert_eq!(
+            intersection.iter().collect::<Vec<_>>(),
+            vec![(0, 1, &"a01")]
+        );
+    }
+}
+
 // #[test]
 // fn test_debug_empty() {
 //     let matrix = SymmetricBitvecAdjacencyMatrix::<usize, ()>::new();
@@ -0,0 +1,373 @@
+use std::{fmt::Debug, marker::PhantomData, mem, ops::Range};
+
+use derivative::Derivative;
+
+use crate::{
+    Directedness,
+    adjacency_matrix::{AdjacencyMatrix, CsrStorage, trait_def::format_debug},
+};
+
+/// Adjacency matrix stored in compressed sparse row (CSR) form.
+///
+/// The entries of each row are stored contiguously, sorted by column, so a
+/// row is a slice found in constant time, and an entry is found by binary
+/// search within its row.  A second index lists the rows of the entries in
+/// each column, so columns can be traversed without scanning the matrix.
+/// The memory used is proportional to the number of entries plus the number
+/// of rows, which suits very sparse graphs with many nodes.
+///
+/// Inserting or removing an entry shifts the entries after it, taking time
+/// proportional to the number of entries, so this storage is best for
+/// matrices which are built once and then mostly read.  For undirected
+/// graphs, each entry is stored once, in the row of its lower index.
+#[derive(Clone, Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct CsrAdjacencyMatrix<V, D> {
+    /// `row_starts[row]..row_starts[row + 1]` is the range of `cols` and
+    /// `values` holding the entries of `row`.  Empty when the matrix has no
+    /// rows.
+    row_starts: Vec<usize>,
+    cols: Vec<usize>,
+    values: Vec<V>,
+    /// `col_starts[col]..col_starts[col + 1]` is the range of `rows` holding
+    /// the rows of the entries in `col`, in increasing order.  Invariant:
+    /// `rows` contains `row` in the range of `col` if and only if `cols`
+    /// contains `col` in the range of `row`.
+    col_starts: Vec<usize>,
+    rows: Vec<usize>,
+    directedness: PhantomData<D>,
+}
+
+impl<V, D> CsrAdjacencyMatrix<V, D>
+where
+    D: Directedness + Default,
+{
+    /// Gets the range of `cols` and `values` holding the entries of `row`.
+    fn row_range(&self, row: usize) -> Range<usize> {
+        match self.row_starts.get(row..row + 2) {
+            Some(&[start, end]) => start..end,
+            _ => 0..0,
+        }
+    }
+
+    /// Gets the range of `rows` holding the rows of the entries in `col`.
+    fn col_range(&self, col: usize) -> Range<usize> {
+        match self.col_starts.get(col..col + 2) {
+            Some(&[start, end]) => start..end,
+            _ => 0..0,
+        }
+    }
+
+    /// Finds the index in `cols` and `values` of the entry at `row` and
+    /// `col`, which must already be in canonical order.
+    fn position(&self, row: usize, col: usize) -> Result<usize, usize> {
+        let range = self.row_range(row);
+        let start = range.start;
+        self.cols[range]
+            .binary_search(&col)
+            .map(|offset| start + offset)
+            .map_err(|offset| start + offset)
+    }
+
+    /// Adds empty rows and columns until the matrix has at least `size` of
+    /// each.
+    fn grow(&mut self, size: usize) {
+        if self.row_starts.len() <= size {
+            self.row_starts.resize(size + 1, self.cols.len());
+        }
+        if self.col_starts.len() <= size {
+            self.col_starts.resize(size + 1, self.rows.len());
+        }
+    }
+
+    /// Removes the entries for which `keep` returns false, given the
+    /// canonical row and column of each entry.  Takes time proportional to
+    /// the size of the matrix, rather than to the number of entries removed.
+    fn retain(&mut self, mut keep: impl FnMut(usize, usize) -> bool) {
+        let size = self.size_bound();
+        let mut old_entries = mem::take(&mut self.cols)
+            .into_iter()
+            .zip(mem::take(&mut self.values));
+        for row in 0..size {
+            let row_len = self.row_starts[row + 1] - self.row_starts[row];
+            self.row_starts[row] = self.cols.len();
+            for (col, value) in old_entries.by_ref().take(row_len) {
+                if keep(row, col) {
+                    self.cols.push(col);
+                    self.values.push(value);
+                }
+            }
+        }
+        if let Some(end) = self.row_starts.last_mut() {
+            *end = self.cols.len();
+        }
+        self.rebuild_col_index();
+    }
+
+    /// Rebuilds `col_starts` and `rows` from the row entries.
+    fn rebuild_col_index(&mut self) {
+        let size = self.size_bound();
+        self.col_starts.clear();
+        self.col_starts.resize(size + 1, 0);
+        for &col in &self.cols {
+            self.col_starts[col + 1] += 1;
+        }
+        for col in 0..size {
+            self.col_starts[col + 1] += self.col_starts[col];
+        }
+        let mut next = self.col_starts.clone();
+        self.rows.clear();
+        self.rows.resize(self.cols.len(), 0);
+        for row in 0..size {
+            for &col in &self.cols[self.row_range(row)] {
+                self.rows[next[col]] = row;
+                next[col] += 1;
+            }
+        }
+    }
+}
+
+impl<V, D> AdjacencyMatrix for CsrAdjacencyMatrix<V, D>
+where
+    D: Directedness + Default,
+{
+    type Value = V;
+    type Directedness = D;
+    type Storage = CsrStorage;
+
+    fn with_size(size: usize) -> Self {
+        Self {
+            row_starts: Vec::with_capacity(size + 1),
+            cols: Vec::new(),
+            values: Vec::new(),
+            col_starts: Vec::with_capacity(size + 1),
+            rows: Vec::new(),
+            directedness: PhantomData,
+        }
+    }
+
+    fn from_sorted_entries(
+        size: usize,
+        entries: impl IntoIterator<Item = (usize, usize, V)>,
+    ) -> Self {
+        let mut matrix = Self::with_size(size);
+        matrix.row_starts.resize(size + 1, 0);
+        let entries = entries.into_iter();
+        matrix.cols.reserve(entries.size_hint().0);
+        matrix.values.reserve(entries.size_hint().0);
+        for (row, col, data) in entries {
+            debug_assert!(row < size && col < size, "entry out of range");
+            debug_assert_eq!((row, col), D::sort_pair((row, col)));
+            matrix.row_starts[row + 1] += 1;
+            matrix.cols.push(col);
+            matrix.values.push(data);
+        }
+        for row in 0..size {
+            matrix.row_starts[row + 1] += matrix.row_starts[row];
+        }
+        matrix.rebuild_col_index();
+        matrix
+    }
+
+    fn size_bound(&self) -> usize {
+        self.row_starts.len().saturating_sub(1)
+    }
+
+    fn insert(&mut self, row: usize, col: usize, data: V) -> Option<V> {
+        let (i1, i2) = D::sort_pair((row, col));
+        self.grow(i2.max(i1) + 1);
+        match self.position(i1, i2) {
+            Ok(index) => Some(mem::replace(&mut self.values[index], data)),
+            Err(index) => {
+                self.cols.insert(index, i2);
+                self.values.insert(index, data);
+                for start in &mut self.row_starts[i1 + 1..] {
+                    *start += 1;
+                }
+                let range = self.col_range(i2);
+                let offset = self.rows[range.clone()].partition_point(|&r| r < i1);
+                self.rows.insert(range.start + offset, i1);
+                for start in &mut self.col_starts[i2 + 1..] {
+                    *start += 1;
+                }
+                None
+            }
+        }
+    }
+
+    fn get(&self, row: usize, col: usize) -> Option<&V> {
+        let (i1, i2) = D::sort_pair((row, col));
+        self.position(i1, i2).ok().map(|index| &self.values[index])
+    }
+
+    fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut V> {
+        let (i1, i2) = D::sort_pair((row, col));
+        self.position(i1, i2)
+            .ok()
+            .map(|index| &mut self.values[index])
+    }
+
+    fn remove(&mut self, row: usize, col: usize) -> Option<V> {
+        let (i1, i2) = D::sort_pair((row, col));
+        let index = self.position(i1, i2).ok()?;
+        self.cols.remove(index);
+        let value = self.values.remove(index);
+        for start in &mut self.row_starts[i1 + 1..] {
+            *start -= 1;
+        }
+        let range = self.col_range(i2);
+        let offset = self.rows[range.clone()].partition_point(|&r| r < i1);
+        self.rows.remove(range.start + offset);
+        for start in &mut self.col_starts[i2 + 1..] {
+            *start -= 1;
+        }
+        Some(value)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (usize, usize, &'a V)>
+    where
+        V: 'a,
+    {
+        (0..self.size_bound()).flat_map(move |row| {
+            let range = self.row_range(row);
+            self.cols[range.clone()]
+                .iter()
+                .zip(&self.values[range])
+                .map(move |(&col, value)| (row, col, value))
+        })
+    }
+
+    fn into_iter(self) -> impl Iterator<Item = (usize, usize, V)> {
+        let row_lens: Vec<usize> = self
+            .row_starts
+            .windows(2)
+            .map(|window| window[1] - window[0])
+            .collect();
+        row_lens
+            .into_iter()
+            .enumerate()
+            .flat_map(|(row, len)| std::iter::repeat_n(row, len))
+            .zip(self.cols)
+            .zip(self.values)
+            .map(|((row, col), value)| (row, col, value))
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn entries_in_row(&self, row: usize) -> impl Iterator<Item = (usize, &'_ V)> + '_ {
+        // In an undirected matrix, the entries with a lower index than `row`
+        // are stored in the rows of their lower index.
+        let lower_entries = (!D::IS_DIRECTED).then(|| {
+            self.rows[self.col_range(row)]
+                .iter()
+                .filter(move |&&r| r != row)
+                .map(move |&r| (r, self.get(r, row).unwrap()))
+        });
+        let range = self.row_range(row);
+        lower_entries.into_iter().flatten().chain(
+            self.cols[range.clone()]
+                .iter()
+                .copied()
+                .zip(&self.values[range]),
+        )
+    }
+
+    fn entries_in_col(&self, col: usize) -> impl Iterator<Item = (usize, &'_ V)> + '_ {
+        let (directed, undirected) = if D::IS_DIRECTED {
+            let entries = self.rows[self.col_range(col)]
+                .iter()
+                .map(move |&row| (row, self.get(row, col).unwrap()));
+            (Some(entries), None)
+        } else {
+            (None, Some(self.entries_in_row(col)))
+        };
+        directed
+            .into_iter()
+            .flatten()
+            .chain(undirected.into_iter().flatten())
+    }
+
+    fn row_len(&self, row: usize) -> usize {
+        let forward = self.row_range(row).len();
+        if D::IS_DIRECTED {
+            forward
+        } else {
+            let has_loop = self.position(row, row).is_ok();
+            forward + self.col_range(row).len() - usize::from(has_loop)
+        }
+    }
+
+    fn col_len(&self, col: usize) -> usize {
+        if D::IS_DIRECTED {
+            self.col_range(col).len()
+        } else {
+            self.row_len(col)
+        }
+    }
+
+    fn clear_row_and_column(&mut self, row: usize, col: usize) {
+        if D::IS_DIRECTED {
+            self.retain(|r, c| r != row && c != col);
+        } else {
+            self.retain(|r, c| r != row && r != col && c != row && c != col);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cols.clear();
+        self.values.clear();
+        self.rows.clear();
+        self.row_starts.fill(0);
+        self.col_starts.fill(0);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.row_starts.reserve(additional);
+        self.col_starts.reserve(additional);
+    }
+
+    fn reserve_exact(&mut self, additional: usize) {
+        self.row_starts.reserve_exact(additional);
+        self.col_starts.reserve_exact(additional);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        let rows_used = (0..self.size_bound())
+            .rev()
+            .find(|&row| !self.row_range(row).is_empty())
+            .map_or(0, |row| row + 1);
+        let cols_used = (0..self.size_bound())
+            .rev()
+            .find(|&col| !self.col_range(col).is_empty())
+            .map_or(0, |col| col + 1);
+        let size = rows_used.max(cols_used);
+        if size == 0 {
+            self.row_starts.clear();
+            self.col_starts.clear();
+        } else {
+            self.row_starts.truncate(size + 1);
+            self.col_starts.truncate(size + 1);
+        }
+        self.row_starts.shrink_to_fit();
+        self.cols.shrink_to_fit();
+        self.values.shrink_to_fit();
+        self.col_starts.shrink_to_fit();
+        self.rows.shrink_to_fit();
+    }
+}
+
+impl<V, D> Debug for CsrAdjacencyMatrix<V, D>
+where
+    V: Debug,
+    D: Directedness + Default,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_debug(self, f, "CsrAdjacencyMatrix")
+    }
+}
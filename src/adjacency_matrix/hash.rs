@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
+    hash::BuildHasher,
     marker::PhantomData,
 };
 
@@ -9,39 +10,41 @@ use derivative::Derivative;
 use crate::{
     Directedness,
     adjacency_matrix::{AdjacencyMatrix, HashStorage, trait_def::format_debug},
+    hashing::DefaultHashBuilder,
 };
 
 /// Hash-based asymmetric adjacency matrix for directed graphs.
 ///
 /// Stores separate forward and backward edge indices for efficient traversal
-/// in both directions.
+/// in both directions.  `S` is the hasher used for the indices.
 #[derive(Clone, Derivative)]
-#[derivative(Default(bound = "D: Default"))]
-pub struct HashAdjacencyMatrix<V, D> {
+#[derivative(Default(bound = "D: Default, S: Default"))]
+pub struct HashAdjacencyMatrix<V, D, S = DefaultHashBuilder> {
     /// Maps each row index to a map of column indices and their associated values.
     /// This represents the forward edges in a directed graph.
-    entries: HashMap<usize, HashMap<usize, V>>,
+    entries: HashMap<usize, HashMap<usize, V, S>, S>,
     /// Maps each column index to a set of row indices that have entries in
     /// `entries`.  Invariant: `entries[row][col]` exists if and only if
     /// `reverse_entries[col]` contains `row`.  This allows efficient retrieval
     /// of all rows that have an entry for a given column.
-    reverse_entries: HashMap<usize, HashSet<usize>>,
+    reverse_entries: HashMap<usize, HashSet<usize, S>, S>,
     size_bound: usize,
     directedness: PhantomData<D>,
 }
 
-impl<V, D> AdjacencyMatrix for HashAdjacencyMatrix<V, D>
+impl<V, D, S> AdjacencyMatrix for HashAdjacencyMatrix<V, D, S>
 where
     D: Directedness + Default,
+    S: BuildHasher + Default + Clone,
 {
     type Value = V;
     type Directedness = D;
-    type Storage = HashStorage;
+    type Storage = HashStorage<S>;
 
     fn with_size(size: usize) -> Self {
         Self {
-            entries: HashMap::with_capacity(size),
-            reverse_entries: HashMap::with_capacity(size),
+            entries: HashMap::with_capacity_and_hasher(size, S::default()),
+            reverse_entries: HashMap::with_capacity_and_hasher(size, S::default()),
             size_bound: 0,
             directedness: PhantomData,
         }
@@ -135,9 +138,11 @@ where
 
     fn entries_in_col(&self, col: usize) -> impl Iterator<Item = (usize, &'_ V)> + '_ {
         let (directed, undirected) = if D::IS_DIRECTED {
-            let sources = self.reverse_entries.get(&col).cloned().unwrap_or_default();
+            // Only the rows recorded in the reverse index are visited, so this
+            // takes time proportional to the number of entries in the column.
+            let sources = self.reverse_entries.get(&col).into_iter().flatten();
             (
-                Some(sources.into_iter().filter_map(move |row| {
+                Some(sources.filter_map(move |&row| {
                     self.entries
                         .get(&row)
                         .and_then(|targets| targets.get(&col))
@@ -219,10 +224,11 @@ where
     }
 }
 
-impl<V, D> Debug for HashAdjacencyMatrix<V, D>
+impl<V, D, S> Debug for HashAdjacencyMatrix<V, D, S>
 where
     V: Debug,
     D: Directedness + Default,
+    S: BuildHasher + Default + Clone,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         format_debug(self, f, "HashAdjacencyMatrix")
@@ -1,6 +1,7 @@
 #[cfg(feature = "bitvec")]
 pub mod bitvec;
 
+pub mod csr;
 pub mod hash;
 pub mod trait_def;
 
@@ -9,7 +10,5 @@ mod tests;
 
 #[cfg(feature = "bitvec")]
 pub use storage::BitvecStorage;
-pub use storage::{HashStorage, Storage};
+pub use storage::{CsrStorage, HashStorage, Storage};
 pub use trait_def::AdjacencyMatrix;
-
-pub(crate) use storage::CompactionCount;
@@ -1,8 +1,13 @@
-use std::{fmt::Debug, hash::Hash};
+use std::{
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+};
 
 use crate::{
     Directedness,
-    adjacency_matrix::{AdjacencyMatrix, hash::HashAdjacencyMatrix},
+    adjacency_matrix::{AdjacencyMatrix, csr::CsrAdjacencyMatrix, hash::HashAdjacencyMatrix},
+    hashing::DefaultHashBuilder,
 };
 
 #[cfg(feature = "bitvec")]
@@ -15,24 +20,16 @@ use crate::adjacency_matrix::bitvec::matrix::BitvecAdjacencyMatrix;
 pub(crate) trait CompactionCount:
     Clone + Copy + Debug + Default + Eq + Hash + PartialOrd + Ord + Send + Sync
 {
-    fn increment(self) -> Self;
 }
 
-impl CompactionCount for () {
-    fn increment(self) -> Self {
-        self
-    }
-}
+impl CompactionCount for () {}
 
-impl CompactionCount for usize {
-    fn increment(self) -> Self {
-        self.wrapping_add(1)
-    }
-}
+impl CompactionCount for usize {}
 
 /// Trait defining storage backend behavior for adjacency matrices.
 ///
-/// Implemented by [`BitvecStorage`] and [`HashStorage`] marker types.
+/// Implemented by [`BitvecStorage`], [`HashStorage`], and [`CsrStorage`]
+/// marker types.
 pub trait Storage {
     #[allow(private_bounds)]
     type CompactionCount: CompactionCount;
@@ -58,13 +55,29 @@ impl Storage for BitvecStorage {
         D: Directedness + Default;
 }
 
-/// Marker type for hash-based adjacency matrix storage.
-pub struct HashStorage;
+/// Marker type for hash-based adjacency matrix storage, using the hasher
+/// `S`.
+pub struct HashStorage<S = DefaultHashBuilder>(PhantomData<S>);
+
+impl<S> Storage for HashStorage<S>
+where
+    S: BuildHasher + Default + Clone,
+{
+    type CompactionCount = ();
+    type Matrix<V, D>
+        = HashAdjacencyMatrix<V, D, S>
+    where
+        D: Directedness + Default;
+}
+
+/// Marker type for adjacency matrix storage in compressed sparse row form.
+/// See [`CsrAdjacencyMatrix`] for when to prefer it.
+pub struct CsrStorage;
 
-impl Storage for HashStorage {
+impl Storage for CsrStorage {
     type CompactionCount = ();
     type Matrix<V, D>
-        = HashAdjacencyMatrix<V, D>
+        = CsrAdjacencyMatrix<V, D>
     where
         D: Directedness + Default;
 }
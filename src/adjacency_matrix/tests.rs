@@ -3,11 +3,13 @@
 
 use super::super::*;
 use crate::adjacency_matrix::bitvec::matrix::BitvecAdjacencyMatrix;
+use crate::adjacency_matrix::csr::CsrAdjacencyMatrix;
 use crate::adjacency_matrix::hash::HashAdjacencyMatrix;
 use crate::test_util::{DropCounter, DroppableValue};
 use generate_test_macro::generate_test_macro;
 use quickcheck::{Arbitrary, TestResult};
 use std::collections::HashMap;
+use std::hash::RandomState;
 use std::marker::PhantomData;
 
 use crate::adjacency_matrix::AdjacencyMatrix;
@@ -194,6 +196,36 @@ where
         assert!(entries2.iter().any(|(from, _)| *from == 1));
     }
 
+    #[test]
+    fn test_row_words() {
+        let mut matrix = M::default();
+        matrix.insert(0, 1, "a");
+        matrix.insert(0, 70, "b");
+        matrix.insert(2, 1, "c");
+        assert_eq!(matrix.row_len(0), 2);
+        assert_eq!(matrix.col_len(1), 2);
+        assert_eq!(matrix.row_len(100), 0);
+
+        let mut target = vec![1 << 5];
+        matrix.row_or_into(0, &mut target);
+        assert_eq!(target, vec![1 << 5 | 1 << 1, 1 << 6]);
+        matrix.row_or_into(2, &mut target);
+        matrix.row_or_into(100, &mut target);
+        assert_eq!(target, vec![1 << 5 | 1 << 1, 1 << 6]);
+        matrix.row_or_into(1, &mut target);
+        if M::Directedness::IS_DIRECTED {
+            assert_eq!(target[0], 1 << 5 | 1 << 1);
+        } else {
+            assert_eq!(target[0], 1 << 5 | 1 << 2 | 1 << 1 | 1);
+        }
+
+        assert_eq!(matrix.row_and_count(0, &[!0, !0]), 2);
+        assert_eq!(matrix.row_and_count(0, &[!0]), 1);
+        assert_eq!(matrix.row_and_count(0, &[1 << 1, 1 << 6]), 2);
+        assert_eq!(matrix.row_and_count(0, &[0, 1 << 7]), 0);
+        assert_eq!(matrix.row_and_count(100, &[!0]), 0);
+    }
+
     #[test]
     fn test_large_indices() {
         let mut matrix = M::default();
@@ -604,6 +636,27 @@ where
         true
     }
 
+    #[quickcheck]
+    fn prop_from_sorted_entries_consistent(ArbMatrix { matrix, insertions }: ArbMatrix<M>) -> bool {
+        let entries = insertions
+            .into_iter()
+            .map(|(row, col, data)| (M::Directedness::sort_pair((row, col)), data))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        let size = compute_size(&matrix);
+        let built = M::from_sorted_entries(
+            size,
+            entries
+                .into_iter()
+                .map(|((row, col), data)| (row, col, data)),
+        );
+        built.len() == matrix.len()
+            && (0..size).all(|i| {
+                (0..size).all(|j| built.get(i, j) == matrix.get(i, j))
+                    && built.row_len(i) == matrix.row_len(i)
+                    && built.col_len(i) == matrix.col_len(i)
+            })
+    }
+
     #[quickcheck]
     fn prop_insert_and_get_consistent(ArbMatrix { mut matrix, .. }: ArbMatrix<M>) -> bool {
         let entries: Vec<_> = matrix
@@ -686,6 +739,23 @@ where
         TestResult::passed()
     }
 
+    #[quickcheck]
+    fn prop_row_words_consistent(ArbMatrix { matrix, .. }: ArbMatrix<M>) -> bool {
+        (0..matrix.size_bound()).all(|row| {
+            let mut target = Vec::new();
+            matrix.row_or_into(row, &mut target);
+            let expected: Vec<_> = matrix.entries_in_row(row).map(|(col, _)| col).collect();
+            let set = (0..target.len() * 64)
+                .filter(|col| target[col / 64] >> (col % 64) & 1 != 0)
+                .count();
+            matrix.row_len(row) == expected.len()
+                && set == expected.len()
+                && matrix.row_and_count(row, &target) == expected.len()
+                && (0..matrix.size_bound())
+                    .all(|col| matrix.col_len(col) == matrix.entries_in_col(col).count())
+        })
+    }
+
     #[quickcheck]
     fn prop_clear_and_len_consistent(ArbMatrix { mut matrix, .. }: ArbMatrix<M>) -> bool {
         matrix.clear();
@@ -720,6 +790,21 @@ adjacency_matrix_tests!(
 adjacency_matrix_drop_tests!(undirected_hash_drop: AdjacencyMatrixDropTests<HashAdjacencyMatrix<DroppableValue, Undirected>>);
 adjacency_matrix_quickcheck_tests!(undirected_hash_quickcheck: AdjacencyMatrixQuickCheckTests<HashAdjacencyMatrix<u8, Undirected>>);
 
+adjacency_matrix_tests!(
+    directed_hash_std_hasher: AdjacencyMatrixTests<HashAdjacencyMatrix<&'static str, Directed, RandomState>>
+);
+adjacency_matrix_quickcheck_tests!(
+    undirected_hash_std_hasher_quickcheck: AdjacencyMatrixQuickCheckTests<HashAdjacencyMatrix<u8, Undirected, RandomState>>
+);
+
+adjacency_matrix_tests!(directed_csr: AdjacencyMatrixTests<CsrAdjacencyMatrix<&'static str, Directed>>);
+adjacency_matrix_drop_tests!(directed_csr_drop: AdjacencyMatrixDropTests<CsrAdjacencyMatrix<DroppableValue, Directed>>);
+adjacency_matrix_quickcheck_tests!(directed_csr_quickcheck: AdjacencyMatrixQuickCheckTests<CsrAdjacencyMatrix<u8, Directed>>);
+
+adjacency_matrix_tests!(undirected_csr: AdjacencyMatrixTests<CsrAdjacencyMatrix<&'static str, Undirected>>);
+adjacency_matrix_drop_tests!(undirected_csr_drop: AdjacencyMatrixDropTests<CsrAdjacencyMatrix<DroppableValue, Undirected>>);
+adjacency_matrix_quickcheck_tests!(undirected_csr_quickcheck: AdjacencyMatrixQuickCheckTests<CsrAdjacencyMatrix<u8, Undirected>>);
+
 #[cfg(feature = "bitvec")]
 adjacency_matrix_tests!(
     directed_bitvec: AdjacencyMatrixTests<BitvecAdjacencyMatrix<&'static str, Directed>>
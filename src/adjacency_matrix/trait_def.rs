@@ -17,6 +17,26 @@ where
     /// Creates a new, empty adjacency matrix.
     fn with_size(size: usize) -> Self;
 
+    /// Creates an adjacency matrix of the given size holding the given
+    /// `(row, col, data)` entries.  The entries must be in canonical order
+    /// (see [`Directedness::sort_pair`]), sorted by row and then column, with
+    /// no two at the same position, and their indices must be less than
+    /// `size`.
+    ///
+    /// The default implementation inserts the entries one at a time;
+    /// implementations whose insertions are expensive build the matrix
+    /// directly.
+    fn from_sorted_entries(
+        size: usize,
+        entries: impl IntoIterator<Item = (usize, usize, Self::Value)>,
+    ) -> Self {
+        let mut matrix = Self::with_size(size);
+        for (row, col, data) in entries {
+            matrix.insert(row, col, data);
+        }
+        matrix
+    }
+
     /// Returns the directedness of the adjacency matrix.
     fn directedness(&self) -> Self::Directedness {
         Self::Directedness::default()
@@ -88,6 +108,45 @@ where
     /// Iterates over all entries in the given col.
     fn entries_in_col(&self, col: usize) -> impl Iterator<Item = (usize, &'_ Self::Value)> + '_;
 
+    /// Returns the number of entries in the given row.
+    fn row_len(&self, row: usize) -> usize {
+        self.entries_in_row(row).count()
+    }
+
+    /// Returns the number of entries in the given column.
+    fn col_len(&self, col: usize) -> usize {
+        self.entries_in_col(col).count()
+    }
+
+    /// Sets the bits of `target` for the columns of the entries in the given
+    /// row, leaving its other bits unchanged.  `target` is a bit set stored
+    /// as words, where column `j` is bit `j % 64` of word `j / 64`, and it is
+    /// extended with zero words as needed.
+    ///
+    /// Repeatedly OR-ing rows into a bit set is the inner loop of
+    /// reachability computations over the matrix.
+    fn row_or_into(&self, row: usize, target: &mut Vec<u64>) {
+        for (col, _) in self.entries_in_row(row) {
+            if target.len() <= col / 64 {
+                target.resize(col / 64 + 1, 0);
+            }
+            target[col / 64] |= 1 << (col % 64);
+        }
+    }
+
+    /// Returns the number of entries in the given row whose columns are set
+    /// in `mask`, a bit set with the same layout as in
+    /// [`row_or_into`](Self::row_or_into).  Columns past the end of `mask`
+    /// are treated as unset.
+    fn row_and_count(&self, row: usize, mask: &[u64]) -> usize {
+        self.entries_in_row(row)
+            .filter(|(col, _)| {
+                mask.get(col / 64)
+                    .is_some_and(|word| word >> (col % 64) & 1 != 0)
+            })
+            .count()
+    }
+
     /// Reserves capacity for at least `additional` more rows and columns to be added.
     fn reserve(&mut self, _additional: usize);
 
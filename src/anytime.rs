@@ -0,0 +1,339 @@
+//! Anytime shortest path search using ARA* (Anytime Repairing A*).
+//!
+//! ARA* first runs a weighted A* search with a heavily inflated heuristic,
+//! which finds some path quickly, and then repeatedly lowers the inflation
+//! factor and repairs the search, reusing previous work.  Each intermediate
+//! result comes with a bound on how far its cost can be from optimal, so the
+//! search can be cut off at any time.
+
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    path::Path,
+    prelude::*,
+};
+
+/// The heuristic inflation factor used for the first search.
+const INITIAL_EPSILON: f64 = 3.0;
+
+/// The amount by which the inflation factor is reduced after each search.
+const EPSILON_STEP: f64 = 0.5;
+
+/// The best path found by [`shortest_path_anytime`].
+#[derive(Clone, Debug)]
+pub struct AnytimePath<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    /// The path from the start node to the goal node.
+    pub path: Path<'g, G>,
+    /// The total weight of the path.
+    pub cost: f64,
+    /// An upper bound on `cost` divided by the cost of an optimal path.  A
+    /// value of 1 means the path is known to be optimal.
+    pub suboptimality: f64,
+}
+
+/// A node in the open list, ordered so that `BinaryHeap` pops the smallest
+/// key first.
+struct OpenEntry<N> {
+    key: f64,
+    g: f64,
+    node: N,
+}
+
+impl<N> PartialEq for OpenEntry<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N> Eq for OpenEntry<N> {}
+
+impl<N> PartialOrd for OpenEntry<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for OpenEntry<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.total_cmp(&self.key)
+    }
+}
+
+struct AraStar<'a, G, W, H>
+where
+    G: Graph + ?Sized,
+{
+    graph: &'a G,
+    goal: &'a G::NodeId,
+    weight_fn: W,
+    heuristic: H,
+    deadline: Instant,
+    epsilon: f64,
+    g: FastHashMap<G::NodeId, f64>,
+    parents: FastHashMap<G::NodeId, (G::EdgeId, G::NodeId)>,
+    open: BinaryHeap<OpenEntry<G::NodeId>>,
+    closed: FastHashSet<G::NodeId>,
+    inconsistent: FastHashSet<G::NodeId>,
+}
+
+impl<G, W, H> AraStar<'_, G, W, H>
+where
+    G: Graph + ?Sized,
+    W: Fn(&G::EdgeId) -> f64,
+    H: Fn(&G::NodeId) -> f64,
+{
+    fn g(&self, node: &G::NodeId) -> f64 {
+        self.g.get(node).copied().unwrap_or(f64::INFINITY)
+    }
+
+    fn push_open(&mut self, node: G::NodeId) {
+        let g = self.g(&node);
+        self.open.push(OpenEntry {
+            key: g + self.epsilon * (self.heuristic)(&node),
+            g,
+            node,
+        });
+    }
+
+    /// Discards stale entries from the top of the open list.
+    fn peek_open(&mut self) -> Option<f64> {
+        while let Some(entry) = self.open.peek() {
+            if entry.g == self.g(&entry.node) && !self.closed.contains(&entry.node) {
+                return Some(entry.key);
+            }
+            self.open.pop();
+        }
+        None
+    }
+
+    /// Expands nodes until no node in the open list could improve the path to
+    /// the goal.  Returns false if the deadline passed first.
+    fn improve_path(&mut self) -> bool {
+        while let Some(min_key) = self.peek_open() {
+            if self.g(self.goal) <= min_key {
+                break;
+            }
+            if Instant::now() >= self.deadline {
+                return false;
+            }
+            let entry = self.open.pop().expect("open list is not empty");
+            self.closed.insert(entry.node.clone());
+            for eid in self.graph.edges_from(&entry.node) {
+                let ends = self.graph.edge_ends(&eid);
+                let next = ends.other_value(&entry.node).into_inner().clone();
+                let new_g = entry.g + (self.weight_fn)(&eid);
+                if new_g < self.g(&next) {
+                    self.g.insert(next.clone(), new_g);
+                    self.parents.insert(next.clone(), (eid, entry.node.clone()));
+                    if self.closed.contains(&next) {
+                        self.inconsistent.insert(next);
+                    } else {
+                        self.push_open(next);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Computes the suboptimality bound of the current path to the goal.
+    /// Moves the contents of the open list into `inconsistent`, from which
+    /// the next search rebuilds it.
+    fn suboptimality(&mut self, finished: bool) -> f64 {
+        let mut lower_bound = f64::INFINITY;
+        while let Some(entry) = self.open.pop() {
+            if entry.g == self.g(&entry.node) && !self.closed.contains(&entry.node) {
+                lower_bound = lower_bound.min(entry.g + (self.heuristic)(&entry.node));
+                self.inconsistent.insert(entry.node);
+            }
+        }
+        for node in self.inconsistent.iter() {
+            lower_bound = lower_bound.min(self.g(node) + (self.heuristic)(node));
+        }
+        let bound = self.g(self.goal) / lower_bound;
+        // A completed search is also bounded by the inflation factor.
+        let bound = if finished {
+            self.epsilon.min(bound)
+        } else {
+            bound
+        };
+        bound.max(1.0)
+    }
+}
+
+/// Finds a path from `start` to `goal` within a time budget.  See
+/// [`Graph::shortest_path_anytime`].
+pub fn shortest_path_anytime<'g, G>(
+    graph: &'g G,
+    start: &G::NodeId,
+    goal: &G::NodeId,
+    weight_fn: impl Fn(&G::EdgeId) -> f64,
+    heuristic: impl Fn(&G::NodeId) -> f64,
+    budget: Duration,
+) -> Option<AnytimePath<'g, G>>
+where
+    G: Graph + ?Sized,
+{
+    let mut search = AraStar {
+        graph,
+        goal,
+        weight_fn,
+        heuristic,
+        deadline: Instant::now() + budget,
+        epsilon: INITIAL_EPSILON,
+        g: [(start.clone(), 0.0)].into_iter().collect(),
+        parents: FastHashMap::default(),
+        open: BinaryHeap::new(),
+        closed: FastHashSet::default(),
+        inconsistent: FastHashSet::default(),
+    };
+    search.push_open(start.clone());
+
+    let mut best: Option<AnytimePath<'g, G>> = None;
+    loop {
+        let finished = search.improve_path();
+        let cost = search.g(goal);
+        if cost.is_finite() && best.as_ref().is_none_or(|best| cost <= best.cost) {
+            let suboptimality = search.suboptimality(finished);
+            best = Some(AnytimePath {
+                path: Path::from_predecessors(graph, &search.parents, goal),
+                cost,
+                suboptimality,
+            });
+        } else if cost.is_infinite() && finished {
+            // The goal is unreachable.
+            return None;
+        } else {
+            search.suboptimality(finished);
+        }
+        if !finished || best.as_ref().is_some_and(|best| best.suboptimality <= 1.0) {
+            break;
+        }
+
+        search.epsilon = (search.epsilon - EPSILON_STEP).max(1.0);
+        let pending: Vec<_> = search.inconsistent.drain().collect();
+        search.open.clear();
+        search.closed.clear();
+        for node in pending {
+            search.push_open(node);
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::BagGraph;
+
+    use super::*;
+
+    type Grid = BagGraph<(i32, i32), f64, Undirected>;
+
+    /// Creates a square grid of nodes with unit-weight edges between
+    /// horizontal and vertical neighbors.
+    fn create_grid(size: i32) -> (Grid, HashMap<(i32, i32), <Grid as Graph>::NodeId>) {
+        let mut graph = Grid::default();
+        let mut ids = HashMap::new();
+        for x in 0..size {
+            for y in 0..size {
+                ids.insert((x, y), graph.add_node((x, y)));
+            }
+        }
+        for x in 0..size {
+            for y in 0..size {
+                if x + 1 < size {
+                    graph.add_new_edge(&ids[&(x, y)], &ids[&(x + 1, y)], 1.0);
+                }
+                if y + 1 < size {
+                    graph.add_new_edge(&ids[&(x, y)], &ids[&(x, y + 1)], 1.0);
+                }
+            }
+        }
+        (graph, ids)
+    }
+
+    fn manhattan(graph: &Grid, goal: (i32, i32)) -> impl Fn(&<Grid as Graph>::NodeId) -> f64 + '_ {
+        move |nid| {
+            let (x, y) = *graph.node_data(nid);
+            ((goal.0 - x).abs() + (goal.1 - y).abs()) as f64
+        }
+    }
+
+    #[test]
+    fn test_finds_optimal_path_with_enough_time() {
+        let (graph, ids) = create_grid(10);
+        let result = graph
+            .shortest_path_anytime(
+                &ids[&(0, 0)],
+                &ids[&(9, 9)],
+                |eid| *graph.edge_data(eid),
+                manhattan(&graph, (9, 9)),
+                Duration::from_secs(10),
+            )
+            .unwrap();
+        assert_eq!(result.cost, 18.0);
+        assert_eq!(result.suboptimality, 1.0);
+        assert_eq!(result.path.first_node(), ids[&(0, 0)]);
+        assert_eq!(result.path.last_node(), ids[&(9, 9)]);
+        assert_eq!(result.path.edges().count(), 18);
+    }
+
+    #[test]
+    fn test_inflated_heuristic_is_bounded() {
+        // A cheap detour which an inflated heuristic will initially avoid.
+        let mut graph = BagGraph::<i32, f64, Directed>::default();
+        let n: Vec<_> = (0..4).map(|i| graph.add_node(i)).collect();
+        graph.add_new_edge(&n[0], &n[3], 4.0);
+        graph.add_new_edge(&n[0], &n[1], 1.0);
+        graph.add_new_edge(&n[1], &n[2], 1.0);
+        graph.add_new_edge(&n[2], &n[3], 1.0);
+        let heuristic = |nid: &_| (3 - *graph.node_data(nid)) as f64;
+
+        let result = shortest_path_anytime(
+            &graph,
+            &n[0],
+            &n[3],
+            |eid| *graph.edge_data(eid),
+            heuristic,
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(result.cost, 3.0);
+        assert_eq!(result.path.nodes().collect::<Vec<_>>(), n);
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let mut graph = BagGraph::<(), f64, Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_new_edge(&b, &a, 1.0);
+        let result = graph.shortest_path_anytime(&a, &b, |_| 1.0, |_| 0.0, Duration::from_secs(1));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_zero_budget_returns_nothing() {
+        let (graph, ids) = create_grid(5);
+        let result = graph.shortest_path_anytime(
+            &ids[&(0, 0)],
+            &ids[&(4, 4)],
+            |_| 1.0,
+            manhattan(&graph, (4, 4)),
+            Duration::ZERO,
+        );
+        assert!(result.is_none());
+    }
+}
@@ -0,0 +1,365 @@
+//! Rate-limited application of queued edits.
+//!
+//! A service which receives edits in bursts can apply them as they arrive,
+//! but a large burst then holds the graph for a long time.  An
+//! [`ApplyQueue`] buffers [`GraphEdit`]s instead, and applies them in
+//! batches bounded by a token bucket each time [`ApplyQueue::tick`] is
+//! called: every tick adds a fixed number of tokens, up to a maximum burst
+//! size, and each edit applied uses one token.  The queue can be limited in
+//! length, so producers find out when it is full and can slow down.
+//!
+//! [`ApplyQueue::tick_with_snapshots`] applies a batch and then offers the
+//! graph to a [`SnapshotScheduler`], so snapshots are only ever taken
+//! between batches and readers of a snapshot never see a batch half applied.
+
+use std::{collections::VecDeque, fmt::Debug, mem};
+
+use crate::{prelude::*, snapshot_scheduler::SnapshotScheduler};
+
+/// An edit to be applied to a graph of type `G`.
+pub enum GraphEdit<G>
+where
+    G: Graph,
+{
+    /// Adds a node.
+    AddNode(G::NodeData),
+    /// Removes a node and its edges.
+    RemoveNode(G::NodeId),
+    /// Adds an edge, as with [`GraphMut::add_edge`].
+    AddEdge {
+        from: G::NodeId,
+        into: G::NodeId,
+        data: G::EdgeData,
+    },
+    /// Removes an edge.
+    RemoveEdge(G::EdgeId),
+    /// Replaces the data of a node.
+    SetNodeData(G::NodeId, G::NodeData),
+    /// Replaces the data of an edge.
+    SetEdgeData(G::EdgeId, G::EdgeData),
+}
+
+impl<G> GraphEdit<G>
+where
+    G: GraphMut,
+{
+    /// Applies the edit to `graph`.  Returns an error, without changing the
+    /// graph, if the edit refers to a node or edge which is not in the
+    /// graph, e.g. because an earlier edit removed it.
+    pub fn apply(self, graph: &mut G) -> Result<(), GraphError> {
+        match self {
+            GraphEdit::AddNode(data) => {
+                graph.add_node(data);
+            }
+            GraphEdit::RemoveNode(nid) => {
+                graph.check_node_id(&nid)?;
+                graph.remove_node(&nid);
+            }
+            GraphEdit::AddEdge { from, into, data } => {
+                graph.try_add_edge(&from, &into, data)?;
+            }
+            GraphEdit::RemoveEdge(eid) => {
+                graph.check_edge_id(&eid)?;
+                graph.remove_edge(&eid);
+            }
+            GraphEdit::SetNodeData(nid, data) => {
+                graph.check_node_id(&nid)?;
+                *graph.node_data_mut(&nid) = data;
+            }
+            GraphEdit::SetEdgeData(eid, data) => {
+                graph.check_edge_id(&eid)?;
+                *graph.edge_data_mut(&eid) = data;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<G> Debug for GraphEdit<G>
+where
+    G: Graph,
+    G::NodeData: Debug,
+    G::EdgeData: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphEdit::AddNode(data) => f.debug_tuple("AddNode").field(data).finish(),
+            GraphEdit::RemoveNode(nid) => f.debug_tuple("RemoveNode").field(nid).finish(),
+            GraphEdit::AddEdge { from, into, data } => f
+                .debug_struct("AddEdge")
+                .field("from", from)
+                .field("into", into)
+                .field("data", data)
+                .finish(),
+            GraphEdit::RemoveEdge(eid) => f.debug_tuple("RemoveEdge").field(eid).finish(),
+            GraphEdit::SetNodeData(nid, data) => {
+                f.debug_tuple("SetNodeData").field(nid).field(data).finish()
+            }
+            GraphEdit::SetEdgeData(eid, data) => {
+                f.debug_tuple("SetEdgeData").field(eid).field(data).finish()
+            }
+        }
+    }
+}
+
+/// What happened during one call to [`ApplyQueue::tick`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TickReport {
+    /// The number of edits taken from the queue, including those which
+    /// failed.
+    pub applied: usize,
+    /// The errors returned by edits which could not be applied, in order.
+    pub errors: Vec<GraphError>,
+    /// The number of edits left in the queue.
+    pub backlog: usize,
+    /// True if a snapshot was taken after the batch.  Only set by
+    /// [`ApplyQueue::tick_with_snapshots`].
+    pub snapshot_taken: bool,
+}
+
+/// A queue of edits which are applied to a graph in rate-limited batches.
+/// See the [module documentation](self).
+pub struct ApplyQueue<G>
+where
+    G: Graph,
+{
+    queue: VecDeque<GraphEdit<G>>,
+    rate: usize,
+    burst: usize,
+    tokens: usize,
+    max_len: Option<usize>,
+}
+
+impl<G> ApplyQueue<G>
+where
+    G: GraphMut,
+{
+    /// Creates an empty queue which applies up to `rate` edits per tick,
+    /// with no limit on its length.  Unused tokens are not saved for later
+    /// ticks unless the burst size is raised with [`Self::with_burst`].
+    pub fn new(rate: usize) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            rate,
+            burst: rate,
+            tokens: 0,
+            max_len: None,
+        }
+    }
+
+    /// Sets the largest number of edits which can be applied in one tick,
+    /// returning the updated queue.  Tokens not used in one tick are saved
+    /// for later ticks, up to this number.  Values less than the rate are
+    /// treated as the rate.
+    pub fn with_burst(mut self, burst: usize) -> Self {
+        self.burst = burst.max(self.rate);
+        self
+    }
+
+    /// Sets the largest number of edits the queue can hold, returning the
+    /// updated queue.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Adds an edit to the end of the queue.  If the queue is full, the edit
+    /// is returned instead, and the caller should slow down or drop it.
+    pub fn push(&mut self, edit: GraphEdit<G>) -> Result<(), GraphEdit<G>> {
+        if self.is_full() {
+            return Err(edit);
+        }
+        self.queue.push_back(edit);
+        Ok(())
+    }
+
+    /// Returns the number of edits waiting to be applied.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns true if no edits are waiting to be applied.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns true if [`Self::push`] would reject an edit.
+    pub fn is_full(&self) -> bool {
+        self.max_len
+            .is_some_and(|max_len| self.queue.len() >= max_len)
+    }
+
+    /// Returns the number of ticks needed to apply the edits now in the
+    /// queue, or `None` if the rate is zero and the queue is not empty.
+    pub fn ticks_to_drain(&self) -> Option<usize> {
+        let pending = self.queue.len().saturating_sub(self.tokens);
+        if pending == 0 {
+            Some(usize::from(!self.queue.is_empty()))
+        } else if self.rate == 0 {
+            None
+        } else {
+            Some(pending.div_ceil(self.rate))
+        }
+    }
+
+    /// Adds this tick's tokens and applies as many queued edits as there
+    /// are tokens, in the order they were pushed.
+    pub fn tick(&mut self, graph: &mut G) -> TickReport {
+        self.tokens = (self.tokens + self.rate).min(self.burst);
+        let count = self.tokens.min(self.queue.len());
+        self.tokens -= count;
+        let errors = self
+            .queue
+            .drain(..count)
+            .filter_map(|edit| edit.apply(graph).err())
+            .collect();
+        TickReport {
+            applied: count,
+            errors,
+            backlog: self.queue.len(),
+            snapshot_taken: false,
+        }
+    }
+
+    /// Like [`Self::tick`], but then records the applied edits as mutations
+    /// with `scheduler` and lets it take a snapshot if one is due.
+    pub fn tick_with_snapshots(
+        &mut self,
+        graph: &mut G,
+        scheduler: &mut SnapshotScheduler<G>,
+    ) -> TickReport
+    where
+        G: Clone + Send + 'static,
+    {
+        let mut report = self.tick(graph);
+        scheduler.record_mutations(report.applied);
+        report.snapshot_taken = scheduler.poll(graph);
+        report
+    }
+
+    /// Removes all edits from the queue without applying them, returning
+    /// them in order.
+    pub fn clear(&mut self) -> Vec<GraphEdit<G>> {
+        mem::take(&mut self.queue).into()
+    }
+}
+
+impl<G> Debug for ApplyQueue<G>
+where
+    G: Graph,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApplyQueue")
+            .field("len", &self.queue.len())
+            .field("rate", &self.rate)
+            .field("burst", &self.burst)
+            .field("tokens", &self.tokens)
+            .field("max_len", &self.max_len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{BagGraph, snapshot_scheduler::SnapshotOn};
+
+    use super::*;
+
+    type TestGraph = BagGraph<u32, (), Directed>;
+
+    #[test]
+    fn test_rate_and_burst() {
+        let mut graph = TestGraph::default();
+        let mut queue = ApplyQueue::new(2).with_burst(3).with_max_len(5);
+        for i in 0..5 {
+            queue.push(GraphEdit::AddNode(i)).unwrap();
+        }
+        assert!(queue.is_full());
+        assert!(queue.push(GraphEdit::AddNode(5)).is_err());
+        assert_eq!(queue.ticks_to_drain(), Some(3));
+
+        let report = queue.tick(&mut graph);
+        assert_eq!((report.applied, report.backlog), (2, 3));
+        assert_eq!(graph.num_nodes(), 2);
+
+        // Unused tokens carry over, up to the burst size.
+        let mut empty = TestGraph::default();
+        let mut idle = ApplyQueue::<TestGraph>::new(2).with_burst(3);
+        idle.tick(&mut empty);
+        idle.tick(&mut empty);
+        for i in 0..5 {
+            idle.push(GraphEdit::AddNode(i)).unwrap();
+        }
+        assert_eq!(idle.tick(&mut empty).applied, 3);
+
+        queue.tick(&mut graph);
+        let report = queue.tick(&mut graph);
+        assert_eq!((report.applied, report.backlog), (1, 0));
+        assert_eq!(graph.num_nodes(), 5);
+        assert_eq!(queue.ticks_to_drain(), Some(0));
+    }
+
+    #[test]
+    fn test_failed_edits() {
+        let mut graph = TestGraph::default();
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        let mut queue = ApplyQueue::new(10);
+        queue.push(GraphEdit::RemoveNode(b.clone())).unwrap();
+        queue
+            .push(GraphEdit::AddEdge {
+                from: a.clone(),
+                into: b.clone(),
+                data: (),
+            })
+            .unwrap();
+        queue.push(GraphEdit::SetNodeData(b, 7)).unwrap();
+        queue.push(GraphEdit::SetNodeData(a.clone(), 9)).unwrap();
+
+        let report = queue.tick(&mut graph);
+        assert_eq!(report.applied, 4);
+        assert_eq!(
+            report.errors,
+            vec![GraphError::InvalidNodeId, GraphError::InvalidNodeId]
+        );
+        assert_eq!(graph.num_edges(), 0);
+        assert_eq!(*graph.node_data(&a), 9);
+    }
+
+    #[test]
+    fn test_snapshots_between_batches() {
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let sink_sizes = Arc::clone(&sizes);
+        let mut scheduler = SnapshotScheduler::new(move |_, graph: &TestGraph| {
+            sink_sizes.lock().unwrap().push(graph.num_nodes());
+            Ok(())
+        })
+        .with_policy(SnapshotOn::Mutations(1));
+        let mut graph = TestGraph::default();
+        let mut queue = ApplyQueue::new(3);
+        for i in 0..5 {
+            queue.push(GraphEdit::AddNode(i)).unwrap();
+        }
+
+        assert!(
+            queue
+                .tick_with_snapshots(&mut graph, &mut scheduler)
+                .snapshot_taken
+        );
+        scheduler.wait().unwrap();
+        assert!(
+            queue
+                .tick_with_snapshots(&mut graph, &mut scheduler)
+                .snapshot_taken
+        );
+        scheduler.wait().unwrap();
+        assert!(
+            !queue
+                .tick_with_snapshots(&mut graph, &mut scheduler)
+                .snapshot_taken
+        );
+        assert_eq!(*sizes.lock().unwrap(), vec![3, 5]);
+    }
+}
@@ -0,0 +1,210 @@
+//! Reusable scratch space for graph algorithms.
+//!
+//! Algorithms like Dijkstra's allocate several tables keyed by node IDs on
+//! every call.  When an algorithm is run many times on small inputs, filling
+//! fresh tables can take more time than the search itself.  An
+//! [`AlgoArena`] keeps those tables between calls, clearing them without
+//! releasing their memory, so after the first few calls no further
+//! allocation is needed.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt::Debug,
+    ops::Add,
+};
+
+use crate::{
+    hashing::FastHashSet,
+    path::Path,
+    prelude::*,
+    shortest_path_tree::{self, ShortestPathTree},
+};
+
+/// Scratch tables for shortest path searches over graphs with node IDs of
+/// type `N`, edge IDs of type `E` and path lengths of type `C`.  Create one
+/// with [`Graph::make_arena`] to size it for a particular graph.
+pub struct AlgoArena<N, E, C> {
+    tree: Option<ShortestPathTree<N, E, C>>,
+    visited: FastHashSet<N>,
+    heap: BinaryHeap<Reverse<(C, N)>>,
+    capacity: usize,
+}
+
+impl<N, E, C> AlgoArena<N, E, C>
+where
+    N: GraphElementId,
+    E: GraphElementId,
+    C: Default + Ord + Copy + Add<Output = C>,
+{
+    /// Creates an empty arena.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an arena with room for searches reaching `num_nodes` nodes.
+    pub fn with_capacity(num_nodes: usize) -> Self {
+        Self {
+            tree: None,
+            visited: FastHashSet::with_capacity_and_hasher(num_nodes, Default::default()),
+            heap: BinaryHeap::with_capacity(num_nodes),
+            capacity: num_nodes,
+        }
+    }
+
+    /// Finds a shortest path tree rooted at `start`, like
+    /// [`shortest_path_tree::shortest_path_tree`].  The tree is stored in the
+    /// arena and replaced by the next search.
+    pub fn shortest_path_tree<G>(
+        &mut self,
+        graph: &G,
+        start: &N,
+        distance_fn: impl Fn(&E) -> C,
+    ) -> &ShortestPathTree<N, E, C>
+    where
+        G: Graph<NodeId = N, EdgeId = E> + ?Sized,
+    {
+        self.search(graph, start, None, distance_fn)
+    }
+
+    /// Returns the length of a shortest path from `start` to `goal`, or
+    /// `None` if there is none.  The search stops as soon as the length is
+    /// known.
+    pub fn shortest_distance<G>(
+        &mut self,
+        graph: &G,
+        start: &N,
+        goal: &N,
+        distance_fn: impl Fn(&E) -> C,
+    ) -> Option<C>
+    where
+        G: Graph<NodeId = N, EdgeId = E> + ?Sized,
+    {
+        self.search(graph, start, Some(goal), distance_fn)
+            .distance(goal)
+    }
+
+    /// Returns a shortest path from `start` to `goal` and its length, or
+    /// `None` if there is none.  The search stops as soon as the path is
+    /// known.
+    pub fn shortest_path<'g, G>(
+        &mut self,
+        graph: &'g G,
+        start: &N,
+        goal: &N,
+        distance_fn: impl Fn(&E) -> C,
+    ) -> Option<(Path<'g, G>, C)>
+    where
+        G: Graph<NodeId = N, EdgeId = E> + ?Sized,
+    {
+        let tree = self.search(graph, start, Some(goal), distance_fn);
+        Some((tree.path_to(graph, goal)?, tree.distance(goal)?))
+    }
+
+    fn search<G>(
+        &mut self,
+        graph: &G,
+        start: &N,
+        goal: Option<&N>,
+        distance_fn: impl Fn(&E) -> C,
+    ) -> &ShortestPathTree<N, E, C>
+    where
+        G: Graph<NodeId = N, EdgeId = E> + ?Sized,
+    {
+        let capacity = self.capacity;
+        let tree = self.tree.get_or_insert_with(|| ShortestPathTree {
+            root: start.clone(),
+            parents: HashMap::with_capacity(capacity),
+            distances: HashMap::with_capacity(capacity),
+        });
+        shortest_path_tree::dijkstra(
+            graph,
+            start,
+            goal,
+            distance_fn,
+            tree,
+            &mut self.visited,
+            &mut self.heap,
+        );
+        tree
+    }
+}
+
+impl<N, E, C> Default for AlgoArena<N, E, C>
+where
+    N: GraphElementId,
+    E: GraphElementId,
+    C: Default + Ord + Copy + Add<Output = C>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E, C> Debug for AlgoArena<N, E, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlgoArena")
+            .field("capacity", &self.capacity)
+            .field("visited_capacity", &self.visited.capacity())
+            .field("heap_capacity", &self.heap.capacity())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_reuse() {
+        let mut graph = BagGraph::<(), u32, Directed>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], 1);
+        graph.add_new_edge(&n[1], &n[2], 1);
+        graph.add_new_edge(&n[0], &n[2], 5);
+        graph.add_new_edge(&n[2], &n[3], 1);
+
+        let mut arena = graph.make_arena();
+        let weight = |eid: &_| *graph.edge_data(eid);
+        for start in &n {
+            let expected = graph.shortest_path_tree(start, weight);
+            let tree = arena.shortest_path_tree(&graph, start, weight);
+            assert_eq!(tree.root, *start);
+            assert_eq!(tree.distances, expected.distances);
+            assert_eq!(tree.parents, expected.parents);
+        }
+
+        assert_eq!(
+            arena.shortest_distance(&graph, &n[0], &n[2], weight),
+            Some(2)
+        );
+        assert_eq!(arena.shortest_distance(&graph, &n[3], &n[0], weight), None);
+        let (path, length) = arena.shortest_path(&graph, &n[0], &n[3], weight).unwrap();
+        assert_eq!(length, 3);
+        assert_eq!(path.nodes().collect::<Vec<_>>(), n);
+    }
+
+    #[test]
+    fn test_early_exit() {
+        let mut graph = LinkedGraph::<(), u32, Undirected>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        for pair in n.windows(2) {
+            graph.add_new_edge(&pair[0], &pair[1], 1);
+        }
+
+        let mut arena = AlgoArena::new();
+        assert_eq!(
+            arena.shortest_distance(&graph, &n[0], &n[1], |_| 1),
+            Some(1)
+        );
+        // Searches after one which stopped early start afresh.
+        let tree = arena.shortest_path_tree(&graph, &n[3], |_| 1);
+        assert_eq!(tree.distance(&n[0]), Some(3));
+        assert_eq!(
+            arena.shortest_distance(&graph, &n[2], &n[2], |_| 1),
+            Some(0)
+        );
+    }
+}
@@ -15,7 +15,7 @@ use crate::map_collector::MapCollector;
 pub struct BagKey(NonZero<usize>);
 
 impl BagKey {
-    pub fn to_index(&self) -> usize {
+    pub fn to_index(self) -> usize {
         self.0.get() - 1
     }
 
@@ -288,6 +288,20 @@ impl<T> Bag<T> {
             .map(|(value, logical_id)| (*logical_id, value))
     }
 
+    /// Gets an iterator over key-value pairs in the bag in order of their
+    /// keys, which is the order in which the values were inserted.  Unlike
+    /// [`Self::pairs`], this takes time proportional to the number of keys
+    /// issued since the last call to `compact()`, including removed ones.
+    pub fn ordered_pairs<'a>(&'a self) -> impl Iterator<Item = (BagKey, &'a T)>
+    where
+        T: 'a,
+    {
+        self.index.iter().enumerate().filter_map(|(i, data_key)| {
+            let data_key = (*data_key)?;
+            Some((BagKey::from_index(i), &self.data[data_key.to_index()].0))
+        })
+    }
+
     /// Gets an iterator over key-value pairs in the bag, where the values are
     /// mutable references.  The keys are stable and can be used to access the
     /// values, but they are not guaranteed to be in any particular order.  The
@@ -620,17 +634,21 @@ mod tests {
                     }
                 }
                 BagOp::Mutate(idx) => {
-                    if let Some(key) = get_key(&map, idx) {
-                        if let Some(val) = bag.get_mut(key) {
-                            *val *= 2;
-                            map.get_mut(&key).map(|v| *v *= 2);
+                    if let Some(key) = get_key(&map, idx)
+                        && let Some(val) = bag.get_mut(key)
+                    {
+                        *val *= 2;
+                        if let Some(v) = map.get_mut(&key) {
+                            *v *= 2;
                         }
                     }
                 }
                 BagOp::MutateAll() => {
                     for (key, val) in bag.pairs_mut() {
                         *val *= 2;
-                        map.get_mut(&key).map(|v| *v *= 2);
+                        if let Some(v) = map.get_mut(&key) {
+                            *v *= 2;
+                        }
                     }
                 }
                 BagOp::Compact => {
@@ -689,6 +707,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ordered_pairs() {
+        let mut bag: Bag<i32> = Bag::default();
+        let k1 = bag.insert(10);
+        let k2 = bag.insert(20);
+        let k3 = bag.insert(30);
+        let k4 = bag.insert(40);
+
+        // Removing the first value moves the last one into its place.
+        bag.remove(k1);
+
+        assert_eq!(
+            bag.ordered_pairs().collect::<Vec<_>>(),
+            vec![(k2, &20), (k3, &30), (k4, &40)]
+        );
+    }
+
     #[test]
     fn test_iter_pairs_mut() {
         let mut bag: Bag<i32> = Bag::default();
@@ -90,18 +90,44 @@ where
         &self.node(id).data
     }
 
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.nodes
+            .get(id.key())
+            .map(|_| ())
+            .ok_or(GraphError::InvalidNodeId)
+    }
+
     fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
         self.nodes.keys().map(BagGraphNodeId::new)
     }
 
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.num_nodes() == 0
+    }
+
     fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
         &self.edge(id).data
     }
 
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.edges
+            .get(id.key())
+            .map(|_| ())
+            .ok_or(GraphError::InvalidEdgeId)
+    }
+
     fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> {
         self.edges.keys().map(BagGraphEdgeId::new)
     }
 
+    fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
     fn edge_ends(
         &self,
         id: &Self::EdgeId,
@@ -201,7 +227,7 @@ where
         from: &Self::NodeId,
         into: &Self::NodeId,
         data: Self::EdgeData,
-    ) -> (Self::EdgeId, Option<(Self::EdgeId, Self::EdgeData)>) {
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
         let ends = D::make_pair(from.key(), into.key());
 
         if !self.allows_parallel_edges() {
@@ -217,16 +243,18 @@ where
                     key: *edge_key,
                     phantom: PhantomData,
                 };
-                return (edge_id.clone(), Some((edge_id, old_data)));
+                return AddEdgeResult::Updated {
+                    id: edge_id.clone(),
+                    old_id: edge_id,
+                    old_data,
+                };
             }
             debug_assert_eq!(self.num_edges_from_into(from, into), 0);
         }
 
         let (from, into) = ends.values();
 
-        let edge_key = self
-            .edges
-            .insert(Edge::new(data, from.clone(), into.clone()));
+        let edge_key = self.edges.insert(Edge::new(data, *from, *into));
 
         let eid = BagGraphEdgeId::new(edge_key);
 
@@ -242,7 +270,7 @@ where
             self.nodes[*into].edges_out.push(edge_key);
         }
 
-        (eid, None)
+        AddEdgeResult::Added(eid)
     }
 
     fn remove_node(&mut self, nid: &Self::NodeId) -> N {
@@ -256,7 +284,7 @@ where
                 let &other_node_key = edge.ends.other_value(&node_key).into_inner();
                 if other_node_key != node_key {
                     let other_node = &mut self.nodes[other_node_key];
-                    other_node.edges_out.retain(|key| *key != *edge_key);
+                    other_node.edges_in.retain(|key| *key != *edge_key);
                     self.edges.remove(*edge_key);
                 }
             }
@@ -328,7 +356,7 @@ where
                 .collect();
         }
         for edge in self.edges.iter_mut() {
-            edge.ends = edge.ends.clone().map(|node_key| node_map[&node_key]);
+            edge.ends = edge.ends.clone().map(|node_key| node_map[node_key]);
         }
         if let Some(node_map_collector) = node_map_collector {
             for (old_key, new_key) in node_map {
@@ -0,0 +1,303 @@
+//! Biconnected components and block-cut trees of undirected graphs.
+//!
+//! A block, or biconnected component, is a maximal connected subgraph which
+//! stays connected when any one node is removed.  Every edge belongs to
+//! exactly one block, and blocks overlap only at articulation points, which
+//! are the nodes whose removal disconnects part of the graph.  A block with
+//! a single edge is a bridge, an edge whose removal disconnects its ends.  The
+//! block-cut tree has a node for each block and each articulation point, with
+//! an edge between a block and each articulation point it contains.
+//!
+//! Parallel edges between two nodes belong to the same block.  Self-loops
+//! do not affect connectivity, so they are not included in any block.  A
+//! node with no other edges forms a block on its own.
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+};
+
+/// A biconnected component of a graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block<N, E> {
+    /// The nodes of the block, in an unspecified order.
+    pub nodes: Vec<N>,
+    /// The edges of the block, in an unspecified order.
+    pub edges: Vec<E>,
+}
+
+/// A node of a block-cut tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockCutNode<N, E> {
+    /// A block of the original graph.
+    Block(Block<N, E>),
+    /// An articulation point of the original graph.
+    Cut(N),
+}
+
+/// Returns the biconnected components of `graph`, found with the
+/// Hopcroft-Tarjan algorithm in `O(V + E)` time.
+pub fn biconnected_components<G>(graph: &G) -> Vec<Block<G::NodeId, G::EdgeId>>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    let mut discovered: FastHashMap<G::NodeId, usize> = FastHashMap::default();
+    let mut low: FastHashMap<G::NodeId, usize> = FastHashMap::default();
+    let mut edge_stack: Vec<G::EdgeId> = Vec::new();
+    let mut blocks = Vec::new();
+
+    for root in graph.node_ids() {
+        if discovered.contains_key(&root) {
+            continue;
+        }
+        discovered.insert(root.clone(), discovered.len());
+        low.insert(root.clone(), discovered[&root]);
+        let mut has_children = false;
+        // Each frame is a node, the edge it was reached by, and its
+        // unexplored edges.
+        let mut stack = vec![(
+            root.clone(),
+            None,
+            graph.edges_from(&root).collect::<Vec<_>>(),
+        )];
+        while let Some((node, parent_edge, edges)) = stack.last_mut() {
+            if let Some(eid) = edges.pop() {
+                if Some(&eid) == parent_edge.as_ref() {
+                    continue;
+                }
+                let next = graph.edge_ends(&eid).other_value(node).into_inner().clone();
+                if next == *node {
+                    continue;
+                }
+                match discovered.get(&next) {
+                    None => {
+                        let index = discovered.len();
+                        discovered.insert(next.clone(), index);
+                        low.insert(next.clone(), index);
+                        edge_stack.push(eid.clone());
+                        let edges = graph.edges_from(&next).collect();
+                        stack.push((next, Some(eid), edges));
+                    }
+                    Some(&index) if index < discovered[node] => {
+                        // A back edge to an ancestor.
+                        edge_stack.push(eid);
+                        let node_low = low.get_mut(node).unwrap();
+                        *node_low = (*node_low).min(index);
+                    }
+                    // The other end of a back edge already seen from its
+                    // descendant.
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            let (child, parent_edge, _) = stack.pop().unwrap();
+            let Some((parent, _, _)) = stack.last() else {
+                break;
+            };
+            let child_low = low[&child];
+            let parent_low = low.get_mut(parent).unwrap();
+            *parent_low = (*parent_low).min(child_low);
+            if *parent == root {
+                has_children = true;
+            }
+            if child_low >= discovered[parent] {
+                let parent_edge = parent_edge.unwrap();
+                let mut edges = Vec::new();
+                while let Some(eid) = edge_stack.pop() {
+                    let done = eid == parent_edge;
+                    edges.push(eid);
+                    if done {
+                        break;
+                    }
+                }
+                blocks.push(block_from_edges(graph, edges));
+            }
+        }
+        if !has_children {
+            blocks.push(Block {
+                nodes: vec![root],
+                edges: Vec::new(),
+            });
+        }
+    }
+    blocks
+}
+
+fn block_from_edges<G>(graph: &G, edges: Vec<G::EdgeId>) -> Block<G::NodeId, G::EdgeId>
+where
+    G: Graph + ?Sized,
+{
+    let mut seen = FastHashSet::default();
+    let nodes = edges
+        .iter()
+        .flat_map(|eid| {
+            let (a, b) = graph.edge_ends(eid).into_values();
+            [a, b]
+        })
+        .filter(|nid| seen.insert(nid.clone()))
+        .collect();
+    Block { nodes, edges }
+}
+
+/// Returns the articulation points of `graph`, in an unspecified order.
+pub fn articulation_points<G>(graph: &G) -> Vec<G::NodeId>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    cut_nodes(&biconnected_components(graph))
+}
+
+/// Returns the bridges of `graph`, in an unspecified order.  A bridge is an
+/// edge whose removal would disconnect its ends, which is exactly an edge
+/// that forms a block on its own.
+pub fn bridges<G>(graph: &G) -> Vec<G::EdgeId>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    biconnected_components(graph)
+        .into_iter()
+        .filter(|block| block.edges.len() == 1)
+        .flat_map(|block| block.edges)
+        .collect()
+}
+
+/// Returns the nodes which belong to more than one block.
+fn cut_nodes<N: GraphElementId, E>(blocks: &[Block<N, E>]) -> Vec<N> {
+    let mut counts: FastHashMap<&N, usize> = FastHashMap::default();
+    for nid in blocks.iter().flat_map(|block| block.nodes.iter()) {
+        *counts.entry(nid).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .map(|(nid, _)| nid.clone())
+        .collect()
+}
+
+/// Constructs the block-cut tree of `graph`.  See
+/// [`GraphUndirected::block_cut_tree`].
+pub fn block_cut_tree<G, H>(graph: &G) -> H
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+    H: GraphMut<NodeData = BlockCutNode<G::NodeId, G::EdgeId>, EdgeData = ()> + Default,
+{
+    let blocks = biconnected_components(graph);
+    let mut result = H::default();
+    let cut_map: FastHashMap<G::NodeId, H::NodeId> = cut_nodes(&blocks)
+        .into_iter()
+        .map(|nid| {
+            let cut = result.add_node(BlockCutNode::Cut(nid.clone()));
+            (nid, cut)
+        })
+        .collect();
+    for block in blocks {
+        let cuts: Vec<_> = block
+            .nodes
+            .iter()
+            .filter_map(|nid| cut_map.get(nid).cloned())
+            .collect();
+        let block_id = result.add_node(BlockCutNode::Block(block));
+        for cut in cuts {
+            result.add_edge(&block_id, &cut, ());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    fn sorted<T: Ord>(mut items: Vec<T>) -> Vec<T> {
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn test_biconnected_components() {
+        // Two triangles sharing node 2, with a pendant edge 4-5 and an
+        // isolated node 6.
+        let mut graph = BagGraph::<(), (), Undirected>::default();
+        let n: Vec<_> = (0..7).map(|_| graph.add_node(())).collect();
+        let e01 = graph.add_new_edge(&n[0], &n[1], ());
+        let e12 = graph.add_new_edge(&n[1], &n[2], ());
+        let e20 = graph.add_new_edge(&n[2], &n[0], ());
+        let e23 = graph.add_new_edge(&n[2], &n[3], ());
+        let e34 = graph.add_new_edge(&n[3], &n[4], ());
+        let e42 = graph.add_new_edge(&n[4], &n[2], ());
+        let e45 = graph.add_new_edge(&n[4], &n[5], ());
+        graph.add_new_edge(&n[5], &n[5], ());
+
+        let blocks: Vec<_> = graph
+            .biconnected_components()
+            .into_iter()
+            .map(|block| (sorted(block.nodes), sorted(block.edges)))
+            .collect();
+        assert_eq!(blocks.len(), 4);
+        assert!(blocks.contains(&(
+            vec![n[0].clone(), n[1].clone(), n[2].clone()],
+            vec![e01, e12, e20]
+        )));
+        assert!(blocks.contains(&(
+            vec![n[2].clone(), n[3].clone(), n[4].clone()],
+            vec![e23, e34, e42]
+        )));
+        assert!(blocks.contains(&(vec![n[4].clone(), n[5].clone()], vec![e45.clone()])));
+        assert!(blocks.contains(&(vec![n[6].clone()], vec![])));
+
+        assert_eq!(
+            sorted(graph.articulation_points()),
+            vec![n[2].clone(), n[4].clone()]
+        );
+        assert_eq!(graph.bridges(), vec![e45]);
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        let mut graph = LinkedGraph::<(), (), Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&b, &a, ());
+        let bc = graph.add_new_edge(&b, &c, ());
+
+        let blocks = graph.biconnected_components();
+        assert_eq!(blocks.len(), 2);
+        let mut sizes: Vec<_> = blocks.iter().map(|block| block.edges.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 2]);
+        assert_eq!(graph.articulation_points(), vec![b.clone()]);
+        // Parallel edges are never bridges.
+        assert_eq!(graph.bridges(), vec![bc]);
+    }
+
+    #[test]
+    fn test_block_cut_tree() {
+        // A path a-b-c-d, where each edge is its own block.
+        let mut graph = LinkedGraph::<(), (), Undirected>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        for pair in n.windows(2) {
+            graph.add_new_edge(&pair[0], &pair[1], ());
+        }
+
+        let tree: BagGraph<BlockCutNode<_, _>, (), Undirected> = graph.block_cut_tree();
+        assert_eq!(tree.num_nodes(), 5);
+        assert_eq!(tree.num_edges(), 4);
+        let cuts: Vec<_> = tree
+            .node_ids()
+            .filter_map(|nid| match tree.node_data(&nid) {
+                BlockCutNode::Cut(cut) => Some((cut.clone(), tree.num_edges_from(&nid))),
+                BlockCutNode::Block(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            sorted(cuts),
+            sorted(vec![(n[1].clone(), 2), (n[2].clone(), 2)])
+        );
+    }
+}
@@ -0,0 +1,499 @@
+//! A compact binary format for saving and loading whole graphs.
+//!
+//! [`write_snapshot`] writes a graph whose node and edge data implement
+//! [`EncodeDecode`], and [`read_snapshot`] reads it back into any
+//! [`GraphMut`] with the same directedness.  The format is designed to load
+//! very large graphs quickly: the structure of the graph is a single array
+//! of fixed-width integers which is read in large blocks, and can be used
+//! in place from a memory-mapped file.
+//!
+//! All integers are little-endian.  A snapshot consists of:
+//!
+//! 1. A 32-byte header: the magic bytes [`MAGIC`], the format
+//!    [`VERSION`] as a `u32`, a `u32` of flags (bit 0 is set for a directed
+//!    graph), and the number of nodes and the number of edges as `u64`s.
+//! 2. The ends of each edge, as two `u64` indices of nodes in the order they
+//!    are written, so this array starts at offset 32 and is 8-byte aligned.
+//! 3. The data of each node, encoded with [`EncodeDecode::encode`].
+//! 4. The data of each edge, in the same order as their ends.
+//!
+//! Nodes and edges are written in the order they are returned by
+//! [`Graph::node_ids`] and [`Graph::edge_ids`], and read back in the same
+//! order, so IDs are not preserved but the order of nodes is.
+//!
+//! Data is read and written with many small calls, so the reader and
+//! writer should be buffered, e.g. with [`io::BufReader`] and
+//! [`io::BufWriter`].
+//!
+//! Since data such as `()` is encoded in no bytes at all, the size of the
+//! input does not bound the size of the graph it describes.  Instead,
+//! [`read_snapshot`] takes a limit on the number of items it reads: the
+//! nodes and edges in the header, and the elements of collections such as
+//! `Vec`s in their data.
+
+use std::io::{self, Read, Write};
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, prelude::*};
+
+/// The bytes a snapshot starts with.
+pub const MAGIC: [u8; 8] = *b"GRPHSNAP";
+
+/// The version of the format written by [`write_snapshot`].
+pub const VERSION: u32 = 1;
+
+const DIRECTED_FLAG: u32 = 1;
+
+/// The number of edges whose ends are read at a time.
+const BLOCK_EDGES: usize = 4096;
+
+/// An error reading a snapshot with [`read_snapshot`].
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    /// The underlying reader failed, or the snapshot ended early.
+    #[error("I/O error reading snapshot: {0}")]
+    Io(#[from] io::Error),
+    /// The input does not start with [`MAGIC`].
+    #[error("not a graph snapshot")]
+    NotASnapshot,
+    /// The snapshot was written in a version of the format this crate
+    /// cannot read.
+    #[error("unsupported snapshot version {0}")]
+    UnsupportedVersion(u32),
+    /// The snapshot's directedness does not match the graph being built.
+    #[error("the snapshot's directedness does not match the target graph")]
+    Directedness,
+    /// An edge refers to a node index past the end of the nodes.
+    #[error("edge refers to unknown node {0}")]
+    UnknownNode(u64),
+    /// The header claims more nodes and edges than the limit passed to
+    /// [`read_snapshot`].
+    #[error("snapshot has {0} nodes and edges, more than the limit")]
+    TooLarge(u128),
+}
+
+/// A reader of snapshot data, which counts the items read against the limit
+/// passed to [`read_snapshot`].
+#[derive(Debug)]
+pub struct SnapshotReader<R> {
+    inner: R,
+    items_left: u64,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    /// Counts `count` items against the limit, failing if it would be
+    /// exceeded.  Implementations of [`EncodeDecode::decode`] for
+    /// collections call this with their length before reading their
+    /// elements.
+    pub fn take_items(&mut self, count: u64) -> io::Result<()> {
+        self.items_left = self
+            .items_left
+            .checked_sub(count)
+            .ok_or_else(|| invalid_data("snapshot exceeds its item limit"))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for SnapshotReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+/// A type which can be written to and read back from a binary snapshot.
+/// Implementations must read exactly the bytes they write.
+pub trait EncodeDecode: Sized {
+    /// Writes the value to `w`.
+    fn encode(&self, w: &mut impl Write) -> io::Result<()>;
+
+    /// Reads a value written by [`Self::encode`] from `r`.
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self>;
+}
+
+macro_rules! impl_encode_decode_for_number {
+    ($($t:ty),*) => {
+        $(
+            impl EncodeDecode for $t {
+                fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+                    w.write_all(&self.to_le_bytes())
+                }
+
+                fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+                    let mut bytes = [0; size_of::<$t>()];
+                    r.read_exact(&mut bytes)?;
+                    Ok(<$t>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_encode_decode_for_number!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl EncodeDecode for () {
+    fn encode(&self, _w: &mut impl Write) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn decode<R: Read>(_r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        Ok(())
+    }
+}
+
+impl EncodeDecode for bool {
+    fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+        u8::from(*self).encode(w)
+    }
+
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        match u8::decode(r)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid_data("invalid bool")),
+        }
+    }
+}
+
+impl EncodeDecode for char {
+    fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+        u32::from(*self).encode(w)
+    }
+
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        char::from_u32(u32::decode(r)?).ok_or_else(|| invalid_data("invalid char"))
+    }
+}
+
+impl EncodeDecode for usize {
+    fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+        (*self as u64).encode(w)
+    }
+
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        usize::try_from(u64::decode(r)?).map_err(|_| invalid_data("usize out of range"))
+    }
+}
+
+impl EncodeDecode for String {
+    fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+        self.len().encode(w)?;
+        w.write_all(self.as_bytes())
+    }
+
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        let len = usize::decode(r)?;
+        let mut bytes = Vec::new();
+        r.take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8"))
+    }
+}
+
+impl<T: EncodeDecode> EncodeDecode for Vec<T> {
+    fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+        self.len().encode(w)?;
+        self.iter().try_for_each(|item| item.encode(w))
+    }
+
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        let len = u64::decode(r)?;
+        r.take_items(len)?;
+        // The length is not used to size the allocation, since the limit
+        // may be far larger than the input.
+        (0..len).map(|_| T::decode(r)).collect()
+    }
+}
+
+impl<T: EncodeDecode> EncodeDecode for Option<T> {
+    fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+        self.is_some().encode(w)?;
+        self.as_ref().map_or(Ok(()), |value| value.encode(w))
+    }
+
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        Ok(if bool::decode(r)? {
+            Some(T::decode(r)?)
+        } else {
+            None
+        })
+    }
+}
+
+impl<A: EncodeDecode, B: EncodeDecode> EncodeDecode for (A, B) {
+    fn encode(&self, w: &mut impl Write) -> io::Result<()> {
+        self.0.encode(w)?;
+        self.1.encode(w)
+    }
+
+    fn decode<R: Read>(r: &mut SnapshotReader<R>) -> io::Result<Self> {
+        Ok((A::decode(r)?, B::decode(r)?))
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes `graph` to `w` in the snapshot format.  See the
+/// [module documentation](self).
+pub fn write_snapshot<G>(graph: &G, w: &mut impl Write) -> io::Result<()>
+where
+    G: Graph + ?Sized,
+    G::NodeData: EncodeDecode,
+    G::EdgeData: EncodeDecode,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let index: FastHashMap<_, _> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, nid)| (nid.clone(), i as u64))
+        .collect();
+    let edges: Vec<_> = graph.edge_ids().collect();
+
+    w.write_all(&MAGIC)?;
+    VERSION.encode(w)?;
+    let flags = if graph.is_directed() {
+        DIRECTED_FLAG
+    } else {
+        0
+    };
+    flags.encode(w)?;
+    nodes.len().encode(w)?;
+    edges.len().encode(w)?;
+
+    let mut block = Vec::with_capacity(BLOCK_EDGES * 16);
+    for chunk in edges.chunks(BLOCK_EDGES) {
+        block.clear();
+        for eid in chunk {
+            let (from, into) = graph.edge_ends(eid).into_values();
+            block.extend_from_slice(&index[&from].to_le_bytes());
+            block.extend_from_slice(&index[&into].to_le_bytes());
+        }
+        w.write_all(&block)?;
+    }
+
+    for nid in &nodes {
+        graph.node_data(nid).encode(w)?;
+    }
+    for eid in &edges {
+        graph.edge_data(eid).encode(w)?;
+    }
+    Ok(())
+}
+
+/// Reads a graph written by [`write_snapshot`] from `r`, reading at most
+/// `max_items` nodes, edges and collection elements in all.  The graph's
+/// directedness must match the snapshot's.  With
+/// [`SingleEdge`](crate::SingleEdge), later parallel edges in the snapshot
+/// replace earlier ones, as with [`GraphMut::add_edge`].
+pub fn read_snapshot<G>(r: &mut impl Read, max_items: u64) -> Result<G, SnapshotError>
+where
+    G: GraphMut + Default,
+    G::NodeData: EncodeDecode,
+    G::EdgeData: EncodeDecode,
+{
+    let r = &mut SnapshotReader {
+        inner: r,
+        items_left: max_items,
+    };
+    let mut magic = [0; MAGIC.len()];
+    r.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(SnapshotError::NotASnapshot);
+    }
+    let version = u32::decode(r)?;
+    if version != VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    let directed = u32::decode(r)? & DIRECTED_FLAG != 0;
+    if directed != G::Directedness::IS_DIRECTED {
+        return Err(SnapshotError::Directedness);
+    }
+    let num_nodes = u64::decode(r)?;
+    let num_edges = u64::decode(r)?;
+    let num_items = u128::from(num_nodes) + u128::from(num_edges);
+    if num_items > u128::from(max_items) {
+        return Err(SnapshotError::TooLarge(num_items));
+    }
+    r.items_left -= num_nodes + num_edges;
+    let num_nodes =
+        usize::try_from(num_nodes).map_err(|_| invalid_data("node count out of range"))?;
+
+    // The limit may be far larger than the input, so the ends are read in
+    // blocks rather than reserved up front, and the nodes are only reserved
+    // as far as the ends read so far imply they exist.
+    let mut ends = Vec::new();
+    let mut block = vec![0; BLOCK_EDGES * 16];
+    let mut remaining = num_edges;
+    while remaining > 0 {
+        let count = remaining.min(BLOCK_EDGES as u64) as usize;
+        let bytes = &mut block[..count * 16];
+        r.read_exact(bytes)?;
+        for pair in bytes.chunks_exact(16) {
+            let from = u64::from_le_bytes(pair[..8].try_into().unwrap());
+            let into = u64::from_le_bytes(pair[8..].try_into().unwrap());
+            for end in [from, into] {
+                if end >= num_nodes as u64 {
+                    return Err(SnapshotError::UnknownNode(end));
+                }
+            }
+            ends.push((from as usize, into as usize));
+        }
+        remaining -= count as u64;
+    }
+
+    let reserved_nodes = num_nodes.min(BLOCK_EDGES.max(2 * ends.len()));
+    let mut graph = G::default();
+    graph.reserve(reserved_nodes, ends.len());
+    let mut nodes = Vec::with_capacity(reserved_nodes);
+    for _ in 0..num_nodes {
+        nodes.push(graph.add_node(G::NodeData::decode(r)?));
+    }
+    for (from, into) in ends {
+        graph.add_edge(&nodes[from], &nodes[into], G::EdgeData::decode(r)?);
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AdjacencyGraph, LinkedGraph, SlabGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut graph = LinkedGraph::<String, (u32, Option<f64>), Directed>::default();
+        let a = graph.add_node("a".to_string());
+        let b = graph.add_node("b".to_string());
+        let c = graph.add_node("ç".to_string());
+        graph.add_edge(&a, &b, (1, Some(0.5)));
+        graph.add_edge(&b, &c, (2, None));
+        graph.add_edge(&c, &a, (3, Some(-1.0)));
+        graph.add_edge(&a, &b, (4, None));
+
+        let mut bytes = Vec::new();
+        graph.write_snapshot(&mut bytes).unwrap();
+        assert_eq!(bytes[..8], MAGIC);
+        // The header, the ends, three strings and four edges' data.
+        assert_eq!(bytes.len(), 32 + 4 * 16 + (9 + 9 + 10) + (13 + 5 + 13 + 5));
+
+        let copy: SlabGraph<String, (u32, Option<f64>), Directed> =
+            read_snapshot(&mut bytes.as_slice(), 100).unwrap();
+        assert_eq!(copy.num_nodes(), 3);
+        assert_eq!(copy.num_edges(), 4);
+        let names: Vec<_> = copy
+            .node_ids()
+            .map(|nid| copy.node_data(&nid).clone())
+            .collect();
+        assert_eq!(names, ["a", "b", "ç"]);
+        assert_eq!(copy.structural_hash(), graph.structural_hash());
+        assert_eq!(
+            copy.structural_hash_by(|name| name.clone(), |data| data.0),
+            graph.structural_hash_by(|name| name.clone(), |data| data.0)
+        );
+    }
+
+    #[test]
+    fn test_undirected_single_edges() {
+        let graph = AdjacencyGraph::<u8, u16, Undirected>::from_edges(
+            0..5,
+            [(0, 1, 10), (1, 2, 12), (4, 4, 44)],
+        );
+        let mut bytes = Vec::new();
+        write_snapshot(&graph, &mut bytes).unwrap();
+        let copy: AdjacencyGraph<u8, u16, Undirected> =
+            read_snapshot(&mut bytes.as_slice(), 100).unwrap();
+        assert_eq!(copy.num_nodes(), 5);
+        assert_eq!(
+            copy.structural_hash_by(|n| *n, |e| *e),
+            graph.structural_hash_by(|n| *n, |e| *e)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut graph = LinkedGraph::<u8, u8, Directed>::default();
+        let a = graph.add_node(1);
+        graph.add_edge(&a, &a, 2);
+        let mut bytes = Vec::new();
+        graph.write_snapshot(&mut bytes).unwrap();
+
+        let read = |bytes: &[u8]| read_snapshot::<LinkedGraph<u8, u8, Directed>>(&mut &*bytes, 100);
+        assert!(read(&bytes).is_ok());
+        assert!(matches!(
+            read_snapshot::<LinkedGraph<u8, u8, Undirected>>(&mut bytes.as_slice(), 100),
+            Err(SnapshotError::Directedness)
+        ));
+        assert!(matches!(
+            read(b"GRAPHML!"),
+            Err(SnapshotError::NotASnapshot)
+        ));
+        assert!(matches!(
+            read(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Io(_))
+        ));
+
+        let mut bad_version = bytes.clone();
+        bad_version[8] = 99;
+        assert!(matches!(
+            read(&bad_version),
+            Err(SnapshotError::UnsupportedVersion(99))
+        ));
+        let mut bad_end = bytes.clone();
+        bad_end[32] = 7;
+        assert!(matches!(read(&bad_end), Err(SnapshotError::UnknownNode(7))));
+    }
+
+    #[test]
+    fn test_forged_node_count() {
+        let mut graph = LinkedGraph::<u8, u8, Directed>::default();
+        graph.add_node(1);
+        let mut bytes = Vec::new();
+        graph.write_snapshot(&mut bytes).unwrap();
+
+        // A header claiming far more nodes than the input holds must fail
+        // when the input runs out, not when reserving space, even when the
+        // limit allows it.
+        bytes[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        let result =
+            read_snapshot::<LinkedGraph<u8, u8, Directed>>(&mut bytes.as_slice(), u64::MAX);
+        match result {
+            Err(SnapshotError::Io(err)) => assert!(matches!(
+                err.kind(),
+                io::ErrorKind::UnexpectedEof | io::ErrorKind::InvalidData
+            )),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_item_limit() {
+        // Nodes with `()` data take no bytes, so only the limit stops a
+        // header claiming 2^40 of them.
+        let mut bytes = Vec::new();
+        LinkedGraph::<(), (), Directed>::default()
+            .write_snapshot(&mut bytes)
+            .unwrap();
+        bytes[16..24].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(matches!(
+            read_snapshot::<LinkedGraph<(), (), Directed>>(&mut bytes.as_slice(), 1000),
+            Err(SnapshotError::TooLarge(n)) if n == 1 << 40
+        ));
+
+        // The same goes for the elements of a `Vec<()>`.
+        let mut graph = LinkedGraph::<Vec<()>, (), Directed>::default();
+        graph.add_node(vec![(); 3]);
+        let mut bytes = Vec::new();
+        graph.write_snapshot(&mut bytes).unwrap();
+        let read =
+            |bytes: &[u8]| read_snapshot::<LinkedGraph<Vec<()>, (), Directed>>(&mut &*bytes, 1000);
+        assert_eq!(read(&bytes).unwrap().num_nodes(), 1);
+        bytes[32..40].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        match read(&bytes) {
+            Err(SnapshotError::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidData),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
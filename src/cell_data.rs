@@ -0,0 +1,152 @@
+//! Convenience support for node and edge data wrapped in a `RefCell`.
+//!
+//! The [`Graph`] trait only hands out shared references to node and edge data,
+//! so the usual way to mutate data in place is to store it in a `RefCell`.
+//! The [`CellData`] trait lets the graph see through such wrappers, so that
+//! [`Graph::node_data_ref`], [`Graph::node_data_ref_mut`],
+//! [`Graph::edge_data_ref`] and [`Graph::edge_data_ref_mut`] can return
+//! `Ref`/`RefMut` guards directly.
+//!
+//! # Avoiding borrow conflicts
+//!
+//! A `RefMut` guard for one node must not be alive while the same node is
+//! borrowed again, which is easy to do by accident inside a traversal.  Two
+//! patterns avoid this:
+//!
+//! - Keep each guard in as small a scope as possible.  Read what you need
+//!   into a local, drop the guard, and only then visit neighbors:
+//!
+//!   ```
+//!   # use std::cell::RefCell;
+//!   # use graphitude::{BagGraph, prelude::*};
+//!   let mut graph = BagGraph::<RefCell<u32>, (), Directed>::default();
+//!   let a = graph.add_node(RefCell::new(1));
+//!   let b = graph.add_node(RefCell::new(2));
+//!   graph.add_new_edge(&a, &b, ());
+//!   graph.add_new_edge(&b, &a, ());
+//!
+//!   for nid in graph.node_ids() {
+//!       let total: u32 = graph.successors(&nid).map(|s| *graph.node_data_ref(&s)).sum();
+//!       *graph.node_data_ref_mut(&nid) += total;
+//!   }
+//!   ```
+//!
+//! - When every update depends on the old values of other nodes, compute all
+//!   of the new values first and write them back in a second pass.
+//!
+//! Borrow conflicts panic with a message naming the offending node or edge ID,
+//! rather than the generic `BorrowMutError` message.
+
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    fmt::Debug,
+};
+
+#[cfg(doc)]
+use crate::Graph;
+
+/// A trait for node and edge data types that wrap their contents in a
+/// `RefCell`.  It is implemented for `RefCell<T>` itself, and can be
+/// implemented for user types which contain a `RefCell` alongside other
+/// fields.
+pub trait CellData {
+    /// The type stored inside the `RefCell`.
+    type Inner;
+
+    /// Returns the `RefCell` holding the mutable part of the data.
+    fn cell(&self) -> &RefCell<Self::Inner>;
+}
+
+impl<T> CellData for RefCell<T> {
+    type Inner = T;
+
+    fn cell(&self) -> &RefCell<T> {
+        self
+    }
+}
+
+/// Borrows the contents of a cell, panicking with a message mentioning `id`
+/// if it is already mutably borrowed.
+pub(crate) fn borrow_cell<'a, C, I>(data: &'a C, id: &I) -> Ref<'a, C::Inner>
+where
+    C: CellData + ?Sized,
+    I: Debug,
+{
+    data.cell()
+        .try_borrow()
+        .unwrap_or_else(|_| panic!("data for {:?} is already mutably borrowed", id))
+}
+
+/// Mutably borrows the contents of a cell, panicking with a message
+/// mentioning `id` if it is already borrowed.
+pub(crate) fn borrow_cell_mut<'a, C, I>(data: &'a C, id: &I) -> RefMut<'a, C::Inner>
+where
+    C: CellData + ?Sized,
+    I: Debug,
+{
+    data.cell()
+        .try_borrow_mut()
+        .unwrap_or_else(|_| panic!("data for {:?} is already borrowed", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::{BagGraph, prelude::*};
+
+    use super::*;
+
+    struct Counter {
+        name: &'static str,
+        count: RefCell<usize>,
+    }
+
+    impl CellData for Counter {
+        type Inner = usize;
+
+        fn cell(&self) -> &RefCell<usize> {
+            &self.count
+        }
+    }
+
+    #[test]
+    fn test_node_data_ref() {
+        let mut graph = BagGraph::<RefCell<i32>, (), Directed>::default();
+        let n = graph.add_node(RefCell::new(5));
+        assert_eq!(*graph.node_data_ref(&n), 5);
+        *graph.node_data_ref_mut(&n) += 1;
+        assert_eq!(*graph.node_data_ref(&n), 6);
+    }
+
+    #[test]
+    fn test_edge_data_ref() {
+        let mut graph = BagGraph::<(), RefCell<String>, Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let e = graph.add_new_edge(&a, &b, RefCell::new("x".to_string()));
+        graph.edge_data_ref_mut(&e).push('y');
+        assert_eq!(*graph.edge_data_ref(&e), "xy");
+    }
+
+    #[test]
+    fn test_custom_cell_data() {
+        let mut graph = BagGraph::<Counter, (), Directed>::default();
+        let n = graph.add_node(Counter {
+            name: "a",
+            count: RefCell::new(0),
+        });
+        *graph.node_data_ref_mut(&n) += 2;
+        assert_eq!(graph.node_data(&n).name, "a");
+        assert_eq!(*graph.node_data_ref(&n), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn test_conflicting_borrow_panics() {
+        let mut graph = BagGraph::<RefCell<i32>, (), Directed>::default();
+        let n = graph.add_node(RefCell::new(0));
+        let _guard = graph.node_data_ref_mut(&n);
+        let _ = graph.node_data_ref(&n);
+    }
+}
@@ -0,0 +1,264 @@
+//! Greedy graph coloring and conflict graphs.
+//!
+//! A coloring assigns each node a color, numbered from zero, so that no two
+//! adjacent nodes have the same color.  Finding a coloring with the fewest
+//! colors is NP-hard, so [`greedy_coloring`] colors the nodes one at a time
+//! in a given order, giving each the smallest color not used by its
+//! neighbors.  [`welsh_powell_coloring`] uses the Welsh–Powell order, which
+//! colors nodes of higher degree first, and never uses more than one color
+//! more than the largest degree of any node.
+//!
+//! Coloring is often used for scheduling, in which each node is a task, an
+//! edge joins tasks which cannot share a resource, and each color is a
+//! resource.  [`conflict_graph`] builds such a graph from a list of items and
+//! a predicate which tells whether two items conflict, and
+//! [`interval_conflict_graph`] builds it more efficiently when items conflict
+//! exactly when their intervals overlap.
+//!
+//! Edge direction and parallel edges are ignored.  Self-loops are also
+//! ignored, since a node with a self-loop has no valid color.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    ops::Range,
+};
+
+use crate::{hashing::FastHashMap, prelude::*};
+
+/// Returns the nodes of `graph` in Welsh–Powell order: by decreasing number
+/// of incident edges, with ties in the order returned by [`Graph::node_ids`].
+pub fn welsh_powell_order<G>(graph: &G) -> Vec<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    let mut nodes: Vec<_> = graph
+        .node_ids()
+        .map(|nid| {
+            // A directed self-loop is both an in- and an out-edge, but it is
+            // still one incident edge.
+            let self_loops = if graph.is_directed() {
+                graph.num_edges_from_into(&nid, &nid)
+            } else {
+                0
+            };
+            let degree = graph.degree(&nid, Direction::Both) - self_loops;
+            (Reverse(degree), nid)
+        })
+        .collect();
+    // A stable sort keeps ties in their original order.
+    nodes.sort_by_key(|(degree, _)| *degree);
+    nodes.into_iter().map(|(_, nid)| nid).collect()
+}
+
+/// Colors the nodes of `graph` in Welsh–Powell order.  See
+/// [`Graph::welsh_powell_coloring`].
+pub fn welsh_powell_coloring<G>(graph: &G) -> HashMap<G::NodeId, usize>
+where
+    G: Graph + ?Sized,
+{
+    greedy_coloring(graph, welsh_powell_order(graph))
+}
+
+/// Colors the nodes in `order`, giving each the smallest color which none
+/// of its neighbors has been given.  Nodes of `graph` which are not in
+/// `order` are not colored and do not constrain the colors of other nodes.
+pub fn greedy_coloring<G>(
+    graph: &G,
+    order: impl IntoIterator<Item = G::NodeId>,
+) -> HashMap<G::NodeId, usize>
+where
+    G: Graph + ?Sized,
+{
+    let mut colors: FastHashMap<G::NodeId, usize> = FastHashMap::default();
+    let mut used = Vec::new();
+    for nid in order {
+        used.clear();
+        used.extend(
+            graph
+                .neighbors(&nid, Direction::Both)
+                .filter(|other| *other != nid)
+                .filter_map(|other| colors.get(&other).copied()),
+        );
+        used.sort_unstable();
+        used.dedup();
+        // The first color which is not used is the first gap in `used`.
+        let color = used
+            .iter()
+            .enumerate()
+            .find(|&(i, &color)| i != color)
+            .map_or(used.len(), |(i, _)| i);
+        colors.insert(nid, color);
+    }
+    colors.into_iter().collect()
+}
+
+/// Constructs a graph with a node for each item, and an edge between each
+/// pair of items for which `conflicts` returns true.  Nodes are added in the
+/// order of `items`.  This checks every pair of items, so it takes
+/// `O(n²)` time; use [`interval_conflict_graph`] when items are intervals.
+pub fn conflict_graph<G, T>(
+    items: impl IntoIterator<Item = T>,
+    mut conflicts: impl FnMut(&T, &T) -> bool,
+) -> G
+where
+    G: GraphMut<NodeData = T, EdgeData = (), Directedness = Undirected> + Default,
+{
+    let mut graph = G::default();
+    let nodes: Vec<_> = items.into_iter().map(|item| graph.add_node(item)).collect();
+    for (i, a) in nodes.iter().enumerate() {
+        for b in &nodes[i + 1..] {
+            if conflicts(graph.node_data(a), graph.node_data(b)) {
+                graph.add_edge(a, b, ());
+            }
+        }
+    }
+    graph
+}
+
+/// Like [`conflict_graph`], but two items conflict if the half-open
+/// intervals `interval` returns for them overlap.  Empty intervals overlap
+/// nothing.  This sorts the intervals and sweeps over them, so it takes
+/// `O(n log n + e)` time for `n` items and `e` conflicts.
+pub fn interval_conflict_graph<G, T, K>(
+    items: impl IntoIterator<Item = T>,
+    interval: impl Fn(&T) -> Range<K>,
+) -> G
+where
+    G: GraphMut<NodeData = T, EdgeData = (), Directedness = Undirected> + Default,
+    K: Ord,
+{
+    let mut graph = G::default();
+    let mut starts: Vec<_> = items
+        .into_iter()
+        .map(|item| {
+            let range = interval(&item);
+            (range, graph.add_node(item))
+        })
+        .filter(|(range, _)| range.start < range.end)
+        .enumerate()
+        .map(|(i, (range, nid))| (range, i, nid))
+        .collect();
+    starts.sort_by(|(a, i, _), (b, j, _)| (&a.start, i).cmp(&(&b.start, j)));
+
+    // The intervals which have started, ordered by when they end.
+    let mut active: BinaryHeap<Reverse<(&K, usize)>> = BinaryHeap::new();
+    for (k, (range, _, nid)) in starts.iter().enumerate() {
+        while active
+            .peek()
+            .is_some_and(|Reverse((end, _))| **end <= range.start)
+        {
+            active.pop();
+        }
+        for Reverse((_, other)) in active.iter() {
+            graph.add_edge(&starts[*other].2, nid, ());
+        }
+        active.push(Reverse((&range.end, k)));
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph, end_pair::EndPair};
+
+    use super::*;
+
+    fn check_coloring<G: Graph>(graph: &G, colors: &HashMap<G::NodeId, usize>) {
+        for eid in graph.edge_ids() {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            if from != into {
+                assert_ne!(colors[&from], colors[&into]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_welsh_powell() {
+        // A wheel: a hub joined to each node of a cycle of five.
+        let mut graph = LinkedGraph::<usize, (), Undirected>::default();
+        let hub = graph.add_node(0);
+        let rim: Vec<_> = (1..=5).map(|i| graph.add_node(i)).collect();
+        for i in 0..5 {
+            graph.add_new_edge(&hub, &rim[i], ());
+            graph.add_new_edge(&rim[i], &rim[(i + 1) % 5], ());
+        }
+        graph.add_new_edge(&rim[0], &rim[0], ());
+
+        let order = welsh_powell_order(&graph);
+        assert_eq!(order[0], hub);
+        let colors = welsh_powell_coloring(&graph);
+        check_coloring(&graph, &colors);
+        assert_eq!(colors[&hub], 0);
+        // An odd cycle needs three colors, plus one for the hub.
+        assert_eq!(colors.values().max(), Some(&3));
+    }
+
+    #[test]
+    fn test_welsh_powell_order_directed_self_loop() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        let d = graph.add_node(());
+        graph.add_new_edge(&a, &a, ());
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&b, &c, ());
+        graph.add_new_edge(&d, &b, ());
+
+        // The self-loop counts once, so `a` has two edges to `b`'s three.
+        assert_eq!(welsh_powell_order(&graph)[0], b);
+    }
+
+    #[test]
+    fn test_greedy_coloring_directed() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&c, &b, ());
+
+        let colors = greedy_coloring(&graph, [b.clone(), a.clone(), c.clone()]);
+        assert_eq!(colors[&b], 0);
+        assert_eq!(colors[&a], 1);
+        assert_eq!(colors[&c], 1);
+
+        let colors = greedy_coloring(&graph, [a.clone()]);
+        assert_eq!(colors.len(), 1);
+    }
+
+    #[test]
+    fn test_interval_conflict_graph_matches_naive() {
+        let intervals = vec![0..4, 2..3, 3..6, 6..7, 5..5, 1..10, 7..9, 8..9];
+        let overlaps = |a: &Range<i32>, b: &Range<i32>| {
+            a.start < a.end && b.start < b.end && a.start < b.end && b.start < a.end
+        };
+        let naive: BagGraph<Range<i32>, (), Undirected> =
+            conflict_graph(intervals.clone(), overlaps);
+        let swept: BagGraph<Range<i32>, (), Undirected> =
+            interval_conflict_graph(intervals, |range| range.clone());
+
+        let edges = |graph: &BagGraph<Range<i32>, (), Undirected>| {
+            let mut edges: Vec<_> = graph
+                .edge_ids()
+                .map(|eid| {
+                    let (a, b) = graph.edge_ends(&eid).into_values();
+                    let (a, b) = (graph.node_data(&a), graph.node_data(&b));
+                    let (a, b) = ((a.start, a.end), (b.start, b.end));
+                    (a.min(b), a.max(b))
+                })
+                .collect();
+            edges.sort();
+            edges
+        };
+        assert_eq!(swept.num_nodes(), 8);
+        assert_eq!(edges(&swept), edges(&naive));
+        assert_eq!(edges(&swept).len(), 9);
+
+        // Three intervals overlap at 2, so three rooms are needed.
+        let colors = welsh_powell_coloring(&swept);
+        check_coloring(&swept, &colors);
+        assert_eq!(colors.values().max(), Some(&2));
+    }
+}
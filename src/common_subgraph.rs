@@ -0,0 +1,370 @@
+//! Approximate maximum common edge subgraphs.
+//!
+//! The maximum common edge subgraph (MCES) of two graphs is the largest set
+//! of edges of the first graph which, under some one-to-one correspondence
+//! between nodes, are also edges of the second.  Its size is a common
+//! measure of the similarity of two graphs, e.g. of molecules, but finding
+//! it is NP-hard.  [`max_common_subgraph_approx`] runs a branch and bound
+//! search which is cut off when a time budget runs out.  The first branch
+//! explored is a greedy one, so a good correspondence is found quickly, and
+//! later branches only replace it with better ones.  If the search finishes
+//! in time, the result is optimal.
+
+use std::{
+    cmp::Reverse,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    GraphElementId,
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+};
+
+/// A correspondence between nodes of two graphs, as found by
+/// [`max_common_subgraph_approx`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommonSubgraph<N1, N2> {
+    /// Pairs of corresponding nodes, ordered by the node in the first graph.
+    /// Only nodes with at least one edge in the common subgraph are listed.
+    pub nodes: Vec<(N1, N2)>,
+    /// The number of edges in the common subgraph.  Parallel edges count
+    /// once, and in a directed graph, edges in opposite directions count
+    /// separately.
+    pub num_edges: usize,
+    /// True if the search finished within its time budget, so that no
+    /// common subgraph has more edges.
+    pub optimal: bool,
+}
+
+/// The adjacency of a graph, with nodes numbered in `node_ids` order.
+struct Adjacency<N> {
+    nodes: Vec<N>,
+    /// Pairs `(u, v)` such that there is an edge from `u` into `v`, or in an
+    /// undirected graph, between them.
+    pairs: FastHashSet<(usize, usize)>,
+    /// The distinct neighbors of each node, in either direction.
+    neighbors: Vec<Vec<usize>>,
+    directed: bool,
+}
+
+impl<N> Adjacency<N> {
+    fn new<G>(graph: &G) -> Self
+    where
+        G: Graph<NodeId = N> + ?Sized,
+        N: GraphElementId,
+    {
+        let nodes: Vec<_> = graph.node_ids().collect();
+        let index: FastHashMap<_, _> = nodes.iter().cloned().zip(0..).collect();
+        let directed = G::Directedness::IS_DIRECTED;
+        let mut pairs = FastHashSet::default();
+        let mut neighbor_sets = vec![FastHashSet::default(); nodes.len()];
+        for eid in graph.edge_ids() {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            let (u, v) = (index[&from], index[&into]);
+            pairs.insert((u, v));
+            if !directed {
+                pairs.insert((v, u));
+            }
+            neighbor_sets[u].insert(v);
+            neighbor_sets[v].insert(u);
+        }
+        let neighbors = neighbor_sets
+            .into_iter()
+            .map(|set| {
+                let mut neighbors: Vec<_> = set.into_iter().collect();
+                neighbors.sort_unstable();
+                neighbors
+            })
+            .collect();
+        Self {
+            nodes,
+            pairs,
+            neighbors,
+            directed,
+        }
+    }
+
+    fn has(&self, u: usize, v: usize) -> bool {
+        self.pairs.contains(&(u, v))
+    }
+
+    /// Returns the number of edges between `u` and `w`, counted as in
+    /// [`CommonSubgraph::num_edges`].
+    fn count(&self, u: usize, w: usize) -> usize {
+        usize::from(self.has(u, w)) + usize::from(self.directed && u != w && self.has(w, u))
+    }
+
+    /// Returns the number of edges between `u` and `w` which are also edges
+    /// between `v` and `x` in `other`.
+    fn common(
+        &self,
+        other: &Adjacency<impl Sized>,
+        (u, v): (usize, usize),
+        (w, x): (usize, usize),
+    ) -> usize {
+        usize::from(self.has(u, w) && other.has(v, x))
+            + usize::from(self.directed && u != w && self.has(w, u) && other.has(x, v))
+    }
+
+    fn num_edges(&self) -> usize {
+        let loops = (0..self.nodes.len()).filter(|&u| self.has(u, u)).count();
+        if self.directed {
+            self.pairs.len()
+        } else {
+            (self.pairs.len() - loops) / 2 + loops
+        }
+    }
+}
+
+/// The state of the branch and bound search.
+struct Search<'a, N1, N2, F> {
+    g1: &'a Adjacency<N1>,
+    g2: &'a Adjacency<N2>,
+    compatible: F,
+    deadline: Instant,
+    /// The nodes of the first graph, in the order they are decided.
+    order: Vec<usize>,
+    /// The node of the second graph each decided node corresponds to, or
+    /// `None` if it was left out.
+    mapping: Vec<Option<Option<usize>>>,
+    used: Vec<bool>,
+    best: Vec<Option<usize>>,
+    best_score: usize,
+    /// The largest possible score, at which the search stops early.
+    limit: usize,
+    /// True once the first, greedy branch has been explored to the end.  The
+    /// time budget is not checked before then, so some result is always
+    /// found.
+    descended: bool,
+    timed_out: bool,
+}
+
+impl<N1, N2, F> Search<'_, N1, N2, F>
+where
+    F: FnMut(&N1, &N2) -> bool,
+{
+    /// Explores the assignments of `order[depth..]`, given a score so far and
+    /// the number of edges of the first graph not yet decided.
+    fn extend(&mut self, depth: usize, score: usize, remaining: usize) {
+        if self.best_score >= self.limit {
+            return;
+        }
+        if self.descended && Instant::now() >= self.deadline {
+            self.timed_out = true;
+            return;
+        }
+        if score > self.best_score {
+            self.best_score = score;
+            self.best = self.mapping.iter().map(|m| m.flatten()).collect();
+        }
+        let Some(&u) = self.order.get(depth) else {
+            self.descended = true;
+            return;
+        };
+
+        // Edges between `u` and decided nodes, including itself, are decided
+        // along with `u`.
+        let decided: Vec<_> = self.g1.neighbors[u]
+            .iter()
+            .copied()
+            .filter(|&w| w == u || self.mapping[w].is_some())
+            .collect();
+        let now_decided: usize = decided.iter().map(|&w| self.g1.count(u, w)).sum();
+        let remaining = remaining - now_decided;
+
+        let mut candidates: Vec<_> = (0..self.g2.nodes.len())
+            .filter(|&v| !self.used[v] && (self.compatible)(&self.g1.nodes[u], &self.g2.nodes[v]))
+            .map(|v| {
+                let gain: usize = decided
+                    .iter()
+                    .map(|&w| {
+                        let x = if w == u {
+                            Some(v)
+                        } else {
+                            self.mapping[w].flatten()
+                        };
+                        x.map_or(0, |x| self.g1.common(self.g2, (u, v), (w, x)))
+                    })
+                    .sum();
+                (gain, v)
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        for (gain, v) in candidates {
+            if score + gain + remaining <= self.best_score {
+                break;
+            }
+            self.mapping[u] = Some(Some(v));
+            self.used[v] = true;
+            self.extend(depth + 1, score + gain, remaining);
+            self.used[v] = false;
+            self.mapping[u] = None;
+            if self.timed_out {
+                return;
+            }
+        }
+        if score + remaining > self.best_score {
+            self.mapping[u] = Some(None);
+            self.extend(depth + 1, score, remaining);
+            self.mapping[u] = None;
+        }
+    }
+}
+
+/// Returns the order in which to decide the nodes of `g1`: each node is the
+/// one with the most neighbors among those already chosen, with ties broken
+/// by degree, so that the gain of each choice is known as early as possible.
+fn search_order<N>(g1: &Adjacency<N>) -> Vec<usize> {
+    let n = g1.nodes.len();
+    let mut chosen = vec![false; n];
+    let mut links = vec![0usize; n];
+    let mut order = Vec::with_capacity(n);
+    for _ in 0..n {
+        let u = (0..n)
+            .filter(|&u| !chosen[u])
+            .max_by_key(|&u| (links[u], g1.neighbors[u].len(), Reverse(u)))
+            .unwrap();
+        chosen[u] = true;
+        order.push(u);
+        for &w in &g1.neighbors[u] {
+            links[w] += 1;
+        }
+    }
+    order
+}
+
+/// Finds a correspondence between nodes of `g1` and `g2` under which as
+/// many edges as possible are shared, spending about `time_budget` on the
+/// search.  The first, greedy branch of the search is always finished, even
+/// if that takes longer.  See the [module documentation](self).
+///
+/// Nodes `a` of `g1` and `b` of `g2` can only correspond if
+/// `node_compat(a, b)` is true, e.g. if they are atoms of the same element.
+pub fn max_common_subgraph_approx<G1, G2>(
+    g1: &G1,
+    g2: &G2,
+    node_compat: impl FnMut(&G1::NodeId, &G2::NodeId) -> bool,
+    time_budget: Duration,
+) -> CommonSubgraph<G1::NodeId, G2::NodeId>
+where
+    G1: Graph + ?Sized,
+    G2: Graph<Directedness = G1::Directedness> + ?Sized,
+{
+    let a1 = Adjacency::new(g1);
+    let a2 = Adjacency::new(g2);
+    let total = a1.num_edges();
+    let mut search = Search {
+        g1: &a1,
+        g2: &a2,
+        compatible: node_compat,
+        deadline: Instant::now() + time_budget,
+        order: search_order(&a1),
+        mapping: vec![None; a1.nodes.len()],
+        used: vec![false; a2.nodes.len()],
+        best: vec![None; a1.nodes.len()],
+        best_score: 0,
+        limit: total.min(a2.num_edges()),
+        descended: false,
+        timed_out: false,
+    };
+    search.extend(0, 0, total);
+
+    let best = search.best;
+    let nodes = (0..a1.nodes.len())
+        .filter_map(|u| {
+            let v = best[u]?;
+            a1.neighbors[u]
+                .iter()
+                .any(|&w| best[w].is_some_and(|x| a1.common(&a2, (u, v), (w, x)) > 0))
+                .then(|| (a1.nodes[u].clone(), a2.nodes[v].clone()))
+        })
+        .collect();
+    CommonSubgraph {
+        nodes,
+        num_edges: search.best_score,
+        optimal: !search.timed_out,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LinkedGraph, generators};
+
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_secs(10);
+
+    /// Counts the edges of `g1` between nodes in `common` which correspond to
+    /// edges of `g2`.
+    fn count_common<G>(g1: &G, g2: &G, common: &CommonSubgraph<G::NodeId, G::NodeId>) -> usize
+    where
+        G: Graph,
+    {
+        let mut count = 0;
+        for (u, v) in &common.nodes {
+            for (w, x) in &common.nodes {
+                let counted = G::Directedness::IS_DIRECTED || u <= w;
+                if counted && g1.has_edge_from_into(u, w) && g2.has_edge_from_into(v, x) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_common_subgraph_of_cycles() {
+        // A 6-cycle and a 5-cycle share a path with 4 edges.
+        let g1: LinkedGraph<(), (), Undirected> = generators::cycle_graph(6, |_| (), |_, _| ());
+        let g2: LinkedGraph<(), (), Undirected> = generators::cycle_graph(5, |_| (), |_, _| ());
+        let common = max_common_subgraph_approx(&g1, &g2, |_, _| true, BUDGET);
+        assert!(common.optimal);
+        assert_eq!(common.num_edges, 4);
+        assert_eq!(common.nodes.len(), 5);
+        assert_eq!(count_common(&g1, &g2, &common), 4);
+    }
+
+    #[test]
+    fn test_node_compatibility() {
+        // Two labeled paths, C-O-C and C-C-O; only one bond is shared.
+        let mut g1 = LinkedGraph::<char, (), Undirected>::default();
+        let [a, b, c] = ['C', 'O', 'C'].map(|label| g1.add_node(label));
+        g1.add_edge(&a, &b, ());
+        g1.add_edge(&b, &c, ());
+        let mut g2 = LinkedGraph::<char, (), Undirected>::default();
+        let [x, y, z] = ['C', 'C', 'O'].map(|label| g2.add_node(label));
+        g2.add_edge(&x, &y, ());
+        g2.add_edge(&y, &z, ());
+
+        let common =
+            max_common_subgraph_approx(&g1, &g2, |u, v| g1.node_data(u) == g2.node_data(v), BUDGET);
+        assert!(common.optimal);
+        assert_eq!(common.num_edges, 1);
+        assert_eq!(count_common(&g1, &g2, &common), 1);
+        assert!(common.nodes.contains(&(b, z)));
+    }
+
+    #[test]
+    fn test_directed_edges() {
+        // Only one of the 2-cycle a <-> b can match an edge of the 3-cycle.
+        let mut g1 = LinkedGraph::<(), (), Directed>::default();
+        let [a, b, c] = [(); 3].map(|_| g1.add_node(()));
+        g1.add_edge(&a, &b, ());
+        g1.add_edge(&b, &a, ());
+        g1.add_edge(&b, &c, ());
+        let g2: LinkedGraph<(), (), Directed> = generators::cycle_graph(3, |_| (), |_, _| ());
+
+        let common = max_common_subgraph_approx(&g1, &g2, |_, _| true, BUDGET);
+        assert!(common.optimal);
+        assert_eq!(common.num_edges, 2);
+        assert_eq!(count_common(&g1, &g2, &common), 2);
+
+        // The greedy branch is finished even without any time.
+        let common = max_common_subgraph_approx(&g1, &g2, |_, _| true, Duration::ZERO);
+        assert!(common.num_edges > 0);
+        assert_eq!(count_common(&g1, &g2, &common), common.num_edges);
+    }
+}
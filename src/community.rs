@@ -0,0 +1,458 @@
+//! Community detection in undirected graphs.
+//!
+//! A community is a group of nodes with more edges among themselves than
+//! to the rest of the graph, such as a circle of friends in a social
+//! network.  Both algorithms here take a weight for each edge; to ignore
+//! weights, give every edge a weight of 1.
+//!
+//! - [`label_propagation`] starts with every node in its own community, and
+//!   repeatedly moves each node to the community with the greatest total
+//!   weight of edges to it.  It is fast, but the result depends on the
+//!   order of the nodes.
+//! - [`louvain`] greedily maximizes modularity, first by moving single nodes
+//!   between communities, and then by merging each community into a single
+//!   node and repeating, until no move improves modularity.  It is slower,
+//!   but usually finds a partition of higher modularity.
+//!
+//! Both are deterministic: nodes are visited in the order returned by
+//! [`Graph::node_ids`], and ties are broken in favor of a node's current
+//! community, and then by the numbers of the communities, which start out
+//! as the positions of the nodes in that order.
+//!
+//! The [`modularity`] of a partition is the fraction of the total edge
+//! weight within communities, minus the fraction expected if the edges were
+//! placed at random while keeping the weighted degree of each node.  It
+//! ranges from -1/2 to 1, and is 0 when all nodes are in one community.
+//! Self-loops count twice towards the degree of their node.
+
+use std::collections::HashMap;
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, prelude::*};
+
+/// An assignment of the nodes of a graph to communities.
+#[derive(Clone, Debug)]
+pub struct Partition<N> {
+    /// The community of each node.  Communities are numbered from 0 in the
+    /// order their first nodes are returned by [`Graph::node_ids`].
+    pub community: HashMap<N, usize>,
+    /// The number of communities.
+    pub num_communities: usize,
+    /// The modularity of the partition.
+    pub modularity: f64,
+}
+
+impl<N> Partition<N>
+where
+    N: Clone + Ord,
+{
+    /// Returns the nodes of each community, indexed by community number.
+    /// Each community is sorted.
+    pub fn groups(&self) -> Vec<Vec<N>> {
+        let mut groups = vec![Vec::new(); self.num_communities];
+        for (nid, &community) in &self.community {
+            groups[community].push(nid.clone());
+        }
+        for group in &mut groups {
+            group.sort();
+        }
+        groups
+    }
+}
+
+/// The weighted structure of a graph with its nodes numbered densely.
+struct WeightedGraph {
+    /// The neighbors of each node other than itself, with the total weight
+    /// of the edges to each.
+    neighbors: Vec<Vec<(usize, f64)>>,
+    /// The total weight of the self-loops of each node.
+    self_loops: Vec<f64>,
+    /// The total weight of all edges.
+    total_weight: f64,
+}
+
+impl WeightedGraph {
+    fn new<G>(graph: &G, nodes: &[G::NodeId], weight_fn: impl Fn(&G::EdgeId) -> f64) -> Self
+    where
+        G: Graph<Directedness = Undirected> + ?Sized,
+    {
+        let index: FastHashMap<_, _> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, nid)| (nid.clone(), i))
+            .collect();
+        let mut weights = vec![FastHashMap::default(); nodes.len()];
+        let mut self_loops = vec![0.0; nodes.len()];
+        let mut total_weight = 0.0;
+        for eid in graph.edge_ids() {
+            let (a, b) = graph.edge_ends(&eid).into_values();
+            let (a, b) = (index[&a], index[&b]);
+            let weight = weight_fn(&eid);
+            total_weight += weight;
+            if a == b {
+                self_loops[a] += weight;
+            } else {
+                *weights[a].entry(b).or_insert(0.0) += weight;
+                *weights[b].entry(a).or_insert(0.0) += weight;
+            }
+        }
+        Self::from_weights(weights, self_loops, total_weight)
+    }
+
+    fn from_weights(
+        weights: Vec<FastHashMap<usize, f64>>,
+        self_loops: Vec<f64>,
+        total_weight: f64,
+    ) -> Self {
+        let neighbors = weights
+            .into_iter()
+            .map(|weights| {
+                let mut neighbors: Vec<_> = weights.into_iter().collect();
+                neighbors.sort_by_key(|&(j, _)| j);
+                neighbors
+            })
+            .collect();
+        Self {
+            neighbors,
+            self_loops,
+            total_weight,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.self_loops.len()
+    }
+
+    /// The weighted degree of a node, counting self-loops twice.
+    fn degree(&self, i: usize) -> f64 {
+        self.neighbors[i].iter().map(|&(_, w)| w).sum::<f64>() + 2.0 * self.self_loops[i]
+    }
+
+    /// Computes the modularity of an assignment of nodes to communities.
+    fn modularity(&self, community: &[usize]) -> f64 {
+        let m = self.total_weight;
+        if m <= 0.0 {
+            return 0.0;
+        }
+        let num_communities = community.iter().max().map_or(0, |&c| c + 1);
+        let mut internal = vec![0.0; num_communities];
+        let mut degrees = vec![0.0; num_communities];
+        for i in 0..self.len() {
+            let c = community[i];
+            internal[c] += self.self_loops[i];
+            // Each edge between different nodes is seen from both ends.
+            internal[c] += self.neighbors[i]
+                .iter()
+                .filter(|&&(j, _)| community[j] == c)
+                .map(|&(_, w)| w / 2.0)
+                .sum::<f64>();
+            degrees[c] += self.degree(i);
+        }
+        internal
+            .iter()
+            .zip(&degrees)
+            .map(|(&l, &d)| l / m - (d / (2.0 * m)).powi(2))
+            .sum()
+    }
+
+    /// Moves single nodes between communities while that improves
+    /// modularity, starting with each node in its own community.  Returns
+    /// the community of each node, and whether any node was moved.
+    fn move_nodes(&self) -> (Vec<usize>, bool) {
+        let n = self.len();
+        let two_m = 2.0 * self.total_weight;
+        let degrees: Vec<_> = (0..n).map(|i| self.degree(i)).collect();
+        let mut community: Vec<_> = (0..n).collect();
+        let mut totals = degrees.clone();
+        let mut moved_any = false;
+        if two_m <= 0.0 {
+            return (community, false);
+        }
+        let mut links: FastHashMap<usize, f64> = FastHashMap::default();
+        loop {
+            let mut moved = false;
+            for i in 0..n {
+                let current = community[i];
+                links.clear();
+                links.insert(current, 0.0);
+                for &(j, w) in &self.neighbors[i] {
+                    *links.entry(community[j]).or_insert(0.0) += w;
+                }
+                totals[current] -= degrees[i];
+                let gain = |c: usize, links_to_c: f64| links_to_c - totals[c] * degrees[i] / two_m;
+                let mut candidates: Vec<_> = links.iter().map(|(&c, &w)| (c, w)).collect();
+                candidates.sort_by_key(|&(c, _)| c);
+                // Only a strict improvement moves the node, so ties favor
+                // its current community, and then the lowest numbered one.
+                let mut best = (current, gain(current, links[&current]));
+                for (c, links_to_c) in candidates {
+                    let g = gain(c, links_to_c);
+                    if g > best.1 + 1e-12 {
+                        best = (c, g);
+                    }
+                }
+                totals[best.0] += degrees[i];
+                if best.0 != current {
+                    community[i] = best.0;
+                    moved = true;
+                    moved_any = true;
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+        (community, moved_any)
+    }
+
+    /// Merges the nodes of each community into a single node.  `community`
+    /// must number the communities densely.
+    fn aggregate(&self, community: &[usize], num_communities: usize) -> Self {
+        let mut weights = vec![FastHashMap::default(); num_communities];
+        let mut self_loops = vec![0.0; num_communities];
+        for i in 0..self.len() {
+            let c = community[i];
+            self_loops[c] += self.self_loops[i];
+            for &(j, w) in &self.neighbors[i] {
+                let d = community[j];
+                if c == d {
+                    // Seen from both ends.
+                    self_loops[c] += w / 2.0;
+                } else {
+                    *weights[c].entry(d).or_insert(0.0) += w;
+                }
+            }
+        }
+        Self::from_weights(weights, self_loops, self.total_weight)
+    }
+}
+
+/// Renumbers communities densely, in order of their first nodes.  Returns
+/// the new numbering and the number of communities.
+fn renumber(community: &[usize]) -> (Vec<usize>, usize) {
+    let mut numbers = FastHashMap::default();
+    let renumbered = community
+        .iter()
+        .map(|&c| {
+            let next = numbers.len();
+            *numbers.entry(c).or_insert(next)
+        })
+        .collect();
+    (renumbered, numbers.len())
+}
+
+fn make_partition<N>(nodes: Vec<N>, weighted: &WeightedGraph, community: &[usize]) -> Partition<N>
+where
+    N: Eq + std::hash::Hash,
+{
+    let (community, num_communities) = renumber(community);
+    let modularity = weighted.modularity(&community);
+    Partition {
+        community: nodes.into_iter().zip(community).collect(),
+        num_communities,
+        modularity,
+    }
+}
+
+/// Finds communities by label propagation, stopping when no node changes
+/// community or after `max_iterations` passes over the nodes.  See the
+/// [module documentation](self).
+pub fn label_propagation<G>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> f64,
+    max_iterations: usize,
+) -> Partition<G::NodeId>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let weighted = WeightedGraph::new(graph, &nodes, weight_fn);
+    let mut label: Vec<_> = (0..nodes.len()).collect();
+    let mut totals: FastHashMap<usize, f64> = FastHashMap::default();
+    for _ in 0..max_iterations {
+        let mut changed = false;
+        for i in 0..nodes.len() {
+            totals.clear();
+            for &(j, w) in &weighted.neighbors[i] {
+                *totals.entry(label[j]).or_insert(0.0) += w;
+            }
+            let Some(max) = totals.values().copied().reduce(f64::max) else {
+                continue;
+            };
+            if totals.get(&label[i]).is_some_and(|&total| total >= max) {
+                continue;
+            }
+            let best = totals
+                .iter()
+                .filter(|&(_, &total)| total >= max)
+                .map(|(&l, _)| l)
+                .max()
+                .unwrap();
+            label[i] = best;
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    make_partition(nodes, &weighted, &label)
+}
+
+/// Finds communities with the Louvain method.  See the [module
+/// documentation](self).
+pub fn louvain<G>(graph: &G, weight_fn: impl Fn(&G::EdgeId) -> f64) -> Partition<G::NodeId>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let weighted = WeightedGraph::new(graph, &nodes, weight_fn);
+    // The community of each original node, as a node of the current level.
+    let mut community: Vec<_> = (0..nodes.len()).collect();
+    let mut level = None;
+    loop {
+        let current = level.as_ref().unwrap_or(&weighted);
+        let (moves, moved) = current.move_nodes();
+        if !moved {
+            break;
+        }
+        let (moves, num_communities) = renumber(&moves);
+        for c in &mut community {
+            *c = moves[*c];
+        }
+        level = Some(current.aggregate(&moves, num_communities));
+    }
+    make_partition(nodes, &weighted, &community)
+}
+
+/// Computes the modularity of an assignment of nodes to communities, where
+/// communities may be numbered arbitrarily.  See the [module
+/// documentation](self).
+///
+/// # Panics
+///
+/// Panics if a node of the graph has no community.
+pub fn modularity<G>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> f64,
+    community: &HashMap<G::NodeId, usize>,
+) -> f64
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let weighted = WeightedGraph::new(graph, &nodes, weight_fn);
+    let assignment: Vec<_> = nodes.iter().map(|nid| community[nid]).collect();
+    let (assignment, _) = renumber(&assignment);
+    weighted.modularity(&assignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LinkedGraph;
+
+    use super::*;
+
+    type TestGraph = LinkedGraph<&'static str, f64, Undirected>;
+
+    /// Two triangles joined by an edge between `c` and `d`.
+    fn two_triangles() -> (TestGraph, Vec<<TestGraph as Graph>::NodeId>) {
+        let mut graph = LinkedGraph::default();
+        let nodes: Vec<_> = ["a", "b", "c", "d", "e", "f"]
+            .into_iter()
+            .map(|name| graph.add_node(name))
+            .collect();
+        for (i, j) in [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3)] {
+            graph.add_edge(&nodes[i], &nodes[j], 1.0);
+        }
+        (graph, nodes)
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_louvain() {
+        let (graph, nodes) = two_triangles();
+        let partition = graph.louvain(|_| 1.0);
+        assert_eq!(partition.num_communities, 2);
+        let mut groups = partition.groups();
+        groups.sort();
+        let mut expected = vec![nodes[..3].to_vec(), nodes[3..].to_vec()];
+        for group in &mut expected {
+            group.sort();
+        }
+        expected.sort();
+        assert_eq!(groups, expected);
+        assert_close(partition.modularity, 6.0 / 7.0 - 0.5);
+        assert_eq!(partition.community[&nodes[0]], 0);
+    }
+
+    #[test]
+    fn test_label_propagation() {
+        let (graph, nodes) = two_triangles();
+        let partition = graph.label_propagation(|_| 1.0, 100);
+        assert_eq!(partition.num_communities, 2);
+        assert_eq!(
+            partition.community[&nodes[0]],
+            partition.community[&nodes[2]]
+        );
+        assert_eq!(
+            partition.community[&nodes[3]],
+            partition.community[&nodes[5]]
+        );
+        assert_close(partition.modularity, 6.0 / 7.0 - 0.5);
+    }
+
+    #[test]
+    fn test_weights() {
+        // With a heavy bridge and light triangle edges, the bridge's ends
+        // belong together.
+        let (graph, nodes) = two_triangles();
+        let bridge = graph.edges_from_into(&nodes[2], &nodes[3]).next().unwrap();
+        let partition = graph.louvain(|eid| if *eid == bridge { 10.0 } else { 0.1 });
+        assert_eq!(
+            partition.community[&nodes[2]],
+            partition.community[&nodes[3]]
+        );
+        assert!(partition.modularity > 0.0);
+    }
+
+    #[test]
+    fn test_modularity() {
+        let (mut graph, nodes) = two_triangles();
+        let all_in_one: HashMap<_, _> = nodes.iter().map(|nid| (nid.clone(), 7)).collect();
+        assert_close(graph.modularity(|_| 1.0, &all_in_one), 0.0);
+        let singletons: HashMap<_, _> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, nid)| (nid.clone(), i))
+            .collect();
+        // Degrees 2, 2, 3, 3, 2, 2 out of a total of 14.
+        assert_close(graph.modularity(|_| 1.0, &singletons), -34.0 / 196.0);
+
+        // A self-loop counts towards both the internal weight and the degree.
+        graph.add_edge(&nodes[0], &nodes[0], 1.0);
+        let two: HashMap<_, _> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, nid)| (nid.clone(), i / 3))
+            .collect();
+        assert_close(
+            graph.modularity(|_| 1.0, &two),
+            4.0 / 8.0 + 3.0 / 8.0 - (9.0f64 / 16.0).powi(2) - (7.0f64 / 16.0).powi(2),
+        );
+    }
+
+    #[test]
+    fn test_empty_and_edgeless() {
+        let mut graph = LinkedGraph::<(), f64, Undirected>::default();
+        let partition = graph.louvain(|_| 1.0);
+        assert_eq!(partition.num_communities, 0);
+        assert_close(partition.modularity, 0.0);
+        graph.add_node(());
+        graph.add_node(());
+        let partition = graph.label_propagation(|_| 1.0, 10);
+        assert_eq!(partition.num_communities, 2);
+        assert_close(partition.modularity, 0.0);
+    }
+}
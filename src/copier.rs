@@ -1,8 +1,12 @@
 #![allow(clippy::type_complexity)]
 
-use std::{collections::HashMap, marker::PhantomData};
+use std::{collections::HashMap, hash::Hash, marker::PhantomData};
 
-use crate::{Directedness, EdgeMultiplicity, Graph, GraphMut, end_pair::EndPair as _};
+use crate::{
+    Directedness, EdgeMultiplicity, Graph, GraphMut,
+    end_pair::EndPair as _,
+    provenance::{Provenance, ProvenanceRecorder},
+};
 
 /// Utility for copying graphs with flexible transformations and mapping of node
 /// and edge IDs.  Uses a builder pattern to allow configuring the copying
@@ -42,7 +46,7 @@ use crate::{Directedness, EdgeMultiplicity, Graph, GraphMut, end_pair::EndPair a
 /// // `node_id_map` and `edge_id_map` contain the mappings of IDs
 /// // from `source_graphs` to `target_graph`.
 /// ```
-pub struct GraphCopier<'g, G, D, M, NT, ET, NM, EM>
+pub struct GraphCopier<'g, G, D, M, NT, ET, NM, EM, PR = ()>
 where
     G: Graph + ?Sized,
 {
@@ -76,6 +80,10 @@ where
     /// cleared and populated during the copying process, allowing the caller to
     /// track how edge IDs in the source graph correspond to edge IDs in the target graph.
     edge_map: EM,
+    /// Whether to swap the ends of each edge when copying it.
+    reverse_edges: bool,
+    /// An optional recorder for the origins of the copied nodes and edges.
+    provenance: PR,
 }
 
 impl<'g, G>
@@ -102,11 +110,13 @@ where
             edge_multiplicity: PhantomData,
             node_map: (),
             edge_map: (),
+            reverse_edges: false,
+            provenance: (),
         }
     }
 }
 
-impl<'g, G, D, M, NT, ET, NM, EM> GraphCopier<'g, G, D, M, NT, ET, NM, EM>
+impl<'g, G, D, M, NT, ET, NM, EM, PR> GraphCopier<'g, G, D, M, NT, ET, NM, EM, PR>
 where
     G: Graph + ?Sized,
     D: Directedness,
@@ -118,7 +128,7 @@ where
     pub fn with_directedness<D2>(
         self,
         _directedness: D2,
-    ) -> GraphCopier<'g, G, D2, M, NT, ET, NM, EM>
+    ) -> GraphCopier<'g, G, D2, M, NT, ET, NM, EM, PR>
     where
         D2: Directedness,
     {
@@ -130,6 +140,8 @@ where
             edge_transformer: self.edge_transformer,
             node_map: self.node_map,
             edge_map: self.edge_map,
+            reverse_edges: self.reverse_edges,
+            provenance: self.provenance,
         }
     }
 
@@ -139,7 +151,7 @@ where
     pub fn with_edge_multiplicity<M2>(
         self,
         _multiplicity: M2,
-    ) -> GraphCopier<'g, G, D, M2, NT, ET, NM, EM>
+    ) -> GraphCopier<'g, G, D, M2, NT, ET, NM, EM, PR>
     where
         M2: EdgeMultiplicity,
     {
@@ -151,6 +163,8 @@ where
             edge_transformer: self.edge_transformer,
             node_map: self.node_map,
             edge_map: self.edge_map,
+            reverse_edges: self.reverse_edges,
+            provenance: self.provenance,
         }
     }
 
@@ -161,7 +175,7 @@ where
     pub fn with_node_map<V>(
         self,
         node_map: &'g mut HashMap<G::NodeId, V>,
-    ) -> GraphCopier<'g, G, D, M, NT, ET, &'g mut HashMap<G::NodeId, V>, EM> {
+    ) -> GraphCopier<'g, G, D, M, NT, ET, &'g mut HashMap<G::NodeId, V>, EM, PR> {
         GraphCopier {
             source: self.source,
             directedness: self.directedness,
@@ -170,6 +184,8 @@ where
             edge_transformer: self.edge_transformer,
             node_map,
             edge_map: self.edge_map,
+            reverse_edges: self.reverse_edges,
+            provenance: self.provenance,
         }
     }
 
@@ -180,7 +196,7 @@ where
     pub fn with_edge_map<V>(
         self,
         edge_map: &'g mut HashMap<G::EdgeId, V>,
-    ) -> GraphCopier<'g, G, D, M, NT, ET, NM, &'g mut HashMap<G::EdgeId, V>> {
+    ) -> GraphCopier<'g, G, D, M, NT, ET, NM, &'g mut HashMap<G::EdgeId, V>, PR> {
         GraphCopier {
             source: self.source,
             directedness: self.directedness,
@@ -189,13 +205,50 @@ where
             edge_transformer: self.edge_transformer,
             node_map: self.node_map,
             edge_map,
+            reverse_edges: self.reverse_edges,
+            provenance: self.provenance,
+        }
+    }
+
+    /// Records the origin of each node and edge copied into the target graph
+    /// in `provenance`, naming the source graph `graph` and this copy
+    /// `transform`, returning a new `GraphCopier`.  See [`crate::provenance`].
+    pub fn with_provenance<TN, TE>(
+        self,
+        provenance: &'g mut Provenance<TN, TE>,
+        graph: &str,
+        transform: &str,
+    ) -> GraphCopier<'g, G, D, M, NT, ET, NM, EM, ProvenanceRecorder<'g, TN, TE>>
+    where
+        TN: Hash + Eq,
+        TE: Hash + Eq,
+    {
+        GraphCopier {
+            source: self.source,
+            directedness: self.directedness,
+            edge_multiplicity: self.edge_multiplicity,
+            node_transformer: self.node_transformer,
+            edge_transformer: self.edge_transformer,
+            node_map: self.node_map,
+            edge_map: self.edge_map,
+            reverse_edges: self.reverse_edges,
+            provenance: ProvenanceRecorder::new(provenance, graph, transform),
+        }
+    }
+
+    /// Returns a `GraphCopier` which swaps the ends of each edge, so that
+    /// copying a directed graph produces its transpose.
+    pub fn reverse_edges(self) -> Self {
+        Self {
+            reverse_edges: true,
+            ..self
         }
     }
 
     /// Returns a new `GraphCopier` with a node transformer that clones the data.
     pub fn clone_nodes(
         self,
-    ) -> GraphCopier<'g, G, D, M, fn(&G::NodeData) -> G::NodeData, ET, NM, EM>
+    ) -> GraphCopier<'g, G, D, M, fn(&G::NodeData) -> G::NodeData, ET, NM, EM, PR>
     where
         G::NodeData: Clone,
     {
@@ -205,7 +258,7 @@ where
     /// Returns a new `GraphCopier` with an edge transformer that clones the data.
     pub fn clone_edges(
         self,
-    ) -> GraphCopier<'g, G, D, M, NT, fn(&G::EdgeData) -> G::EdgeData, NM, EM>
+    ) -> GraphCopier<'g, G, D, M, NT, fn(&G::EdgeData) -> G::EdgeData, NM, EM, PR>
     where
         G::EdgeData: Clone,
     {
@@ -214,7 +267,10 @@ where
 
     /// Returns a new `GraphCopier` with the given node transformer function,
     /// which will be applied to each node's data during copying.
-    pub fn transform_nodes<F, TN>(self, transformer: F) -> GraphCopier<'g, G, D, M, F, ET, NM, EM>
+    pub fn transform_nodes<F, TN>(
+        self,
+        transformer: F,
+    ) -> GraphCopier<'g, G, D, M, F, ET, NM, EM, PR>
     where
         F: FnMut(&G::NodeData) -> TN,
     {
@@ -226,12 +282,17 @@ where
             edge_transformer: self.edge_transformer,
             node_map: self.node_map,
             edge_map: self.edge_map,
+            reverse_edges: self.reverse_edges,
+            provenance: self.provenance,
         }
     }
 
     /// Returns a new `GraphCopier` with the given edge transformer function,
     /// which will be applied to each edge's data during copying.
-    pub fn transform_edges<F, TE>(self, transformer: F) -> GraphCopier<'g, G, D, M, NT, F, NM, EM>
+    pub fn transform_edges<F, TE>(
+        self,
+        transformer: F,
+    ) -> GraphCopier<'g, G, D, M, NT, F, NM, EM, PR>
     where
         F: FnMut(&G::EdgeData) -> TE,
     {
@@ -243,6 +304,8 @@ where
             edge_transformer: transformer,
             node_map: self.node_map,
             edge_map: self.edge_map,
+            reverse_edges: self.reverse_edges,
+            provenance: self.provenance,
         }
     }
 
@@ -260,6 +323,7 @@ where
         ET: FnMut(&G::EdgeData) -> T::EdgeData,
         NM: IntoHashMapRef<'g, G::NodeId, T::NodeId>,
         EM: IntoHashMapRef<'g, G::EdgeId, T::EdgeId>,
+        PR: IntoProvenanceRecorder<'g, T::NodeId, T::EdgeId>,
     {
         let mut target = T::default();
         self.copy_into(&mut target);
@@ -277,16 +341,22 @@ where
         ET: FnMut(&G::EdgeData) -> T::EdgeData,
         NM: IntoHashMapRef<'g, G::NodeId, T::NodeId>,
         EM: IntoHashMapRef<'g, G::EdgeId, T::EdgeId>,
+        PR: IntoProvenanceRecorder<'g, T::NodeId, T::EdgeId>,
     {
         let node_map = match self.node_map.into_hash_map_ref() {
             Some(map) => map,
             None => &mut HashMap::new(),
         };
 
+        let mut provenance = self.provenance.into_recorder();
+
         // Copy all the nodes, saving them into a map.
         for node_id in self.source.node_ids() {
             let node_data = (self.node_transformer)(self.source.node_data(&node_id));
             let new_node_id = target.add_node(node_data);
+            if let Some(recorder) = &mut provenance {
+                recorder.record_node(new_node_id.clone(), &node_id);
+            }
             node_map.insert(node_id.clone(), new_node_id);
         }
 
@@ -300,21 +370,166 @@ where
         for edge_id in self.source.edge_ids() {
             let edge_data = (self.edge_transformer)(self.source.edge_data(&edge_id));
             let ends = self.source.edge_ends(&edge_id);
-            let source_node_id = &node_map[ends.left()];
-            let target_node_id = &node_map[ends.right()];
-            let (new_edge_id, old_edge) =
-                target.add_edge(source_node_id, target_node_id, edge_data);
+            let (source_node_id, target_node_id) = if self.reverse_edges {
+                (&node_map[ends.right()], &node_map[ends.left()])
+            } else {
+                (&node_map[ends.left()], &node_map[ends.right()])
+            };
+            let result = target.add_edge(source_node_id, target_node_id, edge_data);
+            let new_edge_id = result.edge_id().clone();
+            if let Some(recorder) = &mut provenance {
+                recorder.record_edge(new_edge_id.clone(), &edge_id);
+            }
 
             // Maintain the edge map if the user provided one.
             if let Some((ref mut edge_map, ref mut reverse_edge_map)) = edge_maps {
+                // Unmap the collapsed edge before mapping its replacement,
+                // which may reuse its ID.
+                if let Some((old_edge_id, _)) = result.updated() {
+                    let collapsed = reverse_edge_map
+                        .remove(&old_edge_id)
+                        .expect("replaced edge should have been copied");
+                    edge_map.remove(&collapsed);
+                }
                 edge_map.insert(edge_id.clone(), new_edge_id.clone());
                 reverse_edge_map.insert(new_edge_id, edge_id);
-                if let Some((old_edge_id, _)) = old_edge {
-                    edge_map.remove(&reverse_edge_map[&old_edge_id]);
-                }
             }
         }
     }
+
+    /// Like [`Self::copy`], but also reports which edges of the source graph
+    /// were collapsed into others because the target graph does not allow
+    /// parallel edges.
+    pub fn copy_with_report<T>(self) -> (T, CopyReport<G::EdgeId>)
+    where
+        T: GraphMut + Default,
+        T::NodeId: 'g,
+        T::EdgeId: 'g,
+        NT: FnMut(&G::NodeData) -> T::NodeData,
+        ET: FnMut(&G::EdgeData) -> T::EdgeData,
+        NM: IntoHashMapRef<'g, G::NodeId, T::NodeId>,
+        EM: IntoHashMapRef<'g, G::EdgeId, T::EdgeId>,
+        PR: IntoProvenanceRecorder<'g, T::NodeId, T::EdgeId>,
+    {
+        let mut target = T::default();
+        let report = self.copy_into_with_report(&mut target);
+        (target, report)
+    }
+
+    /// Like [`Self::copy_into`], but also reports which edges of the source
+    /// graph were collapsed into others because the target graph does not
+    /// allow parallel edges.
+    pub fn copy_into_with_report<T>(self, target: &mut T) -> CopyReport<G::EdgeId>
+    where
+        T: GraphMut,
+        T::NodeId: 'g,
+        T::EdgeId: 'g,
+        NT: FnMut(&G::NodeData) -> T::NodeData,
+        ET: FnMut(&G::EdgeData) -> T::EdgeData,
+        NM: IntoHashMapRef<'g, G::NodeId, T::NodeId>,
+        EM: IntoHashMapRef<'g, G::EdgeId, T::EdgeId>,
+        PR: IntoProvenanceRecorder<'g, T::NodeId, T::EdgeId>,
+    {
+        let report = self.report_for(target);
+        self.copy_into(target);
+        report
+    }
+
+    /// Like [`Self::copy`], but fails instead of collapsing parallel edges
+    /// when the target graph does not allow them.
+    pub fn try_copy<T>(self) -> Result<T, LossyCopyError<G::EdgeId>>
+    where
+        T: GraphMut + Default,
+        T::NodeId: 'g,
+        T::EdgeId: 'g,
+        NT: FnMut(&G::NodeData) -> T::NodeData,
+        ET: FnMut(&G::EdgeData) -> T::EdgeData,
+        NM: IntoHashMapRef<'g, G::NodeId, T::NodeId>,
+        EM: IntoHashMapRef<'g, G::EdgeId, T::EdgeId>,
+        PR: IntoProvenanceRecorder<'g, T::NodeId, T::EdgeId>,
+    {
+        let mut target = T::default();
+        self.try_copy_into(&mut target)?;
+        Ok(target)
+    }
+
+    /// Like [`Self::copy_into`], but fails instead of collapsing parallel
+    /// edges when the target graph does not allow them.  The target graph
+    /// and ID maps are not modified if the copy fails.
+    pub fn try_copy_into<T>(self, target: &mut T) -> Result<(), LossyCopyError<G::EdgeId>>
+    where
+        T: GraphMut,
+        T::NodeId: 'g,
+        T::EdgeId: 'g,
+        NT: FnMut(&G::NodeData) -> T::NodeData,
+        ET: FnMut(&G::EdgeData) -> T::EdgeData,
+        NM: IntoHashMapRef<'g, G::NodeId, T::NodeId>,
+        EM: IntoHashMapRef<'g, G::EdgeId, T::EdgeId>,
+        PR: IntoProvenanceRecorder<'g, T::NodeId, T::EdgeId>,
+    {
+        let report = self.report_for(target);
+        if !report.is_lossless() {
+            return Err(LossyCopyError { report });
+        }
+        self.copy_into(target);
+        Ok(())
+    }
+
+    /// Finds the edges which will be collapsed when copying into `target`.
+    /// Since the source nodes are copied into new nodes, only parallel edges
+    /// of the source graph can collide.
+    fn report_for<T>(&self, target: &T) -> CopyReport<G::EdgeId>
+    where
+        T: Graph,
+    {
+        let mut collapsed = Vec::new();
+        if target.allows_parallel_edges() {
+            return CopyReport { collapsed };
+        }
+        let mut last_edges = HashMap::new();
+        for edge_id in self.source.edge_ids() {
+            let ends = self.source.edge_ends(&edge_id);
+            let (left, right) = ends.into_values();
+            let key = if self.reverse_edges {
+                T::Directedness::make_pair(right, left)
+            } else {
+                T::Directedness::make_pair(left, right)
+            };
+            if let Some(previous) = last_edges.insert(key, edge_id.clone()) {
+                collapsed.push((previous, edge_id));
+            }
+        }
+        CopyReport { collapsed }
+    }
+}
+
+/// A report of the edges lost when copying a graph with parallel edges into
+/// a graph which does not allow them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CopyReport<E> {
+    /// Pairs of source edge IDs `(collapsed, replacement)`, in the order the
+    /// source edges were copied.  The data of the `collapsed` edge was
+    /// replaced by the data of the `replacement` edge, which was copied later
+    /// between the same nodes.  Edge maps provided to the copier do not
+    /// contain collapsed edges.
+    pub collapsed: Vec<(E, E)>,
+}
+
+impl<E> CopyReport<E> {
+    /// Returns true if no edges were collapsed.
+    pub fn is_lossless(&self) -> bool {
+        self.collapsed.is_empty()
+    }
+}
+
+/// The error returned by [`GraphCopier::try_copy`] and
+/// [`GraphCopier::try_copy_into`] when copying would collapse parallel
+/// edges.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("copying would collapse {} parallel edges", report.collapsed.len())]
+pub struct LossyCopyError<E> {
+    /// The edges which would have been collapsed.
+    pub report: CopyReport<E>,
 }
 
 #[doc(hidden)]
@@ -336,3 +551,85 @@ where
         Some(self)
     }
 }
+
+#[doc(hidden)]
+pub trait IntoProvenanceRecorder<'a, N, E> {
+    fn into_recorder(self) -> Option<ProvenanceRecorder<'a, N, E>>;
+}
+
+impl<'a, N, E> IntoProvenanceRecorder<'a, N, E> for () {
+    fn into_recorder(self) -> Option<ProvenanceRecorder<'a, N, E>> {
+        None
+    }
+}
+
+impl<'a, N, E> IntoProvenanceRecorder<'a, N, E> for ProvenanceRecorder<'a, N, E> {
+    fn into_recorder(self) -> Option<ProvenanceRecorder<'a, N, E>> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_report_collapsed_edges() {
+        let mut source = BagGraph::<(), i32, Undirected>::default();
+        let a = source.add_node(());
+        let b = source.add_node(());
+        let e1 = source.add_new_edge(&a, &b, 1);
+        let e2 = source.add_new_edge(&b, &a, 2);
+        let e3 = source.add_new_edge(&a, &a, 3);
+        let e4 = source.add_new_edge(&a, &b, 4);
+
+        let mut edge_map = HashMap::new();
+        let (target, report) = GraphCopier::new(&source)
+            .clone_nodes()
+            .clone_edges()
+            .with_edge_map(&mut edge_map)
+            .copy_with_report::<LinkedGraph<(), i32, Undirected, SingleEdge>>();
+        assert_eq!(
+            report.collapsed,
+            vec![(e1.clone(), e2.clone()), (e2.clone(), e4.clone())]
+        );
+        assert_eq!(target.num_edges(), 2);
+        assert_eq!(*target.edge_data(&edge_map[&e4]), 4);
+        assert!(!edge_map.contains_key(&e1));
+        assert!(edge_map.contains_key(&e3));
+
+        let (_, report) = GraphCopier::new(&source)
+            .clone_nodes()
+            .clone_edges()
+            .copy_with_report::<LinkedGraph<(), i32, Undirected, MultipleEdges>>();
+        assert!(report.is_lossless());
+    }
+
+    #[test]
+    fn test_strict_copy() {
+        let mut source = BagGraph::<(), i32, Directed>::default();
+        let a = source.add_node(());
+        let b = source.add_node(());
+        let e1 = source.add_new_edge(&a, &b, 1);
+        let e2 = source.add_new_edge(&b, &a, 2);
+
+        let copied = GraphCopier::new(&source)
+            .clone_nodes()
+            .clone_edges()
+            .try_copy::<LinkedGraph<(), i32, Directed, SingleEdge>>()
+            .unwrap();
+        assert_eq!(copied.num_edges(), 2);
+
+        let mut target = LinkedGraph::<(), i32, Undirected, SingleEdge>::default();
+        let error = GraphCopier::new(&source)
+            .clone_nodes()
+            .clone_edges()
+            .try_copy_into(&mut target)
+            .unwrap_err();
+        assert_eq!(error.report.collapsed, vec![(e1, e2)]);
+        assert_eq!(error.to_string(), "copying would collapse 1 parallel edges");
+        assert_eq!(target.num_nodes(), 0);
+    }
+}
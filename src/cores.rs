@@ -0,0 +1,139 @@
+//! k-core decomposition.
+//!
+//! The k-core of a graph is the largest subgraph in which every node has
+//! degree at least `k`.  It is found by repeatedly removing nodes of degree
+//! less than `k`, which strips away sparse, loosely attached parts of a graph
+//! such as the fringes of a social network.  The core number of a node is the
+//! largest `k` for which it is in the k-core.
+//!
+//! [`core_numbers`] computes the core number of every node at once by
+//! removing nodes in order of their remaining degree (Batagelj and
+//! Zaversnik), in `O(V + E)` time.
+//!
+//! The degree of a node is its number of distinct neighbors, so edge
+//! direction, parallel edges and self-loops are ignored.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+};
+
+/// Returns the core number of every node.
+pub fn core_numbers<G>(graph: &G) -> HashMap<G::NodeId, usize>
+where
+    G: Graph + ?Sized,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let indices: FastHashMap<_, _> = nodes.iter().enumerate().map(|(i, nid)| (nid, i)).collect();
+    let neighbors: Vec<Vec<usize>> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, nid)| {
+            let set: FastHashSet<_> = graph
+                .successors(nid)
+                .chain(graph.predecessors(nid))
+                .map(|other| indices[&other])
+                .filter(|&j| j != i)
+                .collect();
+            set.into_iter().collect()
+        })
+        .collect();
+
+    // Nodes are kept in buckets by degree.  A node's degree only drops while
+    // it is above the current bucket, so the buckets are visited in order,
+    // and entries left behind by a drop are skipped as stale.
+    let mut degrees: Vec<_> = neighbors.iter().map(Vec::len).collect();
+    let max_degree = degrees.iter().copied().max().unwrap_or(0);
+    let mut buckets = vec![Vec::new(); max_degree + 1];
+    for (i, &degree) in degrees.iter().enumerate() {
+        buckets[degree].push(i);
+    }
+    let mut cores: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut k = 0;
+    while k <= max_degree {
+        let Some(i) = buckets[k].pop() else {
+            k += 1;
+            continue;
+        };
+        if cores[i].is_some() || degrees[i] != k {
+            continue;
+        }
+        cores[i] = Some(k);
+        for &j in &neighbors[i] {
+            if cores[j].is_none() && degrees[j] > k {
+                degrees[j] -= 1;
+                buckets[degrees[j]].push(j);
+            }
+        }
+    }
+
+    nodes
+        .into_iter()
+        .zip(cores)
+        .map(|(nid, core)| (nid, core.expect("every node is removed")))
+        .collect()
+}
+
+/// Returns the nodes of the k-core, in which every node has at least `k`
+/// neighbors within the k-core.
+pub fn k_core<G>(graph: &G, k: usize) -> HashSet<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    core_numbers(graph)
+        .into_iter()
+        .filter_map(|(nid, core)| (core >= k).then_some(nid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_core_numbers() {
+        // A 4-clique, a triangle hanging off it, a pendant node and two
+        // isolated nodes.
+        let mut graph = LinkedGraph::<(), (), Undirected>::default();
+        let n: Vec<_> = (0..9).map(|_| graph.add_node(())).collect();
+        for i in 0..4 {
+            for j in i + 1..4 {
+                graph.add_new_edge(&n[i], &n[j], ());
+            }
+        }
+        graph.add_new_edge(&n[3], &n[4], ());
+        graph.add_new_edge(&n[4], &n[5], ());
+        graph.add_new_edge(&n[5], &n[3], ());
+        graph.add_new_edge(&n[5], &n[6], ());
+        // Parallel edges and self-loops don't count.
+        graph.add_new_edge(&n[6], &n[5], ());
+        graph.add_new_edge(&n[6], &n[6], ());
+
+        let cores = graph.core_numbers();
+        let expected = [3, 3, 3, 3, 2, 2, 1, 0, 0];
+        for (nid, core) in n.iter().zip(expected) {
+            assert_eq!(cores[nid], core);
+        }
+
+        assert_eq!(graph.k_core(2), n[0..6].iter().cloned().collect());
+        assert_eq!(graph.k_core(0).len(), 9);
+        assert!(graph.k_core(4).is_empty());
+    }
+
+    #[test]
+    fn test_directed() {
+        // Direction is ignored, so a directed cycle is a 2-core.
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], ());
+        graph.add_new_edge(&n[1], &n[2], ());
+        graph.add_new_edge(&n[2], &n[0], ());
+        graph.add_new_edge(&n[3], &n[0], ());
+        let core = graph.k_core(2);
+        assert_eq!(core, n[0..3].iter().cloned().collect());
+    }
+}
@@ -0,0 +1,299 @@
+//! Finding and enumerating cycles in directed graphs.
+//!
+//! [`find_cycle`] finds a single cycle with a depth-first search, which is
+//! enough to report why a graph that should be acyclic is not.
+//! [`enumerate_simple_cycles`] enumerates every simple cycle using Johnson's
+//! algorithm, which takes time proportional to the size of the graph for
+//! each cycle found.
+//!
+//! Cycles are returned as [`Path`]s which start and end at the same node.
+//! A self-loop is a cycle of one edge, and each of a set of parallel edges
+//! gives a separate cycle.
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    path::Path,
+    prelude::*,
+};
+
+/// Returns a cycle in the graph, or `None` if the graph is acyclic.
+pub fn find_cycle<G>(graph: &G) -> Option<Path<'_, G>>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    // Nodes on the current search path, with their positions in `path`.
+    let mut on_path: FastHashMap<G::NodeId, usize> = FastHashMap::default();
+    let mut done: FastHashSet<G::NodeId> = FastHashSet::default();
+    for start in graph.node_ids() {
+        if done.contains(&start) {
+            continue;
+        }
+        // Each frame is a node and its unexplored outgoing edges.
+        let mut stack = vec![(start.clone(), graph.edges_from(&start).collect::<Vec<_>>())];
+        let mut path: Vec<G::EdgeId> = Vec::new();
+        on_path.insert(start, 0);
+        while let Some((node, edges)) = stack.last_mut() {
+            let Some(eid) = edges.pop() else {
+                on_path.remove(node);
+                done.insert(node.clone());
+                stack.pop();
+                path.pop();
+                continue;
+            };
+            let (_, next) = graph.edge_ends(&eid).into_values();
+            if let Some(&position) = on_path.get(&next) {
+                path.push(eid);
+                return Some(Path::from_edges(graph, next, path.drain(position..)));
+            }
+            if !done.contains(&next) {
+                on_path.insert(next.clone(), path.len() + 1);
+                path.push(eid);
+                let edges = graph.edges_from(&next).collect();
+                stack.push((next, edges));
+            }
+        }
+    }
+    None
+}
+
+/// Returns true if the graph contains a cycle.
+pub fn has_cycle<G>(graph: &G) -> bool
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    find_cycle(graph).is_some()
+}
+
+/// Returns up to `max_cycles` simple cycles of the graph, found with
+/// Johnson's algorithm.  A cycle is simple if it visits no node more than
+/// once.  Each cycle starts at its node which comes first in the order of
+/// [`Graph::node_ids`], and the cycles are grouped by their starting node in
+/// that order.
+pub fn enumerate_simple_cycles<G>(graph: &G, max_cycles: usize) -> Vec<Path<'_, G>>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let index: FastHashMap<_, _> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, nid)| (nid.clone(), i))
+        .collect();
+    let mut successors = vec![Vec::new(); nodes.len()];
+    let mut predecessors = vec![Vec::new(); nodes.len()];
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        let (from, into) = (index[&from], index[&into]);
+        successors[from].push((eid, into));
+        predecessors[into].push(from);
+    }
+
+    let mut cycles = Vec::new();
+    let mut search = CircuitSearch {
+        successors: &successors,
+        in_component: vec![false; nodes.len()],
+        blocked: vec![false; nodes.len()],
+        blockers: vec![Vec::new(); nodes.len()],
+    };
+    for start in 0..nodes.len() {
+        if cycles.len() >= max_cycles {
+            break;
+        }
+        // Johnson's algorithm searches the strongly connected component of
+        // `start` in the subgraph of nodes from `start` onwards.
+        let forward = reachable(start, |v| successors[v].iter().map(|&(_, w)| w));
+        let backward = reachable(start, |v| predecessors[v].iter().copied());
+        for v in start..nodes.len() {
+            search.in_component[v] = forward.contains(&v) && backward.contains(&v);
+            search.blocked[v] = false;
+            search.blockers[v].clear();
+        }
+        search.search(start, max_cycles - cycles.len(), |edges| {
+            cycles.push(Path::from_edges(graph, nodes[start].clone(), edges));
+        });
+        search.in_component[start] = false;
+    }
+    cycles
+}
+
+/// Returns the nodes with indices at least `start` which can be reached from
+/// `start` through such nodes.
+fn reachable<I>(start: usize, neighbors: impl Fn(usize) -> I) -> FastHashSet<usize>
+where
+    I: Iterator<Item = usize>,
+{
+    let mut seen = FastHashSet::from_iter([start]);
+    let mut stack = vec![start];
+    while let Some(v) = stack.pop() {
+        for w in neighbors(v) {
+            if w >= start && seen.insert(w) {
+                stack.push(w);
+            }
+        }
+    }
+    seen
+}
+
+/// The state of the circuit search of Johnson's algorithm.
+struct CircuitSearch<'a, E> {
+    successors: &'a [Vec<(E, usize)>],
+    in_component: Vec<bool>,
+    blocked: Vec<bool>,
+    /// For each node, the nodes to unblock when it is unblocked.
+    blockers: Vec<Vec<usize>>,
+}
+
+impl<E: Clone> CircuitSearch<'_, E> {
+    /// Reports up to `max_cycles` cycles through `start` to `found`.
+    fn search(&mut self, start: usize, max_cycles: usize, mut found: impl FnMut(Vec<E>)) {
+        let mut count = 0;
+        // Each frame is a node, the position of its next edge to explore,
+        // and whether a cycle has been found through it.
+        let mut stack = vec![(start, 0, false)];
+        let mut path: Vec<E> = Vec::new();
+        self.blocked[start] = true;
+        while let Some(&mut (v, ref mut position, ref mut found_cycle)) = stack.last_mut() {
+            if let Some((eid, w)) = self.successors[v].get(*position) {
+                *position += 1;
+                if !self.in_component[*w] {
+                    continue;
+                }
+                if *w == start {
+                    *found_cycle = true;
+                    let mut cycle = path.clone();
+                    cycle.push(eid.clone());
+                    found(cycle);
+                    count += 1;
+                    if count == max_cycles {
+                        return;
+                    }
+                } else if !self.blocked[*w] {
+                    self.blocked[*w] = true;
+                    path.push(eid.clone());
+                    stack.push((*w, 0, false));
+                }
+                continue;
+            }
+
+            let found_cycle = *found_cycle;
+            stack.pop();
+            if found_cycle {
+                self.unblock(v);
+            } else {
+                for &(_, w) in self.successors[v].iter() {
+                    if self.in_component[w] && !self.blockers[w].contains(&v) {
+                        self.blockers[w].push(v);
+                    }
+                }
+            }
+            if let Some((_, _, parent_found)) = stack.last_mut() {
+                *parent_found |= found_cycle;
+                path.pop();
+            }
+        }
+    }
+
+    fn unblock(&mut self, v: usize) {
+        let mut stack = vec![v];
+        while let Some(v) = stack.pop() {
+            if self.blocked[v] {
+                self.blocked[v] = false;
+                stack.append(&mut self.blockers[v]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_find_cycle() {
+        let mut graph = LinkedGraph::<&str, (), Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&b, &c, ());
+        graph.add_new_edge(&a, &c, ());
+        graph.add_new_edge(&d, &a, ());
+        assert!(!graph.has_cycle());
+        assert_eq!(graph.find_cycle(), None);
+
+        let cb = graph.add_new_edge(&c, &b, ());
+        let cycle = graph.find_cycle().unwrap();
+        assert_eq!(cycle.first_node(), cycle.last_node());
+        assert_eq!(cycle.edges().count(), 2);
+        assert!(cycle.edges().any(|eid| eid == cb));
+        assert!(graph.has_cycle());
+
+        graph.remove_edge(&cb);
+        let dd = graph.add_new_edge(&d, &d, ());
+        let cycle = graph.find_cycle().unwrap();
+        assert_eq!(cycle.edges().collect::<Vec<_>>(), vec![dd]);
+        assert_eq!(cycle.nodes().collect::<Vec<_>>(), vec![d.clone(), d]);
+    }
+
+    #[test]
+    fn test_enumerate_simple_cycles() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        let e01 = graph.add_new_edge(&n[0], &n[1], ());
+        let e12 = graph.add_new_edge(&n[1], &n[2], ());
+        let e20 = graph.add_new_edge(&n[2], &n[0], ());
+        let e21 = graph.add_new_edge(&n[2], &n[1], ());
+        graph.add_new_edge(&n[2], &n[3], ());
+        let e33 = graph.add_new_edge(&n[3], &n[3], ());
+        let e10 = graph.add_new_edge(&n[1], &n[0], ());
+        let e10b = graph.add_new_edge(&n[1], &n[0], ());
+
+        let cycles: HashSet<Vec<_>> = graph
+            .enumerate_simple_cycles(usize::MAX)
+            .iter()
+            .map(|cycle| cycle.edges().collect())
+            .collect();
+        assert_eq!(
+            cycles,
+            HashSet::from([
+                vec![e01.clone(), e12.clone(), e20],
+                vec![e01.clone(), e10],
+                vec![e01, e10b],
+                vec![e12, e21],
+                vec![e33],
+            ])
+        );
+
+        assert_eq!(graph.enumerate_simple_cycles(2).len(), 2);
+        assert!(graph.enumerate_simple_cycles(0).is_empty());
+    }
+
+    #[test]
+    fn test_complete_graph_cycles() {
+        // A complete directed graph on n nodes has sum over k of
+        // C(n, k) * (k - 1)! simple cycles of length at least 2.
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        for a in n.iter() {
+            for b in n.iter() {
+                if a != b {
+                    graph.add_new_edge(a, b, ());
+                }
+            }
+        }
+        let cycles = graph.enumerate_simple_cycles(usize::MAX);
+        assert_eq!(cycles.len(), 10 + 20 + 30 + 24);
+        for cycle in cycles.iter() {
+            let nodes: Vec<_> = cycle.nodes().collect();
+            assert_eq!(nodes.first(), nodes.last());
+            let distinct: HashSet<_> = nodes[1..].iter().collect();
+            assert_eq!(distinct.len(), nodes.len() - 1);
+        }
+    }
+}
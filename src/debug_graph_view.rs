@@ -7,6 +7,9 @@ use crate::{BagGraph, copier::GraphCopier, format_debug::format_debug, prelude::
 /// This type creates a snapshot of a graph with transformed data that can be
 /// used for debug formatting. The transformation is applied once during construction,
 /// and the result is stored in an internal `LinkedGraph`.
+///
+/// Because the view is a snapshot of transformed data, it is read-only:
+/// edits could not be written through to the source graph.
 pub struct DebugGraphView<N, E, D: Directedness> {
     inner: BagGraph<N, E, D>,
 }
@@ -53,10 +56,18 @@ where
         self.inner.node_data(id)
     }
 
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
     fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
         self.inner.edge_ids()
     }
 
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
     fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
         self.inner.edge_data(id)
     }
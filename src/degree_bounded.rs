@@ -0,0 +1,390 @@
+use std::fmt::Debug;
+
+use crate::{
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A wrapper around a graph which limits the number of edges from and into
+/// each node.  This is useful for modeling systems with fan-out limits, such
+/// as devices with a fixed number of ports, without checking the limits at
+/// every call site.
+///
+/// [`GraphMut::try_add_edge`] returns [`GraphError::DegreeLimitExceeded`]
+/// if adding an edge would give its source more than
+/// [`Self::max_out_degree`] edges from it, or its target more than
+/// [`Self::max_in_degree`] edges into it, and [`GraphMut::add_edge`] panics
+/// in the same situation.  Replacing the data of an existing edge in a graph
+/// without parallel edges is always allowed.  In an undirected graph, both
+/// limits apply to both ends of each edge.
+///
+/// Degrees are counted with [`Graph::num_edges_from`] and
+/// [`Graph::num_edges_into`].  The limits are only checked when edges are
+/// added, so lowering a limit does not remove existing edges.
+pub struct DegreeBoundedGraph<G>
+where
+    G: Graph,
+{
+    inner: G,
+    max_out_degree: Option<usize>,
+    max_in_degree: Option<usize>,
+}
+
+impl<G> DegreeBoundedGraph<G>
+where
+    G: Graph,
+{
+    /// Wraps an existing graph, with no limits.
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            max_out_degree: None,
+            max_in_degree: None,
+        }
+    }
+
+    /// Sets the maximum number of edges from each node, returning the
+    /// updated graph.
+    pub fn with_max_out_degree(mut self, max_out_degree: usize) -> Self {
+        self.max_out_degree = Some(max_out_degree);
+        self
+    }
+
+    /// Sets the maximum number of edges into each node, returning the updated
+    /// graph.
+    pub fn with_max_in_degree(mut self, max_in_degree: usize) -> Self {
+        self.max_in_degree = Some(max_in_degree);
+        self
+    }
+
+    /// Sets or removes the maximum number of edges from each node.
+    pub fn set_max_out_degree(&mut self, max_out_degree: Option<usize>) {
+        self.max_out_degree = max_out_degree;
+    }
+
+    /// Sets or removes the maximum number of edges into each node.
+    pub fn set_max_in_degree(&mut self, max_in_degree: Option<usize>) {
+        self.max_in_degree = max_in_degree;
+    }
+
+    /// Gets the maximum number of edges from each node, if any.
+    pub fn max_out_degree(&self) -> Option<usize> {
+        self.max_out_degree
+    }
+
+    /// Gets the maximum number of edges into each node, if any.
+    pub fn max_in_degree(&self) -> Option<usize> {
+        self.max_in_degree
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding the limits.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Checks whether an edge can be added from `from` into `into` without
+    /// exceeding the limits.
+    pub fn check_degrees(&self, from: &G::NodeId, into: &G::NodeId) -> Result<(), GraphError> {
+        if !self.inner.allows_parallel_edges() && self.inner.has_edge_from_into(from, into) {
+            return Ok(());
+        }
+        let within = |limit: Option<usize>, degree: usize| limit.is_none_or(|limit| degree < limit);
+        let ok = if self.inner.is_directed() {
+            within(self.max_out_degree, self.inner.num_edges_from(from))
+                && within(self.max_in_degree, self.inner.num_edges_into(into))
+        } else {
+            [from, into].into_iter().all(|nid| {
+                let degree = self.inner.num_edges_from(nid);
+                within(self.max_out_degree, degree) && within(self.max_in_degree, degree)
+            })
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(GraphError::DegreeLimitExceeded)
+        }
+    }
+}
+
+impl<G> Default for DegreeBoundedGraph<G>
+where
+    G: Graph + Default,
+{
+    fn default() -> Self {
+        Self::new(G::default())
+    }
+}
+
+impl<G> Clone for DegreeBoundedGraph<G>
+where
+    G: Graph + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            max_out_degree: self.max_out_degree,
+            max_in_degree: self.max_in_degree,
+        }
+    }
+}
+
+impl<G> Debug for DegreeBoundedGraph<G>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DegreeBoundedGraph")
+            .field("inner", &self.inner)
+            .field("max_out_degree", &self.max_out_degree)
+            .field("max_in_degree", &self.max_in_degree)
+            .finish()
+    }
+}
+
+impl<G> Graph for DegreeBoundedGraph<G>
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from_into(from, into)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.inner.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.inner.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.inner.num_edges_from(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G> GraphMut for DegreeBoundedGraph<G>
+where
+    G: GraphMut,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        self.inner.node_data_mut(id)
+    }
+
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        self.inner.edge_data_mut(id)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        self.inner.add_node(data)
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        self.inner.remove_node(id)
+    }
+
+    /// Adds an edge as described by [`GraphMut::add_edge`].  Panics if
+    /// adding the edge would exceed a degree limit.
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        if let Err(error) = self.check_degrees(from, into) {
+            panic!("{error}: cannot add edge from {from:?} into {into:?}");
+        }
+        self.inner.add_edge(from, into, data)
+    }
+
+    fn try_add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> Result<AddEdgeResult<Self::EdgeId, Self::EdgeData>, GraphError> {
+        self.check_node_id(from)?;
+        self.check_node_id(into)?;
+        self.check_degrees(from, into)?;
+        Ok(self.inner.add_edge(from, into, data))
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        self.inner.remove_edge(id)
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        self.inner.compact(node_map_collector, edge_map_collector);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+}
+
+impl<G> StableNodeIds for DegreeBoundedGraph<G> where G: StableNodeIds {}
+
+impl<G> StableEdgeIds for DegreeBoundedGraph<G> where G: StableEdgeIds {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_directed_limits() {
+        let mut graph =
+            DegreeBoundedGraph::new(BagGraph::<(), (), Directed>::default()).with_max_out_degree(2);
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&a, &c, ());
+        assert_eq!(
+            graph.try_add_edge(&a, &a, ()),
+            Err(GraphError::DegreeLimitExceeded)
+        );
+        assert_eq!(graph.num_edges(), 2);
+
+        graph.set_max_in_degree(Some(1));
+        assert!(graph.try_add_edge(&b, &a, ()).is_ok());
+        assert_eq!(
+            graph.try_add_edge(&c, &b, ()),
+            Err(GraphError::DegreeLimitExceeded)
+        );
+
+        graph.set_max_out_degree(None);
+        let eid = graph.edges_from_into(&a, &c).next().unwrap();
+        graph.remove_edge(&eid);
+        assert!(graph.try_add_edge(&b, &c, ()).is_ok());
+        assert_eq!(graph.max_out_degree(), None);
+        assert_eq!(graph.max_in_degree(), Some(1));
+    }
+
+    #[test]
+    fn test_undirected_limits() {
+        let mut graph =
+            DegreeBoundedGraph::new(LinkedGraph::<(), i32, Undirected, SingleEdge>::default())
+                .with_max_out_degree(1);
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_edge(&a, &b, 1);
+        assert_eq!(
+            graph.try_add_edge(&c, &b, 2),
+            Err(GraphError::DegreeLimitExceeded)
+        );
+        // Replacing an existing edge does not increase any degree.
+        let replaced = graph.add_edge(&b, &a, 3).updated();
+        assert_eq!(replaced.map(|(_, data)| data), Some(1));
+        assert_eq!(
+            graph
+                .try_add_edge(&c, &c, 4)
+                .map(|result| result.is_added()),
+            Ok(true)
+        );
+        assert_eq!(
+            graph.try_add_edge(&a, &a, 5),
+            Err(GraphError::DegreeLimitExceeded)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Degree limit exceeded")]
+    fn test_add_edge_panics() {
+        let mut graph =
+            DegreeBoundedGraph::new(BagGraph::<(), (), Directed>::default()).with_max_in_degree(0);
+        let a = graph.add_node(());
+        graph.add_new_edge(&a, &a, ());
+    }
+}
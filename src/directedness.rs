@@ -54,3 +54,18 @@ impl Directedness for Undirected {
 
     const IS_DIRECTED: bool = false;
 }
+
+/// Which edges of a node to consider, for
+/// [`Graph::degree`](crate::Graph::degree) and
+/// [`Graph::neighbors`](crate::Graph::neighbors).  In an undirected graph,
+/// every edge of a node is both incoming and outgoing, so all three
+/// directions are equivalent.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Direction {
+    /// Incoming edges.
+    In,
+    /// Outgoing edges.
+    Out,
+    /// Incoming and outgoing edges.
+    Both,
+}
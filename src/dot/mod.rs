@@ -1,4 +1,6 @@
 pub mod attr;
+pub mod overlay;
 pub mod parser;
 pub mod renderer;
+pub mod stable_names;
 pub mod types;
@@ -0,0 +1,286 @@
+//! Renderers which highlight part of a graph on top of another renderer.
+
+use std::collections::HashSet;
+
+use crate::{
+    dot::{attr::Attr, renderer::DotRenderer, types::Color},
+    prelude::*,
+};
+
+/// A [`DotRenderer`] which adds attributes to a set of highlighted edges, and
+/// otherwise renders the graph as `inner` does.  This can be used to show
+/// a path or tree, such as a
+/// [`ShortestPathTree`](crate::shortest_path_tree::ShortestPathTree), in the
+/// context of the whole graph.
+pub struct HighlightEdges<'r, R, E> {
+    inner: &'r R,
+    edges: HashSet<E>,
+    attrs: Vec<Attr>,
+}
+
+impl<'r, R, E> HighlightEdges<'r, R, E>
+where
+    E: GraphElementId,
+{
+    /// Creates a renderer which highlights `edges` in red, drawn with a
+    /// thicker line.
+    pub fn new(inner: &'r R, edges: impl IntoIterator<Item = E>) -> Self {
+        Self {
+            inner,
+            edges: edges.into_iter().collect(),
+            attrs: vec![
+                Attr::Color(vec![Color::Named("red".to_string())]),
+                Attr::Penwidth(2.0),
+            ],
+        }
+    }
+
+    /// Sets the attributes added to highlighted edges, returning the updated
+    /// renderer.
+    pub fn with_attrs(mut self, attrs: Vec<Attr>) -> Self {
+        self.attrs = attrs;
+        self
+    }
+
+    /// Returns true if an edge is highlighted.
+    pub fn is_highlighted(&self, edge_id: &E) -> bool {
+        self.edges.contains(edge_id)
+    }
+}
+
+impl<G, R> DotRenderer<G> for HighlightEdges<'_, R, G::EdgeId>
+where
+    G: Graph,
+    R: DotRenderer<G>,
+{
+    type Error = R::Error;
+
+    fn graph_name(&self) -> Result<String, Self::Error> {
+        self.inner.graph_name()
+    }
+
+    fn node_name(&self, node_id: &G::NodeId, index: usize) -> Result<String, Self::Error> {
+        self.inner.node_name(node_id, index)
+    }
+
+    fn node_attrs(&self, node_id: &G::NodeId, name: &mut String) -> Result<Vec<Attr>, Self::Error> {
+        self.inner.node_attrs(node_id, name)
+    }
+
+    /// Returns the attributes of `inner`, followed by the highlight
+    /// attributes if the edge is highlighted.  Graphviz uses the last value
+    /// of a repeated attribute, so the highlight takes precedence.
+    fn edge_attrs(&self, edge_id: &G::EdgeId) -> Result<Vec<Attr>, Self::Error> {
+        let mut attrs = self.inner.edge_attrs(edge_id)?;
+        if self.is_highlighted(edge_id) {
+            attrs.extend(self.attrs.iter().cloned());
+        }
+        Ok(attrs)
+    }
+
+    fn node_cluster(&self, node_id: &G::NodeId) -> Result<Option<Vec<String>>, Self::Error> {
+        self.inner.node_cluster(node_id)
+    }
+
+    fn cluster_attrs(&self, path: &[String]) -> Result<Vec<Attr>, Self::Error> {
+        self.inner.cluster_attrs(path)
+    }
+
+    fn is_strict(&self, graph: &G) -> bool {
+        self.inner.is_strict(graph)
+    }
+}
+
+/// A [`DotRenderer`] which adds attributes computed from a numeric weight
+/// of each edge's data, and otherwise renders the graph as `inner` does.
+/// Each edge gets a `weight` attribute with its weight, which Graphviz
+/// layouts use to keep heavier edges shorter and straighter, and a
+/// `penwidth` attribute scaled linearly from the lightest edge to the
+/// heaviest, so heavier edges are drawn with thicker lines.
+///
+/// The `dot` layout requires weights to be non-negative integers, so the
+/// weights should be rounded for it, or the `weight` attribute omitted with
+/// [`Self::without_weight`].
+pub struct WeightedEdges<'g, 'r, G, R, F>
+where
+    G: Graph + ?Sized,
+{
+    graph: &'g G,
+    inner: &'r R,
+    weight_fn: F,
+    /// The lowest and highest weights of the edges, or `None` if the graph
+    /// has no edges.
+    weight_range: Option<(f64, f64)>,
+    penwidth_range: (f64, f64),
+    emit_weight: bool,
+    emit_label: bool,
+}
+
+impl<'g, 'r, G, R, F> WeightedEdges<'g, 'r, G, R, F>
+where
+    G: Graph + ?Sized,
+    F: Fn(&G::EdgeData) -> f64,
+{
+    /// Creates a renderer for `graph` which weights each edge with
+    /// `weight_fn` of its data.  Pen widths range from 1 to 5 points.  Takes
+    /// time proportional to the number of edges, to find the range of the
+    /// weights.
+    pub fn new(graph: &'g G, inner: &'r R, weight_fn: F) -> Self {
+        let weight_range = graph
+            .edge_ids()
+            .map(|eid| weight_fn(graph.edge_data(&eid)))
+            .fold(None, |range: Option<(f64, f64)>, weight| {
+                Some(range.map_or((weight, weight), |(low, high)| {
+                    (low.min(weight), high.max(weight))
+                }))
+            });
+        Self {
+            graph,
+            inner,
+            weight_fn,
+            weight_range,
+            penwidth_range: (1.0, 5.0),
+            emit_weight: true,
+            emit_label: false,
+        }
+    }
+
+    /// Sets the pen widths of the lightest and heaviest edges, returning the
+    /// updated renderer.
+    pub fn with_penwidth_range(mut self, min: f64, max: f64) -> Self {
+        self.penwidth_range = (min, max);
+        self
+    }
+
+    /// Also labels each edge with its weight, returning the updated
+    /// renderer.
+    pub fn with_labels(mut self) -> Self {
+        self.emit_label = true;
+        self
+    }
+
+    /// Omits the `weight` attribute, so the weights only affect the pen
+    /// widths, returning the updated renderer.
+    pub fn without_weight(mut self) -> Self {
+        self.emit_weight = false;
+        self
+    }
+
+    /// Returns the pen width for an edge with the given weight.
+    fn penwidth(&self, weight: f64) -> f64 {
+        let (min, max) = self.penwidth_range;
+        match self.weight_range {
+            Some((low, high)) if high > low => min + (max - min) * (weight - low) / (high - low),
+            _ => min,
+        }
+    }
+}
+
+impl<G, R, F> DotRenderer<G> for WeightedEdges<'_, '_, G, R, F>
+where
+    G: Graph,
+    R: DotRenderer<G>,
+    F: Fn(&G::EdgeData) -> f64,
+{
+    type Error = R::Error;
+
+    fn graph_name(&self) -> Result<String, Self::Error> {
+        self.inner.graph_name()
+    }
+
+    fn node_name(&self, node_id: &G::NodeId, index: usize) -> Result<String, Self::Error> {
+        self.inner.node_name(node_id, index)
+    }
+
+    fn node_attrs(&self, node_id: &G::NodeId, name: &mut String) -> Result<Vec<Attr>, Self::Error> {
+        self.inner.node_attrs(node_id, name)
+    }
+
+    /// Returns the attributes of `inner`, followed by the attributes for the
+    /// edge's weight, which take precedence over any `inner` sets.
+    fn edge_attrs(&self, edge_id: &G::EdgeId) -> Result<Vec<Attr>, Self::Error> {
+        let mut attrs = self.inner.edge_attrs(edge_id)?;
+        let weight = (self.weight_fn)(self.graph.edge_data(edge_id));
+        if self.emit_weight {
+            attrs.push(Attr::WeightDouble(weight));
+        }
+        attrs.push(Attr::Penwidth(self.penwidth(weight)));
+        if self.emit_label {
+            attrs.push(Attr::Label(weight.to_string()));
+        }
+        Ok(attrs)
+    }
+
+    fn node_cluster(&self, node_id: &G::NodeId) -> Result<Option<Vec<String>>, Self::Error> {
+        self.inner.node_cluster(node_id)
+    }
+
+    fn cluster_attrs(&self, path: &[String]) -> Result<Vec<Attr>, Self::Error> {
+        self.inner.cluster_attrs(path)
+    }
+
+    fn is_strict(&self, graph: &G) -> bool {
+        self.inner.is_strict(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::BagGraph;
+
+    use super::*;
+
+    struct PlainRenderer;
+
+    impl<G: Graph> DotRenderer<G> for PlainRenderer {
+        type Error = Infallible;
+    }
+
+    #[test]
+    fn test_highlight_edges() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let ab = graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&b, &a, ());
+
+        let renderer = HighlightEdges::new(&PlainRenderer, [ab]);
+        assert_eq!(
+            graph.to_dot_string(&renderer).unwrap(),
+            "digraph G {\n    n0;\n    n1;\n\n    n0 -> n1 [color = red, penwidth = 2];\n    n1 -> n0;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_weighted_edges() {
+        let mut graph = BagGraph::<(), f64, Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, 1.0);
+        graph.add_new_edge(&b, &c, 3.0);
+        graph.add_new_edge(&c, &a, 2.0);
+
+        let renderer = WeightedEdges::new(&graph, &PlainRenderer, |weight: &f64| *weight);
+        assert_eq!(
+            graph.to_dot_string(&renderer).unwrap(),
+            "digraph G {\n    n0;\n    n1;\n    n2;\n\n    \
+             n0 -> n1 [weight = 1, penwidth = 1];\n    \
+             n1 -> n2 [weight = 3, penwidth = 5];\n    \
+             n2 -> n0 [weight = 2, penwidth = 3];\n}\n"
+        );
+
+        let renderer = WeightedEdges::new(&graph, &PlainRenderer, |weight: &f64| *weight * 0.5)
+            .with_penwidth_range(2.0, 4.0)
+            .with_labels()
+            .without_weight();
+        assert!(
+            graph
+                .to_dot_string(&renderer)
+                .unwrap()
+                .contains("n2 -> n0 [penwidth = 3, label = 1];")
+        );
+    }
+}
@@ -4,7 +4,8 @@ use std::{
 };
 
 use dot_parser::ast::{
-    EdgeStmt, Graph as DotGraph, ID, NodeID, NodeStmt, Stmt, StmtList, Subgraph, either::Either,
+    AttrStmt, CompassPt, EdgeStmt, Graph as DotGraph, ID, NodeID, NodeStmt, Port, Stmt, StmtList,
+    Subgraph, either::Either,
 };
 
 use crate::{dot::attr::Attr, prelude::*};
@@ -42,6 +43,183 @@ fn extract_node_ids(either: &Either<NodeID, Subgraph<(ID<'_>, ID<'_>)>>) -> Vec<
     }
 }
 
+/// Returns the port of a single node in an edge statement, in the form used
+/// by the `tailport` and `headport` attributes.
+fn node_port(either: &Either<NodeID, Subgraph<(ID<'_>, ID<'_>)>>) -> Option<String> {
+    let compass = |compass: &CompassPt| match compass {
+        CompassPt::N => "n",
+        CompassPt::NE => "ne",
+        CompassPt::E => "e",
+        CompassPt::SE => "se",
+        CompassPt::S => "s",
+        CompassPt::SW => "sw",
+        CompassPt::W => "w",
+        CompassPt::NW => "nw",
+        CompassPt::C => "c",
+        CompassPt::Underscore => "_",
+    };
+    match either {
+        Either::Left(NodeID {
+            port: Some(port), ..
+        }) => Some(match port {
+            Port::ID(name, Some(pt)) => format!("{name}:{}", compass(pt)),
+            Port::ID(name, None) => name.clone(),
+            Port::Compass(pt) => compass(pt).to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// Replaces the `tailport` and `headport` attributes of an edge with the
+/// ports given in the edge statement, if any.
+fn set_port_attrs(attrs: &mut Vec<Attr>, tail_port: Option<&str>, head_port: Option<&str>) {
+    if let Some(port) = tail_port {
+        attrs.retain(|attr| !matches!(attr, Attr::Tailport(_)));
+        attrs.push(Attr::Tailport(port.to_string()));
+    }
+    if let Some(port) = head_port {
+        attrs.retain(|attr| !matches!(attr, Attr::Headport(_)));
+        attrs.push(Attr::Headport(port.to_string()));
+    }
+}
+
+/// Rewrites the string literals in DOT source into forms the underlying
+/// grammar accepts, without changing their meaning:
+///
+/// - Quoted strings joined with `+` are merged into one quoted string.
+/// - A quoted string ending in an escaped backslash gets a trailing line
+///   continuation, which [`unescape_dot_string`] removes again, since the
+///   grammar would otherwise read `\"` as an escaped quote.
+/// - HTML-like strings containing nested `<...>` tags, which the grammar
+///   does not allow, are rewritten as quoted strings.  Like HTML strings
+///   without nested tags, their value keeps the outer angle brackets.
+///   HTML-like strings containing backslashes are rewritten the same way,
+///   so that unescaping the attribute value gives back the backslashes,
+///   which have no special meaning in HTML.
+///
+/// Comments and preprocessor lines are copied unchanged.
+fn normalize_dot_source(data: &str) -> String {
+    let mut output = String::with_capacity(data.len());
+    let mut rest = data;
+    let mut at_line_start = true;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("//") || (at_line_start && c == '#') {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if rest.starts_with("/*") {
+            let end = rest[2..].find("*/").map_or(rest.len(), |i| i + 4);
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if c == '"' {
+            let mut content = String::new();
+            loop {
+                let (literal, after) = split_quoted(rest);
+                content.push_str(literal);
+                rest = after;
+                let next = rest.trim_start();
+                match next.strip_prefix('+').map(str::trim_start) {
+                    Some(joined) if joined.starts_with('"') => rest = joined,
+                    _ => break,
+                }
+            }
+            output.push('"');
+            output.push_str(&content);
+            if content.ends_with('\\') {
+                output.push_str("\\\n");
+            }
+            output.push('"');
+        } else if c == '<' {
+            let Some((literal, after)) = split_html(rest) else {
+                // An unterminated string is left for the grammar to reject.
+                output.push_str(rest);
+                break;
+            };
+            rest = after;
+            if literal[1..literal.len() - 1].contains(['<', '>', '\\']) {
+                output.push('"');
+                output.push_str(&literal.replace('\\', "\\\\").replace('"', "\\\""));
+                output.push('"');
+            } else {
+                output.push_str(literal);
+            }
+        } else {
+            output.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        at_line_start = c == '\n';
+    }
+    output
+}
+
+/// Splits a quoted string from the start of `s`, returning its contents,
+/// still escaped, and the text after the closing quote.  An unterminated
+/// string runs to the end of `s`.
+fn split_quoted(s: &str) -> (&str, &str) {
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return (&s[1..i], &s[i + 1..]),
+            _ => {}
+        }
+    }
+    (&s[1..], "")
+}
+
+/// Splits an HTML-like string from the start of `s`, returning it with its
+/// outer angle brackets and the text after it, or `None` if the string is
+/// unterminated.
+fn split_html(s: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(s.split_at(i + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Removes the escapes which the DOT language itself defines from the
+/// contents of a quoted string: `\"` becomes `"`, `\\` becomes `\`, and a
+/// backslash before a line break joins the lines.  Other escapes, such as
+/// Graphviz's `\n` and `\l` in labels, are left for the attribute to
+/// interpret.
+fn unescape_dot_string(s: &str) -> String {
+    let mut output = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some(&quote @ ('"' | '\\')) => {
+                output.push(quote);
+                chars.next();
+            }
+            Some('\n') => {
+                chars.next();
+            }
+            Some('\r') => {
+                chars.next();
+                chars.next_if_eq(&'\n');
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
 /// Errors that can occur during DOT format parsing.
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
@@ -69,6 +247,63 @@ pub enum ParseError<B: GraphBuilder> {
     Builder(#[source] B::Error),
 }
 
+/// A subgraph statement (`subgraph name { ... }`) enclosing a node, as
+/// passed to [`GraphBuilder::set_node_subgraphs`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DotSubgraph {
+    /// The name of the subgraph, if it has one.
+    pub id: Option<String>,
+    /// The attributes set on the subgraph itself, either as `name = value`
+    /// statements or in `graph [...]` statements.
+    pub attrs: Vec<Attr>,
+}
+
+impl DotSubgraph {
+    /// Returns true if the subgraph is a cluster, i.e. its name starts with
+    /// `cluster`.
+    pub fn is_cluster(&self) -> bool {
+        self.id.as_ref().is_some_and(|id| id.starts_with("cluster"))
+    }
+
+    /// Returns the value of the subgraph's `label` attribute, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.attrs.iter().find_map(|attr| match attr {
+            Attr::Label(label) => Some(label.as_str()),
+            _ => None,
+        })
+    }
+
+    fn from_ast(subgraph: &Subgraph<(ID<'_>, ID<'_>)>) -> Result<Self, String> {
+        let mut attrs = Vec::new();
+        for stmt in &subgraph.stmts {
+            match stmt {
+                Stmt::IDEq(name, value) => {
+                    // Unlike attribute lists, `name = value` statements keep
+                    // the quotes around quoted values.
+                    let value = value
+                        .strip_prefix('"')
+                        .and_then(|v| v.strip_suffix('"'))
+                        .map(unescape_dot_string)
+                        .unwrap_or_else(|| value.clone());
+                    attrs.push(
+                        Attr::parse(name, &value).map_err(|e| {
+                            format!("Failed to parse attribute '{}': {:?}", name, e)
+                        })?,
+                    )
+                }
+                Stmt::AttrStmt(AttrStmt::Graph(attr_list)) => {
+                    attrs.extend(parse_attrs(std::slice::from_ref(attr_list))?)
+                }
+                _ => {}
+            }
+        }
+        Ok(Self {
+            id: subgraph.id.clone(),
+            attrs,
+        })
+    }
+}
+
 /// Trait for building graph data from DOT format statements.
 ///
 /// Implementors of this trait provide the logic for converting DOT format
@@ -104,6 +339,21 @@ pub trait GraphBuilder {
         let _ = node_id;
         unimplemented!("make_implicit_node_data must be implemented to handle implicit nodes")
     }
+
+    /// Called after a node is created by a statement inside one or more
+    /// subgraph statements, with the enclosing subgraphs, outermost first.
+    /// A node belongs to the subgraphs of the statement which declares it,
+    /// or for an implicit node, of the first edge statement which refers to
+    /// it.  Does nothing by default.
+    fn set_node_subgraphs(
+        &mut self,
+        graph: &mut Self::Graph,
+        node_id: &<Self::Graph as Graph>::NodeId,
+        subgraphs: &[DotSubgraph],
+    ) -> Result<(), Self::Error> {
+        let _ = (graph, node_id, subgraphs);
+        Ok(())
+    }
 }
 
 /// Parse DOT attribute lists into a Vec<Attr>.
@@ -117,6 +367,7 @@ fn parse_attrs(
             for (name, value) in &alist.elems {
                 let name_str: String = name.clone().into();
                 let value_str: String = value.clone().into();
+                let value_str = unescape_dot_string(&value_str);
                 let attr = Attr::parse(&name_str, &value_str)
                     .map_err(|e| format!("Failed to parse attribute '{}': {:?}", name_str, e))?;
                 attrs.push(attr);
@@ -164,7 +415,8 @@ where
     G: GraphMut,
     B: GraphBuilder<Graph = G>,
 {
-    let dot_ast: DotGraph<_> = DotGraph::try_from(data)
+    let data = normalize_dot_source(data);
+    let dot_ast: DotGraph<_> = DotGraph::try_from(data.as_str())
         .map_err(|e| ParseError::ParseError(format!("Failed to parse DOT data: {:?}", e)))?;
 
     if dot_ast.is_digraph != G::Directedness::IS_DIRECTED {
@@ -181,6 +433,7 @@ where
         graph: &mut G,
         node_map: &mut HashMap<String, G::NodeId>,
         builder: &mut B,
+        subgraphs: &mut Vec<DotSubgraph>,
     ) -> Result<(), ParseError<B>>
     where
         G: Graph + GraphMut,
@@ -201,13 +454,21 @@ where
                                 .make_node_data(entry.key(), &attrs)
                                 .map_err(ParseError::Builder)?;
                             let new_node_id = graph.add_node(node_data);
+                            if !subgraphs.is_empty() {
+                                builder
+                                    .set_node_subgraphs(graph, &new_node_id, subgraphs)
+                                    .map_err(ParseError::Builder)?;
+                            }
                             entry.insert(new_node_id);
                         }
                     }
                 }
                 Stmt::Subgraph(subgraph) => {
                     // Recursively process subgraph statements
-                    process_stmts_for_nodes(&subgraph.stmts, graph, node_map, builder)?;
+                    subgraphs
+                        .push(DotSubgraph::from_ast(subgraph).map_err(ParseError::ParseError)?);
+                    process_stmts_for_nodes(&subgraph.stmts, graph, node_map, builder, subgraphs)?;
+                    subgraphs.pop();
                 }
                 _ => {}
             }
@@ -215,7 +476,13 @@ where
         Ok(())
     }
 
-    process_stmts_for_nodes(&dot_ast.stmts, &mut graph, &mut node_map, builder)?;
+    process_stmts_for_nodes(
+        &dot_ast.stmts,
+        &mut graph,
+        &mut node_map,
+        builder,
+        &mut Vec::new(),
+    )?;
 
     // Second pass: collect all node IDs referenced in edges and create implicit nodes
     fn process_stmts_for_implicit_nodes<G, B>(
@@ -223,6 +490,7 @@ where
         graph: &mut G,
         node_map: &mut HashMap<String, G::NodeId>,
         builder: &mut B,
+        subgraphs: &mut Vec<DotSubgraph>,
     ) -> Result<(), ParseError<B>>
     where
         G: Graph + GraphMut,
@@ -243,6 +511,11 @@ where
                                             .make_implicit_node_data(entry.key())
                                             .map_err(ParseError::Builder)?;
                                         let new_node_id = graph.add_node(node_data);
+                                        if !subgraphs.is_empty() {
+                                            builder
+                                                .set_node_subgraphs(graph, &new_node_id, subgraphs)
+                                                .map_err(ParseError::Builder)?;
+                                        }
                                         entry.insert(new_node_id);
                                     }
                                 }
@@ -262,7 +535,16 @@ where
                 }
                 Stmt::Subgraph(subgraph) => {
                     // Recursively process subgraph statements
-                    process_stmts_for_implicit_nodes(&subgraph.stmts, graph, node_map, builder)?;
+                    subgraphs
+                        .push(DotSubgraph::from_ast(subgraph).map_err(ParseError::ParseError)?);
+                    process_stmts_for_implicit_nodes(
+                        &subgraph.stmts,
+                        graph,
+                        node_map,
+                        builder,
+                        subgraphs,
+                    )?;
+                    subgraphs.pop();
                 }
                 _ => {}
             }
@@ -270,7 +552,13 @@ where
         Ok(())
     }
 
-    process_stmts_for_implicit_nodes(&dot_ast.stmts, &mut graph, &mut node_map, builder)?;
+    process_stmts_for_implicit_nodes(
+        &dot_ast.stmts,
+        &mut graph,
+        &mut node_map,
+        builder,
+        &mut Vec::new(),
+    )?;
 
     // Third pass: create edges (all nodes now exist)
     fn process_stmts_for_edges<G, B>(
@@ -300,6 +588,7 @@ where
                     // Process edge chain: from -> next.to -> next.next.to -> ...
                     for (from_id_string, from_id) in from_node_ids {
                         let mut current_from = from_id.clone();
+                        let mut current_from_port = node_port(&edge_stmt.from);
                         let mut current_rhs = Some(&edge_stmt.next);
 
                         while let Some(rhs) = current_rhs {
@@ -312,16 +601,20 @@ where
                                         .map(|node_id| (id_str, node_id))
                                 })
                                 .collect();
+                            let to_port = node_port(&rhs.to);
 
                             for (to_id_string, to_id) in to_node_ids.iter() {
-                                let attrs =
+                                let mut attrs =
                                     parse_edge_attrs(edge_stmt).map_err(ParseError::ParseError)?;
+                                set_port_attrs(
+                                    &mut attrs,
+                                    current_from_port.as_deref(),
+                                    to_port.as_deref(),
+                                );
                                 let edge_data = builder
                                     .make_edge_data(&attrs)
                                     .map_err(ParseError::Builder)?;
-                                if let (_, Some(_)) =
-                                    graph.add_edge(&current_from, to_id, edge_data)
-                                {
+                                if graph.add_edge(&current_from, to_id, edge_data).is_updated() {
                                     return Err(ParseError::DuplicateEdge(
                                         from_id_string,
                                         to_id_string.clone(),
@@ -334,6 +627,7 @@ where
                             if let Some((_, first_to)) = to_node_ids.first() {
                                 current_from = first_to.clone();
                             }
+                            current_from_port = to_port;
 
                             current_rhs = rhs.next.as_deref();
                         }
@@ -842,6 +1136,17 @@ mod tests {
         assert_eq!(graph.num_edges(), 8);
     }
 
+    #[test]
+    fn test_parse_error_unterminated_html_string() {
+        let dot = "digraph { a [label=<";
+
+        let mut builder = SimpleBuilder(Directed);
+        let result: Result<BagGraph<String, (), Directed>, _> =
+            parse_dot_into_graph(dot, &mut builder);
+
+        assert!(matches!(result, Err(ParseError::ParseError(_))));
+    }
+
     #[test]
     fn test_parse_error_malformed_syntax() {
         let invalid_inputs = vec![
@@ -1041,4 +1346,217 @@ mod tests {
             Err(e) => panic!("Unexpected error: {:?}", e),
         }
     }
+
+    /// Records the labels of the clusters containing each node in its data,
+    /// e.g. "n0 in core/io".
+    #[derive(Debug)]
+    struct ClusterBuilder;
+
+    impl GraphBuilder for ClusterBuilder {
+        type Graph = BagGraph<String, (), Directed>;
+        type Error = std::convert::Infallible;
+
+        fn make_node_data(&mut self, id: &str, _attrs: &[Attr]) -> Result<String, Self::Error> {
+            Ok(id.to_string())
+        }
+
+        fn make_implicit_node_data(&mut self, node_id: &str) -> Result<String, Self::Error> {
+            Ok(node_id.to_string())
+        }
+
+        fn make_edge_data(&mut self, _attrs: &[Attr]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_node_subgraphs(
+            &mut self,
+            graph: &mut Self::Graph,
+            node_id: &<Self::Graph as Graph>::NodeId,
+            subgraphs: &[DotSubgraph],
+        ) -> Result<(), Self::Error> {
+            let path: Vec<_> = subgraphs
+                .iter()
+                .filter(|subgraph| subgraph.is_cluster())
+                .map(|subgraph| subgraph.label().unwrap_or("?"))
+                .collect();
+            if !path.is_empty() {
+                let data = graph.node_data_mut(node_id);
+                *data = format!("{} in {}", data, path.join("/"));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_clusters() {
+        // The output of the renderer's nested cluster test, plus an implicit
+        // node inside a cluster and a subgraph which is not a cluster.
+        let dot = r#"
+            digraph G {
+                n1;
+                subgraph cluster_0 {
+                    label = core;
+                    n2;
+                    subgraph cluster_1 {
+                        graph [label="io"];
+                        n0;
+                        n3;
+                        n3 -> n4;
+                    }
+                }
+                subgraph same_rank {
+                    n5;
+                }
+                n1 -> n0;
+                n0 -> n2;
+            }
+        "#;
+
+        let graph: BagGraph<String, (), Directed> =
+            parse_dot_into_graph(dot, &mut ClusterBuilder).unwrap();
+        let mut nodes: Vec<_> = graph
+            .node_ids()
+            .map(|id| graph.node_data(&id).clone())
+            .collect();
+        nodes.sort();
+        assert_eq!(
+            nodes,
+            vec![
+                "n0 in core/io",
+                "n1",
+                "n2 in core",
+                "n3 in core/io",
+                "n4 in core/io",
+                "n5",
+            ]
+        );
+        assert_eq!(graph.num_edges(), 3);
+    }
+
+    #[test]
+    fn test_dot_subgraph_from_ast() {
+        let dot = r#"digraph G { subgraph cluster_x { color = red; label = "X"; a; } }"#;
+        let ast: DotGraph<_> = DotGraph::try_from(dot).unwrap();
+        let Some(Stmt::Subgraph(subgraph)) = ast.stmts.into_iter().next() else {
+            panic!("expected a subgraph");
+        };
+        let subgraph = DotSubgraph::from_ast(&subgraph).unwrap();
+        assert_eq!(subgraph.id.as_deref(), Some("cluster_x"));
+        assert!(subgraph.is_cluster());
+        assert_eq!(subgraph.label(), Some("X"));
+        assert_eq!(subgraph.attrs.len(), 2);
+    }
+
+    /// A builder which keeps the labels of nodes.
+    #[derive(Debug)]
+    struct LabelBuilder;
+
+    impl GraphBuilder for LabelBuilder {
+        type Graph = BagGraph<String, (), Directed>;
+        type Error = std::convert::Infallible;
+
+        fn make_node_data(&mut self, id: &str, attrs: &[Attr]) -> Result<String, Self::Error> {
+            Ok(attrs
+                .iter()
+                .find_map(|attr| match attr {
+                    Attr::Label(label) => Some(label.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| id.to_string()))
+        }
+
+        fn make_edge_data(&mut self, _attrs: &[Attr]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn labels(dot: &str) -> Vec<String> {
+        let graph = parse_dot_into_graph(dot, &mut LabelBuilder).unwrap();
+        let mut labels: Vec<_> = graph
+            .node_ids()
+            .map(|id| graph.node_data(&id).clone())
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    #[test]
+    fn test_parse_html_labels() {
+        let dot = r#"
+            digraph G {
+                // A "comment" with <brackets>
+                a [label=<<table border="0"><tr><td>A &amp; B</td></tr></table>>];
+                b [label=<<b>bold</b>>, shape=none];
+                c [label=<plain>];
+            }
+        "#;
+        assert_eq!(
+            labels(dot),
+            vec![
+                "<<b>bold</b>>",
+                r#"<<table border="0"><tr><td>A &amp; B</td></tr></table>>"#,
+                "<plain>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_html_label_backslashes_round_trip() {
+        use crate::dot::renderer::{DotRenderer, generate_dot_file};
+
+        type LabelGraph = BagGraph<String, (), Directed>;
+
+        /// Renders the data of each node as its label.
+        struct LabelRenderer<'g>(&'g LabelGraph);
+
+        impl DotRenderer<LabelGraph> for LabelRenderer<'_> {
+            type Error = std::convert::Infallible;
+
+            fn node_attrs(
+                &self,
+                node_id: &<LabelGraph as Graph>::NodeId,
+                _name: &mut String,
+            ) -> Result<Vec<Attr>, Self::Error> {
+                Ok(vec![Attr::Label(self.0.node_data(node_id).clone())])
+            }
+        }
+
+        let dot = r#"
+            digraph G {
+                a [label=<C:\\temp\n>];
+                b [label=<<b>x\\y</b>>];
+            }
+        "#;
+        let expected = vec![r"<<b>x\\y</b>>", r"<C:\\temp\n>"];
+        assert_eq!(labels(dot), expected);
+
+        let graph = parse_dot_into_graph(dot, &mut LabelBuilder).unwrap();
+        let mut output = Vec::new();
+        generate_dot_file(&graph, &LabelRenderer(&graph), &mut output).unwrap();
+        assert_eq!(labels(&String::from_utf8(output).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_parse_quoted_string_escapes() {
+        let dot = "digraph G {\n\
+            a [label=\"say \\\"hi\\\"\\lnext\\l\"];\n\
+            b [label=\"back\\\\\"];\n\
+            c [label=\"one \\\n\
+            line\"];\n\
+            d [label=\"two\n\
+            lines\"];\n\
+            e [label=\"con\" + \"cat\"\n\
+                + \"enated\"];\n\
+        }";
+        assert_eq!(
+            labels(dot),
+            vec![
+                "back\\",
+                "concatenated",
+                "one line",
+                "say \"hi\"\\lnext\\l",
+                "two\nlines",
+            ]
+        );
+    }
 }
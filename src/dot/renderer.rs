@@ -1,6 +1,6 @@
 use std::{collections::HashMap, error::Error, io};
 
-use crate::{dot::attr::Attr, prelude::*};
+use crate::{dot::attr::Attr, end_pair::EndPair as _, prelude::*};
 
 /// Validates if a string is a valid DOT identifier.
 /// Returns true if the identifier is valid.
@@ -25,6 +25,44 @@ fn is_valid_dot_id(s: &str) -> bool {
     s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
+/// Escapes a string for use in DOT format (only called when quoting is
+/// needed).
+fn escape_dot_string(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Formats a value for DOT output, adding quotes only if needed.
+fn format_dot_value(s: &str) -> String {
+    if !is_valid_dot_id(s) {
+        format!("\"{}\"", escape_dot_string(s))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Writes an attribute list, or nothing if there are no attributes.
+fn write_attr_list(output: &mut impl io::Write, attrs: &[Attr]) -> io::Result<()> {
+    if !attrs.is_empty() {
+        write!(output, " [")?;
+        for (i, attr) in attrs.iter().enumerate() {
+            if i > 0 {
+                write!(output, ", ")?;
+            }
+            write!(
+                output,
+                "{} = {}",
+                attr.name(),
+                format_dot_value(&attr.value())
+            )?;
+        }
+        write!(output, "]")?;
+    }
+    Ok(())
+}
+
 /// Errors that can occur during DOT file generation.
 #[derive(Debug, thiserror::Error)]
 pub enum DotError<E> {
@@ -74,6 +112,21 @@ pub trait DotRenderer<G: Graph> {
         Ok(vec![])
     }
 
+    /// Returns the path of nested clusters containing a node, outermost
+    /// first, or `None` if the node is not in any cluster.  Nodes whose paths
+    /// share a prefix are placed in the same outer clusters.
+    fn node_cluster(&self, node_id: &G::NodeId) -> Result<Option<Vec<String>>, Self::Error> {
+        let _ = node_id;
+        Ok(None)
+    }
+
+    /// Returns a list of attributes for the cluster with the given path, as
+    /// returned by `node_cluster`.  By default, this labels the cluster with
+    /// the last element of its path.
+    fn cluster_attrs(&self, path: &[String]) -> Result<Vec<Attr>, Self::Error> {
+        Ok(path.last().cloned().map(Attr::Label).into_iter().collect())
+    }
+
     /// Returns whether the graph is strict (no parallel edges allowed).
     fn is_strict(&self, _graph: &G) -> bool {
         !G::EdgeMultiplicity::ALLOWS_PARALLEL_EDGES
@@ -100,6 +153,37 @@ where
         attrs: Vec<Attr>,
     }
 
+    /// A cluster of nodes, in the order they were first seen.
+    struct Cluster<N> {
+        nodes: Vec<N>,
+        children: Vec<(String, Cluster<N>)>,
+    }
+
+    impl<N> Cluster<N> {
+        fn new() -> Self {
+            Self {
+                nodes: Vec::new(),
+                children: Vec::new(),
+            }
+        }
+
+        fn insert(&mut self, path: &[String], node: N) {
+            match path.split_first() {
+                None => self.nodes.push(node),
+                Some((head, rest)) => {
+                    let index = match self.children.iter().position(|(name, _)| name == head) {
+                        Some(index) => index,
+                        None => {
+                            self.children.push((head.clone(), Cluster::new()));
+                            self.children.len() - 1
+                        }
+                    };
+                    self.children[index].1.insert(rest, node);
+                }
+            }
+        }
+    }
+
     struct GraphWrapper<'a, G: Graph> {
         _phantom: std::marker::PhantomData<&'a G>,
         node_info: HashMap<G::NodeId, NodeInfo>,
@@ -153,23 +237,6 @@ where
 
     let wrapper = GraphWrapper::new(graph, generator)?;
 
-    // Escape a string for use in DOT format (only called when quoting is needed)
-    fn escape_dot_string(s: &str) -> String {
-        s.replace('\\', "\\\\")
-            .replace('"', "\\\"")
-            .replace('\n', "\\n")
-            .replace('\r', "\\r")
-    }
-
-    // Format a value for DOT output, adding quotes only if needed
-    fn format_dot_value(s: &str) -> String {
-        if !is_valid_dot_id(s) {
-            format!("\"{}\"", escape_dot_string(s))
-        } else {
-            s.to_string()
-        }
-    }
-
     // Custom DOT renderer that properly handles optional attributes
     let is_digraph = G::Directedness::IS_DIRECTED;
     let strict_prefix = if generator.is_strict(graph) {
@@ -188,35 +255,76 @@ where
     )
     .map_err(DotError::IoError)?;
 
-    // Write nodes
+    // Write nodes, grouped into clusters
+    let mut root = Cluster::new();
     for node_id in graph.node_ids() {
-        let node_info = wrapper
-            .node_info
-            .get(&node_id)
-            .expect("Node ID should exist in map");
-        write!(output, "    {}", node_info.name).map_err(DotError::IoError)?;
-
-        // Write node attributes if any
-        if !node_info.attrs.is_empty() {
-            write!(output, " [").map_err(DotError::IoError)?;
-            for (i, attr) in node_info.attrs.iter().enumerate() {
-                if i > 0 {
-                    write!(output, ", ").map_err(DotError::IoError)?;
-                }
-                write!(
+        let path = generator
+            .node_cluster(&node_id)
+            .map_err(DotError::Generator)?
+            .unwrap_or_default();
+        root.insert(&path, node_id);
+    }
+
+    fn write_cluster<G, D>(
+        output: &mut impl io::Write,
+        generator: &D,
+        node_info: &HashMap<G::NodeId, NodeInfo>,
+        cluster: &Cluster<G::NodeId>,
+        path: &mut Vec<String>,
+        next_cluster_index: &mut usize,
+    ) -> Result<(), DotError<D::Error>>
+    where
+        G: Graph,
+        D: DotRenderer<G>,
+    {
+        let indent = "    ".repeat(path.len() + 1);
+        for node_id in cluster.nodes.iter() {
+            let node_info = node_info.get(node_id).expect("Node ID should exist in map");
+            write!(output, "{}{}", indent, node_info.name)?;
+            write_attr_list(output, &node_info.attrs)?;
+            writeln!(output, ";")?;
+        }
+        for (name, child) in cluster.children.iter() {
+            path.push(name.clone());
+            writeln!(
+                output,
+                "{}subgraph cluster_{} {{",
+                indent, next_cluster_index
+            )?;
+            *next_cluster_index += 1;
+            let attrs = generator.cluster_attrs(path).map_err(DotError::Generator)?;
+            for attr in attrs {
+                writeln!(
                     output,
-                    "{} = {}",
+                    "{}    {} = {};",
+                    indent,
                     attr.name(),
                     format_dot_value(&attr.value())
-                )
-                .map_err(DotError::IoError)?;
+                )?;
             }
-            write!(output, "]").map_err(DotError::IoError)?;
+            write_cluster(
+                output,
+                generator,
+                node_info,
+                child,
+                path,
+                next_cluster_index,
+            )?;
+            writeln!(output, "{}}}", indent)?;
+            path.pop();
         }
-
-        writeln!(output, ";").map_err(DotError::IoError)?;
+        Ok(())
     }
 
+    write_cluster(
+        output,
+        generator,
+        &wrapper.node_info,
+        &root,
+        &mut Vec::new(),
+        &mut 0,
+    )?;
+
     // Blank line between nodes and edges
     if graph.num_edges() > 0 && graph.num_nodes() > 0 {
         writeln!(output).map_err(DotError::IoError)?;
@@ -224,8 +332,6 @@ where
 
     // Write edges
     for edge_id in graph.edge_ids() {
-        use crate::end_pair::EndPair as _;
-
         let ends = graph.edge_ends(&edge_id);
         let (source_id, target_id) = ends.into_values();
         let source_info = wrapper
@@ -248,30 +354,86 @@ where
         )
         .map_err(DotError::IoError)?;
 
-        // Write edge attributes if any - omit entirely if no attributes
-        if !edge_info.attrs.is_empty() {
-            write!(output, " [").map_err(DotError::IoError)?;
-            for (i, attr) in edge_info.attrs.iter().enumerate() {
-                if i > 0 {
-                    write!(output, ", ").map_err(DotError::IoError)?;
-                }
-                write!(
-                    output,
-                    "{} = {}",
-                    attr.name(),
-                    format_dot_value(&attr.value())
-                )
-                .map_err(DotError::IoError)?;
-            }
-            write!(output, "]").map_err(DotError::IoError)?;
-        }
-
+        write_attr_list(output, &edge_info.attrs).map_err(DotError::IoError)?;
         writeln!(output, ";").map_err(DotError::IoError)?;
     }
 
     writeln!(output, "}}").map_err(DotError::IoError)
 }
 
+/// Like [`generate_dot_file`], but writes each node and edge as soon as its
+/// attributes have been generated, rather than generating the attributes of
+/// the whole graph first.  Only the names of the nodes are kept in memory,
+/// so this is suitable for exporting graphs whose attributes would not fit
+/// in memory.
+///
+/// Clusters are not supported: [`DotRenderer::node_cluster`] is not called,
+/// and all nodes are written at the top level.  If a node name or attribute
+/// turns out to be invalid, the output written so far is incomplete.
+pub fn stream_dot_file<G, D>(
+    graph: &G,
+    generator: &D,
+    output: &mut impl io::Write,
+) -> Result<(), DotError<D::Error>>
+where
+    G: Graph,
+    D: DotRenderer<G>,
+{
+    let graph_name = generator.graph_name().map_err(DotError::Generator)?;
+    if !is_valid_dot_id(&graph_name) {
+        return Err(DotError::InvalidId(graph_name));
+    }
+    let strict_prefix = if generator.is_strict(graph) {
+        "strict "
+    } else {
+        ""
+    };
+    let (graph_type, edge_op) = if G::Directedness::IS_DIRECTED {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    writeln!(output, "{}{} {} {{", strict_prefix, graph_type, graph_name)?;
+
+    let mut names = HashMap::with_capacity(graph.num_nodes());
+    for (index, node_id) in graph.node_ids().enumerate() {
+        let mut name = generator
+            .node_name(&node_id, index)
+            .map_err(DotError::Generator)?;
+        let attrs = generator
+            .node_attrs(&node_id, &mut name)
+            .map_err(DotError::Generator)?;
+        if !is_valid_dot_id(&name) {
+            return Err(DotError::InvalidId(name));
+        }
+        write!(output, "    {}", name)?;
+        write_attr_list(output, &attrs)?;
+        writeln!(output, ";")?;
+        names.insert(node_id, name);
+    }
+
+    if graph.num_edges() > 0 && !names.is_empty() {
+        writeln!(output)?;
+    }
+
+    for edge_id in graph.edge_ids() {
+        let (source_id, target_id) = graph.edge_ends(&edge_id).into_values();
+        let attrs = generator
+            .edge_attrs(&edge_id)
+            .map_err(DotError::Generator)?;
+        write!(
+            output,
+            "    {} {} {}",
+            names[&source_id], edge_op, names[&target_id]
+        )?;
+        write_attr_list(output, &attrs)?;
+        writeln!(output, ";")?;
+    }
+
+    writeln!(output, "}}")?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::marker::PhantomData;
@@ -493,6 +655,42 @@ mod tests {
         assert!(dot.contains("color = red"));
     }
 
+    #[test]
+    fn test_stream_matches_generate() {
+        let mut graph: BagGraph<String, (), Directed> = BagGraph::default();
+        let a = graph.add_node("a".to_string());
+        let b = graph.add_node("b".to_string());
+        let c = graph.add_node("c".to_string());
+        graph.add_edge(&a, &b, ());
+        graph.add_edge(&b, &c, ());
+        graph.add_edge(&c, &c, ());
+
+        let generator = AttributeGenerator;
+        let mut expected = Vec::new();
+        generate_dot_file(&graph, &generator, &mut expected).unwrap();
+        let mut output = Vec::new();
+        stream_dot_file(&graph, &generator, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            String::from_utf8(expected).unwrap()
+        );
+
+        let empty: BagGraph<String, (), Undirected> = BagGraph::default();
+        let mut output = Vec::new();
+        stream_dot_file(&empty, &AttributeGenerator, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "graph G {\n}\n");
+    }
+
+    #[test]
+    fn test_stream_invalid_node_name() {
+        let mut graph: BagGraph<String, (), Directed> = BagGraph::default();
+        graph.add_node("a".to_string());
+
+        let mut output = Vec::new();
+        let result = stream_dot_file(&graph, &InvalidNodeNameGenerator, &mut output);
+        assert!(matches!(result, Err(DotError::InvalidId(_))));
+    }
+
     #[test]
     fn test_format_dot_value_quoting() {
         // Test that values are quoted when necessary
@@ -546,4 +744,66 @@ mod tests {
         assert!(dot.contains("digraph Triangle"));
         assert_eq!(dot.matches("->").count(), 3);
     }
+
+    /// Places each node in the clusters named by the directory part of its
+    /// data, e.g. "core/io/reader" is in cluster "io" inside cluster "core".
+    struct ClusterGenerator<'a> {
+        graph: &'a BagGraph<String, (), Directed>,
+    }
+
+    impl DotRenderer<BagGraph<String, (), Directed>> for ClusterGenerator<'_> {
+        type Error = std::convert::Infallible;
+
+        fn node_cluster(
+            &self,
+            node_id: &<BagGraph<String, (), Directed> as Graph>::NodeId,
+        ) -> Result<Option<Vec<String>>, Self::Error> {
+            let mut path: Vec<String> = self
+                .graph
+                .node_data(node_id)
+                .split('/')
+                .map(str::to_string)
+                .collect();
+            path.pop();
+            Ok((!path.is_empty()).then_some(path))
+        }
+    }
+
+    #[test]
+    fn test_generate_nested_clusters() {
+        let mut graph: BagGraph<String, (), Directed> = BagGraph::default();
+        let a = graph.add_node("core/io/reader".to_string());
+        let b = graph.add_node("main".to_string());
+        let c = graph.add_node("core/util".to_string());
+        graph.add_node("core/io/writer".to_string());
+        graph.add_edge(&b, &a, ());
+        graph.add_edge(&a, &c, ());
+
+        let generator = ClusterGenerator { graph: &graph };
+        let mut output = Vec::new();
+
+        generate_dot_file(&graph, &generator, &mut output).unwrap();
+        let dot = String::from_utf8(output).unwrap();
+
+        assert_eq!(
+            dot,
+            "\
+digraph G {
+    n1;
+    subgraph cluster_0 {
+        label = core;
+        n2;
+        subgraph cluster_1 {
+            label = io;
+            n0;
+            n3;
+        }
+    }
+
+    n1 -> n0;
+    n0 -> n2;
+}
+"
+        );
+    }
 }
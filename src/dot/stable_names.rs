@@ -0,0 +1,206 @@
+//! Node names which stay the same between versions of a graph.
+//!
+//! By default, DOT output names nodes by their position in
+//! [`Graph::node_ids`], so adding or removing a single node can rename most
+//! of the others, and a diff of two renders shows every node as changed.
+//! [`StableNames`] instead names each node after a key, such as its label or
+//! the ID of the thing it represents, remembering the name given to each key
+//! across versions.  A node keeps its name for as long as its key is present,
+//! and names are never reused for different keys.
+//!
+//! Names are applied to a render with [`WithNodeNames`], which wraps another
+//! renderer.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{
+    dot::{attr::Attr, renderer::DotRenderer},
+    prelude::*,
+};
+
+/// Names given to node keys across versions of a graph.
+#[derive(Clone, Debug)]
+pub struct StableNames<K> {
+    names: HashMap<K, String>,
+    next_index: usize,
+}
+
+impl<K> StableNames<K>
+where
+    K: Hash + Eq + Ord + Clone,
+{
+    /// Creates an empty set of names.
+    pub fn new() -> Self {
+        Self {
+            names: HashMap::new(),
+            next_index: 0,
+        }
+    }
+
+    /// Creates a set of names for the nodes of `previous`, as a starting
+    /// point for naming later versions.  Keys are named in sorted order, so
+    /// the result does not depend on the order of `previous.node_ids()`.
+    pub fn from_graph<G>(previous: &G, key_fn: impl Fn(&G::NodeId) -> K) -> Self
+    where
+        G: Graph + ?Sized,
+    {
+        let mut names = Self::new();
+        names.assign(previous, key_fn);
+        names
+    }
+
+    /// Returns the name given to a key, if any.
+    pub fn name(&self, key: &K) -> Option<&str> {
+        self.names.get(key).map(String::as_str)
+    }
+
+    /// Names the nodes of `graph` by their keys, reusing the name given to
+    /// each key before.  Keys seen for the first time are given new names in
+    /// sorted order.  Returns the name of each node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two nodes of `graph` have the same key.
+    pub fn assign<G>(
+        &mut self,
+        graph: &G,
+        key_fn: impl Fn(&G::NodeId) -> K,
+    ) -> HashMap<G::NodeId, String>
+    where
+        G: Graph + ?Sized,
+    {
+        let mut keys: Vec<_> = graph.node_ids().map(|nid| (key_fn(&nid), nid)).collect();
+        keys.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut seen = HashSet::new();
+        keys.into_iter()
+            .map(|(key, nid)| {
+                assert!(seen.insert(key.clone()), "duplicate node key");
+                let next_index = &mut self.next_index;
+                let name = self.names.entry(key).or_insert_with(|| {
+                    *next_index += 1;
+                    format!("n{}", *next_index - 1)
+                });
+                (nid, name.clone())
+            })
+            .collect()
+    }
+}
+
+impl<K> Default for StableNames<K>
+where
+    K: Hash + Eq + Ord + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`DotRenderer`] which names nodes from a map, such as one returned by
+/// [`StableNames::assign`], and otherwise renders the graph as `inner` does.
+/// Nodes missing from the map are named by `inner`.
+pub struct WithNodeNames<'r, R, N> {
+    inner: &'r R,
+    names: HashMap<N, String>,
+}
+
+impl<'r, R, N> WithNodeNames<'r, R, N> {
+    /// Creates a renderer which names nodes according to `names`.
+    pub fn new(inner: &'r R, names: HashMap<N, String>) -> Self {
+        Self { inner, names }
+    }
+}
+
+impl<G, R> DotRenderer<G> for WithNodeNames<'_, R, G::NodeId>
+where
+    G: Graph,
+    R: DotRenderer<G>,
+{
+    type Error = R::Error;
+
+    fn graph_name(&self) -> Result<String, Self::Error> {
+        self.inner.graph_name()
+    }
+
+    fn node_name(&self, node_id: &G::NodeId, index: usize) -> Result<String, Self::Error> {
+        match self.names.get(node_id) {
+            Some(name) => Ok(name.clone()),
+            None => self.inner.node_name(node_id, index),
+        }
+    }
+
+    fn node_attrs(&self, node_id: &G::NodeId, name: &mut String) -> Result<Vec<Attr>, Self::Error> {
+        self.inner.node_attrs(node_id, name)
+    }
+
+    fn edge_attrs(&self, edge_id: &G::EdgeId) -> Result<Vec<Attr>, Self::Error> {
+        self.inner.edge_attrs(edge_id)
+    }
+
+    fn node_cluster(&self, node_id: &G::NodeId) -> Result<Option<Vec<String>>, Self::Error> {
+        self.inner.node_cluster(node_id)
+    }
+
+    fn cluster_attrs(&self, path: &[String]) -> Result<Vec<Attr>, Self::Error> {
+        self.inner.cluster_attrs(path)
+    }
+
+    fn is_strict(&self, graph: &G) -> bool {
+        self.inner.is_strict(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    struct PlainRenderer;
+
+    impl<G: Graph> DotRenderer<G> for PlainRenderer {
+        type Error = Infallible;
+    }
+
+    #[test]
+    fn test_stable_names() {
+        let mut v1 = BagGraph::<&str, (), Directed>::default();
+        let a = v1.add_node("a");
+        let c = v1.add_node("c");
+        v1.add_new_edge(&a, &c, ());
+        let mut names = StableNames::from_graph(&v1, |nid| *v1.node_data(nid));
+        assert_eq!(names.name(&"a"), Some("n0"));
+        assert_eq!(names.name(&"c"), Some("n1"));
+
+        // The second version is a different type of graph, with a new node
+        // inserted before the others and one removed.
+        let mut v2 = LinkedGraph::<&str, (), Directed>::default();
+        let b = v2.add_node("b");
+        let c = v2.add_node("c");
+        let d = v2.add_node("d");
+        v2.add_new_edge(&b, &c, ());
+        v2.add_new_edge(&c, &d, ());
+        let assigned = v2.stable_visual_ids(&mut names, |nid| *v2.node_data(nid));
+        assert_eq!(assigned[&b], "n2");
+        assert_eq!(assigned[&c], "n1");
+        assert_eq!(assigned[&d], "n3");
+
+        // Names of removed keys are remembered, and not reused.
+        let mut v3 = BagGraph::<&str, (), Directed>::default();
+        let a = v3.add_node("a");
+        let e = v3.add_node("e");
+        v3.add_new_edge(&e, &a, ());
+        let assigned = v3.stable_visual_ids(&mut names, |nid| *v3.node_data(nid));
+        assert_eq!(assigned[&a], "n0");
+        assert_eq!(assigned[&e], "n4");
+        assert_eq!(
+            v3.to_dot_string(&WithNodeNames::new(&PlainRenderer, assigned))
+                .unwrap(),
+            "digraph G {\n    n0;\n    n4;\n\n    n4 -> n0;\n}\n"
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! An object-safe facade for [`Graph`].
+//!
+//! [`Graph`] returns `impl Iterator` from many of its methods and has
+//! generic methods, so it cannot be used as a trait object.  [`DynGraph`]
+//! has the core queries of [`Graph`] with boxed iterators instead, and is
+//! implemented for every graph, so any graph can be stored as a
+//! `Box<dyn DynGraph<...>>` or `&dyn DynGraph<...>`.  In turn, [`Graph`] is
+//! implemented for `dyn DynGraph<...>`, so a trait object can be passed to
+//! any algorithm which takes a graph.
+//!
+//! Graphs stored behind the same trait object type must have the same node
+//! and edge ID types, which is the case for graphs of the same kind, and
+//! for wrappers such as [`SoftDeleteGraph`](crate::SoftDeleteGraph) and
+//! [`NotifyingGraph`](crate::NotifyingGraph) around them.
+
+use crate::{error::GraphError, prelude::*};
+
+/// A boxed iterator returned by the methods of [`DynGraph`].
+pub type DynIter<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
+
+/// An object-safe version of [`Graph`].  See [`crate::dyn_graph`].
+pub trait DynGraph {
+    type Directedness: Directedness;
+    type EdgeMultiplicity: EdgeMultiplicity;
+    type NodeData;
+    type EdgeData;
+    type NodeId: GraphElementId;
+    type EdgeId: GraphElementId;
+
+    /// See [`Graph::node_ids`].
+    fn dyn_node_ids(&self) -> DynIter<'_, Self::NodeId>;
+
+    /// See [`Graph::node_data`].
+    fn dyn_node_data(&self, id: &Self::NodeId) -> &Self::NodeData;
+
+    /// See [`Graph::check_node_id`].
+    fn dyn_check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError>;
+
+    /// See [`Graph::num_nodes`].
+    fn dyn_num_nodes(&self) -> usize;
+
+    /// See [`Graph::edge_ids`].
+    fn dyn_edge_ids(&self) -> DynIter<'_, Self::EdgeId>;
+
+    /// See [`Graph::edge_data`].
+    fn dyn_edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData;
+
+    /// See [`Graph::check_edge_id`].
+    fn dyn_check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError>;
+
+    /// See [`Graph::num_edges`].
+    fn dyn_num_edges(&self) -> usize;
+
+    /// See [`Graph::edge_ends`].
+    fn dyn_edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId>;
+
+    /// See [`Graph::edges_from`].
+    fn dyn_edges_from<'a, 'b: 'a>(&'a self, from: &'b Self::NodeId) -> DynIter<'a, Self::EdgeId>;
+
+    /// See [`Graph::edges_into`].
+    fn dyn_edges_into<'a, 'b: 'a>(&'a self, into: &'b Self::NodeId) -> DynIter<'a, Self::EdgeId>;
+
+    /// See [`Graph::edges_from_into`].
+    fn dyn_edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> DynIter<'a, Self::EdgeId>;
+
+    /// See [`Graph::num_edges_from`].
+    fn dyn_num_edges_from(&self, from: &Self::NodeId) -> usize;
+
+    /// See [`Graph::num_edges_into`].
+    fn dyn_num_edges_into(&self, into: &Self::NodeId) -> usize;
+}
+
+impl<G> DynGraph for G
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn dyn_node_ids(&self) -> DynIter<'_, Self::NodeId> {
+        Box::new(self.node_ids())
+    }
+
+    fn dyn_node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.node_data(id)
+    }
+
+    fn dyn_check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.check_node_id(id)
+    }
+
+    fn dyn_num_nodes(&self) -> usize {
+        self.num_nodes()
+    }
+
+    fn dyn_edge_ids(&self) -> DynIter<'_, Self::EdgeId> {
+        Box::new(self.edge_ids())
+    }
+
+    fn dyn_edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.edge_data(id)
+    }
+
+    fn dyn_check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.check_edge_id(id)
+    }
+
+    fn dyn_num_edges(&self) -> usize {
+        self.num_edges()
+    }
+
+    fn dyn_edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.edge_ends(id)
+    }
+
+    fn dyn_edges_from<'a, 'b: 'a>(&'a self, from: &'b Self::NodeId) -> DynIter<'a, Self::EdgeId> {
+        Box::new(self.edges_from(from))
+    }
+
+    fn dyn_edges_into<'a, 'b: 'a>(&'a self, into: &'b Self::NodeId) -> DynIter<'a, Self::EdgeId> {
+        Box::new(self.edges_into(into))
+    }
+
+    fn dyn_edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> DynIter<'a, Self::EdgeId> {
+        Box::new(self.edges_from_into(from, into))
+    }
+
+    fn dyn_num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.num_edges_from(from)
+    }
+
+    fn dyn_num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.num_edges_into(into)
+    }
+}
+
+impl<D, M, ND, ED, N, E> Graph
+    for dyn DynGraph<
+            Directedness = D,
+            EdgeMultiplicity = M,
+            NodeData = ND,
+            EdgeData = ED,
+            NodeId = N,
+            EdgeId = E,
+        > + '_
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    N: GraphElementId,
+    E: GraphElementId,
+{
+    type Directedness = D;
+    type EdgeMultiplicity = M;
+    type NodeData = ND;
+    type EdgeData = ED;
+    type NodeId = N;
+    type EdgeId = E;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.dyn_node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.dyn_node_data(id)
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.dyn_check_node_id(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.dyn_num_nodes()
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.dyn_edge_ids()
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.dyn_edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.dyn_check_edge_id(id)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.dyn_num_edges()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.dyn_edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.dyn_edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.dyn_edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.dyn_edges_from_into(from, into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.dyn_num_edges_from(from)
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.dyn_num_edges_into(into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, NotifyingGraph, SoftDeleteGraph};
+
+    use super::*;
+
+    type Inner = BagGraph<&'static str, u32, Directed>;
+    type Object = dyn DynGraph<
+            Directedness = Directed,
+            EdgeMultiplicity = MultipleEdges,
+            NodeData = &'static str,
+            EdgeData = u32,
+            NodeId = <Inner as Graph>::NodeId,
+            EdgeId = <Inner as Graph>::EdgeId,
+        >;
+
+    fn example() -> Inner {
+        let mut graph = Inner::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_new_edge(&a, &b, 1);
+        graph.add_new_edge(&b, &c, 2);
+        graph.add_new_edge(&a, &c, 5);
+        graph
+    }
+
+    #[test]
+    fn test_heterogeneous() {
+        let mut soft = SoftDeleteGraph::new(example());
+        let hidden = soft.node_ids().nth(1).unwrap();
+        soft.soft_remove_node(&hidden);
+        let graphs: Vec<Box<Object>> = vec![
+            Box::new(example()),
+            Box::new(soft),
+            Box::new(NotifyingGraph::new(example())),
+        ];
+        let counts: Vec<_> = graphs
+            .iter()
+            .map(|graph| (graph.num_nodes(), graph.num_edges()))
+            .collect();
+        assert_eq!(counts, vec![(3, 3), (2, 1), (3, 3)]);
+    }
+
+    #[test]
+    fn test_algorithms_on_object() {
+        let inner = example();
+        let graph: &Object = &inner;
+        let a = graph
+            .node_ids()
+            .find(|nid| *graph.node_data(nid) == "a")
+            .unwrap();
+        let names: Vec<_> = graph.bfs(&a).map(|nid| *graph.node_data(&nid)).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert_eq!(graph.successors(&a).count(), 2);
+        assert!(!graph.has_cycle());
+
+        #[cfg(feature = "pathfinding")]
+        {
+            let tree = graph.shortest_path_tree(&a, |eid| *graph.edge_data(eid));
+            let c = graph
+                .node_ids()
+                .find(|nid| *graph.node_data(nid) == "c")
+                .unwrap();
+            assert_eq!(tree.distance(&c), Some(3));
+        }
+    }
+}
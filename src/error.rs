@@ -0,0 +1,32 @@
+//! Errors reported by the fallible methods of [`Graph`](crate::Graph) and
+//! [`GraphMut`](crate::GraphMut).
+
+/// An error caused by passing a node or edge ID which cannot be used with a
+/// graph, or by a mutation which the graph does not permit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum GraphError {
+    /// The node ID does not refer to a node in the graph, e.g. because the
+    /// node has been removed.
+    #[error("Invalid node ID")]
+    InvalidNodeId,
+    /// The edge ID does not refer to an edge in the graph, e.g. because the
+    /// edge has been removed.
+    #[error("Invalid edge ID")]
+    InvalidEdgeId,
+    /// The ID was created by a different graph.
+    #[error("ID belongs to a different graph")]
+    ForeignId,
+    /// The ID was created before the graph was compacted, so it may no longer
+    /// refer to the same node or edge.
+    #[error("ID was invalidated by compaction")]
+    StaleId,
+    /// Adding the edge would exceed a degree limit of a
+    /// [`DegreeBoundedGraph`](crate::DegreeBoundedGraph).
+    #[error("Degree limit exceeded")]
+    DegreeLimitExceeded,
+    /// The graph does not allow parallel edges and already has an edge
+    /// between the two nodes.  See
+    /// [`GraphMut::add_edge_strict`](crate::GraphMut::add_edge_strict).
+    #[error("Edge already exists")]
+    DuplicateEdge,
+}
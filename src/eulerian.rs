@@ -0,0 +1,229 @@
+//! Eulerian paths and circuits.
+//!
+//! An Eulerian path uses every edge of the graph exactly once, and an
+//! Eulerian circuit is an Eulerian path which ends where it starts.  They
+//! are found with Hierholzer's algorithm in `O(V + E)` time.
+//!
+//! A graph has an Eulerian circuit if all of its edges are connected and
+//! every node has as many edges into it as out of it (for an undirected
+//! graph, every node has an even degree).  It has an Eulerian path if, in
+//! addition, one node may have one more edge out than in and another one
+//! more edge in than out (for an undirected graph, two nodes may have odd
+//! degrees).  Nodes with no edges are ignored.  Self-loops and parallel
+//! edges are allowed.
+//!
+//! A graph with no edges has an empty path and circuit starting at any of
+//! its nodes, and neither if it has no nodes.
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    path::Path,
+    prelude::*,
+};
+
+/// Returns an Eulerian circuit of `graph`, or `None` if it has none.
+pub fn eulerian_circuit<G>(graph: &G) -> Option<Path<'_, G>>
+where
+    G: Graph + ?Sized,
+{
+    let balance = degree_balance(graph);
+    if balance.values().any(|&balance| balance != 0) {
+        return None;
+    }
+    let start = balance
+        .into_keys()
+        .next()
+        .or_else(|| graph.node_ids().next())?;
+    hierholzer(graph, start)
+}
+
+/// Returns an Eulerian path of `graph`, or `None` if it has none.  If the
+/// graph has an Eulerian circuit, one is returned.
+pub fn eulerian_path<G>(graph: &G) -> Option<Path<'_, G>>
+where
+    G: Graph + ?Sized,
+{
+    let balance = degree_balance(graph);
+    let mut starts = balance.iter().filter(|&(_, &balance)| balance > 0);
+    let start = if graph.is_directed() {
+        // The start has one more edge out than in, and the end one more in
+        // than out.
+        let start = starts.next();
+        let ends = balance.values().filter(|&&balance| balance < 0).count();
+        if starts.next().is_some()
+            || ends > 1
+            || start.is_some_and(|(_, &balance)| balance != 1)
+            || balance.values().any(|&balance| balance < -1)
+        {
+            return None;
+        }
+        start.map(|(nid, _)| nid.clone())
+    } else {
+        // Either end of the path may be used as the start.
+        let odd: Vec<_> = starts.map(|(nid, _)| nid.clone()).collect();
+        match odd.len() {
+            0 => None,
+            2 => Some(odd[0].clone()),
+            _ => return None,
+        }
+    };
+    let start = start
+        .or_else(|| balance.into_keys().next())
+        .or_else(|| graph.node_ids().next())?;
+    hierholzer(graph, start)
+}
+
+/// For a directed graph, maps each node with edges to the number of edges
+/// out of it minus the number of edges into it.  For an undirected graph,
+/// maps each node with edges to 1 if its degree is odd, or 0 if it is even.
+fn degree_balance<G>(graph: &G) -> FastHashMap<G::NodeId, isize>
+where
+    G: Graph + ?Sized,
+{
+    let mut balance: FastHashMap<G::NodeId, isize> = FastHashMap::default();
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        if graph.is_directed() {
+            *balance.entry(from).or_default() += 1;
+            *balance.entry(into).or_default() -= 1;
+        } else {
+            for nid in [from, into] {
+                let degree = balance.entry(nid).or_default();
+                *degree = 1 - *degree;
+            }
+        }
+    }
+    balance
+}
+
+/// Follows unused edges from `start` with Hierholzer's algorithm.  Returns
+/// `None` if some edges cannot be reached.
+fn hierholzer<G>(graph: &G, start: G::NodeId) -> Option<Path<'_, G>>
+where
+    G: Graph + ?Sized,
+{
+    let mut unexplored: FastHashMap<G::NodeId, Vec<G::EdgeId>> = FastHashMap::default();
+    let mut used: FastHashSet<G::EdgeId> = FastHashSet::default();
+    // Each frame is a node and the edge it was reached by.  Edges are added
+    // to the circuit in reverse order as the search backs out of them.
+    let mut stack: Vec<(G::NodeId, Option<G::EdgeId>)> = vec![(start.clone(), None)];
+    let mut circuit = Vec::with_capacity(graph.num_edges());
+    while let Some((node, _)) = stack.last() {
+        let edges = unexplored.entry(node.clone()).or_insert_with(|| {
+            let mut edges: Vec<_> = graph.edges_from(node).collect();
+            edges.reverse();
+            edges
+        });
+        let next_edge = std::iter::from_fn(|| edges.pop()).find(|eid| !used.contains(eid));
+        match next_edge {
+            Some(eid) => {
+                let next = graph.edge_ends(&eid).other_value(node).into_inner().clone();
+                used.insert(eid.clone());
+                stack.push((next, Some(eid)));
+            }
+            None => {
+                if let Some((_, Some(eid))) = stack.pop() {
+                    circuit.push(eid);
+                }
+            }
+        }
+    }
+    if circuit.len() != graph.num_edges() {
+        return None;
+    }
+    circuit.reverse();
+    Some(Path::from_edges(graph, start, circuit))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    /// Checks that `path` uses every edge of `graph` exactly once.
+    fn assert_eulerian<G: Graph>(graph: &G, path: &Path<'_, G>) {
+        let mut edges: Vec<_> = path.edges().collect();
+        edges.sort();
+        let mut expected: Vec<_> = graph.edge_ids().collect();
+        expected.sort();
+        assert_eq!(edges, expected);
+    }
+
+    #[test]
+    fn test_undirected() {
+        // A square with a diagonal has two odd nodes.
+        let mut graph = LinkedGraph::<(), (), Undirected>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        for i in 0..4 {
+            graph.add_new_edge(&n[i], &n[(i + 1) % 4], ());
+        }
+        let diagonal = graph.add_new_edge(&n[0], &n[2], ());
+        graph.add_new_edge(&n[1], &n[1], ());
+
+        assert!(graph.eulerian_circuit().is_none());
+        let path = graph.eulerian_path().unwrap();
+        assert_eulerian(&graph, &path);
+        let mut ends = vec![path.first_node(), path.last_node()];
+        ends.sort();
+        let mut expected = vec![n[0].clone(), n[2].clone()];
+        expected.sort();
+        assert_eq!(ends, expected);
+
+        graph.remove_edge(&diagonal);
+        let circuit = graph.eulerian_circuit().unwrap();
+        assert_eulerian(&graph, &circuit);
+        assert_eq!(circuit.first_node(), circuit.last_node());
+    }
+
+    #[test]
+    fn test_directed() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], ());
+        graph.add_new_edge(&n[1], &n[2], ());
+        graph.add_new_edge(&n[2], &n[0], ());
+        graph.add_new_edge(&n[0], &n[1], ());
+        graph.add_new_edge(&n[1], &n[0], ());
+
+        let circuit = graph.eulerian_circuit().unwrap();
+        assert_eulerian(&graph, &circuit);
+        assert_eq!(circuit.first_node(), circuit.last_node());
+
+        let extra = graph.add_new_edge(&n[2], &n[1], ());
+        assert!(graph.eulerian_circuit().is_none());
+        let path = graph.eulerian_path().unwrap();
+        assert_eulerian(&graph, &path);
+        assert_eq!(path.first_node(), n[2]);
+        assert_eq!(path.last_node(), n[1]);
+
+        graph.remove_edge(&extra);
+        graph.add_new_edge(&n[2], &n[1], ());
+        graph.add_new_edge(&n[2], &n[1], ());
+        assert!(graph.eulerian_path().is_none());
+    }
+
+    #[test]
+    fn test_disconnected() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], ());
+        graph.add_new_edge(&n[1], &n[0], ());
+        assert!(graph.eulerian_circuit().is_some());
+        graph.add_new_edge(&n[2], &n[3], ());
+        graph.add_new_edge(&n[3], &n[2], ());
+        assert!(graph.eulerian_circuit().is_none());
+        assert!(graph.eulerian_path().is_none());
+    }
+
+    #[test]
+    fn test_no_edges() {
+        let mut graph = BagGraph::<(), (), Undirected>::default();
+        assert!(graph.eulerian_circuit().is_none());
+        let a = graph.add_node(());
+        let path = graph.eulerian_path().unwrap();
+        assert_eq!(path.first_node(), a);
+        assert_eq!(path.edges().count(), 0);
+    }
+}
@@ -0,0 +1,360 @@
+//! Running tasks in the order given by a DAG of dependencies.
+//!
+//! An [`Executor`] runs a task for each node of an [`Acyclic`] graph, where
+//! an edge from `a` into `b` means that `b` depends on `a`: the task of `b`
+//! is only started once the task of every node with an edge into `b` has
+//! succeeded.  Tasks whose dependencies are met run in parallel on a pool of
+//! scoped threads, up to a configurable number at a time.  The caller is
+//! told of each task starting, finishing, failing or being skipped through
+//! a callback, which runs on the calling thread, so it can report progress
+//! or update state without synchronization.
+//!
+//! What happens when a task fails is chosen by a [`FailurePolicy`]: either
+//! no more tasks are started, or only the tasks which depend on the failed
+//! one, directly or indirectly, are skipped.
+
+use std::{
+    collections::VecDeque,
+    panic::{self, AssertUnwindSafe},
+    sync::{Mutex, mpsc},
+    thread,
+};
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    preconditions::Acyclic,
+    prelude::*,
+};
+
+/// What an [`Executor`] does when a task fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Start no more tasks, but wait for those already running to finish.
+    /// Every task which was not started is skipped.
+    #[default]
+    Abort,
+    /// Skip the tasks which depend on the failed one, and keep running the
+    /// others.
+    Continue,
+}
+
+/// A change in the state of a task, as reported by [`Executor::run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskEvent<N, T, E> {
+    /// The task of a node was started.
+    Started(N),
+    /// The task of a node succeeded with the given result.
+    Finished(N, T),
+    /// The task of a node failed with the given error.
+    Failed(N, E),
+    /// The task of a node was not run, because a task it depends on failed,
+    /// or because another task failed under [`FailurePolicy::Abort`].
+    Skipped(N),
+}
+
+/// The nodes whose tasks were run or skipped by [`Executor::run`], each in
+/// the order the events were reported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionReport<N> {
+    /// The nodes whose tasks succeeded.
+    pub finished: Vec<N>,
+    /// The nodes whose tasks failed.
+    pub failed: Vec<N>,
+    /// The nodes whose tasks were not run.
+    pub skipped: Vec<N>,
+}
+
+impl<N> ExecutionReport<N> {
+    /// Returns true if every task succeeded.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+}
+
+/// Runs a task for each node of a DAG, respecting the dependencies given by
+/// its edges.  See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Executor {
+    parallelism: usize,
+    failure_policy: FailurePolicy,
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Executor {
+    /// Creates an executor which runs as many tasks at a time as the machine
+    /// has CPUs, and aborts when a task fails.
+    pub fn new() -> Self {
+        Self {
+            parallelism: thread::available_parallelism().map_or(1, usize::from),
+            failure_policy: FailurePolicy::Abort,
+        }
+    }
+
+    /// Sets the largest number of tasks to run at a time, returning the
+    /// updated executor.  Zero is treated as one.
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Sets what to do when a task fails, returning the updated executor.
+    pub fn with_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Gets the largest number of tasks run at a time.
+    pub fn parallelism(&self) -> usize {
+        self.parallelism
+    }
+
+    /// Gets what is done when a task fails.
+    pub fn failure_policy(&self) -> FailurePolicy {
+        self.failure_policy
+    }
+
+    /// Runs `task` for each node of `dag`, calling `on_event` on this thread
+    /// as tasks start and end, and returns once every task has finished or
+    /// been skipped.  If a task panics, the panic is propagated once the
+    /// tasks already running have finished.
+    pub fn run<G, T, E>(
+        &self,
+        dag: Acyclic<'_, G>,
+        task: impl Fn(&G::NodeId) -> Result<T, E> + Sync,
+        mut on_event: impl FnMut(TaskEvent<G::NodeId, T, E>),
+    ) -> ExecutionReport<G::NodeId>
+    where
+        G: Graph<Directedness = Directed> + ?Sized,
+        T: Send,
+        E: Send,
+    {
+        let graph = dag.graph();
+        let mut report = ExecutionReport {
+            finished: Vec::new(),
+            failed: Vec::new(),
+            skipped: Vec::new(),
+        };
+        let mut waiting: FastHashMap<G::NodeId, usize> = FastHashMap::default();
+        let mut ready = VecDeque::new();
+        for nid in graph.node_ids() {
+            match graph.num_edges_into(&nid) {
+                0 => ready.push_back(nid),
+                n => {
+                    waiting.insert(nid, n);
+                }
+            }
+        }
+        // Nodes which depend on a failed or skipped task.
+        let mut blocked = FastHashSet::default();
+
+        let (job_sender, job_receiver) = mpsc::channel::<G::NodeId>();
+        let job_receiver = Mutex::new(job_receiver);
+        let (result_sender, result_receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..self.parallelism.min(graph.num_nodes()) {
+                let result_sender = result_sender.clone();
+                let (job_receiver, task) = (&job_receiver, &task);
+                scope.spawn(move || {
+                    while let Ok(nid) = {
+                        let receiver = job_receiver.lock().unwrap();
+                        receiver.recv()
+                    } {
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| task(&nid)));
+                        let stop = result.is_err();
+                        if result_sender.send((nid, result)).is_err() || stop {
+                            break;
+                        }
+                    }
+                });
+            }
+
+            let mut running = 0;
+            let mut aborted = false;
+            loop {
+                while running < self.parallelism && !aborted {
+                    let Some(nid) = ready.pop_front() else {
+                        break;
+                    };
+                    if blocked.contains(&nid) {
+                        // Pass the failure on to the node's dependents.
+                        release_dependents(graph, &nid, &mut waiting, &mut ready, |into| {
+                            blocked.insert(into);
+                        });
+                        on_event(TaskEvent::Skipped(nid.clone()));
+                        report.skipped.push(nid);
+                        continue;
+                    }
+                    on_event(TaskEvent::Started(nid.clone()));
+                    job_sender.send(nid).unwrap();
+                    running += 1;
+                }
+                if running == 0 {
+                    break;
+                }
+                let (nid, result) = result_receiver.recv().unwrap();
+                running -= 1;
+                match result {
+                    Ok(Ok(value)) => {
+                        release_dependents(graph, &nid, &mut waiting, &mut ready, |_| {});
+                        on_event(TaskEvent::Finished(nid.clone(), value));
+                        report.finished.push(nid);
+                    }
+                    Ok(Err(error)) => {
+                        match self.failure_policy {
+                            FailurePolicy::Abort => aborted = true,
+                            FailurePolicy::Continue => {
+                                release_dependents(graph, &nid, &mut waiting, &mut ready, |into| {
+                                    blocked.insert(into);
+                                });
+                            }
+                        }
+                        on_event(TaskEvent::Failed(nid.clone(), error));
+                        report.failed.push(nid);
+                    }
+                    Err(payload) => {
+                        drop(job_sender);
+                        panic::resume_unwind(payload);
+                    }
+                }
+            }
+        });
+
+        // Only an abort leaves tasks which were never started.
+        if report.finished.len() + report.failed.len() + report.skipped.len() < graph.num_nodes() {
+            let done: FastHashSet<_> = report
+                .finished
+                .iter()
+                .chain(&report.failed)
+                .chain(&report.skipped)
+                .cloned()
+                .collect();
+            for nid in dag.topological_order() {
+                if !done.contains(&nid) {
+                    on_event(TaskEvent::Skipped(nid.clone()));
+                    report.skipped.push(nid);
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Counts the task of `nid` as done for each of its dependents, moving
+/// those with no more dependencies to `ready`, and calls `mark` with each
+/// dependent.
+fn release_dependents<G>(
+    graph: &G,
+    nid: &G::NodeId,
+    waiting: &mut FastHashMap<G::NodeId, usize>,
+    ready: &mut VecDeque<G::NodeId>,
+    mut mark: impl FnMut(G::NodeId),
+) where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    for eid in graph.edges_from(nid) {
+        let (_, into) = graph.edge_ends(&eid).into_values();
+        mark(into.clone());
+        let count = waiting.get_mut(&into).expect("graph has a cycle");
+        *count -= 1;
+        if *count == 0 {
+            waiting.remove(&into);
+            ready.push_back(into);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use crate::BagGraph;
+
+    use super::*;
+
+    #[test]
+    fn test_runs_in_dependency_order() {
+        let mut graph = BagGraph::<&str, (), Directed>::default();
+        let [a, b, c, d, e] = ["a", "b", "c", "d", "e"].map(|name| graph.add_node(name));
+        for (from, into) in [(&a, &b), (&a, &c), (&b, &d), (&c, &d)] {
+            graph.add_edge(from, into, ());
+        }
+        let dag = graph.check_acyclic().unwrap();
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let mut finished = Vec::new();
+        let report = Executor::new().with_parallelism(2).run(
+            dag,
+            |nid| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(5));
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, ()>(*graph.node_data(nid))
+            },
+            |event| {
+                if let TaskEvent::Finished(_, name) = event {
+                    finished.push(name);
+                }
+            },
+        );
+        assert!(report.is_success());
+        assert_eq!(report.finished.len(), 5);
+        assert!(max_running.load(Ordering::SeqCst) <= 2);
+        let position = |name| finished.iter().position(|&n| n == name).unwrap();
+        assert!(position("a") < position("b") && position("a") < position("c"));
+        assert!(position("b") < position("d") && position("c") < position("d"));
+        assert!(finished.contains(graph.node_data(&e)));
+    }
+
+    #[test]
+    fn test_failure_policies() {
+        let mut graph = BagGraph::<&str, (), Directed>::default();
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.add_node(name));
+        graph.add_edge(&a, &b, ());
+        graph.add_edge(&b, &c, ());
+        let task = |nid: &_| {
+            if *graph.node_data(nid) == "b" {
+                Err("b failed")
+            } else {
+                Ok(())
+            }
+        };
+
+        let mut events = Vec::new();
+        let report = Executor::new()
+            .with_parallelism(1)
+            .with_failure_policy(FailurePolicy::Continue)
+            .run(graph.check_acyclic().unwrap(), task, |event| {
+                if let TaskEvent::Failed(_, error) = event {
+                    events.push(error);
+                }
+            });
+        assert_eq!(events, vec!["b failed"]);
+        assert_eq!(report.failed, vec![b.clone()]);
+        assert_eq!(report.skipped, vec![c.clone()]);
+        assert_eq!(report.finished.len(), 2);
+        assert!(report.finished.contains(&d));
+
+        // Under the default policy, nothing is started after the failure.
+        let report =
+            Executor::new()
+                .with_parallelism(1)
+                .run(graph.check_acyclic().unwrap(), task, |_| {});
+        assert_eq!(report.failed, vec![b]);
+        assert!(report.skipped.contains(&c));
+        assert_eq!(
+            report.finished.len() + report.failed.len() + report.skipped.len(),
+            4
+        );
+        assert!(!report.is_success());
+    }
+}
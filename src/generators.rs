@@ -0,0 +1,301 @@
+//! Generators for common families of graphs.
+//!
+//! Each generator creates a new graph of any type implementing [`GraphMut`]
+//! and [`Default`].  Nodes are numbered from zero in the order they are
+//! added, and the data for each node and edge is supplied by closures which
+//! receive these numbers.  For a directed graph, edges go from the lower
+//! numbered node to the higher one unless stated otherwise.
+//!
+//! The random generators [`erdos_renyi`] and [`barabasi_albert`] require the
+//! `rand` feature.  They accept any [`Rng`], so a seeded generator can be
+//! used to get reproducible graphs.
+
+#[cfg(feature = "rand")]
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::prelude::*;
+
+/// Adds `n` nodes to `graph`, returning their IDs in order.
+fn add_nodes<G: GraphMut>(
+    graph: &mut G,
+    n: usize,
+    node_data_fn: impl FnMut(usize) -> G::NodeData,
+) -> Vec<G::NodeId> {
+    (0..n)
+        .map(node_data_fn)
+        .map(|data| graph.add_node(data))
+        .collect()
+}
+
+/// Creates a complete graph with `n` nodes, in which every pair of distinct
+/// nodes is connected.  A directed graph has edges in both directions.
+pub fn complete_graph<G>(
+    n: usize,
+    node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_data_fn: impl FnMut(usize, usize) -> G::EdgeData,
+) -> G
+where
+    G: GraphMut + Default,
+{
+    let mut graph = G::default();
+    let num_edges = if graph.is_directed() {
+        n * n.saturating_sub(1)
+    } else {
+        n * n.saturating_sub(1) / 2
+    };
+    graph.reserve(n, num_edges);
+    let nodes = add_nodes(&mut graph, n, node_data_fn);
+    for i in 0..n {
+        for j in 0..n {
+            if i < j || (i > j && graph.is_directed()) {
+                graph.add_edge(&nodes[i], &nodes[j], edge_data_fn(i, j));
+            }
+        }
+    }
+    graph
+}
+
+/// Creates a path graph with `n` nodes, in which node `i` is connected to
+/// node `i + 1`.
+pub fn path_graph<G>(
+    n: usize,
+    node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_data_fn: impl FnMut(usize, usize) -> G::EdgeData,
+) -> G
+where
+    G: GraphMut + Default,
+{
+    let mut graph = G::default();
+    graph.reserve(n, n.saturating_sub(1));
+    let nodes = add_nodes(&mut graph, n, node_data_fn);
+    for i in 1..n {
+        graph.add_edge(&nodes[i - 1], &nodes[i], edge_data_fn(i - 1, i));
+    }
+    graph
+}
+
+/// Creates a cycle graph with `n` nodes.  This is a [path graph](path_graph)
+/// with an additional edge from the last node to the first.  If `n` is less
+/// than 3, there is no additional edge.
+pub fn cycle_graph<G>(
+    n: usize,
+    node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_data_fn: impl FnMut(usize, usize) -> G::EdgeData,
+) -> G
+where
+    G: GraphMut + Default,
+{
+    let mut graph = G::default();
+    graph.reserve(n, n);
+    let nodes = add_nodes(&mut graph, n, node_data_fn);
+    for i in 1..n {
+        graph.add_edge(&nodes[i - 1], &nodes[i], edge_data_fn(i - 1, i));
+    }
+    if n >= 3 {
+        graph.add_edge(&nodes[n - 1], &nodes[0], edge_data_fn(n - 1, 0));
+    }
+    graph
+}
+
+/// Creates a grid graph with `width * height` nodes.  The node in column `x`
+/// and row `y` is numbered `y * width + x`, and is connected to the nodes to
+/// its right and below it.
+pub fn grid_graph<G>(
+    width: usize,
+    height: usize,
+    node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_data_fn: impl FnMut(usize, usize) -> G::EdgeData,
+) -> G
+where
+    G: GraphMut + Default,
+{
+    let mut graph = G::default();
+    let n = width * height;
+    graph.reserve(n, 2 * n);
+    let nodes = add_nodes(&mut graph, n, node_data_fn);
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            if x + 1 < width {
+                graph.add_edge(&nodes[i], &nodes[i + 1], edge_data_fn(i, i + 1));
+            }
+            if y + 1 < height {
+                graph.add_edge(&nodes[i], &nodes[i + width], edge_data_fn(i, i + width));
+            }
+        }
+    }
+    graph
+}
+
+/// Creates an Erdős–Rényi random graph with `n` nodes, in which each pair of
+/// distinct nodes is connected with probability `p`.  In a directed graph,
+/// each of the two possible edges between a pair is chosen independently.
+/// Panics if `p` is not between 0 and 1.
+#[cfg(feature = "rand")]
+pub fn erdos_renyi<G, R>(
+    n: usize,
+    p: f64,
+    rng: &mut R,
+    node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_data_fn: impl FnMut(usize, usize) -> G::EdgeData,
+) -> G
+where
+    G: GraphMut + Default,
+    R: Rng + ?Sized,
+{
+    assert!((0.0..=1.0).contains(&p), "p must be between 0 and 1");
+    let mut graph = G::default();
+    graph.reserve(n, 0);
+    let nodes = add_nodes(&mut graph, n, node_data_fn);
+    for i in 0..n {
+        for j in 0..n {
+            if (i < j || (i > j && graph.is_directed())) && rng.random_bool(p) {
+                graph.add_edge(&nodes[i], &nodes[j], edge_data_fn(i, j));
+            }
+        }
+    }
+    graph
+}
+
+/// Creates a Barabási–Albert scale-free random graph with `n` nodes using
+/// preferential attachment.
+///
+/// The graph starts with `m` nodes and no edges.  Each remaining node is
+/// connected to `m` distinct existing nodes, chosen with probability
+/// proportional to their degree, so the graph has `m * (n - m)` edges.  In a
+/// directed graph, edges go from each new node to the nodes it chose.
+/// Panics unless `0 < m < n`.
+#[cfg(feature = "rand")]
+pub fn barabasi_albert<G, R>(
+    n: usize,
+    m: usize,
+    rng: &mut R,
+    node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_data_fn: impl FnMut(usize, usize) -> G::EdgeData,
+) -> G
+where
+    G: GraphMut + Default,
+    R: Rng + ?Sized,
+{
+    assert!(0 < m && m < n, "m must be positive and less than n");
+    let mut graph = G::default();
+    graph.reserve(n, m * (n - m));
+    let nodes = add_nodes(&mut graph, n, node_data_fn);
+    // Each node appears in `repeated` once for every edge it is an end of, so
+    // choosing uniformly from it chooses nodes in proportion to their degree.
+    let mut repeated: Vec<usize> = Vec::with_capacity(2 * m * (n - m));
+    let mut targets: Vec<usize> = (0..m).collect();
+    for source in m..n {
+        for &target in &targets {
+            graph.add_edge(&nodes[source], &nodes[target], edge_data_fn(source, target));
+        }
+        repeated.extend(&targets);
+        repeated.extend(std::iter::repeat_n(source, m));
+        targets.clear();
+        while targets.len() < m {
+            let &target = repeated.choose(rng).expect("no candidate nodes");
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    type UndirectedGraph = BagGraph<usize, (), Undirected>;
+    type DirectedGraph = LinkedGraph<usize, (usize, usize), Directed>;
+
+    fn degrees<G: Graph<NodeData = usize>>(graph: &G) -> Vec<usize> {
+        let mut degrees = vec![0; graph.num_nodes()];
+        for id in graph.node_ids() {
+            degrees[*graph.node_data(&id)] = graph.num_edges_from(&id);
+        }
+        degrees
+    }
+
+    #[test]
+    fn test_complete_graph() {
+        let graph: UndirectedGraph = complete_graph(5, |i| i, |_, _| ());
+        assert_eq!(graph.num_nodes(), 5);
+        assert_eq!(graph.num_edges(), 10);
+        assert_eq!(degrees(&graph), vec![4; 5]);
+
+        let graph: DirectedGraph = complete_graph(4, |i| i, |i, j| (i, j));
+        assert_eq!(graph.num_edges(), 12);
+        assert!(graph.edge_ids().all(|eid| {
+            let (from, into) = graph.edge_ends(&eid);
+            *graph.edge_data(&eid) == (*graph.node_data(&from), *graph.node_data(&into))
+        }));
+    }
+
+    #[test]
+    fn test_path_and_cycle_graphs() {
+        let path: DirectedGraph = path_graph(4, |i| i, |i, j| (i, j));
+        assert_eq!(path.num_edges(), 3);
+        assert_eq!(degrees(&path), vec![1, 1, 1, 0]);
+
+        let cycle: DirectedGraph = cycle_graph(4, |i| i, |i, j| (i, j));
+        assert_eq!(cycle.num_edges(), 4);
+        assert_eq!(degrees(&cycle), vec![1; 4]);
+
+        let small: UndirectedGraph = cycle_graph(2, |i| i, |_, _| ());
+        assert_eq!(small.num_edges(), 1);
+        let empty: UndirectedGraph = cycle_graph(0, |i| i, |_, _| ());
+        assert_eq!(empty.num_nodes(), 0);
+    }
+
+    #[test]
+    fn test_grid_graph() {
+        let graph: UndirectedGraph = grid_graph(3, 2, |i| i, |_, _| ());
+        assert_eq!(graph.num_nodes(), 6);
+        assert_eq!(graph.num_edges(), 7);
+        assert_eq!(degrees(&graph), vec![2, 3, 2, 2, 3, 2]);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_erdos_renyi() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let empty: UndirectedGraph = erdos_renyi(10, 0.0, &mut rng, |i| i, |_, _| ());
+        assert_eq!(empty.num_edges(), 0);
+        let full: DirectedGraph = erdos_renyi(10, 1.0, &mut rng, |i| i, |i, j| (i, j));
+        assert_eq!(full.num_edges(), 90);
+        let half: UndirectedGraph = erdos_renyi(40, 0.5, &mut rng, |i| i, |_, _| ());
+        // The expected number of edges is 390.
+        assert!((300..480).contains(&half.num_edges()));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_barabasi_albert() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64(2);
+        let graph: UndirectedGraph = barabasi_albert(50, 3, &mut rng, |i| i, |_, _| ());
+        assert_eq!(graph.num_nodes(), 50);
+        assert_eq!(graph.num_edges(), 3 * 47);
+        assert!(degrees(&graph)[3..].iter().all(|&degree| degree >= 3));
+        // Every new node chooses distinct targets.
+        assert!(graph.node_ids().all(|id| {
+            let neighbors: Vec<_> = graph.successors(&id).collect();
+            let unique: std::collections::HashSet<_> = neighbors.iter().collect();
+            unique.len() == neighbors.len()
+        }));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    #[should_panic(expected = "less than n")]
+    fn test_barabasi_albert_invalid() {
+        let mut rng = <rand::rngs::SmallRng as rand::SeedableRng>::seed_from_u64(0);
+        let _: UndirectedGraph = barabasi_albert(3, 3, &mut rng, |i| i, |_, _| ());
+    }
+}
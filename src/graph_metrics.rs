@@ -0,0 +1,231 @@
+//! Metrics for long-lived graphs, reported through the [`metrics`] façade.
+//!
+//! A [`MetricsObserver`] is a [`GraphObserver`] which keeps gauges of the
+//! number of nodes and edges in a [`NotifyingGraph`], and counts the nodes
+//! and edges added and removed and the compactions performed.  Mutation
+//! rates are derived from the counters by the metrics backend, e.g. with
+//! Prometheus's `rate()`.  Every metric is labeled with `graph` set to the
+//! name given to the observer, so several graphs can be reported at once.
+//!
+//! The metrics are registered with whichever recorder is installed when the
+//! observer is created; if none is installed, they are discarded.
+
+use metrics::{Counter, Gauge, Unit, counter, describe_counter, describe_gauge, gauge};
+
+use crate::{
+    notifying_graph::{GraphEvent, GraphObserver, NotifyingGraph, ObserverId},
+    prelude::*,
+};
+
+/// The name of the gauge holding the number of nodes.
+pub const NODES: &str = "graphitude_nodes";
+/// The name of the gauge holding the number of edges.
+pub const EDGES: &str = "graphitude_edges";
+/// The name of the counter of nodes added.
+pub const NODES_ADDED: &str = "graphitude_nodes_added_total";
+/// The name of the counter of nodes removed.
+pub const NODES_REMOVED: &str = "graphitude_nodes_removed_total";
+/// The name of the counter of edges added.
+pub const EDGES_ADDED: &str = "graphitude_edges_added_total";
+/// The name of the counter of edges removed.
+pub const EDGES_REMOVED: &str = "graphitude_edges_removed_total";
+/// The name of the counter of compactions.
+pub const COMPACTIONS: &str = "graphitude_compactions_total";
+
+/// The label which identifies the graph a metric belongs to.
+pub const GRAPH_LABEL: &str = "graph";
+
+/// Describes the metrics reported by [`MetricsObserver`] to the installed
+/// recorder.  This only needs to be called once, and is optional.
+pub fn describe_metrics() {
+    describe_gauge!(NODES, Unit::Count, "Number of nodes in the graph.");
+    describe_gauge!(EDGES, Unit::Count, "Number of edges in the graph.");
+    describe_counter!(NODES_ADDED, Unit::Count, "Nodes added to the graph.");
+    describe_counter!(NODES_REMOVED, Unit::Count, "Nodes removed from the graph.");
+    describe_counter!(EDGES_ADDED, Unit::Count, "Edges added to the graph.");
+    describe_counter!(EDGES_REMOVED, Unit::Count, "Edges removed from the graph.");
+    describe_counter!(COMPACTIONS, Unit::Count, "Compactions of the graph.");
+}
+
+/// A [`GraphObserver`] which reports the size of a graph and the mutations
+/// made to it as metrics.  See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct MetricsObserver {
+    nodes: Gauge,
+    edges: Gauge,
+    nodes_added: Counter,
+    nodes_removed: Counter,
+    edges_added: Counter,
+    edges_removed: Counter,
+    compactions: Counter,
+}
+
+impl MetricsObserver {
+    /// Registers the metrics for a graph named `name` with the installed
+    /// recorder.  The gauges are not changed until the first event; use
+    /// [`NotifyingGraph::add_metrics_observer`] to start them at the size of
+    /// an existing graph.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            nodes: gauge!(NODES, GRAPH_LABEL => name.clone()),
+            edges: gauge!(EDGES, GRAPH_LABEL => name.clone()),
+            nodes_added: counter!(NODES_ADDED, GRAPH_LABEL => name.clone()),
+            nodes_removed: counter!(NODES_REMOVED, GRAPH_LABEL => name.clone()),
+            edges_added: counter!(EDGES_ADDED, GRAPH_LABEL => name.clone()),
+            edges_removed: counter!(EDGES_REMOVED, GRAPH_LABEL => name.clone()),
+            compactions: counter!(COMPACTIONS, GRAPH_LABEL => name),
+        }
+    }
+
+    /// Sets the gauges to the size of `graph`.
+    pub fn set_size<G>(&self, graph: &G)
+    where
+        G: Graph + ?Sized,
+    {
+        self.nodes.set(graph.num_nodes() as f64);
+        self.edges.set(graph.num_edges() as f64);
+    }
+}
+
+impl<G> GraphObserver<G> for MetricsObserver
+where
+    G: Graph + ?Sized,
+{
+    fn notify(&mut self, event: &GraphEvent<'_, G>) {
+        match event {
+            GraphEvent::NodeAdded { .. } => {
+                self.nodes.increment(1.0);
+                self.nodes_added.increment(1);
+            }
+            GraphEvent::NodeRemoved { .. } => {
+                self.nodes.decrement(1.0);
+                self.nodes_removed.increment(1);
+            }
+            GraphEvent::EdgeAdded { .. } => {
+                self.edges.increment(1.0);
+                self.edges_added.increment(1);
+            }
+            GraphEvent::EdgeRemoved { .. } => {
+                self.edges.decrement(1.0);
+                self.edges_removed.increment(1);
+            }
+            GraphEvent::Compacted { .. } => self.compactions.increment(1),
+        }
+    }
+}
+
+impl<G> NotifyingGraph<G>
+where
+    G: Graph,
+{
+    /// Registers a [`MetricsObserver`] for this graph under the given name,
+    /// with its gauges starting at the current size of the graph.
+    pub fn add_metrics_observer(&mut self, name: impl Into<String>) -> ObserverId {
+        let observer = MetricsObserver::new(name);
+        observer.set_size(self);
+        self.add_observer(observer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicU64, Ordering},
+        },
+    };
+
+    use metrics::{Histogram, Key, KeyName, Metadata, Recorder, SharedString};
+
+    use crate::{BagGraph, notifying_graph::CompactOn};
+
+    use super::*;
+
+    /// A recorder which keeps the value of each metric as an `AtomicU64`.
+    #[derive(Default)]
+    struct TestRecorder {
+        values: Mutex<HashMap<Key, Arc<AtomicU64>>>,
+    }
+
+    impl TestRecorder {
+        fn bits(&self, name: &'static str, graph: &'static str) -> u64 {
+            let key = Key::from_parts(name, vec![metrics::Label::new(GRAPH_LABEL, graph)]);
+            self.values.lock().unwrap()[&key].load(Ordering::Relaxed)
+        }
+
+        fn gauge(&self, name: &'static str, graph: &'static str) -> f64 {
+            f64::from_bits(self.bits(name, graph))
+        }
+
+        fn counter(&self, name: &'static str, graph: &'static str) -> u64 {
+            self.bits(name, graph)
+        }
+
+        fn register(&self, key: &Key) -> Arc<AtomicU64> {
+            self.values
+                .lock()
+                .unwrap()
+                .entry(key.clone())
+                .or_default()
+                .clone()
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.register(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.register(key))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn test_metrics_observer() {
+        let recorder = TestRecorder::default();
+        let mut inner = BagGraph::<(), (), Directed>::default();
+        let a = inner.add_node(());
+        let mut graph = NotifyingGraph::new(inner);
+        let mut other = NotifyingGraph::<BagGraph<(), (), Directed>>::default();
+        metrics::with_local_recorder(&recorder, || {
+            describe_metrics();
+            graph.add_metrics_observer("main");
+            other.add_metrics_observer("other");
+        });
+        graph.set_compaction_policy(CompactOn::Removals(2));
+        assert_eq!(recorder.gauge(NODES, "main"), 1.0);
+
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&b, &c, ());
+        other.add_node(());
+        assert_eq!(recorder.gauge(NODES, "main"), 3.0);
+        assert_eq!(recorder.gauge(EDGES, "main"), 2.0);
+        assert_eq!(recorder.counter(NODES_ADDED, "main"), 2);
+        assert_eq!(recorder.counter(EDGES_ADDED, "main"), 2);
+
+        // Removing `b` removes both edges, which triggers a compaction.
+        graph.remove_node(&b);
+        assert_eq!(recorder.gauge(NODES, "main"), 2.0);
+        assert_eq!(recorder.gauge(EDGES, "main"), 0.0);
+        assert_eq!(recorder.counter(NODES_REMOVED, "main"), 1);
+        assert_eq!(recorder.counter(EDGES_REMOVED, "main"), 2);
+        assert_eq!(recorder.counter(COMPACTIONS, "main"), 1);
+
+        assert_eq!(recorder.gauge(NODES, "other"), 1.0);
+        assert_eq!(recorder.counter(COMPACTIONS, "other"), 0);
+    }
+}
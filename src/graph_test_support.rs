@@ -5,7 +5,6 @@ use std::hash::Hash;
 use quickcheck::Arbitrary;
 use tracing::info_span;
 
-use crate::end_pair::EndPair as _;
 use crate::prelude::*;
 use crate::tracing_support::{TimingScope, init_tracing, set_timing_scope};
 
@@ -39,7 +38,11 @@ where
         for ((from, into), data) in edge_data.iter() {
             let from_id = &node_ids[*from];
             let into_id = &node_ids[*into];
-            edge_ids.push(graph.add_edge(from_id, into_id, data.clone()).0);
+            edge_ids.push(
+                graph
+                    .add_edge(from_id, into_id, data.clone())
+                    .into_edge_id(),
+            );
         }
         Self {
             graph,
@@ -149,120 +152,11 @@ pub fn check_graph_consistency<G: Graph>(graph: &G) {
         return;
     }
 
-    // Verify all nodes are valid
-    for node_id in graph.node_ids() {
-        let num_from = {
-            let _span = info_span!("num_edges_from").entered();
-            graph.num_edges_from(&node_id)
-        };
-
-        let edges_from_count = {
-            let _span = info_span!("edges_from.count").entered();
-            graph.edges_from(&node_id).count()
-        };
-
-        let num_into = {
-            let _span = info_span!("num_edges_into").entered();
-            graph.num_edges_into(&node_id)
-        };
-
-        let edges_into_count = {
-            let _span = info_span!("edges_into.count").entered();
-            graph.edges_into(&node_id).count()
-        };
-
-        assert_eq!(num_from, edges_from_count);
-        assert_eq!(num_into, edges_into_count);
-
-        let has_from = {
-            let _span = info_span!("has_edge_from").entered();
-            graph.has_edge_from(&node_id)
-        };
-
-        let has_into = {
-            let _span = info_span!("has_edge_into").entered();
-            graph.has_edge_into(&node_id)
-        };
-
-        assert_eq!(has_from, num_from > 0);
-        assert_eq!(has_into, num_into > 0);
-    }
-
-    // Verify all edges are valid
-    for edge_id in graph.edge_ids() {
-        let (left, right) = graph.edge_ends(&edge_id).into_values();
-        assert_eq!(
-            graph.edges_from_into(&left, &right).count(),
-            graph
-                .edges_from_into(&left, &right)
-                .collect::<HashSet<_>>()
-                .len()
-        );
-
-        if !graph.is_directed() {
-            assert!(graph.has_edge_from_into(&right, &left))
-        }
-        if !graph.allows_parallel_edges() {
-            assert_eq!(graph.num_edges_from_into(&left, &right), 1);
-        }
-
-        {
-            let _span = info_span!("has_edge").entered();
-            let has_edge = graph.has_edge_from_into(&left, &right);
-            assert!(has_edge);
-        }
-
-        {
-            let _span = info_span!("edges_between.any").entered();
-            let between_has = graph.edges_from_into(&left, &right).any(|e| e == edge_id);
-            assert!(between_has);
-        }
-
-        {
-            let _span = info_span!("edges_from.any").entered();
-            let from_has = graph.edges_from(&left).any(|e| e == edge_id);
-            assert!(from_has);
-        }
-
-        {
-            let _span = info_span!("edges_into.any").entered();
-            let into_has = graph.edges_into(&right).any(|e| e == edge_id);
-            assert!(into_has);
-        }
-
-        let num_from = {
-            let _span = info_span!("num_edges_from").entered();
-            graph.num_edges_from(&left)
-        };
-
-        let edges_from_count = {
-            let _span = info_span!("edges_from.count").entered();
-            graph.edges_from(&left).count()
-        };
-
-        let num_into = {
-            let _span = info_span!("num_edges_into").entered();
-            graph.num_edges_into(&right)
-        };
-
-        let edges_into_count = {
-            let _span = info_span!("edges_into.count").entered();
-            graph.edges_into(&right).count()
-        };
-
-        assert_eq!(num_from, edges_from_count);
-        assert_eq!(num_into, edges_into_count);
-    }
-
-    // Verify node and edge IDs are unique.
-    let node_ids: HashSet<_> = graph.node_ids().collect();
-    assert_eq!(node_ids.len(), graph.node_ids().count());
-    let edge_ids: HashSet<_> = graph.edge_ids().collect();
-    assert_eq!(edge_ids.len(), graph.edge_ids().count());
-
-    // Verify counts are correct
-    assert_eq!(graph.node_ids().count(), graph.num_nodes(),);
-    assert_eq!(graph.edge_ids().count(), graph.num_edges(),);
+    let violations = {
+        let _span = info_span!("validate").entered();
+        graph.validate()
+    };
+    assert!(violations.is_empty(), "{violations:#?}");
 
     // Check is_empty consistency
     assert_eq!(graph.is_empty(), graph.num_nodes() == 0);
@@ -7,6 +7,7 @@ use quickcheck::TestResult;
 use crate::end_pair::EndPair as _;
 use crate::generate_large_graph::generate_large_graph;
 use crate::graph_test_support::{ArbGraph, check_graph_consistency, has_duplicates};
+use crate::notifying_graph::{CompactOn, NotifyingGraph};
 use crate::{GraphCopier, prelude::*};
 
 #[doc(hidden)]
@@ -562,7 +563,7 @@ where
         let ed1 = self.new_edge_data();
         let n1 = graph.add_node(nd1);
         let n2 = graph.add_node(nd2);
-        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).0;
+        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).into_edge_id();
         assert_eq!(*graph.edge_data(&e1), ed1);
     }
 
@@ -575,7 +576,7 @@ where
         let ed2 = self.new_edge_data();
         let n1 = graph.add_node(nd1);
         let n2 = graph.add_node(nd2);
-        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).0;
+        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).into_edge_id();
         *graph.edge_data_mut(&e1) = ed2.clone();
         assert_eq!(*graph.edge_data(&e1), ed2);
     }
@@ -593,8 +594,8 @@ where
         let n1 = graph.add_node(nd1);
         let n2 = graph.add_node(nd2);
         let n3 = graph.add_node(nd3);
-        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).0;
-        let e2 = graph.add_edge(&n2, &n3, ed2.clone()).0;
+        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).into_edge_id();
+        let e2 = graph.add_edge(&n2, &n3, ed2.clone()).into_edge_id();
 
         // Check edges_from and num_edges_from for each node
         if graph.is_directed() {
@@ -677,8 +678,8 @@ where
         let n1 = graph.add_node(nd1);
         let n2 = graph.add_node(nd2);
         let n3 = graph.add_node(nd3);
-        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).0;
-        let e2 = graph.add_edge(&n1, &n3, ed2.clone()).0;
+        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).into_edge_id();
+        let e2 = graph.add_edge(&n1, &n3, ed2.clone()).into_edge_id();
 
         let edge_ids: Vec<_> = graph.edge_ids().collect();
         assert_eq!(edge_ids.len(), 2);
@@ -698,10 +699,10 @@ where
         let n2 = graph.add_node(nd2.clone());
 
         // Normal edge.
-        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).0;
+        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).into_edge_id();
         assert_eq!(graph.num_edges(), 1);
         // Self edge.
-        let e2 = graph.add_edge(&n1, &n1, ed2.clone()).0;
+        let e2 = graph.add_edge(&n1, &n1, ed2.clone()).into_edge_id();
         assert_eq!(graph.num_edges(), 2);
         // Duplicate edge.
         let add_3 = graph.add_edge(&n1, &n2, ed1.clone());
@@ -715,10 +716,10 @@ where
         assert!(edges_from_n1.contains(&e1));
         assert!(edges_from_n1.contains(&e2));
         if graph.allows_parallel_edges() {
-            assert!(edges_from_n1.contains(&add_3.clone().0));
+            assert!(edges_from_n1.contains(add_3.edge_id()));
             assert_eq!(edges_from_n1.len(), 3);
         } else {
-            assert!(matches!(&add_3, (_, Some((_, data))) if *data == ed1));
+            assert!(matches!(&add_3, AddEdgeResult::Updated { old_data, .. } if *old_data == ed1));
             dbg!(&edges_from_n1);
             assert_eq!(edges_from_n1.len(), 2);
         }
@@ -730,10 +731,12 @@ where
         } else {
             assert!(edges_into_n1.contains(&e1));
             if graph.allows_parallel_edges() {
-                assert!(edges_into_n1.contains(&add_3.0));
+                assert!(edges_into_n1.contains(add_3.edge_id()));
                 assert_eq!(edges_into_n1.len(), 3);
             } else {
-                assert!(matches!(&add_3, (_, Some((_, data))) if *data == ed1));
+                assert!(
+                    matches!(&add_3, AddEdgeResult::Updated { old_data, .. } if *old_data == ed1)
+                );
                 assert_eq!(edges_into_n1.len(), 2);
             }
         }
@@ -795,6 +798,53 @@ where
         assert_eq!(graph.num_edges(), 0);
     }
 
+    #[test]
+    pub fn test_remove_node_cleans_edges_into(&mut self) {
+        let mut graph = self.new_graph();
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let n3 = graph.add_node(self.new_node_data());
+        graph.add_edge(&n1, &n2, self.new_edge_data());
+        graph.add_edge(&n3, &n2, self.new_edge_data());
+
+        graph.remove_node(&n1);
+        assert_eq!(graph.num_edges_into(&n2), 1);
+        assert_eq!(graph.edges_into(&n2).count(), 1);
+        check_graph_consistency(&graph);
+
+        graph.compact(None, None);
+        assert_eq!(graph.num_edges(), 1);
+        check_graph_consistency(&graph);
+    }
+
+    #[test]
+    pub fn test_remove_node_with_edges(&mut self) {
+        let mut graph = self.new_graph();
+        let nd1 = self.new_node_data();
+        let n1 = graph.add_node(nd1.clone());
+        let n2 = graph.add_node(self.new_node_data());
+        let n3 = graph.add_node(self.new_node_data());
+        let expected = [(&n1, &n2), (&n3, &n1), (&n1, &n1)].map(|(from, into)| {
+            let data = self.new_edge_data();
+            graph.add_edge(from, into, data.clone());
+            (
+                <G::Directedness as Directedness>::make_pair(from.clone(), into.clone()),
+                data,
+            )
+        });
+        graph.add_edge(&n2, &n3, self.new_edge_data());
+
+        let (data, edges) = graph.remove_node_with_edges(&n1);
+        assert_eq!(data, nd1);
+        assert_eq!(edges.len(), expected.len());
+        for edge in &expected {
+            assert!(edges.contains(edge));
+        }
+        assert_eq!(graph.check_node_id(&n1), Err(GraphError::InvalidNodeId));
+        assert_eq!(graph.num_nodes(), 2);
+        assert_eq!(graph.num_edges(), 1);
+    }
+
     #[test]
     pub fn test_edges_from(&mut self) {
         use std::collections::HashSet;
@@ -805,11 +855,21 @@ where
         let n2 = graph.add_node(self.new_node_data());
         let n3 = graph.add_node(self.new_node_data());
 
-        let e0 = graph.add_edge(&n0, &n1, self.new_edge_data()).0;
-        let e1 = graph.add_edge(&n0, &n2, self.new_edge_data()).0;
-        let e2 = graph.add_edge(&n1, &n2, self.new_edge_data()).0;
-        let e3 = graph.add_edge(&n1, &n3, self.new_edge_data()).0;
-        let e4 = graph.add_edge(&n2, &n3, self.new_edge_data()).0;
+        let e0 = graph
+            .add_edge(&n0, &n1, self.new_edge_data())
+            .into_edge_id();
+        let e1 = graph
+            .add_edge(&n0, &n2, self.new_edge_data())
+            .into_edge_id();
+        let e2 = graph
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        let e3 = graph
+            .add_edge(&n1, &n3, self.new_edge_data())
+            .into_edge_id();
+        let e4 = graph
+            .add_edge(&n2, &n3, self.new_edge_data())
+            .into_edge_id();
         // Check edges_from for all nodes
         assert_eq!(
             graph.edges_from(&n0).collect::<HashSet<_>>(),
@@ -861,6 +921,33 @@ where
         }
     }
 
+    #[test]
+    pub fn test_fold_edges_by_source(&mut self) {
+        use std::collections::HashSet;
+
+        let mut graph = self.new_graph();
+        let n: Vec<_> = (0..5)
+            .map(|_| graph.add_node(self.new_node_data()))
+            .collect();
+        graph.add_edge(&n[0], &n[1], self.new_edge_data());
+        graph.add_edge(&n[1], &n[2], self.new_edge_data());
+        graph.add_edge(&n[1], &n[3], self.new_edge_data());
+        graph.add_edge(&n[3], &n[3], self.new_edge_data());
+        graph.add_edge(&n[4], &n[1], self.new_edge_data());
+        graph.remove_node(&n[0]);
+
+        let folded = graph.fold_edges_by_source(
+            |_| HashSet::new(),
+            |acc, eid, _| {
+                assert!(acc.insert(eid.clone()));
+            },
+        );
+        assert_eq!(folded.len(), graph.num_nodes());
+        for nid in graph.node_ids() {
+            assert_eq!(folded[&nid], graph.edges_from(&nid).collect::<HashSet<_>>());
+        }
+    }
+
     #[test]
     pub fn test_edges_into(&mut self) {
         let mut graph = self.new_graph();
@@ -870,7 +957,7 @@ where
 
         let n1 = graph.add_node(nd1);
         let n2 = graph.add_node(nd2);
-        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).0;
+        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).into_edge_id();
 
         // Check edges_into and num_edges_into
         assert_eq!(graph.edges_into(&n2).collect::<Vec<_>>(), vec![e1.clone()]);
@@ -901,7 +988,7 @@ where
 
         let n1 = graph.add_node(nd1);
         let n2 = graph.add_node(nd2);
-        let e1 = graph.add_edge(&n1, &n2, ed1).0;
+        let e1 = graph.add_edge(&n1, &n2, ed1).into_edge_id();
 
         assert_eq!(graph.num_edges_from_into(&n1, &n2), 1);
         assert_eq!(
@@ -924,8 +1011,12 @@ where
         let n1 = source.add_node(self.new_node_data());
         let n2 = source.add_node(self.new_node_data());
         let n3 = source.add_node(self.new_node_data());
-        let e1 = source.add_edge(&n1, &n2, self.new_edge_data()).0;
-        let e2 = source.add_edge(&n2, &n3, self.new_edge_data()).0;
+        let e1 = source
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        let e2 = source
+            .add_edge(&n2, &n3, self.new_edge_data())
+            .into_edge_id();
 
         let mut node_map = HashMap::new();
         let mut edge_map = HashMap::new();
@@ -945,6 +1036,248 @@ where
         assert_eq!(source.edge_data(&e2), target.edge_data(&edge_map[&e2]));
     }
 
+    #[test]
+    pub fn test_copy_reversed(&mut self) {
+        let mut source = self.new_graph();
+        let n1 = source.add_node(self.new_node_data());
+        let n2 = source.add_node(self.new_node_data());
+        let n3 = source.add_node(self.new_node_data());
+        let e1 = source
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        let e2 = source
+            .add_edge(&n2, &n3, self.new_edge_data())
+            .into_edge_id();
+
+        let mut node_map = HashMap::new();
+        let mut edge_map = HashMap::new();
+        let target = GraphCopier::new(&source)
+            .clone_nodes()
+            .clone_edges()
+            .reverse_edges()
+            .with_node_map(&mut node_map)
+            .with_edge_map(&mut edge_map)
+            .copy::<G>();
+
+        assert_eq!(target.num_edges(), 2);
+        for (eid, from, into) in [(&e1, &n1, &n2), (&e2, &n2, &n3)] {
+            assert_eq!(source.edge_data(eid), target.edge_data(&edge_map[eid]));
+            assert!(target.has_edge_from_into(&node_map[into], &node_map[from]));
+            assert_eq!(
+                target.has_edge_from_into(&node_map[from], &node_map[into]),
+                !target.is_directed()
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_reverse_edge(&mut self) {
+        let mut graph = self.new_graph();
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let data = self.new_edge_data();
+        let e1 = graph.add_edge(&n1, &n2, data.clone()).into_edge_id();
+
+        let result = graph.reverse_edge(&e1);
+        assert!(result.is_added());
+        let e2 = result.into_edge_id();
+        assert_eq!(graph.num_edges(), 1);
+        assert_eq!(graph.edge_data(&e2), &data);
+        assert!(graph.has_edge_from_into(&n2, &n1));
+        assert_eq!(graph.has_edge_from_into(&n1, &n2), !graph.is_directed());
+
+        if graph.is_directed() {
+            // Reversing onto an existing edge replaces it in a graph without
+            // parallel edges.
+            let e3 = graph
+                .add_edge(&n1, &n2, self.new_edge_data())
+                .into_edge_id();
+            let result = graph.reverse_edge(&e3);
+            assert_eq!(result.is_updated(), !graph.allows_parallel_edges());
+            assert_eq!(graph.num_edges_from_into(&n2, &n1), graph.num_edges());
+        }
+    }
+
+    #[test]
+    pub fn test_add_edge_result(&mut self) {
+        let mut graph = self.new_graph();
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let data1 = self.new_edge_data();
+        let data2 = self.new_edge_data();
+        let e1 = graph.add_edge(&n1, &n2, data1.clone()).unwrap();
+        let result = graph.add_edge(&n1, &n2, data2.clone());
+        if graph.allows_parallel_edges() {
+            assert!(result.is_added());
+            assert_ne!(*result.edge_id(), e1);
+            assert_eq!(graph.num_edges(), 2);
+        } else {
+            assert_eq!(result.clone().updated(), Some((e1, data1)));
+            assert_eq!(graph.num_edges(), 1);
+        }
+        assert_eq!(*graph.edge_data(result.edge_id()), data2);
+    }
+
+    #[test]
+    pub fn test_add_edge_strict(&mut self) {
+        let mut graph = self.new_graph();
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let data1 = self.new_edge_data();
+        let e1 = graph
+            .add_edge_strict(&n1, &n2, data1.clone())
+            .expect("no edge exists");
+        let result = graph.add_edge_strict(&n2, &n1, self.new_edge_data());
+        if graph.allows_parallel_edges() || graph.is_directed() {
+            assert!(result.is_ok_and(|id| id != e1));
+            assert_eq!(graph.num_edges(), 2);
+        } else {
+            assert_eq!(result, Err(GraphError::DuplicateEdge));
+            assert_eq!(graph.num_edges(), 1);
+        }
+        let result = graph.add_edge_strict(&n1, &n2, self.new_edge_data());
+        if graph.allows_parallel_edges() {
+            assert!(result.is_ok());
+        } else {
+            assert_eq!(result, Err(GraphError::DuplicateEdge));
+            assert_eq!(graph.edge_data(&e1), &data1);
+        }
+
+        graph.remove_node(&n2);
+        assert_eq!(
+            graph.add_edge_strict(&n1, &n2, self.new_edge_data()),
+            Err(GraphError::InvalidNodeId)
+        );
+    }
+
+    #[test]
+    pub fn test_parallel_edge_indices(&mut self) {
+        let mut graph = self.new_graph();
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let n3 = graph.add_node(self.new_node_data());
+        let data: Vec<_> = (0..3).map(|_| self.new_edge_data()).collect();
+        for d in &data {
+            graph.add_edge(&n1, &n2, d.clone());
+        }
+        graph.add_edge(&n1, &n3, self.new_edge_data());
+
+        let edges = graph.parallel_edges(&n1, &n2);
+        let expected = if graph.allows_parallel_edges() { 3 } else { 1 };
+        assert_eq!(edges.len(), expected);
+        if graph.allows_parallel_edges() {
+            // Parallel edges are indexed in the order they were added.
+            let edge_data: Vec<_> = edges
+                .iter()
+                .map(|eid| graph.edge_data(eid).clone())
+                .collect();
+            assert_eq!(edge_data, data);
+        }
+        for (index, eid) in edges.iter().enumerate() {
+            assert_eq!(graph.edge_index_between(eid), index);
+            assert_eq!(graph.nth_edge_between(&n1, &n2, index).as_ref(), Some(eid));
+        }
+        assert_eq!(graph.nth_edge_between(&n1, &n2, expected), None);
+        if !graph.is_directed() {
+            assert_eq!(graph.parallel_edges(&n2, &n1), edges);
+        }
+
+        let removed_data = graph.edge_data(&edges[0]).clone();
+        assert_eq!(
+            graph.remove_nth_edge_between(&n1, &n2, 0),
+            Some(removed_data)
+        );
+        assert_eq!(
+            graph.check_edge_id(&edges[0]),
+            Err(GraphError::InvalidEdgeId)
+        );
+        for (index, eid) in edges[1..].iter().enumerate() {
+            assert_eq!(graph.edge_index_between(eid), index);
+        }
+        assert_eq!(graph.parallel_edges(&n1, &n2), edges[1..].to_vec());
+        assert_eq!(graph.remove_nth_edge_between(&n1, &n2, expected), None);
+        assert_eq!(graph.num_edges_from(&n1), expected);
+    }
+
+    #[test]
+    pub fn test_try_add_and_remove(&mut self) {
+        let mut graph = self.new_graph();
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let n3 = graph.add_node(self.new_node_data());
+        let e1 = graph
+            .try_add_edge(&n1, &n2, self.new_edge_data())
+            .expect("ends are valid")
+            .into_edge_id();
+        assert_eq!(graph.check_edge_id(&e1), Ok(()));
+
+        graph.remove_node(&n3);
+        assert_eq!(graph.check_node_id(&n3), Err(GraphError::InvalidNodeId));
+        assert_eq!(
+            graph
+                .try_add_edge(&n1, &n3, self.new_edge_data())
+                .map(|_| ()),
+            Err(GraphError::InvalidNodeId)
+        );
+        assert_eq!(graph.num_edges(), 1);
+        assert_eq!(graph.try_remove_node(&n3), Err(GraphError::InvalidNodeId));
+
+        assert!(graph.try_remove_edge(&e1).is_ok());
+        assert_eq!(graph.try_remove_edge(&e1), Err(GraphError::InvalidEdgeId));
+        assert_eq!(graph.num_nodes(), 2);
+    }
+
+    #[test]
+    pub fn test_get_data(&mut self) {
+        let mut graph = self.new_graph();
+        let nd1 = self.new_node_data();
+        let ed1 = self.new_edge_data();
+        let n1 = graph.add_node(nd1.clone());
+        let n2 = graph.add_node(self.new_node_data());
+        let e1 = graph.add_edge(&n1, &n2, ed1.clone()).into_edge_id();
+        assert_eq!(graph.get_node_data(&n1), Some(&nd1));
+        assert_eq!(graph.get_edge_data(&e1), Some(&ed1));
+        assert!(graph.get_node_data_mut(&n2).is_some());
+        assert!(graph.get_edge_data_mut(&e1).is_some());
+
+        graph.remove_node(&n2);
+        assert_eq!(graph.get_node_data(&n2), None);
+        assert_eq!(graph.get_edge_data(&e1), None);
+        assert!(graph.get_node_data_mut(&n2).is_none());
+        assert!(graph.get_edge_data_mut(&e1).is_none());
+        assert_eq!(graph.get_node_data(&n1), Some(&nd1));
+    }
+
+    #[test]
+    pub fn test_contains(&mut self) {
+        let mut graph = self.new_graph();
+        assert!(graph.is_empty());
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let e1 = graph
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        assert!(!graph.is_empty());
+        assert!(graph.contains_node(&n1));
+        assert!(graph.contains_node(&n2));
+        assert!(graph.contains_edge(&e1));
+
+        graph.remove_edge(&e1);
+        assert!(!graph.contains_edge(&e1));
+        assert!(graph.contains_node(&n2));
+
+        let e2 = graph
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        graph.remove_node(&n2);
+        assert!(!graph.contains_node(&n2));
+        assert!(!graph.contains_edge(&e2));
+        assert!(!graph.is_empty());
+
+        graph.clear();
+        assert!(graph.is_empty());
+    }
+
     #[test]
     pub fn test_clear(&mut self) {
         let mut graph = self.new_graph();
@@ -964,6 +1297,77 @@ where
         assert!(graph.is_empty());
     }
 
+    #[test]
+    pub fn test_counts_match_ids(&mut self) {
+        fn check<G: Graph>(graph: &G) {
+            assert_eq!(graph.num_nodes(), graph.node_ids().count());
+            assert_eq!(graph.num_edges(), graph.edge_ids().count());
+        }
+
+        let mut graph = self.new_graph();
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+        let n3 = graph.add_node(self.new_node_data());
+        check(&graph);
+
+        let e1 = graph
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        graph.add_edge(&n2, &n1, self.new_edge_data());
+        graph.add_edge(&n1, &n2, self.new_edge_data());
+        graph.add_edge(&n2, &n2, self.new_edge_data());
+        graph.add_edge(&n3, &n2, self.new_edge_data());
+        graph.add_edge(&n1, &n3, self.new_edge_data());
+        check(&graph);
+
+        if graph.check_edge_id(&e1).is_ok() {
+            graph.remove_edge(&e1);
+            check(&graph);
+        }
+
+        graph.remove_node(&n2);
+        check(&graph);
+        assert_eq!(graph.num_nodes(), 2);
+
+        let n = graph.node_ids().next().unwrap();
+        graph.add_edge(&n, &n, self.new_edge_data());
+        check(&graph);
+
+        graph.clear();
+        check(&graph);
+    }
+
+    #[test]
+    pub fn test_notifying_batch_removal_with_compaction(&mut self) {
+        // Compacting after every removal would invalidate the IDs a batch
+        // removal has yet to process.
+        let mut graph = NotifyingGraph::new(self.new_graph());
+        graph.set_compaction_policy(CompactOn::Removals(1));
+        let n1 = graph.add_node(self.new_node_data());
+        let nd2 = self.new_node_data();
+        let n2 = graph.add_node(nd2.clone());
+        let n3 = graph.add_node(self.new_node_data());
+        graph.add_edge(&n1, &n2, self.new_edge_data());
+        graph.add_edge(&n2, &n1, self.new_edge_data());
+        graph.add_edge(&n2, &n2, self.new_edge_data());
+        graph.add_edge(&n3, &n2, self.new_edge_data());
+        graph.add_edge(&n1, &n3, self.new_edge_data());
+        let num_edges = graph.num_edges();
+
+        let (data, edges) = graph.remove_node_with_edges(&n2);
+        assert_eq!(data, nd2);
+        assert_eq!(edges.len(), num_edges - graph.num_edges());
+        assert_eq!(graph.num_nodes(), 2);
+        check_graph_consistency(&graph);
+
+        let nids = graph.node_ids().collect::<Vec<_>>();
+        graph.add_edge(&nids[0], &nids[1], self.new_edge_data());
+        graph.add_edge(&nids[1], &nids[1], self.new_edge_data());
+        graph.clear();
+        assert!(graph.is_empty());
+        assert_eq!(graph.num_edges(), 0);
+    }
+
     #[test]
     pub fn test_successors(&mut self) {
         let mut graph = self.new_graph();
@@ -972,11 +1376,21 @@ where
         let n2 = graph.add_node(self.new_node_data());
         let n3 = graph.add_node(self.new_node_data());
 
-        let e0 = graph.add_edge(&n0, &n1, self.new_edge_data()).0;
-        let e1 = graph.add_edge(&n0, &n2, self.new_edge_data()).0;
-        let e2 = graph.add_edge(&n1, &n2, self.new_edge_data()).0;
-        let e3 = graph.add_edge(&n1, &n3, self.new_edge_data()).0;
-        let e4 = graph.add_edge(&n2, &n3, self.new_edge_data()).0;
+        let e0 = graph
+            .add_edge(&n0, &n1, self.new_edge_data())
+            .into_edge_id();
+        let e1 = graph
+            .add_edge(&n0, &n2, self.new_edge_data())
+            .into_edge_id();
+        let e2 = graph
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        let e3 = graph
+            .add_edge(&n1, &n3, self.new_edge_data())
+            .into_edge_id();
+        let e4 = graph
+            .add_edge(&n2, &n3, self.new_edge_data())
+            .into_edge_id();
         if graph.is_directed() {
             assert_eq!(graph.edge_ends(&e0).into_values(), (n0.clone(), n1.clone()));
             assert_eq!(graph.edge_ends(&e1).into_values(), (n0.clone(), n2.clone()));
@@ -1117,6 +1531,52 @@ where
         }
     }
 
+    #[test]
+    pub fn test_degree_and_neighbors(&mut self) {
+        let mut graph = self.new_graph();
+        let n0 = graph.add_node(self.new_node_data());
+        let n1 = graph.add_node(self.new_node_data());
+        let n2 = graph.add_node(self.new_node_data());
+
+        graph.add_edge(&n0, &n1, self.new_edge_data());
+        graph.add_edge(&n1, &n2, self.new_edge_data());
+        graph.add_edge(&n2, &n1, self.new_edge_data());
+
+        let neighbors =
+            |graph: &G, node, direction| graph.neighbors(node, direction).collect::<HashSet<_>>();
+        assert_eq!(
+            neighbors(&graph, &n1, Direction::Both),
+            HashSet::from([n0.clone(), n2.clone()])
+        );
+        assert_eq!(graph.neighbors(&n1, Direction::Both).count(), 2);
+        if graph.is_directed() {
+            assert_eq!(
+                neighbors(&graph, &n1, Direction::In),
+                HashSet::from([n0.clone(), n2.clone()])
+            );
+            assert_eq!(
+                neighbors(&graph, &n1, Direction::Out),
+                HashSet::from([n2.clone()])
+            );
+            assert_eq!(neighbors(&graph, &n0, Direction::In), HashSet::new());
+            assert_eq!(graph.degree(&n1, Direction::In), 2);
+            assert_eq!(graph.degree(&n1, Direction::Out), 1);
+            assert_eq!(graph.degree(&n1, Direction::Both), 3);
+        } else {
+            for direction in [Direction::In, Direction::Out] {
+                assert_eq!(
+                    neighbors(&graph, &n1, direction),
+                    HashSet::from([n0.clone(), n2.clone()])
+                );
+            }
+            let edges = graph.num_edges_from(&n1);
+            for direction in [Direction::In, Direction::Out, Direction::Both] {
+                assert_eq!(graph.degree(&n1, direction), edges);
+            }
+        }
+        assert_eq!(graph.degree(&n0, Direction::Both), 1);
+    }
+
     #[cfg(feature = "pathfinding")]
     #[test]
     pub fn test_shortest_paths(&mut self) {
@@ -1150,6 +1610,28 @@ where
         assert_eq!(paths[&n3].1, 2);
     }
 
+    #[cfg(feature = "pathfinding")]
+    #[test]
+    pub fn test_shortest_paths_weighted(&mut self) {
+        let mut graph = self.new_graph();
+        let n: Vec<_> = (0..4)
+            .map(|_| graph.add_node(self.new_node_data()))
+            .collect();
+
+        let mut weights = HashMap::new();
+        for (from, into, weight) in [(0, 1, 1), (1, 2, 1), (2, 3, 1), (0, 3, 5), (0, 2, 3)] {
+            let eid = graph
+                .add_edge(&n[from], &n[into], self.new_edge_data())
+                .into_edge_id();
+            weights.insert(eid, weight);
+        }
+
+        let paths = graph.shortest_paths(&n[0], |eid| weights[eid]);
+        assert_eq!(paths[&n[2]].1, 2);
+        assert_eq!(paths[&n[3]].1, 3);
+        assert_eq!(paths[&n[3]].0.nodes().collect::<Vec<_>>(), n);
+    }
+
     #[cfg(feature = "pathfinding")]
     #[test]
     pub fn test_shortest_paths_disconnected(&mut self) {
@@ -1178,9 +1660,9 @@ where
         let n1 = graph.add_node(nd1.clone());
         let n2 = graph.add_node(nd2.clone());
         let n3 = graph.add_node(self.new_node_data());
-        let e1 = graph.add_edge(&n1, &n1, ed1.clone()).0;
-        let e2 = graph.add_edge(&n1, &n2, ed2.clone()).0;
-        let _e3 = graph.add_edge(&n2, &n3, ed3.clone()).0;
+        let e1 = graph.add_edge(&n1, &n1, ed1.clone()).into_edge_id();
+        let e2 = graph.add_edge(&n1, &n2, ed2.clone()).into_edge_id();
+        let _e3 = graph.add_edge(&n2, &n3, ed3.clone()).into_edge_id();
         graph.remove_node(&n3);
         assert_eq!(graph.node_ids().count(), 2);
         assert_eq!(graph.edge_ids().count(), 2);
@@ -1205,8 +1687,12 @@ where
         let n1 = source.add_node(self.new_node_data());
         let n2 = source.add_node(self.new_node_data());
         let n3 = source.add_node(self.new_node_data());
-        let e0 = source.add_edge(&n1, &n2, self.new_edge_data()).0;
-        let e1 = source.add_edge(&n2, &n3, self.new_edge_data()).0;
+        let e0 = source
+            .add_edge(&n1, &n2, self.new_edge_data())
+            .into_edge_id();
+        let e1 = source
+            .add_edge(&n2, &n3, self.new_edge_data())
+            .into_edge_id();
 
         let mut transform_node = |x: &String| format!("ND[{x}]");
         let mut transform_edge = |x: &String| format!("ED[{x}]");
@@ -1250,9 +1736,9 @@ where
         let n1 = graph.add_node(self.new_node_data());
         let n2 = graph.add_node(self.new_node_data());
         let result = graph.add_edge(&n1, &n2, self.new_edge_data());
-        assert!(matches!(result, (_, None)));
+        assert!(result.is_added());
         let result = graph.add_edge(&n1, &n2, self.new_edge_data());
-        assert_eq!(graph.allows_parallel_edges(), matches!(result, (_, None)));
+        assert_eq!(graph.allows_parallel_edges(), result.is_added());
 
         if graph.allows_parallel_edges() {
             assert_eq!(graph.num_edges(), 2);
@@ -1,21 +1,79 @@
-use std::{collections::HashSet, fmt::Debug, hash::Hash};
+use std::{
+    cell::{Ref, RefMut},
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+    ops::Add,
+};
 
 #[cfg(feature = "pathfinding")]
-use std::{collections::HashMap, ops::Add};
+use {
+    crate::{
+        anytime::{self, AnytimePath},
+        arena::AlgoArena,
+        pareto,
+        preconditions::NonNegative,
+        shortest_path_tree::{self, ShortestPathTree},
+    },
+    std::time::Duration,
+};
+
+#[cfg(feature = "json")]
+use crate::json;
+
+#[cfg(feature = "async")]
+use crate::stream::TraversalStream;
+
+#[cfg(feature = "rayon")]
+use crate::search::ParBfsLevels;
+
+#[cfg(feature = "petgraph-interop")]
+use crate::petgraph_interop::{self, PetGraphOf, PetgraphDirectedness, StableGraphOf};
 
 #[cfg(feature = "dot")]
 use {
-    crate::dot::{parser, renderer},
+    crate::dot::{parser, renderer, stable_names},
     std::io,
 };
 
 use crate::{
+    add_edge_result::AddEdgeResult,
+    adjacency_matrix::AdjacencyMatrix,
+    biconnected::{self, Block, BlockCutNode},
+    binary_snapshot::{self, EncodeDecode, SnapshotError},
+    cell_data::{CellData, borrow_cell, borrow_cell_mut},
+    coloring,
+    community::{self, Partition},
+    copier::GraphCopier,
+    cores, cycles,
     debug_graph_view::DebugGraphView,
     end_pair::EndPair,
+    error::GraphError,
+    eulerian,
+    grouping::{self, NodeGrouping},
+    kinds::{self, Kinded},
+    line_graph,
     map_collector::MapCollector,
+    mapped_graph::{self, MappedGraphView},
+    matrix_import, mean_cycle,
+    normalize::{self, Normalization},
     path::Path,
+    ports::{self, EdgePorts, NodePorts, UndeclaredPortError},
+    preconditions::Acyclic,
     prelude::*,
-    search::{BfsIterator, BfsIteratorWithPaths, DfsIterator, DfsIteratorWithPaths},
+    property_map::RemapKeys,
+    reversed_graph::ReversedGraphView,
+    scc::{self, Component},
+    search::{
+        self, BfsIterator, BfsIteratorWithContext, BfsIteratorWithPaths, DfsIterator,
+        DfsIteratorWithContext, DfsIteratorWithPaths, DfsPostOrderIterator, DfsVisitor,
+    },
+    signature, simulation,
+    spanning_tree::{self, SpanningForest},
+    sparsify::{self, Sparsification},
+    structural_hash,
+    validate::{self, Violation},
+    vertex_cover,
 };
 
 /// A trait representing a node or edge identifier in a graph.
@@ -49,7 +107,10 @@ pub trait Graph {
         Self::EdgeMultiplicity::ALLOWS_PARALLEL_EDGES
     }
 
-    /// Checks if the graph is empty (has no nodes or edges).
+    /// Returns true if the graph has no nodes, and therefore no edges.  The
+    /// default implementation checks whether [`Graph::node_ids`] yields
+    /// anything; implementations which count their nodes return
+    /// `num_nodes() == 0` instead.
     fn is_empty(&self) -> bool {
         if self.node_ids().next().is_none() {
             debug_assert!(self.edge_ids().next().is_none());
@@ -77,6 +138,18 @@ pub trait Graph {
         DebugGraphView::new(self, node_fmt, edge_fmt)
     }
 
+    /// Creates a view of the graph in which the data of each node and edge is
+    /// replaced by a projection of it, such as one of its fields.  The view
+    /// has the same IDs as the graph and copies nothing.  See
+    /// [`MappedGraphView`].
+    fn map<N, E, NF, EF>(&self, node_fn: NF, edge_fn: EF) -> MappedGraphView<'_, Self, N, E, NF, EF>
+    where
+        NF: Fn(&Self::NodeData) -> &N,
+        EF: Fn(&Self::EdgeData) -> &E,
+    {
+        MappedGraphView::new(self, node_fn, edge_fn)
+    }
+
     /// Writes a DOT representation of the graph to the given output.
     #[cfg(feature = "dot")]
     fn write_dot<D>(
@@ -91,6 +164,22 @@ pub trait Graph {
         renderer::generate_dot_file(self, generator, output)
     }
 
+    /// Like [`Self::write_dot`], but writes each node and edge as soon as
+    /// its attributes are generated, without clusters.  See
+    /// [`renderer::stream_dot_file`].
+    #[cfg(feature = "dot")]
+    fn stream_dot<D>(
+        &self,
+        generator: &D,
+        output: &mut impl io::Write,
+    ) -> Result<(), renderer::DotError<D::Error>>
+    where
+        D: renderer::DotRenderer<Self>,
+        Self: Sized,
+    {
+        renderer::stream_dot_file(self, generator, output)
+    }
+
     /// Generates a DOT representation of the graph as a String.
     #[cfg(feature = "dot")]
     fn to_dot_string<D>(&self, generator: &D) -> Result<String, renderer::DotError<D::Error>>
@@ -103,6 +192,22 @@ pub trait Graph {
         Ok(String::from_utf8(output).expect("Generated DOT is not valid UTF-8"))
     }
 
+    /// Names the nodes for DOT output by a key, reusing the names given to
+    /// the same keys in earlier versions of the graph.  Render with
+    /// [`WithNodeNames`](stable_names::WithNodeNames) to apply the names.
+    /// See [`stable_names`].
+    #[cfg(feature = "dot")]
+    fn stable_visual_ids<K>(
+        &self,
+        previous: &mut stable_names::StableNames<K>,
+        key_fn: impl Fn(&Self::NodeId) -> K,
+    ) -> HashMap<Self::NodeId, String>
+    where
+        K: Hash + Eq + Ord + Clone,
+    {
+        previous.assign(self, key_fn)
+    }
+
     /// Creates a new path starting from the given starting node.  This is a
     /// convenience method to avoid having to import the `Path` type separately
     /// and specify its type argument explicity.
@@ -118,6 +223,64 @@ pub trait Graph {
     /// Gets the data associated with a node.
     fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData;
 
+    /// Checks whether `id` refers to a node in this graph.  Implementations
+    /// report IDs from other graphs or from before a compaction where they
+    /// can detect them.  The default implementation searches
+    /// [`Graph::node_ids`].
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        if self.node_ids().any(|nid| &nid == id) {
+            Ok(())
+        } else {
+            Err(GraphError::InvalidNodeId)
+        }
+    }
+
+    /// Returns true if `id` refers to a node in this graph, i.e. if
+    /// [`Graph::check_node_id`] accepts it.  Unlike most methods taking a
+    /// node ID, this never panics.
+    fn contains_node(&self, id: &Self::NodeId) -> bool {
+        self.check_node_id(id).is_ok()
+    }
+
+    /// Like [`Graph::node_data`], but returns `None` instead of panicking if
+    /// `id` is not accepted by [`Graph::check_node_id`], as when the node has
+    /// been removed or the ID belongs to another graph.
+    fn get_node_data(&self, id: &Self::NodeId) -> Option<&Self::NodeData> {
+        self.check_node_id(id).ok()?;
+        Some(self.node_data(id))
+    }
+
+    /// Returns a copy of a node's data, or the default value if `id` is not a
+    /// valid node ID.  See [`Graph::get_node_data`].
+    fn node_data_or_default(&self, id: &Self::NodeId) -> Self::NodeData
+    where
+        Self::NodeData: Clone + Default,
+    {
+        self.get_node_data(id).cloned().unwrap_or_default()
+    }
+
+    /// Borrows the contents of a node's data when it is stored in a
+    /// `RefCell`.  Panics if the data is already mutably borrowed.  See
+    /// [`crate::cell_data`] for patterns that avoid borrow conflicts.
+    fn node_data_ref(&self, id: &Self::NodeId) -> Ref<'_, <Self::NodeData as CellData>::Inner>
+    where
+        Self::NodeData: CellData,
+    {
+        borrow_cell(self.node_data(id), id)
+    }
+
+    /// Mutably borrows the contents of a node's data when it is stored in a
+    /// `RefCell`.  Panics if the data is already borrowed.
+    fn node_data_ref_mut(
+        &self,
+        id: &Self::NodeId,
+    ) -> RefMut<'_, <Self::NodeData as CellData>::Inner>
+    where
+        Self::NodeData: CellData,
+    {
+        borrow_cell_mut(self.node_data(id), id)
+    }
+
     /// Gets the number of nodes in the graph.
     fn num_nodes(&self) -> usize {
         self.node_ids().count()
@@ -159,11 +322,90 @@ pub trait Graph {
         })
     }
 
+    /// Gets an iterator over the nodes connected to a given node by edges in
+    /// the given direction: its predecessors for [`Direction::In`], its
+    /// successors for [`Direction::Out`], and both for [`Direction::Both`].
+    /// Each node is reported once.
+    fn neighbors<'a, 'b: 'a>(
+        &'a self,
+        node: &'b Self::NodeId,
+        direction: Direction,
+    ) -> impl Iterator<Item = Self::NodeId> + 'a {
+        let directed = Self::Directedness::IS_DIRECTED;
+        let outgoing = direction != Direction::In || !directed;
+        let incoming = direction != Direction::Out && directed;
+        let edges_from = outgoing.then(|| self.edges_from(node));
+        let edges_into = incoming.then(|| self.edges_into(node));
+        let mut visited = HashSet::new();
+        edges_from
+            .into_iter()
+            .flatten()
+            .chain(edges_into.into_iter().flatten())
+            .filter_map(move |eid| {
+                let nid = self.edge_ends(&eid).into_other_value(node).into_inner();
+                visited.insert(nid.clone()).then_some(nid)
+            })
+    }
+
     // Edges
 
     /// Gets the data associated with an edge.
     fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData;
 
+    /// Checks whether `id` refers to an edge in this graph.  See
+    /// [`Graph::check_node_id`].  The default implementation searches
+    /// [`Graph::edge_ids`].
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        if self.edge_ids().any(|eid| &eid == id) {
+            Ok(())
+        } else {
+            Err(GraphError::InvalidEdgeId)
+        }
+    }
+
+    /// Returns true if `id` refers to an edge in this graph.  See
+    /// [`Graph::contains_node`].
+    fn contains_edge(&self, id: &Self::EdgeId) -> bool {
+        self.check_edge_id(id).is_ok()
+    }
+
+    /// Like [`Graph::edge_data`], but returns `None` instead of panicking if
+    /// `id` is not accepted by [`Graph::check_edge_id`].
+    fn get_edge_data(&self, id: &Self::EdgeId) -> Option<&Self::EdgeData> {
+        self.check_edge_id(id).ok()?;
+        Some(self.edge_data(id))
+    }
+
+    /// Returns a copy of an edge's data, or the default value if `id` is not
+    /// a valid edge ID.  See [`Graph::get_edge_data`].
+    fn edge_data_or_default(&self, id: &Self::EdgeId) -> Self::EdgeData
+    where
+        Self::EdgeData: Clone + Default,
+    {
+        self.get_edge_data(id).cloned().unwrap_or_default()
+    }
+
+    /// Borrows the contents of an edge's data when it is stored in a
+    /// `RefCell`.  Panics if the data is already mutably borrowed.
+    fn edge_data_ref(&self, id: &Self::EdgeId) -> Ref<'_, <Self::EdgeData as CellData>::Inner>
+    where
+        Self::EdgeData: CellData,
+    {
+        borrow_cell(self.edge_data(id), id)
+    }
+
+    /// Mutably borrows the contents of an edge's data when it is stored in a
+    /// `RefCell`.  Panics if the data is already borrowed.
+    fn edge_data_ref_mut(
+        &self,
+        id: &Self::EdgeId,
+    ) -> RefMut<'_, <Self::EdgeData as CellData>::Inner>
+    where
+        Self::EdgeData: CellData,
+    {
+        borrow_cell_mut(self.edge_data(id), id)
+    }
+
     /// Gets a vector of all edges in the graph.
     fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_;
 
@@ -242,11 +484,83 @@ pub trait Graph {
         self.edges_from(from).count()
     }
 
+    /// Gets the number of edges of a given node in the given direction.  In a
+    /// directed graph, the degree for [`Direction::Both`] is the sum of the
+    /// other two, so a self-loop counts twice.  In an undirected graph, all
+    /// directions give the number of edges of the node.
+    fn degree(&self, node: &Self::NodeId, direction: Direction) -> usize {
+        match direction {
+            Direction::In => self.num_edges_into(node),
+            Direction::Out => self.num_edges_from(node),
+            Direction::Both if Self::Directedness::IS_DIRECTED => {
+                self.num_edges_into(node) + self.num_edges_from(node)
+            }
+            Direction::Both => self.num_edges_from(node),
+        }
+    }
+
     /// Gets the number of edges from one node into another.
     fn num_edges_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> usize {
         self.edges_from_into(from, into).count()
     }
 
+    /// Gets the edges from one node into another, in the order returned by
+    /// [`Self::edges_from_into`], so that each edge's position in the result
+    /// is its index among the parallel edges of the pair.  See
+    /// [`Self::edge_index_between`].
+    fn parallel_edges(&self, from: &Self::NodeId, into: &Self::NodeId) -> Vec<Self::EdgeId> {
+        self.edges_from_into(from, into).collect()
+    }
+
+    /// Gets the index of an edge among the edges between its ends.  The
+    /// edges between a pair of nodes are numbered from zero in the order
+    /// returned by [`Self::edges_from_into`], which for the graphs in this
+    /// crate is the order in which they were added, so the index of an edge
+    /// only changes when an earlier edge between the same nodes is removed.
+    /// In a graph without parallel edges, the index is always zero.
+    fn edge_index_between(&self, id: &Self::EdgeId) -> usize {
+        let (from, into) = self.edge_ends(id).into_values();
+        self.edges_from_into(&from, &into)
+            .position(|eid| eid == *id)
+            .expect("edge is between its ends")
+    }
+
+    /// Gets the edge with the given index among the edges from one node into
+    /// another, or `None` if there are not that many edges.  See
+    /// [`Self::edge_index_between`].
+    fn nth_edge_between(
+        &self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        index: usize,
+    ) -> Option<Self::EdgeId> {
+        self.edges_from_into(from, into).nth(index)
+    }
+
+    /// Folds the outgoing edges of every node into a value per node.  Each
+    /// node's accumulator starts as `init_fn(node)`, and `fold_fn` is called
+    /// with it for each edge returned by [`Graph::edges_from`], in an
+    /// unspecified order.
+    ///
+    /// This computes per-node aggregates such as out-degrees or total weights
+    /// in one call.  Implementations may override it to sweep their storage
+    /// once instead of looking up the edges of each node separately.
+    fn fold_edges_by_source<A>(
+        &self,
+        mut init_fn: impl FnMut(&Self::NodeId) -> A,
+        mut fold_fn: impl FnMut(&mut A, &Self::EdgeId, &Self::EdgeData),
+    ) -> HashMap<Self::NodeId, A> {
+        self.node_ids()
+            .map(|nid| {
+                let mut acc = init_fn(&nid);
+                for eid in self.edges_from(&nid) {
+                    fold_fn(&mut acc, &eid, self.edge_data(&eid));
+                }
+                (nid, acc)
+            })
+            .collect()
+    }
+
     // Searches
 
     /// Performs a breadth-first search starting from the given node.
@@ -259,6 +573,28 @@ pub trait Graph {
         BfsIterator::new(self, start)
     }
 
+    /// Performs a breadth-first search starting from the given node,
+    /// expanding each level in parallel and yielding the nodes of each level
+    /// together.  See [`ParBfsLevels`].
+    #[cfg(feature = "rayon")]
+    fn par_bfs(&self, start: &Self::NodeId) -> ParBfsLevels<'_, Self>
+    where
+        Self: Sync,
+        Self::NodeId: Send + Sync,
+    {
+        self.par_bfs_multi(vec![start.clone()])
+    }
+
+    /// Like [`Self::par_bfs`], but starting from the given nodes.
+    #[cfg(feature = "rayon")]
+    fn par_bfs_multi(&self, start: Vec<Self::NodeId>) -> ParBfsLevels<'_, Self>
+    where
+        Self: Sync,
+        Self::NodeId: Send + Sync,
+    {
+        ParBfsLevels::new(self, start)
+    }
+
     /// Performs a depth-first search starting from the given node.
     fn dfs(&self, start: &Self::NodeId) -> DfsIterator<'_, Self> {
         self.dfs_multi(vec![start.clone()])
@@ -269,6 +605,37 @@ pub trait Graph {
         DfsIterator::new(self, start)
     }
 
+    /// Performs a depth-first search starting from the given node, yielding
+    /// nodes in post-order.
+    fn dfs_post_order(&self, start: &Self::NodeId) -> DfsPostOrderIterator<'_, Self> {
+        self.dfs_post_order_multi(vec![start.clone()])
+    }
+
+    /// Performs a depth-first search starting from the given nodes, yielding
+    /// nodes in post-order.
+    fn dfs_post_order_multi(&self, start: Vec<Self::NodeId>) -> DfsPostOrderIterator<'_, Self> {
+        DfsPostOrderIterator::new(self, start)
+    }
+
+    /// Performs a depth-first search starting from the given node, calling
+    /// `visitor` as nodes are discovered and finished and edges are
+    /// classified.  See [`DfsVisitor`].
+    fn depth_first_visit<V>(&self, start: &Self::NodeId, visitor: &mut V)
+    where
+        V: DfsVisitor<Self> + ?Sized,
+    {
+        search::depth_first_visit(self, vec![start.clone()], visitor)
+    }
+
+    /// Like [`Graph::depth_first_visit`], but searches from each of the given
+    /// nodes in turn which has not already been discovered.
+    fn depth_first_visit_multi<V>(&self, start: Vec<Self::NodeId>, visitor: &mut V)
+    where
+        V: DfsVisitor<Self> + ?Sized,
+    {
+        search::depth_first_visit(self, start, visitor)
+    }
+
     /// Performs a breadth-first search starting from the given node.
     fn bfs_with_paths(&self, start: &Self::NodeId) -> BfsIteratorWithPaths<'_, Self> {
         self.bfs_multi_with_paths(vec![start.clone()])
@@ -289,86 +656,445 @@ pub trait Graph {
         DfsIteratorWithPaths::new(self, start)
     }
 
+    /// Performs a breadth-first search starting from the given node, yielding
+    /// the depth and parent edge of each visited node.
+    fn bfs_with_context(&self, start: &Self::NodeId) -> BfsIteratorWithContext<'_, Self> {
+        self.bfs_multi_with_context(vec![start.clone()])
+    }
+
+    /// Performs a breadth-first search starting from the given nodes,
+    /// yielding the depth and parent edge of each visited node.
+    fn bfs_multi_with_context(&self, start: Vec<Self::NodeId>) -> BfsIteratorWithContext<'_, Self> {
+        BfsIteratorWithContext::new(self, start)
+    }
+
+    /// Performs a depth-first search starting from the given node, yielding
+    /// the depth and parent edge of each visited node.
+    fn dfs_with_context(&self, start: &Self::NodeId) -> DfsIteratorWithContext<'_, Self> {
+        self.dfs_multi_with_context(vec![start.clone()])
+    }
+
+    /// Performs a depth-first search starting from the given nodes, yielding
+    /// the depth and parent edge of each visited node.
+    fn dfs_multi_with_context(&self, start: Vec<Self::NodeId>) -> DfsIteratorWithContext<'_, Self> {
+        DfsIteratorWithContext::new(self, start)
+    }
+
+    /// Performs a breadth-first search starting from the given node, as a
+    /// stream which periodically yields to the executor.  See
+    /// [`crate::stream`].
+    #[cfg(feature = "async")]
+    fn bfs_stream(&self, start: &Self::NodeId) -> TraversalStream<BfsIterator<'_, Self>> {
+        TraversalStream::new(self.bfs(start))
+    }
+
+    /// Performs a depth-first search starting from the given node, as a
+    /// stream which periodically yields to the executor.  See
+    /// [`crate::stream`].
+    #[cfg(feature = "async")]
+    fn dfs_stream(&self, start: &Self::NodeId) -> TraversalStream<DfsIterator<'_, Self>> {
+        TraversalStream::new(self.dfs(start))
+    }
+
+    /// Converts the graph to Cytoscape.js elements JSON.  See
+    /// [`json::to_cytoscape_json`].
+    #[cfg(feature = "json")]
+    fn to_cytoscape_json(
+        &self,
+        node_attrs: impl Fn(&Self::NodeId, &Self::NodeData) -> json::JsonAttrs,
+        edge_attrs: impl Fn(&Self::EdgeId, &Self::EdgeData) -> json::JsonAttrs,
+    ) -> serde_json::Value {
+        json::to_cytoscape_json(self, node_attrs, edge_attrs)
+    }
+
+    /// Converts the graph to the graphology JSON format used by Sigma.js.  See
+    /// [`json::to_sigma_json`].
+    #[cfg(feature = "json")]
+    fn to_sigma_json(
+        &self,
+        node_attrs: impl Fn(&Self::NodeId, &Self::NodeData) -> json::JsonAttrs,
+        edge_attrs: impl Fn(&Self::EdgeId, &Self::EdgeData) -> json::JsonAttrs,
+    ) -> serde_json::Value {
+        json::to_sigma_json(self, node_attrs, edge_attrs)
+    }
+
+    /// Converts the graph to the node-link JSON format used by D3 and
+    /// NetworkX.  See [`json::to_json_graph`].
+    #[cfg(feature = "json")]
+    fn to_json_graph(
+        &self,
+        node_attrs: impl Fn(&Self::NodeId, &Self::NodeData) -> json::JsonAttrs,
+        edge_attrs: impl Fn(&Self::EdgeId, &Self::EdgeData) -> json::JsonAttrs,
+    ) -> serde_json::Value {
+        json::to_json_graph(self, node_attrs, edge_attrs)
+    }
+
+    /// Writes the graph in a compact binary format which can be read back
+    /// quickly with [`GraphMut::read_snapshot`].  See [`binary_snapshot`].
+    fn write_snapshot(&self, output: &mut impl std::io::Write) -> std::io::Result<()>
+    where
+        Self::NodeData: EncodeDecode,
+        Self::EdgeData: EncodeDecode,
+    {
+        binary_snapshot::write_snapshot(self, output)
+    }
+
+    /// Copies the graph into a [`petgraph::Graph`], with the node with index
+    /// `i` being the `i`th node returned by [`Self::node_ids`].  See
+    /// [`petgraph_interop`].
+    #[cfg(feature = "petgraph-interop")]
+    fn to_petgraph(&self) -> PetGraphOf<Self>
+    where
+        Self::Directedness: PetgraphDirectedness,
+        Self::NodeData: Clone,
+        Self::EdgeData: Clone,
+    {
+        petgraph_interop::to_petgraph(self)
+    }
+
+    /// Like [`Self::to_petgraph`], but copies the graph into a
+    /// [`petgraph::stable_graph::StableGraph`].
+    #[cfg(feature = "petgraph-interop")]
+    fn to_stable_graph(&self) -> StableGraphOf<Self>
+    where
+        Self::Directedness: PetgraphDirectedness,
+        Self::NodeData: Clone,
+        Self::EdgeData: Clone,
+    {
+        petgraph_interop::to_stable_graph(self)
+    }
+
     // Pathfinding
 
     /// Finds shortest paths from a starting node to all other nodes using
     /// Dijkstra's algorithm.  Returns a map from each reachable node to a
-    /// tuple of the path taken and the total cost.
+    /// tuple of the path taken and the total cost.  Runs in `O((V + E) log V)`
+    /// time.  To use floating point costs, wrap them in an
+    /// [`OrderedCost`](crate::ordered_cost::OrderedCost).
+    ///
+    /// Each path is stored separately, so the result can take space
+    /// quadratic in the number of nodes.  [`Graph::shortest_path_tree`]
+    /// stores one edge per node instead, and its result can be used as a
+    /// graph through [`ShortestPathTree::as_graph`].
     #[cfg(feature = "pathfinding")]
     fn shortest_paths<C: Default + Ord + Copy + Add<Output = C>>(
         &self,
         start: &Self::NodeId,
         distance_fn: impl Fn(&Self::EdgeId) -> C,
     ) -> HashMap<Self::NodeId, (Path<'_, Self>, C)> {
-        // Find shortest paths using Dijkstra's algorithm.
+        let tree = self.shortest_path_tree(start, distance_fn);
+        tree.distances
+            .iter()
+            .filter_map(|(node, &dist)| Some((node.clone(), (tree.path_to(self, node)?, dist))))
+            .collect()
+    }
 
-        let mut distances: HashMap<Self::NodeId, C> = HashMap::new();
-        let mut predecessors: HashMap<Self::NodeId, (Self::EdgeId, Self::NodeId)> = HashMap::new();
-        let mut unvisited: HashSet<Self::NodeId> = self.node_ids().collect();
+    /// Finds a shortest path tree rooted at `start` using Dijkstra's
+    /// algorithm.  The tree maps each reachable node to the edge by which a
+    /// shortest path arrives at it, which is more compact than the paths
+    /// returned by [`Graph::shortest_paths`].  See
+    /// [`shortest_path_tree::shortest_path_tree`].
+    #[cfg(feature = "pathfinding")]
+    fn shortest_path_tree<C: Default + Ord + Copy + Add<Output = C>>(
+        &self,
+        start: &Self::NodeId,
+        distance_fn: impl Fn(&Self::EdgeId) -> C,
+    ) -> ShortestPathTree<Self::NodeId, Self::EdgeId, C> {
+        shortest_path_tree::shortest_path_tree(self, start, distance_fn)
+    }
 
-        distances.insert(start.clone(), C::default());
+    /// Like [`Graph::shortest_path_tree`], but only accepts lengths which
+    /// are known not to be negative, such as those returned by
+    /// [`NonNegative::check_edges`], so that Dijkstra's algorithm cannot
+    /// silently give wrong answers.
+    #[cfg(feature = "pathfinding")]
+    fn checked_shortest_path_tree<C: Default + Ord + Copy + Add<Output = C>>(
+        &self,
+        start: &Self::NodeId,
+        distance_fn: impl Fn(&Self::EdgeId) -> NonNegative<C>,
+    ) -> ShortestPathTree<Self::NodeId, Self::EdgeId, NonNegative<C>> {
+        shortest_path_tree::shortest_path_tree(self, start, distance_fn)
+    }
 
-        while !unvisited.is_empty() {
-            // Find unvisited node with minimum distance
-            let current = unvisited
-                .iter()
-                .filter_map(|node| distances.get(node).map(|&dist| (node.clone(), dist)))
-                .min_by_key(|(_, dist)| *dist);
+    /// Creates scratch space for shortest path searches, sized for this
+    /// graph, which can be reused across many searches to avoid allocating
+    /// new tables each time.  See [`AlgoArena`].
+    #[cfg(feature = "pathfinding")]
+    fn make_arena<C: Default + Ord + Copy + Add<Output = C>>(
+        &self,
+    ) -> AlgoArena<Self::NodeId, Self::EdgeId, C> {
+        AlgoArena::with_capacity(self.num_nodes())
+    }
 
-            let (current_node, current_dist) = match current {
-                Some(pair) => pair,
-                None => break, // No more reachable nodes
-            };
+    /// Finds a path from `start` to `goal` within a time budget, using ARA*
+    /// (Anytime Repairing A*).  The search first finds a path quickly using an
+    /// inflated heuristic, then improves it until it is known to be optimal
+    /// or the budget runs out.  Returns the best path found along with a
+    /// bound on its suboptimality, or `None` if no path was found in time or
+    /// the goal is unreachable.
+    ///
+    /// The heuristic must not overestimate the remaining cost to `goal`.
+    #[cfg(feature = "pathfinding")]
+    fn shortest_path_anytime(
+        &self,
+        start: &Self::NodeId,
+        goal: &Self::NodeId,
+        weight_fn: impl Fn(&Self::EdgeId) -> f64,
+        heuristic: impl Fn(&Self::NodeId) -> f64,
+        budget: Duration,
+    ) -> Option<AnytimePath<'_, Self>> {
+        anytime::shortest_path_anytime(self, start, goal, weight_fn, heuristic, budget)
+    }
+
+    /// Finds the Pareto frontier of paths from `start` to `goal` when each
+    /// edge has several costs, such as travel time, tolls and distance.
+    /// Returns every path whose total cost vector is not dominated by that of
+    /// another path, sorted lexicographically by cost.  Paths with equal
+    /// costs are reported once.  See the [`pareto`] module.
+    ///
+    /// Costs must not be negative.  Using a single cost gives the same result
+    /// as [`Graph::shortest_paths`].
+    #[cfg(feature = "pathfinding")]
+    fn pareto_shortest_paths<C, const N: usize>(
+        &self,
+        start: &Self::NodeId,
+        goal: &Self::NodeId,
+        costs_fn: impl Fn(&Self::EdgeId) -> [C; N],
+    ) -> Vec<(Path<'_, Self>, [C; N])>
+    where
+        C: Default + PartialOrd + Copy + Add<Output = C>,
+    {
+        pareto::pareto_shortest_paths(self, start, goal, costs_fn)
+    }
+
+    /// Like [`Graph::pareto_shortest_paths`], but only considers paths whose
+    /// costs satisfy `feasible`, such as a limit on one of the costs.
+    /// `feasible` must reject every cost vector which is no better than one
+    /// it rejects.  See [`pareto::constrained_pareto_shortest_paths`].
+    #[cfg(feature = "pathfinding")]
+    fn constrained_pareto_shortest_paths<C, const N: usize>(
+        &self,
+        start: &Self::NodeId,
+        goal: &Self::NodeId,
+        costs_fn: impl Fn(&Self::EdgeId) -> [C; N],
+        feasible: impl Fn(&[C; N]) -> bool,
+    ) -> Vec<(Path<'_, Self>, [C; N])>
+    where
+        C: Default + PartialOrd + Copy + Add<Output = C>,
+    {
+        pareto::constrained_pareto_shortest_paths(self, start, goal, costs_fn, feasible)
+    }
 
-            unvisited.remove(&current_node);
+    /// Finds the lexicographically shortest path from `start` to `goal` among
+    /// the paths whose costs satisfy `feasible`, e.g. the fastest path within
+    /// a budget when the first cost is time.  Returns the path and its costs,
+    /// or `None` if there is no such path.  See
+    /// [`pareto::constrained_shortest_path`].
+    #[cfg(feature = "pathfinding")]
+    fn constrained_shortest_path<C, const N: usize>(
+        &self,
+        start: &Self::NodeId,
+        goal: &Self::NodeId,
+        costs_fn: impl Fn(&Self::EdgeId) -> [C; N],
+        feasible: impl Fn(&[C; N]) -> bool,
+    ) -> Option<(Path<'_, Self>, [C; N])>
+    where
+        C: Default + PartialOrd + Copy + Add<Output = C>,
+    {
+        pareto::constrained_shortest_path(self, start, goal, costs_fn, feasible)
+    }
 
-            // Update distances to neighbors
-            for edge_id in self.edges_from(&current_node) {
-                let ends = self.edge_ends(&edge_id);
-                let neighbor = ends.other_value(&current_node).into_inner();
-                if unvisited.contains(&neighbor) {
-                    let edge_distance = distance_fn(&edge_id);
-                    let new_dist = current_dist + edge_distance;
+    // Ports
 
-                    let should_update = distances
-                        .get(&neighbor)
-                        .is_none_or(|&old_dist| new_dist < old_dist);
+    /// Returns the edges with an end attached to `port` of `node`.  See
+    /// [`ports`].
+    fn edges_at_port<'a>(
+        &'a self,
+        node: &'a Self::NodeId,
+        port: &'a str,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a
+    where
+        Self::EdgeData: EdgePorts,
+    {
+        ports::edges_at_port(self, node, port)
+    }
 
-                    if should_update {
-                        distances.insert(neighbor.clone(), new_dist);
-                        predecessors.insert(neighbor.clone(), (edge_id, current_node.clone()));
-                    }
-                }
-            }
-        }
+    /// Checks that every port an edge is attached to is declared by the node
+    /// at that end.  See [`ports`].
+    fn validate_ports(&self) -> Result<(), UndeclaredPortError<Self::EdgeId>>
+    where
+        Self::NodeData: NodePorts,
+        Self::EdgeData: EdgePorts,
+    {
+        ports::validate_ports(self)
+    }
 
-        // Build paths from predecessors
-        let mut result: HashMap<<Self as Graph>::NodeId, (Path<Self>, C)> = HashMap::new();
-        for (node, &dist) in &distances {
-            if node == start {
-                result.insert(
-                    start.clone(),
-                    (Path::new(self, start.clone()), C::default()),
-                );
-            } else {
-                let mut current = node.clone();
+    // Cores
 
-                let mut path_edges = Vec::new();
-                while let Some(pred) = predecessors.get(&current) {
-                    path_edges.push(pred.0.clone());
-                    current = pred.1.clone();
-                }
+    /// Returns the core number of every node, the largest `k` for which it
+    /// is in the k-core.  See [`cores`].
+    fn core_numbers(&self) -> HashMap<Self::NodeId, usize> {
+        cores::core_numbers(self)
+    }
 
-                let mut path = Path::new(self, start.clone());
-                for edge_id in path_edges.iter().rev() {
-                    path.add_edge(edge_id.clone());
-                }
+    /// Returns the nodes of the k-core, the largest subgraph in which every
+    /// node has at least `k` neighbors.  See [`cores`].
+    fn k_core(&self, k: usize) -> HashSet<Self::NodeId> {
+        cores::k_core(self, k)
+    }
 
-                result.insert(node.clone(), (path, dist));
-            }
-        }
+    // Coloring
+
+    /// Colors the nodes greedily in Welsh–Powell order, so that no two
+    /// adjacent nodes have the same color, and returns the color of each
+    /// node.  See [`coloring`].
+    fn welsh_powell_coloring(&self) -> HashMap<Self::NodeId, usize> {
+        coloring::welsh_powell_coloring(self)
+    }
+
+    /// Colors the nodes greedily in the given order.  See [`coloring`].
+    fn greedy_coloring(
+        &self,
+        order: impl IntoIterator<Item = Self::NodeId>,
+    ) -> HashMap<Self::NodeId, usize> {
+        coloring::greedy_coloring(self, order)
+    }
+
+    // Covers
 
-        result
+    /// Finds a vertex cover, which is minimum for small graphs and at most
+    /// twice the minimum size otherwise.  See [`vertex_cover`].
+    fn min_vertex_cover(&self) -> HashSet<Self::NodeId> {
+        vertex_cover::min_vertex_cover(self)
+    }
+
+    /// Finds an independent set, which is maximum for small graphs and
+    /// maximal otherwise.  See [`vertex_cover`].
+    fn max_independent_set(&self) -> HashSet<Self::NodeId> {
+        vertex_cover::max_independent_set(self)
+    }
+
+    // Weights
+
+    /// Normalizes the weight `weight_fn` returns for every edge according
+    /// to `scheme`, returning the results by edge.  See [`normalize`].
+    fn normalize_weights(
+        &self,
+        weight_fn: impl Fn(&Self::EdgeId) -> f64,
+        scheme: Normalization,
+    ) -> HashMap<Self::EdgeId, f64> {
+        normalize::normalize_weights(self, weight_fn, scheme)
+    }
+
+    // Signatures
+
+    /// Returns a hash of the structure of the graph within `depth` steps of
+    /// a node.  See [`signature`].
+    fn node_signature(&self, node: &Self::NodeId, depth: usize) -> u64 {
+        signature::node_signature(self, node, depth)
+    }
+
+    /// Groups nodes whose neighborhoods within `depth` steps have equal
+    /// signatures, returning the groups with at least `min_group_size`
+    /// nodes.  See [`signature`].
+    fn find_similar_nodes(&self, depth: usize, min_group_size: usize) -> Vec<Vec<Self::NodeId>> {
+        signature::find_similar_nodes(self, depth, min_group_size)
+    }
+
+    /// Returns a hash of the structure of the whole graph, ignoring node and
+    /// edge data, which is equal for isomorphic graphs and stable across
+    /// runs.  See [`structural_hash`].
+    fn structural_hash(&self) -> u64 {
+        structural_hash::structural_hash(self)
+    }
+
+    /// Like [`Self::structural_hash`], but including the keys `node_key` and
+    /// `edge_key` return for the node and edge data.
+    fn structural_hash_by<NK, EK>(
+        &self,
+        node_key: impl Fn(&Self::NodeData) -> NK,
+        edge_key: impl Fn(&Self::EdgeData) -> EK,
+    ) -> u64
+    where
+        NK: Hash,
+        EK: Hash,
+    {
+        structural_hash::structural_hash_by(self, node_key, edge_key)
+    }
+
+    // Grouping
+
+    /// Groups the nodes by the key `key_fn` returns for their data, and
+    /// counts the edges within and between the groups.  See [`grouping`].
+    fn group_nodes_by<K>(&self, key_fn: impl FnMut(&Self::NodeData) -> K) -> NodeGrouping<K, Self>
+    where
+        K: Eq + Hash + Clone,
+    {
+        grouping::group_nodes_by(self, key_fn)
+    }
+
+    // Node kinds
+
+    /// Returns the nodes of kind `K`, with their data as a `K`.  See
+    /// [`kinds`].
+    fn nodes_of<'g, K>(&'g self) -> impl Iterator<Item = (Self::NodeId, &'g K)>
+    where
+        K: 'g,
+        Self::NodeData: Kinded<K>,
+    {
+        kinds::nodes_of(self)
+    }
+
+    /// Calls `f` with each node of kind `K` and its data as a `K`.  See
+    /// [`kinds`].
+    fn visit_nodes_of<K>(&self, f: impl FnMut(Self::NodeId, &K))
+    where
+        Self::NodeData: Kinded<K>,
+    {
+        kinds::visit_nodes_of(self, f)
+    }
+
+    /// Returns the edges from a node of kind `A` to a node of kind `B`, with
+    /// the data of their ends.  See [`kinds`].
+    fn edges_between_kinds<'g, A, B>(&'g self) -> impl Iterator<Item = (Self::EdgeId, &'g A, &'g B)>
+    where
+        A: 'g,
+        B: 'g,
+        Self::NodeData: Kinded<A> + Kinded<B>,
+    {
+        kinds::edges_between_kinds(self)
+    }
+
+    // Derived graphs
+
+    /// Constructs the line graph of this graph, in which each node
+    /// corresponds to an edge of this graph, and two nodes are connected if
+    /// the corresponding edges share an end.  In a directed graph, the node
+    /// for an edge into a node is connected to the node for each edge out of
+    /// it.
+    ///
+    /// The node data for each new node is computed from the original edge by
+    /// `node_data_fn`, and the edge data for each new edge is computed from
+    /// the shared node by `edge_data_fn`.  Edges which share both ends (i.e.,
+    /// parallel edges) are connected once for each shared end, so `H` should
+    /// support multiple edges if this graph does.
+    fn line_graph<H>(
+        &self,
+        node_data_fn: impl Fn(&Self::EdgeId) -> H::NodeData,
+        edge_data_fn: impl Fn(&Self::NodeId) -> H::EdgeData,
+    ) -> H
+    where
+        H: GraphMut<Directedness = Self::Directedness> + Default,
+    {
+        line_graph::line_graph(self, node_data_fn, edge_data_fn)
+    }
+
+    /// Checks that the graph is internally consistent, returning every
+    /// inconsistency found, or an empty list if there are none.  This is
+    /// useful after loading a graph from an untrusted source.  See
+    /// [`validate`].
+    fn validate(&self) -> Vec<Violation<Self::NodeId, Self::EdgeId>> {
+        validate::validate(self)
     }
 
     /// Returns true if the graph implementation is known to be very slow for
@@ -384,46 +1110,367 @@ pub trait Graph {
 /// A trait which is automatically implemented for directed graphs, providing
 /// methods specific to directed graphs.
 pub trait GraphDirected: Graph {
+    /// Creates a view of the graph with the direction of every edge
+    /// reversed, without copying it.  See [`ReversedGraphView`].
+    fn reversed(&self) -> ReversedGraphView<'_, Self>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        ReversedGraphView::new(self)
+    }
+
     /// Finds the strongly connected component containing the given node.
-    #[cfg(feature = "pathfinding")]
-    fn strongly_connected_component(&self, start: &Self::NodeId) -> Vec<Self::NodeId> {
-        pathfinding::prelude::strongly_connected_component(start, |nid| {
-            self.successors(nid).collect::<Vec<_>>()
-        })
+    /// See [`scc`].
+    fn strongly_connected_component(&self, start: &Self::NodeId) -> Vec<Self::NodeId>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        scc::strongly_connected_component(self, start)
     }
 
-    /// Partitions the graph into strongly connected components.
-    #[cfg(feature = "pathfinding")]
-    fn strongly_connected_components(&self) -> Vec<Vec<Self::NodeId>> {
-        pathfinding::prelude::strongly_connected_components(
-            &self.node_ids().collect::<Vec<_>>(),
-            |nid| self.successors(nid).collect::<Vec<_>>(),
-        )
+    /// Partitions the graph into strongly connected components, in reverse
+    /// topological order.  See [`scc`].
+    fn strongly_connected_components(&self) -> Vec<Vec<Self::NodeId>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        scc::strongly_connected_components(self)
     }
 
     /// Partitions nodes reachable from a starting point into strongly connected components.
-    #[cfg(feature = "pathfinding")]
-    fn strongly_connected_components_from(&self, start: &Self::NodeId) -> Vec<Vec<Self::NodeId>> {
-        pathfinding::prelude::strongly_connected_components_from(start, |nid| {
-            self.successors(nid).collect::<Vec<_>>()
-        })
+    fn strongly_connected_components_from(&self, start: &Self::NodeId) -> Vec<Vec<Self::NodeId>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        scc::strongly_connected_components_from(self, start)
+    }
+
+    /// Like [`Self::strongly_connected_components`], but also returns the
+    /// edges within each component.
+    fn strongly_connected_components_with_edges(&self) -> Vec<Component<Self::NodeId, Self::EdgeId>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        scc::strongly_connected_components_with_edges(self)
+    }
+
+    /// Chooses at most `max_edges` edges which preserve reachability between
+    /// nodes as much as possible, e.g., to thin out a large graph for
+    /// rendering.  See [`sparsify`].
+    fn sparsify_for_reachability(&self, max_edges: usize) -> Sparsification<Self::EdgeId>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        sparsify::sparsify_for_reachability(self, max_edges)
+    }
+
+    /// Finds a cycle in the graph, or returns `None` if the graph is acyclic.
+    /// See [`cycles`].
+    fn find_cycle(&self) -> Option<Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        cycles::find_cycle(self)
+    }
+
+    /// Returns true if the graph contains a cycle.
+    fn has_cycle(&self) -> bool
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        cycles::has_cycle(self)
+    }
+
+    /// Checks that the graph has no cycles, returning a witness of the fact
+    /// which gives access to algorithms that require it, or a cycle of the
+    /// graph.  See [`Acyclic`].
+    fn check_acyclic(&self) -> Result<Acyclic<'_, Self>, Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        Acyclic::new(self)
+    }
+
+    /// Assigns each node a layer equal to the length of the longest path
+    /// into it from a node with no edges into it, or returns a cycle of the
+    /// graph if it has one.  See [`Acyclic::layering`].
+    fn layering(&self) -> Result<HashMap<Self::NodeId, usize>, Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        self.check_acyclic().map(|dag| dag.layering())
+    }
+
+    /// Like [`GraphDirected::layering`], but measures the longest paths from
+    /// `roots`, and only assigns layers to the nodes reachable from them.
+    /// See [`Acyclic::layering_from`].
+    fn layering_from(
+        &self,
+        roots: impl IntoIterator<Item = Self::NodeId>,
+    ) -> Result<HashMap<Self::NodeId, usize>, Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        self.check_acyclic().map(|dag| dag.layering_from(roots))
+    }
+
+    /// Returns a path which uses every edge of the graph once and ends where
+    /// it starts, or `None` if there is none.  See [`eulerian`].
+    fn eulerian_circuit(&self) -> Option<Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        eulerian::eulerian_circuit(self)
+    }
+
+    /// Returns a path which uses every edge of the graph once, or `None` if
+    /// there is none.  See [`eulerian`].
+    fn eulerian_path(&self) -> Option<Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        eulerian::eulerian_path(self)
+    }
+
+    /// Returns a cycle whose total edge weight is negative, along with its
+    /// total weight, or `None` if there is none.  See [`mean_cycle`].
+    fn find_negative_cycle(
+        &self,
+        weight_fn: impl Fn(&Self::EdgeId) -> f64,
+    ) -> Option<(Path<'_, Self>, f64)>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        mean_cycle::find_negative_cycle(self, weight_fn)
+    }
+
+    /// Returns a cycle with the smallest mean edge weight, along with its
+    /// mean, or `None` if the graph is acyclic.  See [`mean_cycle`].
+    fn min_mean_cycle(
+        &self,
+        weight_fn: impl Fn(&Self::EdgeId) -> f64,
+    ) -> Option<(Path<'_, Self>, f64)>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        mean_cycle::min_mean_cycle(self, weight_fn)
+    }
+
+    /// Returns a cycle with the smallest ratio of total cost to total time,
+    /// along with its ratio, or `None` if the graph is acyclic.  The total
+    /// time of every cycle must be positive.  See [`mean_cycle`].
+    fn min_cycle_ratio(
+        &self,
+        cost_fn: impl Fn(&Self::EdgeId) -> f64,
+        time_fn: impl Fn(&Self::EdgeId) -> f64,
+    ) -> Option<(Path<'_, Self>, f64)>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        mean_cycle::min_cycle_ratio(self, cost_fn, time_fn)
+    }
+
+    /// Returns up to `max_cycles` simple cycles of the graph, using
+    /// Johnson's algorithm.  See [`cycles`].
+    fn enumerate_simple_cycles(&self, max_cycles: usize) -> Vec<Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Directed>,
+    {
+        cycles::enumerate_simple_cycles(self, max_cycles)
+    }
+
+    /// Returns true if `node` simulates `other_node` of `other`, treating
+    /// both graphs as labeled transition systems whose labels are compared
+    /// with `label_eq`.  See [`simulation`].
+    fn simulates<H>(
+        &self,
+        node: &Self::NodeId,
+        other: &H,
+        other_node: &H::NodeId,
+        label_eq: impl Fn(&Self::EdgeData, &H::EdgeData) -> bool,
+    ) -> bool
+    where
+        Self: Graph<Directedness = Directed>,
+        H: Graph<Directedness = Directed> + ?Sized,
+    {
+        simulation::simulates(self, node, other, other_node, label_eq)
+    }
+
+    /// Returns true if `node` and `other_node` of `other` are bisimilar,
+    /// treating both graphs as labeled transition systems whose labels are
+    /// compared with `label_eq`.  See [`simulation`].
+    fn bisimilar<H>(
+        &self,
+        node: &Self::NodeId,
+        other: &H,
+        other_node: &H::NodeId,
+        label_eq: impl Fn(&Self::EdgeData, &H::EdgeData) -> bool,
+    ) -> bool
+    where
+        Self: Graph<Directedness = Directed>,
+        H: Graph<Directedness = Directed> + ?Sized,
+    {
+        simulation::bisimilar(self, node, other, other_node, label_eq)
+    }
+
+    /// Returns a copy of the graph with the direction of every edge reversed.
+    /// Use [`GraphCopier::reverse_edges`] directly to track how node and edge
+    /// IDs correspond between the two graphs.
+    fn transposed<H>(&self) -> H
+    where
+        H: GraphMut<NodeData = Self::NodeData, EdgeData = Self::EdgeData> + Default,
+        Self::NodeData: Clone,
+        Self::EdgeData: Clone,
+    {
+        GraphCopier::new(self)
+            .clone_nodes()
+            .clone_edges()
+            .reverse_edges()
+            .copy()
     }
 }
 
-impl<G> GraphDirected for G where G: Graph<Directedness = Directed> {}
+impl<G> GraphDirected for G where G: Graph<Directedness = Directed> + ?Sized {}
 
 /// A trait which is automatically implemented for undirected graphs, providing
 /// methods specific to undirected graphs.
 pub trait GraphUndirected: Graph {
+    /// Returns the biconnected components of the graph.  See
+    /// [`biconnected`].
+    fn biconnected_components(&self) -> Vec<Block<Self::NodeId, Self::EdgeId>>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        biconnected::biconnected_components(self)
+    }
+
+    /// Returns a path which uses every edge of the graph once and ends where
+    /// it starts, or `None` if there is none.  See [`eulerian`].
+    fn eulerian_circuit(&self) -> Option<Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        eulerian::eulerian_circuit(self)
+    }
+
+    /// Returns a path which uses every edge of the graph once, or `None` if
+    /// there is none.  See [`eulerian`].
+    fn eulerian_path(&self) -> Option<Path<'_, Self>>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        eulerian::eulerian_path(self)
+    }
+
+    /// Returns the articulation points of the graph, i.e. the nodes whose
+    /// removal would disconnect part of the graph.
+    fn articulation_points(&self) -> Vec<Self::NodeId>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        biconnected::articulation_points(self)
+    }
+
+    /// Returns the bridges of the graph, i.e. the edges whose removal would
+    /// disconnect their ends.
+    fn bridges(&self) -> Vec<Self::EdgeId>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        biconnected::bridges(self)
+    }
+
+    /// Constructs the block-cut tree of the graph, with a node for each
+    /// biconnected component and each articulation point.  See
+    /// [`biconnected`].
+    fn block_cut_tree<H>(&self) -> H
+    where
+        Self: Graph<Directedness = Undirected>,
+        H: GraphMut<NodeData = BlockCutNode<Self::NodeId, Self::EdgeId>, EdgeData = ()> + Default,
+    {
+        biconnected::block_cut_tree(self)
+    }
+
     #[cfg(feature = "pathfinding")]
     fn connected_components(&self) -> Vec<HashSet<Self::NodeId>> {
         pathfinding::prelude::connected_components(&self.node_ids().collect::<Vec<_>>(), |nid| {
             self.successors(nid).collect::<Vec<_>>()
         })
     }
+
+    /// Finds a spanning forest, ignoring edge weights.  See
+    /// [`spanning_tree::spanning_forest`].
+    fn spanning_forest(&self) -> Vec<Self::EdgeId>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        spanning_tree::spanning_forest(self)
+    }
+
+    /// Finds communities by label propagation, with edges weighted by
+    /// `weight_fn`.  See [`community`].
+    fn label_propagation(
+        &self,
+        weight_fn: impl Fn(&Self::EdgeId) -> f64,
+        max_iterations: usize,
+    ) -> Partition<Self::NodeId>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        community::label_propagation(self, weight_fn, max_iterations)
+    }
+
+    /// Finds communities with the Louvain method, with edges weighted by
+    /// `weight_fn`.  See [`community`].
+    fn louvain(&self, weight_fn: impl Fn(&Self::EdgeId) -> f64) -> Partition<Self::NodeId>
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        community::louvain(self, weight_fn)
+    }
+
+    /// Computes the modularity of an assignment of nodes to communities,
+    /// with edges weighted by `weight_fn`.  See [`community`].
+    fn modularity(
+        &self,
+        weight_fn: impl Fn(&Self::EdgeId) -> f64,
+        community: &HashMap<Self::NodeId, usize>,
+    ) -> f64
+    where
+        Self: Graph<Directedness = Undirected>,
+    {
+        community::modularity(self, weight_fn, community)
+    }
+
+    /// Finds a minimum spanning forest using Borůvka's algorithm.  See
+    /// [`spanning_tree`](crate::spanning_tree) for other algorithms.
+    fn minimum_spanning_forest<C>(
+        &self,
+        weight_fn: impl Fn(&Self::EdgeId) -> C,
+    ) -> SpanningForest<Self::EdgeId, C>
+    where
+        Self: Graph<Directedness = Undirected>,
+        C: Default + Ord + Copy + Add<Output = C>,
+    {
+        spanning_tree::boruvka(self, weight_fn)
+    }
+
+    /// Like [`Self::minimum_spanning_forest`], but searches for the cheapest
+    /// edge leaving each component in parallel.
+    #[cfg(feature = "rayon")]
+    fn par_minimum_spanning_forest<C>(
+        &self,
+        weight_fn: impl Fn(&Self::EdgeId) -> C,
+    ) -> SpanningForest<Self::EdgeId, C>
+    where
+        Self: Graph<Directedness = Undirected>,
+        C: Default + Ord + Copy + Add<Output = C> + Send + Sync,
+    {
+        spanning_tree::par_boruvka(self, weight_fn)
+    }
 }
 
-impl<G> GraphUndirected for G where G: Graph<Directedness = Undirected> {}
+impl<G> GraphUndirected for G where G: Graph<Directedness = Undirected> + ?Sized {}
 
 /// A trait for graphs that support mutation operations.
 ///
@@ -436,6 +1483,20 @@ pub trait GraphMut: Graph {
     /// Gets a mutable reference to the data associated with an edge.
     fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData;
 
+    /// Like [`Self::node_data_mut`], but returns `None` instead of panicking
+    /// if `id` is not a valid node ID.
+    fn get_node_data_mut(&mut self, id: &Self::NodeId) -> Option<&mut Self::NodeData> {
+        self.check_node_id(id).ok()?;
+        Some(self.node_data_mut(id))
+    }
+
+    /// Like [`Self::edge_data_mut`], but returns `None` instead of panicking
+    /// if `id` is not a valid edge ID.
+    fn get_edge_data_mut(&mut self, id: &Self::EdgeId) -> Option<&mut Self::EdgeData> {
+        self.check_edge_id(id).ok()?;
+        Some(self.edge_data_mut(id))
+    }
+
     /// Removes all nodes and edges from the graph.
     fn clear(&mut self) {
         for nid in self.node_ids().collect::<Vec<_>>() {
@@ -450,6 +1511,38 @@ pub trait GraphMut: Graph {
     /// connected to the node are also be removed.
     fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData;
 
+    /// Like [`Self::remove_node`], but returns an error instead of panicking
+    /// if `id` is not a valid node ID.
+    fn try_remove_node(&mut self, id: &Self::NodeId) -> Result<Self::NodeData, GraphError> {
+        self.check_node_id(id)?;
+        Ok(self.remove_node(id))
+    }
+
+    /// Like [`Self::remove_node`], but also returns the ends and data of the
+    /// edges removed along with the node, in order of their IDs.  The ends
+    /// include the ID of the removed node, which is no longer valid.
+    #[allow(clippy::type_complexity)]
+    fn remove_node_with_edges(
+        &mut self,
+        id: &Self::NodeId,
+    ) -> (
+        Self::NodeData,
+        Vec<(
+            <Self::Directedness as Directedness>::EndPair<Self::NodeId>,
+            Self::EdgeData,
+        )>,
+    ) {
+        let mut edges: Vec<_> = self.edges_from(id).chain(self.edges_into(id)).collect();
+        // A self-loop may be listed more than once.
+        edges.sort();
+        edges.dedup();
+        let edges = edges
+            .into_iter()
+            .map(|eid| (self.edge_ends(&eid), self.remove_edge(&eid)))
+            .collect();
+        (self.remove_node(id), edges)
+    }
+
     /// Adds an edge with the given data between two nodes and returns the
     /// `EdgeId`.  Use [`Self::add_edge`] for graphs that do not
     /// support parallel edges.
@@ -463,28 +1556,134 @@ pub trait GraphMut: Graph {
         Self: Graph<EdgeMultiplicity = MultipleEdges>,
     {
         match self.add_edge(from, into, data) {
-            (eid, None) => eid,
-            (_, Some(_)) => {
+            AddEdgeResult::Added(eid) => eid,
+            AddEdgeResult::Updated { .. } => {
                 unreachable!("Edge already exists between {:?} and {:?}", from, into)
             }
         }
     }
 
-    /// Adds an edge if possible, or replaces the data of an existing edge.  If
-    /// a new edge is added, returns its `EdgeId` and `None`.  If an edge
-    /// already exists, returns `(new_id, Some((old_id, old_data)))`.  The
-    /// `new_id` may be the same as the `old_id` if the graph implementation
-    /// reuses edge IDs when replacing edges.
+    /// Adds an edge if possible, or replaces the data of an existing edge.
+    /// Returns [`AddEdgeResult::Added`] with the new edge's ID, or
+    /// [`AddEdgeResult::Updated`] with the old ID and data if an edge already
+    /// existed.  The new ID may be the same as the old one if the graph
+    /// implementation reuses edge IDs when replacing edges.  To treat
+    /// replacing an edge as an error, use [`Self::add_edge_strict`].
     fn add_edge(
         &mut self,
         from: &Self::NodeId,
         into: &Self::NodeId,
         data: Self::EdgeData,
-    ) -> (Self::EdgeId, Option<(Self::EdgeId, Self::EdgeData)>);
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData>;
+
+    /// Like [`Self::add_edge`], but returns an error instead of panicking if
+    /// either end is not a valid node ID.  The graph is unchanged if an
+    /// error is returned.
+    fn try_add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> Result<AddEdgeResult<Self::EdgeId, Self::EdgeData>, GraphError> {
+        self.check_node_id(from)?;
+        self.check_node_id(into)?;
+        Ok(self.add_edge(from, into, data))
+    }
+
+    /// Adds a new edge, returning its ID, or returns
+    /// [`GraphError::DuplicateEdge`] instead of replacing an existing edge
+    /// when the graph does not allow parallel edges.  Other errors are
+    /// reported as by [`Self::try_add_edge`].  The graph is unchanged if an
+    /// error is returned.
+    fn add_edge_strict(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> Result<Self::EdgeId, GraphError> {
+        self.check_node_id(from)?;
+        self.check_node_id(into)?;
+        if !self.allows_parallel_edges() && self.has_edge_from_into(from, into) {
+            return Err(GraphError::DuplicateEdge);
+        }
+        let result = self.add_edge(from, into, data);
+        debug_assert!(result.is_added(), "an existing edge was replaced");
+        Ok(result.into_edge_id())
+    }
 
     /// Remove an edge between two nodes, returning its data.
     fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData;
 
+    /// Like [`Self::remove_edge`], but returns an error instead of panicking
+    /// if `id` is not a valid edge ID.
+    fn try_remove_edge(&mut self, id: &Self::EdgeId) -> Result<Self::EdgeData, GraphError> {
+        self.check_edge_id(id)?;
+        Ok(self.remove_edge(id))
+    }
+
+    /// Reverses the direction of an edge, keeping its data.  Returns the
+    /// edge's new ID and any edge it replaced, as with [`Self::add_edge`].
+    /// Does nothing in an undirected graph, returning the edge's ID as
+    /// [`AddEdgeResult::Added`].
+    fn reverse_edge(&mut self, id: &Self::EdgeId) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        if !self.is_directed() {
+            return AddEdgeResult::Added(id.clone());
+        }
+        let (from, into) = self.edge_ends(id).into_values();
+        let data = self.remove_edge(id);
+        self.add_edge(&into, &from, data)
+    }
+
+    /// Moves the nodes and edges of this graph into a new graph, converting
+    /// their data with `node_fn` and `edge_fn`.  The new graph has its own
+    /// IDs, but the same directedness and edge multiplicity, so every edge is
+    /// carried over.  To project the data without consuming the graph, use
+    /// [`Graph::map`].
+    fn into_map<H>(
+        self,
+        node_fn: impl FnMut(Self::NodeData) -> H::NodeData,
+        edge_fn: impl FnMut(Self::EdgeData) -> H::EdgeData,
+    ) -> H
+    where
+        Self: Sized,
+        H: GraphMut<Directedness = Self::Directedness, EdgeMultiplicity = Self::EdgeMultiplicity>
+            + Default,
+    {
+        mapped_graph::into_map(self, node_fn, edge_fn)
+    }
+
+    /// Applies `f` to the data of every edge in the graph.
+    fn transform_edge_data(&mut self, mut f: impl FnMut(&mut Self::EdgeData)) {
+        for eid in self.edge_ids().collect::<Vec<_>>() {
+            f(self.edge_data_mut(&eid));
+        }
+    }
+
+    /// Like [`Graph::normalize_weights`], but stores the normalized weights
+    /// in the edge data with `set_weight`.  See [`normalize`].
+    fn normalize_weights_in_place(
+        &mut self,
+        weight_fn: impl Fn(&Self::EdgeData) -> f64,
+        scheme: Normalization,
+        set_weight: impl FnMut(&mut Self::EdgeData, f64),
+    ) {
+        normalize::normalize_weights_in_place(self, weight_fn, scheme, set_weight)
+    }
+
+    /// Removes the edge with the given index among the edges from one node
+    /// into another, returning its data, or `None` if there are not that
+    /// many edges.  The edges after it move down by one index.  See
+    /// [`Graph::edge_index_between`].
+    fn remove_nth_edge_between(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        index: usize,
+    ) -> Option<Self::EdgeData> {
+        let eid = self.nth_edge_between(from, into, index)?;
+        Some(self.remove_edge(&eid))
+    }
+
     /// Removes all edges from one node into another.
     fn remove_edges_from_into(&mut self, from: &Self::NodeId, into: &Self::NodeId) {
         for eid in self.edges_from_into(from, into).collect::<Vec<_>>() {
@@ -524,6 +1723,142 @@ pub trait GraphMut: Graph {
     /// Does nothing by default.
     fn shrink_to_fit(&mut self) {}
 
+    /// Compacts the graph as [`GraphMut::compact`] does, and updates the keys
+    /// of the given node and edge tables, such as
+    /// [`PropertyMap`](crate::property_map::PropertyMap)s, to match.
+    /// Entries for nodes and edges which have been removed are dropped.
+    fn compact_with(
+        &mut self,
+        node_tables: &mut [&mut dyn RemapKeys<Self::NodeId>],
+        edge_tables: &mut [&mut dyn RemapKeys<Self::EdgeId>],
+    ) {
+        let mut node_map = HashMap::new();
+        let mut edge_map = HashMap::new();
+        self.compact(Some(&mut node_map), Some(&mut edge_map));
+        // A graph whose compaction does nothing reports no IDs, so its
+        // surviving IDs map to themselves.
+        if node_map.is_empty() {
+            node_map = self.node_ids().map(|nid| (nid.clone(), nid)).collect();
+        }
+        if edge_map.is_empty() {
+            edge_map = self.edge_ids().map(|eid| (eid.clone(), eid)).collect();
+        }
+        for table in node_tables.iter_mut() {
+            table.remap_keys(&node_map);
+        }
+        for table in edge_tables.iter_mut() {
+            table.remap_keys(&edge_map);
+        }
+    }
+
+    /// Shrinks the graph as [`GraphMut::shrink_to_fit`] does, along with the
+    /// given node and edge tables.
+    fn shrink_to_fit_with(
+        &mut self,
+        node_tables: &mut [&mut dyn RemapKeys<Self::NodeId>],
+        edge_tables: &mut [&mut dyn RemapKeys<Self::EdgeId>],
+    ) {
+        self.shrink_to_fit();
+        for table in node_tables.iter_mut() {
+            table.shrink_to_fit();
+        }
+        for table in edge_tables.iter_mut() {
+            table.shrink_to_fit();
+        }
+    }
+
+    /// Reads a graph written by [`Graph::write_snapshot`], reading at most
+    /// `max_items` nodes, edges and collection elements.  See
+    /// [`binary_snapshot::read_snapshot`].
+    fn read_snapshot(input: &mut impl std::io::Read, max_items: u64) -> Result<Self, SnapshotError>
+    where
+        Self: Sized + Default,
+        Self::NodeData: EncodeDecode,
+        Self::EdgeData: EncodeDecode,
+    {
+        binary_snapshot::read_snapshot(input, max_items)
+    }
+
+    /// Constructs a graph from an adjacency matrix, with one node for each
+    /// row of the matrix and one edge for each entry.  Node `i` is added
+    /// `i`th, with data computed by `node_data_fn(i)`, and the data of the
+    /// edge for the entry at `(row, col)` is computed by
+    /// `edge_data_fn(row, col, value)`.  The number of nodes is the matrix's
+    /// [`size_bound`](AdjacencyMatrix::size_bound).
+    fn from_adjacency_matrix<M>(
+        matrix: &M,
+        node_data_fn: impl FnMut(usize) -> Self::NodeData,
+        edge_data_fn: impl FnMut(usize, usize, &M::Value) -> Self::EdgeData,
+    ) -> Self
+    where
+        Self: Sized + Default,
+        M: AdjacencyMatrix<Directedness = Self::Directedness>,
+    {
+        matrix_import::from_adjacency_matrix(matrix, node_data_fn, edge_data_fn)
+    }
+
+    /// Constructs a graph from a square two-dimensional array, with one node
+    /// for each row.  Node `i` is added `i`th, with data computed by
+    /// `node_data_fn(i)`.  For each element, `edge_fn(row, col, value)`
+    /// returns the data of the edge from `row` to `col`, or `None` if there
+    /// is no such edge, so it can apply a threshold to a weight matrix.  An
+    /// undirected graph only uses the upper triangle of the array, including
+    /// the diagonal.  Panics if the array is not square.
+    #[cfg(feature = "ndarray")]
+    fn from_ndarray<S>(
+        array: &ndarray::ArrayBase<S, ndarray::Ix2>,
+        node_data_fn: impl FnMut(usize) -> Self::NodeData,
+        edge_fn: impl FnMut(usize, usize, &S::Elem) -> Option<Self::EdgeData>,
+    ) -> Self
+    where
+        Self: Sized + Default,
+        S: ndarray::Data,
+    {
+        matrix_import::from_ndarray(array, node_data_fn, edge_fn)
+    }
+
+    /// Copies a [`petgraph::Graph`] into a new graph, adding nodes in order of
+    /// their indices.  See [`petgraph_interop`].
+    #[cfg(feature = "petgraph-interop")]
+    fn from_petgraph<Ix>(
+        source: &petgraph::Graph<
+            Self::NodeData,
+            Self::EdgeData,
+            <Self::Directedness as PetgraphDirectedness>::EdgeType,
+            Ix,
+        >,
+    ) -> Self
+    where
+        Self: Sized + Default,
+        Self::Directedness: PetgraphDirectedness,
+        Self::NodeData: Clone,
+        Self::EdgeData: Clone,
+        Ix: petgraph::graph::IndexType,
+    {
+        petgraph_interop::from_petgraph(source)
+    }
+
+    /// Copies a [`petgraph::stable_graph::StableGraph`] into a new graph,
+    /// adding nodes in order of their indices.  See [`petgraph_interop`].
+    #[cfg(feature = "petgraph-interop")]
+    fn from_stable_graph<Ix>(
+        source: &petgraph::stable_graph::StableGraph<
+            Self::NodeData,
+            Self::EdgeData,
+            <Self::Directedness as PetgraphDirectedness>::EdgeType,
+            Ix,
+        >,
+    ) -> Self
+    where
+        Self: Sized + Default,
+        Self::Directedness: PetgraphDirectedness,
+        Self::NodeData: Clone,
+        Self::EdgeData: Clone,
+        Ix: petgraph::graph::IndexType,
+    {
+        petgraph_interop::from_stable_graph(source)
+    }
+
     /// Parses a DOT representation of a graph from a string, using the given
     /// graph builder to construct the graph.
     #[cfg(feature = "dot")]
@@ -534,4 +1869,18 @@ pub trait GraphMut: Graph {
     {
         parser::parse_dot_into_graph(data, builder)
     }
+
+    /// Reads a graph from node-link JSON, as used by D3 and NetworkX.  See
+    /// [`json::from_json_graph`].
+    #[cfg(feature = "json")]
+    fn from_json_graph<E>(
+        value: &serde_json::Value,
+        node_data_fn: impl FnMut(&json::JsonAttrs) -> Result<Self::NodeData, E>,
+        edge_data_fn: impl FnMut(&json::JsonAttrs) -> Result<Self::EdgeData, E>,
+    ) -> Result<Self, json::NodeLinkError<E>>
+    where
+        Self: Sized + Default,
+    {
+        json::from_json_graph(value, node_data_fn, edge_data_fn)
+    }
 }
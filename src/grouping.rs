@@ -0,0 +1,256 @@
+//! Statistics about groups of nodes.
+//!
+//! [`group_nodes_by`] partitions the nodes of a graph by a key computed from
+//! their data, such as the team which owns each module of a code graph, and
+//! counts the edges within each group and between each pair of groups.
+//!
+//! An edge is internal to a group if both of its ends are in the group, and
+//! external to each group containing one of its ends otherwise.  A self-loop
+//! is internal to the group of its node.
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, prelude::*};
+
+/// A group of nodes which share the same key.
+pub struct NodeGroup<G>
+where
+    G: Graph + ?Sized,
+{
+    /// The nodes in the group, in the order returned by
+    /// [`Graph::node_ids`].
+    pub nodes: Vec<G::NodeId>,
+    /// The number of edges with both ends in the group.
+    pub internal_edges: usize,
+    /// The number of edges with exactly one end in the group.
+    pub external_edges: usize,
+}
+
+impl<G> NodeGroup<G>
+where
+    G: Graph + ?Sized,
+{
+    /// Returns the number of edges with at least one end in the group.
+    pub fn incident_edges(&self) -> usize {
+        self.internal_edges + self.external_edges
+    }
+
+    /// Returns the fraction of edges incident to the group which are
+    /// internal to it, or `None` if no edges are incident to the group.
+    pub fn internal_ratio(&self) -> Option<f64> {
+        let incident = self.incident_edges();
+        (incident > 0).then(|| self.internal_edges as f64 / incident as f64)
+    }
+}
+
+impl<G> Debug for NodeGroup<G>
+where
+    G: Graph + ?Sized,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeGroup")
+            .field("nodes", &self.nodes)
+            .field("internal_edges", &self.internal_edges)
+            .field("external_edges", &self.external_edges)
+            .finish()
+    }
+}
+
+/// The nodes of a graph grouped by key, with the number of edges between
+/// each pair of groups.
+pub struct NodeGrouping<K, G>
+where
+    G: Graph + ?Sized,
+{
+    /// The groups, by key.
+    pub groups: HashMap<K, NodeGroup<G>>,
+    /// The number of edges between each pair of distinct groups.  In a
+    /// directed graph, the pair is the keys of the groups of the source and
+    /// target nodes.  In an undirected graph, each pair is stored once, in
+    /// the order in which it was first seen; use [`NodeGrouping::cut_size`]
+    /// to look up counts regardless of order.
+    pub cuts: HashMap<(K, K), usize>,
+    directed: bool,
+}
+
+impl<K, G> NodeGrouping<K, G>
+where
+    K: Eq + Hash + Clone,
+    G: Graph + ?Sized,
+{
+    /// Returns the number of groups.
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns true if the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Returns the group with the given key, if any.
+    pub fn group(&self, key: &K) -> Option<&NodeGroup<G>> {
+        self.groups.get(key)
+    }
+
+    /// Returns the number of edges between two distinct groups.  In a
+    /// directed graph, only edges from `from` into `into` are counted.
+    /// Returns 0 if `from` and `into` are equal.
+    pub fn cut_size(&self, from: &K, into: &K) -> usize {
+        let get = |a: &K, b: &K| self.cuts.get(&(a.clone(), b.clone())).copied().unwrap_or(0);
+        if self.directed || from == into {
+            get(from, into)
+        } else {
+            get(from, into) + get(into, from)
+        }
+    }
+
+    /// Returns the total number of edges between distinct groups.
+    pub fn total_cut_size(&self) -> usize {
+        self.cuts.values().sum()
+    }
+}
+
+impl<K, G> Debug for NodeGrouping<K, G>
+where
+    K: Debug,
+    G: Graph + ?Sized,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeGrouping")
+            .field("groups", &self.groups)
+            .field("cuts", &self.cuts)
+            .finish()
+    }
+}
+
+/// Groups the nodes of a graph by the key `key_fn` returns for their data,
+/// and counts the internal and external edges of each group and the edges
+/// between each pair of groups.
+pub fn group_nodes_by<G, K>(
+    graph: &G,
+    mut key_fn: impl FnMut(&G::NodeData) -> K,
+) -> NodeGrouping<K, G>
+where
+    G: Graph + ?Sized,
+    K: Eq + Hash + Clone,
+{
+    let mut groups: HashMap<K, NodeGroup<G>> = HashMap::new();
+    let mut keys = FastHashMap::default();
+    for nid in graph.node_ids() {
+        let key = key_fn(graph.node_data(&nid));
+        groups
+            .entry(key.clone())
+            .or_insert_with(|| NodeGroup {
+                nodes: Vec::new(),
+                internal_edges: 0,
+                external_edges: 0,
+            })
+            .nodes
+            .push(nid.clone());
+        keys.insert(nid, key);
+    }
+
+    let directed = graph.is_directed();
+    let mut cuts: HashMap<(K, K), usize> = HashMap::new();
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        let from_key = &keys[&from];
+        let into_key = &keys[&into];
+        if from_key == into_key {
+            groups
+                .get_mut(from_key)
+                .expect("key should have a group")
+                .internal_edges += 1;
+            continue;
+        }
+        groups
+            .get_mut(from_key)
+            .expect("key should have a group")
+            .external_edges += 1;
+        groups
+            .get_mut(into_key)
+            .expect("key should have a group")
+            .external_edges += 1;
+        let reversed = (into_key.clone(), from_key.clone());
+        if !directed && cuts.contains_key(&reversed) {
+            *cuts.get_mut(&reversed).expect("pair should be present") += 1;
+        } else {
+            *cuts
+                .entry((from_key.clone(), into_key.clone()))
+                .or_default() += 1;
+        }
+    }
+
+    NodeGrouping {
+        groups,
+        cuts,
+        directed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_directed() {
+        let mut graph = LinkedGraph::<(&str, &str), (), Directed>::default();
+        let a1 = graph.add_node(("a", "parser"));
+        let a2 = graph.add_node(("a", "lexer"));
+        let b1 = graph.add_node(("b", "codegen"));
+        let c1 = graph.add_node(("c", "driver"));
+        graph.add_new_edge(&a1, &a2, ());
+        graph.add_new_edge(&a1, &a1, ());
+        graph.add_new_edge(&b1, &a1, ());
+        graph.add_new_edge(&b1, &a2, ());
+        graph.add_new_edge(&c1, &b1, ());
+        graph.add_new_edge(&a2, &b1, ());
+
+        let grouping = graph.group_nodes_by(|&(team, _)| team);
+        assert_eq!(grouping.len(), 3);
+
+        let a = grouping.group(&"a").unwrap();
+        assert_eq!(a.nodes, vec![a1, a2]);
+        assert_eq!(a.internal_edges, 2);
+        assert_eq!(a.external_edges, 3);
+        assert_eq!(a.internal_ratio(), Some(0.4));
+
+        let c = grouping.group(&"c").unwrap();
+        assert_eq!((c.internal_edges, c.external_edges), (0, 1));
+        assert_eq!(c.internal_ratio(), Some(0.0));
+
+        assert_eq!(grouping.cut_size(&"b", &"a"), 2);
+        assert_eq!(grouping.cut_size(&"a", &"b"), 1);
+        assert_eq!(grouping.cut_size(&"a", &"c"), 0);
+        assert_eq!(grouping.cut_size(&"a", &"a"), 0);
+        assert_eq!(grouping.total_cut_size(), 4);
+    }
+
+    #[test]
+    fn test_undirected() {
+        let mut graph = BagGraph::<u32, (), Undirected>::default();
+        let n: Vec<_> = (0..5).map(|i| graph.add_node(i)).collect();
+        graph.add_new_edge(&n[0], &n[2], ());
+        graph.add_new_edge(&n[1], &n[2], ());
+        graph.add_new_edge(&n[2], &n[1], ());
+        graph.add_new_edge(&n[3], &n[1], ());
+
+        let grouping = graph.group_nodes_by(|&i| i % 2);
+        assert_eq!(grouping.len(), 2);
+        let even = grouping.group(&0).unwrap();
+        assert_eq!(even.nodes.len(), 3);
+        assert_eq!((even.internal_edges, even.external_edges), (1, 2));
+        let odd = grouping.group(&1).unwrap();
+        assert_eq!((odd.internal_edges, odd.external_edges), (1, 2));
+        assert_eq!(grouping.cut_size(&0, &1), 2);
+        assert_eq!(grouping.cut_size(&1, &0), 2);
+        assert_eq!(grouping.total_cut_size(), 2);
+
+        let empty = BagGraph::<u32, (), Undirected>::default().group_nodes_by(|&i| i);
+        assert!(empty.is_empty());
+        assert_eq!(empty.total_cut_size(), 0);
+    }
+}
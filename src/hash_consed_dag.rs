@@ -0,0 +1,234 @@
+//! A directed acyclic graph with automatic structural sharing.
+//!
+//! A [`HashConsedDag`] stores each distinct combination of node data and
+//! children once.  Adding a node which is structurally identical to an
+//! existing one (it has equal data and the same children in the same order)
+//! returns the existing node's ID instead of creating a new node.  Since
+//! children must exist before their parents, structurally identical sub-DAGs
+//! are always shared, which makes the type suitable for building expression
+//! DAGs and similar structures.
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+use crate::prelude::*;
+
+/// The data and children which identify a node.
+type NodeKey<G> = (<G as Graph>::NodeData, Vec<<G as Graph>::NodeId>);
+
+/// A wrapper around a directed graph which deduplicates structurally
+/// identical nodes.  See the [module documentation](self).
+///
+/// Each edge goes from a node to one of its children, and its data is the
+/// position of the child in the node's list of children, so a node may have
+/// the same child more than once.  The graph can only be modified through
+/// [`HashConsedDag::add_node`], so it implements [`Graph`] but not
+/// [`GraphMut`].
+pub struct HashConsedDag<G>
+where
+    G: Graph,
+{
+    inner: G,
+    index: HashMap<NodeKey<G>, G::NodeId>,
+}
+
+impl<G> HashConsedDag<G>
+where
+    G: GraphMut<Directedness = Directed, EdgeMultiplicity = MultipleEdges, EdgeData = usize>
+        + Default,
+    G::NodeData: Hash + Eq + Clone,
+{
+    /// Creates an empty DAG.
+    pub fn new() -> Self {
+        Self {
+            inner: G::default(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<G> HashConsedDag<G>
+where
+    G: GraphMut<Directedness = Directed, EdgeMultiplicity = MultipleEdges, EdgeData = usize>,
+    G::NodeData: Hash + Eq + Clone,
+{
+    /// Returns the ID of the node with the given data and children, adding it
+    /// if there is no such node.  Panics if any child is not a node of this
+    /// graph.
+    pub fn add_node(&mut self, data: G::NodeData, children: &[G::NodeId]) -> G::NodeId {
+        let key = (data, children.to_vec());
+        if let Some(id) = self.index.get(&key) {
+            return id.clone();
+        }
+        let id = self.inner.add_node(key.0.clone());
+        for (position, child) in children.iter().enumerate() {
+            self.inner.add_new_edge(&id, child, position);
+        }
+        self.index.insert(key, id.clone());
+        id
+    }
+
+    /// Returns the ID of the node with the given data and children, if there
+    /// is one.
+    pub fn find_node(&self, data: &G::NodeData, children: &[G::NodeId]) -> Option<G::NodeId> {
+        // The key must be owned to look it up, so this clones `data`.
+        self.index.get(&(data.clone(), children.to_vec())).cloned()
+    }
+}
+
+impl<G> HashConsedDag<G>
+where
+    G: Graph<Directedness = Directed, EdgeData = usize>,
+{
+    /// Returns the children of a node, in order.
+    pub fn children(&self, id: &G::NodeId) -> Vec<G::NodeId> {
+        let mut edges: Vec<_> = self
+            .inner
+            .edges_from(id)
+            .map(|eid| (*self.inner.edge_data(&eid), eid))
+            .collect();
+        edges.sort_by_key(|(position, _)| *position);
+        edges
+            .into_iter()
+            .map(|(_, eid)| self.inner.edge_ends(&eid).1)
+            .collect()
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding the index of nodes.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+}
+
+impl<G> Default for HashConsedDag<G>
+where
+    G: GraphMut<Directedness = Directed, EdgeMultiplicity = MultipleEdges, EdgeData = usize>
+        + Default,
+    G::NodeData: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G> Debug for HashConsedDag<G>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashConsedDag")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<G> Graph for HashConsedDag<G>
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_into(into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    type Expr = HashConsedDag<BagGraph<&'static str, usize, Directed>>;
+
+    #[test]
+    fn test_shared_subexpressions() {
+        // (a + b) * (a + b) - a
+        let mut dag = Expr::new();
+        let a = dag.add_node("a", &[]);
+        let b = dag.add_node("b", &[]);
+        let sum1 = dag.add_node("+", &[a.clone(), b.clone()]);
+        let sum2 = dag.add_node("+", &[a.clone(), b.clone()]);
+        assert_eq!(sum1, sum2);
+        let product = dag.add_node("*", &[sum1.clone(), sum2.clone()]);
+        let a2 = dag.add_node("a", &[]);
+        let root = dag.add_node("-", &[product.clone(), a2]);
+
+        assert_eq!(dag.num_nodes(), 5);
+        assert_eq!(dag.num_edges(), 6);
+        assert_eq!(dag.children(&product), vec![sum1.clone(), sum1.clone()]);
+        assert_eq!(dag.children(&root), vec![product, a.clone()]);
+        assert_eq!(dag.find_node(&"+", &[a.clone(), b.clone()]), Some(sum1));
+    }
+
+    #[test]
+    fn test_child_order_matters() {
+        let mut dag = HashConsedDag::<LinkedGraph<char, usize, Directed>>::default();
+        let x = dag.add_node('x', &[]);
+        let y = dag.add_node('y', &[]);
+        let xy = dag.add_node('-', &[x.clone(), y.clone()]);
+        let yx = dag.add_node('-', &[y.clone(), x.clone()]);
+        assert_ne!(xy, yx);
+        assert_eq!(dag.children(&yx), vec![y, x]);
+        assert_eq!(dag.find_node(&'+', &[]), None);
+        assert_eq!(dag.into_inner().num_nodes(), 4);
+    }
+}
@@ -0,0 +1,31 @@
+//! The hasher used by hash-based storage and by algorithms.
+//!
+//! Node and edge IDs are small values which are hashed very often, and which
+//! rarely come from untrusted input, so the DoS resistance of the standard
+//! library's SipHash is usually not worth its cost.  With the `fxhash`
+//! feature, [`DefaultHashBuilder`] is the much faster FxHash from the
+//! `rustc-hash` crate; otherwise it is [`RandomState`].
+//!
+//! [`DefaultHashBuilder`] is the default hasher of
+//! [`HashStorage`](crate::HashStorage), which takes the hasher as a type
+//! parameter to allow choosing another, and of the maps and sets used
+//! internally by algorithms.  Maps returned by algorithms use the standard
+//! hasher, so that their types do not depend on which features are enabled.
+
+use std::collections::{HashMap, HashSet};
+#[cfg(not(feature = "fxhash"))]
+use std::hash::RandomState;
+
+/// The hasher used when none is specified.
+#[cfg(feature = "fxhash")]
+pub type DefaultHashBuilder = rustc_hash::FxBuildHasher;
+
+/// The hasher used when none is specified.
+#[cfg(not(feature = "fxhash"))]
+pub type DefaultHashBuilder = RandomState;
+
+/// A [`HashMap`] using [`DefaultHashBuilder`].
+pub type FastHashMap<K, V> = HashMap<K, V, DefaultHashBuilder>;
+
+/// A [`HashSet`] using [`DefaultHashBuilder`].
+pub type FastHashSet<T> = HashSet<T, DefaultHashBuilder>;
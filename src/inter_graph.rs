@@ -0,0 +1,403 @@
+//! Links between nodes of different graphs.
+//!
+//! A large model is sometimes split into several graphs, with some
+//! references crossing from one graph into another.  Such references cannot
+//! be stored as edges, so an [`InterGraphLinks`] registry records them
+//! instead, along with the key of the graph each end belongs to.  The keys
+//! are chosen by the caller, e.g. a partition number, and all the graphs
+//! must have the same node ID type.  [`InterGraphLinks::insert`] checks each
+//! end against the graph given for it, so with graphs whose node IDs
+//! identify their graph, such as [`LinkedGraph`](crate::LinkedGraph), a node
+//! filed under the wrong key is rejected with [`GraphError::ForeignId`].
+//!
+//! Removing a node from a graph does not remove the links to it, so the
+//! registry can be checked against each graph with
+//! [`InterGraphLinks::check_graph`], and broken links can be dropped with
+//! [`InterGraphLinks::retain_valid`].  When a graph is compacted, pass
+//! [`InterGraphLinks::remap_for`] to [`GraphMut::compact_with`] so the links
+//! follow its nodes; links to nodes which no longer exist are dropped.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    hash::Hash,
+};
+
+use crate::{hashing::FastHashMap, prelude::*, property_map::RemapKeys};
+
+/// Identifies a link in an [`InterGraphLinks`] registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LinkId(usize);
+
+/// A link from a node of one graph to a node of another.  Each end is a
+/// graph key and a node ID in that graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link<P, N, L> {
+    pub from: (P, N),
+    pub into: (P, N),
+    pub data: L,
+}
+
+/// A registry of links, carrying data of type `L`, between nodes of graphs
+/// identified by keys of type `P`.  See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct InterGraphLinks<P, N, L> {
+    links: FastHashMap<LinkId, Link<P, N, L>>,
+    by_node: FastHashMap<(P, N), BTreeSet<LinkId>>,
+    by_pair: FastHashMap<(P, P), BTreeSet<LinkId>>,
+    next_id: usize,
+}
+
+impl<P, N, L> InterGraphLinks<P, N, L>
+where
+    P: Eq + Hash + Clone,
+    N: Eq + Hash + Clone,
+{
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            links: FastHashMap::default(),
+            by_node: FastHashMap::default(),
+            by_pair: FastHashMap::default(),
+            next_id: 0,
+        }
+    }
+
+    /// Returns the number of links.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Returns true if there are no links.
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Adds a link from node `from.1` of graph `from.0` to node `into.1` of
+    /// graph `into.0`, without checking that the nodes belong to those
+    /// graphs.  Panics if both ends are in the same graph.
+    pub fn insert_unchecked(&mut self, from: (P, N), into: (P, N), data: L) -> LinkId {
+        assert!(from.0 != into.0, "a link must join two different graphs");
+        let id = LinkId(self.next_id);
+        self.next_id += 1;
+        self.by_node.entry(from.clone()).or_default().insert(id);
+        self.by_node.entry(into.clone()).or_default().insert(id);
+        self.by_pair
+            .entry((from.0.clone(), into.0.clone()))
+            .or_default()
+            .insert(id);
+        self.links.insert(id, Link { from, into, data });
+        id
+    }
+
+    /// Adds a link from node `from.1` of graph `from.0`, which is
+    /// `from_graph`, to node `into.1` of graph `into.0`, which is
+    /// `into_graph`.  Returns the error reported by [`Graph::check_node_id`]
+    /// if an end is not a valid node of its graph.  Panics if both ends are
+    /// in the same graph.
+    pub fn insert<G1, G2>(
+        &mut self,
+        from_graph: &G1,
+        from: (P, N),
+        into_graph: &G2,
+        into: (P, N),
+        data: L,
+    ) -> Result<LinkId, GraphError>
+    where
+        G1: Graph<NodeId = N> + ?Sized,
+        G2: Graph<NodeId = N> + ?Sized,
+    {
+        from_graph.check_node_id(&from.1)?;
+        into_graph.check_node_id(&into.1)?;
+        Ok(self.insert_unchecked(from, into, data))
+    }
+
+    /// Gets a link.
+    pub fn get(&self, id: LinkId) -> Option<&Link<P, N, L>> {
+        self.links.get(&id)
+    }
+
+    /// Gets mutable access to the data of a link.
+    pub fn data_mut(&mut self, id: LinkId) -> Option<&mut L> {
+        self.links.get_mut(&id).map(|link| &mut link.data)
+    }
+
+    /// Removes a link, returning it if it existed.
+    pub fn remove(&mut self, id: LinkId) -> Option<Link<P, N, L>> {
+        let link = self.links.remove(&id)?;
+        for end in [&link.from, &link.into] {
+            remove_from_index(&mut self.by_node, end, id);
+        }
+        remove_from_index(
+            &mut self.by_pair,
+            &(link.from.0.clone(), link.into.0.clone()),
+            id,
+        );
+        Some(link)
+    }
+
+    /// Returns every link, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (LinkId, &Link<P, N, L>)> {
+        let mut ids: Vec<_> = self.links.keys().copied().collect();
+        ids.sort();
+        ids.into_iter().map(|id| (id, &self.links[&id]))
+    }
+
+    /// Returns the links from nodes of graph `from` to nodes of graph `into`,
+    /// in the order they were added.
+    pub fn links_between<'a>(
+        &'a self,
+        from: &P,
+        into: &P,
+    ) -> impl Iterator<Item = (LinkId, &'a Link<P, N, L>)> + 'a {
+        self.by_pair
+            .get(&(from.clone(), into.clone()))
+            .into_iter()
+            .flatten()
+            .map(|id| (*id, &self.links[id]))
+    }
+
+    /// Returns the links to or from node `node` of graph `graph`, in the
+    /// order they were added.
+    pub fn links_of<'a>(
+        &'a self,
+        graph: &P,
+        node: &N,
+    ) -> impl Iterator<Item = (LinkId, &'a Link<P, N, L>)> + 'a {
+        self.by_node
+            .get(&(graph.clone(), node.clone()))
+            .into_iter()
+            .flatten()
+            .map(|id| (*id, &self.links[id]))
+    }
+
+    /// Removes the links to or from node `node` of graph `graph`, e.g.
+    /// before removing the node itself.
+    pub fn remove_links_of(&mut self, graph: &P, node: &N) -> Vec<Link<P, N, L>> {
+        let ids = self
+            .by_node
+            .get(&(graph.clone(), node.clone()))
+            .cloned()
+            .unwrap_or_default();
+        ids.into_iter().filter_map(|id| self.remove(id)).collect()
+    }
+
+    /// Removes every link to or from a node of graph `graph`.
+    pub fn remove_graph(&mut self, graph: &P) -> Vec<Link<P, N, L>> {
+        let ids: BTreeSet<_> = self
+            .links
+            .iter()
+            .filter(|(_, link)| link.from.0 == *graph || link.into.0 == *graph)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.into_iter().filter_map(|id| self.remove(id)).collect()
+    }
+
+    /// Checks the ends of links in graph `key` against `graph`, returning
+    /// the links whose end is not a valid node of `graph`, with the error
+    /// reported by [`Graph::check_node_id`].
+    pub fn check_graph<G>(&self, key: &P, graph: &G) -> Vec<(LinkId, GraphError)>
+    where
+        G: Graph<NodeId = N> + ?Sized,
+    {
+        let mut errors: Vec<_> = self
+            .by_node
+            .iter()
+            .filter(|((p, _), _)| p == key)
+            .filter_map(|((_, nid), ids)| Some((graph.check_node_id(nid).err()?, ids)))
+            .flat_map(|(error, ids)| ids.iter().map(move |id| (*id, error)))
+            .collect();
+        errors.sort_by_key(|(id, _)| *id);
+        errors
+    }
+
+    /// Removes the links whose end in graph `key` is not a valid node of
+    /// `graph`, returning them in the order they were added.
+    pub fn retain_valid<G>(&mut self, key: &P, graph: &G) -> Vec<Link<P, N, L>>
+    where
+        G: Graph<NodeId = N> + ?Sized,
+    {
+        self.check_graph(key, graph)
+            .into_iter()
+            .filter_map(|(id, _)| self.remove(id))
+            .collect()
+    }
+
+    /// Replaces each node ID of graph `key` with the ID it maps to in `map`,
+    /// which has an entry for every node that survived a compaction.  Links
+    /// whose end in graph `key` does not appear in `map` are removed, since
+    /// their IDs may now belong to other nodes, and returned in the order
+    /// they were added.
+    pub fn remap_nodes(&mut self, key: &P, map: &HashMap<N, N>) -> Vec<Link<P, N, L>> {
+        let stale: BTreeSet<_> = self
+            .by_node
+            .iter()
+            .filter(|((p, nid), _)| p == key && !map.contains_key(nid))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        let removed = stale.into_iter().filter_map(|id| self.remove(id)).collect();
+        // Remapping one node at a time could move a node's links onto an ID
+        // which has not been remapped yet, so take all the links first.
+        let moved: Vec<_> = map
+            .iter()
+            .filter_map(|(old, new)| {
+                let ids = self.by_node.remove(&(key.clone(), old.clone()))?;
+                Some((old, new, ids))
+            })
+            .collect();
+        for (old, new, ids) in moved {
+            for id in &ids {
+                let link = self.links.get_mut(id).unwrap();
+                for end in [&mut link.from, &mut link.into] {
+                    if end.0 == *key && end.1 == *old {
+                        end.1 = new.clone();
+                    }
+                }
+            }
+            self.by_node
+                .entry((key.clone(), new.clone()))
+                .or_default()
+                .extend(ids);
+        }
+        removed
+    }
+
+    /// Returns a table which updates the links of graph `key` when passed to
+    /// [`GraphMut::compact_with`] for that graph.
+    pub fn remap_for(&mut self, key: P) -> RemapLinks<'_, P, N, L> {
+        RemapLinks { links: self, key }
+    }
+}
+
+impl<P, N, L> Default for InterGraphLinks<P, N, L>
+where
+    P: Eq + Hash + Clone,
+    N: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The links of one graph in an [`InterGraphLinks`] registry, as a table
+/// for [`GraphMut::compact_with`].  See [`InterGraphLinks::remap_for`].
+pub struct RemapLinks<'a, P, N, L> {
+    links: &'a mut InterGraphLinks<P, N, L>,
+    key: P,
+}
+
+impl<P, N, L> RemapKeys<N> for RemapLinks<'_, P, N, L>
+where
+    P: Eq + Hash + Clone,
+    N: Eq + Hash + Clone,
+{
+    fn remap_keys(&mut self, map: &HashMap<N, N>) {
+        self.links.remap_nodes(&self.key, map);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.links.links.shrink_to_fit();
+        self.links.by_node.shrink_to_fit();
+        self.links.by_pair.shrink_to_fit();
+    }
+}
+
+fn remove_from_index<K>(index: &mut FastHashMap<K, BTreeSet<LinkId>>, key: &K, id: LinkId)
+where
+    K: Eq + Hash,
+{
+    if let Some(ids) = index.get_mut(key) {
+        ids.remove(&id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LinkedGraph, SlabGraph};
+
+    use super::*;
+
+    fn data<'a, P: 'a, N: 'a>(
+        links: impl Iterator<Item = (LinkId, &'a Link<P, N, &'static str>)>,
+    ) -> Vec<&'static str> {
+        links.map(|(_, link)| link.data).collect()
+    }
+
+    #[test]
+    fn test_links_between_graphs() {
+        let mut left = LinkedGraph::<&str, (), Directed>::default();
+        let mut right = LinkedGraph::<&str, (), Directed>::default();
+        let a = left.add_node("a");
+        let b = left.add_node("b");
+        let x = right.add_node("x");
+
+        let mut links = InterGraphLinks::new();
+        let ax = links.insert_unchecked((0, a.clone()), (1, x.clone()), "ax");
+        let xb = links
+            .insert(&right, (1, x.clone()), &left, (0, b.clone()), "xb")
+            .unwrap();
+        // `x` belongs to `right`, so it is not a node of `left`.
+        assert_eq!(
+            links.insert(&left, (0, x.clone()), &right, (1, x.clone()), "bad"),
+            Err(GraphError::ForeignId)
+        );
+
+        assert_eq!(data(links.links_between(&0, &1)), vec!["ax"]);
+        assert_eq!(data(links.links_between(&1, &0)), vec!["xb"]);
+        assert_eq!(data(links.links_of(&1, &x)), vec!["ax", "xb"]);
+        assert!(links.check_graph(&0, &left).is_empty());
+
+        left.remove_node(&b);
+        assert_eq!(
+            links.check_graph(&0, &left),
+            vec![(xb, GraphError::InvalidNodeId)]
+        );
+        let removed = links.retain_valid(&0, &left);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].data, "xb");
+        assert_eq!(links.len(), 1);
+
+        assert_eq!(links.remove_graph(&1).len(), 1);
+        assert!(links.is_empty());
+        assert!(links.get(ax).is_none());
+    }
+
+    #[test]
+    fn test_remap_for_compaction() {
+        let mut graph = SlabGraph::<usize, (), Undirected>::default();
+        let nodes: Vec<_> = (0..4).map(|i| graph.add_node(i)).collect();
+        let mut other = SlabGraph::<usize, (), Undirected>::default();
+        let target = other.add_node(0);
+        let mut links = InterGraphLinks::new();
+        for (i, nid) in nodes.iter().enumerate().skip(1) {
+            links
+                .insert(&graph, ("main", *nid), &other, ("other", target), i)
+                .unwrap();
+        }
+        graph.remove_node(&nodes[0]);
+        graph.remove_node(&nodes[1]);
+        links.remove_links_of(&"main", &nodes[1]);
+
+        graph.compact_with(&mut [&mut links.remap_for("main")], &mut []);
+        assert!(links.check_graph(&"main", &graph).is_empty());
+        for (_, link) in links.iter() {
+            assert_eq!(*graph.node_data(&link.from.1), link.data);
+        }
+        assert_eq!(links.links_between(&"main", &"other").count(), 2);
+
+        // A link to a removed node is dropped by compaction, even when it was
+        // not removed along with the node, since the compacted graph may
+        // reuse the node's ID.
+        let second = graph.node_ids().find(|nid| graph.node_data(nid) == &2);
+        graph.remove_node(&second.unwrap());
+        graph.compact_with(&mut [&mut links.remap_for("main")], &mut []);
+        assert!(links.check_graph(&"main", &graph).is_empty());
+        assert_eq!(links.len(), 1);
+        let (_, link) = links.iter().next().unwrap();
+        assert_eq!(link.data, 3);
+        assert_eq!(*graph.node_data(&link.from.1), 3);
+        assert!(links.check_graph(&"other", &other).is_empty());
+    }
+}
@@ -0,0 +1,371 @@
+//! Export of graphs to the JSON formats used by JavaScript visualization
+//! libraries.
+//!
+//! - [`to_cytoscape_json`] produces the `elements` format accepted by
+//!   [Cytoscape.js](https://js.cytoscape.org/#notation/elements-json).
+//! - [`to_sigma_json`] produces the serialization format of
+//!   [graphology](https://graphology.github.io/serialization.html), which is
+//!   the graph model used by Sigma.js.
+//! - [`to_json_graph`] produces the node-link format used by D3 and
+//!   NetworkX, with a `nodes` array and a `links` array of objects with
+//!   `source` and `target` fields.  [`from_json_graph`] reads it back.
+//!
+//! In all formats, nodes are named `n0`, `n1`, etc. and edges are named
+//! `e0`, `e1`, etc., in the order they are returned by [`Graph::node_ids`]
+//! and [`Graph::edge_ids`].  The attributes of each element are computed by
+//! user-supplied closures.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value, json};
+
+use crate::{end_pair::EndPair, prelude::*};
+
+/// An error reading a graph with [`from_json_graph`].  `E` is the error
+/// type of the functions which convert attributes to node and edge data.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum NodeLinkError<E> {
+    /// A required field is missing or has the wrong type.
+    #[error("missing or invalid `{0}` field")]
+    Field(&'static str),
+    /// The `directed` field does not match the directedness of the graph
+    /// being built.
+    #[error("the graph's directedness does not match the target graph")]
+    Directedness,
+    /// Two nodes have the same ID.
+    #[error("duplicate node ID {0}")]
+    DuplicateNode(Value),
+    /// A link refers to a node ID which is not in the `nodes` array.
+    #[error("link refers to unknown node {0}")]
+    UnknownNode(Value),
+    /// The attributes of a node or edge could not be converted to its data.
+    #[error("invalid element data: {0}")]
+    Data(E),
+}
+
+/// A map of attribute names to values for a single node or edge.
+pub type JsonAttrs = Map<String, Value>;
+
+fn node_names<G>(graph: &G) -> HashMap<G::NodeId, String>
+where
+    G: Graph + ?Sized,
+{
+    graph
+        .node_ids()
+        .enumerate()
+        .map(|(i, nid)| (nid, format!("n{}", i)))
+        .collect()
+}
+
+/// Converts a graph to Cytoscape.js elements JSON.  The attributes returned
+/// by `node_attrs` and `edge_attrs` are placed in each element's `data`
+/// object alongside the `id`, `source` and `target` fields, which take
+/// precedence over attributes of the same name.
+pub fn to_cytoscape_json<G>(
+    graph: &G,
+    node_attrs: impl Fn(&G::NodeId, &G::NodeData) -> JsonAttrs,
+    edge_attrs: impl Fn(&G::EdgeId, &G::EdgeData) -> JsonAttrs,
+) -> Value
+where
+    G: Graph + ?Sized,
+{
+    let names = node_names(graph);
+    let nodes: Vec<Value> = graph
+        .node_ids()
+        .map(|nid| {
+            let mut data = node_attrs(&nid, graph.node_data(&nid));
+            data.insert("id".to_string(), json!(names[&nid]));
+            json!({ "data": data })
+        })
+        .collect();
+    let edges: Vec<Value> = graph
+        .edge_ids()
+        .enumerate()
+        .map(|(i, eid)| {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            let mut data = edge_attrs(&eid, graph.edge_data(&eid));
+            data.insert("id".to_string(), json!(format!("e{}", i)));
+            data.insert("source".to_string(), json!(names[&from]));
+            data.insert("target".to_string(), json!(names[&into]));
+            json!({ "data": data })
+        })
+        .collect();
+    json!({ "elements": { "nodes": nodes, "edges": edges } })
+}
+
+/// Converts a graph to the graphology serialization format used by Sigma.js.
+/// The graph's directedness and edge multiplicity are recorded in the
+/// `options` object.
+pub fn to_sigma_json<G>(
+    graph: &G,
+    node_attrs: impl Fn(&G::NodeId, &G::NodeData) -> JsonAttrs,
+    edge_attrs: impl Fn(&G::EdgeId, &G::EdgeData) -> JsonAttrs,
+) -> Value
+where
+    G: Graph + ?Sized,
+{
+    let names = node_names(graph);
+    let nodes: Vec<Value> = graph
+        .node_ids()
+        .map(|nid| {
+            json!({
+                "key": names[&nid],
+                "attributes": node_attrs(&nid, graph.node_data(&nid)),
+            })
+        })
+        .collect();
+    let edges: Vec<Value> = graph
+        .edge_ids()
+        .enumerate()
+        .map(|(i, eid)| {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            json!({
+                "key": format!("e{}", i),
+                "source": names[&from],
+                "target": names[&into],
+                "attributes": edge_attrs(&eid, graph.edge_data(&eid)),
+            })
+        })
+        .collect();
+    json!({
+        "attributes": {},
+        "options": {
+            "type": if graph.is_directed() { "directed" } else { "undirected" },
+            "multi": graph.allows_parallel_edges(),
+            "allowSelfLoops": true,
+        },
+        "nodes": nodes,
+        "edges": edges,
+    })
+}
+
+/// Converts a graph to node-link JSON, as used by D3 and NetworkX.  The
+/// attributes returned by `node_attrs` are placed in each node object
+/// alongside its `id`, and those returned by `edge_attrs` in each link object
+/// alongside its `source` and `target`.  These fields take precedence over
+/// attributes of the same name.
+pub fn to_json_graph<G>(
+    graph: &G,
+    node_attrs: impl Fn(&G::NodeId, &G::NodeData) -> JsonAttrs,
+    edge_attrs: impl Fn(&G::EdgeId, &G::EdgeData) -> JsonAttrs,
+) -> Value
+where
+    G: Graph + ?Sized,
+{
+    let names = node_names(graph);
+    let nodes: Vec<Value> = graph
+        .node_ids()
+        .map(|nid| {
+            let mut node = node_attrs(&nid, graph.node_data(&nid));
+            node.insert("id".to_string(), json!(names[&nid]));
+            Value::Object(node)
+        })
+        .collect();
+    let links: Vec<Value> = graph
+        .edge_ids()
+        .map(|eid| {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            let mut link = edge_attrs(&eid, graph.edge_data(&eid));
+            link.insert("source".to_string(), json!(names[&from]));
+            link.insert("target".to_string(), json!(names[&into]));
+            Value::Object(link)
+        })
+        .collect();
+    json!({
+        "directed": graph.is_directed(),
+        "multigraph": graph.allows_parallel_edges(),
+        "graph": {},
+        "nodes": nodes,
+        "links": links,
+    })
+}
+
+/// Reads a graph from node-link JSON, as written by [`to_json_graph`],
+/// D3 or NetworkX.  Node IDs may be any JSON values.  The links may be in an
+/// array named `links` or `edges`.  If the `directed` field is present, it
+/// must match the directedness of `H`.
+///
+/// `node_data_fn` converts the fields of each node other than `id` to its
+/// data, and `edge_data_fn` converts the fields of each link other than
+/// `source` and `target` to its data.  Nodes and edges are added in the order
+/// they appear.
+pub fn from_json_graph<H, E>(
+    value: &Value,
+    mut node_data_fn: impl FnMut(&JsonAttrs) -> Result<H::NodeData, E>,
+    mut edge_data_fn: impl FnMut(&JsonAttrs) -> Result<H::EdgeData, E>,
+) -> Result<H, NodeLinkError<E>>
+where
+    H: GraphMut + Default,
+{
+    if let Some(directed) = value.get("directed")
+        && directed.as_bool().ok_or(NodeLinkError::Field("directed"))?
+            != H::Directedness::IS_DIRECTED
+    {
+        return Err(NodeLinkError::Directedness);
+    }
+    let nodes = value
+        .get("nodes")
+        .and_then(Value::as_array)
+        .ok_or(NodeLinkError::Field("nodes"))?;
+    let links = value
+        .get("links")
+        .or_else(|| value.get("edges"))
+        .and_then(Value::as_array)
+        .ok_or(NodeLinkError::Field("links"))?;
+
+    let mut graph = H::default();
+    // JSON values are not hashable, so nodes are looked up by their
+    // serialized IDs.
+    let mut ids: HashMap<String, H::NodeId> = HashMap::new();
+    for node in nodes {
+        let mut attrs = node
+            .as_object()
+            .ok_or(NodeLinkError::Field("nodes"))?
+            .clone();
+        let id = attrs.remove("id").ok_or(NodeLinkError::Field("id"))?;
+        let key = id.to_string();
+        if ids.contains_key(&key) {
+            return Err(NodeLinkError::DuplicateNode(id));
+        }
+        let data = node_data_fn(&attrs).map_err(NodeLinkError::Data)?;
+        ids.insert(key, graph.add_node(data));
+    }
+    for link in links {
+        let mut attrs = link
+            .as_object()
+            .ok_or(NodeLinkError::Field("links"))?
+            .clone();
+        let mut end = |field| {
+            let id = attrs.remove(field).ok_or(NodeLinkError::Field(field))?;
+            ids.get(&id.to_string())
+                .cloned()
+                .ok_or(NodeLinkError::UnknownNode(id))
+        };
+        let from = end("source")?;
+        let into = end("target")?;
+        let data = edge_data_fn(&attrs).map_err(NodeLinkError::Data)?;
+        graph.add_edge(&from, &into, data);
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    fn create_graph() -> BagGraph<&'static str, u32, Directed> {
+        let mut graph = BagGraph::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.add_new_edge(&a, &b, 5);
+        graph
+    }
+
+    fn label(_: &impl Sized, data: &&str) -> JsonAttrs {
+        JsonAttrs::from_iter([("label".to_string(), json!(data))])
+    }
+
+    fn weight(_: &impl Sized, data: &u32) -> JsonAttrs {
+        JsonAttrs::from_iter([("weight".to_string(), json!(data))])
+    }
+
+    #[test]
+    fn test_cytoscape() {
+        let graph = create_graph();
+        let value = graph.to_cytoscape_json(label, weight);
+        let nodes = value["elements"]["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 2);
+        let a = nodes.iter().find(|n| n["data"]["label"] == "a").unwrap();
+        let b = nodes.iter().find(|n| n["data"]["label"] == "b").unwrap();
+        assert_eq!(
+            value["elements"]["edges"],
+            json!([{ "data": {
+                "id": "e0",
+                "source": a["data"]["id"],
+                "target": b["data"]["id"],
+                "weight": 5,
+            }}])
+        );
+    }
+
+    #[test]
+    fn test_node_link_round_trip() {
+        let graph = create_graph();
+        let value = graph.to_json_graph(label, weight);
+        assert_eq!(value["directed"], json!(true));
+        assert_eq!(value["links"][0]["weight"], json!(5));
+
+        let copy: LinkedGraph<String, u64, Directed> = LinkedGraph::from_json_graph(
+            &value,
+            |attrs| {
+                attrs["label"]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or("no label")
+            },
+            |attrs| attrs["weight"].as_u64().ok_or("no weight"),
+        )
+        .unwrap();
+        assert_eq!(copy.num_nodes(), 2);
+        let eid = copy.edge_ids().next().unwrap();
+        let (from, into) = copy.edge_ends(&eid).into_values();
+        assert_eq!(
+            (
+                copy.node_data(&from).as_str(),
+                copy.node_data(&into).as_str()
+            ),
+            ("a", "b")
+        );
+        assert_eq!(*copy.edge_data(&eid), 5);
+    }
+
+    #[test]
+    fn test_node_link_import() {
+        // NetworkX writes integer IDs and may call the links `edges`.
+        let value = json!({
+            "nodes": [{ "id": 1 }, { "id": "1" }, { "id": 2 }],
+            "edges": [{ "source": 1, "target": 2 }, { "source": "1", "target": 1 }],
+        });
+        let unit = |_: &JsonAttrs| Ok::<_, ()>(());
+        let graph: BagGraph<(), (), Undirected> = from_json_graph(&value, unit, unit).unwrap();
+        assert_eq!((graph.num_nodes(), graph.num_edges()), (3, 2));
+
+        let error = from_json_graph::<BagGraph<(), (), Directed>, _>(
+            &json!({ "directed": false, "nodes": [], "links": [] }),
+            unit,
+            unit,
+        );
+        assert_eq!(error.unwrap_err(), NodeLinkError::Directedness);
+        let error = from_json_graph::<BagGraph<(), (), Directed>, _>(
+            &json!({ "nodes": [{ "id": 0 }], "links": [{ "source": 0, "target": 9 }] }),
+            unit,
+            unit,
+        );
+        assert_eq!(error.unwrap_err(), NodeLinkError::UnknownNode(json!(9)));
+        let error = from_json_graph::<BagGraph<(), (), Directed>, _>(
+            &json!({ "nodes": [{ "id": 0 }], "links": [] }),
+            |_| Err("bad"),
+            |_| Ok(()),
+        );
+        assert_eq!(error.unwrap_err().to_string(), "invalid element data: bad");
+    }
+
+    #[test]
+    fn test_sigma() {
+        let graph = create_graph();
+        let value = graph.to_sigma_json(label, |_, _| JsonAttrs::new());
+        assert_eq!(
+            value["options"],
+            json!({ "type": "directed", "multi": true, "allowSelfLoops": true })
+        );
+        let nodes = value["nodes"].as_array().unwrap();
+        let a = nodes
+            .iter()
+            .find(|n| n["attributes"]["label"] == "a")
+            .unwrap();
+        assert_eq!(value["edges"][0]["source"], a["key"]);
+        assert_eq!(value["edges"][0]["attributes"], json!({}));
+    }
+}
@@ -0,0 +1,412 @@
+//! Graphs whose nodes can be looked up by external keys.
+//!
+//! Graphs built from external data, such as rows of a database or objects
+//! identified by UUIDs, usually need to find the node for a key when adding
+//! edges.  [`GraphMutKeyed`] is implemented by graphs which keep that index
+//! themselves, and [`KeyedGraph`] adds it to any graph, so callers do not
+//! need a separate map from keys to node IDs which they keep up to date by
+//! hand.
+//!
+//! A key identifies at most one node, and a node has at most one key.  Nodes
+//! added with [`GraphMut::add_node`] have no key.  Removing a node removes
+//! its key, and compacting the graph updates the index to match.
+
+use std::{fmt::Debug, hash::Hash, mem};
+
+use crate::{
+    hashing::FastHashMap,
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A graph in which nodes can be added with a key, and found by it later.
+pub trait GraphMutKeyed: GraphMut {
+    /// The type of keys.
+    type Key: Eq + Hash + Clone;
+
+    /// Adds a node with the given key and data, returning its ID and `None`.
+    /// If a node with the key already exists, replaces its data instead, and
+    /// returns its ID and the old data.
+    fn add_node_with_id(
+        &mut self,
+        key: Self::Key,
+        data: Self::NodeData,
+    ) -> (Self::NodeId, Option<Self::NodeData>);
+
+    /// Returns the node with the given key, if there is one.
+    fn node_by_key(&self, key: &Self::Key) -> Option<Self::NodeId>;
+
+    /// Returns the key of a node, or `None` if it was added without one.
+    fn key_of(&self, id: &Self::NodeId) -> Option<&Self::Key>;
+
+    /// Returns the node with the given key, adding it with data computed by
+    /// `data_fn` if there is none.
+    fn get_or_add_node_with_id(
+        &mut self,
+        key: Self::Key,
+        data_fn: impl FnOnce() -> Self::NodeData,
+    ) -> Self::NodeId {
+        match self.node_by_key(&key) {
+            Some(nid) => nid,
+            None => self.add_node_with_id(key, data_fn()).0,
+        }
+    }
+
+    /// Removes the node with the given key, returning its data, or `None` if
+    /// there is no such node.
+    fn remove_node_by_key(&mut self, key: &Self::Key) -> Option<Self::NodeData> {
+        let nid = self.node_by_key(key)?;
+        Some(self.remove_node(&nid))
+    }
+}
+
+/// A wrapper around a graph which indexes nodes by keys of type `K`.  See
+/// the [module documentation](self).
+pub struct KeyedGraph<G, K>
+where
+    G: Graph,
+{
+    inner: G,
+    nodes: FastHashMap<K, G::NodeId>,
+    keys: FastHashMap<G::NodeId, K>,
+}
+
+impl<G, K> KeyedGraph<G, K>
+where
+    G: Graph,
+    K: Eq + Hash + Clone,
+{
+    /// Wraps an existing graph.  Its nodes have no keys.
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            nodes: FastHashMap::default(),
+            keys: FastHashMap::default(),
+        }
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding the keys.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Returns the keys and the nodes they identify, in an unspecified order.
+    pub fn keys(&self) -> impl Iterator<Item = (&K, &G::NodeId)> {
+        self.nodes.iter()
+    }
+}
+
+impl<G, K> Default for KeyedGraph<G, K>
+where
+    G: Graph + Default,
+    K: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new(G::default())
+    }
+}
+
+impl<G, K> Clone for KeyedGraph<G, K>
+where
+    G: Graph + Clone,
+    K: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            nodes: self.nodes.clone(),
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+impl<G, K> Debug for KeyedGraph<G, K>
+where
+    G: Graph + Debug,
+    K: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedGraph")
+            .field("inner", &self.inner)
+            .field("keys", &self.keys)
+            .finish()
+    }
+}
+
+impl<G, K> Graph for KeyedGraph<G, K>
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from_into(from, into)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.inner.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.inner.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.inner.num_edges_from(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G, K> GraphMut for KeyedGraph<G, K>
+where
+    G: GraphMut,
+    K: Eq + Hash + Clone,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        self.inner.node_data_mut(id)
+    }
+
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        self.inner.edge_data_mut(id)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.nodes.clear();
+        self.keys.clear();
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        self.inner.add_node(data)
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        if let Some(key) = self.keys.remove(id) {
+            self.nodes.remove(&key);
+        }
+        self.inner.remove_node(id)
+    }
+
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        self.inner.add_edge(from, into, data)
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        self.inner.remove_edge(id)
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        let mut node_map = FastHashMap::default();
+        self.inner.compact(
+            Some(&mut |old: G::NodeId, new: G::NodeId| {
+                node_map.insert(old, new);
+            }),
+            edge_map_collector,
+        );
+        for nid in self.nodes.values_mut() {
+            if let Some(new) = node_map.get(nid) {
+                *nid = new.clone();
+            }
+        }
+        self.keys = self
+            .nodes
+            .iter()
+            .map(|(key, nid)| (nid.clone(), key.clone()))
+            .collect();
+        if let Some(collector) = node_map_collector {
+            for (old, new) in node_map {
+                collector.insert(old, new);
+            }
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+        self.nodes.shrink_to_fit();
+        self.keys.shrink_to_fit();
+    }
+}
+
+impl<G, K> GraphMutKeyed for KeyedGraph<G, K>
+where
+    G: GraphMut,
+    K: Eq + Hash + Clone,
+{
+    type Key = K;
+
+    fn add_node_with_id(
+        &mut self,
+        key: Self::Key,
+        data: Self::NodeData,
+    ) -> (Self::NodeId, Option<Self::NodeData>) {
+        if let Some(nid) = self.nodes.get(&key) {
+            let old = mem::replace(self.inner.node_data_mut(nid), data);
+            return (nid.clone(), Some(old));
+        }
+        let nid = self.inner.add_node(data);
+        self.nodes.insert(key.clone(), nid.clone());
+        self.keys.insert(nid.clone(), key);
+        (nid, None)
+    }
+
+    fn node_by_key(&self, key: &Self::Key) -> Option<Self::NodeId> {
+        self.nodes.get(key).cloned()
+    }
+
+    fn key_of(&self, id: &Self::NodeId) -> Option<&Self::Key> {
+        self.keys.get(id)
+    }
+}
+
+impl<G, K> StableNodeIds for KeyedGraph<G, K> where G: StableNodeIds {}
+
+impl<G, K> StableEdgeIds for KeyedGraph<G, K> where G: StableEdgeIds {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_add_and_find_by_key() {
+        let mut graph = KeyedGraph::<LinkedGraph<&str, (), Directed>, u64>::default();
+        let (a, replaced) = graph.add_node_with_id(17, "a");
+        assert_eq!(replaced, None);
+        let b = graph.get_or_add_node_with_id(42, || "b");
+        assert_eq!(graph.get_or_add_node_with_id(42, || unreachable!()), b);
+        let c = graph.add_node("c");
+        graph.add_new_edge(&a, &b, ());
+
+        assert_eq!(graph.node_by_key(&17), Some(a.clone()));
+        assert_eq!(graph.key_of(&b), Some(&42));
+        assert_eq!(graph.key_of(&c), None);
+        assert_eq!(graph.node_by_key(&99), None);
+
+        let (a2, replaced) = graph.add_node_with_id(17, "A");
+        assert_eq!(a2, a);
+        assert_eq!(replaced, Some("a"));
+        assert_eq!(*graph.node_data(&a), "A");
+        assert_eq!(graph.num_nodes(), 3);
+
+        assert_eq!(graph.remove_node_by_key(&42), Some("b"));
+        assert_eq!(graph.remove_node_by_key(&42), None);
+        assert_eq!(graph.num_edges(), 0);
+        graph.remove_node(&a);
+        assert_eq!(graph.node_by_key(&17), None);
+        assert_eq!(graph.keys().count(), 0);
+    }
+
+    #[test]
+    fn test_compact_updates_keys() {
+        let mut graph = KeyedGraph::<BagGraph<&str, (), Undirected>, &str>::default();
+        for name in ["a", "b", "c", "d"] {
+            graph.add_node_with_id(name, name);
+        }
+        graph.remove_node_by_key(&"a");
+        graph.remove_node_by_key(&"c");
+
+        let mut node_map = HashMap::new();
+        graph.compact(Some(&mut node_map), None);
+        assert!(
+            node_map
+                .values()
+                .all(|nid| graph.check_node_id(nid).is_ok())
+        );
+        for name in ["b", "d"] {
+            let nid = graph.node_by_key(&name).unwrap();
+            assert_eq!(*graph.node_data(&nid), name);
+            assert_eq!(graph.key_of(&nid), Some(&name));
+        }
+    }
+}
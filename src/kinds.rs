@@ -0,0 +1,192 @@
+//! Graphs whose nodes are of several kinds.
+//!
+//! Heterogeneous graphs, such as knowledge graphs, often use an enum as
+//! their node data, with a variant for each kind of node.  Implementing
+//! [`Kinded`] for each kind lets algorithms select the nodes of one kind,
+//! or the edges between two kinds, without matching on the enum.  The
+//! [`impl_kinded!`](crate::impl_kinded) macro implements it for enums whose
+//! variants each wrap a distinct type:
+//!
+//! ```
+//! use graphitude::{impl_kinded, prelude::*, LinkedGraph};
+//!
+//! struct Person(&'static str);
+//! struct City(&'static str);
+//!
+//! enum Entity {
+//!     Person(Person),
+//!     City(City),
+//! }
+//!
+//! impl_kinded!(Entity { Person(Person), City(City) });
+//!
+//! let mut graph = LinkedGraph::<Entity, (), Directed>::default();
+//! let alice = graph.add_node(Entity::Person(Person("Alice")));
+//! let paris = graph.add_node(Entity::City(City("Paris")));
+//! graph.add_new_edge(&alice, &paris, ());
+//!
+//! let people: Vec<_> = graph.nodes_of::<Person>().map(|(_, p)| p.0).collect();
+//! assert_eq!(people, vec!["Alice"]);
+//! let lives_in: Vec<_> = graph
+//!     .edges_between_kinds::<Person, City>()
+//!     .map(|(_, person, city)| (person.0, city.0))
+//!     .collect();
+//! assert_eq!(lives_in, vec![("Alice", "Paris")]);
+//! ```
+
+use crate::{end_pair::EndPair, prelude::*};
+
+/// Data which may be of kind `K`.
+pub trait Kinded<K> {
+    /// Returns the data as a `K`, or `None` if it is of another kind.
+    fn as_kind(&self) -> Option<&K>;
+
+    /// Returns true if the data is of kind `K`.
+    fn is_kind(&self) -> bool {
+        self.as_kind().is_some()
+    }
+}
+
+/// Implements [`Kinded`] for each variant of an enum, where each variant has
+/// a single field of a type which no other variant uses.
+#[macro_export]
+macro_rules! impl_kinded {
+    ($enum:ty { $($variant:ident($kind:ty)),+ $(,)? }) => {
+        $(
+            impl $crate::kinds::Kinded<$kind> for $enum {
+                fn as_kind(&self) -> Option<&$kind> {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        Self::$variant(data) => Some(data),
+                        _ => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+/// Returns the nodes of kind `K`, with their data as a `K`.
+pub fn nodes_of<'g, G, K>(graph: &'g G) -> impl Iterator<Item = (G::NodeId, &'g K)>
+where
+    K: 'g,
+    G: Graph + ?Sized,
+    G::NodeData: Kinded<K>,
+{
+    graph
+        .node_ids()
+        .filter_map(|nid| graph.node_data(&nid).as_kind().map(|data| (nid, data)))
+}
+
+/// Calls `f` with each node of kind `K` and its data as a `K`.
+pub fn visit_nodes_of<G, K>(graph: &G, mut f: impl FnMut(G::NodeId, &K))
+where
+    G: Graph + ?Sized,
+    G::NodeData: Kinded<K>,
+{
+    for (nid, data) in nodes_of(graph) {
+        f(nid, data);
+    }
+}
+
+/// Returns the edges from a node of kind `A` to a node of kind `B`, with the
+/// data of their ends.  In an undirected graph, an edge may be traversed in
+/// either direction, so an edge between two nodes of both kinds is returned
+/// once with the data of the source end as the `A`.
+pub fn edges_between_kinds<'g, G, A, B>(
+    graph: &'g G,
+) -> impl Iterator<Item = (G::EdgeId, &'g A, &'g B)>
+where
+    A: 'g,
+    B: 'g,
+    G: Graph + ?Sized,
+    G::NodeData: Kinded<A> + Kinded<B>,
+{
+    graph.edge_ids().filter_map(|eid| {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        let (from, into) = (graph.node_data(&from), graph.node_data(&into));
+        let forward = || Some((Kinded::<A>::as_kind(from)?, Kinded::<B>::as_kind(into)?));
+        let backward = || Some((Kinded::<A>::as_kind(into)?, Kinded::<B>::as_kind(from)?));
+        let (a, b) = if graph.is_directed() {
+            forward()?
+        } else {
+            forward().or_else(backward)?
+        };
+        Some((eid, a, b))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Person(&'static str);
+    #[derive(Debug, PartialEq)]
+    struct City(&'static str);
+
+    enum Entity {
+        Person(Person),
+        City(City),
+        Unknown,
+    }
+
+    impl_kinded!(Entity { Person(Person), City(City) });
+
+    #[test]
+    fn test_nodes_of() {
+        let mut graph = BagGraph::<Entity, (), Directed>::default();
+        let alice = graph.add_node(Entity::Person(Person("Alice")));
+        graph.add_node(Entity::City(City("Paris")));
+        graph.add_node(Entity::Unknown);
+        let bob = graph.add_node(Entity::Person(Person("Bob")));
+
+        let mut people: Vec<_> = nodes_of::<_, Person>(&graph).collect();
+        people.sort_by_key(|(_, p)| p.0);
+        assert_eq!(
+            people,
+            vec![(alice, &Person("Alice")), (bob, &Person("Bob"))]
+        );
+
+        let mut cities = Vec::new();
+        visit_nodes_of(&graph, |_, city: &City| cities.push(city.0));
+        assert_eq!(cities, vec!["Paris"]);
+        assert!(Kinded::<City>::is_kind(&Entity::City(City("Rome"))));
+        assert!(!Kinded::<City>::is_kind(&Entity::Unknown));
+    }
+
+    #[test]
+    fn test_edges_between_kinds_directed() {
+        let mut graph = LinkedGraph::<Entity, (), Directed>::default();
+        let alice = graph.add_node(Entity::Person(Person("Alice")));
+        let bob = graph.add_node(Entity::Person(Person("Bob")));
+        let paris = graph.add_node(Entity::City(City("Paris")));
+        let lives_in = graph.add_new_edge(&alice, &paris, ());
+        graph.add_new_edge(&paris, &bob, ());
+        graph.add_new_edge(&alice, &bob, ());
+
+        let edges: Vec<_> = edges_between_kinds::<_, Person, City>(&graph).collect();
+        assert_eq!(edges, vec![(lives_in, &Person("Alice"), &City("Paris"))]);
+        assert_eq!(edges_between_kinds::<_, Person, Person>(&graph).count(), 1);
+    }
+
+    #[test]
+    fn test_edges_between_kinds_undirected() {
+        let mut graph = LinkedGraph::<Entity, (), Undirected>::default();
+        let alice = graph.add_node(Entity::Person(Person("Alice")));
+        let paris = graph.add_node(Entity::City(City("Paris")));
+        let unknown = graph.add_node(Entity::Unknown);
+        let lives_in = graph.add_new_edge(&paris, &alice, ());
+        graph.add_new_edge(&alice, &unknown, ());
+
+        let edges: Vec<_> = edges_between_kinds::<_, Person, City>(&graph).collect();
+        assert_eq!(
+            edges,
+            vec![(lives_in.clone(), &Person("Alice"), &City("Paris"))]
+        );
+        let edges: Vec<_> = edges_between_kinds::<_, City, Person>(&graph).collect();
+        assert_eq!(edges, vec![(lives_in, &City("Paris"), &Person("Alice"))]);
+    }
+}
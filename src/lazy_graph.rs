@@ -0,0 +1,460 @@
+//! Graphs whose edge data is loaded on first access.
+//!
+//! Deserializing every edge's data along with the structure of a large
+//! graph can take much longer than loading the structure alone, and is
+//! wasted on edges whose data is never looked at.  A [`LazyGraph`] wraps a
+//! graph whose edge data is a [`LazyEdge`], which holds a key, such as a
+//! file offset or a database row ID, and a cache for the data.  The first
+//! time the data of an edge is accessed through the wrapper, a loader
+//! function is called with the edge's ID and key, and its result is cached
+//! in the edge, so later accesses are as fast as with any other graph.
+//!
+//! The loader cannot fail; if loading can, use a `Result` as the edge data.
+
+use std::{fmt::Debug, marker::PhantomData, sync::OnceLock};
+
+use crate::{
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// The data of an edge in a graph wrapped by [`LazyGraph`]: a key from
+/// which the data can be loaded, and the data itself once it has been.
+pub struct LazyEdge<K, E> {
+    key: Option<K>,
+    value: OnceLock<E>,
+}
+
+impl<K, E> LazyEdge<K, E> {
+    /// Creates an edge whose data will be loaded from `key`.
+    pub fn new(key: K) -> Self {
+        Self {
+            key: Some(key),
+            value: OnceLock::new(),
+        }
+    }
+
+    /// Creates an edge whose data is already loaded, and has no key.
+    pub fn loaded(value: E) -> Self {
+        Self {
+            key: None,
+            value: OnceLock::from(value),
+        }
+    }
+
+    /// Returns the key the data is loaded from, if any.
+    pub fn key(&self) -> Option<&K> {
+        self.key.as_ref()
+    }
+
+    /// Returns the data if it has been loaded.
+    pub fn get(&self) -> Option<&E> {
+        self.value.get()
+    }
+
+    /// Returns true if the data has been loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Returns the data, calling `load` with the key to load it if needed.
+    fn get_or_load(&self, load: impl FnOnce(&K) -> E) -> &E {
+        self.value
+            .get_or_init(|| load(self.key.as_ref().expect("edge has neither data nor key")))
+    }
+
+    /// Returns the data, calling `load` with the key to load it if needed.
+    fn into_loaded(self, load: impl FnOnce(&K) -> E) -> E {
+        let Self { key, value } = self;
+        value
+            .into_inner()
+            .unwrap_or_else(|| load(key.as_ref().expect("edge has neither data nor key")))
+    }
+}
+
+impl<K, E> Clone for LazyEdge<K, E>
+where
+    K: Clone,
+    E: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<K, E> Debug for LazyEdge<K, E>
+where
+    K: Debug,
+    E: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyEdge")
+            .field("key", &self.key)
+            .field("value", &self.value.get())
+            .finish()
+    }
+}
+
+/// A wrapper around a graph with [`LazyEdge`] data which loads the data of
+/// each edge the first time it is accessed.  See the [module
+/// documentation](self).
+///
+/// The wrapper implements [`Graph`] and [`GraphMut`] with the loaded data
+/// as the edge data, and uses the inner graph's node data and IDs.  Edges
+/// added through the wrapper are stored with their data already loaded.
+/// Removing an edge, or replacing it with [`GraphMut::add_edge`], loads its
+/// data in order to return it.
+pub struct LazyGraph<G, K, E, F>
+where
+    G: Graph,
+{
+    inner: G,
+    loader: F,
+    phantom: PhantomData<fn(&K) -> E>,
+}
+
+impl<G, K, E, F> LazyGraph<G, K, E, F>
+where
+    G: Graph<EdgeData = LazyEdge<K, E>>,
+    F: Fn(&G::EdgeId, &K) -> E,
+{
+    /// Wraps an existing graph, using `loader` to load the data of each edge
+    /// from its ID and key.
+    pub fn new(inner: G, loader: F) -> Self {
+        Self {
+            inner,
+            loader,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding the loader.  The data loaded so far
+    /// stays cached in the edges.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Returns true if the data of edge `id` has been loaded.
+    pub fn is_loaded(&self, id: &G::EdgeId) -> bool {
+        self.inner.edge_data(id).is_loaded()
+    }
+
+    /// Returns the number of edges whose data has been loaded.
+    pub fn num_loaded(&self) -> usize {
+        self.inner
+            .edge_ids()
+            .filter(|eid| self.inner.edge_data(eid).is_loaded())
+            .count()
+    }
+
+    /// Loads the data of every edge which has not been loaded yet, e.g. to
+    /// do the work before the graph is shared with readers.
+    pub fn load_all(&self) {
+        for eid in self.inner.edge_ids() {
+            self.load(&eid, self.inner.edge_data(&eid));
+        }
+    }
+
+    /// Discards the loaded data of edge `id`, so it is loaded again the next
+    /// time it is accessed, and returns the data.  Returns `None` if the data
+    /// was not loaded, or if the edge has no key to load it from again.
+    pub fn unload(&mut self, id: &G::EdgeId) -> Option<E>
+    where
+        G: GraphMut,
+    {
+        let edge = self.inner.edge_data_mut(id);
+        edge.key.as_ref()?;
+        edge.value.take()
+    }
+
+    /// Returns the data of `edge`, loading it if needed.
+    fn load<'a>(&self, id: &G::EdgeId, edge: &'a LazyEdge<K, E>) -> &'a E {
+        edge.get_or_load(|key| (self.loader)(id, key))
+    }
+}
+
+impl<G, K, E, F> Clone for LazyGraph<G, K, E, F>
+where
+    G: Graph + Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            loader: self.loader.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<G, K, E, F> Debug for LazyGraph<G, K, E, F>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyGraph")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<G, K, E, F> Graph for LazyGraph<G, K, E, F>
+where
+    G: Graph<EdgeData = LazyEdge<K, E>>,
+    F: Fn(&G::EdgeId, &K) -> E,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = E;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    /// Gets the data of an edge, loading it first if needed.
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.load(id, self.inner.edge_data(id))
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from_into(from, into)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.inner.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.inner.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.inner.num_edges_from(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G, K, E, F> GraphMut for LazyGraph<G, K, E, F>
+where
+    G: GraphMut<EdgeData = LazyEdge<K, E>>,
+    F: Fn(&G::EdgeId, &K) -> E,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        self.inner.node_data_mut(id)
+    }
+
+    /// Gets the data of an edge mutably, loading it first if needed.
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        let edge = self.inner.edge_data_mut(id);
+        edge.get_or_load(|key| (self.loader)(id, key));
+        edge.value.get_mut().unwrap()
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        self.inner.add_node(data)
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        self.inner.remove_node(id)
+    }
+
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        match self.inner.add_edge(from, into, LazyEdge::loaded(data)) {
+            AddEdgeResult::Added(eid) => AddEdgeResult::Added(eid),
+            AddEdgeResult::Updated {
+                id,
+                old_id,
+                old_data,
+            } => {
+                let old_data = old_data.into_loaded(|key| (self.loader)(&old_id, key));
+                AddEdgeResult::Updated {
+                    id,
+                    old_id,
+                    old_data,
+                }
+            }
+        }
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        self.inner
+            .remove_edge(id)
+            .into_loaded(|key| (self.loader)(id, key))
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        self.inner.compact(node_map_collector, edge_map_collector);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+}
+
+impl<G, K, E, F> StableNodeIds for LazyGraph<G, K, E, F>
+where
+    G: Graph<EdgeData = LazyEdge<K, E>> + StableNodeIds,
+    F: Fn(&G::EdgeId, &K) -> E,
+{
+}
+
+impl<G, K, E, F> StableEdgeIds for LazyGraph<G, K, E, F>
+where
+    G: Graph<EdgeData = LazyEdge<K, E>> + StableEdgeIds,
+    F: Fn(&G::EdgeId, &K) -> E,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::LinkedGraph;
+
+    use super::*;
+
+    type Inner = LinkedGraph<&'static str, LazyEdge<usize, String>, Directed>;
+
+    #[test]
+    fn test_loads_on_first_access() {
+        let payloads = ["first", "second", "third"];
+        let loads = Cell::new(0);
+        let mut inner = Inner::default();
+        let a = inner.add_node("a");
+        let b = inner.add_node("b");
+        let e0 = inner.add_new_edge(&a, &b, LazyEdge::new(0));
+        let e1 = inner.add_new_edge(&b, &a, LazyEdge::new(1));
+        let graph = LazyGraph::new(inner, |_, &key: &usize| {
+            loads.set(loads.get() + 1);
+            payloads[key].to_uppercase()
+        });
+
+        assert_eq!(graph.num_edges(), 2);
+        assert_eq!(graph.successors(&a).collect::<Vec<_>>(), vec![b.clone()]);
+        assert_eq!(loads.get(), 0);
+
+        assert_eq!(graph.edge_data(&e1), "SECOND");
+        assert_eq!(graph.edge_data(&e1), "SECOND");
+        assert_eq!(loads.get(), 1);
+        assert!(graph.is_loaded(&e1));
+        assert!(!graph.is_loaded(&e0));
+        assert_eq!(graph.num_loaded(), 1);
+
+        graph.load_all();
+        assert_eq!(loads.get(), 2);
+        assert_eq!(graph.inner().edge_data(&e0).get().unwrap(), "FIRST");
+    }
+
+    #[test]
+    fn test_mutation() {
+        let loads = Cell::new(0);
+        let mut inner = Inner::default();
+        let a = inner.add_node("a");
+        let b = inner.add_node("b");
+        let e0 = inner.add_new_edge(&a, &b, LazyEdge::new(7));
+        let mut graph = LazyGraph::new(inner, |_, key: &usize| {
+            loads.set(loads.get() + 1);
+            key.to_string()
+        });
+
+        graph.edge_data_mut(&e0).push('!');
+        assert_eq!(graph.edge_data(&e0), "7!");
+        assert_eq!(graph.unload(&e0), Some("7!".to_string()));
+        assert_eq!(graph.edge_data(&e0), "7");
+        assert_eq!(loads.get(), 2);
+
+        // Added edges have no key, so their data cannot be unloaded.
+        let e1 = graph.add_new_edge(&b, &a, "new".to_string());
+        assert_eq!(graph.unload(&e1), None);
+        assert_eq!(graph.edge_data(&e1), "new");
+
+        assert_eq!(graph.remove_edge(&e0), "7");
+        assert_eq!(loads.get(), 2);
+        assert_eq!(graph.num_edges(), 1);
+    }
+}
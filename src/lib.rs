@@ -5,7 +5,8 @@
 //! Mutating the data stored in nodes and edges is not provided directly
 //! through these traits.  If you need to mutate the data, use interior
 //! mutability (e.g., `RefCell`, `Cell`, `Mutex`, etc.) in your node and edge
-//! data types.
+//! data types.  For data stored in a `RefCell`, see [`cell_data`] for
+//! convenience accessors.
 //!
 //! This module provides:
 //!
@@ -18,30 +19,180 @@
 //!
 //! - Flexible node and edge data storage through associated types
 //! - Support for both directed and undirected graphs
-//! - Graph traversal algorithms: DFS, BFS
+//! - An object-safe facade, so graphs can be used as trait objects
+//! - Graph traversal algorithms: DFS, BFS, DFS post-order, and DFS with a
+//!   visitor for edge classification
+//! - BFS which expands each level in parallel (requires `rayon` feature)
+//! - Cooperative traversal streams for async executors (requires `async`
+//!   feature)
 //! - Path finding utilities with Dijkstra's algorithm (requires `pathfinding`
 //!   feature)
+//! - Shortest path trees, usable as graphs, with DOT rendering of the tree
+//!   over the graph (requires `pathfinding` feature)
+//! - Reusable scratch space for repeated shortest path searches (requires
+//!   `pathfinding` feature)
+//! - Totally ordered floating point costs, for algorithms which need `Ord`
+//!   costs
+//! - Multi-objective (Pareto) shortest paths, optionally under resource
+//!   constraints (requires `pathfinding` feature)
+//! - Strongly connected components, with the edges within each component
+//! - 2-SAT solving with implication graphs
+//! - Reachability-preserving edge sparsification for visualization
+//! - Spanning forests, and minimum spanning forests, optionally computed in
+//!   parallel (requires `rayon` feature)
 //! - Queries for nodes, edges, predecessors, and successors
+//! - Validation of a graph's internal consistency, reporting every violation
+//! - Finding a cycle, or enumerating all simple cycles with Johnson's
+//!   algorithm
+//! - Longest-path layering of DAGs, for scheduling and layered layouts
+//! - Running a task per node of a DAG in dependency order, in parallel
+//! - Witness types for algorithm preconditions, such as acyclic graphs and
+//!   non-negative weights
+//! - Side tables keyed by node or edge IDs which follow compaction
+//! - Links between nodes of different graphs, checked against each graph
+//! - Marker traits for graphs whose IDs are never invalidated by compaction
+//! - Translation of IDs held outside a graph across its last few compactions
+//! - Named ports on nodes for edges to attach to, with DOT port syntax
+//! - DOT node names which stay the same between versions of a graph, for
+//!   visual diffs (requires `dot` feature)
+//! - DOT edge weights and pen widths computed from numeric edge data
+//!   (requires `dot` feature)
+//! - Provenance of nodes and edges produced by copies and transformations
+//! - Biconnected components, articulation points, bridges, and block-cut
+//!   trees
+//! - Eulerian paths and circuits
+//! - Simulation preorders and bisimilarity of labeled transition systems
+//! - Negative cycles (Bellman-Ford), minimum mean cycles (Karp's algorithm)
+//!   and minimum cost-to-time ratio cycles
+//! - Minimum vertex covers and maximum independent sets
+//! - Approximate maximum common edge subgraphs within a time budget
+//! - Greedy Welsh–Powell coloring, and conflict graphs built from overlapping
+//!   intervals
+//! - k-core decomposition and core numbers
+//! - Community detection by label propagation and Louvain modularity
+//!   optimization, with optional edge weights
+//! - Normalization of edge weights, in place or into a map
+//! - Structural signatures of node neighborhoods for finding near-duplicates
+//! - Canonical hashes of whole graphs, stable across runs, for caching
+//!   results by graph shape
+//! - Grouping nodes by key, with edge counts within and between groups
+//! - Selecting nodes and edges by kind in graphs with enum node data
+//! - Undo and redo of graph mutations, grouped into actions, with savepoints
+//!   to roll back to
+//! - Edges from each node kept sorted by a comparator on their data
+//! - Views of graphs with node and edge data projected to other types, and
+//!   conversion of the data of owned graphs
+//! - Views of directed graphs with every edge reversed
+//! - Edge data loaded on first access, for graphs whose structure is much
+//!   cheaper to load than their edge data
+//! - Looking up nodes by external keys such as UUIDs or database keys
+//! - Node and edge counts, mutation counters, and compaction events reported
+//!   through the `metrics` façade (requires `metrics` feature)
+//! - Persistent graphs with cheap copy-on-write snapshots which keep the
+//!   graph's IDs, for analyzing a changing graph concurrently
+//! - Scheduled snapshots serialized on a background thread
+//! - A compact, versioned binary format for quickly saving and loading very
+//!   large graphs
+//! - Queued edits applied in rate-limited batches, with backpressure
+//! - Export to Cytoscape.js and Sigma.js JSON, and node-link JSON import and
+//!   export for D3 and NetworkX with `to_json_graph` and `from_json_graph`
+//!   (requires `json` feature)
+//! - Generators for complete, path, cycle, and grid graphs, and random
+//!   Erdős–Rényi and Barabási–Albert graphs (requires `rand` feature)
+//! - Construction from adjacency matrices, or from `ndarray` arrays (requires
+//!   `ndarray` feature)
+//! - Conversion to and from `petgraph` graphs (requires `petgraph-interop`
+//!   feature)
 
 // Modules for using existing graph implementations and utilities.
+pub mod add_edge_result;
 pub mod adjacency_graph;
 pub mod adjacency_matrix;
+#[cfg(feature = "pathfinding")]
+pub mod anytime;
+pub mod apply_queue;
+#[cfg(feature = "pathfinding")]
+pub mod arena;
 pub mod bag_graph;
+pub mod biconnected;
+pub mod binary_snapshot;
+pub mod cell_data;
+pub mod coloring;
+pub mod common_subgraph;
+pub mod community;
 pub mod copier;
+pub mod cores;
+pub mod cycles;
 pub mod debug_graph_view;
+pub mod degree_bounded;
 pub mod directedness;
 #[cfg(feature = "dot")]
 pub mod dot;
+pub mod dyn_graph;
 pub mod edge_multiplicity;
 pub mod end_pair;
+pub mod error;
+pub mod eulerian;
+pub mod executor;
 pub mod generate_large_graph;
+pub mod generators;
+#[cfg(feature = "metrics")]
+pub mod graph_metrics;
 pub mod graph_traits;
+pub mod grouping;
+pub mod hash_consed_dag;
+pub mod hashing;
+pub mod inter_graph;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod keyed;
+pub mod kinds;
+pub mod lazy_graph;
+pub mod line_graph;
 pub mod linked_graph;
 pub mod map_collector;
+pub mod mapped_graph;
+pub mod matrix_import;
+pub mod mean_cycle;
+pub mod memoize;
+pub mod normalize;
+pub mod notifying_graph;
 pub mod object_graph;
+pub mod ordered_cost;
+#[cfg(feature = "pathfinding")]
+pub mod pareto;
 pub mod path;
+pub mod persistent_graph;
+#[cfg(feature = "petgraph-interop")]
+pub mod petgraph_interop;
+pub mod ports;
+pub mod preconditions;
 pub mod prelude;
+pub mod property_map;
+pub mod provenance;
+pub mod rebasing;
+pub mod reversed_graph;
+pub mod scc;
 pub mod search;
+#[cfg(feature = "pathfinding")]
+pub mod shortest_path_tree;
+pub mod signature;
+pub mod simulation;
+pub mod slab_graph;
+pub mod snapshot_scheduler;
+pub mod soft_delete;
+pub mod sorted_edges;
+pub mod spanning_tree;
+pub mod sparsify;
+pub mod stable_ids;
+pub mod stats;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod structural_hash;
+pub mod two_sat;
+pub mod undo;
+pub mod validate;
+pub mod vertex_cover;
 
 // Modules for creating new graph implementations.
 pub mod format_debug;
@@ -56,13 +207,29 @@ mod test_util;
 mod triangular;
 mod util;
 
+pub use add_edge_result::AddEdgeResult;
 pub use adjacency_graph::AdjacencyGraph;
 #[cfg(feature = "bitvec")]
 pub use adjacency_matrix::BitvecStorage;
-pub use adjacency_matrix::{HashStorage, Storage};
+pub use adjacency_matrix::{CsrStorage, HashStorage, Storage};
 pub use bag_graph::BagGraph;
+pub use cell_data::CellData;
 pub use copier::GraphCopier;
-pub use directedness::{Directed, Directedness, Undirected};
+pub use degree_bounded::DegreeBoundedGraph;
+pub use directedness::{Directed, Directedness, Direction, Undirected};
+pub use dyn_graph::DynGraph;
 pub use edge_multiplicity::{EdgeMultiplicity, MultipleEdges, SingleEdge};
+pub use error::GraphError;
 pub use graph_traits::{Graph, GraphDirected, GraphElementId, GraphMut, GraphUndirected};
+pub use hash_consed_dag::HashConsedDag;
+pub use keyed::{GraphMutKeyed, KeyedGraph};
+pub use lazy_graph::LazyGraph;
 pub use linked_graph::LinkedGraph;
+pub use notifying_graph::{CompactOn, NotifyingGraph};
+pub use persistent_graph::PersistentGraph;
+pub use rebasing::RebasingGraph;
+pub use slab_graph::SlabGraph;
+pub use soft_delete::SoftDeleteGraph;
+pub use sorted_edges::SortedEdgesGraph;
+pub use stable_ids::{StableEdgeIds, StableNodeIds};
+pub use undo::UndoableGraph;
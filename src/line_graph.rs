@@ -0,0 +1,110 @@
+use crate::{hashing::FastHashMap, prelude::*};
+
+/// Constructs the line graph of `graph`.  See [`Graph::line_graph`].
+pub fn line_graph<G, H>(
+    graph: &G,
+    node_data_fn: impl Fn(&G::EdgeId) -> H::NodeData,
+    edge_data_fn: impl Fn(&G::NodeId) -> H::EdgeData,
+) -> H
+where
+    G: Graph + ?Sized,
+    H: GraphMut<Directedness = G::Directedness> + Default,
+{
+    let mut result = H::default();
+    result.reserve(graph.num_edges(), 0);
+    let node_map: FastHashMap<G::EdgeId, H::NodeId> = graph
+        .edge_ids()
+        .map(|eid| {
+            let nid = result.add_node(node_data_fn(&eid));
+            (eid, nid)
+        })
+        .collect();
+
+    for nid in graph.node_ids() {
+        if graph.is_directed() {
+            // Each edge into the node leads to each edge out of it.  A
+            // self-loop leads to itself.
+            for into_edge in graph.edges_into(&nid) {
+                for from_edge in graph.edges_from(&nid) {
+                    result.add_edge(
+                        &node_map[&into_edge],
+                        &node_map[&from_edge],
+                        edge_data_fn(&nid),
+                    );
+                }
+            }
+        } else {
+            // Each pair of distinct edges sharing the node is connected.  A
+            // self-loop may be reported twice by `edges_from`.
+            let mut incident: Vec<_> = graph.edges_from(&nid).collect();
+            incident.sort();
+            incident.dedup();
+            for (i, a) in incident.iter().enumerate() {
+                for b in incident[i + 1..].iter() {
+                    result.add_edge(&node_map[a], &node_map[b], edge_data_fn(&nid));
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_directed_line_graph() {
+        let mut graph = BagGraph::<(), &str, Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, "ab");
+        graph.add_new_edge(&b, &c, "bc");
+        graph.add_new_edge(&c, &c, "cc");
+
+        let lg: BagGraph<&str, (), Directed> =
+            graph.line_graph(|eid| *graph.edge_data(eid), |_| ());
+        assert_eq!(lg.num_nodes(), 3);
+        let mut pairs: Vec<_> = lg
+            .edge_ids()
+            .map(|eid| {
+                let (from, into) = lg.edge_ends(&eid);
+                (*lg.node_data(&from), *lg.node_data(&into))
+            })
+            .collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![("ab", "bc"), ("bc", "cc"), ("cc", "cc")]);
+    }
+
+    #[test]
+    fn test_undirected_star() {
+        let mut graph = LinkedGraph::<(), (), Undirected>::default();
+        let center = graph.add_node(());
+        for _ in 0..3 {
+            let leaf = graph.add_node(());
+            graph.add_new_edge(&center, &leaf, ());
+        }
+        let lg: BagGraph<(), (), Undirected> = graph.line_graph(|_| (), |_| ());
+        assert_eq!(lg.num_nodes(), 3);
+        assert_eq!(lg.num_edges(), 3);
+    }
+
+    #[test]
+    fn test_parallel_edges_share_both_ends() {
+        let mut graph = BagGraph::<(), (), Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&a, &b, ());
+        graph.add_new_edge(&b, &b, ());
+
+        let lg: BagGraph<(), (), Undirected> = graph.line_graph(|_| (), |_| ());
+        assert_eq!(lg.num_nodes(), 3);
+        // The parallel edges meet at both `a` and `b`; the loop meets each of
+        // them once at `b`.
+        assert_eq!(lg.num_edges(), 4);
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 
 use derivative::Derivative;
 
-use crate::{Graph, GraphElementId, linked_graph::GraphId};
+use crate::{Graph, GraphElementId, GraphError, linked_graph::GraphId};
 
 use super::Edge;
 
@@ -32,6 +32,18 @@ impl<G: Graph> LinkedGraphEdgeId<G> {
         self.ptr.as_ptr()
     }
 
+    /// Checks that the ID belongs to the graph with the given ID and that the
+    /// edge has not been removed.
+    pub(super) fn check(&self, graph_id: GraphId) -> Result<(), GraphError> {
+        if self.graph_id != graph_id {
+            Err(GraphError::ForeignId)
+        } else if self.ptr.strong_count() == 0 {
+            Err(GraphError::InvalidEdgeId)
+        } else {
+            Ok(())
+        }
+    }
+
     pub(super) fn upgrade(&self, graph_id: GraphId) -> Arc<Edge<G>> {
         assert_eq!(
             self.graph_id, graph_id,
@@ -6,8 +6,13 @@ use std::{
 };
 
 use crate::{
-    copier::GraphCopier, edge_multiplicity::EdgeMultiplicity, end_pair::EndPair,
-    format_debug::format_debug, prelude::*, util::OtherValue,
+    copier::GraphCopier,
+    edge_multiplicity::EdgeMultiplicity,
+    end_pair::EndPair,
+    format_debug::format_debug,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+    util::OtherValue,
 };
 
 mod edge_id;
@@ -28,6 +33,9 @@ struct Node<G: Graph> {
     // Only maintained for directed graphs, since for undirected graphs
     // edges_out is sufficient to find all edges.
     edges_in: Vec<LinkedGraphEdgeId<G>>,
+    // The index of the node's slot in `LinkedGraph::nodes`, so the node can
+    // be removed without searching for it.
+    index: Cell<usize>,
 }
 
 struct Edge<G: Graph> {
@@ -48,6 +56,10 @@ impl<G: Graph> Edge<G> {
 /// are stored in insertion order.  Nodes and edge IDs remain valid until the
 /// node or edge is removed.
 ///
+/// Removing a node takes time proportional to the number of edges of it and
+/// its neighbors, plus amortized constant time to free its slot in the list
+/// of nodes.
+///
 /// # Type Parameters
 /// * `N` - The type of data stored in nodes
 /// * `E` - The type of data stored in edges
@@ -59,7 +71,12 @@ where
     D: Directedness,
     M: EdgeMultiplicity,
 {
-    nodes: Vec<Arc<Node<Self>>>,
+    /// The nodes in insertion order, with `None` in the slots of removed
+    /// nodes until there are enough of them to be worth compacting.
+    nodes: Vec<Option<Arc<Node<Self>>>>,
+    /// The number of `None` slots in `nodes`.
+    num_removed: usize,
+    num_edges: usize,
 
     /// A boxed value whose pointer value serves as a unique identifier for this
     /// graph instance.  This is needed to ensure safety, to ensure that the
@@ -113,6 +130,24 @@ where
         // where we have &mut self, so no other references to the edges can exist.
         unsafe { &mut *(Arc::as_ptr(&arc) as *mut _) }
     }
+
+    /// Iterates over the nodes in insertion order.
+    fn live_nodes(&self) -> impl Iterator<Item = &Arc<Node<Self>>> {
+        self.nodes.iter().flatten()
+    }
+
+    /// Removes the slots of removed nodes from `nodes` once they make up
+    /// more than half of it, so that removal takes amortized constant time.
+    fn compact_node_slots(&mut self) {
+        if self.num_removed * 2 <= self.nodes.len() {
+            return;
+        }
+        self.nodes.retain(Option::is_some);
+        for (index, node) in self.live_nodes().enumerate() {
+            node.index.set(index);
+        }
+        self.num_removed = 0;
+    }
 }
 
 impl<N, E, D, M> Graph for LinkedGraph<N, E, D, M>
@@ -131,12 +166,27 @@ where
         &self.node(id).data
     }
 
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        id.check(self.id.as_ref())
+    }
+
     fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
-        self.nodes
-            .iter()
+        self.live_nodes()
             .map(|node| LinkedGraphNodeId::new(node, self.id.as_ref()))
     }
 
+    fn num_nodes(&self) -> usize {
+        self.nodes.len() - self.num_removed
+    }
+
+    fn is_empty(&self) -> bool {
+        self.num_nodes() == 0
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        id.check(self.id.as_ref())
+    }
+
     fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
         let edge = self.edge(id);
         // SAFETY: There can be no mutable references to the data, the graph
@@ -147,8 +197,7 @@ where
     fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> {
         if self.is_directed() {
             // For directed graphs, just iterate normally
-            self.nodes
-                .iter()
+            self.live_nodes()
                 .flat_map(|node| {
                     node.edges_out
                         .iter()
@@ -160,8 +209,7 @@ where
             // For undirected graphs, deduplicate by Arc pointer address
             use std::collections::HashSet;
             let mut seen = HashSet::new();
-            self.nodes
-                .iter()
+            self.live_nodes()
                 .flat_map(|node| node.edges_out.iter())
                 .filter(move |edge| seen.insert(Arc::as_ptr(edge)))
                 .map(|edge| LinkedGraphEdgeId::new(edge, self.id.as_ref()))
@@ -238,6 +286,10 @@ where
         })
     }
 
+    fn num_edges(&self) -> usize {
+        self.num_edges
+    }
+
     fn num_edges_into(&self, into: &Self::NodeId) -> usize {
         if self.is_directed() {
             self.node(into).edges_in.len()
@@ -269,6 +321,8 @@ where
 
     fn clear(&mut self) {
         self.nodes.clear();
+        self.num_removed = 0;
+        self.num_edges = 0;
     }
 
     fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
@@ -276,9 +330,10 @@ where
             data,
             edges_out: Vec::new(),
             edges_in: Vec::new(),
+            index: Cell::new(self.nodes.len()),
         });
         let nid = LinkedGraphNodeId::new(&node, self.id.as_ref());
-        self.nodes.push(node);
+        self.nodes.push(Some(node));
         nid
     }
 
@@ -287,8 +342,8 @@ where
         from: &Self::NodeId,
         into: &Self::NodeId,
         data: Self::EdgeData,
-    ) -> (Self::EdgeId, Option<(Self::EdgeId, Self::EdgeData)>) {
-        let ends = ((from.clone(), into.clone())).into();
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        let ends = (from.clone(), into.clone()).into();
 
         if !self.allows_parallel_edges() {
             debug_assert!(self.num_edges_from_into(from, into) <= 1);
@@ -304,7 +359,11 @@ where
                 // to the graph or edge data can exist.
                 std::mem::swap(unsafe { &mut *edge.data.get() }, &mut old_data);
                 let edge_id = LinkedGraphEdgeId::new(edge, self.id.as_ref());
-                return (edge_id.clone(), Some((edge_id, old_data)));
+                return AddEdgeResult::Updated {
+                    id: edge_id.clone(),
+                    old_id: edge_id,
+                    old_data,
+                };
             }
             debug_assert_eq!(self.num_edges_from_into(from, into), 0);
         }
@@ -327,16 +386,17 @@ where
             self.node_mut(into).edges_out.push(edge);
         }
 
-        (eid, None)
+        self.num_edges += 1;
+        AddEdgeResult::Added(eid)
     }
 
     fn remove_node(&mut self, nid: &Self::NodeId) -> N {
-        let index = self
-            .nodes
-            .iter()
-            .position(|node| Arc::as_ptr(node) == nid.as_ptr())
-            .expect("Node does not exist");
-        let node = self.nodes.remove(index);
+        let index = self.node(nid).index.get();
+        let node = self.nodes[index].take().expect("Node does not exist");
+        debug_assert_eq!(Arc::as_ptr(&node), nid.as_ptr());
+        self.num_removed += 1;
+        self.compact_node_slots();
+        self.num_edges -= node.edges_out.len();
 
         // Remove outgoing edges from other nodes
         for edge in &node.edges_out {
@@ -370,6 +430,7 @@ where
                     from_node
                         .edges_out
                         .retain(|edge| Arc::as_ptr(edge) != eid.as_ptr());
+                    self.num_edges -= 1;
                 }
             }
         }
@@ -398,9 +459,10 @@ where
             let to_node = self.node_mut(into_nid);
             to_node
                 .edges_out
-                .retain(|edge| eid.as_ptr() != Arc::as_ptr(&edge));
+                .retain(|edge| eid.as_ptr() != Arc::as_ptr(edge));
         }
 
+        self.num_edges -= 1;
         Arc::into_inner(edge)
             .expect("Edge has multiple references")
             .data
@@ -408,6 +470,20 @@ where
     }
 }
 
+impl<N, E, D, M> StableNodeIds for LinkedGraph<N, E, D, M>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+{
+}
+
+impl<N, E, D, M> StableEdgeIds for LinkedGraph<N, E, D, M>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+{
+}
+
 impl<N, E, D, M> Clone for LinkedGraph<N, E, D, M>
 where
     N: Clone,
@@ -4,7 +4,7 @@ use std::{
     sync::{Arc, Weak},
 };
 
-use crate::{Graph, linked_graph::GraphId};
+use crate::{Graph, GraphError, linked_graph::GraphId};
 
 use super::Node;
 
@@ -28,6 +28,18 @@ impl<G: Graph> LinkedGraphNodeId<G> {
         self.ptr.as_ptr()
     }
 
+    /// Checks that the ID belongs to the graph with the given ID and that the
+    /// node has not been removed.
+    pub(super) fn check(&self, graph_id: GraphId) -> Result<(), GraphError> {
+        if self.graph_id != graph_id {
+            Err(GraphError::ForeignId)
+        } else if self.ptr.strong_count() == 0 {
+            Err(GraphError::InvalidNodeId)
+        } else {
+            Ok(())
+        }
+    }
+
     pub(super) fn upgrade(&self, graph_id: GraphId) -> Arc<Node<G>> {
         assert_eq!(
             self.graph_id, graph_id,
@@ -0,0 +1,300 @@
+//! Graphs with their node and edge data projected to other types.
+//!
+//! [`Graph::map`] creates a [`MappedGraphView`], which borrows a graph and
+//! presents a projection of each node's and edge's data, such as a field of
+//! a struct, in place of the data itself.  Nothing is copied: the view has
+//! the same node and edge IDs as the graph, and each projection is applied
+//! when the data is looked up.
+//!
+//! [`GraphMut::into_map`] instead consumes a graph, moving each node's and
+//! edge's data through a conversion function into a new graph of any type.
+
+use std::{fmt::Debug, marker::PhantomData};
+
+use crate::{
+    end_pair::EndPair,
+    format_debug::format_debug,
+    hashing::FastHashMap,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A view of a graph in which the data of each node and edge is replaced by
+/// a projection of it.  Created by [`Graph::map`].
+///
+/// The view shares the IDs of the underlying graph, so IDs from one can be
+/// used with the other.
+pub struct MappedGraphView<'g, G, N, E, NF, EF>
+where
+    G: Graph + ?Sized,
+{
+    graph: &'g G,
+    node_fn: NF,
+    edge_fn: EF,
+    phantom: PhantomData<fn() -> (&'g N, &'g E)>,
+}
+
+impl<'g, G, N, E, NF, EF> MappedGraphView<'g, G, N, E, NF, EF>
+where
+    G: Graph + ?Sized,
+    NF: Fn(&G::NodeData) -> &N,
+    EF: Fn(&G::EdgeData) -> &E,
+{
+    /// Creates a view of `graph` which projects the data of each node with
+    /// `node_fn` and the data of each edge with `edge_fn`.
+    pub fn new(graph: &'g G, node_fn: NF, edge_fn: EF) -> Self {
+        Self {
+            graph,
+            node_fn,
+            edge_fn,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Gets the underlying graph.
+    pub fn inner(&self) -> &'g G {
+        self.graph
+    }
+}
+
+impl<G, N, E, NF, EF> Clone for MappedGraphView<'_, G, N, E, NF, EF>
+where
+    G: Graph + ?Sized,
+    NF: Clone,
+    EF: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            graph: self.graph,
+            node_fn: self.node_fn.clone(),
+            edge_fn: self.edge_fn.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<G, N, E, NF, EF> Debug for MappedGraphView<'_, G, N, E, NF, EF>
+where
+    G: Graph + ?Sized,
+    N: Debug,
+    E: Debug,
+    NF: Fn(&G::NodeData) -> &N,
+    EF: Fn(&G::EdgeData) -> &E,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_debug(self, f, "MappedGraphView")
+    }
+}
+
+impl<G, N, E, NF, EF> Graph for MappedGraphView<'_, G, N, E, NF, EF>
+where
+    G: Graph + ?Sized,
+    NF: Fn(&G::NodeData) -> &N,
+    EF: Fn(&G::EdgeData) -> &E,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = N;
+    type EdgeData = E;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.graph.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        (self.node_fn)(self.graph.node_data(id))
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.graph.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.graph.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        (self.edge_fn)(self.graph.edge_data(id))
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.graph.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.graph.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.graph.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.graph.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.graph.edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.graph.edges_from_into(from, into)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.graph.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.graph.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.graph.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.graph.num_edges_from(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.graph.is_very_slow()
+    }
+}
+
+impl<G, N, E, NF, EF> StableNodeIds for MappedGraphView<'_, G, N, E, NF, EF>
+where
+    G: StableNodeIds + ?Sized,
+    NF: Fn(&G::NodeData) -> &N,
+    EF: Fn(&G::EdgeData) -> &E,
+{
+}
+
+impl<G, N, E, NF, EF> StableEdgeIds for MappedGraphView<'_, G, N, E, NF, EF>
+where
+    G: StableEdgeIds + ?Sized,
+    NF: Fn(&G::NodeData) -> &N,
+    EF: Fn(&G::EdgeData) -> &E,
+{
+}
+
+/// Moves the nodes and edges of `graph` into a new graph, converting their
+/// data with `node_fn` and `edge_fn`.  See [`GraphMut::into_map`].
+pub fn into_map<G, H>(
+    mut graph: G,
+    mut node_fn: impl FnMut(G::NodeData) -> H::NodeData,
+    mut edge_fn: impl FnMut(G::EdgeData) -> H::EdgeData,
+) -> H
+where
+    G: GraphMut,
+    H: GraphMut<Directedness = G::Directedness, EdgeMultiplicity = G::EdgeMultiplicity> + Default,
+{
+    // Removing a node removes its edges, so the edges are taken out first.
+    let edges: Vec<_> = graph
+        .edge_ids()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|eid| {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            (from, into, graph.remove_edge(&eid))
+        })
+        .collect();
+    let mut result = H::default();
+    result.reserve(graph.num_nodes(), edges.len());
+    let node_map: FastHashMap<_, _> = graph
+        .node_ids()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|nid| {
+            let data = node_fn(graph.remove_node(&nid));
+            (nid, result.add_node(data))
+        })
+        .collect();
+    for (from, into, data) in edges {
+        result.add_edge(&node_map[&from], &node_map[&into], edge_fn(data));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LinkedGraph;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct City {
+        name: String,
+        population: u32,
+    }
+
+    #[test]
+    fn test_map_view() {
+        let mut graph = LinkedGraph::<City, (u32, &str), Directed>::default();
+        let a = graph.add_node(City {
+            name: "Avon".to_string(),
+            population: 100,
+        });
+        let b = graph.add_node(City {
+            name: "Bree".to_string(),
+            population: 250,
+        });
+        let ab = graph.add_new_edge(&a, &b, (12, "north road"));
+
+        let view = graph.map(|city| &city.name, |(length, _)| length);
+        assert_eq!(view.node_data(&a), "Avon");
+        assert_eq!(view.node_data(&b), "Bree");
+        assert_eq!(*view.edge_data(&ab), 12);
+        assert_eq!(view.edges_from(&a).collect::<Vec<_>>(), vec![ab.clone()]);
+        assert_eq!(view.edge_ends(&ab), (a.clone(), b.clone()));
+        assert_eq!(view.num_nodes(), 2);
+        assert_eq!(view.inner().node_data(&b).population, 250);
+
+        let names = view.clone();
+        assert_eq!(names.node_data(&a), view.node_data(&a));
+    }
+
+    #[test]
+    fn test_into_map() {
+        let mut graph = LinkedGraph::<&str, u32, Undirected>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_edge(&a, &b, 1);
+        graph.add_edge(&b, &c, 2);
+        graph.add_edge(&c, &c, 3);
+
+        let mapped: LinkedGraph<String, u64, Undirected> =
+            graph.into_map(|name| name.to_uppercase(), |weight| u64::from(weight) * 10);
+        assert_eq!(mapped.num_nodes(), 3);
+        let mut edges: Vec<_> = mapped
+            .edge_ids()
+            .map(|eid| {
+                let (from, into) = mapped.edge_ends(&eid).into_values();
+                let mut ends = [mapped.node_data(&from), mapped.node_data(&into)];
+                ends.sort();
+                (ends.map(String::as_str), *mapped.edge_data(&eid))
+            })
+            .collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![(["A", "B"], 10), (["B", "C"], 20), (["C", "C"], 30)]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Construction of graphs from adjacency matrices.
+//!
+//! [`from_adjacency_matrix`] converts one of this crate's [`AdjacencyMatrix`]
+//! types, in which every entry is an edge.  With the `ndarray` feature,
+//! [`from_ndarray`] converts a dense square array, such as a correlation or
+//! similarity matrix, using a closure to decide which entries are edges.
+//!
+//! In both cases node `i` of the new graph corresponds to row and column `i`
+//! of the matrix, and nodes are added in order of their indices.
+
+use crate::{adjacency_matrix::AdjacencyMatrix, prelude::*};
+
+#[cfg(feature = "ndarray")]
+use ndarray::{ArrayBase, Data, Ix2};
+
+/// Constructs a graph from an adjacency matrix.  See
+/// [`GraphMut::from_adjacency_matrix`].
+pub fn from_adjacency_matrix<G, M>(
+    matrix: &M,
+    mut node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_data_fn: impl FnMut(usize, usize, &M::Value) -> G::EdgeData,
+) -> G
+where
+    G: GraphMut<Directedness = M::Directedness> + Default,
+    M: AdjacencyMatrix,
+{
+    let mut graph = G::default();
+    graph.reserve(matrix.size_bound(), matrix.len());
+    let nodes: Vec<_> = (0..matrix.size_bound())
+        .map(|i| graph.add_node(node_data_fn(i)))
+        .collect();
+    for (row, col, value) in matrix.iter() {
+        graph.add_edge(&nodes[row], &nodes[col], edge_data_fn(row, col, value));
+    }
+    graph
+}
+
+/// Constructs a graph from a square two-dimensional array.  See
+/// [`GraphMut::from_ndarray`].
+#[cfg(feature = "ndarray")]
+pub fn from_ndarray<G, S>(
+    array: &ArrayBase<S, Ix2>,
+    mut node_data_fn: impl FnMut(usize) -> G::NodeData,
+    mut edge_fn: impl FnMut(usize, usize, &S::Elem) -> Option<G::EdgeData>,
+) -> G
+where
+    G: GraphMut + Default,
+    S: Data,
+{
+    let (rows, cols) = array.dim();
+    assert_eq!(rows, cols, "adjacency array must be square");
+    let mut graph = G::default();
+    graph.reserve(rows, 0);
+    let nodes: Vec<_> = (0..rows).map(|i| graph.add_node(node_data_fn(i))).collect();
+    for ((row, col), value) in array.indexed_iter() {
+        // An undirected graph only uses the upper triangle.
+        if !graph.is_directed() && col < row {
+            continue;
+        }
+        if let Some(data) = edge_fn(row, col, value) {
+            graph.add_edge(&nodes[row], &nodes[col], data);
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BagGraph, LinkedGraph,
+        adjacency_matrix::{AdjacencyMatrix, hash::HashAdjacencyMatrix},
+        end_pair::EndPair,
+    };
+
+    use super::*;
+
+    fn sorted_edges<G: Graph<NodeData = usize>>(graph: &G) -> Vec<(usize, usize)> {
+        let mut edges: Vec<_> = graph
+            .edge_ids()
+            .map(|eid| {
+                let (from, into) = graph.edge_ends(&eid).into_values();
+                let (from, into) = (*graph.node_data(&from), *graph.node_data(&into));
+                if graph.is_directed() {
+                    (from, into)
+                } else {
+                    (from.min(into), from.max(into))
+                }
+            })
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    #[test]
+    fn test_from_directed_matrix() {
+        let mut matrix = HashAdjacencyMatrix::<&str, Directed>::with_size(3);
+        matrix.insert(0, 1, "a");
+        matrix.insert(1, 0, "b");
+        matrix.insert(2, 2, "c");
+
+        let graph: BagGraph<usize, String, Directed> = GraphMut::from_adjacency_matrix(
+            &matrix,
+            |i| i,
+            |row, col, value| format!("{}{}{}", value, row, col),
+        );
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(sorted_edges(&graph), vec![(0, 1), (1, 0), (2, 2)]);
+        let mut data: Vec<_> = graph.edge_ids().map(|eid| graph.edge_data(&eid)).collect();
+        data.sort();
+        assert_eq!(data, vec!["a01", "b10", "c22"]);
+    }
+
+    #[test]
+    fn test_from_undirected_matrix() {
+        let mut matrix = HashAdjacencyMatrix::<(), Undirected>::with_size(3);
+        matrix.insert(0, 1, ());
+        matrix.insert(2, 1, ());
+
+        let graph: LinkedGraph<usize, (), Undirected> =
+            from_adjacency_matrix(&matrix, |i| i, |_, _, _| ());
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(sorted_edges(&graph), vec![(0, 1), (1, 2)]);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_from_ndarray_with_threshold() {
+        let similarity = ndarray::array![[1.0, 0.9, 0.2], [0.9, 1.0, 0.6], [0.2, 0.6, 1.0]];
+        let edge_fn = |row, col, &w: &f64| (row != col && w >= 0.5).then_some(w);
+
+        let undirected: BagGraph<usize, f64, Undirected> =
+            GraphMut::from_ndarray(&similarity, |i| i, edge_fn);
+        assert_eq!(sorted_edges(&undirected), vec![(0, 1), (1, 2)]);
+
+        let directed: BagGraph<usize, f64, Directed> =
+            GraphMut::from_ndarray(&similarity.view(), |i| i, edge_fn);
+        assert_eq!(
+            sorted_edges(&directed),
+            vec![(0, 1), (1, 0), (1, 2), (2, 1)]
+        );
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    #[should_panic(expected = "must be square")]
+    fn test_from_ndarray_not_square() {
+        let array = ndarray::Array2::<u8>::zeros((2, 3));
+        let _: BagGraph<usize, u8, Directed> = from_ndarray(&array, |i| i, |_, _, &w| Some(w));
+    }
+}
@@ -0,0 +1,369 @@
+//! Negative cycles, minimum mean cycles and minimum cost-to-time ratio
+//! cycles of directed graphs.
+//!
+//! [`find_negative_cycle`] finds a cycle whose total weight is negative with
+//! the Bellman-Ford algorithm, in `O(V E)` time.  With weights of
+//! `-ln(rate)`, such a cycle is an arbitrage opportunity: a sequence of
+//! exchanges whose rates multiply to more than one.
+//!
+//! The mean weight of a cycle is the total weight of its edges divided by
+//! the number of edges.  [`min_mean_cycle`] finds a cycle with the smallest
+//! mean weight using Karp's algorithm, in `O(V E)` time and `O(V^2)` space.
+//!
+//! The ratio of a cycle is the total cost of its edges divided by their
+//! total time, e.g. the cycle time of a timed event graph.
+//! [`min_cycle_ratio`] finds a cycle with the smallest ratio by repeatedly
+//! finding a cycle with a negative total of `cost - ratio * time` with the
+//! Bellman-Ford algorithm, and taking its ratio as the new bound, until there
+//! is none.  The total time of every cycle must be positive.
+//!
+//! Both functions return the cycle as a [`Path`] which starts and ends at
+//! the same node, with its mean or ratio as computed from its own edges.
+//! Weights, costs and times may be negative.  A self-loop is a cycle of one
+//! edge.
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, path::Path, prelude::*};
+
+/// The edges of a graph, with the node indices of their ends.
+struct IndexedEdges<G: Graph + ?Sized> {
+    num_nodes: usize,
+    edges: Vec<(usize, usize, G::EdgeId)>,
+    nodes: Vec<G::NodeId>,
+}
+
+impl<G: Graph + ?Sized> IndexedEdges<G> {
+    fn new(graph: &G) -> Self {
+        let nodes: Vec<_> = graph.node_ids().collect();
+        let indices: FastHashMap<_, _> =
+            nodes.iter().enumerate().map(|(i, nid)| (nid, i)).collect();
+        let edges = graph
+            .edge_ids()
+            .map(|eid| {
+                let (from, into) = graph.edge_ends(&eid).into_values();
+                (indices[&from], indices[&into], eid)
+            })
+            .collect();
+        Self {
+            num_nodes: nodes.len(),
+            edges,
+            nodes,
+        }
+    }
+
+    /// Converts a cycle of edge indices into a path.
+    fn cycle_path<'g>(&self, graph: &'g G, cycle: &[usize]) -> Path<'g, G> {
+        let start = self.nodes[self.edges[cycle[0]].0].clone();
+        Path::from_edges(graph, start, cycle.iter().map(|&e| self.edges[e].2.clone()))
+    }
+}
+
+/// Returns a cycle of `graph` whose total weight is negative, along with
+/// its total weight, or `None` if there is none.
+pub fn find_negative_cycle<G>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> f64,
+) -> Option<(Path<'_, G>, f64)>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    let indexed = IndexedEdges::new(graph);
+    let weights: Vec<_> = indexed
+        .edges
+        .iter()
+        .map(|(_, _, eid)| weight_fn(eid))
+        .collect();
+    let cycle = negative_cycle(&indexed, |e| weights[e])?;
+    let total = cycle.iter().map(|&e| weights[e]).sum();
+    Some((indexed.cycle_path(graph, &cycle), total))
+}
+
+/// Returns a cycle of `graph` with the smallest mean weight, along with its
+/// mean weight, or `None` if the graph is acyclic.
+pub fn min_mean_cycle<G>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> f64,
+) -> Option<(Path<'_, G>, f64)>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    let indexed = IndexedEdges::new(graph);
+    let n = indexed.num_nodes;
+    let weights: Vec<_> = indexed
+        .edges
+        .iter()
+        .map(|(_, _, eid)| weight_fn(eid))
+        .collect();
+
+    // `walks[k][v]` is the weight of the lightest walk of exactly `k` edges
+    // ending at `v` and starting anywhere, and `last_edges[k][v]` is the last
+    // edge of that walk.
+    let mut walks = vec![vec![f64::INFINITY; n]; n + 1];
+    let mut last_edges = vec![vec![None; n]; n + 1];
+    walks[0].fill(0.0);
+    for k in 1..=n {
+        for (e, &(from, into, _)) in indexed.edges.iter().enumerate() {
+            let weight = walks[k - 1][from] + weights[e];
+            if weight < walks[k][into] {
+                walks[k][into] = weight;
+                last_edges[k][into] = Some(e);
+            }
+        }
+    }
+
+    // Karp's theorem: the minimum mean is the minimum over `v` of the
+    // maximum over `k` of `(walks[n][v] - walks[k][v]) / (n - k)`.
+    let (end, _) = (0..n)
+        .filter(|&v| walks[n][v].is_finite())
+        .map(|v| {
+            let mean = (0..n)
+                .filter(|&k| walks[k][v].is_finite())
+                .map(|k| (walks[n][v] - walks[k][v]) / (n - k) as f64)
+                .fold(f64::NEG_INFINITY, f64::max);
+            (v, mean)
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+    // Every cycle in the lightest walk of `n` edges ending at `end` has the
+    // minimum mean weight.  Follow the walk backwards until a node repeats.
+    let mut seen: FastHashMap<usize, usize> = FastHashMap::default();
+    let mut walk = Vec::with_capacity(n);
+    let mut node = end;
+    let mut k = n;
+    loop {
+        if let Some(&later) = seen.get(&node) {
+            // The walk visits `n + 1` nodes, so this happens by the time
+            // `k` reaches zero.
+            let mut cycle = walk[n - later..n - k].to_vec();
+            cycle.reverse();
+            return Some(finish_cycle(graph, &indexed, cycle, |e| (weights[e], 1.0)));
+        }
+        seen.insert(node, k);
+        let e = last_edges[k][node].expect("walk has an edge at every step");
+        walk.push(e);
+        node = indexed.edges[e].0;
+        k -= 1;
+    }
+}
+
+/// Returns a cycle of `graph` with the smallest ratio of total cost to total
+/// time, along with its ratio, or `None` if the graph is acyclic.  The total
+/// time of every cycle must be positive.
+pub fn min_cycle_ratio<G>(
+    graph: &G,
+    cost_fn: impl Fn(&G::EdgeId) -> f64,
+    time_fn: impl Fn(&G::EdgeId) -> f64,
+) -> Option<(Path<'_, G>, f64)>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    let indexed = IndexedEdges::new(graph);
+    let costs: Vec<_> = indexed
+        .edges
+        .iter()
+        .map(|(_, _, eid)| cost_fn(eid))
+        .collect();
+    let times: Vec<_> = indexed
+        .edges
+        .iter()
+        .map(|(_, _, eid)| time_fn(eid))
+        .collect();
+    let cost_and_time = |e: usize| (costs[e], times[e]);
+
+    let initial = negative_cycle(&indexed, |_| -1.0)?;
+    let (mut best, mut ratio) = finish_cycle(graph, &indexed, initial, cost_and_time);
+    // Each cycle found has a smaller ratio than the one before, so this
+    // terminates.  Checking the ratio directly guards against rounding
+    // errors in the search.
+    while let Some(cycle) = negative_cycle(&indexed, |e| costs[e] - ratio * times[e]) {
+        let (path, cycle_ratio) = finish_cycle(graph, &indexed, cycle, cost_and_time);
+        if cycle_ratio >= ratio {
+            break;
+        }
+        best = path;
+        ratio = cycle_ratio;
+    }
+    Some((best, ratio))
+}
+
+/// Converts a cycle of edge indices into a path, along with the ratio of the
+/// totals of the two values `values` gives for each edge.
+fn finish_cycle<'g, G>(
+    graph: &'g G,
+    indexed: &IndexedEdges<G>,
+    cycle: Vec<usize>,
+    values: impl Fn(usize) -> (f64, f64),
+) -> (Path<'g, G>, f64)
+where
+    G: Graph + ?Sized,
+{
+    let (numerator, denominator) = cycle
+        .iter()
+        .map(|&e| values(e))
+        .fold((0.0, 0.0), |(a, b), (c, d)| (a + c, b + d));
+    (indexed.cycle_path(graph, &cycle), numerator / denominator)
+}
+
+/// Finds a cycle with a negative total weight using the Bellman-Ford
+/// algorithm, starting from every node at once.  Returns the edge indices of
+/// the cycle in order.
+fn negative_cycle<G>(
+    indexed: &IndexedEdges<G>,
+    weight_fn: impl Fn(usize) -> f64,
+) -> Option<Vec<usize>>
+where
+    G: Graph + ?Sized,
+{
+    let n = indexed.num_nodes;
+    let mut distances = vec![0.0; n];
+    let mut last_edges: Vec<Option<usize>> = vec![None; n];
+    let mut updated = None;
+    for _ in 0..n {
+        updated = None;
+        for (e, &(from, into, _)) in indexed.edges.iter().enumerate() {
+            let distance = distances[from] + weight_fn(e);
+            if distance < distances[into] {
+                distances[into] = distance;
+                last_edges[into] = Some(e);
+                updated = Some(into);
+            }
+        }
+        updated?;
+    }
+
+    // A node updated in the last round is reached from a cycle of last
+    // edges, so following them `n` times leads onto the cycle.
+    let mut node = updated?;
+    for _ in 0..n {
+        node = indexed.edges[last_edges[node]?].0;
+    }
+    let start = node;
+    let mut cycle = Vec::new();
+    loop {
+        let e = last_edges[node]?;
+        cycle.push(e);
+        node = indexed.edges[e].0;
+        if node == start {
+            break;
+        }
+    }
+    cycle.reverse();
+    Some(cycle)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    type TestGraph = LinkedGraph<(), (f64, f64), Directed>;
+
+    /// Checks that `path` is a cycle whose edges are sorted to `expected`.
+    fn assert_cycle<G: Graph>(path: &Path<'_, G>, mut expected: Vec<G::EdgeId>) {
+        assert_eq!(path.first_node(), path.last_node());
+        let mut edges: Vec<_> = path.edges().collect();
+        edges.sort();
+        expected.sort();
+        assert_eq!(edges, expected);
+    }
+
+    /// Builds a graph with a two-edge cycle `a -> b -> a`, a three-edge cycle
+    /// `b -> c -> d -> b`, and a self-loop at `d`, with edge data `(cost,
+    /// time)`.
+    fn example() -> (TestGraph, Vec<<TestGraph as Graph>::EdgeId>) {
+        let mut graph = LinkedGraph::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        let edges = vec![
+            graph.add_new_edge(&n[0], &n[1], (4.0, 3.0)),
+            graph.add_new_edge(&n[1], &n[0], (2.0, 1.0)),
+            graph.add_new_edge(&n[1], &n[2], (1.0, 1.0)),
+            graph.add_new_edge(&n[2], &n[3], (2.0, 0.5)),
+            graph.add_new_edge(&n[3], &n[1], (3.0, 0.5)),
+            graph.add_new_edge(&n[3], &n[3], (5.0, 1.0)),
+        ];
+        (graph, edges)
+    }
+
+    #[test]
+    fn test_min_mean_cycle() {
+        let (graph, edges) = example();
+        let (cycle, mean) = graph.min_mean_cycle(|eid| graph.edge_data(eid).0).unwrap();
+        assert_eq!(mean, 2.0);
+        assert_cycle(&cycle, edges[2..5].to_vec());
+
+        let (cycle, mean) = graph.min_mean_cycle(|eid| -graph.edge_data(eid).0).unwrap();
+        assert_eq!(mean, -5.0);
+        assert_cycle(&cycle, vec![edges[5].clone()]);
+    }
+
+    #[test]
+    fn test_min_cycle_ratio() {
+        let (graph, edges) = example();
+        let (cycle, ratio) = graph
+            .min_cycle_ratio(|eid| graph.edge_data(eid).0, |eid| graph.edge_data(eid).1)
+            .unwrap();
+        assert_eq!(ratio, 1.5);
+        assert_cycle(&cycle, edges[0..2].to_vec());
+
+        // With unit times, the ratio is the mean.
+        let (cycle, ratio) = graph
+            .min_cycle_ratio(|eid| graph.edge_data(eid).0, |_| 1.0)
+            .unwrap();
+        assert_eq!(ratio, 2.0);
+        assert_cycle(&cycle, edges[2..5].to_vec());
+    }
+
+    #[test]
+    fn test_find_negative_cycle() {
+        let (graph, edges) = example();
+        assert!(
+            graph
+                .find_negative_cycle(|eid| graph.edge_data(eid).0)
+                .is_none()
+        );
+
+        // Only the three-edge cycle has a total of less than three times its
+        // number of edges.
+        let (cycle, total) = graph
+            .find_negative_cycle(|eid| graph.edge_data(eid).0 - 2.5)
+            .unwrap();
+        assert_eq!(total, -1.5);
+        assert_cycle(&cycle, edges[2..5].to_vec());
+    }
+
+    #[test]
+    fn test_arbitrage() {
+        // Exchange rates between currencies, where trading around the
+        // triangle returns 0.9 * 0.95 * 1.25 = 1.06875 times the starting
+        // amount.
+        let mut graph = BagGraph::<&str, f64, Directed>::default();
+        let usd = graph.add_node("USD");
+        let eur = graph.add_node("EUR");
+        let gbp = graph.add_node("GBP");
+        graph.add_new_edge(&usd, &eur, 0.9);
+        graph.add_new_edge(&eur, &usd, 1.1);
+        graph.add_new_edge(&eur, &gbp, 0.95);
+        graph.add_new_edge(&gbp, &usd, 1.25);
+        graph.add_new_edge(&usd, &gbp, 0.75);
+        let (cycle, total) = graph
+            .find_negative_cycle(|eid| -graph.edge_data(eid).ln())
+            .unwrap();
+        assert!(((-total).exp() - 0.9 * 0.95 * 1.25).abs() < 1e-9);
+        let mut nodes: Vec<_> = cycle.nodes().map(|nid| *graph.node_data(&nid)).collect();
+        assert_eq!(nodes.first(), nodes.last());
+        nodes.pop();
+        nodes.sort();
+        assert_eq!(nodes, vec!["EUR", "GBP", "USD"]);
+    }
+
+    #[test]
+    fn test_acyclic() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        assert!(graph.min_mean_cycle(|_| 1.0).is_none());
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_new_edge(&a, &b, ());
+        assert!(graph.min_mean_cycle(|_| 1.0).is_none());
+        assert!(graph.min_cycle_ratio(|_| 1.0, |_| 1.0).is_none());
+    }
+}
@@ -0,0 +1,379 @@
+//! Caching of algorithm results for local queries.
+//!
+//! Many queries only depend on a small region of a graph, such as the ego-net
+//! of a node.  A [`SubgraphMemo`] caches the result of each query along with a
+//! fingerprint of the subgraph induced by its region, and reuses the result
+//! for as long as the region is unchanged.
+//!
+//! A region is considered changed if its set of node IDs, or the IDs and ends
+//! of the edges between those nodes, differ from when the result was
+//! computed.  Changes to node or edge *data* are not detected; call
+//! [`SubgraphMemo::invalidate_node`] after mutating data that a query depends
+//! on.
+//!
+//! When the graph is wrapped in a [`NotifyingGraph`], the observer returned by
+//! [`SubgraphMemo::observer`] evicts affected entries as soon as the graph is
+//! mutated, rather than waiting for the next lookup to notice the changed
+//! fingerprint.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use crate::{
+    end_pair::EndPair,
+    notifying_graph::{GraphEvent, GraphObserver},
+    prelude::*,
+};
+
+#[cfg(doc)]
+use crate::NotifyingGraph;
+
+struct MemoEntry<N, V> {
+    fingerprint: u64,
+    region: Vec<N>,
+    value: V,
+}
+
+struct MemoState<N, K, V> {
+    entries: HashMap<K, MemoEntry<N, V>>,
+    keys_by_node: HashMap<N, HashSet<K>>,
+}
+
+impl<N, K, V> MemoState<N, K, V>
+where
+    N: Hash + Eq,
+    K: Hash + Eq + Clone,
+{
+    fn remove_entry(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            for nid in entry.region {
+                if let Some(keys) = self.keys_by_node.get_mut(&nid) {
+                    keys.remove(key);
+                    if keys.is_empty() {
+                        self.keys_by_node.remove(&nid);
+                    }
+                }
+            }
+        }
+    }
+
+    fn invalidate_node(&mut self, nid: &N) {
+        if let Some(keys) = self.keys_by_node.remove(nid) {
+            for key in keys {
+                self.remove_entry(&key);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.keys_by_node.clear();
+    }
+}
+
+/// A cache of query results, keyed by a caller-chosen query key of type `K`
+/// and validated against a fingerprint of the region each query depends on.
+///
+/// Cloning a `SubgraphMemo` yields a handle to the same cache.
+pub struct SubgraphMemo<G, K, V>
+where
+    G: Graph,
+{
+    state: Rc<RefCell<MemoState<G::NodeId, K, V>>>,
+}
+
+impl<G, K, V> SubgraphMemo<G, K, V>
+where
+    G: Graph,
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(MemoState {
+                entries: HashMap::new(),
+                keys_by_node: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Returns the cached result for `key` if the subgraph induced by
+    /// `region` is unchanged since it was computed.  Otherwise, calls
+    /// `compute` and caches its result.
+    pub fn memoize_on_subgraph(
+        &self,
+        graph: &G,
+        key: K,
+        region: impl IntoIterator<Item = G::NodeId>,
+        compute: impl FnOnce(&G, &[G::NodeId]) -> V,
+    ) -> V {
+        let mut region: Vec<_> = region.into_iter().collect();
+        region.sort();
+        region.dedup();
+        let fingerprint = subgraph_fingerprint(graph, &region);
+
+        let mut state = self.state.borrow_mut();
+        if let Some(entry) = state.entries.get(&key) {
+            if entry.fingerprint == fingerprint && entry.region == region {
+                return entry.value.clone();
+            }
+            state.remove_entry(&key);
+        }
+        // The cache is not borrowed while computing, so `compute` may itself
+        // consult this memo for other keys.
+        drop(state);
+        let value = compute(graph, &region);
+
+        let mut state = self.state.borrow_mut();
+        state.remove_entry(&key);
+        for nid in region.iter() {
+            state
+                .keys_by_node
+                .entry(nid.clone())
+                .or_default()
+                .insert(key.clone());
+        }
+        state.entries.insert(
+            key,
+            MemoEntry {
+                fingerprint,
+                region,
+                value: value.clone(),
+            },
+        );
+        value
+    }
+
+    /// Evicts every cached result whose region contains `id`.
+    pub fn invalidate_node(&self, id: &G::NodeId) {
+        self.state.borrow_mut().invalidate_node(id);
+    }
+
+    /// Evicts the cached result for `key`, if any.
+    pub fn invalidate(&self, key: &K) {
+        self.state.borrow_mut().remove_entry(key);
+    }
+
+    /// Evicts all cached results.
+    pub fn clear(&self) {
+        self.state.borrow_mut().clear();
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        self.state.borrow().entries.len()
+    }
+
+    /// Returns true if no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an observer which evicts cached results as the graph is
+    /// mutated.  Register it with [`NotifyingGraph::add_observer`].
+    pub fn observer(&self) -> impl GraphObserver<G> + 'static
+    where
+        G: 'static,
+        K: 'static,
+        V: 'static,
+    {
+        let state = self.state.clone();
+        move |event: &GraphEvent<'_, G>| {
+            let mut state = state.borrow_mut();
+            match event {
+                GraphEvent::NodeAdded { .. } => {}
+                GraphEvent::NodeRemoved { id, .. } => state.invalidate_node(id),
+                GraphEvent::EdgeAdded { ends, .. } | GraphEvent::EdgeRemoved { ends, .. } => {
+                    let (from, into) = ends.values();
+                    state.invalidate_node(from);
+                    state.invalidate_node(into);
+                }
+                GraphEvent::Compacted { .. } => state.clear(),
+            }
+        }
+    }
+}
+
+impl<G, K, V> Default for SubgraphMemo<G, K, V>
+where
+    G: Graph,
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G, K, V> Clone for SubgraphMemo<G, K, V>
+where
+    G: Graph,
+{
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Computes a fingerprint of the subgraph induced by `region`, which must be
+/// sorted and free of duplicates.  The fingerprint covers node IDs and the IDs
+/// and ends of edges between nodes in the region, but not node or edge data.
+pub fn subgraph_fingerprint<G>(graph: &G, region: &[G::NodeId]) -> u64
+where
+    G: Graph + ?Sized,
+{
+    let mut hasher = DefaultHasher::new();
+    region.hash(&mut hasher);
+    let mut edges: Vec<_> = region
+        .iter()
+        .flat_map(|nid| graph.edges_from(nid))
+        .filter_map(|eid| {
+            let ends = graph.edge_ends(&eid);
+            let (from, into) = ends.values();
+            (region.binary_search(from).is_ok() && region.binary_search(into).is_ok())
+                .then_some((eid, ends))
+        })
+        .collect();
+    // Undirected edges are reported from both ends.
+    edges.sort();
+    edges.dedup();
+    edges.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the nodes within `radius` steps of `center`, following edges in
+/// either direction.  The result includes `center` itself.
+pub fn ego_net<G>(graph: &G, center: &G::NodeId, radius: usize) -> Vec<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    let mut visited = HashSet::from([center.clone()]);
+    let mut frontier = vec![center.clone()];
+    for _ in 0..radius {
+        let mut next = Vec::new();
+        for nid in frontier.iter() {
+            for neighbor in graph.successors(nid).chain(graph.predecessors(nid)) {
+                if visited.insert(neighbor.clone()) {
+                    next.push(neighbor);
+                }
+            }
+        }
+        frontier = next;
+    }
+    visited.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::{BagGraph, NotifyingGraph};
+
+    use super::*;
+
+    type Inner = BagGraph<u32, (), Undirected>;
+
+    fn create_graph() -> (NotifyingGraph<Inner>, Vec<<Inner as Graph>::NodeId>) {
+        // A path 0 - 1 - 2 - 3 - 4.
+        let mut graph = NotifyingGraph::<Inner>::default();
+        let n: Vec<_> = (0..5).map(|i| graph.add_node(i)).collect();
+        for pair in n.windows(2) {
+            graph.add_new_edge(&pair[0], &pair[1], ());
+        }
+        (graph, n)
+    }
+
+    fn region_sum(graph: &Inner, region: &[<Inner as Graph>::NodeId], calls: &Cell<usize>) -> u32 {
+        calls.set(calls.get() + 1);
+        region.iter().map(|nid| graph.node_data(nid)).sum()
+    }
+
+    #[test]
+    fn test_ego_net() {
+        let (graph, n) = create_graph();
+        let mut net = ego_net(&graph, &n[2], 1);
+        net.sort();
+        assert_eq!(net, vec![n[1].clone(), n[2].clone(), n[3].clone()]);
+        assert_eq!(ego_net(&graph, &n[0], 0), vec![n[0].clone()]);
+    }
+
+    #[test]
+    fn test_cache_hit_and_fingerprint_miss() {
+        let (graph, n) = create_graph();
+        let mut inner = graph.into_inner();
+        let memo = SubgraphMemo::<Inner, _, _>::new();
+        let calls = Cell::new(0);
+        let query = |graph: &Inner, center: &<Inner as Graph>::NodeId| {
+            memo.memoize_on_subgraph(graph, center.clone(), ego_net(graph, center, 1), |g, r| {
+                region_sum(g, r, &calls)
+            })
+        };
+
+        assert_eq!(query(&inner, &n[1]), 3);
+        assert_eq!(query(&inner, &n[1]), 3);
+        assert_eq!(calls.get(), 1);
+
+        // Mutating the graph directly is detected by the fingerprint.
+        inner.add_new_edge(&n[0], &n[2], ());
+        assert_eq!(query(&inner, &n[1]), 3);
+        assert_eq!(calls.get(), 2);
+
+        // Unrelated regions are unaffected.
+        assert_eq!(query(&inner, &n[4]), 7);
+        inner.add_new_edge(&n[0], &n[1], ());
+        assert_eq!(query(&inner, &n[4]), 7);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_observer_evicts_entries() {
+        let (mut graph, n) = create_graph();
+        let memo = SubgraphMemo::<Inner, _, u32>::new();
+        graph.add_observer(memo.observer());
+        let calls = Cell::new(0);
+        for center in n.iter() {
+            memo.memoize_on_subgraph(
+                graph.inner(),
+                center.clone(),
+                ego_net(&graph, center, 1),
+                |g, r| region_sum(g, r, &calls),
+            );
+        }
+        assert_eq!(memo.len(), 5);
+
+        // Removing the edge 3 - 4 affects the regions around 2, 3 and 4.
+        let eid = graph.edges_from_into(&n[3], &n[4]).next().unwrap();
+        graph.remove_edge(&eid);
+        assert_eq!(memo.len(), 2);
+
+        graph.remove_node(&n[0]);
+        assert_eq!(memo.len(), 0);
+    }
+
+    #[test]
+    fn test_invalidate_node_after_data_change() {
+        let (graph, n) = create_graph();
+        let mut inner = graph.into_inner();
+        let memo = SubgraphMemo::<Inner, _, _>::new();
+        let calls = Cell::new(0);
+        let region = [n[0].clone(), n[1].clone()];
+        memo.memoize_on_subgraph(&inner, "sum", region.clone(), |g, r| {
+            region_sum(g, r, &calls)
+        });
+        *inner.node_data_mut(&n[0]) = 10;
+        memo.invalidate_node(&n[0]);
+        assert_eq!(
+            memo.memoize_on_subgraph(&inner, "sum", region.clone(), |g, r| region_sum(
+                g, r, &calls
+            )),
+            11
+        );
+        assert_eq!(calls.get(), 2);
+    }
+}
@@ -0,0 +1,194 @@
+//! Normalization of edge weights.
+//!
+//! [`normalize_weights`] rescales a weight for every edge of a graph
+//! according to a [`Normalization`] scheme and returns the results in a map,
+//! leaving the graph unchanged.  [`normalize_weights_in_place`] writes the
+//! results back into the edge data instead, so that, for example, a transition
+//! matrix for a random walk can be prepared without copying the graph.
+
+use std::collections::HashMap;
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, prelude::*};
+
+/// A scheme for normalizing edge weights.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Rescales weights linearly so that the smallest is 0 and the largest is
+    /// 1.  If all weights are equal, they are all normalized to 0.
+    MinMax,
+    /// Subtracts the mean weight and divides by the standard deviation of the
+    /// weights.  If all weights are equal, they are all normalized to 0.
+    ZScore,
+    /// Divides the weight of each edge by the total weight of the edges from
+    /// the same node, so that the normalized weights of the edges from each
+    /// node sum to 1.  The edges from a node whose total weight is 0 are
+    /// normalized to 0.  Only meaningful for directed graphs.
+    RowStochastic,
+}
+
+/// Returns the normalized weight of every edge in the graph.
+///
+/// # Panics
+///
+/// Panics if `scheme` is [`Normalization::RowStochastic`] and the graph is
+/// undirected, since an undirected edge belongs to the rows of both of its
+/// ends.
+pub fn normalize_weights<G>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> f64,
+    scheme: Normalization,
+) -> HashMap<G::EdgeId, f64>
+where
+    G: Graph + ?Sized,
+{
+    let weights: FastHashMap<_, _> = graph
+        .edge_ids()
+        .map(|eid| {
+            let weight = weight_fn(&eid);
+            (eid, weight)
+        })
+        .collect();
+    let scale = |weights: FastHashMap<_, f64>, offset: f64, divisor: f64| {
+        weights
+            .into_iter()
+            .map(|(eid, weight)| {
+                let normalized = if divisor == 0.0 {
+                    0.0
+                } else {
+                    (weight - offset) / divisor
+                };
+                (eid, normalized)
+            })
+            .collect()
+    };
+    match scheme {
+        Normalization::MinMax => {
+            let min = weights.values().copied().fold(f64::INFINITY, f64::min);
+            let max = weights.values().copied().fold(f64::NEG_INFINITY, f64::max);
+            scale(weights, min, max - min)
+        }
+        Normalization::ZScore => {
+            let n = weights.len() as f64;
+            let mean = weights.values().sum::<f64>() / n;
+            let variance = weights
+                .values()
+                .map(|weight| (weight - mean) * (weight - mean))
+                .sum::<f64>()
+                / n;
+            scale(weights, mean, variance.sqrt())
+        }
+        Normalization::RowStochastic => {
+            assert!(
+                graph.is_directed(),
+                "row-stochastic normalization requires a directed graph"
+            );
+            let mut totals: FastHashMap<G::NodeId, f64> = FastHashMap::default();
+            for (eid, weight) in weights.iter() {
+                *totals
+                    .entry(graph.edge_ends(eid).left().clone())
+                    .or_default() += weight;
+            }
+            weights
+                .into_iter()
+                .map(|(eid, weight)| {
+                    let total = totals[graph.edge_ends(&eid).left()];
+                    let normalized = if total == 0.0 { 0.0 } else { weight / total };
+                    (eid, normalized)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Normalizes the weight `weight_fn` returns for the data of every edge in
+/// the graph, and stores the results with `set_weight`.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`normalize_weights`].
+pub fn normalize_weights_in_place<G>(
+    graph: &mut G,
+    weight_fn: impl Fn(&G::EdgeData) -> f64,
+    scheme: Normalization,
+    mut set_weight: impl FnMut(&mut G::EdgeData, f64),
+) where
+    G: GraphMut + ?Sized,
+{
+    let normalized = normalize_weights(graph, |eid| weight_fn(graph.edge_data(eid)), scheme);
+    for (eid, weight) in normalized {
+        set_weight(graph.edge_data_mut(&eid), weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_min_max_and_z_score() {
+        let mut graph = BagGraph::<(), f64, Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let e1 = graph.add_new_edge(&a, &b, 2.0);
+        let e2 = graph.add_new_edge(&a, &a, 4.0);
+        let e3 = graph.add_new_edge(&b, &b, 6.0);
+
+        let weights = graph.normalize_weights(|eid| *graph.edge_data(eid), Normalization::MinMax);
+        assert_eq!(weights[&e1], 0.0);
+        assert_eq!(weights[&e2], 0.5);
+        assert_eq!(weights[&e3], 1.0);
+
+        let weights = graph.normalize_weights(|eid| *graph.edge_data(eid), Normalization::ZScore);
+        let deviation = (8.0f64 / 3.0).sqrt();
+        assert_eq!(weights[&e1], -2.0 / deviation);
+        assert_eq!(weights[&e2], 0.0);
+        assert_eq!(weights[&e3], 2.0 / deviation);
+
+        let weights = graph.normalize_weights(|_| 1.0, Normalization::ZScore);
+        assert!(weights.values().all(|&weight| weight == 0.0));
+    }
+
+    #[test]
+    fn test_row_stochastic_in_place() {
+        let mut graph = LinkedGraph::<(), (u32, f64), Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        let ab = graph.add_new_edge(&a, &b, (1, 0.0));
+        let ac = graph.add_new_edge(&a, &c, (3, 0.0));
+        let ba = graph.add_new_edge(&b, &a, (5, 0.0));
+        let cc = graph.add_new_edge(&c, &c, (0, 0.0));
+
+        graph.normalize_weights_in_place(
+            |&(count, _)| count as f64,
+            Normalization::RowStochastic,
+            |data, weight| data.1 = weight,
+        );
+        assert_eq!(graph.edge_data(&ab).1, 0.25);
+        assert_eq!(graph.edge_data(&ac).1, 0.75);
+        assert_eq!(graph.edge_data(&ba).1, 1.0);
+        assert_eq!(graph.edge_data(&cc).1, 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "requires a directed graph")]
+    fn test_row_stochastic_undirected() {
+        let mut graph = BagGraph::<(), f64, Undirected>::default();
+        let a = graph.add_node(());
+        graph.add_new_edge(&a, &a, 1.0);
+        graph.normalize_weights(|eid| *graph.edge_data(eid), Normalization::RowStochastic);
+    }
+
+    #[test]
+    fn test_transform_edge_data() {
+        let mut graph = BagGraph::<(), i32, Directed>::default();
+        let a = graph.add_node(());
+        let e1 = graph.add_new_edge(&a, &a, 1);
+        let e2 = graph.add_new_edge(&a, &a, 2);
+        graph.transform_edge_data(|data| *data *= 10);
+        assert_eq!(*graph.edge_data(&e1), 10);
+        assert_eq!(*graph.edge_data(&e2), 20);
+    }
+}
@@ -0,0 +1,689 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
+
+use crate::{
+    end_pair::EndPair,
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A mutation of a [`NotifyingGraph`], as reported to a [`GraphObserver`].
+///
+/// Events are delivered after the mutation has been applied to the inner
+/// graph, so the IDs carried by `*Removed` events are no longer valid in the
+/// graph itself; the data of removed elements is passed along instead.
+pub enum GraphEvent<'a, G>
+where
+    G: Graph + ?Sized,
+{
+    /// A node was added.
+    NodeAdded {
+        id: &'a G::NodeId,
+        data: &'a G::NodeData,
+    },
+    /// A node was removed.  Removal of the node's incident edges is reported
+    /// first, as separate [`GraphEvent::EdgeRemoved`] events.
+    NodeRemoved {
+        id: &'a G::NodeId,
+        data: &'a G::NodeData,
+    },
+    /// An edge was added.
+    EdgeAdded {
+        id: &'a G::EdgeId,
+        ends: &'a <G::Directedness as Directedness>::EndPair<G::NodeId>,
+        data: &'a G::EdgeData,
+    },
+    /// An edge was removed.  When [`GraphMut::add_edge`] replaces an existing
+    /// edge, the old edge is reported as removed before the new one is
+    /// reported as added.
+    EdgeRemoved {
+        id: &'a G::EdgeId,
+        ends: &'a <G::Directedness as Directedness>::EndPair<G::NodeId>,
+        data: &'a G::EdgeData,
+    },
+    /// The graph was compacted.  The maps contain an entry for every node and
+    /// edge ID that was changed by the compaction.
+    Compacted {
+        node_map: &'a HashMap<G::NodeId, G::NodeId>,
+        edge_map: &'a HashMap<G::EdgeId, G::EdgeId>,
+    },
+}
+
+/// A callback that is notified of mutations made through a [`NotifyingGraph`].
+/// This trait is implemented for any `FnMut(&GraphEvent<G>)`.
+pub trait GraphObserver<G>
+where
+    G: Graph + ?Sized,
+{
+    /// Called after each mutation of the graph.
+    fn notify(&mut self, event: &GraphEvent<'_, G>);
+}
+
+impl<G, F> GraphObserver<G> for F
+where
+    G: Graph + ?Sized,
+    F: FnMut(&GraphEvent<'_, G>),
+{
+    fn notify(&mut self, event: &GraphEvent<'_, G>) {
+        self(event)
+    }
+}
+
+/// A policy for compacting a [`NotifyingGraph`] automatically as elements are
+/// removed from it.  See [`NotifyingGraph::set_compaction_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CompactOn {
+    /// Never compact automatically.
+    #[default]
+    Never,
+    /// Compact once the number of nodes and edges removed since the last
+    /// compaction reaches the given number.
+    Removals(usize),
+    /// Compact once the number of nodes and edges removed since the last
+    /// compaction reaches the given fraction of the number of nodes and
+    /// edges the graph held at that time plus those added since.
+    RemovalRatio(f64),
+}
+
+/// Identifies an observer registered with [`NotifyingGraph::add_observer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObserverId(usize);
+
+/// A wrapper around a graph which notifies registered observers whenever a
+/// node or edge is added or removed.  This is useful for keeping external
+/// indexes (e.g., a map from names to node IDs) in sync with the graph
+/// without having to update them at every call site.
+///
+/// The wrapper implements [`Graph`] and [`GraphMut`] by delegating to the
+/// inner graph, and uses the inner graph's node and edge IDs.  It can also
+/// compact the inner graph automatically according to a [`CompactOn`]
+/// policy.
+pub struct NotifyingGraph<G>
+where
+    G: Graph,
+{
+    inner: G,
+    observers: Vec<(ObserverId, Box<dyn GraphObserver<G>>)>,
+    next_observer_id: usize,
+    compaction_policy: CompactOn,
+    removals_since_compaction: usize,
+}
+
+impl<G> NotifyingGraph<G>
+where
+    G: Graph,
+{
+    /// Wraps an existing graph.  No events are reported for the nodes and
+    /// edges it already contains.
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            observers: Vec::new(),
+            next_observer_id: 0,
+            compaction_policy: CompactOn::Never,
+            removals_since_compaction: 0,
+        }
+    }
+
+    /// Registers an observer, returning an ID which can be used to remove it.
+    pub fn add_observer(&mut self, observer: impl GraphObserver<G> + 'static) -> ObserverId {
+        let id = ObserverId(self.next_observer_id);
+        self.next_observer_id += 1;
+        self.observers.push((id, Box::new(observer)));
+        id
+    }
+
+    /// Removes a previously registered observer.  Returns `true` if the
+    /// observer was found.
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        let len = self.observers.len();
+        self.observers.retain(|(oid, _)| *oid != id);
+        self.observers.len() != len
+    }
+
+    /// Sets the policy for compacting the graph automatically.  When the
+    /// policy's threshold is reached, the [`GraphMut`] method that removed
+    /// the last element, such as [`GraphMut::remove_node`] or
+    /// [`GraphMut::clear`], compacts the graph before returning, which
+    /// invalidates any node and edge IDs held outside the graph.  Observers
+    /// are sent a [`GraphEvent::Compacted`] event with the ID remapping.
+    pub fn set_compaction_policy(&mut self, policy: CompactOn) {
+        self.compaction_policy = policy;
+    }
+
+    /// Gets the current compaction policy.
+    pub fn compaction_policy(&self) -> CompactOn {
+        self.compaction_policy
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding all observers.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    fn emit(&mut self, event: GraphEvent<'_, G>) {
+        for (_, observer) in self.observers.iter_mut() {
+            observer.notify(&event);
+        }
+    }
+
+    /// Returns true if the compaction policy's threshold has been reached.
+    fn should_compact(&self) -> bool {
+        let removed = self.removals_since_compaction;
+        match self.compaction_policy {
+            CompactOn::Never => false,
+            CompactOn::Removals(limit) => removed >= limit.max(1),
+            CompactOn::RemovalRatio(ratio) => {
+                let live = self.inner.num_nodes() + self.inner.num_edges();
+                removed > 0 && removed as f64 >= ratio * (live + removed) as f64
+            }
+        }
+    }
+}
+
+impl<G> Default for NotifyingGraph<G>
+where
+    G: Graph + Default,
+{
+    fn default() -> Self {
+        Self::new(G::default())
+    }
+}
+
+impl<G> Debug for NotifyingGraph<G>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotifyingGraph")
+            .field("inner", &self.inner)
+            .field("num_observers", &self.observers.len())
+            .field("compaction_policy", &self.compaction_policy)
+            .finish()
+    }
+}
+
+impl<G> Graph for NotifyingGraph<G>
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from_into(from, into)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.inner.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.inner.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.inner.num_edges_from(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G> NotifyingGraph<G>
+where
+    G: GraphMut,
+{
+    fn remove_edge_uncompacted(&mut self, id: &G::EdgeId) -> G::EdgeData {
+        let ends = self.inner.edge_ends(id);
+        let data = self.inner.remove_edge(id);
+        self.removals_since_compaction += 1;
+        self.emit(GraphEvent::EdgeRemoved {
+            id,
+            ends: &ends,
+            data: &data,
+        });
+        data
+    }
+
+    fn remove_node_uncompacted(&mut self, id: &G::NodeId) -> G::NodeData {
+        // Remove the incident edges one at a time so that each removal can be
+        // reported along with the edge's data.
+        let mut incident = HashSet::new();
+        let edges = self
+            .inner
+            .edges_from(id)
+            .chain(self.inner.edges_into(id))
+            .filter(|eid| incident.insert(eid.clone()))
+            .collect::<Vec<_>>();
+        for eid in edges {
+            self.remove_edge_uncompacted(&eid);
+        }
+        let data = self.inner.remove_node(id);
+        self.removals_since_compaction += 1;
+        self.emit(GraphEvent::NodeRemoved { id, data: &data });
+        data
+    }
+
+    /// Compacts the graph if the compaction policy's threshold has been
+    /// reached.  Called only at the end of a public mutation, since
+    /// compacting invalidates any IDs the mutation is still using.
+    fn compact_if_needed(&mut self) {
+        if self.should_compact() {
+            self.compact(None, None);
+        }
+    }
+}
+
+impl<G> GraphMut for NotifyingGraph<G>
+where
+    G: GraphMut,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        self.inner.node_data_mut(id)
+    }
+
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        self.inner.edge_data_mut(id)
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        let id = self.inner.add_node(data);
+        let inner = &self.inner;
+        for (_, observer) in self.observers.iter_mut() {
+            observer.notify(&GraphEvent::NodeAdded {
+                id: &id,
+                data: inner.node_data(&id),
+            });
+        }
+        id
+    }
+
+    fn clear(&mut self) {
+        for nid in self.inner.node_ids().collect::<Vec<_>>() {
+            self.remove_node_uncompacted(&nid);
+        }
+        self.compact_if_needed();
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        let data = self.remove_node_uncompacted(id);
+        self.compact_if_needed();
+        data
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn remove_node_with_edges(
+        &mut self,
+        id: &Self::NodeId,
+    ) -> (
+        Self::NodeData,
+        Vec<(
+            <Self::Directedness as Directedness>::EndPair<Self::NodeId>,
+            Self::EdgeData,
+        )>,
+    ) {
+        let mut edges: Vec<_> = self.edges_from(id).chain(self.edges_into(id)).collect();
+        edges.sort();
+        edges.dedup();
+        let edges = edges
+            .into_iter()
+            .map(|eid| (self.edge_ends(&eid), self.remove_edge_uncompacted(&eid)))
+            .collect();
+        let data = self.remove_node_uncompacted(id);
+        self.compact_if_needed();
+        (data, edges)
+    }
+
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        let result = self.inner.add_edge(from, into, data);
+        let eid = result.edge_id();
+        let ends = self.inner.edge_ends(eid);
+        if let AddEdgeResult::Updated {
+            old_id, old_data, ..
+        } = &result
+        {
+            self.removals_since_compaction += 1;
+            self.emit(GraphEvent::EdgeRemoved {
+                id: old_id,
+                ends: &ends,
+                data: old_data,
+            });
+        }
+        let inner = &self.inner;
+        for (_, observer) in self.observers.iter_mut() {
+            observer.notify(&GraphEvent::EdgeAdded {
+                id: eid,
+                ends: &ends,
+                data: inner.edge_data(eid),
+            });
+        }
+        result
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        let data = self.remove_edge_uncompacted(id);
+        self.compact_if_needed();
+        data
+    }
+
+    fn reverse_edge(&mut self, id: &Self::EdgeId) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        if !self.is_directed() {
+            return AddEdgeResult::Added(id.clone());
+        }
+        let (from, into) = self.edge_ends(id).into_values();
+        let data = self.remove_edge_uncompacted(id);
+        let result = self.add_edge(&into, &from, data);
+        self.compact_if_needed();
+        result
+    }
+
+    fn remove_edges_from_into(&mut self, from: &Self::NodeId, into: &Self::NodeId) {
+        for eid in self.edges_from_into(from, into).collect::<Vec<_>>() {
+            self.remove_edge_uncompacted(&eid);
+        }
+        self.compact_if_needed();
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        let mut node_map = HashMap::new();
+        let mut edge_map = HashMap::new();
+        self.inner.compact(Some(&mut node_map), Some(&mut edge_map));
+        self.removals_since_compaction = 0;
+        // The collectors are sent every surviving ID, as by the inner graph,
+        // so they can tell unchanged IDs from removed ones; observers are
+        // only sent the IDs that changed.
+        if let Some(collector) = node_map_collector {
+            for (old, new) in node_map.iter() {
+                collector.insert(old.clone(), new.clone());
+            }
+        }
+        if let Some(collector) = edge_map_collector {
+            for (old, new) in edge_map.iter() {
+                collector.insert(old.clone(), new.clone());
+            }
+        }
+        node_map.retain(|old, new| old != new);
+        edge_map.retain(|old, new| old != new);
+        self.emit(GraphEvent::Compacted {
+            node_map: &node_map,
+            edge_map: &edge_map,
+        });
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+}
+
+impl<G> StableNodeIds for NotifyingGraph<G> where G: StableNodeIds {}
+
+impl<G> StableEdgeIds for NotifyingGraph<G> where G: StableEdgeIds {}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    type Inner = BagGraph<&'static str, i32, Directed>;
+
+    fn recorded(graph: &mut NotifyingGraph<Inner>) -> Rc<RefCell<Vec<String>>> {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let log2 = log.clone();
+        graph.add_observer(move |event: &GraphEvent<'_, Inner>| {
+            let entry = match event {
+                GraphEvent::NodeAdded { data, .. } => format!("+{}", data),
+                GraphEvent::NodeRemoved { data, .. } => format!("-{}", data),
+                GraphEvent::EdgeAdded { data, .. } => format!("+e{}", data),
+                GraphEvent::EdgeRemoved { data, .. } => format!("-e{}", data),
+                GraphEvent::Compacted { .. } => "compacted".to_string(),
+            };
+            log2.borrow_mut().push(entry);
+        });
+        log
+    }
+
+    #[test]
+    fn test_add_and_remove_events() {
+        let mut graph = NotifyingGraph::<Inner>::default();
+        let log = recorded(&mut graph);
+
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let e = graph.add_new_edge(&a, &b, 1);
+        graph.add_new_edge(&b, &a, 2);
+        assert_eq!(graph.remove_edge(&e), 1);
+        assert_eq!(graph.remove_node(&b), "b");
+
+        assert_eq!(
+            *log.borrow(),
+            vec!["+a", "+b", "+e1", "+e2", "-e1", "-e2", "-b"]
+        );
+        assert_eq!(graph.num_nodes(), 1);
+        assert_eq!(graph.num_edges(), 0);
+    }
+
+    #[test]
+    fn test_replaced_edge_events() {
+        let mut graph = NotifyingGraph::<LinkedGraph<(), i32, Undirected, SingleEdge>>::default();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let log2 = log.clone();
+        graph.add_observer(move |event: &GraphEvent<'_, _>| match event {
+            GraphEvent::EdgeAdded { data, .. } => log2.borrow_mut().push(format!("+{}", data)),
+            GraphEvent::EdgeRemoved { data, .. } => log2.borrow_mut().push(format!("-{}", data)),
+            _ => {}
+        });
+
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(&a, &b, 1);
+        graph.add_edge(&b, &a, 2);
+        assert_eq!(*log.borrow(), vec!["+1", "-1", "+2"]);
+    }
+
+    #[test]
+    fn test_self_loop_removed_once() {
+        let mut graph = NotifyingGraph::<Inner>::default();
+        let log = recorded(&mut graph);
+        let a = graph.add_node("a");
+        graph.add_new_edge(&a, &a, 7);
+        graph.clear();
+        assert_eq!(*log.borrow(), vec!["+a", "+e7", "-e7", "-a"]);
+    }
+
+    #[test]
+    fn test_remove_observer() {
+        let mut graph = NotifyingGraph::<Inner>::default();
+        let count = Rc::new(RefCell::new(0));
+        let count2 = count.clone();
+        let id = graph.add_observer(move |_: &GraphEvent<'_, Inner>| *count2.borrow_mut() += 1);
+        graph.add_node("a");
+        assert!(graph.remove_observer(id));
+        assert!(!graph.remove_observer(id));
+        graph.add_node("b");
+        assert_eq!(*count.borrow(), 1);
+    }
+
+    #[test]
+    fn test_compaction_event() {
+        let mut graph = NotifyingGraph::<Inner>::default();
+        let log = recorded(&mut graph);
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.remove_node(&a);
+        let mut node_map = HashMap::new();
+        graph.compact(Some(&mut node_map), None);
+        assert_eq!(log.borrow().last().unwrap(), "compacted");
+        assert_eq!(graph.node_data(&node_map[&b]), &"b");
+    }
+
+    #[test]
+    fn test_compact_reports_unchanged_ids() {
+        let mut graph = NotifyingGraph::<Inner>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.remove_node(&c);
+        let changed = Rc::new(RefCell::new(0));
+        let changed2 = changed.clone();
+        graph.add_observer(move |event: &GraphEvent<'_, Inner>| {
+            if let GraphEvent::Compacted { node_map, .. } = event {
+                *changed2.borrow_mut() = node_map.len();
+            }
+        });
+        let mut node_map = HashMap::new();
+        graph.compact(Some(&mut node_map), None);
+        assert_eq!(node_map.len(), 2);
+        assert_eq!(node_map[&a], a);
+        assert_eq!(node_map[&b], b);
+        assert!(!node_map.contains_key(&c));
+        assert_eq!(*changed.borrow(), 0);
+    }
+
+    #[test]
+    fn test_compact_on_removal_ratio() {
+        let mut graph = NotifyingGraph::<Inner>::default();
+        graph.set_compaction_policy(CompactOn::RemovalRatio(0.3));
+        let node_maps = Rc::new(RefCell::new(Vec::new()));
+        let node_maps2 = node_maps.clone();
+        graph.add_observer(move |event: &GraphEvent<'_, Inner>| {
+            if let GraphEvent::Compacted { node_map, .. } = event {
+                node_maps2.borrow_mut().push((*node_map).clone());
+            }
+        });
+
+        let mut ids: Vec<_> = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]
+            .into_iter()
+            .map(|data| graph.add_node(data))
+            .collect();
+        graph.remove_node(&ids[0]);
+        graph.remove_node(&ids[1]);
+        assert!(node_maps.borrow().is_empty());
+        graph.remove_node(&ids[2]);
+        assert_eq!(node_maps.borrow().len(), 1);
+
+        // Remap the surviving IDs as a remap listener would.
+        let node_map = &node_maps.borrow()[0];
+        for id in ids[3..].iter_mut() {
+            if let Some(new_id) = node_map.get(id) {
+                *id = new_id.clone();
+            }
+        }
+        let data: Vec<_> = ids[3..].iter().map(|id| *graph.node_data(id)).collect();
+        assert_eq!(data, vec!["d", "e", "f", "g", "h", "i", "j"]);
+    }
+
+    #[test]
+    fn test_compact_on_removals() {
+        let mut graph = NotifyingGraph::<Inner>::default();
+        let log = recorded(&mut graph);
+        graph.set_compaction_policy(CompactOn::Removals(2));
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.add_new_edge(&a, &b, 1);
+        graph.add_new_edge(&b, &b, 2);
+        // Removing `b` removes two edges and a node, but compacts only once,
+        // after the node is gone.
+        graph.remove_node(&b);
+        let compactions = log.borrow().iter().filter(|e| *e == "compacted").count();
+        assert_eq!(compactions, 1);
+        assert_eq!(log.borrow()[6..], ["-b", "compacted"]);
+
+        graph.set_compaction_policy(CompactOn::Never);
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.remove_node(&c);
+        graph.remove_node(&d);
+        assert_ne!(log.borrow().last().unwrap(), "compacted");
+    }
+}
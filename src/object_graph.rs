@@ -1,4 +1,11 @@
-use std::{fmt::Debug, marker::PhantomData, mem::transmute, ptr::NonNull};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::{BuildHasher, Hash, RandomState},
+    marker::PhantomData,
+    mem::transmute,
+    ptr::NonNull,
+};
 
 use derivative::Derivative;
 
@@ -43,9 +50,15 @@ impl<'a, N> From<&'a N> for NodeId<'a, N> {
 pub type EdgeId<'g, N> = (NodeId<'g, N>, NodeId<'g, N>);
 
 /// A graph representation for traversing object graphs using a user-provided neighbor function.
-pub struct ObjectGraph<'a, N, F> {
+///
+/// Nodes are normally identified by the address of the object.  Objects added
+/// with [`ObjectGraph::get_or_insert_node`] are also indexed by value, using
+/// a hash map with the hasher `S`, so that equal objects map to a single
+/// node.
+pub struct ObjectGraph<'a, N, F, S = RandomState> {
     neighbors_fn: F,
     roots: Vec<&'a N>,
+    index: HashMap<&'a N, NodeId<'a, N>, S>,
 }
 
 impl<'a, N: Debug, F> ObjectGraph<'a, N, F>
@@ -62,9 +75,54 @@ where
         Self {
             neighbors_fn,
             roots,
+            index: HashMap::default(),
+        }
+    }
+}
+
+impl<'a, N: Debug, F, S> ObjectGraph<'a, N, F, S>
+where
+    F: Fn(&'a N) -> Vec<&'a N>,
+{
+    /// Create a new ObjectGraph with no roots, which indexes objects using
+    /// the given hasher.  Nodes can be added with
+    /// [`ObjectGraph::get_or_insert_node`].
+    pub fn with_hasher(neighbors_fn: F, hasher: S) -> Self {
+        Self {
+            neighbors_fn,
+            roots: Vec::new(),
+            index: HashMap::with_hasher(hasher),
         }
     }
 
+    /// Gets the NodeId of an object equal to `node` which was added with
+    /// [`ObjectGraph::get_or_insert_node`], or adds `node` as a new root if
+    /// there is none.  This allows a deduplicated graph to be built from a
+    /// stream of objects without a separate map from keys to node IDs.
+    ///
+    /// Only objects added by this method are deduplicated; objects returned
+    /// by the neighbor function are still identified by address.
+    pub fn get_or_insert_node(&mut self, node: &'a N) -> NodeId<'a, N>
+    where
+        N: Hash + Eq,
+        S: BuildHasher,
+    {
+        *self.index.entry(node).or_insert_with(|| {
+            self.roots.push(node);
+            NodeId::from(node)
+        })
+    }
+
+    /// Gets the NodeId of an object equal to `node` which was added with
+    /// [`ObjectGraph::get_or_insert_node`].
+    pub fn find_node(&self, node: &N) -> Option<NodeId<'a, N>>
+    where
+        N: Hash + Eq,
+        S: BuildHasher,
+    {
+        self.index.get(node).copied()
+    }
+
     /// Get the NodeId of the root node.
     pub fn roots(&self) -> impl Iterator<Item = NodeId<'a, N>> {
         self.roots.iter().cloned().map(NodeId::from)
@@ -95,7 +153,7 @@ where
 
 impl<'a, N: Debug> GraphElementId for (NodeId<'a, N>, NodeId<'a, N>) {}
 
-impl<'d, N: Debug, F> Graph for ObjectGraph<'d, N, F>
+impl<'d, N: Debug, F, S> Graph for ObjectGraph<'d, N, F, S>
 where
     F: Fn(&'d N) -> Vec<&'d N>,
 {
@@ -185,6 +243,23 @@ mod tests {
         assert!(!graph.has_edge_from_into(&root_id, &second_neighbors[0]));
     }
 
+    #[test]
+    fn test_get_or_insert_node() {
+        // A stream of keys in which "a" appears twice.
+        let keys: Vec<String> = ["a", "b", "a"].into_iter().map(String::from).collect();
+
+        let mut graph = ObjectGraph::with_hasher(|_: &String| Vec::new(), RandomState::new());
+        let ids: Vec<_> = keys
+            .iter()
+            .map(|key| graph.get_or_insert_node(key))
+            .collect();
+        assert_eq!(ids[0], ids[2]);
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(graph.num_nodes(), 2);
+        assert_eq!(graph.find_node(&"a".to_string()), Some(ids[0]));
+        assert_eq!(graph.find_node(&"c".to_string()), None);
+    }
+
     #[cfg(feature = "pathfinding")]
     #[test]
     fn test_shortest_paths() {
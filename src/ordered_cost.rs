@@ -0,0 +1,228 @@
+//! Floating point costs for algorithms which need totally ordered costs.
+//!
+//! The shortest path and minimum spanning forest methods of
+//! [`Graph`](crate::Graph) require costs which implement [`Ord`], which
+//! `f32` and `f64` do not, because `NaN` is not comparable with anything.
+//! Wrapping each cost in an [`OrderedCost`] orders floats with
+//! [`f64::total_cmp`], so that they can be used anyway:
+//!
+//! ```
+//! # #[cfg(feature = "pathfinding")] {
+//! use graphitude::{LinkedGraph, ordered_cost::OrderedCost, prelude::*};
+//!
+//! let mut graph = LinkedGraph::<(), f64, Directed>::default();
+//! let a = graph.add_node(());
+//! let b = graph.add_node(());
+//! let c = graph.add_node(());
+//! graph.add_edge(&a, &b, 1.5);
+//! graph.add_edge(&b, &c, 0.25);
+//! graph.add_edge(&a, &c, 2.0);
+//! let paths = graph.shortest_paths(&a, |eid| OrderedCost(*graph.edge_data(eid)));
+//! assert_eq!(paths[&c].1, OrderedCost(1.75));
+//! # }
+//! ```
+//!
+//! Under the total order, `-0.0` is less than `0.0`, and `NaN` is greater
+//! than every other value, whatever its sign, so a `NaN` cost acts like an
+//! infinite one.
+//! Negative costs are ordered correctly, but algorithms such as Dijkstra's
+//! give wrong answers with them; see
+//! [`NonNegative`](crate::preconditions::NonNegative).
+
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    ops::Add,
+};
+
+/// A floating point type which can be totally ordered.  Implemented for
+/// `f32` and `f64`.
+pub trait TotalOrder: Copy {
+    /// Compares two values as [`f64::total_cmp`] does.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Returns true if the value is `NaN`.
+    fn is_nan(self) -> bool;
+
+    /// Returns the bits of the value, for hashing.
+    fn to_bits_u64(self) -> u64;
+}
+
+impl TotalOrder for f32 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+
+    fn to_bits_u64(self) -> u64 {
+        self.to_bits().into()
+    }
+}
+
+impl TotalOrder for f64 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+
+    fn to_bits_u64(self) -> u64 {
+        self.to_bits()
+    }
+}
+
+/// A floating point cost which is totally ordered, so it can be used where
+/// costs must implement [`Ord`].  See the [module documentation](self).
+///
+/// Two costs other than `NaN` are equal only if they have the same bits, so
+/// `0.0` and `-0.0` are different costs.  Every `NaN` is equal to every
+/// other, and greater than any other cost; [`f64::total_cmp`] alone would
+/// put a `NaN` with its sign bit set, as produced by `0.0 / 0.0` on some
+/// platforms, below every other value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrderedCost<F>(pub F);
+
+impl<F> OrderedCost<F> {
+    /// Gets the cost.
+    pub fn get(&self) -> &F {
+        &self.0
+    }
+
+    /// Unwraps the cost.
+    pub fn into_inner(self) -> F {
+        self.0
+    }
+}
+
+impl<F> From<F> for OrderedCost<F> {
+    fn from(cost: F) -> Self {
+        Self(cost)
+    }
+}
+
+impl<F> PartialEq for OrderedCost<F>
+where
+    F: TotalOrder,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F> Eq for OrderedCost<F> where F: TotalOrder {}
+
+impl<F> PartialOrd for OrderedCost<F>
+where
+    F: TotalOrder,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F> Ord for OrderedCost<F>
+where
+    F: TotalOrder,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (false, false) => self.0.total_cmp(&other.0),
+            (self_nan, other_nan) => self_nan.cmp(&other_nan),
+        }
+    }
+}
+
+impl<F> Hash for OrderedCost<F>
+where
+    F: TotalOrder,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Every `NaN` is equal, so they must all hash alike.
+        let bits = if self.0.is_nan() {
+            u64::MAX
+        } else {
+            self.0.to_bits_u64()
+        };
+        bits.hash(state);
+    }
+}
+
+impl<F> Add for OrderedCost<F>
+where
+    F: Add<Output = F>,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LinkedGraph, preconditions::NonNegative, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_total_order() {
+        let mut costs = [2.5, f64::NAN, -1.0, 0.0, -0.0, f64::INFINITY].map(OrderedCost);
+        costs.sort();
+        let sorted = costs.map(OrderedCost::into_inner);
+        assert_eq!(sorted[..5], [-1.0, -0.0, 0.0, 2.5, f64::INFINITY]);
+        assert!(sorted[0].is_sign_negative() && sorted[1].is_sign_negative());
+        assert!(sorted[5].is_nan());
+        assert_eq!(OrderedCost(f32::NAN), OrderedCost(f32::NAN));
+        assert_ne!(OrderedCost(0.0), OrderedCost(-0.0));
+        assert_eq!(OrderedCost(1.0) + OrderedCost(0.5), OrderedCost(1.5));
+    }
+
+    #[test]
+    fn test_negative_nan() {
+        let negative_nan = -f64::NAN;
+        assert!(negative_nan.is_sign_negative());
+        assert!(OrderedCost(negative_nan) > OrderedCost(f64::INFINITY));
+        assert!(OrderedCost(negative_nan) > OrderedCost(f64::NEG_INFINITY));
+        assert_eq!(OrderedCost(negative_nan), OrderedCost(f64::NAN));
+        let mut costs = [-f64::NAN, 1.0, f64::NEG_INFINITY].map(OrderedCost);
+        costs.sort();
+        assert_eq!(costs[0], OrderedCost(f64::NEG_INFINITY));
+        assert!(costs[2].into_inner().is_nan());
+    }
+
+    #[cfg(feature = "pathfinding")]
+    #[test]
+    fn test_negative_nan_shortest_paths() {
+        let mut graph = LinkedGraph::<(), f64, Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_edge(&a, &b, -f64::NAN);
+        graph.add_edge(&b, &c, 1.0);
+        graph.add_edge(&a, &c, 2.0);
+        let paths = graph.shortest_paths(&a, |eid| OrderedCost(*graph.edge_data(eid)));
+        assert_eq!(paths[&c].1, OrderedCost(2.0));
+    }
+
+    #[test]
+    fn test_minimum_spanning_forest() {
+        let mut graph = LinkedGraph::<(), f64, Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_edge(&a, &b, 0.5);
+        let bc = graph.add_new_edge(&b, &c, 0.75);
+        graph.add_edge(&a, &c, 1.0);
+        let forest = graph.minimum_spanning_forest(|eid| {
+            NonNegative::new(OrderedCost(*graph.edge_data(eid))).unwrap()
+        });
+        assert_eq!(forest.edges.len(), 2);
+        assert!(forest.edges.contains(&bc));
+        assert_eq!(forest.total_weight.into_inner(), OrderedCost(1.25));
+    }
+}
@@ -0,0 +1,359 @@
+//! Multi-objective shortest paths.
+//!
+//! When edges have several independent costs (e.g., travel time, tolls and
+//! distance), there is usually no single shortest path.  Instead,
+//! [`pareto_shortest_paths`] finds the *Pareto frontier*: every path whose
+//! cost vector is not dominated by that of another path, where one vector
+//! dominates another if it is no worse in every component and better in at
+//! least one.
+//!
+//! The search is a label-setting algorithm (Martins' algorithm).  Each node
+//! keeps the set of nondominated cost vectors with which it has been reached,
+//! and labels are expanded in lexicographic order of their costs, so the
+//! first path found is also the lexicographically shortest one.  The frontier
+//! can be exponentially large in the worst case.
+//!
+//! [`constrained_pareto_shortest_paths`] and [`constrained_shortest_path`]
+//! only consider paths whose costs satisfy a constraint, such as a limit on
+//! one of the costs.  The constraint also prunes the search, so a tight
+//! limit keeps the frontier small.
+
+use std::{cmp::Ordering, collections::BinaryHeap, ops::Add};
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, path::Path, prelude::*};
+
+/// Returns true if `a` dominates or is equal to `b`.
+fn dominates_or_equals<C: PartialOrd, const N: usize>(a: &[C; N], b: &[C; N]) -> bool {
+    a.iter().zip(b.iter()).all(|(x, y)| x <= y)
+}
+
+/// Compares cost vectors lexicographically, treating incomparable
+/// components (e.g., NaN) as equal.
+fn lexicographic_cmp<C: PartialOrd, const N: usize>(a: &[C; N], b: &[C; N]) -> Ordering {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| x.partial_cmp(y).unwrap_or(Ordering::Equal))
+        .find(|ord| ord.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn add_costs<C, const N: usize>(a: &[C; N], b: &[C; N]) -> [C; N]
+where
+    C: Copy + Add<Output = C>,
+{
+    std::array::from_fn(|i| a[i] + b[i])
+}
+
+/// A partial path, identified by its index in the list of labels.
+struct Label<G, C, const N: usize>
+where
+    G: Graph + ?Sized,
+{
+    node: G::NodeId,
+    costs: [C; N],
+    parent: Option<(usize, G::EdgeId)>,
+}
+
+/// An entry in the queue of labels, ordered so that `BinaryHeap` pops the
+/// lexicographically smallest cost vector first.
+struct QueueEntry<C, const N: usize> {
+    costs: [C; N],
+    label: usize,
+}
+
+impl<C: PartialOrd, const N: usize> PartialEq for QueueEntry<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: PartialOrd, const N: usize> Eq for QueueEntry<C, N> {}
+
+impl<C: PartialOrd, const N: usize> PartialOrd for QueueEntry<C, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: PartialOrd, const N: usize> Ord for QueueEntry<C, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        lexicographic_cmp(&other.costs, &self.costs).then(other.label.cmp(&self.label))
+    }
+}
+
+/// Finds the Pareto frontier of paths from `start` to `goal`.  See
+/// [`Graph::pareto_shortest_paths`].
+pub fn pareto_shortest_paths<'g, G, C, const N: usize>(
+    graph: &'g G,
+    start: &G::NodeId,
+    goal: &G::NodeId,
+    costs_fn: impl Fn(&G::EdgeId) -> [C; N],
+) -> Vec<(Path<'g, G>, [C; N])>
+where
+    G: Graph + ?Sized,
+    C: Default + PartialOrd + Copy + Add<Output = C>,
+{
+    search(graph, start, goal, costs_fn, |_| true, false)
+}
+
+/// Finds the Pareto frontier of paths from `start` to `goal` among the paths
+/// whose costs satisfy `feasible`, such as a limit on one of the costs.
+/// See [`Graph::constrained_pareto_shortest_paths`].
+///
+/// `feasible` must be monotone: if it rejects a cost vector, it must also
+/// reject every vector which is no better in any component.  Partial paths
+/// it rejects are discarded as soon as they are found.
+pub fn constrained_pareto_shortest_paths<'g, G, C, const N: usize>(
+    graph: &'g G,
+    start: &G::NodeId,
+    goal: &G::NodeId,
+    costs_fn: impl Fn(&G::EdgeId) -> [C; N],
+    feasible: impl Fn(&[C; N]) -> bool,
+) -> Vec<(Path<'g, G>, [C; N])>
+where
+    G: Graph + ?Sized,
+    C: Default + PartialOrd + Copy + Add<Output = C>,
+{
+    search(graph, start, goal, costs_fn, feasible, false)
+}
+
+/// Finds the lexicographically shortest path from `start` to `goal` among
+/// the paths whose costs satisfy `feasible`, e.g. the fastest path within a
+/// budget when the first cost is time.  See
+/// [`Graph::constrained_shortest_path`].
+///
+/// `feasible` must be monotone, as for
+/// [`constrained_pareto_shortest_paths`].  The search stops as soon as the
+/// path is found.
+pub fn constrained_shortest_path<'g, G, C, const N: usize>(
+    graph: &'g G,
+    start: &G::NodeId,
+    goal: &G::NodeId,
+    costs_fn: impl Fn(&G::EdgeId) -> [C; N],
+    feasible: impl Fn(&[C; N]) -> bool,
+) -> Option<(Path<'g, G>, [C; N])>
+where
+    G: Graph + ?Sized,
+    C: Default + PartialOrd + Copy + Add<Output = C>,
+{
+    search(graph, start, goal, costs_fn, feasible, true)
+        .into_iter()
+        .next()
+}
+
+/// Runs Martins' algorithm, discarding labels whose costs are not
+/// `feasible`.  If `first_only` is true, stops as soon as the goal is
+/// reached.
+fn search<'g, G, C, const N: usize>(
+    graph: &'g G,
+    start: &G::NodeId,
+    goal: &G::NodeId,
+    costs_fn: impl Fn(&G::EdgeId) -> [C; N],
+    feasible: impl Fn(&[C; N]) -> bool,
+    first_only: bool,
+) -> Vec<(Path<'g, G>, [C; N])>
+where
+    G: Graph + ?Sized,
+    C: Default + PartialOrd + Copy + Add<Output = C>,
+{
+    if !feasible(&[C::default(); N]) {
+        return Vec::new();
+    }
+    let mut labels: Vec<Label<G, C, N>> = vec![Label {
+        node: start.clone(),
+        costs: [C::default(); N],
+        parent: None,
+    }];
+    let mut queue = BinaryHeap::from([QueueEntry {
+        costs: [C::default(); N],
+        label: 0,
+    }]);
+    // The permanent labels of each node, which are mutually nondominated.
+    let mut settled: FastHashMap<G::NodeId, Vec<usize>> = FastHashMap::default();
+
+    let is_dominated = |settled: &FastHashMap<G::NodeId, Vec<usize>>,
+                        labels: &[Label<G, C, N>],
+                        node: &G::NodeId,
+                        costs: &[C; N]| {
+        [node, goal].into_iter().any(|nid| {
+            settled.get(nid).is_some_and(|indices| {
+                indices
+                    .iter()
+                    .any(|&i| dominates_or_equals(&labels[i].costs, costs))
+            })
+        })
+    };
+
+    while let Some(QueueEntry { costs, label }) = queue.pop() {
+        let node = labels[label].node.clone();
+        if is_dominated(&settled, &labels, &node, &costs) {
+            continue;
+        }
+        settled.entry(node.clone()).or_default().push(label);
+        if &node == goal {
+            if first_only {
+                break;
+            }
+            continue;
+        }
+
+        for eid in graph.edges_from(&node) {
+            let ends = graph.edge_ends(&eid);
+            let next = ends.other_value(&node).into_inner().clone();
+            let next_costs = add_costs(&costs, &costs_fn(&eid));
+            if !feasible(&next_costs) || is_dominated(&settled, &labels, &next, &next_costs) {
+                continue;
+            }
+            labels.push(Label {
+                node: next,
+                costs: next_costs,
+                parent: Some((label, eid)),
+            });
+            queue.push(QueueEntry {
+                costs: next_costs,
+                label: labels.len() - 1,
+            });
+        }
+    }
+
+    settled
+        .remove(goal)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|label| {
+            let mut edges = Vec::new();
+            let mut current = label;
+            while let Some((parent, eid)) = &labels[current].parent {
+                edges.push(eid.clone());
+                current = *parent;
+            }
+            edges.reverse();
+            (
+                Path::from_edges(graph, start.clone(), edges),
+                labels[label].costs,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_frontier_of_tradeoffs() {
+        // Three routes from `a` to `d`, costing (time, toll): a fast toll
+        // road, a slow free road, and a dominated detour.
+        let mut graph = BagGraph::<&str, [u32; 2], Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.add_new_edge(&a, &b, [1, 5]);
+        graph.add_new_edge(&b, &d, [1, 5]);
+        graph.add_new_edge(&a, &c, [5, 0]);
+        graph.add_new_edge(&c, &d, [5, 0]);
+        graph.add_new_edge(&a, &d, [11, 1]);
+        graph.add_new_edge(&b, &c, [1, 0]);
+
+        let frontier = graph.pareto_shortest_paths(&a, &d, |eid| *graph.edge_data(eid));
+        let summary: Vec<_> = frontier
+            .iter()
+            .map(|(path, costs)| {
+                let nodes: Vec<_> = path.nodes().map(|nid| *graph.node_data(&nid)).collect();
+                (nodes, *costs)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (vec!["a", "b", "d"], [2, 10]),
+                (vec!["a", "b", "c", "d"], [7, 5]),
+                (vec!["a", "c", "d"], [10, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constrained() {
+        // The same routes as above, costing (time, toll).
+        let mut graph = BagGraph::<&str, [u32; 2], Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.add_new_edge(&a, &b, [1, 5]);
+        graph.add_new_edge(&b, &d, [1, 5]);
+        graph.add_new_edge(&a, &c, [5, 0]);
+        graph.add_new_edge(&c, &d, [5, 0]);
+        graph.add_new_edge(&a, &d, [11, 1]);
+        graph.add_new_edge(&b, &c, [1, 0]);
+        let costs = |eid: &_| *graph.edge_data(eid);
+        let names = |path: &Path<'_, _>| -> Vec<_> {
+            path.nodes().map(|nid| *graph.node_data(&nid)).collect()
+        };
+
+        let (path, cost) = graph
+            .constrained_shortest_path(&a, &d, costs, |&[_, toll]| toll <= 5)
+            .unwrap();
+        assert_eq!((names(&path), cost), (vec!["a", "b", "c", "d"], [7, 5]));
+
+        let frontier =
+            graph.constrained_pareto_shortest_paths(&a, &d, costs, |&[time, _]| time <= 10);
+        let costs_found: Vec<_> = frontier.iter().map(|(_, cost)| *cost).collect();
+        assert_eq!(costs_found, vec![[2, 10], [7, 5], [10, 0]]);
+
+        assert!(
+            graph
+                .constrained_shortest_path(&a, &d, costs, |&[time, toll]| time < 5 && toll < 5)
+                .is_none()
+        );
+        assert!(
+            graph
+                .constrained_pareto_shortest_paths(&a, &a, costs, |_| false)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_single_criterion_matches_dijkstra() {
+        let mut graph = LinkedGraph::<(), u32, Undirected>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], 4);
+        graph.add_new_edge(&n[0], &n[2], 1);
+        graph.add_new_edge(&n[2], &n[1], 2);
+        graph.add_new_edge(&n[1], &n[3], 1);
+
+        let frontier = pareto_shortest_paths(&graph, &n[0], &n[3], |eid| [*graph.edge_data(eid)]);
+        assert_eq!(frontier.len(), 1);
+        let (path, costs) = &frontier[0];
+        assert_eq!(*costs, [4]);
+        assert_eq!(
+            costs[0],
+            graph.shortest_paths(&n[0], |eid| *graph.edge_data(eid))[&n[3]].1
+        );
+        assert_eq!(path.last_node(), n[3]);
+    }
+
+    #[test]
+    fn test_float_costs_and_unreachable_goal() {
+        let mut graph = BagGraph::<(), [f64; 3], Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, [1.0, 2.0, 3.0]);
+        graph.add_new_edge(&a, &b, [1.0, 2.0, 3.0]);
+        graph.add_new_edge(&a, &b, [3.0, 2.0, 1.0]);
+
+        // Equal cost vectors are reported once.
+        let frontier = graph.pareto_shortest_paths(&a, &b, |eid| *graph.edge_data(eid));
+        assert_eq!(frontier.len(), 2);
+        assert!(graph.pareto_shortest_paths(&a, &c, |_| [1.0; 3]).is_empty());
+
+        let trivial = graph.pareto_shortest_paths(&a, &a, |_| [1.0; 3]);
+        assert_eq!(trivial.len(), 1);
+        assert_eq!(trivial[0].1, [0.0; 3]);
+    }
+}
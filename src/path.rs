@@ -1,4 +1,4 @@
-use std::iter::once;
+use std::{collections::HashMap, hash::BuildHasher, iter::once};
 
 use derivative::Derivative;
 
@@ -21,6 +21,28 @@ pub struct Path<'g, G: Graph + ?Sized> {
     nodes: Vec<G::NodeId>,
 }
 
+/// A value in a predecessor map passed to [`Path::from_predecessors`].
+/// Maps built by searches hold an edge and the node it came from, and maps
+/// such as [`ShortestPathTree::parents`](crate::shortest_path_tree::ShortestPathTree::parents)
+/// hold `None` for the node a search started from.
+pub trait Predecessor<E, N> {
+    /// Returns the edge by which a node was reached and the node it came
+    /// from, if any.
+    fn predecessor(&self) -> Option<(&E, &N)>;
+}
+
+impl<E, N> Predecessor<E, N> for (E, N) {
+    fn predecessor(&self) -> Option<(&E, &N)> {
+        Some((&self.0, &self.1))
+    }
+}
+
+impl<E, N> Predecessor<E, N> for Option<(E, N)> {
+    fn predecessor(&self) -> Option<(&E, &N)> {
+        self.as_ref().map(|(eid, nid)| (eid, nid))
+    }
+}
+
 impl<'g, G: Graph + ?Sized> Path<'g, G> {
     /// Creates a new path starting at the given node.
     pub fn new(graph: &'g G, start: G::NodeId) -> Self {
@@ -44,6 +66,35 @@ impl<'g, G: Graph + ?Sized> Path<'g, G> {
         path
     }
 
+    /// Creates the path to `target` recorded in a map from each node to the
+    /// edge by which a search reached it and the node that edge came from,
+    /// as built by most search algorithms.  The path starts at the first node
+    /// found with no predecessor in the map, so it consists of just `target`
+    /// if `target` has none.  Panics if following the map from `target`
+    /// revisits a node.
+    pub fn from_predecessors<P, S>(
+        graph: &'g G,
+        predecessors: &HashMap<G::NodeId, P, S>,
+        target: &G::NodeId,
+    ) -> Self
+    where
+        P: Predecessor<G::EdgeId, G::NodeId>,
+        S: BuildHasher,
+    {
+        let mut edges = Vec::new();
+        let mut current = target;
+        while let Some((eid, parent)) = predecessors.get(current).and_then(P::predecessor) {
+            assert!(
+                edges.len() < predecessors.len(),
+                "predecessor map contains a cycle"
+            );
+            edges.push(eid.clone());
+            current = parent;
+        }
+        edges.reverse();
+        Self::from_edges(graph, current.clone(), edges)
+    }
+
     /// Returns the first node in the path.
     pub fn first_node(&self) -> G::NodeId {
         self.nodes.first().expect("Path has no nodes").clone()
@@ -148,7 +199,7 @@ mod tests {
     macro_rules! tests {
         ($mod: ident,$type: ty) => {
             mod $mod {
-                use crate::adjacency_graph::AdjacencyGraph;
+                use crate::{adjacency_graph::AdjacencyGraph, path::Path};
                 use $crate::prelude::*;
 
                 #[test]
@@ -165,7 +216,7 @@ mod tests {
                     let mut graph = <$type>::default();
                     let n1 = graph.add_node("n1");
                     let n2 = graph.add_node("n2");
-                    let e1 = graph.add_edge(&n1, &n2, "e1").0;
+                    let e1 = graph.add_edge(&n1, &n2, "e1").into_edge_id();
 
                     let mut path = graph.new_path(&n1);
                     path.add_edge(e1);
@@ -181,8 +232,8 @@ mod tests {
                     let n1 = graph.add_node("n1");
                     let n2 = graph.add_node("n2");
                     let n3 = graph.add_node("n3");
-                    let e1 = graph.add_edge(&n1, &n2, "e1").0;
-                    let e2 = graph.add_edge(&n2, &n3, "e2").0;
+                    let e1 = graph.add_edge(&n1, &n2, "e1").into_edge_id();
+                    let e2 = graph.add_edge(&n2, &n3, "e2").into_edge_id();
                     let mut path = graph.new_path(&n1);
                     path.add_edge(e1.clone());
                     path.add_edge(e2.clone());
@@ -196,14 +247,37 @@ mod tests {
                     assert_eq!(iter.next(), None);
                 }
 
+                #[test]
+                fn test_from_predecessors() {
+                    let mut graph = <$type>::default();
+                    let n1 = graph.add_node("n1");
+                    let n2 = graph.add_node("n2");
+                    let n3 = graph.add_node("n3");
+                    let e1 = graph.add_edge(&n1, &n2, "e12").into_edge_id();
+                    let e2 = graph.add_edge(&n2, &n3, "e23").into_edge_id();
+                    let predecessors = std::collections::HashMap::from([
+                        (n2.clone(), (e1.clone(), n1.clone())),
+                        (n3.clone(), (e2.clone(), n2.clone())),
+                    ]);
+
+                    let path = Path::from_predecessors(&graph, &predecessors, &n3);
+                    assert_eq!(path.first_node(), n1);
+                    assert_eq!(path.edges().collect::<Vec<_>>(), vec![e1, e2]);
+                    assert_eq!(path.nodes().collect::<Vec<_>>(), vec![n1.clone(), n2, n3]);
+
+                    let path = Path::from_predecessors(&graph, &predecessors, &n1);
+                    assert_eq!(path.nodes().collect::<Vec<_>>(), vec![n1]);
+                    assert_eq!(path.edges().count(), 0);
+                }
+
                 #[test]
                 fn test_extend_with() {
                     let mut graph = <$type>::default();
                     let n1 = graph.add_node("n1");
                     let n2 = graph.add_node("n2");
                     let n3 = graph.add_node("n3");
-                    let e1 = graph.add_edge(&n1, &n2, "e12").0;
-                    let e2 = graph.add_edge(&n2, &n3, "e23").0;
+                    let e1 = graph.add_edge(&n1, &n2, "e12").into_edge_id();
+                    let e2 = graph.add_edge(&n2, &n3, "e23").into_edge_id();
 
                     let mut path1 = graph.new_path(&n1);
                     path1.add_edge(e1);
@@ -223,8 +297,8 @@ mod tests {
                     let n1 = graph.add_node("n1");
                     let n2 = graph.add_node("n2");
                     let n3 = graph.add_node("n3");
-                    let e1 = graph.add_edge(&n1, &n2, "e12").0;
-                    let e2 = graph.add_edge(&n2, &n3, "e23").0;
+                    let e1 = graph.add_edge(&n1, &n2, "e12").into_edge_id();
+                    let e2 = graph.add_edge(&n2, &n3, "e23").into_edge_id();
 
                     let mut path = graph.new_path(&n1);
                     path.extend(vec![e1, e2]);
@@ -239,9 +313,9 @@ mod tests {
                     let n1 = graph.add_node("n1");
                     let n2 = graph.add_node("n2");
                     let n3 = graph.add_node("n3");
-                    let e1 = graph.add_edge(&n1, &n2, "e12").0;
-                    let e2 = graph.add_edge(&n2, &n3, "e23").0;
-                    let e3 = graph.add_edge(&n3, &n1, "e31").0;
+                    let e1 = graph.add_edge(&n1, &n2, "e12").into_edge_id();
+                    let e2 = graph.add_edge(&n2, &n3, "e23").into_edge_id();
+                    let e3 = graph.add_edge(&n3, &n1, "e31").into_edge_id();
 
                     let mut path = graph.new_path(&n1);
                     path.extend(vec![e1, e2, e3]);
@@ -0,0 +1,298 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+    sync::Arc,
+};
+
+use crate::slab_graph::slab::{SlabKey, SlabSlots, SlabSlotsMut, SlabStorage};
+
+/// The number of slots in each chunk.  Changing a value in a shared chunk
+/// copies the whole chunk, so smaller chunks make changes after a snapshot
+/// cheaper, and larger chunks make snapshots cheaper.
+const CHUNK_SIZE: usize = 64;
+
+/// Storage in [`ChunkedSlab`]s, used by
+/// [`PersistentGraph`](super::PersistentGraph).
+#[derive(Debug)]
+pub struct ChunkedSlabs;
+
+impl SlabStorage for ChunkedSlabs {
+    type Slab<T> = ChunkedSlab<T>;
+
+    const GRAPH_NAME: &'static str = "PersistentGraph";
+}
+
+#[derive(Clone)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+type Chunk<T> = Arc<Vec<Slot<T>>>;
+
+/// A slab with generational keys, like the one used by
+/// [`SlabGraph`](crate::SlabGraph), whose slots are stored in reference
+/// counted chunks.  Cloning a `ChunkedSlab` copies only the pointers to its
+/// chunks, and a chunk shared with a clone is copied the first time one of
+/// its values is changed, so each clone behaves as an independent slab.
+pub struct ChunkedSlab<T> {
+    chunks: Vec<Chunk<T>>,
+    /// Indices of empty slots, in the order they will be reused.
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> ChunkedSlab<T> {
+    /// Creates a new, empty slab.
+    pub fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn slot(&self, key: SlabKey) -> Option<&Slot<T>> {
+        let index = key.index() as usize;
+        self.chunks
+            .get(index / CHUNK_SIZE)?
+            .get(index % CHUNK_SIZE)
+            .filter(|slot| slot.generation == key.generation())
+    }
+
+    /// Returns the keys and values in the slab, in order of their slots.
+    pub fn pairs(&self) -> impl Iterator<Item = (SlabKey, &T)> {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let key = SlabKey::new(index as u32, slot.generation);
+                slot.value.as_ref().map(|value| (key, value))
+            })
+    }
+
+    /// Returns true if the slot of `key` is stored in the same chunk as in
+    /// `other`, so that neither slab has changed it since they were cloned.
+    #[cfg(test)]
+    pub fn shares_chunk_with(&self, other: &Self, key: SlabKey) -> bool {
+        let chunk_index = key.index() as usize / CHUNK_SIZE;
+        match (self.chunks.get(chunk_index), other.chunks.get(chunk_index)) {
+            (Some(chunk), Some(other_chunk)) => Arc::ptr_eq(chunk, other_chunk),
+            _ => false,
+        }
+    }
+}
+
+impl<T: Clone> ChunkedSlab<T> {
+    /// Gets the slot with the given index for writing, first copying its
+    /// chunk if it is shared.
+    fn slot_mut(&mut self, index: u32) -> Option<&mut Slot<T>> {
+        let index = index as usize;
+        let chunk = self.chunks.get_mut(index / CHUNK_SIZE)?;
+        Arc::make_mut(chunk).get_mut(index % CHUNK_SIZE)
+    }
+
+    /// Returns a mutable reference to the value for a key, or `None` if the
+    /// key is invalid or its value has been removed.
+    pub fn get_mut(&mut self, key: SlabKey) -> Option<&mut T> {
+        // Check the key first, so that an invalid key never copies a chunk.
+        self.get(key)?;
+        self.slot_mut(key.index())?.value.as_mut()
+    }
+}
+
+impl<T> SlabSlots<T> for ChunkedSlab<T> {
+    fn get(&self, key: SlabKey) -> Option<&T> {
+        self.slot(key)?.value.as_ref()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn keys(&self) -> impl Iterator<Item = SlabKey> + '_ {
+        self.pairs().map(|(key, _)| key)
+    }
+}
+
+/// Changing a value in a chunk shared with a clone copies the chunk first.
+impl<T: Clone> SlabSlotsMut<T> for ChunkedSlab<T> {
+    fn insert(&mut self, value: T) -> SlabKey {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = self.slot_mut(index).expect("free slot must exist");
+            slot.value = Some(value);
+            return SlabKey::new(index, slot.generation);
+        }
+        if self
+            .chunks
+            .last()
+            .is_none_or(|chunk| chunk.len() == CHUNK_SIZE)
+        {
+            self.chunks.push(Arc::new(Vec::with_capacity(CHUNK_SIZE)));
+        }
+        let index = (self.chunks.len() - 1) * CHUNK_SIZE + self.chunks.last().unwrap().len();
+        let index = u32::try_from(index).expect("too many slots in slab");
+        Arc::make_mut(self.chunks.last_mut().unwrap()).push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+        SlabKey::new(index, 0)
+    }
+
+    fn remove(&mut self, key: SlabKey) -> Option<T> {
+        self.get(key)?;
+        let slot = self.slot_mut(key.index())?;
+        let value = slot.value.take()?;
+        // A slot whose generation is exhausted is retired rather than risk
+        // reusing an old key.
+        let reusable = match slot.generation.checked_add(1) {
+            Some(generation) => {
+                slot.generation = generation;
+                true
+            }
+            None => false,
+        };
+        if reusable {
+            self.free.push(key.index());
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Chunks shared with clones are replaced rather than copied.
+    fn clear(&mut self) {
+        for (chunk_index, chunk) in self.chunks.iter_mut().enumerate() {
+            let mut cleared = Vec::with_capacity(CHUNK_SIZE);
+            for (offset, slot) in chunk.iter().enumerate() {
+                let mut generation = slot.generation;
+                // A slot whose generation is exhausted is retired rather
+                // than risk reusing an old key.
+                if slot.value.is_some()
+                    && let Some(next) = generation.checked_add(1)
+                {
+                    generation = next;
+                    self.free.push((chunk_index * CHUNK_SIZE + offset) as u32);
+                }
+                cleared.push(Slot {
+                    generation,
+                    value: None,
+                });
+            }
+            *chunk = Arc::new(cleared);
+        }
+        self.len = 0;
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.chunks.reserve(
+            additional
+                .saturating_sub(self.free.len())
+                .div_ceil(CHUNK_SIZE),
+        );
+    }
+
+    fn reserve_exact(&mut self, additional: usize) {
+        self.chunks.reserve_exact(
+            additional
+                .saturating_sub(self.free.len())
+                .div_ceil(CHUNK_SIZE),
+        );
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.chunks.shrink_to_fit();
+        self.free.shrink_to_fit();
+    }
+}
+
+impl<T> Clone for ChunkedSlab<T> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+            free: self.free.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for ChunkedSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for ChunkedSlab<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.pairs()).finish()
+    }
+}
+
+impl<T> Index<SlabKey> for ChunkedSlab<T> {
+    type Output = T;
+
+    fn index(&self, key: SlabKey) -> &Self::Output {
+        self.get(key).expect("invalid SlabKey")
+    }
+}
+
+impl<T: Clone> IndexMut<SlabKey> for ChunkedSlab<T> {
+    fn index_mut(&mut self, key: SlabKey) -> &mut Self::Output {
+        self.get_mut(key).expect("invalid SlabKey")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_chunks() {
+        let mut slab = ChunkedSlab::new();
+        let keys: Vec<_> = (0..200).map(|i| slab.insert(i)).collect();
+        let snapshot = slab.clone();
+        assert!(slab.shares_chunk_with(&snapshot, keys[0]));
+        assert!(slab.shares_chunk_with(&snapshot, keys[199]));
+
+        // Only the chunk of the changed value is copied.
+        slab[keys[0]] = -1;
+        assert!(!slab.shares_chunk_with(&snapshot, keys[0]));
+        assert!(slab.shares_chunk_with(&snapshot, keys[100]));
+        assert_eq!(snapshot[keys[0]], 0);
+        assert_eq!(slab[keys[0]], -1);
+
+        assert_eq!(slab.remove(keys[150]), Some(150));
+        assert_eq!(snapshot.get(keys[150]), Some(&150));
+        assert_eq!(slab.get(keys[150]), None);
+        assert_eq!((slab.len(), snapshot.len()), (199, 200));
+    }
+
+    #[test]
+    fn test_reuse_slots() {
+        let mut slab = ChunkedSlab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        assert_eq!(slab.remove(a), Some('a'));
+        assert_eq!(slab.remove(a), None);
+
+        // The slot is reused with a new generation.
+        let c = slab.insert('c');
+        assert_eq!(c.index(), a.index());
+        assert_ne!(c, a);
+        assert_eq!(slab.get(a), None);
+        assert_eq!(slab.keys().collect::<Vec<_>>(), vec![c, b]);
+
+        let snapshot = slab.clone();
+        slab.clear();
+        assert_eq!(slab.len(), 0);
+        assert_eq!(slab.get(b), None);
+        assert_eq!(snapshot[b], 'b');
+        let d = slab.insert('d');
+        assert_eq!(slab.get(c), None);
+        assert_eq!(slab[d], 'd');
+    }
+}
@@ -0,0 +1,125 @@
+//! A graph whose copies share storage, for taking cheap snapshots.
+//!
+//! A [`PersistentGraph`] stores its nodes and edges in fixed-size chunks
+//! behind reference counts.  [`PersistentGraph::snapshot`] copies only the
+//! pointers to the chunks, so it is cheap even for a large graph, and the
+//! snapshot keeps the IDs of the original.  When either graph is changed
+//! afterwards, only the chunks holding the changed nodes and edges are
+//! copied, so a graph which keeps changing while many snapshots of it are
+//! analyzed, possibly on other threads, uses memory in proportion to the
+//! changes rather than to the number of snapshots.
+
+use crate::{edge_multiplicity::EdgeMultiplicity, prelude::*, slab_graph::SlabGraph};
+
+mod chunks;
+
+use chunks::ChunkedSlabs;
+
+/// A graph representation with cheap, copy-on-write snapshots.
+///
+/// This is a [`SlabGraph`] whose arenas are split into reference counted
+/// chunks of 64 slots, so nodes and edges are identified by the same
+/// generational indices.  [`snapshot`](Self::snapshot)
+/// and [`clone`](Clone::clone) share the chunks instead of copying the nodes
+/// and edges, taking time proportional to the number of chunks, and the copy
+/// has the same IDs as the original.  Changing a node or edge in a chunk
+/// which is shared copies that chunk first, so changes to one copy are never
+/// seen by the others.
+///
+/// Reading is as fast as with a `SlabGraph`, but changing the graph requires
+/// the node and edge data to implement `Clone`.  A snapshot can be sent to
+/// another thread if the node and edge data can.
+///
+/// # Type Parameters
+/// * `N` - The type of data stored in nodes
+/// * `E` - The type of data stored in edges
+/// * `D` - The directedness ([`Directed`] or [`Undirected`])
+/// * `M` - The edge multiplicity ([`MultipleEdges`] or [`SingleEdge`])
+pub type PersistentGraph<N, E, D, M = MultipleEdges> = SlabGraph<N, E, D, M, ChunkedSlabs>;
+
+impl<N, E, D, M> PersistentGraph<N, E, D, M>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+{
+    /// Takes a read-only snapshot of the graph as it is now.  The snapshot
+    /// shares storage with this graph, and is unaffected by later changes to
+    /// it.  IDs of this graph can be used with the snapshot.
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_snapshots_are_independent() {
+        let mut graph = PersistentGraph::<String, u32, Directed>::default();
+        let a = graph.add_node("a".to_string());
+        let b = graph.add_node("b".to_string());
+        let ab = graph.add_new_edge(&a, &b, 1);
+        let before = graph.snapshot();
+
+        let c = graph.add_node("c".to_string());
+        graph.add_edge(&b, &c, 2);
+        *graph.edge_data_mut(&ab) = 10;
+        graph.remove_node(&a);
+        let after = graph.snapshot();
+        graph.clear();
+
+        assert_eq!(before.num_nodes(), 2);
+        assert_eq!(before.node_data(&a), "a");
+        assert_eq!(*before.edge_data(&ab), 1);
+        assert_eq!(before.edges_from(&a).collect::<Vec<_>>(), vec![ab]);
+        assert_eq!(before.check_node_id(&c), Err(GraphError::InvalidNodeId));
+
+        assert_eq!(after.num_nodes(), 2);
+        assert_eq!(after.num_edges(), 1);
+        assert_eq!(after.check_edge_id(&ab), Err(GraphError::InvalidEdgeId));
+        assert_eq!(after.successors(&b).collect::<Vec<_>>(), vec![c]);
+
+        assert!(graph.is_empty());
+    }
+
+    #[test]
+    fn test_snapshots_share_storage() {
+        let mut graph = PersistentGraph::<usize, (), Undirected>::default();
+        let nodes: Vec<_> = (0..200).map(|i| graph.add_node(i)).collect();
+        for pair in nodes.windows(2) {
+            graph.add_edge(&pair[0], &pair[1], ());
+        }
+        let snapshot = graph.snapshot();
+        *graph.node_data_mut(&nodes[199]) = 0;
+        assert!(
+            graph
+                .nodes
+                .shares_chunk_with(&snapshot.nodes, nodes[0].key())
+        );
+        assert!(
+            !graph
+                .nodes
+                .shares_chunk_with(&snapshot.nodes, nodes[199].key())
+        );
+        assert_eq!(*snapshot.node_data(&nodes[199]), 199);
+    }
+
+    #[test]
+    fn test_snapshots_on_other_threads() {
+        let mut graph = PersistentGraph::<usize, (), Directed>::default();
+        let root = graph.add_node(0);
+        let handles: Vec<_> = (1..=4)
+            .map(|i| {
+                let node = graph.add_node(i);
+                graph.add_edge(&root, &node, ());
+                let snapshot = graph.snapshot();
+                thread::spawn(move || snapshot.num_edges_from(&root))
+            })
+            .collect();
+        let counts: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(counts, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,326 @@
+//! Conversions to and from [`petgraph`] graphs.
+//!
+//! These functions copy a graph between this crate's graph types and
+//! [`petgraph::Graph`] or [`StableGraph`], keeping the data of every node and
+//! edge, so that code which uses `petgraph` can be moved over gradually.
+//! [`Directed`] and [`Undirected`] graphs convert to and from `petgraph`
+//! graphs with the edge type given by [`PetgraphDirectedness`].
+//!
+//! When converting to `petgraph`, nodes are added in the order returned by
+//! [`Graph::node_ids`], so the node with index `i` is the `i`th node ID.  When
+//! converting from `petgraph`, nodes are added in order of their indices, and
+//! edges are added in order of their indices after all the nodes.  Indices
+//! which a [`StableGraph`] has left vacant are skipped.
+
+use std::collections::HashMap;
+
+use petgraph::{
+    graph::{IndexType, NodeIndex},
+    stable_graph::StableGraph,
+    visit::{EdgeRef, IntoEdgeReferences, NodeIndexable},
+};
+
+use crate::{end_pair::EndPair, prelude::*};
+
+/// Directedness which corresponds to a `petgraph` edge type.
+pub trait PetgraphDirectedness: Directedness {
+    /// The corresponding `petgraph` edge type.
+    type EdgeType: petgraph::EdgeType;
+}
+
+impl PetgraphDirectedness for Directed {
+    type EdgeType = petgraph::Directed;
+}
+
+impl PetgraphDirectedness for Undirected {
+    type EdgeType = petgraph::Undirected;
+}
+
+/// The [`petgraph::Graph`] type corresponding to `G`.
+pub type PetGraphOf<G> = petgraph::Graph<
+    <G as Graph>::NodeData,
+    <G as Graph>::EdgeData,
+    <<G as Graph>::Directedness as PetgraphDirectedness>::EdgeType,
+>;
+
+/// The [`StableGraph`] type corresponding to `G`.
+pub type StableGraphOf<G> = StableGraph<
+    <G as Graph>::NodeData,
+    <G as Graph>::EdgeData,
+    <<G as Graph>::Directedness as PetgraphDirectedness>::EdgeType,
+>;
+
+/// Copies `graph` into a [`petgraph::Graph`].  See [`Graph::to_petgraph`].
+pub fn to_petgraph<G>(graph: &G) -> PetGraphOf<G>
+where
+    G: Graph + ?Sized,
+    G::Directedness: PetgraphDirectedness,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+{
+    let mut result = petgraph::Graph::with_capacity(graph.num_nodes(), graph.num_edges());
+    copy_into(
+        graph,
+        &mut result,
+        |result, data| result.add_node(data),
+        |result, from, into, data| {
+            result.add_edge(from, into, data);
+        },
+    );
+    result
+}
+
+/// Copies `graph` into a [`StableGraph`].  See [`Graph::to_stable_graph`].
+pub fn to_stable_graph<G>(graph: &G) -> StableGraphOf<G>
+where
+    G: Graph + ?Sized,
+    G::Directedness: PetgraphDirectedness,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+{
+    let mut result = StableGraph::with_capacity(graph.num_nodes(), graph.num_edges());
+    copy_into(
+        graph,
+        &mut result,
+        |result, data| result.add_node(data),
+        |result, from, into, data| {
+            result.add_edge(from, into, data);
+        },
+    );
+    result
+}
+
+/// Moves the nodes and edges of `graph` into a [`petgraph::Graph`], without
+/// cloning their data.
+pub fn into_petgraph<G>(mut graph: G) -> PetGraphOf<G>
+where
+    G: GraphMut,
+    G::Directedness: PetgraphDirectedness,
+{
+    let mut result = petgraph::Graph::with_capacity(graph.num_nodes(), graph.num_edges());
+    // Edges must be removed before their ends, but added after them.
+    let edges: Vec<_> = graph
+        .edge_ids()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|eid| {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            (from, into, graph.remove_edge(&eid))
+        })
+        .collect();
+    let mut index = HashMap::with_capacity(graph.num_nodes());
+    for nid in graph.node_ids().collect::<Vec<_>>() {
+        let data = graph.remove_node(&nid);
+        index.insert(nid, result.add_node(data));
+    }
+    for (from, into, data) in edges {
+        result.add_edge(index[&from], index[&into], data);
+    }
+    result
+}
+
+/// Copies a [`petgraph::Graph`] into a new graph.  See
+/// [`GraphMut::from_petgraph`].
+pub fn from_petgraph<G, Ix>(
+    source: &petgraph::Graph<
+        G::NodeData,
+        G::EdgeData,
+        <G::Directedness as PetgraphDirectedness>::EdgeType,
+        Ix,
+    >,
+) -> G
+where
+    G: GraphMut + Default,
+    G::Directedness: PetgraphDirectedness,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+    Ix: IndexType,
+{
+    let mut graph = G::default();
+    graph.reserve(source.node_count(), source.edge_count());
+    let nodes: Vec<_> = source
+        .node_weights()
+        .map(|data| graph.add_node(data.clone()))
+        .collect();
+    for edge in source.edge_references() {
+        graph.add_edge(
+            &nodes[edge.source().index()],
+            &nodes[edge.target().index()],
+            edge.weight().clone(),
+        );
+    }
+    graph
+}
+
+/// Moves the nodes and edges of a [`petgraph::Graph`] into a new graph,
+/// without cloning their data.
+pub fn from_petgraph_owned<G, Ix>(
+    source: petgraph::Graph<
+        G::NodeData,
+        G::EdgeData,
+        <G::Directedness as PetgraphDirectedness>::EdgeType,
+        Ix,
+    >,
+) -> G
+where
+    G: GraphMut + Default,
+    G::Directedness: PetgraphDirectedness,
+    Ix: IndexType,
+{
+    let (nodes, edges) = source.into_nodes_edges();
+    let mut graph = G::default();
+    graph.reserve(nodes.len(), edges.len());
+    let nodes: Vec<_> = nodes
+        .into_iter()
+        .map(|node| graph.add_node(node.weight))
+        .collect();
+    for edge in edges {
+        let (from, into) = (edge.source().index(), edge.target().index());
+        graph.add_edge(&nodes[from], &nodes[into], edge.weight);
+    }
+    graph
+}
+
+/// Copies a [`StableGraph`] into a new graph.  See
+/// [`GraphMut::from_stable_graph`].
+pub fn from_stable_graph<G, Ix>(
+    source: &StableGraph<
+        G::NodeData,
+        G::EdgeData,
+        <G::Directedness as PetgraphDirectedness>::EdgeType,
+        Ix,
+    >,
+) -> G
+where
+    G: GraphMut + Default,
+    G::Directedness: PetgraphDirectedness,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+    Ix: IndexType,
+{
+    let mut graph = G::default();
+    graph.reserve(source.node_count(), source.edge_count());
+    let mut nodes = vec![None; source.node_bound()];
+    for index in source.node_indices() {
+        nodes[index.index()] = Some(graph.add_node(source[index].clone()));
+    }
+    let node = |index: NodeIndex<Ix>| nodes[index.index()].as_ref().unwrap();
+    for edge in source.edge_references() {
+        graph.add_edge(
+            node(edge.source()),
+            node(edge.target()),
+            edge.weight().clone(),
+        );
+    }
+    graph
+}
+
+/// Adds the nodes and then the edges of `graph` to `target` with the given
+/// functions.
+fn copy_into<G, T>(
+    graph: &G,
+    target: &mut T,
+    add_node: impl Fn(&mut T, G::NodeData) -> NodeIndex,
+    add_edge: impl Fn(&mut T, NodeIndex, NodeIndex, G::EdgeData),
+) where
+    G: Graph + ?Sized,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+{
+    let index: HashMap<_, _> = graph
+        .node_ids()
+        .map(|nid| {
+            let data = graph.node_data(&nid).clone();
+            (nid, add_node(target, data))
+        })
+        .collect();
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        add_edge(
+            target,
+            index[&from],
+            index[&into],
+            graph.edge_data(&eid).clone(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut graph = LinkedGraph::<&str, i32, Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_new_edge(&a, &b, 1);
+        graph.add_new_edge(&b, &c, 2);
+        graph.add_new_edge(&c, &c, 3);
+
+        let pg = graph.to_petgraph();
+        assert_eq!(pg.node_count(), 3);
+        assert_eq!(pg.edge_count(), 3);
+        let names: Vec<_> = pg.node_weights().copied().collect();
+        let expected: Vec<_> = graph.node_ids().map(|nid| *graph.node_data(&nid)).collect();
+        assert_eq!(names, expected);
+
+        let copy: BagGraph<&str, i32, Directed> = GraphMut::from_petgraph(&pg);
+        let owned: BagGraph<&str, i32, Directed> = from_petgraph_owned(pg);
+        for other in [&copy, &owned] {
+            let mut edges: Vec<_> = other
+                .edge_ids()
+                .map(|eid| {
+                    let (from, into) = other.edge_ends(&eid).into_values();
+                    (
+                        *other.node_data(&from),
+                        *other.node_data(&into),
+                        *other.edge_data(&eid),
+                    )
+                })
+                .collect();
+            edges.sort();
+            assert_eq!(edges, vec![("a", "b", 1), ("b", "c", 2), ("c", "c", 3)]);
+        }
+    }
+
+    #[test]
+    fn test_stable_graph_with_vacancies() {
+        let mut sg = StableGraph::<&str, i32, petgraph::Undirected>::default();
+        let a = sg.add_node("a");
+        let b = sg.add_node("b");
+        let c = sg.add_node("c");
+        sg.add_edge(a, b, 1);
+        sg.add_edge(b, c, 2);
+        sg.remove_node(b);
+        sg.add_edge(c, a, 3);
+
+        let graph: LinkedGraph<&str, i32, Undirected> = GraphMut::from_stable_graph(&sg);
+        assert_eq!(graph.num_nodes(), 2);
+        assert_eq!(graph.num_edges(), 1);
+        let eid = graph.edge_ids().next().unwrap();
+        assert_eq!(*graph.edge_data(&eid), 3);
+
+        let sg = graph.to_stable_graph();
+        assert_eq!(sg.node_count(), 2);
+        assert_eq!(sg.edge_weights().copied().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_into_petgraph() {
+        let mut graph = BagGraph::<String, String, Undirected>::default();
+        let a = graph.add_node("a".to_string());
+        let b = graph.add_node("b".to_string());
+        graph.add_new_edge(&a, &b, "ab".to_string());
+
+        let pg = into_petgraph(graph);
+        let edge = pg.edge_references().next().unwrap();
+        assert_eq!(edge.weight(), "ab");
+        let mut ends = [&pg[edge.source()], &pg[edge.target()]];
+        ends.sort();
+        assert_eq!(ends, ["a", "b"]);
+    }
+}
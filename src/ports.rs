@@ -0,0 +1,298 @@
+//! Named connection points on nodes.
+//!
+//! In models like hardware netlists, edges connect to particular pins of a
+//! component rather than to the component as a whole.  A node can declare
+//! named ports by having node data which implements [`NodePorts`], and an
+//! edge can attach each of its ends to a port by having edge data which
+//! implements [`EdgePorts`].  [`PortedNode`] and [`PortedEdge`] wrap existing
+//! data to do so.
+//!
+//! The tail of an edge is its first end as returned by
+//! [`Graph::edge_ends`], and the head is its second end.
+//!
+//! Ports correspond to the DOT port syntax: `a:out -> b:in` sets the edge's
+//! `tailport` and `headport` attributes, which the DOT parser passes to
+//! [`GraphBuilder::make_edge_data`](crate::dot::parser::GraphBuilder::make_edge_data)
+//! and [`edge_ports_from_attrs`] extracts.  In the other direction,
+//! [`edge_port_attrs`] gives the attributes to return from
+//! [`DotRenderer::edge_attrs`](crate::dot::renderer::DotRenderer::edge_attrs).
+
+#[cfg(feature = "dot")]
+use crate::dot::attr::Attr;
+use crate::{end_pair::EndPair, prelude::*};
+
+/// Node data which declares named ports.
+pub trait NodePorts {
+    /// Returns true if the node has a port with the given name.
+    fn has_port(&self, port: &str) -> bool;
+}
+
+/// Edge data which attaches the ends of an edge to ports.
+pub trait EdgePorts {
+    /// Returns the port the tail of the edge is attached to, if any.
+    fn tail_port(&self) -> Option<&str>;
+
+    /// Returns the port the head of the edge is attached to, if any.
+    fn head_port(&self) -> Option<&str>;
+}
+
+/// Node data along with the names of the node's ports.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PortedNode<T> {
+    pub data: T,
+    pub ports: Vec<String>,
+}
+
+impl<T> NodePorts for PortedNode<T> {
+    fn has_port(&self, port: &str) -> bool {
+        self.ports.iter().any(|p| p == port)
+    }
+}
+
+/// Edge data along with the ports its ends are attached to.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PortedEdge<T> {
+    pub data: T,
+    pub tail_port: Option<String>,
+    pub head_port: Option<String>,
+}
+
+impl<T> EdgePorts for PortedEdge<T> {
+    fn tail_port(&self) -> Option<&str> {
+        self.tail_port.as_deref()
+    }
+
+    fn head_port(&self) -> Option<&str> {
+        self.head_port.as_deref()
+    }
+}
+
+/// An edge attached to a port which its node does not declare.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("edge {edge:?} is attached to undeclared port {port:?}")]
+pub struct UndeclaredPortError<E> {
+    pub edge: E,
+    pub port: String,
+}
+
+/// Returns the edges with an end attached to `port` of `node`.  A self-loop
+/// with both ends attached to the port is returned once.
+pub fn edges_at_port<'a, G>(
+    graph: &'a G,
+    node: &'a G::NodeId,
+    port: &'a str,
+) -> impl Iterator<Item = G::EdgeId> + 'a
+where
+    G: Graph + ?Sized,
+    G::EdgeData: EdgePorts,
+{
+    let attached = move |eid: &G::EdgeId| {
+        let (tail, head) = graph.edge_ends(eid).into_values();
+        let data = graph.edge_data(eid);
+        (tail == *node && data.tail_port() == Some(port))
+            || (head == *node && data.head_port() == Some(port))
+    };
+    // For undirected graphs, `edges_from` already returns every edge at the
+    // node.
+    let incoming = G::Directedness::IS_DIRECTED.then(|| {
+        graph.edges_into(node).filter(move |eid| {
+            let (tail, _) = graph.edge_ends(eid).into_values();
+            tail != *node
+        })
+    });
+    graph
+        .edges_from(node)
+        .chain(incoming.into_iter().flatten())
+        .filter(attached)
+}
+
+/// Checks that every port an edge is attached to is declared by the node at
+/// that end.
+pub fn validate_ports<G>(graph: &G) -> Result<(), UndeclaredPortError<G::EdgeId>>
+where
+    G: Graph + ?Sized,
+    G::NodeData: NodePorts,
+    G::EdgeData: EdgePorts,
+{
+    for eid in graph.edge_ids() {
+        let (tail, head) = graph.edge_ends(&eid).into_values();
+        let data = graph.edge_data(&eid);
+        for (node, port) in [(tail, data.tail_port()), (head, data.head_port())] {
+            if let Some(port) = port
+                && !graph.node_data(&node).has_port(port)
+            {
+                return Err(UndeclaredPortError {
+                    edge: eid,
+                    port: port.to_string(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the `tailport` and `headport` attributes in `attrs`.
+#[cfg(feature = "dot")]
+pub fn edge_ports_from_attrs(attrs: &[Attr]) -> (Option<String>, Option<String>) {
+    let mut ports = (None, None);
+    for attr in attrs {
+        match attr {
+            Attr::Tailport(port) => ports.0 = Some(port.clone()),
+            Attr::Headport(port) => ports.1 = Some(port.clone()),
+            _ => {}
+        }
+    }
+    ports
+}
+
+/// Returns `tailport` and `headport` attributes for the ports of an edge.
+#[cfg(feature = "dot")]
+pub fn edge_port_attrs(data: &impl EdgePorts) -> Vec<Attr> {
+    let tail = data
+        .tail_port()
+        .map(|port| Attr::Tailport(port.to_string()));
+    let head = data
+        .head_port()
+        .map(|port| Attr::Headport(port.to_string()));
+    tail.into_iter().chain(head).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    fn pins(names: &[&str]) -> PortedNode<()> {
+        PortedNode {
+            data: (),
+            ports: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn wire(tail: &str, head: &str) -> PortedEdge<()> {
+        PortedEdge {
+            data: (),
+            tail_port: Some(tail.to_string()),
+            head_port: Some(head.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_edges_at_port() {
+        let mut graph = LinkedGraph::<PortedNode<()>, PortedEdge<()>, Directed>::default();
+        let gate = graph.add_node(pins(&["a", "b", "y"]));
+        let other = graph.add_node(pins(&["a", "y"]));
+        let ya = graph.add_new_edge(&gate, &other, wire("y", "a"));
+        let yb = graph.add_new_edge(&other, &gate, wire("y", "b"));
+        let feedback = graph.add_new_edge(&gate, &gate, wire("y", "a"));
+
+        let mut at_y: Vec<_> = graph.edges_at_port(&gate, "y").collect();
+        at_y.sort();
+        let mut expected = vec![ya.clone(), feedback.clone()];
+        expected.sort();
+        assert_eq!(at_y, expected);
+        let at_a: Vec<_> = graph.edges_at_port(&gate, "a").collect();
+        assert_eq!(at_a, vec![feedback]);
+        let at_b: Vec<_> = graph.edges_at_port(&gate, "b").collect();
+        assert_eq!(at_b, vec![yb.clone()]);
+        let at_a: Vec<_> = graph.edges_at_port(&other, "a").collect();
+        assert_eq!(at_a, vec![ya]);
+
+        assert_eq!(graph.validate_ports(), Ok(()));
+        graph.edge_data_mut(&yb).head_port = Some("c".to_string());
+        assert_eq!(
+            graph.validate_ports(),
+            Err(UndeclaredPortError {
+                edge: yb,
+                port: "c".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_undirected() {
+        let mut graph = BagGraph::<PortedNode<()>, PortedEdge<()>, Undirected>::default();
+        let a = graph.add_node(pins(&["p", "q"]));
+        let b = graph.add_node(pins(&["p"]));
+        let ab = graph.add_new_edge(&a, &b, wire("q", "p"));
+        let loop_edge = graph.add_new_edge(&a, &a, wire("p", "p"));
+        assert_eq!(
+            graph.edges_at_port(&a, "q").collect::<Vec<_>>(),
+            vec![ab.clone()]
+        );
+        assert_eq!(graph.edges_at_port(&b, "p").collect::<Vec<_>>(), vec![ab]);
+        assert_eq!(
+            graph.edges_at_port(&a, "p").collect::<Vec<_>>(),
+            vec![loop_edge]
+        );
+    }
+
+    #[cfg(feature = "dot")]
+    #[test]
+    fn test_dot_ports() {
+        use crate::dot::parser::GraphBuilder;
+
+        #[derive(Debug)]
+        struct Builder;
+
+        impl GraphBuilder for Builder {
+            type Graph = BagGraph<String, PortedEdge<()>, Directed>;
+            type Error = std::convert::Infallible;
+
+            fn make_node_data(&mut self, id: &str, _attrs: &[Attr]) -> Result<String, Self::Error> {
+                Ok(id.to_string())
+            }
+
+            fn make_implicit_node_data(&mut self, id: &str) -> Result<String, Self::Error> {
+                Ok(id.to_string())
+            }
+
+            fn make_edge_data(&mut self, attrs: &[Attr]) -> Result<PortedEdge<()>, Self::Error> {
+                let (tail_port, head_port) = edge_ports_from_attrs(attrs);
+                Ok(PortedEdge {
+                    data: (),
+                    tail_port,
+                    head_port,
+                })
+            }
+        }
+
+        let graph = BagGraph::from_dot_string(
+            "digraph { a:out -> b:in:n -> c; c -> a [headport=x] }",
+            &mut Builder,
+        )
+        .unwrap();
+        let mut ports: Vec<_> = graph
+            .edge_ids()
+            .map(|eid| {
+                let (from, into) = graph.edge_ends(&eid).into_values();
+                let data = graph.edge_data(&eid);
+                (
+                    graph.node_data(&from).as_str(),
+                    graph.node_data(&into).as_str(),
+                    data.tail_port(),
+                    data.head_port(),
+                )
+            })
+            .collect();
+        ports.sort();
+        assert_eq!(
+            ports,
+            vec![
+                ("a", "b", Some("out"), Some("in:n")),
+                ("b", "c", Some("in:n"), None),
+                ("c", "a", None, Some("x")),
+            ]
+        );
+
+        let attrs = edge_port_attrs(&wire("y", "a"));
+        assert_eq!(
+            attrs,
+            vec![
+                Attr::Tailport("y".to_string()),
+                Attr::Headport("a".to_string())
+            ]
+        );
+    }
+}
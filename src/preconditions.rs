@@ -0,0 +1,302 @@
+//! Witness types for the preconditions of algorithms.
+//!
+//! Some algorithms silently give wrong answers when their input does not
+//! meet a precondition.  The types in this module can only be created by
+//! checking the precondition, so an algorithm which takes one cannot be
+//! called with input that has not been checked.
+//!
+//! - [`Acyclic`] borrows a directed graph which has no cycles.  Because the
+//!   graph is borrowed, it cannot be changed while the witness exists.
+//!   [`Acyclic::topological_order`] and [`Acyclic::layering`] are only
+//!   available through it.
+//! - [`NonNegative`] wraps a weight which is not negative.
+//!   [`Graph::checked_shortest_path_tree`] only accepts `NonNegative`
+//!   weights, which ensures that Dijkstra's algorithm is only run on weights
+//!   it can handle.  They can also be returned from the distance function of
+//!   [`Graph::shortest_paths`] or [`Graph::shortest_path_tree`].
+
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::{Add, Deref},
+};
+
+use crate::{cycles, end_pair::EndPair, hashing::FastHashMap, path::Path, prelude::*};
+
+/// A directed graph which is known to contain no cycles.
+#[derive(Debug)]
+pub struct Acyclic<'g, G: ?Sized> {
+    graph: &'g G,
+}
+
+impl<G: ?Sized> Clone for Acyclic<'_, G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<G: ?Sized> Copy for Acyclic<'_, G> {}
+
+impl<'g, G> Acyclic<'g, G>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    /// Checks that `graph` has no cycles.  Returns a cycle of the graph if
+    /// it has one.
+    pub fn new(graph: &'g G) -> Result<Self, Path<'g, G>> {
+        match cycles::find_cycle(graph) {
+            Some(cycle) => Err(cycle),
+            None => Ok(Self { graph }),
+        }
+    }
+
+    /// Gets the graph.
+    pub fn graph(&self) -> &'g G {
+        self.graph
+    }
+
+    /// Returns the nodes of the graph in an order where the source of every
+    /// edge comes before its target, using Kahn's algorithm.
+    pub fn topological_order(&self) -> Vec<G::NodeId> {
+        let mut in_degrees: FastHashMap<G::NodeId, usize> = FastHashMap::default();
+        let mut ready = VecDeque::new();
+        for nid in self.graph.node_ids() {
+            let in_degree = self.graph.num_edges_into(&nid);
+            if in_degree == 0 {
+                ready.push_back(nid);
+            } else {
+                in_degrees.insert(nid, in_degree);
+            }
+        }
+        let mut order = Vec::with_capacity(self.graph.num_nodes());
+        while let Some(nid) = ready.pop_front() {
+            for eid in self.graph.edges_from(&nid) {
+                let (_, into) = self.graph.edge_ends(&eid).into_values();
+                let in_degree = in_degrees.get_mut(&into).expect("graph has a cycle");
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    in_degrees.remove(&into);
+                    ready.push_back(into);
+                }
+            }
+            order.push(nid);
+        }
+        order
+    }
+
+    /// Assigns each node a layer equal to the number of edges in the longest
+    /// path into it from a source, i.e. a node with no edges into it.
+    /// Sources are in layer 0, and the target of every edge is in a higher
+    /// layer than its source.  This is the longest-path layering used to
+    /// schedule tasks as early as possible, or to place nodes in rows for a
+    /// Sugiyama-style layout.
+    pub fn layering(&self) -> HashMap<G::NodeId, usize> {
+        let sources: Vec<_> = self
+            .graph
+            .node_ids()
+            .filter(|nid| self.graph.num_edges_into(nid) == 0)
+            .collect();
+        self.layering_from(sources)
+    }
+
+    /// Like [`Self::layering`], but measures the longest paths from `roots`
+    /// instead of from the sources of the graph.  Only the nodes reachable
+    /// from `roots` are assigned a layer.  A root which is reachable from
+    /// another root is placed after it, like any other node.
+    pub fn layering_from(
+        &self,
+        roots: impl IntoIterator<Item = G::NodeId>,
+    ) -> HashMap<G::NodeId, usize> {
+        let mut layers: HashMap<G::NodeId, usize> = roots.into_iter().map(|nid| (nid, 0)).collect();
+        for nid in self.topological_order() {
+            let Some(&layer) = layers.get(&nid) else {
+                continue;
+            };
+            for eid in self.graph.edges_from(&nid) {
+                let (_, into) = self.graph.edge_ends(&eid).into_values();
+                let into_layer = layers.entry(into).or_insert(0);
+                *into_layer = (*into_layer).max(layer + 1);
+            }
+        }
+        layers
+    }
+}
+
+impl<G: ?Sized> Deref for Acyclic<'_, G> {
+    type Target = G;
+
+    fn deref(&self) -> &G {
+        self.graph
+    }
+}
+
+/// A weight which is known not to be negative, i.e. not less than
+/// `W::default()`.
+///
+/// The sum of two non-negative weights is non-negative, so `NonNegative`
+/// implements [`Add`] and can be used as the cost type of the shortest path
+/// methods of [`Graph`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NonNegative<W>(W);
+
+impl<W> NonNegative<W>
+where
+    W: Default + PartialOrd,
+{
+    /// Checks that `weight` is not negative.  Returns the weight as an error
+    /// if it is negative or not comparable with zero, like `f64::NAN`.
+    pub fn new(weight: W) -> Result<Self, W> {
+        if weight >= W::default() {
+            Ok(Self(weight))
+        } else {
+            Err(weight)
+        }
+    }
+
+    /// Checks the weights of all edges of `graph`, and returns a function
+    /// giving the weight of each edge for use with the shortest path methods
+    /// of [`Graph`].  Returns the first edge found with a negative weight, or
+    /// one not comparable with zero, as an error.
+    pub fn check_edges<'g, G>(
+        graph: &'g G,
+        weight_fn: impl Fn(&G::EdgeId) -> W + 'g,
+    ) -> Result<impl Fn(&G::EdgeId) -> Self + 'g, G::EdgeId>
+    where
+        G: Graph + ?Sized,
+    {
+        if let Some(eid) = graph
+            .edge_ids()
+            .find(|eid| Self::new(weight_fn(eid)).is_err())
+        {
+            return Err(eid);
+        }
+        Ok(move |eid: &G::EdgeId| Self(weight_fn(eid)))
+    }
+}
+
+impl<W> NonNegative<W> {
+    /// Gets the weight.
+    pub fn get(&self) -> &W {
+        &self.0
+    }
+
+    /// Unwraps the weight.
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W> Add for NonNegative<W>
+where
+    W: Add<Output = W>,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LinkedGraph;
+
+    use super::*;
+
+    #[test]
+    fn test_topological_order() {
+        let mut graph = LinkedGraph::<&str, (), Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.add_new_edge(&c, &b, ());
+        graph.add_new_edge(&b, &a, ());
+        graph.add_new_edge(&c, &a, ());
+        graph.add_new_edge(&d, &b, ());
+
+        let acyclic = graph.check_acyclic().unwrap();
+        assert_eq!(acyclic.num_nodes(), 4);
+        let order = acyclic.topological_order();
+        assert_eq!(order.len(), 4);
+        let position = |nid: &_| order.iter().position(|n| n == nid).unwrap();
+        for eid in graph.edge_ids() {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            assert!(position(&from) < position(&into));
+        }
+
+        let ca = graph.add_new_edge(&a, &c, ());
+        let cycle = graph.check_acyclic().unwrap_err();
+        assert!(cycle.edges().any(|eid| eid == ca));
+    }
+
+    #[test]
+    fn test_layering() {
+        let mut graph = LinkedGraph::<&str, (), Directed>::default();
+        let [a, b, c, d, e, f] = ["a", "b", "c", "d", "e", "f"].map(|name| graph.add_node(name));
+        for (from, into) in [(&a, &b), (&b, &c), (&a, &c), (&d, &c), (&c, &e)] {
+            graph.add_new_edge(from, into, ());
+        }
+
+        let acyclic = graph.check_acyclic().unwrap();
+        let layers = acyclic.layering();
+        assert_eq!(layers.len(), 6);
+        for (nid, layer) in [(&a, 0), (&b, 1), (&c, 2), (&d, 0), (&e, 3), (&f, 0)] {
+            assert_eq!(layers[nid], layer);
+        }
+
+        let layers = acyclic.layering_from([b.clone(), d.clone()]);
+        assert_eq!(layers.len(), 4);
+        for (nid, layer) in [(&b, 0), (&c, 1), (&d, 0), (&e, 2)] {
+            assert_eq!(layers[nid], layer);
+        }
+
+        graph.add_new_edge(&e, &a, ());
+        assert!(graph.layering().is_err());
+    }
+
+    #[test]
+    fn test_non_negative() {
+        assert_eq!(NonNegative::new(3).map(NonNegative::into_inner), Ok(3));
+        assert_eq!(NonNegative::new(0), Ok(NonNegative(0)));
+        assert_eq!(NonNegative::new(-1), Err(-1));
+        assert!(NonNegative::new(f64::NAN).is_err());
+        assert_eq!(NonNegative(2) + NonNegative(3), NonNegative(5));
+    }
+
+    #[cfg(feature = "pathfinding")]
+    #[test]
+    fn test_checked_shortest_paths() {
+        use crate::BagGraph;
+
+        let mut graph = BagGraph::<(), i32, Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_new_edge(&a, &b, 2);
+        graph.add_new_edge(&b, &c, 3);
+        let ac = graph.add_new_edge(&a, &c, 7);
+
+        let weights = NonNegative::check_edges(&graph, |eid| *graph.edge_data(eid)).unwrap();
+        let tree = graph.checked_shortest_path_tree(&a, weights);
+        assert_eq!(tree.distance(&c).map(NonNegative::into_inner), Some(5));
+
+        *graph.edge_data_mut(&ac) = -7;
+        assert_eq!(
+            NonNegative::check_edges(&graph, |eid| *graph.edge_data(eid)).err(),
+            Some(ac)
+        );
+    }
+
+    #[test]
+    fn test_check_edges_rejects_nan() {
+        let mut graph = LinkedGraph::<(), f64, Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_new_edge(&a, &b, 1.0);
+        let ba = graph.add_new_edge(&b, &a, f64::NAN);
+        assert_eq!(
+            NonNegative::check_edges(&graph, |eid| *graph.edge_data(eid)).err(),
+            Some(ba)
+        );
+    }
+}
@@ -1,4 +1,4 @@
 pub use crate::{
-    Directed, Directedness, EdgeMultiplicity, Graph, GraphDirected, GraphElementId, GraphMut,
-    GraphUndirected, MultipleEdges, SingleEdge, Undirected,
+    AddEdgeResult, Directed, Directedness, Direction, EdgeMultiplicity, Graph, GraphDirected,
+    GraphElementId, GraphError, GraphMut, GraphUndirected, MultipleEdges, SingleEdge, Undirected,
 };
@@ -0,0 +1,320 @@
+//! Side tables of data keyed by node or edge IDs.
+//!
+//! Compacting a graph with [`GraphMut::compact`] changes the IDs of its
+//! nodes and edges, so any table keyed by those IDs must be updated to match.
+//! A [`PropertyMap`] can be passed to [`GraphMut::compact_with`], which
+//! compacts the graph and updates the keys of every map given to it, so the
+//! tables do not need to be updated by hand.
+
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, ops::Index};
+
+use crate::prelude::*;
+
+/// A table which can update its keys when the graph they belong to is
+/// compacted.  See [`GraphMut::compact_with`].
+pub trait RemapKeys<K> {
+    /// Replaces each key with the key it maps to in `map`, which has an entry
+    /// for every element that survived the compaction.  Entries whose keys
+    /// do not appear in `map` belong to removed elements and are dropped,
+    /// since their keys may now belong to other elements.
+    fn remap_keys(&mut self, map: &HashMap<K, K>);
+
+    /// Reduces the memory used by the table as much as possible.
+    fn shrink_to_fit(&mut self);
+}
+
+/// A map from node or edge IDs to values of type `T`.
+#[derive(Clone, Debug)]
+pub struct PropertyMap<K, T> {
+    values: HashMap<K, T>,
+}
+
+impl<K, T> PropertyMap<K, T>
+where
+    K: Hash + Eq,
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Gets the value for a key, if any.
+    pub fn get<Q>(&self, key: &Q) -> Option<&T>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.get(key)
+    }
+
+    /// Gets a mutable reference to the value for a key, if any.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut T>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.get_mut(key)
+    }
+
+    /// Returns true if the map has a value for a key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.contains_key(key)
+    }
+
+    /// Sets the value for a key, returning the old value, if any.
+    pub fn insert(&mut self, key: K, value: T) -> Option<T> {
+        self.values.insert(key, value)
+    }
+
+    /// Removes the value for a key, returning it, if any.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<T>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.remove(key)
+    }
+
+    /// Gets the value for a key, inserting the result of `default` first if
+    /// there is none.
+    pub fn get_or_insert_with(&mut self, key: K, default: impl FnOnce() -> T) -> &mut T {
+        self.values.entry(key).or_insert_with(default)
+    }
+
+    /// Keeps only the entries for which `keep` returns true.
+    pub fn retain(&mut self, keep: impl FnMut(&K, &mut T) -> bool) {
+        self.values.retain(keep);
+    }
+
+    /// Removes every entry.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Gets an iterator over the entries, in an unspecified order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &T)> {
+        self.values.iter()
+    }
+
+    /// Gets an iterator over the keys, in an unspecified order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.values.keys()
+    }
+
+    /// Gets an iterator over the values, in an unspecified order.
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.values.values()
+    }
+
+    /// Gets an iterator over mutable references to the values, in an
+    /// unspecified order.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.values.values_mut()
+    }
+
+    /// Removes the entries whose keys are not valid node IDs of `graph`.
+    pub fn retain_nodes<G>(&mut self, graph: &G)
+    where
+        G: Graph<NodeId = K> + ?Sized,
+    {
+        self.values
+            .retain(|key, _| graph.check_node_id(key).is_ok());
+    }
+
+    /// Removes the entries whose keys are not valid edge IDs of `graph`.
+    pub fn retain_edges<G>(&mut self, graph: &G)
+    where
+        G: Graph<EdgeId = K> + ?Sized,
+    {
+        self.values
+            .retain(|key, _| graph.check_edge_id(key).is_ok());
+    }
+}
+
+impl<K, T> Default for PropertyMap<K, T>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> FromIterator<(K, T)> for PropertyMap<K, T>
+where
+    K: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, T)>>(iter: I) -> Self {
+        Self {
+            values: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<K, T> Extend<(K, T)> for PropertyMap<K, T>
+where
+    K: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = (K, T)>>(&mut self, iter: I) {
+        self.values.extend(iter);
+    }
+}
+
+impl<K, T> IntoIterator for PropertyMap<K, T> {
+    type Item = (K, T);
+    type IntoIter = std::collections::hash_map::IntoIter<K, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl<K, T> From<HashMap<K, T>> for PropertyMap<K, T> {
+    fn from(values: HashMap<K, T>) -> Self {
+        Self { values }
+    }
+}
+
+impl<K, T> From<PropertyMap<K, T>> for HashMap<K, T> {
+    fn from(map: PropertyMap<K, T>) -> Self {
+        map.values
+    }
+}
+
+impl<K, Q, T> Index<&Q> for PropertyMap<K, T>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+{
+    type Output = T;
+
+    /// Gets the value for a key.  Panics if there is none.
+    fn index(&self, key: &Q) -> &T {
+        &self.values[key]
+    }
+}
+
+impl<K, T> RemapKeys<K> for PropertyMap<K, T>
+where
+    K: Hash + Eq + Clone,
+{
+    fn remap_keys(&mut self, map: &HashMap<K, K>) {
+        // The new keys may collide with old keys which have not been
+        // replaced yet, so the map is rebuilt rather than updated in place.
+        self.values = std::mem::take(&mut self.values)
+            .into_iter()
+            .filter_map(|(key, value)| Some((map.get(&key)?.clone(), value)))
+            .collect();
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.values.shrink_to_fit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph, end_pair::EndPair};
+
+    use super::*;
+
+    #[test]
+    fn test_compact_with() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let nodes: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        let e01 = graph.add_new_edge(&nodes[0], &nodes[1], ());
+        let e23 = graph.add_new_edge(&nodes[2], &nodes[3], ());
+        let mut names: PropertyMap<_, _> = nodes
+            .iter()
+            .zip(["a", "b", "c", "d"])
+            .map(|(nid, name)| (nid.clone(), name))
+            .collect();
+        let mut ranks: PropertyMap<_, _> = nodes.iter().map(|nid| (nid.clone(), 0)).collect();
+        let mut labels = PropertyMap::new();
+        labels.insert(e01.clone(), "first");
+        labels.insert(e23.clone(), "second");
+
+        graph.remove_node(&nodes[0]);
+        names.retain_nodes(&graph);
+        ranks.retain_nodes(&graph);
+        labels.retain_edges(&graph);
+        assert_eq!(names.len(), 3);
+        assert_eq!(labels.len(), 1);
+
+        graph.compact_with(&mut [&mut names, &mut ranks], &mut [&mut labels]);
+        let mut found: Vec<_> = graph.node_ids().map(|nid| names[&nid]).collect();
+        found.sort();
+        assert_eq!(found, vec!["b", "c", "d"]);
+        assert!(graph.node_ids().all(|nid| ranks.contains_key(&nid)));
+        let edges: Vec<_> = graph.edge_ids().collect();
+        assert_eq!(labels.get(&edges[0]), Some(&"second"));
+        let (from, into) = graph.edge_ends(&edges[0]).into_values();
+        assert_eq!((names[&from], names[&into]), ("c", "d"));
+    }
+
+    #[test]
+    fn test_remap_keys_with_overlap() {
+        let mut map: PropertyMap<_, _> = [(1, "a"), (2, "b"), (3, "c")].into_iter().collect();
+        map.remap_keys(&HashMap::from([(1, 2), (2, 3), (3, 1)]));
+        assert_eq!(map.get(&2), Some(&"a"));
+        assert_eq!(map.get(&3), Some(&"b"));
+        assert_eq!(map.get(&1), Some(&"c"));
+    }
+
+    #[test]
+    fn test_remap_keys_drops_removed() {
+        let mut map: PropertyMap<_, _> = [(1, "a"), (2, "b"), (3, "c")].into_iter().collect();
+        map.remap_keys(&HashMap::from([(2, 1), (3, 2)]));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&"b"));
+        assert_eq!(map.get(&2), Some(&"c"));
+    }
+
+    #[test]
+    fn test_compact_with_stale_entries() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let nodes: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        let mut names: PropertyMap<_, _> = nodes
+            .iter()
+            .zip(["a", "b", "c"])
+            .map(|(nid, name)| (nid.clone(), name))
+            .collect();
+        // The entry for the removed node is not pruned before compacting.
+        graph.remove_node(&nodes[0]);
+        graph.compact_with(&mut [&mut names], &mut []);
+        assert_eq!(names.len(), 2);
+        let mut found: Vec<_> = graph.node_ids().map(|nid| names[&nid]).collect();
+        found.sort();
+        assert_eq!(found, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_compact_with_stable_ids() {
+        let mut graph = LinkedGraph::<(), (), Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let mut names = PropertyMap::new();
+        names.insert(a.clone(), "a");
+        names.insert(b.clone(), "b");
+        graph.remove_node(&a);
+        graph.compact_with(&mut [&mut names], &mut []);
+        assert_eq!(names.len(), 1);
+        assert_eq!(names[&b], "b");
+    }
+}
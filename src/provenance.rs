@@ -0,0 +1,187 @@
+//! Tracking where the nodes and edges of a graph came from.
+//!
+//! When a graph is built by a pipeline of copies and transformations, it can
+//! be hard to tell which element of which input a node or edge came from.  A
+//! [`Provenance`] log records an [`Origin`] for each node and edge produced
+//! by a [`GraphCopier`](crate::GraphCopier) configured with
+//! [`with_provenance`](crate::GraphCopier::with_provenance), without adding
+//! any fields to the node or edge data.
+//!
+//! Source IDs are recorded in their `Debug` form, so a single log can cover
+//! elements copied from graphs of different types, such as when several
+//! graphs are merged into one.
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash, sync::Arc};
+
+/// Where a node or edge came from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Origin {
+    /// The name given to the source graph.
+    pub graph: Arc<str>,
+    /// The ID of the element in the source graph, formatted with `Debug`.
+    pub source: String,
+    /// The name given to the copy or transformation which produced the
+    /// element.
+    pub transform: Arc<str>,
+}
+
+/// A log of the origins of the nodes and edges of a graph, keyed by their
+/// IDs in that graph.
+#[derive(Clone, Debug)]
+pub struct Provenance<N, E> {
+    nodes: HashMap<N, Origin>,
+    edges: HashMap<E, Origin>,
+}
+
+impl<N, E> Provenance<N, E>
+where
+    N: Hash + Eq,
+    E: Hash + Eq,
+{
+    /// Creates an empty log.
+    pub fn new() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+        }
+    }
+
+    /// Gets the origin of a node, if one was recorded.
+    pub fn node(&self, id: &N) -> Option<&Origin> {
+        self.nodes.get(id)
+    }
+
+    /// Gets the origin of an edge, if one was recorded.
+    pub fn edge(&self, id: &E) -> Option<&Origin> {
+        self.edges.get(id)
+    }
+
+    /// Records the origin of a node, replacing any earlier record.
+    pub fn record_node(&mut self, id: N, origin: Origin) {
+        self.nodes.insert(id, origin);
+    }
+
+    /// Records the origin of an edge, replacing any earlier record.
+    pub fn record_edge(&mut self, id: E, origin: Origin) {
+        self.edges.insert(id, origin);
+    }
+
+    /// Gets an iterator over the recorded nodes and their origins, in an
+    /// unspecified order.
+    pub fn nodes(&self) -> impl Iterator<Item = (&N, &Origin)> {
+        self.nodes.iter()
+    }
+
+    /// Gets an iterator over the recorded edges and their origins, in an
+    /// unspecified order.
+    pub fn edges(&self) -> impl Iterator<Item = (&E, &Origin)> {
+        self.edges.iter()
+    }
+
+    /// Removes every record.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+    }
+}
+
+impl<N, E> Default for Provenance<N, E>
+where
+    N: Hash + Eq,
+    E: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`Provenance`] log along with the names to record for each element
+/// copied into it.  Created by
+/// [`GraphCopier::with_provenance`](crate::GraphCopier::with_provenance).
+#[derive(Debug)]
+pub struct ProvenanceRecorder<'a, N, E> {
+    log: &'a mut Provenance<N, E>,
+    graph: Arc<str>,
+    transform: Arc<str>,
+}
+
+impl<'a, N, E> ProvenanceRecorder<'a, N, E>
+where
+    N: Hash + Eq,
+    E: Hash + Eq,
+{
+    pub(crate) fn new(log: &'a mut Provenance<N, E>, graph: &str, transform: &str) -> Self {
+        Self {
+            log,
+            graph: graph.into(),
+            transform: transform.into(),
+        }
+    }
+
+    fn origin(&self, source: &impl Debug) -> Origin {
+        Origin {
+            graph: Arc::clone(&self.graph),
+            source: format!("{source:?}"),
+            transform: Arc::clone(&self.transform),
+        }
+    }
+
+    pub(crate) fn record_node(&mut self, id: N, source: &impl Debug) {
+        let origin = self.origin(source);
+        self.log.record_node(id, origin);
+    }
+
+    pub(crate) fn record_edge(&mut self, id: E, source: &impl Debug) {
+        let origin = self.origin(source);
+        self.log.record_edge(id, origin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, GraphCopier, LinkedGraph, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_pipeline() {
+        let mut source = BagGraph::<u32, u32, Directed>::default();
+        let a = source.add_node(1);
+        let b = source.add_node(2);
+        let ab = source.add_new_edge(&a, &b, 3);
+
+        let mut provenance = Provenance::new();
+        let mut node_map = HashMap::new();
+        let mut doubled: LinkedGraph<u32, u32, Directed> = GraphCopier::new(&source)
+            .transform_nodes(|n| n * 2)
+            .clone_edges()
+            .with_node_map(&mut node_map)
+            .with_provenance(&mut provenance, "input", "double")
+            .copy();
+        assert_eq!(doubled.num_nodes(), 2);
+
+        let origin = provenance.node(&node_map[&b]).unwrap();
+        assert_eq!(&*origin.graph, "input");
+        assert_eq!(origin.source, format!("{b:?}"));
+        assert_eq!(&*origin.transform, "double");
+        let (new_edge, origin) = provenance.edges().next().unwrap();
+        assert_eq!(*doubled.edge_data(new_edge), 3);
+        assert_eq!(origin.source, format!("{ab:?}"));
+
+        // A second stage merges another copy into the same target, and its
+        // elements are recorded in the same log.
+        GraphCopier::new(&source)
+            .clone_nodes()
+            .clone_edges()
+            .with_provenance(&mut provenance, "input", "merge")
+            .copy_into(&mut doubled);
+        assert_eq!(provenance.nodes().count(), 4);
+        let mut merged_transforms: Vec<_> = doubled
+            .node_ids()
+            .filter(|nid| *doubled.node_data(nid) == 2)
+            .map(|nid| provenance.node(&nid).unwrap().transform.to_string())
+            .collect();
+        merged_transforms.sort();
+        assert_eq!(merged_transforms, vec!["double", "merge"]);
+    }
+}
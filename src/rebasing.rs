@@ -0,0 +1,525 @@
+//! Translation of node and edge IDs across compactions.
+//!
+//! Compacting a graph may change the IDs of its nodes and edges, so IDs held
+//! outside the graph, e.g. in a cache, become stale.  A [`RebasingGraph`]
+//! records the ID changes made by its last few compactions, so an ID
+//! obtained before them can be translated to the current ID of the same
+//! node or edge.  IDs are handed out as [`RebasableId`]s, which remember the
+//! compaction epoch they were obtained in, and are translated with
+//! [`RebasingGraph::translate_node`] and [`RebasingGraph::translate_edge`],
+//! or updated in place with [`RebasingGraph::rebase_node`] and
+//! [`RebasingGraph::rebase_edge`].  Translation fails, rather than panicking
+//! or returning the wrong element, if the element has been removed or the
+//! ID is older than the recorded history.
+
+use std::{collections::VecDeque, fmt::Debug};
+
+use crate::{
+    GraphElementId,
+    hashing::FastHashMap,
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A node or edge ID along with the compaction epoch of the
+/// [`RebasingGraph`] it was obtained from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RebasableId<I> {
+    id: I,
+    epoch: u64,
+}
+
+impl<I> RebasableId<I> {
+    /// Returns the ID as it was in its epoch.  It may no longer be valid.
+    pub fn id(&self) -> &I {
+        &self.id
+    }
+
+    /// Returns the epoch the ID belongs to.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+/// The ID changes made by one compaction, with an entry for every node and
+/// edge which survived it.
+struct Compaction<G>
+where
+    G: Graph,
+{
+    node_map: FastHashMap<G::NodeId, G::NodeId>,
+    edge_map: FastHashMap<G::EdgeId, G::EdgeId>,
+}
+
+impl<G> Clone for Compaction<G>
+where
+    G: Graph,
+{
+    fn clone(&self) -> Self {
+        Self {
+            node_map: self.node_map.clone(),
+            edge_map: self.edge_map.clone(),
+        }
+    }
+}
+
+/// A wrapper around a graph which keeps the ID translation tables of its
+/// last few compactions.  See the [module documentation](self).
+///
+/// The epoch starts at zero and increases by one with each call to
+/// [`GraphMut::compact`] or [`GraphMut::clear`]; clearing the graph is
+/// recorded as a compaction which removes everything.
+pub struct RebasingGraph<G>
+where
+    G: Graph,
+{
+    inner: G,
+    epoch: u64,
+    history: VecDeque<Compaction<G>>,
+    max_history: usize,
+}
+
+impl<G> RebasingGraph<G>
+where
+    G: Graph,
+{
+    /// Wraps an existing graph, keeping the translation tables of up to
+    /// `max_history` compactions.
+    pub fn new(inner: G, max_history: usize) -> Self {
+        Self {
+            inner,
+            epoch: 0,
+            history: VecDeque::with_capacity(max_history),
+            max_history,
+        }
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding the translation tables.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Returns the current epoch, i.e. the number of compactions so far.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the maximum number of compactions whose translation tables
+    /// are kept.
+    pub fn max_history(&self) -> usize {
+        self.max_history
+    }
+
+    /// Sets the maximum number of compactions whose translation tables are
+    /// kept, discarding the oldest tables if there are now too many.
+    pub fn set_max_history(&mut self, max_history: usize) {
+        self.max_history = max_history;
+        while self.history.len() > max_history {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns the oldest epoch whose IDs can still be translated.
+    pub fn oldest_epoch(&self) -> u64 {
+        self.epoch - self.history.len() as u64
+    }
+
+    /// Stamps a node ID with the current epoch.
+    pub fn rebasable_node(&self, id: &G::NodeId) -> RebasableId<G::NodeId> {
+        RebasableId {
+            id: id.clone(),
+            epoch: self.epoch,
+        }
+    }
+
+    /// Stamps an edge ID with the current epoch.
+    pub fn rebasable_edge(&self, id: &G::EdgeId) -> RebasableId<G::EdgeId> {
+        RebasableId {
+            id: id.clone(),
+            epoch: self.epoch,
+        }
+    }
+
+    /// Returns the current ID of the node `id` referred to, or `None` if the
+    /// node has been removed or `id` is older than [`Self::oldest_epoch`].
+    pub fn translate_node(&self, id: &RebasableId<G::NodeId>) -> Option<G::NodeId> {
+        let new_id = self.translate(id, |compaction| &compaction.node_map)?;
+        self.inner.check_node_id(&new_id).ok()?;
+        Some(new_id)
+    }
+
+    /// Returns the current ID of the edge `id` referred to, or `None` if the
+    /// edge has been removed or `id` is older than [`Self::oldest_epoch`].
+    pub fn translate_edge(&self, id: &RebasableId<G::EdgeId>) -> Option<G::EdgeId> {
+        let new_id = self.translate(id, |compaction| &compaction.edge_map)?;
+        self.inner.check_edge_id(&new_id).ok()?;
+        Some(new_id)
+    }
+
+    /// Updates `id` to refer to its node in the current epoch.  Returns
+    /// false, leaving `id` unchanged, if it cannot be translated.
+    pub fn rebase_node(&self, id: &mut RebasableId<G::NodeId>) -> bool {
+        self.translate_node(id)
+            .map(|new_id| *id = self.rebasable_node(&new_id))
+            .is_some()
+    }
+
+    /// Updates `id` to refer to its edge in the current epoch.  Returns
+    /// false, leaving `id` unchanged, if it cannot be translated.
+    pub fn rebase_edge(&self, id: &mut RebasableId<G::EdgeId>) -> bool {
+        self.translate_edge(id)
+            .map(|new_id| *id = self.rebasable_edge(&new_id))
+            .is_some()
+    }
+
+    /// Follows `id` through the compactions since its epoch, using the
+    /// table chosen by `table`.
+    fn translate<I>(
+        &self,
+        id: &RebasableId<I>,
+        table: impl Fn(&Compaction<G>) -> &FastHashMap<I, I>,
+    ) -> Option<I>
+    where
+        I: GraphElementId,
+    {
+        if id.epoch > self.epoch || id.epoch < self.oldest_epoch() {
+            return None;
+        }
+        let skip = (id.epoch - self.oldest_epoch()) as usize;
+        self.history
+            .iter()
+            .skip(skip)
+            .try_fold(id.id.clone(), |id, compaction| {
+                table(compaction).get(&id).cloned()
+            })
+    }
+
+    /// Records a compaction, discarding the oldest one if the history is
+    /// full.
+    fn push_compaction(&mut self, compaction: Compaction<G>) {
+        self.epoch += 1;
+        if self.max_history == 0 {
+            return;
+        }
+        if self.history.len() == self.max_history {
+            self.history.pop_front();
+        }
+        self.history.push_back(compaction);
+    }
+}
+
+impl<G> Default for RebasingGraph<G>
+where
+    G: Graph + Default,
+{
+    /// Creates an empty graph which keeps the translation table of its last
+    /// compaction only.
+    fn default() -> Self {
+        Self::new(G::default(), 1)
+    }
+}
+
+impl<G> Clone for RebasingGraph<G>
+where
+    G: Graph + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            epoch: self.epoch,
+            history: self.history.clone(),
+            max_history: self.max_history,
+        }
+    }
+}
+
+impl<G> Debug for RebasingGraph<G>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RebasingGraph")
+            .field("inner", &self.inner)
+            .field("epoch", &self.epoch)
+            .field("history_len", &self.history.len())
+            .field("max_history", &self.max_history)
+            .finish()
+    }
+}
+
+impl<G> Graph for RebasingGraph<G>
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from_into(from, into)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.inner.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.inner.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.inner.num_edges_from(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G> GraphMut for RebasingGraph<G>
+where
+    G: GraphMut,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        self.inner.node_data_mut(id)
+    }
+
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        self.inner.edge_data_mut(id)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.push_compaction(Compaction {
+            node_map: FastHashMap::default(),
+            edge_map: FastHashMap::default(),
+        });
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        self.inner.add_node(data)
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        self.inner.remove_node(id)
+    }
+
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        self.inner.add_edge(from, into, data)
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        self.inner.remove_edge(id)
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        // Some graphs only report the IDs which changed, so the surviving
+        // IDs are listed first to tell unchanged IDs from removed ones.
+        let old_nodes: Vec<_> = self.inner.node_ids().collect();
+        let old_edges: Vec<_> = self.inner.edge_ids().collect();
+        let mut node_map: FastHashMap<G::NodeId, _> = FastHashMap::default();
+        let mut edge_map: FastHashMap<G::EdgeId, _> = FastHashMap::default();
+        self.inner.compact(
+            Some(&mut |old, new| {
+                node_map.insert(old, new);
+            }),
+            Some(&mut |old, new| {
+                edge_map.insert(old, new);
+            }),
+        );
+        let compaction: Compaction<G> = Compaction {
+            node_map: complete_map(old_nodes, node_map),
+            edge_map: complete_map(old_edges, edge_map),
+        };
+        if let Some(collector) = node_map_collector {
+            for (old, new) in &compaction.node_map {
+                collector.insert(old.clone(), new.clone());
+            }
+        }
+        if let Some(collector) = edge_map_collector {
+            for (old, new) in &compaction.edge_map {
+                collector.insert(old.clone(), new.clone());
+            }
+        }
+        self.push_compaction(compaction);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+}
+
+/// Returns a map with an entry for each of `old_ids`, which is its entry in
+/// `changed` if it has one and the ID itself otherwise.
+fn complete_map<I>(old_ids: Vec<I>, mut changed: FastHashMap<I, I>) -> FastHashMap<I, I>
+where
+    I: GraphElementId,
+{
+    old_ids
+        .into_iter()
+        .map(|old| {
+            let new = changed.remove(&old).unwrap_or_else(|| old.clone());
+            (old, new)
+        })
+        .collect()
+}
+
+impl<G> StableNodeIds for RebasingGraph<G> where G: StableNodeIds {}
+
+impl<G> StableEdgeIds for RebasingGraph<G> where G: StableEdgeIds {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_translate_across_compactions() {
+        let mut graph = RebasingGraph::new(BagGraph::<u32, (), Directed>::default(), 2);
+        let nodes: Vec<_> = (0..4).map(|i| graph.add_node(i)).collect();
+        let eid = graph.add_new_edge(&nodes[2], &nodes[3], ());
+        let refs: Vec<_> = nodes.iter().map(|nid| graph.rebasable_node(nid)).collect();
+        let edge_ref = graph.rebasable_edge(&eid);
+
+        graph.remove_node(&nodes[0]);
+        graph.compact(None, None);
+        assert_eq!(graph.epoch(), 1);
+        assert_eq!(graph.translate_node(&refs[0]), None);
+        for (i, node_ref) in refs.iter().enumerate().skip(1) {
+            let nid = graph.translate_node(node_ref).unwrap();
+            assert_eq!(*graph.node_data(&nid), i as u32);
+        }
+        let new_eid = graph.translate_edge(&edge_ref).unwrap();
+        assert_eq!(
+            graph.edge_ends(&new_eid),
+            (
+                graph.translate_node(&refs[2]).unwrap(),
+                graph.translate_node(&refs[3]).unwrap()
+            )
+        );
+
+        // A reference rebased after the first compaction survives two more.
+        let mut rebased = refs[3].clone();
+        assert!(graph.rebase_node(&mut rebased));
+        assert_eq!(rebased.epoch(), 1);
+        let nid = graph.translate_node(&refs[1]).unwrap();
+        graph.remove_node(&nid);
+        graph.compact(None, None);
+        assert_eq!(graph.translate_node(&refs[1]), None);
+        assert!(graph.translate_node(&refs[2]).is_some());
+        graph.compact(None, None);
+        assert_eq!(graph.oldest_epoch(), 1);
+        assert_eq!(graph.translate_node(&refs[2]), None);
+        let nid = graph.translate_node(&rebased).unwrap();
+        assert_eq!(*graph.node_data(&nid), 3);
+    }
+
+    #[test]
+    fn test_unchanged_ids_and_clear() {
+        let mut graph = RebasingGraph::new(LinkedGraph::<u32, (), Undirected>::default(), 4);
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+        let a_ref = graph.rebasable_node(&a);
+        let b_ref = graph.rebasable_node(&b);
+        graph.remove_node(&b);
+        graph.compact(None, None);
+        assert_eq!(graph.translate_node(&a_ref), Some(a));
+        assert_eq!(graph.translate_node(&b_ref), None);
+
+        graph.clear();
+        graph.add_node(3);
+        assert_eq!(graph.epoch(), 2);
+        assert_eq!(graph.translate_node(&a_ref), None);
+
+        graph.set_max_history(0);
+        let c = graph.add_node(4);
+        let c_ref = graph.rebasable_node(&c);
+        assert_eq!(graph.translate_node(&c_ref), Some(c));
+        graph.compact(None, None);
+        assert_eq!(graph.translate_node(&c_ref), None);
+    }
+}
@@ -0,0 +1,222 @@
+//! Directed graphs with the direction of every edge reversed.
+//!
+//! [`GraphDirected::reversed`] creates a [`ReversedGraphView`], which
+//! borrows a directed graph and presents its transpose: each edge from `a`
+//! into `b` in the graph is an edge from `b` into `a` in the view.  Nothing
+//! is copied, and the view has the same node and edge IDs as the graph, so
+//! algorithms which need the transpose temporarily, such as Kosaraju's
+//! algorithm or backward dataflow analyses, can run on the view directly.
+
+use std::fmt::Debug;
+
+use crate::{
+    end_pair::EndPair,
+    format_debug::format_debug,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A view of a directed graph with the direction of every edge reversed.
+/// Created by [`GraphDirected::reversed`].
+///
+/// The view shares the IDs of the underlying graph, so IDs from one can be
+/// used with the other.  Each method is answered by the corresponding method
+/// for the other direction on the underlying graph, so it is as fast as that
+/// method; for example, [`Graph::edges_from`] on the view is
+/// [`Graph::edges_into`] on the graph.
+pub struct ReversedGraphView<'g, G>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    graph: &'g G,
+}
+
+impl<'g, G> ReversedGraphView<'g, G>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    /// Creates a view of `graph` with its edges reversed.
+    pub fn new(graph: &'g G) -> Self {
+        Self { graph }
+    }
+
+    /// Gets the underlying graph.
+    pub fn inner(&self) -> &'g G {
+        self.graph
+    }
+}
+
+impl<G> Clone for ReversedGraphView<'_, G>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<G> Copy for ReversedGraphView<'_, G> where G: Graph<Directedness = Directed> + ?Sized {}
+
+impl<G> Debug for ReversedGraphView<'_, G>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+    G::NodeData: Debug,
+    G::EdgeData: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_debug(self, f, "ReversedGraphView")
+    }
+}
+
+impl<G> Graph for ReversedGraphView<'_, G>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    type Directedness = Directed;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.graph.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.graph.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.graph.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.graph.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.graph.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.graph.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.graph.edge_ids()
+    }
+
+    fn edge_ends(&self, id: &Self::EdgeId) -> (Self::NodeId, Self::NodeId) {
+        let (from, into) = self.graph.edge_ends(id).into_values();
+        (into, from)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.graph.edges_into(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.graph.edges_from(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.graph.edges_from_into(into, from)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.graph.has_edge_from_into(into, from)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.graph.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.graph.num_edges_from(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.graph.num_edges_into(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.graph.is_very_slow()
+    }
+}
+
+impl<G> StableNodeIds for ReversedGraphView<'_, G> where
+    G: StableNodeIds<Directedness = Directed> + ?Sized
+{
+}
+
+impl<G> StableEdgeIds for ReversedGraphView<'_, G> where
+    G: StableEdgeIds<Directedness = Directed> + ?Sized
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LinkedGraph;
+
+    use super::*;
+
+    #[test]
+    fn test_reversed_view() {
+        let mut graph = LinkedGraph::<&str, u32, Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let ab = graph.add_new_edge(&a, &b, 1);
+        let bc = graph.add_new_edge(&b, &c, 2);
+        let cc = graph.add_new_edge(&c, &c, 3);
+
+        let view = graph.reversed();
+        assert_eq!(view.edge_ends(&ab), (b.clone(), a.clone()));
+        assert_eq!(view.edge_data(&bc), &2);
+        assert_eq!(view.edges_from(&b).collect::<Vec<_>>(), vec![ab.clone()]);
+        assert_eq!(view.edges_into(&b).collect::<Vec<_>>(), vec![bc.clone()]);
+        assert_eq!(view.edges_from_into(&c, &b).collect::<Vec<_>>(), vec![bc]);
+        assert!(view.has_edge_from_into(&c, &c));
+        assert!(!view.has_edge_from_into(&a, &b));
+        assert_eq!((view.num_edges_from(&c), view.num_edges_into(&c)), (2, 1));
+        assert_eq!(
+            view.successors(&c).collect::<Vec<_>>(),
+            vec![b.clone(), c.clone()]
+        );
+        assert_eq!(view.predecessors(&a).collect::<Vec<_>>(), vec![b.clone()]);
+        assert_eq!(view.reversed().edge_ends(&cc), (c.clone(), c.clone()));
+        assert_eq!(view.inner().num_edges(), 3);
+    }
+
+    #[test]
+    fn test_reachability() {
+        let mut graph = LinkedGraph::<(), (), Directed>::default();
+        let nodes: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_edge(&nodes[0], &nodes[1], ());
+        graph.add_edge(&nodes[1], &nodes[2], ());
+        graph.add_edge(&nodes[3], &nodes[2], ());
+
+        // The nodes which can reach node 2 are those reachable from it in
+        // the reversed graph.
+        let mut reaching: Vec<_> = graph.reversed().bfs(&nodes[2]).collect();
+        reaching.sort();
+        let mut expected = nodes.clone();
+        expected.sort();
+        assert_eq!(reaching, expected);
+        assert_eq!(
+            graph.reversed().strongly_connected_components().len(),
+            graph.strongly_connected_components().len()
+        );
+    }
+}
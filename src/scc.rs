@@ -0,0 +1,253 @@
+//! Strongly connected components of directed graphs.
+//!
+//! A strongly connected component is a maximal set of nodes in which every
+//! node can be reached from every other.  Every node belongs to exactly one
+//! component, and the edges between components form a directed acyclic graph.
+//!
+//! Components are found with Tarjan's algorithm in `O(V + E)` time, and are
+//! returned in reverse topological order: no edge leads from a component to
+//! an earlier one.  An edge belongs to a component if both of its ends do,
+//! so self-loops belong to the component of their node, and edges between
+//! components belong to none.
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, prelude::*};
+
+/// A strongly connected component of a graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Component<N, E> {
+    /// The nodes of the component, in an unspecified order.
+    pub nodes: Vec<N>,
+    /// The edges of the component, in an unspecified order.
+    pub edges: Vec<E>,
+}
+
+/// Partitions the nodes of `graph` into strongly connected components.
+pub fn strongly_connected_components<G>(graph: &G) -> Vec<Vec<G::NodeId>>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    tarjan(graph, graph.node_ids())
+}
+
+/// Partitions the nodes reachable from `start` into strongly connected
+/// components.  The last component is the one containing `start`.
+pub fn strongly_connected_components_from<G>(graph: &G, start: &G::NodeId) -> Vec<Vec<G::NodeId>>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    tarjan(graph, [start.clone()])
+}
+
+/// Returns the strongly connected component containing `start`.
+pub fn strongly_connected_component<G>(graph: &G, start: &G::NodeId) -> Vec<G::NodeId>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    strongly_connected_components_from(graph, start)
+        .pop()
+        .expect("the component of the start node")
+}
+
+/// Like [`strongly_connected_components`], but also returns the edges of each
+/// component.
+pub fn strongly_connected_components_with_edges<G>(
+    graph: &G,
+) -> Vec<Component<G::NodeId, G::EdgeId>>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    let components = strongly_connected_components(graph);
+    let component_of: FastHashMap<&G::NodeId, usize> = components
+        .iter()
+        .enumerate()
+        .flat_map(|(c, nodes)| nodes.iter().map(move |nid| (nid, c)))
+        .collect();
+    let mut edges: Vec<Vec<G::EdgeId>> = vec![Vec::new(); components.len()];
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        let c = component_of[&from];
+        if c == component_of[&into] {
+            edges[c].push(eid);
+        }
+    }
+    components
+        .into_iter()
+        .zip(edges)
+        .map(|(nodes, edges)| Component { nodes, edges })
+        .collect()
+}
+
+/// Finds the components of the nodes reachable from `roots` with Tarjan's
+/// algorithm.
+fn tarjan<G>(graph: &G, roots: impl IntoIterator<Item = G::NodeId>) -> Vec<Vec<G::NodeId>>
+where
+    G: Graph + ?Sized,
+{
+    // The discovery time of each node, or `None` once its component is done.
+    let mut discovered: FastHashMap<G::NodeId, Option<usize>> = FastHashMap::default();
+    let mut low: Vec<usize> = Vec::new();
+    // Nodes which have been discovered but not assigned to a component.
+    let mut pending: Vec<G::NodeId> = Vec::new();
+    let mut components = Vec::new();
+
+    for root in roots {
+        if discovered.contains_key(&root) {
+            continue;
+        }
+        // Each frame is a node, its discovery time, and its unexplored
+        // successors.
+        let mut stack = vec![(
+            root.clone(),
+            low.len(),
+            graph.successors(&root).collect::<Vec<_>>(),
+        )];
+        discovered.insert(root.clone(), Some(low.len()));
+        low.push(low.len());
+        pending.push(root);
+        while let Some((_, index, successors)) = stack.last_mut() {
+            if let Some(next) = successors.pop() {
+                match discovered.get(&next) {
+                    None => {
+                        let next_index = low.len();
+                        discovered.insert(next.clone(), Some(next_index));
+                        low.push(next_index);
+                        pending.push(next.clone());
+                        let successors = graph.successors(&next).collect();
+                        stack.push((next, next_index, successors));
+                    }
+                    Some(Some(next_index)) => {
+                        low[*index] = low[*index].min(*next_index);
+                    }
+                    // The node belongs to a component which is already done.
+                    Some(None) => {}
+                }
+                continue;
+            }
+
+            let (node, index, _) = stack.pop().unwrap();
+            if let Some((_, parent_index, _)) = stack.last() {
+                low[*parent_index] = low[*parent_index].min(low[index]);
+            }
+            if low[index] == index {
+                let mut component = Vec::new();
+                while let Some(nid) = pending.pop() {
+                    discovered.insert(nid.clone(), None);
+                    let done = nid == node;
+                    component.push(nid);
+                    if done {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    fn as_sets<N: GraphElementId>(components: Vec<Vec<N>>) -> Vec<HashSet<N>> {
+        components
+            .into_iter()
+            .map(|nodes| nodes.into_iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_components_in_reverse_topological_order() {
+        let mut graph = LinkedGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        // {0, 1} -> {2, 3, 4}, with 4 reached only from 3.
+        graph.add_new_edge(&n[0], &n[1], ());
+        graph.add_new_edge(&n[1], &n[0], ());
+        graph.add_new_edge(&n[1], &n[2], ());
+        graph.add_new_edge(&n[2], &n[3], ());
+        graph.add_new_edge(&n[3], &n[4], ());
+        graph.add_new_edge(&n[4], &n[2], ());
+
+        let components = as_sets(strongly_connected_components(&graph));
+        assert_eq!(
+            components,
+            vec![
+                n[2..5].iter().cloned().collect(),
+                n[0..2].iter().cloned().collect(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_isolated_nodes_and_self_loops() {
+        let mut graph = BagGraph::<(), (), Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let ab = graph.add_new_edge(&a, &b, ());
+        let bb = graph.add_new_edge(&b, &b, ());
+
+        let components = strongly_connected_components_with_edges(&graph);
+        assert_eq!(components.len(), 2);
+        assert_eq!(
+            components[0],
+            Component {
+                nodes: vec![b],
+                edges: vec![bb],
+            }
+        );
+        assert_eq!(
+            components[1],
+            Component {
+                nodes: vec![a],
+                edges: vec![],
+            }
+        );
+        assert!(!components.iter().any(|c| c.edges.contains(&ab)));
+    }
+
+    #[test]
+    fn test_components_from() {
+        let mut graph = LinkedGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], ());
+        graph.add_new_edge(&n[1], &n[0], ());
+        graph.add_new_edge(&n[1], &n[2], ());
+        graph.add_new_edge(&n[3], &n[0], ());
+
+        let components = as_sets(strongly_connected_components_from(&graph, &n[1]));
+        assert_eq!(
+            components,
+            vec![
+                HashSet::from([n[2].clone()]),
+                n[0..2].iter().cloned().collect()
+            ]
+        );
+        let component: HashSet<_> = strongly_connected_component(&graph, &n[0])
+            .into_iter()
+            .collect();
+        assert_eq!(component, n[0..2].iter().cloned().collect());
+        assert_eq!(
+            strongly_connected_component(&graph, &n[3]),
+            vec![n[3].clone()]
+        );
+    }
+
+    #[test]
+    fn test_long_cycle() {
+        let mut graph = LinkedGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..10_000).map(|_| graph.add_node(())).collect();
+        for pair in n.windows(2) {
+            graph.add_new_edge(&pair[0], &pair[1], ());
+        }
+        graph.add_new_edge(&n[n.len() - 1], &n[0], ());
+
+        let components = strongly_connected_components_with_edges(&graph);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].nodes.len(), n.len());
+        assert_eq!(components[0].edges.len(), n.len());
+    }
+}
@@ -1,19 +1,97 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::VecDeque;
+#[cfg(feature = "rayon")]
+use std::mem;
 
-use crate::{end_pair::EndPair as _, path::Path};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    end_pair::EndPair as _,
+    hashing::{FastHashMap, FastHashSet},
+    path::Path,
+};
 
 use super::prelude::*;
 
 const DEFAULT_HASH_SET_CAPACITY: usize = 64;
 
+type EdgeFilter<'g, G> = Box<dyn FnMut(&<G as Graph>::EdgeId) -> bool + 'g>;
+
+/// A frame of a [`DfsIterator`]: a node on the current path, the edge it was
+/// reached by, and its edges which have not been examined.
+type DfsFrame<G> = (
+    <G as Graph>::NodeId,
+    Option<<G as Graph>::EdgeId>,
+    Vec<<G as Graph>::EdgeId>,
+);
+
+/// Options for limiting a [`BfsIterator`] or [`DfsIterator`], and the state
+/// needed to detect cycles.
+struct SearchOptions<'g, G: Graph + ?Sized> {
+    max_depth: Option<usize>,
+    edge_filter: Option<EdgeFilter<'g, G>>,
+    /// The back edges found so far, if cycles are being detected.
+    back_edges: Option<Vec<G::EdgeId>>,
+    /// Self-loops already examined in an undirected graph, where they may be
+    /// listed twice.
+    self_loops: FastHashSet<G::EdgeId>,
+}
+
+impl<'g, G> SearchOptions<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    fn new() -> Self {
+        Self {
+            max_depth: None,
+            edge_filter: None,
+            back_edges: None,
+            self_loops: FastHashSet::default(),
+        }
+    }
+
+    /// Returns true if the edges from a node at `depth` should be followed.
+    fn expands(&self, depth: usize) -> bool {
+        self.max_depth.is_none_or(|max_depth| depth < max_depth)
+    }
+
+    /// Returns true if the edge passes the edge filter.
+    fn follows(&mut self, eid: &G::EdgeId) -> bool {
+        self.edge_filter.as_mut().is_none_or(|filter| filter(eid))
+    }
+
+    fn back_edges(&self) -> &[G::EdgeId] {
+        self.back_edges.as_deref().unwrap_or_default()
+    }
+
+    /// Records `eid` as a back edge, unless it is an undirected self-loop
+    /// which has already been recorded.
+    fn add_back_edge(&mut self, graph: &G, eid: G::EdgeId, from: &G::NodeId, into: &G::NodeId) {
+        if !graph.is_directed() && from == into && !self.self_loops.insert(eid.clone()) {
+            return;
+        }
+        if let Some(back_edges) = &mut self.back_edges {
+            back_edges.push(eid);
+        }
+    }
+}
+
 /// Iterator for breadth-first search traversal of a graph.
 ///
 /// Visits nodes in breadth-first order starting from one or more root nodes.
-/// Each node is visited at most once.
+/// Each node is visited at most once.  The search can be limited with
+/// [`max_depth`](Self::max_depth) and [`filter_edges`](Self::filter_edges),
+/// and [`detect_cycles`](Self::detect_cycles) records edges which close a
+/// cycle.  These options should be set before the iteration starts.
 pub struct BfsIterator<'g, G: Graph + ?Sized> {
     graph: &'g G,
-    visited: HashSet<G::NodeId>,
-    queue: VecDeque<G::NodeId>,
+    visited: FastHashSet<G::NodeId>,
+    /// Each entry is a node, its depth, and the edge it was reached by.
+    queue: VecDeque<SearchStep<G>>,
+    options: SearchOptions<'g, G>,
+    /// The edge each visited node was reached by, if cycles are being
+    /// detected in a directed graph.
+    parent_edges: FastHashMap<G::NodeId, G::EdgeId>,
 }
 
 impl<'g, G> BfsIterator<'g, G>
@@ -23,8 +101,79 @@ where
     pub fn new(graph: &'g G, start: Vec<G::NodeId>) -> Self {
         Self {
             graph,
-            visited: HashSet::with_capacity(DEFAULT_HASH_SET_CAPACITY),
-            queue: start.into(),
+            visited: FastHashSet::with_capacity_and_hasher(
+                DEFAULT_HASH_SET_CAPACITY,
+                Default::default(),
+            ),
+            queue: start.into_iter().map(|nid| (nid, 0, None)).collect(),
+            options: SearchOptions::new(),
+            parent_edges: FastHashMap::default(),
+        }
+    }
+
+    /// Only visits nodes within `max_depth` edges of a root node.  Edges
+    /// from the nodes at that depth are not examined.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Only follows the edges for which `filter` returns true.  Other edges
+    /// are ignored, as if they were not in the graph.
+    pub fn filter_edges(mut self, filter: impl FnMut(&G::EdgeId) -> bool + 'g) -> Self {
+        self.options.edge_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Records the edges found during the search which close a cycle, to be
+    /// read with [`Self::back_edges`].
+    ///
+    /// In an undirected graph, every edge which is not in the search tree
+    /// closes a cycle and is recorded.  In a directed graph, an edge is
+    /// recorded if it leads to an ancestor in the search tree, which takes
+    /// time proportional to the depth of the search.  Unlike with
+    /// [`DfsIterator::detect_cycles`], a directed cycle is not always found
+    /// this way, if none of its nodes is an ancestor of the others in the
+    /// breadth-first search tree.
+    pub fn detect_cycles(mut self) -> Self {
+        self.options.back_edges = Some(Vec::new());
+        self
+    }
+
+    /// Gets the edges found so far which close a cycle, in the order they
+    /// were found.  Empty unless [`Self::detect_cycles`] was called.
+    pub fn back_edges(&self) -> &[G::EdgeId] {
+        self.options.back_edges()
+    }
+
+    /// Returns true if an edge from `nid` into the visited node `neighbor`
+    /// closes a cycle.
+    fn closes_cycle(
+        &self,
+        nid: &G::NodeId,
+        neighbor: &G::NodeId,
+        eid: &G::EdgeId,
+        parent_edge: Option<&G::EdgeId>,
+    ) -> bool {
+        if !self.graph.is_directed() {
+            // The edge a node was reached by leads back to its parent.
+            return parent_edge != Some(eid);
+        }
+        let mut ancestor = nid.clone();
+        loop {
+            if ancestor == *neighbor {
+                return true;
+            }
+            match self.parent_edges.get(&ancestor) {
+                Some(edge) => {
+                    ancestor = self
+                        .graph
+                        .edge_ends(edge)
+                        .into_other_value(&ancestor)
+                        .into_inner()
+                }
+                None => return false,
+            }
         }
     }
 }
@@ -36,16 +185,31 @@ where
     type Item = G::NodeId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(nid) = self.queue.pop_front() {
-            if self.visited.contains(&nid) {
+        let graph = self.graph;
+        let detecting = self.options.back_edges.is_some();
+        while let Some((nid, depth, parent_edge)) = self.queue.pop_front() {
+            if !self.visited.insert(nid.clone()) {
                 continue;
             }
-            self.visited.insert(nid.clone());
-            for eid in self.graph.edges_from(&nid) {
-                let ends = self.graph.edge_ends(&eid);
-                let neighbor = ends.into_other_value(&nid).into_inner();
-                if !self.visited.contains(&neighbor) {
-                    self.queue.push_back(neighbor);
+            if let Some(eid) = parent_edge
+                .as_ref()
+                .filter(|_| detecting && graph.is_directed())
+            {
+                self.parent_edges.insert(nid.clone(), eid.clone());
+            }
+            if self.options.expands(depth) {
+                for eid in graph.edges_from(&nid) {
+                    if !self.options.follows(&eid) {
+                        continue;
+                    }
+                    let neighbor = graph.edge_ends(&eid).into_other_value(&nid).into_inner();
+                    if !self.visited.contains(&neighbor) {
+                        self.queue.push_back((neighbor, depth + 1, Some(eid)));
+                    } else if detecting
+                        && self.closes_cycle(&nid, &neighbor, &eid, parent_edge.as_ref())
+                    {
+                        self.options.add_back_edge(graph, eid, &nid, &neighbor);
+                    }
                 }
             }
             return Some(nid);
@@ -60,7 +224,7 @@ where
 /// Each node is visited at most once, and the first path found is returned.
 pub struct BfsIteratorWithPaths<'g, G: Graph + ?Sized> {
     graph: &'g G,
-    visited: HashSet<G::NodeId>,
+    visited: FastHashSet<G::NodeId>,
     queue: VecDeque<Path<'g, G>>,
 }
 
@@ -71,7 +235,10 @@ where
     pub fn new(graph: &'g G, start: Vec<G::NodeId>) -> Self {
         Self {
             graph,
-            visited: HashSet::with_capacity(DEFAULT_HASH_SET_CAPACITY),
+            visited: FastHashSet::with_capacity_and_hasher(
+                DEFAULT_HASH_SET_CAPACITY,
+                Default::default(),
+            ),
             queue: start.into_iter().map(|nid| Path::new(graph, nid)).collect(),
         }
     }
@@ -109,11 +276,18 @@ where
 /// Iterator for depth-first search traversal of a graph.
 ///
 /// Visits nodes in depth-first order starting from one or more root nodes.
-/// Each node is visited at most once.
+/// Each node is visited at most once.  The search can be limited with
+/// [`max_depth`](Self::max_depth) and [`filter_edges`](Self::filter_edges),
+/// and [`detect_cycles`](Self::detect_cycles) records edges which close a
+/// cycle.  These options should be set before the iteration starts.
 pub struct DfsIterator<'g, G: Graph + ?Sized> {
     graph: &'g G,
-    visited: HashSet<G::NodeId>,
-    stack: Vec<G::NodeId>,
+    visited: FastHashSet<G::NodeId>,
+    roots: Vec<G::NodeId>,
+    stack: Vec<DfsFrame<G>>,
+    options: SearchOptions<'g, G>,
+    /// The nodes on the current path, if cycles are being detected.
+    on_path: FastHashSet<G::NodeId>,
 }
 
 impl<'g, G> DfsIterator<'g, G>
@@ -121,14 +295,67 @@ where
     G: Graph + ?Sized,
 {
     pub fn new(graph: &'g G, start: Vec<G::NodeId>) -> Self {
-        let mut stack = start;
-        stack.reverse();
+        let mut roots = start;
+        roots.reverse();
         Self {
             graph,
-            visited: HashSet::with_capacity(DEFAULT_HASH_SET_CAPACITY),
-            stack,
+            visited: FastHashSet::with_capacity_and_hasher(
+                DEFAULT_HASH_SET_CAPACITY,
+                Default::default(),
+            ),
+            roots,
+            stack: Vec::new(),
+            options: SearchOptions::new(),
+            on_path: FastHashSet::default(),
         }
     }
+
+    /// Only visits nodes within `max_depth` edges of a root node along the
+    /// path taken by the search.  Edges from the nodes at that depth are not
+    /// examined.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Only follows the edges for which `filter` returns true.  Other edges
+    /// are ignored, as if they were not in the graph.
+    pub fn filter_edges(mut self, filter: impl FnMut(&G::EdgeId) -> bool + 'g) -> Self {
+        self.options.edge_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Records the back edges found during the search, which lead to a node
+    /// on the current path and so close a cycle, to be read with
+    /// [`Self::back_edges`].  Once the search has finished, the part of the
+    /// graph it explored has a cycle if and only if a back edge was found.
+    /// See [`DfsVisitor`] for a finer classification of edges.
+    pub fn detect_cycles(mut self) -> Self {
+        self.options.back_edges = Some(Vec::new());
+        self
+    }
+
+    /// Gets the back edges found so far, in the order they were found.
+    /// Empty unless [`Self::detect_cycles`] was called.
+    pub fn back_edges(&self) -> &[G::EdgeId] {
+        self.options.back_edges()
+    }
+
+    /// Visits a node, starting a frame for it.
+    fn push(&mut self, nid: G::NodeId, parent_edge: Option<G::EdgeId>) -> G::NodeId {
+        self.visited.insert(nid.clone());
+        if self.options.back_edges.is_some() {
+            self.on_path.insert(nid.clone());
+        }
+        let mut edges = if self.options.expands(self.stack.len()) {
+            self.graph.edges_from(&nid).collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        edges.reverse();
+        self.stack.push((nid.clone(), parent_edge, edges));
+        nid
+    }
 }
 
 impl<'g, G> Iterator for DfsIterator<'g, G>
@@ -138,15 +365,37 @@ where
     type Item = G::NodeId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(nid) = self.stack.pop() {
-            if self.visited.insert(nid.clone()) {
-                let mut successors = self.graph.successors(&nid).collect::<Vec<_>>();
-                successors.reverse();
-                self.stack.extend(successors);
-                return Some(nid);
+        let graph = self.graph;
+        loop {
+            let Some((nid, parent_edge, edges)) = self.stack.last_mut() else {
+                let root = self.roots.pop()?;
+                if self.visited.contains(&root) {
+                    continue;
+                }
+                return Some(self.push(root, None));
+            };
+            let Some(eid) = edges.pop() else {
+                self.on_path.remove(nid);
+                self.stack.pop();
+                continue;
+            };
+            if !self.options.follows(&eid) {
+                continue;
+            }
+            let neighbor = graph.edge_ends(&eid).other_value(nid).into_inner().clone();
+            if !self.visited.contains(&neighbor) {
+                return Some(self.push(neighbor, Some(eid)));
+            }
+            // In an undirected graph, the edge a node was reached by leads
+            // back to its parent, and other edges to finished nodes were
+            // already examined from their other end.
+            if self.on_path.contains(&neighbor)
+                && (graph.is_directed() || parent_edge.as_ref() != Some(&eid))
+            {
+                let nid = nid.clone();
+                self.options.add_back_edge(graph, eid, &nid, &neighbor);
             }
         }
-        None
     }
 }
 
@@ -156,7 +405,7 @@ where
 /// Each node is visited at most once, and the first path found is returned.
 pub struct DfsIteratorWithPaths<'g, G: Graph + ?Sized> {
     graph: &'g G,
-    visited: HashSet<G::NodeId>,
+    visited: FastHashSet<G::NodeId>,
     stack: Vec<Path<'g, G>>,
 }
 
@@ -172,7 +421,10 @@ where
         stack.reverse();
         Self {
             graph,
-            visited: HashSet::with_capacity(DEFAULT_HASH_SET_CAPACITY),
+            visited: FastHashSet::with_capacity_and_hasher(
+                DEFAULT_HASH_SET_CAPACITY,
+                Default::default(),
+            ),
             stack,
         }
     }
@@ -206,8 +458,363 @@ where
     }
 }
 
+/// The item type of [`BfsIteratorWithContext`] and
+/// [`DfsIteratorWithContext`]: a visited node, its depth in the search tree,
+/// and the edge by which it was reached, which is `None` for root nodes.
+pub type SearchStep<G> = (<G as Graph>::NodeId, usize, Option<<G as Graph>::EdgeId>);
+
+/// Iterator for breadth-first search traversal that yields the depth and
+/// parent edge of each node.
+///
+/// Visits nodes in breadth-first order, so the depth of each node is its
+/// distance in edges from the nearest root node.  Each node is visited at most
+/// once.
+pub struct BfsIteratorWithContext<'g, G: Graph + ?Sized> {
+    graph: &'g G,
+    visited: FastHashSet<G::NodeId>,
+    queue: VecDeque<SearchStep<G>>,
+}
+
+impl<'g, G> BfsIteratorWithContext<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    pub fn new(graph: &'g G, start: Vec<G::NodeId>) -> Self {
+        Self {
+            graph,
+            visited: FastHashSet::with_capacity_and_hasher(
+                DEFAULT_HASH_SET_CAPACITY,
+                Default::default(),
+            ),
+            queue: start.into_iter().map(|nid| (nid, 0, None)).collect(),
+        }
+    }
+}
+
+impl<'g, G> Iterator for BfsIteratorWithContext<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    type Item = SearchStep<G>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((nid, depth, parent_edge)) = self.queue.pop_front() {
+            if self.visited.insert(nid.clone()) {
+                for eid in self.graph.edges_from(&nid) {
+                    let neighbor = self
+                        .graph
+                        .edge_ends(&eid)
+                        .into_other_value(&nid)
+                        .into_inner();
+                    if !self.visited.contains(&neighbor) {
+                        self.queue.push_back((neighbor, depth + 1, Some(eid)));
+                    }
+                }
+                return Some((nid, depth, parent_edge));
+            }
+        }
+        None
+    }
+}
+
+/// Iterator for depth-first search traversal that yields the depth and parent
+/// edge of each node.
+///
+/// Visits nodes in depth-first order.  Each node is visited at most once.
+pub struct DfsIteratorWithContext<'g, G: Graph + ?Sized> {
+    graph: &'g G,
+    visited: FastHashSet<G::NodeId>,
+    stack: Vec<SearchStep<G>>,
+}
+
+impl<'g, G> DfsIteratorWithContext<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    pub fn new(graph: &'g G, start: Vec<G::NodeId>) -> Self {
+        let stack = start.into_iter().rev().map(|nid| (nid, 0, None)).collect();
+        Self {
+            graph,
+            visited: FastHashSet::with_capacity_and_hasher(
+                DEFAULT_HASH_SET_CAPACITY,
+                Default::default(),
+            ),
+            stack,
+        }
+    }
+}
+
+impl<'g, G> Iterator for DfsIteratorWithContext<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    type Item = SearchStep<G>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((nid, depth, parent_edge)) = self.stack.pop() {
+            if self.visited.insert(nid.clone()) {
+                let edges = self.graph.edges_from(&nid).collect::<Vec<_>>();
+                for eid in edges.into_iter().rev() {
+                    let neighbor = self
+                        .graph
+                        .edge_ends(&eid)
+                        .into_other_value(&nid)
+                        .into_inner();
+                    if !self.visited.contains(&neighbor) {
+                        self.stack.push((neighbor, depth + 1, Some(eid)));
+                    }
+                }
+                return Some((nid, depth, parent_edge));
+            }
+        }
+        None
+    }
+}
+
+/// Iterator for depth-first search traversal which yields nodes in
+/// post-order, i.e. each node after all the nodes discovered from it.
+///
+/// Each node is visited at most once.  Reversing the post-order of a
+/// directed acyclic graph gives a topological order.
+pub struct DfsPostOrderIterator<'g, G: Graph + ?Sized> {
+    graph: &'g G,
+    visited: FastHashSet<G::NodeId>,
+    roots: Vec<G::NodeId>,
+    /// Each frame is a node and its successors which have not been explored.
+    stack: Vec<(G::NodeId, Vec<G::NodeId>)>,
+}
+
+impl<'g, G> DfsPostOrderIterator<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    pub fn new(graph: &'g G, start: Vec<G::NodeId>) -> Self {
+        let mut roots = start;
+        roots.reverse();
+        Self {
+            graph,
+            visited: FastHashSet::with_capacity_and_hasher(
+                DEFAULT_HASH_SET_CAPACITY,
+                Default::default(),
+            ),
+            roots,
+            stack: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, nid: G::NodeId) {
+        let mut successors = self.graph.successors(&nid).collect::<Vec<_>>();
+        successors.reverse();
+        self.stack.push((nid, successors));
+    }
+}
+
+impl<'g, G> Iterator for DfsPostOrderIterator<'g, G>
+where
+    G: Graph + ?Sized,
+{
+    type Item = G::NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((_, successors)) = self.stack.last_mut() else {
+                let root = self.roots.pop()?;
+                if self.visited.insert(root.clone()) {
+                    self.push(root);
+                }
+                continue;
+            };
+            match successors.pop() {
+                Some(nid) => {
+                    if self.visited.insert(nid.clone()) {
+                        self.push(nid);
+                    }
+                }
+                None => return self.stack.pop().map(|(nid, _)| nid),
+            }
+        }
+    }
+}
+
+/// Callbacks for the events of a depth-first search, used with
+/// [`Graph::depth_first_visit`].  All methods do nothing by default.
+///
+/// Each edge is classified when it is first examined.  In a directed graph,
+/// an edge is a tree edge if it leads to an undiscovered node, a back edge if
+/// it leads to a node which has been discovered but not finished, a forward
+/// edge if it leads to a finished descendant, and a cross edge otherwise.
+/// In an undirected graph, each edge is examined from both ends but reported
+/// only once, as either a tree edge or a back edge.
+pub trait DfsVisitor<G: Graph + ?Sized> {
+    /// Called when a node is first reached.
+    fn discover_node(&mut self, node: &G::NodeId) {
+        let _ = node;
+    }
+
+    /// Called for an edge by which a node is discovered.
+    fn tree_edge(&mut self, edge: &G::EdgeId, from: &G::NodeId, into: &G::NodeId) {
+        let _ = (edge, from, into);
+    }
+
+    /// Called for an edge into an ancestor in the search tree, including a
+    /// self-loop.
+    fn back_edge(&mut self, edge: &G::EdgeId, from: &G::NodeId, into: &G::NodeId) {
+        let _ = (edge, from, into);
+    }
+
+    /// Called for an edge into a descendant in the search tree which has
+    /// already been finished.
+    fn forward_edge(&mut self, edge: &G::EdgeId, from: &G::NodeId, into: &G::NodeId) {
+        let _ = (edge, from, into);
+    }
+
+    /// Called for an edge into a node which is neither an ancestor nor a
+    /// descendant in the search tree.
+    fn cross_edge(&mut self, edge: &G::EdgeId, from: &G::NodeId, into: &G::NodeId) {
+        let _ = (edge, from, into);
+    }
+
+    /// Called when all edges from a node have been examined.
+    fn finish_node(&mut self, node: &G::NodeId) {
+        let _ = node;
+    }
+}
+
+/// Performs a depth-first search from each of the `start` nodes in turn,
+/// reporting events to `visitor`.  See [`Graph::depth_first_visit`].
+pub fn depth_first_visit<G, V>(graph: &G, start: Vec<G::NodeId>, visitor: &mut V)
+where
+    G: Graph + ?Sized,
+    V: DfsVisitor<G> + ?Sized,
+{
+    // The discovery time of each node, and whether it has been finished.
+    let mut discovered: FastHashMap<G::NodeId, (usize, bool)> = FastHashMap::default();
+    let mut self_loops: FastHashSet<G::EdgeId> = FastHashSet::default();
+    for root in start {
+        if discovered.contains_key(&root) {
+            continue;
+        }
+        visitor.discover_node(&root);
+        discovered.insert(root.clone(), (discovered.len(), false));
+        // Each frame is a node, the edge it was reached by, and its
+        // unexplored edges.
+        let mut edges = graph.edges_from(&root).collect::<Vec<_>>();
+        edges.reverse();
+        let mut stack = vec![(root, None, edges)];
+        while let Some((node, parent_edge, edges)) = stack.last_mut() {
+            let Some(eid) = edges.pop() else {
+                visitor.finish_node(node);
+                discovered.get_mut(node).unwrap().1 = true;
+                stack.pop();
+                continue;
+            };
+            let next = graph.edge_ends(&eid).other_value(node).into_inner().clone();
+            match discovered.get(&next) {
+                None => {
+                    visitor.tree_edge(&eid, node, &next);
+                    visitor.discover_node(&next);
+                    discovered.insert(next.clone(), (discovered.len(), false));
+                    let mut edges = graph.edges_from(&next).collect::<Vec<_>>();
+                    edges.reverse();
+                    stack.push((next, Some(eid), edges));
+                }
+                Some(&(_, false)) => {
+                    // In an undirected graph, the edge a node was reached by
+                    // leads back to its parent, and a self-loop may be
+                    // examined twice.
+                    if !graph.is_directed()
+                        && (parent_edge.as_ref() == Some(&eid)
+                            || (next == *node && !self_loops.insert(eid.clone())))
+                    {
+                        continue;
+                    }
+                    visitor.back_edge(&eid, node, &next);
+                }
+                Some(&(next_index, true)) => {
+                    if !graph.is_directed() {
+                        // Already reported as a back edge from `next`.
+                    } else if next_index > discovered[node].0 {
+                        visitor.forward_edge(&eid, node, &next);
+                    } else {
+                        visitor.cross_edge(&eid, node, &next);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Iterator for breadth-first search which expands each level of the search
+/// in parallel.
+///
+/// Yields the nodes at each distance from the root nodes in turn, starting
+/// with the roots themselves, so the index of a level is the unweighted
+/// distance of its nodes.  The edges from the nodes of a level are examined
+/// in parallel, and the nodes they lead to are then deduplicated on the
+/// calling thread.  Nodes within a level are in an unspecified order.
+#[cfg(feature = "rayon")]
+pub struct ParBfsLevels<'g, G: Graph + ?Sized> {
+    graph: &'g G,
+    visited: FastHashSet<G::NodeId>,
+    frontier: Vec<G::NodeId>,
+}
+
+#[cfg(feature = "rayon")]
+impl<'g, G> ParBfsLevels<'g, G>
+where
+    G: Graph + Sync + ?Sized,
+    G::NodeId: Send + Sync,
+{
+    pub fn new(graph: &'g G, start: Vec<G::NodeId>) -> Self {
+        let mut visited = FastHashSet::default();
+        let frontier = start
+            .into_iter()
+            .filter(|nid| visited.insert(nid.clone()))
+            .collect();
+        Self {
+            graph,
+            visited,
+            frontier,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'g, G> Iterator for ParBfsLevels<'g, G>
+where
+    G: Graph + Sync + ?Sized,
+    G::NodeId: Send + Sync,
+{
+    type Item = Vec<G::NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frontier.is_empty() {
+            return None;
+        }
+        let graph = self.graph;
+        let visited = &self.visited;
+        let candidates: Vec<_> = self
+            .frontier
+            .par_iter()
+            .flat_map_iter(|nid| {
+                graph.edges_from(nid).filter_map(move |eid| {
+                    let neighbor = graph.edge_ends(&eid).into_other_value(nid).into_inner();
+                    (!visited.contains(&neighbor)).then_some(neighbor)
+                })
+            })
+            .collect();
+        let next = candidates
+            .into_iter()
+            .filter(|nid| self.visited.insert(nid.clone()))
+            .collect();
+        Some(mem::replace(&mut self.frontier, next))
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use crate::{BagGraph, Directed, GraphMut};
 
     use super::*;
@@ -225,9 +832,9 @@ mod tests {
         let n2 = graph.add_node(2);
         let n3 = graph.add_node(3);
         let edges = vec![
-            graph.add_edge(&n0, &n1, ()).0,
-            graph.add_edge(&n0, &n2, ()).0,
-            graph.add_edge(&n1, &n3, ()).0,
+            graph.add_edge(&n0, &n1, ()).into_edge_id(),
+            graph.add_edge(&n0, &n2, ()).into_edge_id(),
+            graph.add_edge(&n1, &n3, ()).into_edge_id(),
         ];
         (graph, vec![n0, n1, n2, n3], edges)
     }
@@ -242,13 +849,46 @@ mod tests {
         let n1 = graph.add_node(1);
         let n2 = graph.add_node(2);
         let edges = vec![
-            graph.add_edge(&n0, &n1, ()).0,
-            graph.add_edge(&n1, &n2, ()).0,
-            graph.add_edge(&n2, &n0, ()).0,
+            graph.add_edge(&n0, &n1, ()).into_edge_id(),
+            graph.add_edge(&n1, &n2, ()).into_edge_id(),
+            graph.add_edge(&n2, &n0, ()).into_edge_id(),
         ];
         (graph, vec![n0, n1, n2], edges)
     }
 
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_bfs_levels() {
+        let (mut graph, nodes, _) = create_simple_graph();
+        let n4 = graph.add_node(4);
+        graph.add_edge(&nodes[2], &nodes[3], ());
+        graph.add_edge(&nodes[3], &nodes[0], ());
+        graph.add_edge(&n4, &nodes[0], ());
+
+        let levels: Vec<Vec<_>> = ParBfsLevels::new(&graph, vec![nodes[0].clone()])
+            .map(|mut level| {
+                level.sort();
+                level
+            })
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                vec![nodes[0].clone()],
+                vec![nodes[1].clone(), nodes[2].clone()],
+                vec![nodes[3].clone()],
+            ]
+        );
+
+        let levels: Vec<_> =
+            ParBfsLevels::new(&graph, vec![nodes[3].clone(), nodes[3].clone(), n4]).collect();
+        assert_eq!(
+            levels.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 1, 2]
+        );
+        assert!(ParBfsLevels::new(&graph, vec![]).next().is_none());
+    }
+
     #[test]
     fn test_bfs_simple_graph() {
         let (graph, nodes, _) = create_simple_graph();
@@ -349,6 +989,99 @@ mod tests {
         assert_eq!(bfs_visited, dfs_visited);
     }
 
+    #[test]
+    fn test_max_depth() {
+        let (graph, nodes, _) = create_simple_graph();
+        let visited: Vec<_> = graph.bfs(&nodes[0]).max_depth(1).collect();
+        assert_eq!(visited.len(), 3);
+        assert!(!visited.contains(&nodes[3]));
+        let visited: Vec<_> = graph.dfs(&nodes[0]).max_depth(1).collect();
+        assert_eq!(
+            visited,
+            vec![nodes[0].clone(), nodes[1].clone(), nodes[2].clone()]
+        );
+        assert_eq!(graph.dfs(&nodes[0]).max_depth(0).count(), 1);
+    }
+
+    #[test]
+    fn test_filter_edges() {
+        let (graph, nodes, edges) = create_simple_graph();
+        let visited: Vec<_> = graph
+            .bfs(&nodes[0])
+            .filter_edges(|eid| *eid != edges[0])
+            .collect();
+        assert_eq!(visited, vec![nodes[0].clone(), nodes[2].clone()]);
+        let visited: Vec<_> = graph
+            .dfs(&nodes[0])
+            .filter_edges(|eid| *eid != edges[1])
+            .collect();
+        assert_eq!(
+            visited,
+            vec![nodes[0].clone(), nodes[1].clone(), nodes[3].clone()]
+        );
+    }
+
+    #[test]
+    fn test_detect_cycles_directed() {
+        let (graph, nodes, _) = create_simple_graph();
+        let mut bfs = graph.bfs(&nodes[0]).detect_cycles();
+        bfs.by_ref().for_each(drop);
+        assert!(bfs.back_edges().is_empty());
+        let mut dfs = graph.dfs(&nodes[0]).detect_cycles();
+        dfs.by_ref().for_each(drop);
+        assert!(dfs.back_edges().is_empty());
+
+        let (mut graph, nodes, edges) = create_cyclic_graph();
+        let loop_edge = graph.add_edge(&nodes[1], &nodes[1], ()).into_edge_id();
+        let mut bfs = graph.bfs(&nodes[0]).detect_cycles();
+        assert_eq!(bfs.by_ref().count(), 3);
+        assert_eq!(bfs.back_edges(), [loop_edge.clone(), edges[2].clone()]);
+        let mut dfs = graph.dfs(&nodes[0]).detect_cycles();
+        assert_eq!(dfs.by_ref().count(), 3);
+        let mut back_edges = dfs.back_edges().to_vec();
+        back_edges.sort();
+        let mut expected = vec![loop_edge, edges[2].clone()];
+        expected.sort();
+        assert_eq!(back_edges, expected);
+
+        // Without the option, nothing is recorded.
+        let mut dfs = graph.dfs(&nodes[0]);
+        dfs.by_ref().for_each(drop);
+        assert!(dfs.back_edges().is_empty());
+    }
+
+    #[test]
+    fn test_detect_cycles_undirected() {
+        let mut graph = BagGraph::<(), (), crate::Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        let d = graph.add_node(());
+        graph.add_edge(&a, &b, ());
+        graph.add_edge(&b, &c, ());
+        graph.add_edge(&c, &a, ());
+        graph.add_edge(&c, &d, ());
+        let dd = graph.add_edge(&d, &d, ()).into_edge_id();
+
+        let mut bfs = graph.bfs(&a).detect_cycles();
+        assert_eq!(bfs.by_ref().count(), 4);
+        assert_eq!(bfs.back_edges().len(), 2);
+        assert!(bfs.back_edges().contains(&dd));
+        let mut dfs = graph.dfs(&a).detect_cycles();
+        assert_eq!(dfs.by_ref().count(), 4);
+        assert_eq!(dfs.back_edges().len(), 2);
+        assert!(dfs.back_edges().contains(&dd));
+
+        // A tree has no back edges, even though each edge leads back to the
+        // node it was reached from.
+        let mut dfs = graph
+            .dfs(&a)
+            .filter_edges(|eid| *eid != dd && graph.edge_ends(eid) != (a.clone(), c.clone()).into())
+            .detect_cycles();
+        dfs.by_ref().for_each(drop);
+        assert!(dfs.back_edges().is_empty());
+    }
+
     #[test]
     fn test_bfs_wth_paths() {
         let (graph, nodes, edges) = create_simple_graph();
@@ -387,4 +1120,186 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_bfs_with_context() {
+        let (graph, nodes, edges) = create_simple_graph();
+        let visited: Vec<_> = BfsIteratorWithContext::new(&graph, vec![nodes[0].clone()]).collect();
+        assert_eq!(
+            visited,
+            vec![
+                (nodes[0].clone(), 0, None),
+                (nodes[1].clone(), 1, Some(edges[0].clone())),
+                (nodes[2].clone(), 1, Some(edges[1].clone())),
+                (nodes[3].clone(), 2, Some(edges[2].clone())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bfs_with_context_multiple_start_nodes() {
+        let (graph, nodes, edges) = create_simple_graph();
+        let visited: Vec<_> =
+            BfsIteratorWithContext::new(&graph, vec![nodes[1].clone(), nodes[2].clone()]).collect();
+        assert_eq!(
+            visited,
+            vec![
+                (nodes[1].clone(), 0, None),
+                (nodes[2].clone(), 0, None),
+                (nodes[3].clone(), 1, Some(edges[2].clone())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dfs_with_context() {
+        let (graph, nodes, edges) = create_simple_graph();
+        let visited: Vec<_> = DfsIteratorWithContext::new(&graph, vec![nodes[0].clone()]).collect();
+        assert_eq!(
+            visited,
+            vec![
+                (nodes[0].clone(), 0, None),
+                (nodes[1].clone(), 1, Some(edges[0].clone())),
+                (nodes[3].clone(), 2, Some(edges[2].clone())),
+                (nodes[2].clone(), 1, Some(edges[1].clone())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dfs_with_context_handles_cycles() {
+        let (graph, nodes, edges) = create_cyclic_graph();
+        let visited: Vec<_> = DfsIteratorWithContext::new(&graph, vec![nodes[0].clone()]).collect();
+        assert_eq!(visited.len(), 3);
+        assert_eq!(visited[2], (nodes[2].clone(), 2, Some(edges[1].clone())));
+    }
+
+    #[test]
+    fn test_dfs_post_order() {
+        let (graph, nodes, _) = create_simple_graph();
+        let visited: Vec<_> = graph.dfs_post_order(&nodes[0]).collect();
+        assert_eq!(
+            visited,
+            vec![
+                nodes[3].clone(),
+                nodes[1].clone(),
+                nodes[2].clone(),
+                nodes[0].clone()
+            ]
+        );
+
+        let (graph, nodes, _) = create_cyclic_graph();
+        let visited: Vec<_> = graph
+            .dfs_post_order_multi(vec![nodes[1].clone(), nodes[0].clone()])
+            .collect();
+        assert_eq!(
+            visited,
+            vec![nodes[0].clone(), nodes[2].clone(), nodes[1].clone()]
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Event<N, E> {
+        Discover(N),
+        Tree(E),
+        Back(E),
+        Forward(E),
+        Cross(E),
+        Finish(N),
+    }
+
+    struct Recorder<N, E>(Vec<Event<N, E>>);
+
+    impl<G: Graph> DfsVisitor<G> for Recorder<G::NodeId, G::EdgeId> {
+        fn discover_node(&mut self, node: &G::NodeId) {
+            self.0.push(Event::Discover(node.clone()));
+        }
+
+        fn tree_edge(&mut self, edge: &G::EdgeId, _: &G::NodeId, _: &G::NodeId) {
+            self.0.push(Event::Tree(edge.clone()));
+        }
+
+        fn back_edge(&mut self, edge: &G::EdgeId, _: &G::NodeId, _: &G::NodeId) {
+            self.0.push(Event::Back(edge.clone()));
+        }
+
+        fn forward_edge(&mut self, edge: &G::EdgeId, _: &G::NodeId, _: &G::NodeId) {
+            self.0.push(Event::Forward(edge.clone()));
+        }
+
+        fn cross_edge(&mut self, edge: &G::EdgeId, _: &G::NodeId, _: &G::NodeId) {
+            self.0.push(Event::Cross(edge.clone()));
+        }
+
+        fn finish_node(&mut self, node: &G::NodeId) {
+            self.0.push(Event::Finish(node.clone()));
+        }
+    }
+
+    #[test]
+    fn test_depth_first_visit_directed() {
+        let (mut graph, n, e) = create_simple_graph();
+        let e03 = graph.add_edge(&n[0], &n[3], ()).into_edge_id();
+        let e23 = graph.add_edge(&n[2], &n[3], ()).into_edge_id();
+        let e30 = graph.add_edge(&n[3], &n[0], ()).into_edge_id();
+
+        let mut recorder = Recorder(Vec::new());
+        graph.depth_first_visit(&n[0], &mut recorder);
+        use Event::*;
+        assert_eq!(
+            recorder.0,
+            vec![
+                Discover(n[0].clone()),
+                Tree(e[0].clone()),
+                Discover(n[1].clone()),
+                Tree(e[2].clone()),
+                Discover(n[3].clone()),
+                Back(e30),
+                Finish(n[3].clone()),
+                Finish(n[1].clone()),
+                Tree(e[1].clone()),
+                Discover(n[2].clone()),
+                Cross(e23),
+                Finish(n[2].clone()),
+                Forward(e03),
+                Finish(n[0].clone()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_depth_first_visit_undirected() {
+        let mut graph = BagGraph::<(), (), crate::Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        let ab = graph.add_edge(&a, &b, ()).into_edge_id();
+        let bc = graph.add_edge(&b, &c, ()).into_edge_id();
+        let ca = graph.add_edge(&c, &a, ()).into_edge_id();
+        let cc = graph.add_edge(&c, &c, ()).into_edge_id();
+
+        let mut recorder = Recorder(Vec::new());
+        graph.depth_first_visit(&a, &mut recorder);
+        let mut tree = Vec::new();
+        let mut back = Vec::new();
+        for event in recorder.0.iter() {
+            match event {
+                Event::Tree(eid) => tree.push(eid.clone()),
+                Event::Back(eid) => back.push(eid.clone()),
+                _ => {}
+            }
+        }
+        // Each edge is reported once; the self-loop and one edge of the
+        // triangle are back edges.
+        assert_eq!(tree.len(), 2);
+        let mut all: Vec<_> = tree.iter().chain(back.iter()).cloned().collect();
+        all.sort();
+        assert_eq!(all, vec![ab, bc, ca, cc]);
+        assert!(
+            !recorder
+                .0
+                .iter()
+                .any(|event| matches!(event, Event::Forward(_) | Event::Cross(_)))
+        );
+    }
 }
@@ -0,0 +1,459 @@
+//! Shortest path trees.
+//!
+//! A [`ShortestPathTree`] records, for every node reachable from a root, the
+//! edge by which a shortest path from the root arrives at it.  This is much
+//! more compact than storing a separate [`Path`] for every node, and is the
+//! form routing tables and similar consumers usually want.  The tree can
+//! also be used as a [`Graph`] in its own right through
+//! [`ShortestPathTree::as_graph`].
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt::Debug,
+    ops::Add,
+};
+
+use crate::{
+    end_pair::EndPair,
+    format_debug::format_debug,
+    hashing::{FastHashMap, FastHashSet},
+    path::Path,
+    prelude::*,
+};
+
+/// The shortest paths from a root node to every node reachable from it, as
+/// found by [`shortest_path_tree`].
+#[derive(Clone, Debug)]
+pub struct ShortestPathTree<N, E, C> {
+    /// The node the paths start from.
+    pub root: N,
+    /// For each reachable node, the last edge of a shortest path to it and
+    /// the node that edge comes from.  The root is mapped to `None`.
+    pub parents: HashMap<N, Option<(E, N)>>,
+    /// The length of a shortest path to each reachable node.
+    pub distances: HashMap<N, C>,
+}
+
+impl<N, E, C> ShortestPathTree<N, E, C>
+where
+    N: GraphElementId,
+    E: GraphElementId,
+    C: Copy,
+{
+    /// Returns true if `node` is reachable from the root.
+    pub fn contains(&self, node: &N) -> bool {
+        self.parents.contains_key(node)
+    }
+
+    /// Returns the last edge of a shortest path to `node` and the node it
+    /// comes from, or `None` for the root and unreachable nodes.
+    pub fn parent(&self, node: &N) -> Option<&(E, N)> {
+        self.parents.get(node)?.as_ref()
+    }
+
+    /// Returns the length of a shortest path to `node`, or `None` if it is
+    /// unreachable.
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances.get(node).copied()
+    }
+
+    /// Returns the edges of the tree, in an unspecified order.
+    pub fn edges(&self) -> impl Iterator<Item = &E> {
+        self.parents.values().flatten().map(|(eid, _)| eid)
+    }
+
+    /// Returns the first edge of a shortest path to `node`, i.e. the next hop
+    /// from the root, or `None` for the root and unreachable nodes.
+    pub fn first_edge(&self, node: &N) -> Option<&E> {
+        let mut first = None;
+        let mut current = node;
+        while let Some((eid, parent)) = self.parent(current) {
+            first = Some(eid);
+            current = parent;
+        }
+        first
+    }
+
+    /// Returns a shortest path to `node`, or `None` if it is unreachable.
+    pub fn path_to<'g, G>(&self, graph: &'g G, node: &N) -> Option<Path<'g, G>>
+    where
+        G: Graph<NodeId = N, EdgeId = E> + ?Sized,
+    {
+        self.contains(node)
+            .then(|| Path::from_predecessors(graph, &self.parents, node))
+    }
+
+    /// Returns a view of the tree as a directed graph, with an edge from
+    /// each node to each of its children, even if the tree was found in an
+    /// undirected graph.  The nodes and edges have the same IDs as in the
+    /// graph the tree was found in, the data of each node is its distance
+    /// from the root, and edges have no data.  Building the view takes time
+    /// and space proportional to the number of nodes in the tree.
+    pub fn as_graph(&self) -> ShortestPathTreeGraph<'_, N, E, C> {
+        let mut children: FastHashMap<N, Vec<(E, N)>> = FastHashMap::default();
+        let mut heads =
+            FastHashMap::with_capacity_and_hasher(self.parents.len(), Default::default());
+        for (node, parent) in &self.parents {
+            if let Some((eid, parent)) = parent {
+                children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push((eid.clone(), node.clone()));
+                heads.insert(eid.clone(), node.clone());
+            }
+        }
+        ShortestPathTreeGraph {
+            tree: self,
+            children,
+            heads,
+        }
+    }
+
+    /// Returns a renderer which draws the graph as `renderer` does, with the
+    /// edges of the tree highlighted.
+    #[cfg(feature = "dot")]
+    pub fn dot_overlay<'r, R>(
+        &self,
+        renderer: &'r R,
+    ) -> crate::dot::overlay::HighlightEdges<'r, R, E> {
+        crate::dot::overlay::HighlightEdges::new(renderer, self.edges().cloned())
+    }
+}
+
+/// A view of a [`ShortestPathTree`] as a directed graph.  Created by
+/// [`ShortestPathTree::as_graph`].
+pub struct ShortestPathTreeGraph<'t, N, E, C> {
+    tree: &'t ShortestPathTree<N, E, C>,
+    /// The edges from each node to its children, with the children.
+    children: FastHashMap<N, Vec<(E, N)>>,
+    /// The child each edge leads to.
+    heads: FastHashMap<E, N>,
+}
+
+impl<'t, N, E, C> ShortestPathTreeGraph<'t, N, E, C> {
+    /// Gets the tree.
+    pub fn tree(&self) -> &'t ShortestPathTree<N, E, C> {
+        self.tree
+    }
+}
+
+impl<N, E, C> Debug for ShortestPathTreeGraph<'_, N, E, C>
+where
+    N: GraphElementId,
+    E: GraphElementId,
+    C: Copy + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_debug(self, f, "ShortestPathTreeGraph")
+    }
+}
+
+impl<N, E, C> Graph for ShortestPathTreeGraph<'_, N, E, C>
+where
+    N: GraphElementId,
+    E: GraphElementId,
+    C: Copy,
+{
+    type Directedness = Directed;
+    type EdgeMultiplicity = SingleEdge;
+    type NodeData = C;
+    type EdgeData = ();
+    type NodeId = N;
+    type EdgeId = E;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.tree.parents.keys().cloned()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.tree.distances.get(id).expect("no such node")
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.tree.parents.len()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        if self.tree.contains(id) {
+            Ok(())
+        } else {
+            Err(GraphError::InvalidNodeId)
+        }
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        assert!(self.heads.contains_key(id), "no such edge");
+        &()
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        if self.heads.contains_key(id) {
+            Ok(())
+        } else {
+            Err(GraphError::InvalidEdgeId)
+        }
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.heads.keys().cloned()
+    }
+
+    fn edge_ends(&self, id: &Self::EdgeId) -> (Self::NodeId, Self::NodeId) {
+        let child = self.heads.get(id).expect("no such edge");
+        let (_, parent) = self.tree.parent(child).expect("edge has no parent");
+        (parent.clone(), child.clone())
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.children
+            .get(from)
+            .into_iter()
+            .flatten()
+            .map(|(eid, _)| eid.clone())
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.tree
+            .parent(into)
+            .map(|(eid, _)| eid.clone())
+            .into_iter()
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.tree
+            .parent(into)
+            .filter(|(_, parent)| parent == from)
+            .map(|(eid, _)| eid.clone())
+            .into_iter()
+    }
+
+    fn num_edges(&self) -> usize {
+        self.heads.len()
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.children.get(from).map_or(0, Vec::len)
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        usize::from(self.tree.parent(into).is_some())
+    }
+}
+
+/// Finds a shortest path tree rooted at `start` using Dijkstra's algorithm.
+/// Edge lengths must not be negative; use
+/// [`NonNegative`](crate::preconditions::NonNegative) lengths to have them
+/// checked.  Runs in `O((V + E) log V)` time.  To avoid allocating new
+/// tables for each call, use
+/// [`AlgoArena::shortest_path_tree`](crate::arena::AlgoArena::shortest_path_tree)
+/// instead.
+pub fn shortest_path_tree<G, C>(
+    graph: &G,
+    start: &G::NodeId,
+    distance_fn: impl Fn(&G::EdgeId) -> C,
+) -> ShortestPathTree<G::NodeId, G::EdgeId, C>
+where
+    G: Graph + ?Sized,
+    C: Default + Ord + Copy + Add<Output = C>,
+{
+    let mut tree = ShortestPathTree {
+        root: start.clone(),
+        parents: HashMap::new(),
+        distances: HashMap::new(),
+    };
+    dijkstra(
+        graph,
+        start,
+        None,
+        distance_fn,
+        &mut tree,
+        &mut FastHashSet::default(),
+        &mut BinaryHeap::new(),
+    );
+    tree
+}
+
+/// Runs Dijkstra's algorithm from `start`, storing the result in `tree`.
+/// The search stops once the distance to `goal` is known, if it is given.
+/// The tables passed in are cleared first, so their allocations can be
+/// reused between calls.
+pub(crate) fn dijkstra<G, C>(
+    graph: &G,
+    start: &G::NodeId,
+    goal: Option<&G::NodeId>,
+    distance_fn: impl Fn(&G::EdgeId) -> C,
+    tree: &mut ShortestPathTree<G::NodeId, G::EdgeId, C>,
+    visited: &mut FastHashSet<G::NodeId>,
+    heap: &mut BinaryHeap<Reverse<(C, G::NodeId)>>,
+) where
+    G: Graph + ?Sized,
+    C: Default + Ord + Copy + Add<Output = C>,
+{
+    // Stale heap entries are skipped when popped rather than being removed
+    // when a shorter distance is found.
+    let ShortestPathTree {
+        root,
+        parents,
+        distances,
+    } = tree;
+    root.clone_from(start);
+    parents.clear();
+    distances.clear();
+    visited.clear();
+    heap.clear();
+
+    distances.insert(start.clone(), C::default());
+    parents.insert(start.clone(), None);
+    heap.push(Reverse((C::default(), start.clone())));
+
+    while let Some(Reverse((current_dist, current_node))) = heap.pop() {
+        if !visited.insert(current_node.clone()) {
+            continue;
+        }
+        if goal == Some(&current_node) {
+            break;
+        }
+
+        for edge_id in graph.edges_from(&current_node) {
+            let ends = graph.edge_ends(&edge_id);
+            let neighbor = ends.other_value(&current_node).into_inner();
+            if visited.contains(neighbor) {
+                continue;
+            }
+            let new_dist = current_dist + distance_fn(&edge_id);
+            let should_update = distances
+                .get(neighbor)
+                .is_none_or(|&old_dist| new_dist < old_dist);
+            if should_update {
+                distances.insert(neighbor.clone(), new_dist);
+                parents.insert(neighbor.clone(), Some((edge_id, current_node.clone())));
+                heap.push(Reverse((new_dist, neighbor.clone())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_directed_tree() {
+        let mut graph = BagGraph::<(), u32, Directed>::default();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        let e01 = graph.add_new_edge(&n[0], &n[1], 1);
+        let e02 = graph.add_new_edge(&n[0], &n[2], 5);
+        let e12 = graph.add_new_edge(&n[1], &n[2], 1);
+        let e23 = graph.add_new_edge(&n[2], &n[3], 1);
+        graph.add_new_edge(&n[4], &n[0], 1);
+
+        let tree = graph.shortest_path_tree(&n[0], |eid| *graph.edge_data(eid));
+        assert_eq!(tree.root, n[0]);
+        assert_eq!(tree.parent(&n[0]), None);
+        assert_eq!(tree.parent(&n[2]), Some(&(e12.clone(), n[1].clone())));
+        assert_eq!(tree.distance(&n[3]), Some(3));
+        assert!(!tree.contains(&n[4]));
+        assert_eq!(tree.distance(&n[4]), None);
+        assert_eq!(tree.first_edge(&n[3]), Some(&e01));
+        assert_eq!(tree.first_edge(&n[0]), None);
+
+        let mut edges: Vec<_> = tree.edges().cloned().collect();
+        edges.sort();
+        assert_eq!(edges, vec![e01.clone(), e12.clone(), e23.clone()]);
+        assert!(!edges.contains(&e02));
+
+        let path = tree.path_to(&graph, &n[3]).unwrap();
+        assert_eq!(path.edges().collect::<Vec<_>>(), vec![e01, e12, e23]);
+        assert_eq!(tree.path_to(&graph, &n[0]).unwrap().edges().count(), 0);
+        assert!(tree.path_to(&graph, &n[4]).is_none());
+    }
+
+    #[test]
+    fn test_as_graph() {
+        let mut graph = LinkedGraph::<(), u32, Undirected>::default();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        let e01 = graph.add_new_edge(&n[0], &n[1], 2);
+        let e12 = graph.add_new_edge(&n[2], &n[1], 1);
+        let e13 = graph.add_new_edge(&n[1], &n[3], 4);
+        graph.add_new_edge(&n[0], &n[3], 9);
+
+        let tree = graph.shortest_path_tree(&n[0], |eid| *graph.edge_data(eid));
+        let tree_graph = tree.as_graph();
+        assert_eq!(tree_graph.validate(), vec![]);
+        assert_eq!(tree_graph.num_nodes(), 4);
+        assert_eq!(tree_graph.num_edges(), 3);
+        assert_eq!(*tree_graph.node_data(&n[3]), 6);
+        assert_eq!(
+            tree_graph.check_node_id(&n[4]),
+            Err(GraphError::InvalidNodeId)
+        );
+        assert_eq!(tree_graph.edge_ends(&e12), (n[1].clone(), n[2].clone()));
+        let mut children: Vec<_> = tree_graph.successors(&n[1]).collect();
+        let mut expected = vec![n[2].clone(), n[3].clone()];
+        children.sort();
+        expected.sort();
+        assert_eq!(children, expected);
+        assert_eq!(tree_graph.edges_into(&n[1]).collect::<Vec<_>>(), vec![e01]);
+        assert!(tree_graph.has_edge_from_into(&n[1], &n[3]));
+        assert!(!tree_graph.has_edge_from_into(&n[3], &n[1]));
+        assert_eq!(tree_graph.edges_from(&n[3]).count(), 0);
+        assert!(tree_graph.check_edge_id(&e13).is_ok());
+        assert_eq!(tree_graph.dfs(&n[0]).count(), tree_graph.num_nodes());
+    }
+
+    #[test]
+    fn test_undirected_tree() {
+        let mut graph = LinkedGraph::<(), u32, Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        let ab = graph.add_new_edge(&a, &b, 2);
+        let cb = graph.add_new_edge(&c, &b, 2);
+        graph.add_new_edge(&c, &a, 7);
+
+        let tree = graph.shortest_path_tree(&a, |eid| *graph.edge_data(eid));
+        assert_eq!(tree.parent(&c), Some(&(cb, b.clone())));
+        assert_eq!(tree.distance(&c), Some(4));
+        assert_eq!(tree.first_edge(&c), Some(&ab));
+    }
+
+    #[cfg(feature = "dot")]
+    #[test]
+    fn test_dot_overlay() {
+        use std::convert::Infallible;
+
+        use crate::dot::renderer::DotRenderer;
+
+        struct PlainRenderer;
+
+        impl<G: Graph> DotRenderer<G> for PlainRenderer {
+            type Error = Infallible;
+        }
+
+        let mut graph = BagGraph::<(), u32, Directed>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_new_edge(&a, &b, 1);
+        graph.add_new_edge(&b, &a, 1);
+
+        let tree = graph.shortest_path_tree(&b, |eid| *graph.edge_data(eid));
+        let dot = graph
+            .to_dot_string(&tree.dot_overlay(&PlainRenderer))
+            .unwrap();
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n1 -> n0 [color = red, penwidth = 2];"));
+    }
+}
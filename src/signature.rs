@@ -0,0 +1,189 @@
+//! Structural fingerprints of node neighborhoods.
+//!
+//! The signature of a node summarizes the structure of the graph within a
+//! given number of steps of it, in the manner of the Weisfeiler-Lehman
+//! graph isomorphism test truncated after `depth` rounds.  Every node starts
+//! with the same label, and in each round a node's label is replaced by a
+//! hash of its label and the multisets of labels of its successors and
+//! predecessors.  Node and edge data are ignored.
+//!
+//! Nodes whose neighborhoods are isomorphic always have equal signatures, so
+//! equal signatures are a cheap way to find candidate duplicates, such as
+//! copy-pasted subsystems in a dependency graph.  Since the signatures are
+//! hashes, and the Weisfeiler-Lehman test cannot tell apart some
+//! non-isomorphic neighborhoods, nodes with equal signatures are not
+//! guaranteed to have isomorphic neighborhoods.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use crate::{
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+};
+
+/// The label every node has before the first round.
+const INITIAL_LABEL: u64 = 0;
+
+/// Returns the signature of `node` after `depth` rounds of refinement.  A
+/// depth of 0 gives every node the same signature.
+pub fn node_signature<G>(graph: &G, node: &G::NodeId, depth: usize) -> u64
+where
+    G: Graph + ?Sized,
+{
+    // Only the nodes within `depth` steps of `node` can affect its label.
+    let mut ball: FastHashSet<_> = [node.clone()].into_iter().collect();
+    let mut queue = VecDeque::from([(node.clone(), 0)]);
+    while let Some((nid, distance)) = queue.pop_front() {
+        if distance == depth {
+            continue;
+        }
+        for other in graph.successors(&nid).chain(graph.predecessors(&nid)) {
+            if ball.insert(other.clone()) {
+                queue.push_back((other, distance + 1));
+            }
+        }
+    }
+    let labels = refine(graph, ball.into_iter().collect(), depth);
+    labels[node]
+}
+
+/// Returns the signature of every node after `depth` rounds of refinement.
+pub fn node_signatures<G>(graph: &G, depth: usize) -> HashMap<G::NodeId, u64>
+where
+    G: Graph + ?Sized,
+{
+    refine(graph, graph.node_ids().collect(), depth)
+}
+
+/// Groups the nodes with equal signatures after `depth` rounds of
+/// refinement, returning the groups with at least `min_group_size` nodes.
+/// Each group is sorted, and the groups are sorted by their first node.
+pub fn find_similar_nodes<G>(graph: &G, depth: usize, min_group_size: usize) -> Vec<Vec<G::NodeId>>
+where
+    G: Graph + ?Sized,
+{
+    let mut groups: FastHashMap<u64, Vec<G::NodeId>> = FastHashMap::default();
+    for (nid, signature) in node_signatures(graph, depth) {
+        groups.entry(signature).or_default().push(nid);
+    }
+    let mut groups: Vec<_> = groups
+        .into_values()
+        .filter(|group| group.len() >= min_group_size)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
+/// Computes the labels of `nodes` after `depth` rounds.  Neighbors outside
+/// `nodes` are treated as having the initial label.
+fn refine<G>(graph: &G, nodes: Vec<G::NodeId>, depth: usize) -> HashMap<G::NodeId, u64>
+where
+    G: Graph + ?Sized,
+{
+    let mut labels: HashMap<_, _> = nodes
+        .iter()
+        .map(|nid| (nid.clone(), INITIAL_LABEL))
+        .collect();
+    let label_of = |labels: &HashMap<G::NodeId, u64>, nid: &G::NodeId| {
+        labels.get(nid).copied().unwrap_or(INITIAL_LABEL)
+    };
+    for _ in 0..depth {
+        labels = nodes
+            .iter()
+            .map(|nid| {
+                let mut successors: Vec<_> = graph
+                    .successors(nid)
+                    .map(|other| label_of(&labels, &other))
+                    .collect();
+                successors.sort_unstable();
+                let mut predecessors: Vec<_> = if graph.is_directed() {
+                    graph
+                        .predecessors(nid)
+                        .map(|other| label_of(&labels, &other))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                predecessors.sort_unstable();
+
+                let mut hasher = DefaultHasher::new();
+                labels[nid].hash(&mut hasher);
+                successors.hash(&mut hasher);
+                predecessors.hash(&mut hasher);
+                (nid.clone(), hasher.finish())
+            })
+            .collect();
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_copied_subsystems() {
+        // Two copies of a -> b -> c, with the first `a` also depending on a
+        // shared node.
+        let mut graph = LinkedGraph::<&str, (), Directed>::default();
+        let a1 = graph.add_node("a1");
+        let b1 = graph.add_node("b1");
+        let c1 = graph.add_node("c1");
+        let a2 = graph.add_node("a2");
+        let b2 = graph.add_node("b2");
+        let c2 = graph.add_node("c2");
+        let shared = graph.add_node("shared");
+        graph.add_new_edge(&a1, &b1, ());
+        graph.add_new_edge(&b1, &c1, ());
+        graph.add_new_edge(&a2, &b2, ());
+        graph.add_new_edge(&b2, &c2, ());
+        graph.add_new_edge(&a1, &shared, ());
+
+        assert_eq!(graph.node_signature(&c1, 0), graph.node_signature(&a1, 0));
+        assert_eq!(graph.node_signature(&c1, 1), graph.node_signature(&c2, 1));
+        assert_ne!(graph.node_signature(&c1, 1), graph.node_signature(&a2, 1));
+        // The shared dependency is two steps from `b1`.
+        assert_eq!(graph.node_signature(&b1, 1), graph.node_signature(&b2, 1));
+        assert_ne!(graph.node_signature(&b1, 2), graph.node_signature(&b2, 2));
+
+        // Signatures of single nodes agree with those of the whole graph.
+        let signatures = node_signatures(&graph, 2);
+        for nid in graph.node_ids() {
+            assert_eq!(signatures[&nid], graph.node_signature(&nid, 2));
+        }
+
+        let mut expected = vec![c1, c2];
+        expected.sort();
+        assert_eq!(graph.find_similar_nodes(2, 2), vec![expected]);
+    }
+
+    #[test]
+    fn test_undirected() {
+        let mut graph = BagGraph::<(), (), Undirected>::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        for i in 0..4 {
+            graph.add_new_edge(&n[i], &n[(i + 1) % 4], ());
+        }
+        let groups = graph.find_similar_nodes(3, 1);
+        assert_eq!(groups, vec![n.clone()]);
+
+        graph.add_new_edge(&n[0], &n[2], ());
+        let groups = graph.find_similar_nodes(1, 2);
+        assert_eq!(
+            groups,
+            vec![
+                vec![n[0].clone(), n[2].clone()],
+                vec![n[1].clone(), n[3].clone()]
+            ]
+        );
+    }
+}
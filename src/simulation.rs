@@ -0,0 +1,218 @@
+//! Simulation preorders and bisimilarity of labeled transition systems.
+//!
+//! A directed graph can represent a labeled transition system, where nodes
+//! are states and each edge is a transition labeled with its data.  A state
+//! `p` simulates a state `q` if every transition `q -b-> q'` can be matched
+//! by a transition `p -a-> p'` with an equivalent label, such that `p'`
+//! simulates `q'` in turn.  Two states are bisimilar if the same holds in
+//! both directions with a single symmetric relation.
+//!
+//! Both relations are computed as greatest fixed points: every pair of
+//! states starts out related, and pairs are removed until every remaining
+//! pair can match each other's transitions.  Each round takes `O(E1 E2)`
+//! time, and there are at most `O(V1 V2)` rounds.  The states may belong to
+//! different graphs, and labels are compared with a function, so the two
+//! graphs need not have the same edge data type.
+
+use std::collections::HashSet;
+
+use crate::{end_pair::EndPair, hashing::FastHashSet, prelude::*};
+
+/// Returns the simulation preorder between the nodes of `g1` and `g2`, as
+/// the set of pairs `(p, q)` such that `p` simulates `q`.  `label_eq`
+/// compares the labels of a transition of `g1` and one of `g2`.
+pub fn simulation_preorder<G1, G2>(
+    g1: &G1,
+    g2: &G2,
+    label_eq: impl Fn(&G1::EdgeData, &G2::EdgeData) -> bool,
+) -> HashSet<(G1::NodeId, G2::NodeId)>
+where
+    G1: Graph<Directedness = Directed> + ?Sized,
+    G2: Graph<Directedness = Directed> + ?Sized,
+{
+    let nodes1: Vec<_> = g1.node_ids().collect();
+    let nodes2: Vec<_> = g2.node_ids().collect();
+    greatest_relation(g1, g2, &nodes1, &nodes2, &label_eq, false)
+}
+
+/// Returns true if node `n1` of `g1` simulates node `n2` of `g2`.  Only the
+/// states reachable from `n1` and `n2` are considered.
+pub fn simulates<G1, G2>(
+    g1: &G1,
+    n1: &G1::NodeId,
+    g2: &G2,
+    n2: &G2::NodeId,
+    label_eq: impl Fn(&G1::EdgeData, &G2::EdgeData) -> bool,
+) -> bool
+where
+    G1: Graph<Directedness = Directed> + ?Sized,
+    G2: Graph<Directedness = Directed> + ?Sized,
+{
+    let nodes1: Vec<_> = g1.bfs(n1).collect();
+    let nodes2: Vec<_> = g2.bfs(n2).collect();
+    greatest_relation(g1, g2, &nodes1, &nodes2, &label_eq, false)
+        .contains(&(n1.clone(), n2.clone()))
+}
+
+/// Returns true if node `n1` of `g1` and node `n2` of `g2` are bisimilar.
+/// Only the states reachable from `n1` and `n2` are considered.
+pub fn bisimilar<G1, G2>(
+    g1: &G1,
+    n1: &G1::NodeId,
+    g2: &G2,
+    n2: &G2::NodeId,
+    label_eq: impl Fn(&G1::EdgeData, &G2::EdgeData) -> bool,
+) -> bool
+where
+    G1: Graph<Directedness = Directed> + ?Sized,
+    G2: Graph<Directedness = Directed> + ?Sized,
+{
+    let nodes1: Vec<_> = g1.bfs(n1).collect();
+    let nodes2: Vec<_> = g2.bfs(n2).collect();
+    greatest_relation(g1, g2, &nodes1, &nodes2, &label_eq, true).contains(&(n1.clone(), n2.clone()))
+}
+
+/// Computes the largest relation between `nodes1` and `nodes2` in which
+/// the first node of each pair can match every transition of the second,
+/// and if `symmetric` is true, the second can match every transition of the
+/// first.
+fn greatest_relation<G1, G2>(
+    g1: &G1,
+    g2: &G2,
+    nodes1: &[G1::NodeId],
+    nodes2: &[G2::NodeId],
+    label_eq: &impl Fn(&G1::EdgeData, &G2::EdgeData) -> bool,
+    symmetric: bool,
+) -> HashSet<(G1::NodeId, G2::NodeId)>
+where
+    G1: Graph<Directedness = Directed> + ?Sized,
+    G2: Graph<Directedness = Directed> + ?Sized,
+{
+    let mut relation: FastHashSet<_> = nodes1
+        .iter()
+        .flat_map(|p| nodes2.iter().map(move |q| (p.clone(), q.clone())))
+        .collect();
+    loop {
+        let failed: Vec<_> = relation
+            .iter()
+            .filter(|(p, q)| {
+                !matches_transitions(g1, p, g2, q, label_eq, &relation)
+                    || (symmetric && !matches_transitions_back(g1, p, g2, q, label_eq, &relation))
+            })
+            .cloned()
+            .collect();
+        if failed.is_empty() {
+            return relation.into_iter().collect();
+        }
+        for pair in &failed {
+            relation.remove(pair);
+        }
+    }
+}
+
+/// Returns true if every transition of `q` is matched by one of `p`.
+fn matches_transitions<G1, G2>(
+    g1: &G1,
+    p: &G1::NodeId,
+    g2: &G2,
+    q: &G2::NodeId,
+    label_eq: &impl Fn(&G1::EdgeData, &G2::EdgeData) -> bool,
+    relation: &FastHashSet<(G1::NodeId, G2::NodeId)>,
+) -> bool
+where
+    G1: Graph<Directedness = Directed> + ?Sized,
+    G2: Graph<Directedness = Directed> + ?Sized,
+{
+    g2.edges_from(q).all(|eq| {
+        let (_, q_next) = g2.edge_ends(&eq).into_values();
+        let label = g2.edge_data(&eq);
+        g1.edges_from(p).any(|ep| {
+            let (_, p_next) = g1.edge_ends(&ep).into_values();
+            label_eq(g1.edge_data(&ep), label) && relation.contains(&(p_next, q_next.clone()))
+        })
+    })
+}
+
+/// Returns true if every transition of `p` is matched by one of `q`.
+fn matches_transitions_back<G1, G2>(
+    g1: &G1,
+    p: &G1::NodeId,
+    g2: &G2,
+    q: &G2::NodeId,
+    label_eq: &impl Fn(&G1::EdgeData, &G2::EdgeData) -> bool,
+    relation: &FastHashSet<(G1::NodeId, G2::NodeId)>,
+) -> bool
+where
+    G1: Graph<Directedness = Directed> + ?Sized,
+    G2: Graph<Directedness = Directed> + ?Sized,
+{
+    g1.edges_from(p).all(|ep| {
+        let (_, p_next) = g1.edge_ends(&ep).into_values();
+        let label = g1.edge_data(&ep);
+        g2.edges_from(q).any(|eq| {
+            let (_, q_next) = g2.edge_ends(&eq).into_values();
+            label_eq(label, g2.edge_data(&eq)) && relation.contains(&(p_next.clone(), q_next))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    /// The classic example: `a.(b + c)` simulates `a.b + a.c`, but not the
+    /// other way around, and they are not bisimilar.
+    #[test]
+    fn test_simulation() {
+        let mut early = BagGraph::<(), char, Directed>::default();
+        let s: Vec<_> = (0..4).map(|_| early.add_node(())).collect();
+        early.add_new_edge(&s[0], &s[1], 'a');
+        early.add_new_edge(&s[1], &s[2], 'b');
+        early.add_new_edge(&s[1], &s[3], 'c');
+
+        let mut late = LinkedGraph::<(), char, Directed>::default();
+        let t: Vec<_> = (0..5).map(|_| late.add_node(())).collect();
+        late.add_new_edge(&t[0], &t[1], 'a');
+        late.add_new_edge(&t[0], &t[2], 'a');
+        late.add_new_edge(&t[1], &t[3], 'b');
+        late.add_new_edge(&t[2], &t[4], 'c');
+
+        let eq = |a: &char, b: &char| a == b;
+        assert!(early.simulates(&s[0], &late, &t[0], eq));
+        assert!(!late.simulates(&t[0], &early, &s[0], eq));
+        assert!(!early.bisimilar(&s[0], &late, &t[0], eq));
+
+        let preorder = simulation_preorder(&early, &late, eq);
+        assert!(preorder.contains(&(s[1].clone(), t[1].clone())));
+        assert!(!preorder.contains(&(s[2].clone(), t[1].clone())));
+        // A state with no transitions is simulated by every state.
+        assert!(
+            s.iter()
+                .all(|p| preorder.contains(&(p.clone(), t[3].clone())))
+        );
+    }
+
+    #[test]
+    fn test_bisimilar() {
+        // A self-loop and a two-state cycle with the same label are
+        // bisimilar, though they have different numbers of states.
+        let mut one = BagGraph::<(), char, Directed>::default();
+        let a = one.add_node(());
+        one.add_new_edge(&a, &a, 'x');
+
+        let mut two = BagGraph::<(), char, Directed>::default();
+        let b = two.add_node(());
+        let c = two.add_node(());
+        two.add_new_edge(&b, &c, 'x');
+        let cb = two.add_new_edge(&c, &b, 'x');
+
+        let eq = |a: &char, b: &char| a == b;
+        assert!(one.bisimilar(&a, &two, &b, eq));
+        *two.edge_data_mut(&cb) = 'y';
+        assert!(!one.bisimilar(&a, &two, &b, eq));
+        assert!(!one.simulates(&a, &two, &b, eq));
+        assert!(two.simulates(&b, &one, &a, |x, y| x == y || *x == 'y'));
+    }
+}
@@ -0,0 +1,95 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use derivative::Derivative;
+
+use crate::{Graph, GraphElementId};
+
+use super::slab::SlabKey;
+
+/// Node identifier for [`SlabGraph`](super::SlabGraph) and
+/// [`PersistentGraph`](crate::PersistentGraph).
+///
+/// Contains the index of the node's slot and the slot's generation.
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Hash(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = ""),
+    PartialOrd(bound = ""),
+    Ord(bound = "")
+)]
+pub struct SlabGraphNodeId<G: Graph> {
+    key: SlabKey,
+    graph: PhantomData<G>,
+}
+
+impl<G: Graph> SlabGraphNodeId<G> {
+    pub(super) fn new(key: SlabKey) -> Self {
+        Self {
+            key,
+            graph: PhantomData,
+        }
+    }
+
+    pub(crate) fn key(&self) -> SlabKey {
+        self.key
+    }
+}
+
+// SAFETY: This is safe because the SlabGraphNodeId only contains a SlabKey and PhantomData.
+unsafe impl<G: Graph> Send for SlabGraphNodeId<G> {}
+unsafe impl<G: Graph> Sync for SlabGraphNodeId<G> {}
+
+impl<G: Graph> GraphElementId for SlabGraphNodeId<G> {}
+
+impl<G: Graph> Debug for SlabGraphNodeId<G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NodeId({:?})", self.key)
+    }
+}
+
+/// Edge identifier for [`SlabGraph`](super::SlabGraph) and
+/// [`PersistentGraph`](crate::PersistentGraph).
+///
+/// Contains the index of the edge's slot and the slot's generation.
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Hash(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = ""),
+    PartialOrd(bound = ""),
+    Ord(bound = "")
+)]
+pub struct SlabGraphEdgeId<G: Graph> {
+    key: SlabKey,
+    graph: PhantomData<G>,
+}
+
+impl<G: Graph> SlabGraphEdgeId<G> {
+    pub(super) fn new(key: SlabKey) -> Self {
+        Self {
+            key,
+            graph: PhantomData,
+        }
+    }
+
+    pub(crate) fn key(&self) -> SlabKey {
+        self.key
+    }
+}
+
+// SAFETY: This is safe because the SlabGraphEdgeId only contains a SlabKey and PhantomData.
+unsafe impl<G: Graph> Send for SlabGraphEdgeId<G> {}
+unsafe impl<G: Graph> Sync for SlabGraphEdgeId<G> {}
+
+impl<G: Graph> GraphElementId for SlabGraphEdgeId<G> {}
+
+impl<G: Graph> Debug for SlabGraphEdgeId<G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EdgeId({:?})", self.key)
+    }
+}
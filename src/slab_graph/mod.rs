@@ -0,0 +1,381 @@
+use crate::{
+    edge_multiplicity::EdgeMultiplicity,
+    end_pair::EndPair,
+    format_debug::format_debug,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+use derivative::Derivative;
+use std::{fmt::Debug, marker::PhantomData, mem};
+
+mod ids;
+pub(crate) mod slab;
+
+pub use ids::{SlabGraphEdgeId, SlabGraphNodeId};
+use slab::{FlatSlabs, SlabKey, SlabSlots, SlabSlotsMut, SlabStorage};
+
+#[derive(Clone)]
+pub(crate) struct Node<N> {
+    data: N,
+    edges_out: Vec<SlabKey>,
+    // Only maintained for directed graphs, since for undirected graphs
+    // edges_out is sufficient to find all edges.
+    edges_in: Vec<SlabKey>,
+}
+
+impl<N> Node<N> {
+    /// Removes an edge from the node's edge lists.
+    fn unlink_edge(&mut self, edge_key: SlabKey) {
+        self.edges_out.retain(|&key| key != edge_key);
+        self.edges_in.retain(|&key| key != edge_key);
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = "E: Clone"))]
+pub(crate) struct Edge<E, D: Directedness> {
+    data: E,
+    ends: D::EndPair<SlabKey>,
+}
+
+/// A graph representation which stores nodes and edges in arenas, and uses
+/// generational indices as IDs.
+///
+/// Unlike [`LinkedGraph`](crate::LinkedGraph), adding a node or edge does
+/// not allocate unless an arena needs to grow, and IDs are plain indices
+/// without reference counts.  The slot of a removed node or edge is reused
+/// by a later one, but with a new generation, so IDs of removed elements are
+/// still reported as invalid rather than referring to the new element.
+/// Nodes and edges are iterated in order of their slots.
+///
+/// # Type Parameters
+/// * `N` - The type of data stored in nodes
+/// * `E` - The type of data stored in edges
+/// * `D` - The directedness ([`Directed`] or [`Undirected`])
+/// * `M` - The edge multiplicity ([`MultipleEdges`] or [`SingleEdge`])
+/// * `S` - The kind of arena the nodes and edges are stored in.  Other
+///   kinds are used by [`PersistentGraph`](crate::PersistentGraph).
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct SlabGraph<N, E, D, M = MultipleEdges, S = FlatSlabs>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+{
+    pub(crate) nodes: S::Slab<Node<N>>,
+    pub(crate) edges: S::Slab<Edge<E, D>>,
+    directedness: PhantomData<D>,
+    edge_multiplicity: PhantomData<M>,
+}
+
+impl<N, E, D, M, S> SlabGraph<N, E, D, M, S>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+{
+    fn node(&self, id: &SlabGraphNodeId<Self>) -> &Node<N> {
+        &self.nodes[id.key()]
+    }
+
+    fn edge(&self, id: &SlabGraphEdgeId<Self>) -> &Edge<E, D> {
+        &self.edges[id.key()]
+    }
+}
+
+impl<N, E, D, M, S> Graph for SlabGraph<N, E, D, M, S>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+{
+    type NodeId = SlabGraphNodeId<Self>;
+    type NodeData = N;
+    type EdgeId = SlabGraphEdgeId<Self>;
+    type EdgeData = E;
+    type Directedness = D;
+    type EdgeMultiplicity = M;
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        &self.node(id).data
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.nodes
+            .get(id.key())
+            .map(|_| ())
+            .ok_or(GraphError::InvalidNodeId)
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.nodes.keys().map(SlabGraphNodeId::new)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.num_nodes() == 0
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        &self.edge(id).data
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.edges
+            .get(id.key())
+            .map(|_| ())
+            .ok_or(GraphError::InvalidEdgeId)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> {
+        self.edges.keys().map(SlabGraphEdgeId::new)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        let (from_key, into_key) = self.edge(id).ends.values();
+        D::make_pair(
+            SlabGraphNodeId::new(*from_key),
+            SlabGraphNodeId::new(*into_key),
+        )
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.node(from)
+            .edges_out
+            .iter()
+            .copied()
+            .map(SlabGraphEdgeId::new)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.node(into)
+            .edges_in
+            .iter()
+            .copied()
+            .map(SlabGraphEdgeId::new)
+            .chain(self.edges_from(into).take_while(|_| !self.is_directed()))
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        let expected_ends = D::make_pair(from.key(), into.key());
+        self.node(from)
+            .edges_out
+            .iter()
+            .filter(move |&&edge_key| self.edges[edge_key].ends == expected_ends)
+            .copied()
+            .map(SlabGraphEdgeId::new)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.edges_from_into(from, into).next().is_some()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        if self.is_directed() {
+            self.node(into).edges_in.len()
+        } else {
+            self.node(into).edges_out.len()
+        }
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.node(from).edges_out.len()
+    }
+}
+
+impl<N, E, D, M, S> GraphMut for SlabGraph<N, E, D, M, S>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+    S::Slab<Node<N>>: SlabSlotsMut<Node<N>>,
+    S::Slab<Edge<E, D>>: SlabSlotsMut<Edge<E, D>>,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        &mut self.nodes[id.key()].data
+    }
+
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        &mut self.edges[id.key()].data
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        SlabGraphNodeId::new(self.nodes.insert(Node {
+            data,
+            edges_out: Vec::new(),
+            edges_in: Vec::new(),
+        }))
+    }
+
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        let ends = D::make_pair(from.key(), into.key());
+
+        let existing = (!self.allows_parallel_edges())
+            .then(|| self.edges_from_into(from, into).next())
+            .flatten();
+        if let Some(eid) = existing {
+            let old_data = mem::replace(&mut self.edges[eid.key()].data, data);
+            return AddEdgeResult::Updated {
+                id: eid,
+                old_id: eid,
+                old_data,
+            };
+        }
+
+        let (&from_key, &into_key) = ends.values();
+        let edge_key = self.edges.insert(Edge { data, ends });
+        self.nodes[from_key].edges_out.push(edge_key);
+        if self.is_directed() {
+            self.nodes[into_key].edges_in.push(edge_key);
+        } else if from_key != into_key {
+            self.nodes[into_key].edges_out.push(edge_key);
+        }
+
+        AddEdgeResult::Added(SlabGraphEdgeId::new(edge_key))
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> N {
+        let node_key = id.key();
+        let node = self.nodes.remove(node_key).expect("NodeId is invalid");
+        // A directed self-loop appears in both lists, so it may already have
+        // been removed.
+        for edge_key in node.edges_out.into_iter().chain(node.edges_in) {
+            if let Some(edge) = self.edges.remove(edge_key) {
+                let &other_key = edge.ends.other_value(&node_key).into_inner();
+                if other_key != node_key {
+                    self.nodes[other_key].unlink_edge(edge_key);
+                }
+            }
+        }
+        node.data
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        let edge_key = id.key();
+        let edge = self.edges.remove(edge_key).expect("EdgeId is invalid");
+        let (&from_key, &into_key) = edge.ends.values();
+        self.nodes[from_key].unlink_edge(edge_key);
+        if into_key != from_key {
+            self.nodes[into_key].unlink_edge(edge_key);
+        }
+        edge.data
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.nodes.reserve(additional_nodes);
+        self.edges.reserve(additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.nodes.reserve_exact(additional_nodes);
+        self.edges.reserve_exact(additional_edges);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.edges.shrink_to_fit();
+    }
+}
+
+impl<N, E, D, M, S> StableNodeIds for SlabGraph<N, E, D, M, S>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+{
+}
+
+impl<N, E, D, M, S> StableEdgeIds for SlabGraph<N, E, D, M, S>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+{
+}
+
+/// The clone has the same IDs as the original.
+impl<N, E, D, M, S> Clone for SlabGraph<N, E, D, M, S>
+where
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+    S::Slab<Node<N>>: Clone,
+    S::Slab<Edge<E, D>>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+            directedness: PhantomData,
+            edge_multiplicity: PhantomData,
+        }
+    }
+}
+
+impl<N, E, D, M, S> Debug for SlabGraph<N, E, D, M, S>
+where
+    N: Debug,
+    E: Debug,
+    D: Directedness,
+    M: EdgeMultiplicity,
+    S: SlabStorage,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format_debug(self, f, S::GRAPH_NAME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reused_slots() {
+        let mut graph = SlabGraph::<&str, (), Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let ab = graph.add_new_edge(&a, &b, ());
+        graph.remove_node(&b);
+        assert_eq!(graph.check_node_id(&b), Err(GraphError::InvalidNodeId));
+        assert_eq!(graph.check_edge_id(&ab), Err(GraphError::InvalidEdgeId));
+
+        // The new node reuses the slot of the removed one.
+        let c = graph.add_node("c");
+        assert_ne!(b, c);
+        assert_eq!(graph.check_node_id(&b), Err(GraphError::InvalidNodeId));
+        assert_eq!(graph.try_remove_node(&b), Err(GraphError::InvalidNodeId));
+        assert_eq!(*graph.node_data(&c), "c");
+        assert_eq!(graph.num_edges_from(&a), 0);
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+/// A key for an entry in a [`Slab`]: the index of a slot, and the generation
+/// of the slot when the entry was inserted.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SlabKey {
+    index: u32,
+    generation: u32,
+}
+
+impl SlabKey {
+    pub(crate) fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    pub(crate) fn index(self) -> u32 {
+        self.index
+    }
+
+    pub(crate) fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+impl Debug for SlabKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+/// A kind of arena in which a [`SlabGraph`](super::SlabGraph) stores its
+/// nodes and edges.
+pub trait SlabStorage {
+    /// The arena holding values of type `T`.
+    type Slab<T>: SlabSlots<T>;
+
+    /// The name of the graph type using this storage, for its `Debug`
+    /// output.
+    const GRAPH_NAME: &'static str;
+}
+
+/// An arena of values with generational [`SlabKey`]s.  Keys are never
+/// invalidated except by removing their value.
+pub trait SlabSlots<T>: Default + Index<SlabKey, Output = T> {
+    /// Returns a reference to the value for a key, or `None` if the key is
+    /// invalid or its value has been removed.
+    fn get(&self, key: SlabKey) -> Option<&T>;
+
+    /// Returns the number of values in the arena.
+    fn len(&self) -> usize;
+
+    /// Returns the keys of the values in the arena, in order of their slots.
+    fn keys(&self) -> impl Iterator<Item = SlabKey> + '_;
+}
+
+/// The operations which change a [`SlabSlots`] arena.
+pub trait SlabSlotsMut<T>: SlabSlots<T> + IndexMut<SlabKey> {
+    /// Inserts a value, reusing an empty slot if there is one, and returns
+    /// its key.
+    fn insert(&mut self, value: T) -> SlabKey;
+
+    /// Removes and returns the value for a key, or returns `None` if the key
+    /// is invalid or its value has already been removed.
+    fn remove(&mut self, key: SlabKey) -> Option<T>;
+
+    /// Removes all values.  The slots are kept, with new generations, so that
+    /// existing keys do not refer to values inserted later.
+    fn clear(&mut self);
+
+    /// Reserves capacity for at least `additional` more values.
+    fn reserve(&mut self, additional: usize);
+
+    /// Reserves the minimum capacity for at least `additional` more values.
+    fn reserve_exact(&mut self, additional: usize);
+
+    /// Shrinks the capacity of the arena as much as possible.  Empty slots
+    /// are kept, so existing keys are unaffected.
+    fn shrink_to_fit(&mut self);
+}
+
+/// Storage in [`Slab`]s, used by [`SlabGraph`](super::SlabGraph).
+#[derive(Debug)]
+pub struct FlatSlabs;
+
+impl SlabStorage for FlatSlabs {
+    type Slab<T> = Slab<T>;
+
+    const GRAPH_NAME: &'static str = "SlabGraph";
+}
+
+#[derive(Clone)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// A vector of slots with generational keys.  Removing a value frees its
+/// slot for reuse by a later insertion, and increments the slot's generation
+/// so that keys for the removed value can be told apart from keys for the
+/// new one.  Keys are never invalidated except by removing their value.
+#[derive(Clone)]
+pub struct Slab<T> {
+    slots: Vec<Slot<T>>,
+    /// Indices of empty slots, in the order they will be reused.
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> Slab<T> {
+    /// Creates a new, empty slab.
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn slot(&self, key: SlabKey) -> Option<&Slot<T>> {
+        self.slots
+            .get(key.index as usize)
+            .filter(|slot| slot.generation == key.generation)
+    }
+
+    /// Returns a mutable reference to the value for a key, or `None` if the
+    /// key is invalid or its value has been removed.
+    pub fn get_mut(&mut self, key: SlabKey) -> Option<&mut T> {
+        self.slots
+            .get_mut(key.index as usize)
+            .filter(|slot| slot.generation == key.generation)?
+            .value
+            .as_mut()
+    }
+
+    /// Returns the keys and values in the slab, in order of their slots.
+    pub fn pairs(&self) -> impl Iterator<Item = (SlabKey, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let key = SlabKey {
+                index: index as u32,
+                generation: slot.generation,
+            };
+            slot.value.as_ref().map(|value| (key, value))
+        })
+    }
+}
+
+impl<T> SlabSlots<T> for Slab<T> {
+    fn get(&self, key: SlabKey) -> Option<&T> {
+        self.slot(key)?.value.as_ref()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn keys(&self) -> impl Iterator<Item = SlabKey> + '_ {
+        self.pairs().map(|(key, _)| key)
+    }
+}
+
+impl<T> SlabSlotsMut<T> for Slab<T> {
+    fn insert(&mut self, value: T) -> SlabKey {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return SlabKey {
+                index,
+                generation: slot.generation,
+            };
+        }
+        let index = u32::try_from(self.slots.len()).expect("too many slots in slab");
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+        SlabKey {
+            index,
+            generation: 0,
+        }
+    }
+
+    fn remove(&mut self, key: SlabKey) -> Option<T> {
+        let slot = self
+            .slots
+            .get_mut(key.index as usize)
+            .filter(|slot| slot.generation == key.generation)?;
+        let value = slot.value.take()?;
+        self.len -= 1;
+        // A slot whose generation is exhausted is retired rather than risk
+        // reusing an old key.
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(key.index);
+        }
+        Some(value)
+    }
+
+    fn clear(&mut self) {
+        let keys: Vec<_> = self.keys().collect();
+        for key in keys {
+            self.remove(key);
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.slots
+            .reserve(additional.saturating_sub(self.free.len()));
+    }
+
+    fn reserve_exact(&mut self, additional: usize) {
+        self.slots
+            .reserve_exact(additional.saturating_sub(self.free.len()));
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.slots.shrink_to_fit();
+        self.free.shrink_to_fit();
+    }
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for Slab<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.pairs()).finish()
+    }
+}
+
+impl<T> Index<SlabKey> for Slab<T> {
+    type Output = T;
+
+    fn index(&self, key: SlabKey) -> &Self::Output {
+        self.get(key).expect("invalid SlabKey")
+    }
+}
+
+impl<T> IndexMut<SlabKey> for Slab<T> {
+    fn index_mut(&mut self, key: SlabKey) -> &mut Self::Output {
+        self.get_mut(key).expect("invalid SlabKey")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuse_slots() {
+        let mut slab = Slab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        assert_eq!(slab.remove(a), Some('a'));
+        assert_eq!(slab.remove(a), None);
+
+        // The slot is reused with a new generation.
+        let c = slab.insert('c');
+        assert_eq!(c.index, a.index);
+        assert_ne!(c, a);
+        assert_eq!(slab.get(a), None);
+        assert_eq!(slab[c], 'c');
+        assert_eq!(slab.len(), 2);
+        assert_eq!(slab.keys().collect::<Vec<_>>(), vec![c, b]);
+
+        slab.clear();
+        assert_eq!(slab.len(), 0);
+        assert_eq!(slab.get(b), None);
+        let d = slab.insert('d');
+        assert_eq!(slab.get(c), None);
+        assert_eq!(slab[d], 'd');
+    }
+
+    #[test]
+    fn test_exhausted_generation() {
+        let mut slab = Slab::new();
+        let key = slab.insert(1);
+        slab.slots[0].generation = u32::MAX;
+        let key = SlabKey {
+            generation: u32::MAX,
+            ..key
+        };
+        slab.remove(key);
+        let new_key = slab.insert(2);
+        assert_ne!(new_key.index, key.index);
+        assert_eq!(slab.len(), 1);
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    fmt::Debug,
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    notifying_graph::{GraphEvent, GraphObserver},
+    prelude::*,
+};
+
+/// A policy for taking snapshots with a [`SnapshotScheduler`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SnapshotOn {
+    /// Only take snapshots when [`SnapshotScheduler::snapshot_now`] is
+    /// called.
+    #[default]
+    Never,
+    /// Take a snapshot once the given number of mutations have been
+    /// recorded since the last one.
+    Mutations(usize),
+    /// Take a snapshot once the given time has passed since the last one, if
+    /// any mutations have been recorded since.
+    Interval(Duration),
+}
+
+type Sink<G> = dyn Fn(u64, &G) -> io::Result<()> + Send + Sync;
+
+/// Takes snapshots of a graph and serializes them on a background thread,
+/// so a service can export its graph periodically without pausing writers
+/// while the serialization runs.
+///
+/// Taking a snapshot clones the graph, which is the only part done on the
+/// calling thread.  The clone is then passed to the sink given to
+/// [`Self::new`] on a new thread, along with a sequence number which starts
+/// at 0 and increases by one with each snapshot.  The sink typically
+/// creates a file or other writer for the snapshot and writes the graph to
+/// it, e.g. with [`Graph::write_dot`].  At most one snapshot is serialized at
+/// a time; a snapshot which falls due while another is being serialized is
+/// taken by the next call to [`Self::poll`] after it finishes.
+///
+/// Mutations are counted either by calling [`Self::record_mutations`], or
+/// automatically by registering [`Self::observer`] with a
+/// [`NotifyingGraph`](crate::NotifyingGraph).  Snapshots are only taken
+/// when [`Self::poll`] is called, so it should be called after each batch of
+/// mutations, or periodically.
+pub struct SnapshotScheduler<G> {
+    sink: Arc<Sink<G>>,
+    policy: SnapshotOn,
+    mutations: Arc<AtomicUsize>,
+    last_snapshot: Instant,
+    next_sequence: u64,
+    pending: Option<JoinHandle<io::Result<()>>>,
+    error: Option<io::Error>,
+}
+
+impl<G> SnapshotScheduler<G>
+where
+    G: Graph + Clone + Send + 'static,
+{
+    /// Creates a scheduler which passes each snapshot to `sink`, with the
+    /// [`SnapshotOn::Never`] policy.
+    pub fn new(sink: impl Fn(u64, &G) -> io::Result<()> + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            policy: SnapshotOn::Never,
+            mutations: Arc::new(AtomicUsize::new(0)),
+            last_snapshot: Instant::now(),
+            next_sequence: 0,
+            pending: None,
+            error: None,
+        }
+    }
+
+    /// Sets the policy for taking snapshots, returning the updated
+    /// scheduler.
+    pub fn with_policy(mut self, policy: SnapshotOn) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the policy for taking snapshots.
+    pub fn set_policy(&mut self, policy: SnapshotOn) {
+        self.policy = policy;
+    }
+
+    /// Gets the current policy.
+    pub fn policy(&self) -> SnapshotOn {
+        self.policy
+    }
+
+    /// Records that the graph has been mutated `count` times.
+    pub fn record_mutations(&self, count: usize) {
+        self.mutations.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Gets the number of mutations recorded since the last snapshot.
+    pub fn mutations_since_snapshot(&self) -> usize {
+        self.mutations.load(Ordering::Relaxed)
+    }
+
+    /// Returns an observer for a [`NotifyingGraph`](crate::NotifyingGraph)
+    /// which records each node or edge added or removed as a mutation.
+    pub fn observer(&self) -> impl GraphObserver<G> + 'static {
+        let mutations = Arc::clone(&self.mutations);
+        move |event: &GraphEvent<'_, G>| {
+            if !matches!(event, GraphEvent::Compacted { .. }) {
+                mutations.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Returns true if a snapshot is being serialized.
+    pub fn is_busy(&mut self) -> bool {
+        self.reap(false);
+        self.pending.is_some()
+    }
+
+    /// Takes a snapshot of `graph` if the policy says one is due and no
+    /// other snapshot is being serialized.  Returns true if a snapshot was
+    /// taken.
+    pub fn poll(&mut self, graph: &G) -> bool {
+        let mutations = self.mutations_since_snapshot();
+        let due = match self.policy {
+            SnapshotOn::Never => false,
+            SnapshotOn::Mutations(limit) => mutations >= limit.max(1),
+            SnapshotOn::Interval(interval) => {
+                mutations > 0 && self.last_snapshot.elapsed() >= interval
+            }
+        };
+        if !due || self.is_busy() {
+            return false;
+        }
+        self.start(graph);
+        true
+    }
+
+    /// Takes a snapshot of `graph`, first waiting for any snapshot being
+    /// serialized to finish.  Returns the sequence number of the snapshot.
+    pub fn snapshot_now(&mut self, graph: &G) -> u64 {
+        self.reap(true);
+        self.start(graph)
+    }
+
+    /// Waits for any snapshot being serialized to finish, and returns the
+    /// first error reported by the sink since the last call to
+    /// [`Self::wait`] or [`Self::take_error`], if any.
+    pub fn wait(&mut self) -> io::Result<()> {
+        self.reap(true);
+        self.take_error().map_or(Ok(()), Err)
+    }
+
+    /// Returns the first error reported by the sink since the last call to
+    /// [`Self::wait`] or [`Self::take_error`], without waiting.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.reap(false);
+        self.error.take()
+    }
+
+    fn start(&mut self, graph: &G) -> u64 {
+        let snapshot = graph.clone();
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.mutations.store(0, Ordering::Relaxed);
+        self.last_snapshot = Instant::now();
+        let sink = Arc::clone(&self.sink);
+        self.pending = Some(thread::spawn(move || sink(sequence, &snapshot)));
+        sequence
+    }
+
+    /// Collects the result of the pending snapshot if it has finished, or
+    /// if `block` is true.
+    fn reap(&mut self, block: bool) {
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|handle| block || handle.is_finished())
+        {
+            let result = match self.pending.take().unwrap().join() {
+                Ok(result) => result,
+                Err(_) => Err(io::Error::other("snapshot sink panicked")),
+            };
+            if let Err(error) = result {
+                self.error.get_or_insert(error);
+            }
+        }
+    }
+}
+
+impl<G> Debug for SnapshotScheduler<G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapshotScheduler")
+            .field("policy", &self.policy)
+            .field("mutations", &self.mutations)
+            .field("next_sequence", &self.next_sequence)
+            .field("busy", &self.pending.is_some())
+            .finish()
+    }
+}
+
+impl<G> Drop for SnapshotScheduler<G> {
+    /// Waits for any snapshot being serialized to finish.
+    fn drop(&mut self) {
+        if let Some(handle) = self.pending.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, mpsc};
+
+    use crate::{BagGraph, NotifyingGraph};
+
+    use super::*;
+
+    type TestGraph = BagGraph<(), (), Directed>;
+    type Log = Arc<Mutex<Vec<(u64, usize)>>>;
+
+    fn recording_scheduler() -> (SnapshotScheduler<TestGraph>, Log) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink_log = Arc::clone(&log);
+        let scheduler = SnapshotScheduler::new(move |sequence, graph: &TestGraph| {
+            sink_log.lock().unwrap().push((sequence, graph.num_nodes()));
+            Ok(())
+        });
+        (scheduler, log)
+    }
+
+    #[test]
+    fn test_mutation_trigger() {
+        let (scheduler, log) = recording_scheduler();
+        let mut scheduler = scheduler.with_policy(SnapshotOn::Mutations(2));
+        let mut graph = TestGraph::default();
+
+        graph.add_node(());
+        scheduler.record_mutations(1);
+        assert!(!scheduler.poll(&graph));
+        graph.add_node(());
+        scheduler.record_mutations(1);
+        assert!(scheduler.poll(&graph));
+        assert_eq!(scheduler.mutations_since_snapshot(), 0);
+        scheduler.wait().unwrap();
+
+        // The snapshot is unaffected by later mutations.
+        graph.add_node(());
+        assert_eq!(scheduler.snapshot_now(&graph), 1);
+        scheduler.wait().unwrap();
+        assert_eq!(*log.lock().unwrap(), vec![(0, 2), (1, 3)]);
+    }
+
+    #[test]
+    fn test_observer() {
+        let (scheduler, log) = recording_scheduler();
+        let mut scheduler = scheduler.with_policy(SnapshotOn::Interval(Duration::ZERO));
+        let mut graph = NotifyingGraph::new(TestGraph::default());
+        graph.add_observer(scheduler.observer());
+
+        assert!(!scheduler.poll(graph.inner()));
+        let a = graph.add_node(());
+        graph.add_new_edge(&a, &a, ());
+        assert_eq!(scheduler.mutations_since_snapshot(), 2);
+        assert!(scheduler.poll(graph.inner()));
+        scheduler.wait().unwrap();
+        assert_eq!(*log.lock().unwrap(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_busy_and_errors() {
+        let (sender, receiver) = mpsc::channel::<()>();
+        let receiver = Mutex::new(receiver);
+        let mut scheduler = SnapshotScheduler::new(move |_, _: &TestGraph| {
+            receiver.lock().unwrap().recv().unwrap();
+            Err(io::Error::other("disk full"))
+        })
+        .with_policy(SnapshotOn::Mutations(1));
+        let graph = TestGraph::default();
+
+        scheduler.record_mutations(1);
+        assert!(scheduler.poll(&graph));
+        scheduler.record_mutations(1);
+        assert!(scheduler.is_busy());
+        assert!(!scheduler.poll(&graph));
+
+        sender.send(()).unwrap();
+        assert_eq!(scheduler.wait().unwrap_err().to_string(), "disk full");
+        assert!(scheduler.wait().is_ok());
+        sender.send(()).unwrap();
+        assert!(scheduler.poll(&graph));
+        assert!(scheduler.wait().is_err());
+    }
+}
@@ -0,0 +1,496 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
+
+use crate::{
+    end_pair::EndPair,
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A wrapper around a graph which supports removing nodes and edges in a way
+/// that can be undone.
+///
+/// [`Self::soft_remove_node`] and [`Self::soft_remove_edge`] hide an element
+/// from every query of the [`Graph`] trait, but keep it in the wrapped graph,
+/// so its ID stays valid and [`Self::restore_node`] or [`Self::restore_edge`]
+/// can bring it back unchanged.  Hiding a node also hides the edges
+/// connected to it, and restoring it shows them again unless they were
+/// soft-removed themselves.  [`Self::purge`] removes all hidden elements
+/// from the wrapped graph for good.
+///
+/// The data of a hidden element can still be read with [`Graph::node_data`]
+/// and [`Graph::edge_data`], but [`Graph::check_node_id`] and
+/// [`Graph::check_edge_id`] report it as invalid.  Counting the edges of the
+/// graph takes time proportional to the number of edges.
+pub struct SoftDeleteGraph<G>
+where
+    G: Graph,
+{
+    inner: G,
+    removed_nodes: HashSet<G::NodeId>,
+    removed_edges: HashSet<G::EdgeId>,
+}
+
+impl<G> SoftDeleteGraph<G>
+where
+    G: Graph,
+{
+    /// Wraps an existing graph, with no hidden elements.
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            removed_nodes: HashSet::new(),
+            removed_edges: HashSet::new(),
+        }
+    }
+
+    /// Gets a reference to the wrapped graph, which includes the hidden
+    /// elements.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, keeping the hidden elements.  Call [`Self::purge`]
+    /// first to discard them.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Hides a node and the edges connected to it.  Returns true if the node
+    /// was visible.  Panics if the wrapped graph does not contain the node.
+    pub fn soft_remove_node(&mut self, id: &G::NodeId) -> bool {
+        if let Err(error) = self.inner.check_node_id(id) {
+            panic!("{error}: {id:?}");
+        }
+        self.removed_nodes.insert(id.clone())
+    }
+
+    /// Shows a hidden node again.  Returns true if the node was hidden.
+    pub fn restore_node(&mut self, id: &G::NodeId) -> bool {
+        self.removed_nodes.remove(id)
+    }
+
+    /// Hides an edge.  Returns true if the edge had not been soft-removed
+    /// already.  Panics if the wrapped graph does not contain the edge.
+    pub fn soft_remove_edge(&mut self, id: &G::EdgeId) -> bool {
+        if let Err(error) = self.inner.check_edge_id(id) {
+            panic!("{error}: {id:?}");
+        }
+        self.removed_edges.insert(id.clone())
+    }
+
+    /// Undoes [`Self::soft_remove_edge`].  Returns true if the edge had been
+    /// soft-removed.  The edge stays hidden while either of its ends is.
+    pub fn restore_edge(&mut self, id: &G::EdgeId) -> bool {
+        self.removed_edges.remove(id)
+    }
+
+    /// Returns true if a node has been soft-removed.
+    pub fn is_node_removed(&self, id: &G::NodeId) -> bool {
+        self.removed_nodes.contains(id)
+    }
+
+    /// Returns true if an edge is hidden, either because it has been
+    /// soft-removed or because one of its ends has.
+    pub fn is_edge_removed(&self, id: &G::EdgeId) -> bool {
+        if self.removed_edges.contains(id) {
+            return true;
+        }
+        let (from, into) = self.inner.edge_ends(id).into_values();
+        self.removed_nodes.contains(&from) || self.removed_nodes.contains(&into)
+    }
+
+    /// Gets an iterator over the soft-removed nodes, in an unspecified order.
+    pub fn removed_node_ids(&self) -> impl Iterator<Item = &G::NodeId> {
+        self.removed_nodes.iter()
+    }
+
+    /// Gets an iterator over the soft-removed edges, in an unspecified order.
+    /// Edges hidden only because one of their ends is hidden are not
+    /// included.
+    pub fn removed_edge_ids(&self) -> impl Iterator<Item = &G::EdgeId> {
+        self.removed_edges.iter()
+    }
+}
+
+impl<G> SoftDeleteGraph<G>
+where
+    G: GraphMut,
+{
+    /// Removes every hidden node and edge from the wrapped graph.
+    pub fn purge(&mut self) {
+        for eid in std::mem::take(&mut self.removed_edges) {
+            self.inner.remove_edge(&eid);
+        }
+        for nid in std::mem::take(&mut self.removed_nodes) {
+            self.inner.remove_node(&nid);
+        }
+    }
+}
+
+impl<G> Default for SoftDeleteGraph<G>
+where
+    G: Graph + Default,
+{
+    fn default() -> Self {
+        Self::new(G::default())
+    }
+}
+
+impl<G> Clone for SoftDeleteGraph<G>
+where
+    G: Graph + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            removed_nodes: self.removed_nodes.clone(),
+            removed_edges: self.removed_edges.clone(),
+        }
+    }
+}
+
+impl<G> Debug for SoftDeleteGraph<G>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoftDeleteGraph")
+            .field("inner", &self.inner)
+            .field("removed_nodes", &self.removed_nodes)
+            .field("removed_edges", &self.removed_edges)
+            .finish()
+    }
+}
+
+impl<G> Graph for SoftDeleteGraph<G>
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner
+            .node_ids()
+            .filter(|nid| !self.removed_nodes.contains(nid))
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes() - self.removed_nodes.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.num_nodes() == 0
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)?;
+        if self.removed_nodes.contains(id) {
+            return Err(GraphError::InvalidNodeId);
+        }
+        Ok(())
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)?;
+        if self.is_edge_removed(id) {
+            return Err(GraphError::InvalidEdgeId);
+        }
+        Ok(())
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner
+            .edge_ids()
+            .filter(|eid| !self.is_edge_removed(eid))
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner
+            .edges_from(from)
+            .filter(|eid| !self.is_edge_removed(eid))
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner
+            .edges_into(into)
+            .filter(|eid| !self.is_edge_removed(eid))
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner
+            .edges_from_into(from, into)
+            .filter(|eid| !self.is_edge_removed(eid))
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G> GraphMut for SoftDeleteGraph<G>
+where
+    G: GraphMut,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        self.inner.node_data_mut(id)
+    }
+
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        self.inner.edge_data_mut(id)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.removed_nodes.clear();
+        self.removed_edges.clear();
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        self.inner.add_node(data)
+    }
+
+    /// Removes a node from the wrapped graph, whether or not it is hidden.
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        for eid in self.inner.edges_from(id).chain(self.inner.edges_into(id)) {
+            self.removed_edges.remove(&eid);
+        }
+        self.removed_nodes.remove(id);
+        self.inner.remove_node(id)
+    }
+
+    /// Adds an edge as described by [`GraphMut::add_edge`].  Panics if either
+    /// end is hidden.  In a graph without parallel edges, a hidden edge
+    /// between the same nodes is replaced, and its ID and data are returned
+    /// as usual.
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        for nid in [from, into] {
+            if self.removed_nodes.contains(nid) {
+                panic!("{}: {nid:?}", GraphError::InvalidNodeId);
+            }
+        }
+        let result = self.inner.add_edge(from, into, data);
+        if let AddEdgeResult::Updated { old_id, .. } = &result {
+            self.removed_edges.remove(old_id);
+        }
+        result
+    }
+
+    /// Removes an edge from the wrapped graph, whether or not it is hidden.
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        self.removed_edges.remove(id);
+        self.inner.remove_edge(id)
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    /// Compacts the wrapped graph, including the hidden elements, whose IDs
+    /// are updated along with the others.
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        let mut node_map = HashMap::new();
+        let mut edge_map = HashMap::new();
+        self.inner.compact(Some(&mut node_map), Some(&mut edge_map));
+        self.removed_nodes = remap(std::mem::take(&mut self.removed_nodes), &node_map);
+        self.removed_edges = remap(std::mem::take(&mut self.removed_edges), &edge_map);
+        if let Some(collector) = node_map_collector {
+            for (old_id, new_id) in node_map {
+                collector.insert(old_id, new_id);
+            }
+        }
+        if let Some(collector) = edge_map_collector {
+            for (old_id, new_id) in edge_map {
+                collector.insert(old_id, new_id);
+            }
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+        self.removed_nodes.shrink_to_fit();
+        self.removed_edges.shrink_to_fit();
+    }
+}
+
+impl<G> StableNodeIds for SoftDeleteGraph<G> where G: StableNodeIds {}
+
+impl<G> StableEdgeIds for SoftDeleteGraph<G> where G: StableEdgeIds {}
+
+/// Replaces the IDs in `ids` which were changed by compaction.
+fn remap<T: GraphElementId>(ids: HashSet<T>, map: &HashMap<T, T>) -> HashSet<T> {
+    ids.into_iter()
+        .map(|id| map.get(&id).cloned().unwrap_or(id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    #[test]
+    fn test_soft_remove_node() {
+        let mut graph = SoftDeleteGraph::new(LinkedGraph::<&str, i32, Directed>::default());
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let ab = graph.add_new_edge(&a, &b, 1);
+        let bc = graph.add_new_edge(&b, &c, 2);
+        let ca = graph.add_new_edge(&c, &a, 3);
+
+        assert!(graph.soft_remove_node(&b));
+        assert!(!graph.soft_remove_node(&b));
+        assert!(graph.is_node_removed(&b));
+        assert_eq!(graph.num_nodes(), 2);
+        assert_eq!(graph.num_edges(), 1);
+        assert_eq!(graph.edge_ids().collect::<Vec<_>>(), vec![ca.clone()]);
+        assert!(!graph.node_ids().any(|nid| nid == b));
+        assert_eq!(graph.successors(&a).count(), 0);
+        assert_eq!(graph.check_node_id(&b), Err(GraphError::InvalidNodeId));
+        assert_eq!(graph.check_edge_id(&ab), Err(GraphError::InvalidEdgeId));
+        assert!(graph.is_edge_removed(&bc));
+        assert_eq!(*graph.node_data(&b), "b");
+
+        assert!(graph.restore_node(&b));
+        assert!(!graph.restore_node(&b));
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.successors(&a).collect::<Vec<_>>(), vec![b.clone()]);
+        assert_eq!(*graph.edge_data(&bc), 2);
+    }
+
+    #[test]
+    fn test_soft_remove_edge() {
+        let mut graph = SoftDeleteGraph::new(BagGraph::<(), (), Undirected>::default());
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let ab = graph.add_new_edge(&a, &b, ());
+
+        assert!(graph.soft_remove_edge(&ab));
+        assert!(!graph.has_edge_from_into(&b, &a));
+        assert_eq!(graph.num_edges(), 0);
+        assert_eq!(graph.removed_edge_ids().collect::<Vec<_>>(), vec![&ab]);
+
+        // Restoring the edge does not show it while an end is hidden.
+        graph.soft_remove_node(&a);
+        assert!(graph.restore_edge(&ab));
+        assert!(graph.is_edge_removed(&ab));
+        graph.restore_node(&a);
+        assert!(graph.has_edge_from_into(&b, &a));
+    }
+
+    #[test]
+    fn test_purge() {
+        let mut graph = SoftDeleteGraph::new(BagGraph::<i32, (), Directed>::default());
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+        let c = graph.add_node(3);
+        graph.add_new_edge(&a, &b, ());
+        let bc = graph.add_new_edge(&b, &c, ());
+        let ca = graph.add_new_edge(&c, &a, ());
+        graph.soft_remove_node(&a);
+        graph.soft_remove_edge(&bc);
+
+        graph.purge();
+        assert_eq!(graph.removed_node_ids().count(), 0);
+        assert_eq!(graph.removed_edge_ids().count(), 0);
+        assert_eq!(graph.inner().num_nodes(), 2);
+        assert_eq!(graph.inner().num_edges(), 0);
+        assert_eq!(graph.num_edges(), 0);
+        assert!(graph.inner().check_edge_id(&ca).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid node ID")]
+    fn test_add_edge_to_removed_node() {
+        let mut graph = SoftDeleteGraph::new(BagGraph::<(), (), Directed>::default());
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.soft_remove_node(&b);
+        graph.add_new_edge(&a, &b, ());
+    }
+
+    #[test]
+    fn test_replace_removed_edge() {
+        let mut graph =
+            SoftDeleteGraph::new(LinkedGraph::<(), i32, Directed, SingleEdge>::default());
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let ab = graph.add_edge(&a, &b, 1).into_edge_id();
+        graph.soft_remove_edge(&ab);
+        let result = graph.add_edge(&a, &b, 2);
+        assert!(!graph.is_edge_removed(result.edge_id()));
+        assert_eq!(result.updated(), Some((ab, 1)));
+        assert_eq!(graph.num_edges(), 1);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut graph = SoftDeleteGraph::new(BagGraph::<i32, i32, Directed>::default());
+        let nodes: Vec<_> = (0..4).map(|i| graph.add_node(i)).collect();
+        let e = graph.add_new_edge(&nodes[2], &nodes[3], 23);
+        graph.remove_node(&nodes[0]);
+        graph.remove_node(&nodes[1]);
+        graph.soft_remove_node(&nodes[2]);
+        graph.soft_remove_edge(&e);
+
+        let mut node_map = HashMap::new();
+        let mut edge_map = HashMap::new();
+        graph.compact(Some(&mut node_map), Some(&mut edge_map));
+        let removed: Vec<_> = graph.removed_node_ids().cloned().collect();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(*graph.node_data(&removed[0]), 2);
+        let removed: Vec<_> = graph.removed_edge_ids().cloned().collect();
+        assert_eq!(*graph.edge_data(&removed[0]), 23);
+        assert_eq!(graph.num_nodes(), 1);
+        assert_eq!(graph.num_edges(), 0);
+    }
+}
@@ -0,0 +1,491 @@
+use std::{cmp::Ordering, fmt::Debug};
+
+use crate::{
+    end_pair::EndPair,
+    hashing::FastHashMap,
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// A wrapper around a graph which keeps the edges from each node sorted by
+/// a comparator on their data.  This is useful when the edges from a node
+/// are usually wanted in priority order, such as by a scheduler which
+/// always takes the highest-priority edge, since the order is maintained as
+/// edges are added instead of being recomputed for every query.
+///
+/// [`Graph::edges_from`] and [`Graph::edges_from_into`] return edges in
+/// increasing order according to the comparator, and edges which compare
+/// equal are returned in the order they were added.
+/// [`Self::first_edge_from`] returns the least edge from a node.  In an
+/// undirected graph, [`Graph::edges_into`] is ordered in the same way; in a
+/// directed graph, it is delegated to the inner graph.
+///
+/// Adding an edge takes time proportional to the number of edges already
+/// leaving its source.  [`GraphMut::edge_data_mut`] cannot tell whether the
+/// data was changed in a way that affects the order, so it leaves the order
+/// alone; use [`Self::update_edge_data`] instead, or call [`Self::resort`]
+/// afterwards.
+pub struct SortedEdgesGraph<G, C>
+where
+    G: Graph,
+{
+    inner: G,
+    compare: C,
+    edges_from: FastHashMap<G::NodeId, Vec<G::EdgeId>>,
+}
+
+impl<G, C> SortedEdgesGraph<G, C>
+where
+    G: Graph,
+    C: Fn(&G::EdgeData, &G::EdgeData) -> Ordering,
+{
+    /// Wraps an existing graph, sorting the edges it already contains.
+    pub fn new(inner: G, compare: C) -> Self {
+        let mut graph = Self {
+            inner,
+            compare,
+            edges_from: FastHashMap::default(),
+        };
+        graph.resort();
+        graph
+    }
+
+    /// Gets the least edge from `from`, if there is one.
+    pub fn first_edge_from(&self, from: &G::NodeId) -> Option<G::EdgeId> {
+        self.edges_from.get(from)?.first().cloned()
+    }
+
+    /// Gets the greatest edge from `from`, if there is one.
+    pub fn last_edge_from(&self, from: &G::NodeId) -> Option<G::EdgeId> {
+        self.edges_from.get(from)?.last().cloned()
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding the order.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Sorts the edges from every node again.  This is only needed after
+    /// changing edge data through [`GraphMut::edge_data_mut`].
+    pub fn resort(&mut self) {
+        self.edges_from.clear();
+        let inner = &self.inner;
+        let compare = &self.compare;
+        for nid in inner.node_ids() {
+            let mut edges: Vec<_> = inner.edges_from(&nid).collect();
+            if !edges.is_empty() {
+                // A stable sort keeps equal edges in the inner graph's order.
+                edges.sort_by(|a, b| compare(inner.edge_data(a), inner.edge_data(b)));
+                self.edges_from.insert(nid, edges);
+            }
+        }
+    }
+
+    /// Returns the nodes whose lists of edges from them contain `eid`.
+    fn sources(&self, eid: &G::EdgeId) -> (G::NodeId, Option<G::NodeId>) {
+        let (from, into) = self.inner.edge_ends(eid).into_values();
+        let other = (!self.inner.is_directed() && from != into).then_some(into);
+        (from, other)
+    }
+
+    fn insert_sorted(&mut self, nid: G::NodeId, eid: G::EdgeId) {
+        let inner = &self.inner;
+        let compare = &self.compare;
+        let data = inner.edge_data(&eid);
+        let edges = self.edges_from.entry(nid).or_default();
+        let index = edges.partition_point(|other| compare(inner.edge_data(other), data).is_le());
+        edges.insert(index, eid);
+    }
+
+    fn insert_edge(&mut self, eid: &G::EdgeId) {
+        let (from, other) = self.sources(eid);
+        self.insert_sorted(from, eid.clone());
+        if let Some(into) = other {
+            self.insert_sorted(into, eid.clone());
+        }
+    }
+
+    fn forget_edge(&mut self, eid: &G::EdgeId) {
+        let (from, other) = self.sources(eid);
+        for nid in std::iter::once(from).chain(other) {
+            if let Some(edges) = self.edges_from.get_mut(&nid) {
+                edges.retain(|other| other != eid);
+                if edges.is_empty() {
+                    self.edges_from.remove(&nid);
+                }
+            }
+        }
+    }
+}
+
+impl<G, C> SortedEdgesGraph<G, C>
+where
+    G: GraphMut,
+    C: Fn(&G::EdgeData, &G::EdgeData) -> Ordering,
+{
+    /// Changes the data of an edge with `f`, then moves the edge to its new
+    /// place in the order.
+    pub fn update_edge_data<R>(
+        &mut self,
+        id: &G::EdgeId,
+        f: impl FnOnce(&mut G::EdgeData) -> R,
+    ) -> R {
+        self.forget_edge(id);
+        let result = f(self.inner.edge_data_mut(id));
+        self.insert_edge(id);
+        result
+    }
+}
+
+impl<G, C> Clone for SortedEdgesGraph<G, C>
+where
+    G: Graph + Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            compare: self.compare.clone(),
+            edges_from: self.edges_from.clone(),
+        }
+    }
+}
+
+impl<G, C> Debug for SortedEdgesGraph<G, C>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SortedEdgesGraph")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<G, C> Graph for SortedEdgesGraph<G, C>
+where
+    G: Graph,
+    C: Fn(&G::EdgeData, &G::EdgeData) -> Ordering,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.edges_from.get(from).into_iter().flatten().cloned()
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        let (sorted, unsorted) = if self.inner.is_directed() {
+            (None, Some(self.inner.edges_into(into)))
+        } else {
+            (Some(self.edges_from(into)), None)
+        };
+        sorted
+            .into_iter()
+            .flatten()
+            .chain(unsorted.into_iter().flatten())
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.edges_from(from).filter(move |eid| {
+            let (source, target) = self.inner.edge_ends(eid).into_values();
+            if source == *from {
+                target == *into
+            } else {
+                source == *into
+            }
+        })
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.inner.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.inner.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.edges_from.get(from).map_or(0, Vec::len)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G, C> GraphMut for SortedEdgesGraph<G, C>
+where
+    G: GraphMut,
+    C: Fn(&G::EdgeData, &G::EdgeData) -> Ordering,
+{
+    /// Gets mutable access to the data of an edge.  The edge is not moved if
+    /// the change affects its place in the order; see
+    /// [`SortedEdgesGraph::update_edge_data`].
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        self.inner.edge_data_mut(id)
+    }
+
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        self.inner.node_data_mut(id)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.edges_from.clear();
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        self.inner.add_node(data)
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        let incident: Vec<_> = self.inner.edges_into(id).collect();
+        for eid in incident {
+            self.forget_edge(&eid);
+        }
+        self.edges_from.remove(id);
+        self.inner.remove_node(id)
+    }
+
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        if !self.inner.allows_parallel_edges() {
+            let existing: Vec<_> = self.inner.edges_from_into(from, into).collect();
+            for eid in existing {
+                self.forget_edge(&eid);
+            }
+        }
+        let result = self.inner.add_edge(from, into, data);
+        self.insert_edge(result.edge_id());
+        result
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        self.forget_edge(id);
+        self.inner.remove_edge(id)
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        let mut node_map = FastHashMap::default();
+        let mut edge_map = FastHashMap::default();
+        self.inner.compact(
+            Some(&mut |old: G::NodeId, new: G::NodeId| {
+                node_map.insert(old, new);
+            }),
+            Some(&mut |old: G::EdgeId, new: G::EdgeId| {
+                edge_map.insert(old, new);
+            }),
+        );
+        self.edges_from = std::mem::take(&mut self.edges_from)
+            .into_iter()
+            .map(|(nid, mut edges)| {
+                for eid in edges.iter_mut() {
+                    if let Some(new) = edge_map.get(eid) {
+                        *eid = new.clone();
+                    }
+                }
+                (node_map.get(&nid).cloned().unwrap_or(nid), edges)
+            })
+            .collect();
+        if let Some(collector) = node_map_collector {
+            for (old, new) in node_map {
+                collector.insert(old, new);
+            }
+        }
+        if let Some(collector) = edge_map_collector {
+            for (old, new) in edge_map {
+                collector.insert(old, new);
+            }
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+        self.edges_from.shrink_to_fit();
+        for edges in self.edges_from.values_mut() {
+            edges.shrink_to_fit();
+        }
+    }
+}
+
+impl<G, C> StableNodeIds for SortedEdgesGraph<G, C>
+where
+    G: StableNodeIds,
+    C: Fn(&G::EdgeData, &G::EdgeData) -> Ordering,
+{
+}
+
+impl<G, C> StableEdgeIds for SortedEdgesGraph<G, C>
+where
+    G: StableEdgeIds,
+    C: Fn(&G::EdgeData, &G::EdgeData) -> Ordering,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    fn by_priority(a: &(u32, &str), b: &(u32, &str)) -> Ordering {
+        Reverse(a.0).cmp(&Reverse(b.0))
+    }
+
+    fn labels<G, C>(graph: &SortedEdgesGraph<G, C>, nid: &G::NodeId) -> Vec<&'static str>
+    where
+        G: Graph<EdgeData = (u32, &'static str)>,
+        C: Fn(&G::EdgeData, &G::EdgeData) -> Ordering,
+    {
+        graph
+            .edges_from(nid)
+            .map(|eid| graph.edge_data(&eid).1)
+            .collect()
+    }
+
+    #[test]
+    fn test_directed_order() {
+        let mut inner = BagGraph::<(), (u32, &str), Directed>::default();
+        let a = inner.add_node(());
+        let b = inner.add_node(());
+        inner.add_new_edge(&a, &b, (1, "low"));
+        let mut graph = SortedEdgesGraph::new(inner, by_priority);
+        let high = graph.add_new_edge(&a, &b, (5, "high"));
+        graph.add_new_edge(&a, &a, (3, "mid"));
+        graph.add_new_edge(&a, &b, (3, "mid2"));
+        graph.add_new_edge(&b, &a, (9, "other"));
+
+        assert_eq!(labels(&graph, &a), vec!["high", "mid", "mid2", "low"]);
+        assert_eq!(graph.first_edge_from(&a), Some(high.clone()));
+        assert_eq!(graph.num_edges_from(&a), 4);
+        let into_b: Vec<_> = graph
+            .edges_from_into(&a, &b)
+            .map(|eid| graph.edge_data(&eid).1)
+            .collect();
+        assert_eq!(into_b, vec!["high", "mid2", "low"]);
+
+        graph.update_edge_data(&high, |data| data.0 = 0);
+        assert_eq!(labels(&graph, &a), vec!["mid", "mid2", "low", "high"]);
+        assert_eq!(graph.last_edge_from(&a), Some(high.clone()));
+
+        graph.edge_data_mut(&high).0 = 10;
+        graph.resort();
+        assert_eq!(graph.first_edge_from(&a), Some(high.clone()));
+
+        graph.remove_edge(&high);
+        graph.remove_node(&b);
+        assert_eq!(labels(&graph, &a), vec!["mid"]);
+        assert_eq!(graph.first_edge_from(&b), None);
+    }
+
+    #[test]
+    fn test_undirected_order_and_compact() {
+        let mut graph = SortedEdgesGraph::new(
+            LinkedGraph::<u32, (u32, &str), Undirected, SingleEdge>::default(),
+            by_priority,
+        );
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        let c = graph.add_node(2);
+        graph.add_edge(&a, &b, (1, "ab"));
+        graph.add_edge(&c, &a, (2, "ca"));
+        graph.add_edge(&b, &c, (3, "bc"));
+        assert_eq!(labels(&graph, &a), vec!["ca", "ab"]);
+        assert_eq!(labels(&graph, &b), vec!["bc", "ab"]);
+
+        // Replacing an edge moves it to its new place.
+        graph.add_edge(&b, &a, (4, "ba"));
+        assert_eq!(labels(&graph, &a), vec!["ba", "ca"]);
+        let into_a: Vec<_> = graph
+            .edges_into(&a)
+            .map(|eid| graph.edge_data(&eid).1)
+            .collect();
+        assert_eq!(into_a, vec!["ba", "ca"]);
+
+        graph.remove_node(&c);
+        graph.compact(None, None);
+        let nids: Vec<_> = graph.node_ids().collect();
+        assert_eq!(nids.len(), 2);
+        for nid in &nids {
+            assert_eq!(labels(&graph, nid), vec!["ba"]);
+        }
+    }
+}
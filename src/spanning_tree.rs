@@ -0,0 +1,414 @@
+//! Spanning forests and minimum spanning forests of undirected graphs.
+//!
+//! [`spanning_forest`] finds a spanning forest without regard to weights, in
+//! nearly linear time.
+//!
+//! Two algorithms are provided, both returning a [`SpanningForest`]:
+//!
+//! - [`boruvka`] runs in `O(E log V)` time and is the default used by
+//!   [`GraphUndirected::minimum_spanning_forest`].  With the `rayon` feature,
+//!   [`par_boruvka`] searches for the cheapest edge leaving each component in
+//!   parallel, which is worthwhile for graphs with many millions of edges.
+//! - [`reverse_delete`] runs in `O(E (V + E))` time.  It is mainly useful as
+//!   an independent check on the other algorithm.
+//!
+//! Ties between edges of equal weight are broken by comparing edge IDs, so
+//! all algorithms select the same forest for the same graph.
+
+use std::ops::Add;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(doc)]
+use crate::GraphUndirected;
+use crate::{end_pair::EndPair, hashing::FastHashMap, prelude::*};
+
+/// A minimum spanning forest, as returned by the algorithms in this module.
+/// If the graph is connected, the forest is a single tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpanningForest<E, C> {
+    /// The edges of the forest, in an unspecified order.
+    pub edges: Vec<E>,
+    /// The sum of the weights of `edges`.
+    pub total_weight: C,
+}
+
+/// An edge with its ends translated to dense indices.
+struct IndexedEdge<E, C> {
+    from: usize,
+    into: usize,
+    weight: C,
+    id: E,
+}
+
+impl<E, C> IndexedEdge<E, C>
+where
+    E: Ord,
+    C: Ord,
+{
+    fn is_cheaper_than(&self, other: &Self) -> bool {
+        (&self.weight, &self.id) < (&other.weight, &other.id)
+    }
+}
+
+fn indexed_edges<G, C>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> C,
+) -> Vec<IndexedEdge<G::EdgeId, C>>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    let node_index: FastHashMap<G::NodeId, usize> = graph
+        .node_ids()
+        .enumerate()
+        .map(|(i, nid)| (nid, i))
+        .collect();
+    graph
+        .edge_ids()
+        .map(|eid| {
+            let (from, into) = graph.edge_ends(&eid).into_values();
+            IndexedEdge {
+                from: node_index[&from],
+                into: node_index[&into],
+                weight: weight_fn(&eid),
+                id: eid,
+            }
+        })
+        .collect()
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+            rank: vec![0; size],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    /// Merges the sets containing `a` and `b`, returning false if they were
+    /// already the same set.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        match self.rank[a].cmp(&self.rank[b]) {
+            std::cmp::Ordering::Less => self.parent[a] = b,
+            std::cmp::Ordering::Greater => self.parent[b] = a,
+            std::cmp::Ordering::Equal => {
+                self.parent[b] = a;
+                self.rank[a] += 1;
+            }
+        }
+        true
+    }
+}
+
+/// The shared driver for the sequential and parallel variants of Borůvka's
+/// algorithm.  `select` is given the edges which still connect distinct
+/// components and a component label for each node, and must return the
+/// index of the cheapest edge leaving each component.
+fn boruvka_impl<E, C>(
+    num_nodes: usize,
+    mut edges: Vec<IndexedEdge<E, C>>,
+    select: impl Fn(&[IndexedEdge<E, C>], &[usize]) -> Vec<usize>,
+) -> SpanningForest<E, C>
+where
+    E: Clone,
+    C: Default + Copy + Add<Output = C>,
+{
+    let mut components = UnionFind::new(num_nodes);
+    let mut labels: Vec<usize> = (0..num_nodes).collect();
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        total_weight: C::default(),
+    };
+    edges.retain(|e| e.from != e.into);
+    while !edges.is_empty() {
+        let cheapest = select(&edges, &labels);
+        for i in cheapest {
+            let edge = &edges[i];
+            if components.union(edge.from, edge.into) {
+                forest.edges.push(edge.id.clone());
+                forest.total_weight = forest.total_weight + edge.weight;
+            }
+        }
+        for (i, label) in labels.iter_mut().enumerate() {
+            *label = components.find(i);
+        }
+        edges.retain(|e| labels[e.from] != labels[e.into]);
+    }
+    forest
+}
+
+/// Finds a spanning forest: a tree spanning each connected component.  Each
+/// edge is considered in the order returned by [`Graph::edge_ids`] and kept
+/// if it joins two nodes which are not yet connected, so the edges of the
+/// forest are returned in that order.
+pub fn spanning_forest<G>(graph: &G) -> Vec<G::EdgeId>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+{
+    let mut components = UnionFind::new(graph.num_nodes());
+    indexed_edges(graph, |_| ())
+        .into_iter()
+        .filter(|edge| components.union(edge.from, edge.into))
+        .map(|edge| edge.id)
+        .collect()
+}
+
+/// Finds a minimum spanning forest using Borůvka's algorithm.
+pub fn boruvka<G, C>(graph: &G, weight_fn: impl Fn(&G::EdgeId) -> C) -> SpanningForest<G::EdgeId, C>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+    C: Default + Ord + Copy + Add<Output = C>,
+{
+    let num_nodes = graph.num_nodes();
+    boruvka_impl(
+        num_nodes,
+        indexed_edges(graph, weight_fn),
+        |edges, labels| {
+            let mut cheapest: Vec<Option<usize>> = vec![None; num_nodes];
+            for (i, edge) in edges.iter().enumerate() {
+                for label in [labels[edge.from], labels[edge.into]] {
+                    if cheapest[label].is_none_or(|j| edge.is_cheaper_than(&edges[j])) {
+                        cheapest[label] = Some(i);
+                    }
+                }
+            }
+            cheapest.into_iter().flatten().collect()
+        },
+    )
+}
+
+/// Finds a minimum spanning forest using Borůvka's algorithm, searching for
+/// the cheapest edge leaving each component in parallel.  The weights of all
+/// edges are computed up front on the calling thread.
+#[cfg(feature = "rayon")]
+pub fn par_boruvka<G, C>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> C,
+) -> SpanningForest<G::EdgeId, C>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+    C: Default + Ord + Copy + Add<Output = C> + Send + Sync,
+{
+    boruvka_impl(
+        graph.num_nodes(),
+        indexed_edges(graph, weight_fn),
+        |edges, labels| {
+            let update = |cheapest: &mut FastHashMap<usize, usize>, label: usize, i: usize| {
+                let current = cheapest.entry(label).or_insert(i);
+                if edges[i].is_cheaper_than(&edges[*current]) {
+                    *current = i;
+                }
+            };
+            edges
+                .par_iter()
+                .enumerate()
+                .fold(FastHashMap::default, |mut cheapest, (i, edge)| {
+                    update(&mut cheapest, labels[edge.from], i);
+                    update(&mut cheapest, labels[edge.into], i);
+                    cheapest
+                })
+                .reduce(FastHashMap::default, |mut a, b| {
+                    for (label, i) in b {
+                        update(&mut a, label, i);
+                    }
+                    a
+                })
+                .into_values()
+                .collect()
+        },
+    )
+}
+
+/// Finds a minimum spanning forest using the reverse-delete algorithm: edges
+/// are considered from most to least expensive, and each edge is discarded
+/// unless doing so would disconnect its ends.
+pub fn reverse_delete<G, C>(
+    graph: &G,
+    weight_fn: impl Fn(&G::EdgeId) -> C,
+) -> SpanningForest<G::EdgeId, C>
+where
+    G: Graph<Directedness = Undirected> + ?Sized,
+    C: Default + Ord + Copy + Add<Output = C>,
+{
+    let mut edges = indexed_edges(graph, weight_fn);
+    edges.sort_by(|a, b| (&b.weight, &b.id).cmp(&(&a.weight, &a.id)));
+
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); graph.num_nodes()];
+    for (i, edge) in edges.iter().enumerate() {
+        adjacency[edge.from].push(i);
+        adjacency[edge.into].push(i);
+    }
+    let mut removed = vec![false; edges.len()];
+
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        total_weight: C::default(),
+    };
+    for i in 0..edges.len() {
+        removed[i] = true;
+        let (from, into) = (edges[i].from, edges[i].into);
+        if from != into && !is_connected(&edges, &adjacency, &removed, from, into) {
+            removed[i] = false;
+            forest.edges.push(edges[i].id.clone());
+            forest.total_weight = forest.total_weight + edges[i].weight;
+        }
+    }
+    forest
+}
+
+fn is_connected<E, C>(
+    edges: &[IndexedEdge<E, C>],
+    adjacency: &[Vec<usize>],
+    removed: &[bool],
+    from: usize,
+    into: usize,
+) -> bool {
+    let mut visited = vec![false; adjacency.len()];
+    let mut stack = vec![from];
+    visited[from] = true;
+    while let Some(node) = stack.pop() {
+        if node == into {
+            return true;
+        }
+        for &i in adjacency[node].iter().filter(|&&i| !removed[i]) {
+            let next = if edges[i].from == node {
+                edges[i].into
+            } else {
+                edges[i].from
+            };
+            if !visited[next] {
+                visited[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{BagGraph, GraphUndirected, LinkedGraph};
+
+    use super::*;
+
+    type TestGraph = BagGraph<(), u32, Undirected>;
+
+    fn weight(graph: &TestGraph) -> impl Fn(&<TestGraph as Graph>::EdgeId) -> u32 + '_ {
+        |eid| *graph.edge_data(eid)
+    }
+
+    fn create_graph() -> TestGraph {
+        // Two components: a weighted square with a diagonal, and a pair of
+        // nodes joined by parallel edges.
+        let mut graph = TestGraph::default();
+        let n: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], 1);
+        graph.add_new_edge(&n[1], &n[2], 4);
+        graph.add_new_edge(&n[2], &n[3], 2);
+        graph.add_new_edge(&n[3], &n[0], 5);
+        graph.add_new_edge(&n[0], &n[2], 3);
+        graph.add_new_edge(&n[1], &n[1], 0);
+        graph.add_new_edge(&n[4], &n[5], 7);
+        graph.add_new_edge(&n[5], &n[4], 6);
+        graph
+    }
+
+    fn edge_weights(
+        graph: &TestGraph,
+        forest: &SpanningForest<<TestGraph as Graph>::EdgeId, u32>,
+    ) -> Vec<u32> {
+        let mut weights: Vec<_> = forest.edges.iter().map(|e| *graph.edge_data(e)).collect();
+        weights.sort();
+        weights
+    }
+
+    #[test]
+    fn test_boruvka() {
+        let graph = create_graph();
+        let forest = boruvka(&graph, weight(&graph));
+        assert_eq!(edge_weights(&graph, &forest), vec![1, 2, 3, 6]);
+        assert_eq!(forest.total_weight, 12);
+    }
+
+    #[test]
+    fn test_reverse_delete() {
+        let graph = create_graph();
+        let forest = reverse_delete(&graph, weight(&graph));
+        assert_eq!(edge_weights(&graph, &forest), vec![1, 2, 3, 6]);
+        assert_eq!(forest.total_weight, 12);
+    }
+
+    #[test]
+    fn test_spanning_forest() {
+        let graph = create_graph();
+        let weights: Vec<_> = graph
+            .spanning_forest()
+            .iter()
+            .map(|eid| *graph.edge_data(eid))
+            .collect();
+        // The first three edges of the square, and the first of the
+        // parallel edges.
+        assert_eq!(weights, vec![1, 4, 2, 7]);
+        assert!(TestGraph::default().spanning_forest().is_empty());
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let graph = TestGraph::default();
+        let forest = graph.minimum_spanning_forest(|_| 1u32);
+        assert!(forest.edges.is_empty());
+        assert_eq!(forest.total_weight, 0);
+    }
+
+    #[test]
+    fn test_ties_select_same_edges() {
+        let mut graph = LinkedGraph::<(), (), Undirected>::default();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        for i in 0..n.len() {
+            for j in i + 1..n.len() {
+                graph.add_new_edge(&n[i], &n[j], ());
+            }
+        }
+        let a = boruvka(&graph, |_| 1);
+        let b = reverse_delete(&graph, |_| 1);
+        assert_eq!(a.edges.len(), 4);
+        assert_eq!(
+            a.edges.iter().collect::<HashSet<_>>(),
+            b.edges.iter().collect::<HashSet<_>>()
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_boruvka() {
+        let graph = create_graph();
+        let forest = par_boruvka(&graph, weight(&graph));
+        assert_eq!(
+            forest.edges.iter().collect::<HashSet<_>>(),
+            boruvka(&graph, weight(&graph))
+                .edges
+                .iter()
+                .collect::<HashSet<_>>()
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Reachability-preserving sparsification of directed graphs.
+//!
+//! [`sparsify_for_reachability`] chooses a subset of the edges of a directed
+//! graph which is small enough to render, while changing which nodes can
+//! reach which as little as possible.  It works in two stages:
+//!
+//! 1. Edges which are not needed to preserve reachability are removed.
+//!    Between strongly connected components, this is the transitive
+//!    reduction of the condensation, with a single edge kept for each pair of
+//!    adjacent components.  Within each component, the kept edges form a tree
+//!    out of an arbitrary root and a tree back into it.  Self-loops are always
+//!    removed.
+//! 2. If more than `max_edges` edges remain, the least important ones are
+//!    removed.  The importance of an edge is the number of paths through it
+//!    in the reduced condensation, weighted by the sizes of the components at
+//!    each end, so edges which many source-to-sink paths depend on are kept
+//!    in preference to peripheral ones.
+//!
+//! The edges removed in the second stage are reported separately, and
+//! [`Sparsification::lost_reachability`] finds which of them actually
+//! disconnected their ends.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+    scc,
+};
+
+/// The ends of an edge, and its ID.
+type EndsAndId<G> = (
+    <G as Graph>::NodeId,
+    <G as Graph>::NodeId,
+    <G as Graph>::EdgeId,
+);
+
+/// The result of [`sparsify_for_reachability`].  Every edge of the graph is
+/// in exactly one of the lists.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sparsification<E> {
+    /// The edges to keep, in an unspecified order.
+    pub kept: Vec<E>,
+    /// Edges removed without changing the reachability of any node.
+    pub redundant: Vec<E>,
+    /// Edges removed to meet the edge budget, which may have made some nodes
+    /// unreachable from others.
+    pub dropped: Vec<E>,
+}
+
+impl<E> Sparsification<E>
+where
+    E: GraphElementId,
+{
+    /// Returns true if every node can reach the same nodes using only the
+    /// kept edges as using every edge.
+    pub fn preserves_reachability(&self) -> bool {
+        self.dropped.is_empty()
+    }
+
+    /// Returns the dropped edges whose target can no longer be reached from
+    /// their source using only the kept edges.  The ends of the other dropped
+    /// edges are still connected, but other pairs of nodes may not be.
+    pub fn lost_reachability<G>(&self, graph: &G) -> Vec<E>
+    where
+        G: Graph<EdgeId = E> + ?Sized,
+    {
+        let mut successors: FastHashMap<G::NodeId, Vec<G::NodeId>> = FastHashMap::default();
+        for eid in &self.kept {
+            let (from, into) = graph.edge_ends(eid).into_values();
+            successors.entry(from).or_default().push(into);
+        }
+        self.dropped
+            .iter()
+            .filter(|eid| {
+                let (from, into) = graph.edge_ends(eid).into_values();
+                let mut visited: FastHashSet<_> = [from.clone()].into_iter().collect();
+                let mut queue = VecDeque::from([from]);
+                while let Some(nid) = queue.pop_front() {
+                    if nid == into {
+                        return false;
+                    }
+                    for next in successors.get(&nid).into_iter().flatten() {
+                        if visited.insert(next.clone()) {
+                            queue.push_back(next.clone());
+                        }
+                    }
+                }
+                true
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Chooses at most `max_edges` edges of a directed graph which preserve
+/// reachability as much as possible.  See the [module documentation](self).
+pub fn sparsify_for_reachability<G>(graph: &G, max_edges: usize) -> Sparsification<G::EdgeId>
+where
+    G: Graph<Directedness = Directed> + ?Sized,
+{
+    let components = scc::strongly_connected_components(graph);
+    let mut component_of =
+        FastHashMap::with_capacity_and_hasher(graph.num_nodes(), Default::default());
+    for (c, component) in components.iter().enumerate() {
+        for nid in component {
+            component_of.insert(nid.clone(), c);
+        }
+    }
+
+    let mut redundant = Vec::new();
+    let mut internal: Vec<Vec<EndsAndId<G>>> = vec![Vec::new(); components.len()];
+    // The edge kept for each pair of adjacent components, ordered so that the
+    // result does not depend on the order of iteration over a hash map.
+    let mut between: BTreeMap<(usize, usize), G::EdgeId> = BTreeMap::new();
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid);
+        let (a, b) = (component_of[&from], component_of[&into]);
+        if from == into || (a != b && between.contains_key(&(a, b))) {
+            redundant.push(eid);
+        } else if a == b {
+            internal[a].push((from, into, eid));
+        } else {
+            between.insert((a, b), eid);
+        }
+    }
+
+    // Candidates for keeping, with the components at each end.
+    let mut candidates = Vec::new();
+    for (c, edges) in internal.into_iter().enumerate() {
+        let (kept, removed) = strongly_connected_subset(&components[c][0], edges);
+        candidates.extend(kept.into_iter().map(|eid| (c, c, eid)));
+        redundant.extend(removed);
+    }
+
+    let condensation = Condensation::new(components.len(), between.keys().copied());
+    let reduced = condensation.transitive_reduction();
+    for ((a, b), eid) in between {
+        if reduced.contains(&(a, b)) {
+            candidates.push((a, b, eid));
+        } else {
+            redundant.push(eid);
+        }
+    }
+
+    let mut dropped = Vec::new();
+    if candidates.len() > max_edges {
+        let sizes: Vec<_> = components.iter().map(|c| c.len() as f64).collect();
+        let (upstream, downstream) = condensation.path_weights(&reduced, &sizes);
+        let importance = |&(a, b, _): &(usize, usize, G::EdgeId)| upstream[a] * downstream[b];
+        // Ties are broken by component to keep the order stable.
+        candidates.sort_by(|x, y| {
+            importance(y)
+                .total_cmp(&importance(x))
+                .then_with(|| (x.0, x.1).cmp(&(y.0, y.1)))
+        });
+        dropped = candidates
+            .split_off(max_edges)
+            .into_iter()
+            .map(|(_, _, eid)| eid)
+            .collect();
+    }
+
+    Sparsification {
+        kept: candidates.into_iter().map(|(_, _, eid)| eid).collect(),
+        redundant,
+        dropped,
+    }
+}
+
+/// Splits the edges within a strongly connected component into a subset
+/// which keeps it strongly connected and the remaining edges.  The subset is
+/// a depth-first tree out of `root` plus enough edges to lead back into it.
+fn strongly_connected_subset<N, E>(root: &N, edges: Vec<(N, N, E)>) -> (Vec<E>, Vec<E>)
+where
+    N: GraphElementId,
+{
+    let mut out_edges: FastHashMap<&N, Vec<usize>> = FastHashMap::default();
+    let mut in_edges: FastHashMap<&N, Vec<usize>> = FastHashMap::default();
+    for (i, (from, into, _)) in edges.iter().enumerate() {
+        out_edges.entry(from).or_default().push(i);
+        in_edges.entry(into).or_default().push(i);
+    }
+    let mut keep = vec![false; edges.len()];
+
+    // A depth-first tree tends to produce long paths, so fewer edges are
+    // needed to get back to the root.
+    let mut visited = FastHashSet::default();
+    let mut stack = vec![(root, None)];
+    while let Some((nid, parent_edge)) = stack.pop() {
+        if !visited.insert(nid) {
+            continue;
+        }
+        if let Some(i) = parent_edge {
+            keep[i] = true;
+        }
+        for &i in out_edges.get(nid).into_iter().flatten().rev() {
+            if !visited.contains(&edges[i].1) {
+                stack.push((&edges[i].1, Some(i)));
+            }
+        }
+    }
+
+    // Grow the set of nodes which can reach the root using kept edges, adding
+    // an edge whenever no kept edge leads into the set.
+    let mut reaches_root: FastHashSet<_> = [root].into_iter().collect();
+    let mut queue = VecDeque::from([root]);
+    let mut fallback = VecDeque::new();
+    loop {
+        while let Some(nid) = queue.pop_front() {
+            for &i in in_edges.get(nid).into_iter().flatten() {
+                if keep[i] {
+                    if reaches_root.insert(&edges[i].0) {
+                        queue.push_back(&edges[i].0);
+                    }
+                } else {
+                    fallback.push_back(i);
+                }
+            }
+        }
+        match fallback.pop_front() {
+            Some(i) if !reaches_root.contains(&edges[i].0) => {
+                keep[i] = true;
+                reaches_root.insert(&edges[i].0);
+                queue.push_back(&edges[i].0);
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    let mut kept = Vec::new();
+    let mut removed = Vec::new();
+    for ((_, _, eid), keep) in edges.into_iter().zip(keep) {
+        if keep {
+            kept.push(eid);
+        } else {
+            removed.push(eid);
+        }
+    }
+    (kept, removed)
+}
+
+/// The condensation of a graph, whose nodes are the indices of its strongly
+/// connected components.
+struct Condensation {
+    successors: Vec<Vec<usize>>,
+    /// The components in topological order.
+    order: Vec<usize>,
+}
+
+impl Condensation {
+    fn new(size: usize, edges: impl Iterator<Item = (usize, usize)>) -> Self {
+        let mut successors = vec![Vec::new(); size];
+        let mut in_degree = vec![0; size];
+        for (a, b) in edges {
+            successors[a].push(b);
+            in_degree[b] += 1;
+        }
+        let mut order: Vec<_> = (0..size).filter(|&c| in_degree[c] == 0).collect();
+        let mut i = 0;
+        while i < order.len() {
+            for &b in &successors[order[i]] {
+                in_degree[b] -= 1;
+                if in_degree[b] == 0 {
+                    order.push(b);
+                }
+            }
+            i += 1;
+        }
+        Self { successors, order }
+    }
+
+    /// Returns the edges of the transitive reduction.  The successors of each
+    /// component are visited in topological order, so a successor which is
+    /// reachable through an earlier one is redundant.
+    fn transitive_reduction(&self) -> FastHashSet<(usize, usize)> {
+        let mut position = vec![0; self.order.len()];
+        for (i, &c) in self.order.iter().enumerate() {
+            position[c] = i;
+        }
+        let mut reduced = FastHashSet::default();
+        // `visited[c] == a` if `c` has been reached while reducing `a`.
+        let mut visited = vec![usize::MAX; self.order.len()];
+        for a in 0..self.successors.len() {
+            let mut successors = self.successors[a].clone();
+            successors.sort_by_key(|&b| position[b]);
+            for b in successors {
+                if visited[b] == a {
+                    continue;
+                }
+                reduced.insert((a, b));
+                let mut stack = vec![b];
+                visited[b] = a;
+                while let Some(c) = stack.pop() {
+                    for &d in &self.successors[c] {
+                        if visited[d] != a {
+                            visited[d] = a;
+                            stack.push(d);
+                        }
+                    }
+                }
+            }
+        }
+        reduced
+    }
+
+    /// Returns, for each component, the total size of the components on
+    /// paths leading into it and out of it using only the given edges,
+    /// counting each path separately and including the component itself.
+    fn path_weights(
+        &self,
+        edges: &FastHashSet<(usize, usize)>,
+        sizes: &[f64],
+    ) -> (Vec<f64>, Vec<f64>) {
+        let mut upstream = sizes.to_vec();
+        let mut downstream = sizes.to_vec();
+        for &a in &self.order {
+            for &b in &self.successors[a] {
+                if edges.contains(&(a, b)) {
+                    upstream[b] += upstream[a];
+                }
+            }
+        }
+        for &a in self.order.iter().rev() {
+            for &b in &self.successors[a] {
+                if edges.contains(&(a, b)) {
+                    downstream[a] += downstream[b];
+                }
+            }
+        }
+        (upstream, downstream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    /// Returns the set of pairs of distinct nodes such that the first can
+    /// reach the second, using only the given edges.
+    fn reachable_pairs<G: Graph<Directedness = Directed>>(
+        graph: &G,
+        edges: &[G::EdgeId],
+    ) -> HashSet<(G::NodeId, G::NodeId)> {
+        let mut pairs = HashSet::new();
+        for start in graph.node_ids() {
+            let mut visited = HashSet::from([start.clone()]);
+            let mut stack = vec![start.clone()];
+            while let Some(nid) = stack.pop() {
+                for eid in edges {
+                    let (from, into) = graph.edge_ends(eid);
+                    if from == nid && visited.insert(into.clone()) {
+                        pairs.insert((start.clone(), into.clone()));
+                        stack.push(into);
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn test_transitive_reduction() {
+        let mut graph = BagGraph::<char, (), Directed>::default();
+        let a = graph.add_node('a');
+        let b = graph.add_node('b');
+        let c = graph.add_node('c');
+        let d = graph.add_node('d');
+        let ab = graph.add_new_edge(&a, &b, ());
+        let bc = graph.add_new_edge(&b, &c, ());
+        let cd = graph.add_new_edge(&c, &d, ());
+        graph.add_edge(&a, &c, ());
+        graph.add_edge(&a, &d, ());
+        graph.add_edge(&a, &b, ());
+        graph.add_edge(&c, &c, ());
+
+        let result = graph.sparsify_for_reachability(usize::MAX);
+        assert!(result.preserves_reachability());
+        let mut kept = result.kept.clone();
+        kept.sort();
+        let mut expected = vec![ab, bc, cd];
+        expected.sort();
+        assert_eq!(kept, expected);
+        assert_eq!(result.redundant.len(), 4);
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        // Two cycles with chords, connected by two parallel paths.
+        let mut graph = LinkedGraph::<usize, (), Directed>::default();
+        let nodes: Vec<_> = (0..8).map(|i| graph.add_node(i)).collect();
+        for (from, into) in [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 0),
+            (0, 2),
+            (3, 1),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 4),
+            (4, 6),
+            (2, 5),
+            (3, 6),
+        ] {
+            graph.add_edge(&nodes[from], &nodes[into], ());
+        }
+
+        let all: Vec<_> = graph.edge_ids().collect();
+        let result = sparsify_for_reachability(&graph, usize::MAX);
+        assert!(result.preserves_reachability());
+        assert_eq!(
+            reachable_pairs(&graph, &result.kept),
+            reachable_pairs(&graph, &all)
+        );
+        // The depth-first tree out of each component's root follows its
+        // cycle, so the chords are dropped, and one edge between the
+        // components is kept.
+        assert_eq!(result.kept.len(), 9);
+        assert_eq!(result.kept.len() + result.redundant.len(), all.len());
+    }
+
+    #[test]
+    fn test_edge_budget() {
+        // A long chain with a short branch joining it near the end.
+        let mut graph = BagGraph::<usize, (), Directed>::default();
+        let nodes: Vec<_> = (0..6).map(|i| graph.add_node(i)).collect();
+        for i in 0..4 {
+            graph.add_edge(&nodes[i], &nodes[i + 1], ());
+        }
+        let branch = graph.add_new_edge(&nodes[5], &nodes[4], ());
+
+        let result = sparsify_for_reachability(&graph, 4);
+        assert!(!result.preserves_reachability());
+        assert_eq!(result.kept.len(), 4);
+        assert!(result.redundant.is_empty());
+        assert_eq!(result.dropped, vec![branch.clone()]);
+        assert_eq!(result.lost_reachability(&graph), vec![branch]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Marker traits for graphs whose IDs stay valid.
+//!
+//! Some graphs keep every node and edge ID valid until that node or edge is
+//! removed, while others may renumber their elements when
+//! [`GraphMut::compact`] is called, so that IDs held across a compaction
+//! refer to the wrong element or fail a runtime check.  Code which stores IDs
+//! for a long time, such as an index or a cache keyed by IDs, can require
+//! [`StableNodeIds`] or [`StableEdgeIds`] to rule out the second kind of
+//! graph at compile time:
+//!
+//! ```
+//! # use std::collections::HashMap;
+//! # use graphitude::{LinkedGraph, StableNodeIds, prelude::*};
+//! struct NameIndex<G: Graph> {
+//!     nodes: HashMap<String, G::NodeId>,
+//! }
+//!
+//! impl<G: GraphMut<NodeData = String> + StableNodeIds> NameIndex<G> {
+//!     fn add(&mut self, graph: &mut G, name: &str) -> G::NodeId {
+//!         let nid = graph.add_node(name.to_string());
+//!         self.nodes.insert(name.to_string(), nid.clone());
+//!         nid
+//!     }
+//! }
+//!
+//! let mut index = NameIndex::<LinkedGraph<String, (), Directed>> {
+//!     nodes: HashMap::new(),
+//! };
+//! let mut graph = LinkedGraph::default();
+//! index.add(&mut graph, "a");
+//! ```
+//!
+//! [`LinkedGraph`](crate::LinkedGraph) and [`SlabGraph`](crate::SlabGraph)
+//! are stable.  [`BagGraph`](crate::BagGraph) and
+//! [`AdjacencyGraph`](crate::AdjacencyGraph) are not, since compaction
+//! renumbers their elements.  Wrapper graphs are stable when the graph they
+//! wrap is.
+
+use crate::prelude::*;
+
+/// A graph whose node IDs stay valid, and keep referring to the same node,
+/// until that node is removed.  Adding or removing other nodes and edges,
+/// compacting, or shrinking the graph does not affect them.
+pub trait StableNodeIds: Graph {}
+
+/// A graph whose edge IDs stay valid, and keep referring to the same edge,
+/// until that edge or one of its ends is removed.  Adding or removing other
+/// nodes and edges, compacting, or shrinking the graph does not affect them.
+pub trait StableEdgeIds: Graph {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LinkedGraph, NotifyingGraph, SlabGraph, SoftDeleteGraph};
+
+    use super::*;
+
+    /// Removes every other node, checking that the IDs of the remaining
+    /// nodes and edges still refer to the same elements.
+    fn check_stable<G>(mut graph: G)
+    where
+        G: GraphMut<NodeData = usize, EdgeData = usize> + StableNodeIds + StableEdgeIds,
+    {
+        let nodes: Vec<_> = (0..6).map(|i| graph.add_node(i)).collect();
+        let edges: Vec<_> = (0..6)
+            .map(|i| {
+                graph
+                    .add_edge(&nodes[i], &nodes[(i + 1) % 6], i)
+                    .into_edge_id()
+            })
+            .collect();
+        graph.remove_node(&nodes[0]);
+        graph.remove_edge(&edges[2]);
+        graph.compact(None, None);
+        graph.shrink_to_fit();
+        graph.add_node(6);
+        for (i, nid) in nodes.iter().enumerate().skip(1) {
+            assert_eq!(*graph.node_data(nid), i);
+        }
+        for i in [1, 3, 4] {
+            assert_eq!(*graph.edge_data(&edges[i]), i);
+        }
+    }
+
+    #[test]
+    fn test_stable_graphs() {
+        check_stable(LinkedGraph::<usize, usize, Directed>::default());
+        check_stable(SlabGraph::<usize, usize, Undirected>::default());
+        check_stable(NotifyingGraph::new(
+            LinkedGraph::<usize, usize, Directed>::default(),
+        ));
+        check_stable(SoftDeleteGraph::new(
+            SlabGraph::<usize, usize, Directed>::default(),
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+//! Basic statistics about the structure of a graph.
+//!
+//! Degrees count the ends of edges, so a self-loop adds 2 to the degree of its
+//! node, and in a directed graph a node's degree is the sum of its in-degree
+//! and out-degree.  The clustering coefficients ignore edge direction,
+//! self-loops and parallel edges, and are computed on the underlying simple
+//! undirected graph.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+};
+
+/// Returns the degree of every node in the graph.
+pub fn degrees<G>(graph: &G) -> HashMap<G::NodeId, usize>
+where
+    G: Graph + ?Sized,
+{
+    let mut degrees: HashMap<_, _> = graph.node_ids().map(|nid| (nid, 0)).collect();
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        *degrees.get_mut(&from).expect("edge ends should be nodes") += 1;
+        *degrees.get_mut(&into).expect("edge ends should be nodes") += 1;
+    }
+    degrees
+}
+
+/// Returns a map from each degree to the number of nodes with that degree.
+/// Degrees which no node has are omitted.
+pub fn degree_distribution<G>(graph: &G) -> BTreeMap<usize, usize>
+where
+    G: Graph + ?Sized,
+{
+    let mut distribution = BTreeMap::new();
+    for degree in degrees(graph).into_values() {
+        *distribution.entry(degree).or_default() += 1;
+    }
+    distribution
+}
+
+/// Returns the number of edges divided by the number of edges in a complete
+/// simple graph with the same number of nodes.  The result may be greater
+/// than 1 if the graph has self-loops or parallel edges.  Returns 0 for
+/// graphs with fewer than two nodes.
+pub fn density<G>(graph: &G) -> f64
+where
+    G: Graph + ?Sized,
+{
+    let n = graph.num_nodes() as f64;
+    if n < 2.0 {
+        return 0.0;
+    }
+    let max_edges = if graph.is_directed() {
+        n * (n - 1.0)
+    } else {
+        n * (n - 1.0) / 2.0
+    };
+    graph.num_edges() as f64 / max_edges
+}
+
+/// Returns the average degree of the nodes in the graph, or 0 for an empty
+/// graph.
+pub fn average_degree<G>(graph: &G) -> f64
+where
+    G: Graph + ?Sized,
+{
+    match graph.num_nodes() {
+        0 => 0.0,
+        n => 2.0 * graph.num_edges() as f64 / n as f64,
+    }
+}
+
+/// Returns the distinct neighbors of a node, ignoring edge direction and
+/// self-loops.
+fn neighbor_set<G>(graph: &G, node: &G::NodeId) -> FastHashSet<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    graph
+        .successors(node)
+        .chain(graph.predecessors(node))
+        .filter(|nid| nid != node)
+        .collect()
+}
+
+/// Counts the pairs of distinct neighbors of a node which are adjacent to
+/// each other, and the total number of such pairs.
+fn neighbor_links<G>(
+    neighbors: &FastHashSet<G::NodeId>,
+    is_adjacent: impl Fn(&G::NodeId, &G::NodeId) -> bool,
+) -> (usize, usize)
+where
+    G: Graph + ?Sized,
+{
+    let neighbors: Vec<_> = neighbors.iter().collect();
+    let mut links = 0;
+    for (i, a) in neighbors.iter().enumerate() {
+        for b in neighbors[i + 1..].iter() {
+            if is_adjacent(a, b) {
+                links += 1;
+            }
+        }
+    }
+    let k = neighbors.len();
+    (links, k * k.saturating_sub(1) / 2)
+}
+
+/// Returns the local clustering coefficient of a node: the fraction of pairs
+/// of its neighbors which are adjacent to each other.  Returns 0 for nodes
+/// with fewer than two neighbors.
+pub fn clustering_coefficient<G>(graph: &G, node: &G::NodeId) -> f64
+where
+    G: Graph + ?Sized,
+{
+    let neighbors = neighbor_set(graph, node);
+    let (links, pairs) = neighbor_links::<G>(&neighbors, |a, b| {
+        graph.has_edge_from_into(a, b) || graph.has_edge_from_into(b, a)
+    });
+    if pairs == 0 {
+        0.0
+    } else {
+        links as f64 / pairs as f64
+    }
+}
+
+/// Returns the global clustering coefficient (transitivity) of the graph:
+/// three times the number of triangles divided by the number of connected
+/// triples of nodes.  Returns 0 if the graph has no connected triples.
+pub fn global_clustering_coefficient<G>(graph: &G) -> f64
+where
+    G: Graph + ?Sized,
+{
+    let neighbors: FastHashMap<_, _> = graph
+        .node_ids()
+        .map(|nid| {
+            let set = neighbor_set(graph, &nid);
+            (nid, set)
+        })
+        .collect();
+    let (mut total_links, mut total_pairs) = (0, 0);
+    for set in neighbors.values() {
+        let (links, pairs) = neighbor_links::<G>(set, |a, b| neighbors[a].contains(b));
+        total_links += links;
+        total_pairs += pairs;
+    }
+    if total_pairs == 0 {
+        0.0
+    } else {
+        total_links as f64 / total_pairs as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    type TestGraph = BagGraph<(), (), Undirected>;
+
+    /// Creates a triangle 0-1-2 with a pendant node 3 attached to 0.
+    fn create_graph() -> (TestGraph, Vec<<TestGraph as Graph>::NodeId>) {
+        let mut graph = BagGraph::default();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_new_edge(&n[0], &n[1], ());
+        graph.add_new_edge(&n[1], &n[2], ());
+        graph.add_new_edge(&n[2], &n[0], ());
+        graph.add_new_edge(&n[0], &n[3], ());
+        (graph, n)
+    }
+
+    #[test]
+    fn test_degree_distribution() {
+        let (mut graph, n) = create_graph();
+        assert_eq!(
+            degree_distribution(&graph),
+            BTreeMap::from([(1, 1), (2, 2), (3, 1)])
+        );
+        graph.add_new_edge(&n[3], &n[3], ());
+        assert_eq!(degrees(&graph)[&n[3]], 3);
+    }
+
+    #[test]
+    fn test_density_and_average_degree() {
+        let (graph, _) = create_graph();
+        assert_eq!(density(&graph), 4.0 / 6.0);
+        assert_eq!(average_degree(&graph), 2.0);
+
+        let mut directed = LinkedGraph::<(), (), Directed>::default();
+        assert_eq!(density(&directed), 0.0);
+        assert_eq!(average_degree(&directed), 0.0);
+        let a = directed.add_node(());
+        let b = directed.add_node(());
+        directed.add_new_edge(&a, &b, ());
+        assert_eq!(density(&directed), 0.5);
+    }
+
+    #[test]
+    fn test_clustering_coefficient() {
+        let (graph, n) = create_graph();
+        assert_eq!(clustering_coefficient(&graph, &n[0]), 1.0 / 3.0);
+        assert_eq!(clustering_coefficient(&graph, &n[1]), 1.0);
+        assert_eq!(clustering_coefficient(&graph, &n[3]), 0.0);
+    }
+
+    #[test]
+    fn test_global_clustering_coefficient() {
+        let (graph, _) = create_graph();
+        // One triangle and five connected triples: three centered on node 0
+        // and one each on nodes 1 and 2.
+        assert_eq!(global_clustering_coefficient(&graph), 3.0 / 5.0);
+
+        let mut directed = BagGraph::<(), (), Directed>::default();
+        let n: Vec<_> = (0..3).map(|_| directed.add_node(())).collect();
+        directed.add_new_edge(&n[0], &n[1], ());
+        directed.add_new_edge(&n[1], &n[2], ());
+        assert_eq!(global_clustering_coefficient(&directed), 0.0);
+        directed.add_new_edge(&n[0], &n[2], ());
+        assert_eq!(global_clustering_coefficient(&directed), 1.0);
+    }
+}
@@ -0,0 +1,144 @@
+//! Traversals which can be consumed as async streams.
+//!
+//! A [`TraversalStream`] wraps one of the traversal iterators from
+//! [`search`](crate::search) and implements [`Stream`].  Advancing a
+//! traversal never blocks, but a long traversal which is always ready would
+//! otherwise monopolize the task polling it.  To keep the executor
+//! responsive, the stream yields at most a fixed budget of items in a row,
+//! then wakes its task and returns [`Poll::Pending`] once so that other tasks
+//! get a chance to run.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+/// The default number of items a [`TraversalStream`] yields before giving
+/// control back to the executor.
+pub const DEFAULT_BUDGET: usize = 128;
+
+/// A stream which yields the items of an iterator, periodically yielding to
+/// the executor.  See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct TraversalStream<I> {
+    iter: I,
+    budget: usize,
+    remaining: usize,
+}
+
+impl<I: Iterator> TraversalStream<I> {
+    /// Creates a stream with the [default budget](DEFAULT_BUDGET).
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            budget: DEFAULT_BUDGET,
+            remaining: DEFAULT_BUDGET,
+        }
+    }
+
+    /// Sets the number of items to yield before giving control back to the
+    /// executor.  Panics if `budget` is zero.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        assert!(budget > 0, "budget must be positive");
+        self.budget = budget;
+        self.remaining = budget;
+        self
+    }
+
+    /// Unwraps the stream, returning the underlying iterator.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I> Stream for TraversalStream<I>
+where
+    I: Iterator + Unpin,
+{
+    type Item = I::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.remaining == 0 {
+            this.remaining = this.budget;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        this.remaining -= 1;
+        Poll::Ready(this.iter.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Waker;
+
+    use crate::{BagGraph, prelude::*};
+
+    use super::*;
+
+    /// Polls a stream to completion, returning its items and the number of
+    /// times it returned `Pending`.
+    fn drain<S: Stream + Unpin>(mut stream: S) -> (Vec<S::Item>, usize) {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut items = Vec::new();
+        let mut pending = 0;
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(item)) => items.push(item),
+                Poll::Ready(None) => return (items, pending),
+                Poll::Pending => pending += 1,
+            }
+        }
+    }
+
+    fn path_graph(len: usize) -> (BagGraph<usize, (), Directed>, Vec<usize>) {
+        let mut graph = BagGraph::default();
+        let nodes: Vec<_> = (0..len).map(|i| graph.add_node(i)).collect();
+        for pair in nodes.windows(2) {
+            graph.add_edge(&pair[0], &pair[1], ());
+        }
+        (graph, (0..len).collect())
+    }
+
+    #[test]
+    fn test_bfs_stream() {
+        let (graph, expected) = path_graph(10);
+        let start = graph
+            .node_ids()
+            .find(|id| *graph.node_data(id) == 0)
+            .unwrap();
+        let (items, pending) = drain(graph.bfs_stream(&start).with_budget(3));
+        let data: Vec<_> = items.iter().map(|id| *graph.node_data(id)).collect();
+        assert_eq!(data, expected);
+        // 11 polls return `Ready`, so the budget runs out 3 times.
+        assert_eq!(pending, 3);
+    }
+
+    #[test]
+    fn test_dfs_stream() {
+        let (graph, expected) = path_graph(5);
+        let start = graph
+            .node_ids()
+            .find(|id| *graph.node_data(id) == 0)
+            .unwrap();
+        let (items, pending) = drain(graph.dfs_stream(&start));
+        let data: Vec<_> = items.iter().map(|id| *graph.node_data(id)).collect();
+        assert_eq!(data, expected);
+        assert_eq!(pending, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "budget must be positive")]
+    fn test_zero_budget() {
+        let (graph, _) = path_graph(1);
+        let start = graph.node_ids().next().unwrap();
+        let _ = graph.bfs_stream(&start).with_budget(0);
+    }
+}
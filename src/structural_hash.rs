@@ -0,0 +1,233 @@
+//! Canonical hashes of whole graphs.
+//!
+//! [`structural_hash`] computes a hash of a graph which does not depend on
+//! its node or edge IDs, or on the order in which they were added, so
+//! isomorphic graphs always have the same hash.  It is computed with the
+//! Weisfeiler-Lehman refinement also used by [`signature`](crate::signature):
+//! each node's label is repeatedly replaced by a hash of its label and the
+//! sorted labels of its edges and neighbors, until the labels stop
+//! splitting the nodes into more classes, and the hash of the graph is a
+//! hash of the sorted final labels.  [`structural_hash_by`] additionally
+//! includes keys derived from the node and edge data.
+//!
+//! Unlike the standard library's hashers, the [`StableHasher`] used here is
+//! not seeded per process, so hashes can be stored and compared across runs,
+//! for example as cache keys for results computed from a graph's shape.
+//! The structure is hashed as fixed-width little-endian integers, so it
+//! hashes the same on every target, but data keys are hashed with their
+//! [`Hash`] implementations, which may differ between targets.
+//!
+//! Like any hash, and since Weisfeiler-Lehman refinement cannot tell apart
+//! some non-isomorphic graphs, equal hashes do not guarantee that two graphs
+//! are isomorphic.
+
+use std::hash::{Hash, Hasher};
+
+use crate::{end_pair::EndPair, hashing::FastHashMap, hashing::FastHashSet, prelude::*};
+
+/// A 64-bit FNV-1a hasher, whose results depend only on the bytes written
+/// to it.
+#[derive(Clone, Copy, Debug)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn stable_hash(value: &impl Hash) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns a hash of the structure of `graph`, ignoring node and edge data.
+pub fn structural_hash<G>(graph: &G) -> u64
+where
+    G: Graph + ?Sized,
+{
+    structural_hash_by(graph, |_| (), |_| ())
+}
+
+/// Returns a hash of the structure of `graph` in which each node is labeled
+/// with `node_key` of its data, and each edge with `edge_key` of its data.
+/// To include the data itself, pass functions returning their argument.
+pub fn structural_hash_by<G, NK, EK>(
+    graph: &G,
+    node_key: impl Fn(&G::NodeData) -> NK,
+    edge_key: impl Fn(&G::EdgeData) -> EK,
+) -> u64
+where
+    G: Graph + ?Sized,
+    NK: Hash,
+    EK: Hash,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let index: FastHashMap<_, _> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, nid)| (nid.clone(), i))
+        .collect();
+    // The label and other end of each edge from and into each node.  In an
+    // undirected graph, all edges are in `edges_out`.
+    let mut edges_out = vec![Vec::new(); nodes.len()];
+    let mut edges_in = vec![Vec::new(); nodes.len()];
+    for eid in graph.edge_ids() {
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        let (from, into) = (index[&from], index[&into]);
+        let label = stable_hash(&edge_key(graph.edge_data(&eid)));
+        edges_out[from].push((label, into));
+        if graph.is_directed() {
+            edges_in[into].push((label, from));
+        } else if from != into {
+            edges_out[into].push((label, from));
+        }
+    }
+
+    let mut labels: Vec<u64> = nodes
+        .iter()
+        .map(|nid| stable_hash(&node_key(graph.node_data(nid))))
+        .collect();
+    let mut num_classes = count_distinct(&labels);
+    loop {
+        labels = (0..nodes.len())
+            .map(|i| {
+                let mut hasher = StableHasher::default();
+                hasher.write(&labels[i].to_le_bytes());
+                for edges in [&edges_out[i], &edges_in[i]] {
+                    let mut neighborhood: Vec<_> = edges
+                        .iter()
+                        .map(|&(label, other)| (label, labels[other]))
+                        .collect();
+                    neighborhood.sort_unstable();
+                    hasher.write(&(neighborhood.len() as u64).to_le_bytes());
+                    for (label, other) in neighborhood {
+                        hasher.write(&label.to_le_bytes());
+                        hasher.write(&other.to_le_bytes());
+                    }
+                }
+                hasher.finish()
+            })
+            .collect();
+        let new_num_classes = count_distinct(&labels);
+        if new_num_classes <= num_classes {
+            break;
+        }
+        num_classes = new_num_classes;
+    }
+
+    labels.sort_unstable();
+    let mut hasher = StableHasher::default();
+    hasher.write(&[u8::from(graph.is_directed())]);
+    hasher.write(&(graph.num_edges() as u64).to_le_bytes());
+    write_u64s(&mut hasher, &labels);
+    hasher.finish()
+}
+
+/// Writes `values` to `hasher` as a count followed by the values, all as
+/// little-endian `u64`s.
+fn write_u64s(hasher: &mut StableHasher, values: &[u64]) {
+    hasher.write(&(values.len() as u64).to_le_bytes());
+    for value in values {
+        hasher.write(&value.to_le_bytes());
+    }
+}
+
+fn count_distinct(labels: &[u64]) -> usize {
+    labels.iter().collect::<FastHashSet<_>>().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    const STABLE_HASH: u64 = 14173482242716490830;
+
+    #[test]
+    fn test_isomorphic_graphs() {
+        let mut graph1 = LinkedGraph::<&str, u32, Directed>::default();
+        let [a, b, c] = ["a", "b", "c"].map(|name| graph1.add_node(name));
+        graph1.add_edge(&a, &b, 1);
+        graph1.add_edge(&b, &c, 2);
+        graph1.add_edge(&a, &c, 3);
+
+        // The same shape, built in a different order in another graph type.
+        let mut graph2 = BagGraph::<&str, u32, Directed>::default();
+        let [z, y, x] = ["z", "y", "x"].map(|name| graph2.add_node(name));
+        graph2.add_edge(&y, &z, 2);
+        graph2.add_edge(&x, &z, 3);
+        graph2.add_edge(&x, &y, 1);
+
+        assert_eq!(graph1.structural_hash(), graph2.structural_hash());
+        assert_eq!(
+            graph1.structural_hash_by(|_| (), |weight| *weight),
+            graph2.structural_hash_by(|_| (), |weight| *weight)
+        );
+        assert_ne!(
+            graph1.structural_hash_by(|name| *name, |_| ()),
+            graph2.structural_hash_by(|name| *name, |_| ())
+        );
+
+        // Reversing an edge turns the triangle into a cycle.
+        let xz = graph2.edges_from_into(&x, &z).next().unwrap();
+        graph2.reverse_edge(&xz);
+        assert_ne!(graph1.structural_hash(), graph2.structural_hash());
+    }
+
+    #[test]
+    fn test_distinguishes_shapes() {
+        let path =
+            crate::generators::path_graph::<LinkedGraph<(), (), Undirected>>(4, |_| (), |_, _| ());
+        let star = {
+            let mut graph = LinkedGraph::<(), (), Undirected>::default();
+            let center = graph.add_node(());
+            for _ in 0..3 {
+                let leaf = graph.add_node(());
+                graph.add_edge(&center, &leaf, ());
+            }
+            graph
+        };
+        assert_ne!(path.structural_hash(), star.structural_hash());
+
+        let mut directed = LinkedGraph::<(), (), Directed>::default();
+        let a = directed.add_node(());
+        let b = directed.add_node(());
+        directed.add_edge(&a, &b, ());
+        let mut undirected = LinkedGraph::<(), (), Undirected>::default();
+        let a = undirected.add_node(());
+        let b = undirected.add_node(());
+        undirected.add_edge(&a, &b, ());
+        assert_ne!(directed.structural_hash(), undirected.structural_hash());
+
+        // Parallel edges and self-loops count.
+        undirected.add_edge(&a, &a, ());
+        let with_loop = undirected.structural_hash();
+        undirected.add_edge(&a, &b, ());
+        assert_ne!(undirected.structural_hash(), with_loop);
+    }
+
+    #[test]
+    fn test_stable_across_runs() {
+        let mut graph = BagGraph::<&str, (), Directed>::default();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.add_edge(&a, &b, ());
+        assert_eq!(graph.structural_hash(), STABLE_HASH);
+    }
+}
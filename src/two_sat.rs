@@ -0,0 +1,269 @@
+//! 2-satisfiability.
+//!
+//! A 2-SAT problem asks for an assignment of boolean variables which
+//! satisfies a conjunction of clauses, each the disjunction of two literals.
+//! The clause `a ∨ b` is equivalent to the implications `¬a → b` and
+//! `¬b → a`, so a [`TwoSat`] problem is solved by building the implication
+//! graph, with a node for each literal and an edge for each implication, and
+//! finding its strongly connected components.  The problem is unsatisfiable
+//! exactly when some variable is in the same component as its negation;
+//! otherwise each variable is set to true if its component comes after the
+//! component of its negation in topological order.  This takes linear time
+//! in the number of variables and clauses.
+//!
+//! ```
+//! use graphitude::two_sat::{Literal, TwoSat};
+//!
+//! // (x0 ∨ x1) ∧ (¬x0 ∨ x1) ∧ (¬x1 ∨ ¬x2)
+//! let mut problem = TwoSat::new(3);
+//! problem.add_clause(Literal::pos(0), Literal::pos(1));
+//! problem.add_clause(Literal::neg(0), Literal::pos(1));
+//! problem.add_clause(Literal::neg(1), Literal::neg(2));
+//! let assignment = problem.solve().unwrap();
+//! assert!(assignment[1] && !assignment[2]);
+//! ```
+
+use std::ops::Not;
+
+use crate::{BagGraph, prelude::*, scc};
+
+/// A variable or its negation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal {
+    /// The index of the variable.
+    pub var: usize,
+    /// True if the literal is the negation of the variable.
+    pub negated: bool,
+}
+
+impl Literal {
+    /// The literal which is true when variable `var` is true.
+    pub fn pos(var: usize) -> Self {
+        Self {
+            var,
+            negated: false,
+        }
+    }
+
+    /// The literal which is true when variable `var` is false.
+    pub fn neg(var: usize) -> Self {
+        Self { var, negated: true }
+    }
+
+    /// Returns the value of the literal under `assignment`.
+    pub fn eval(self, assignment: &[bool]) -> bool {
+        assignment[self.var] != self.negated
+    }
+
+    /// The index of the literal's node in the implication graph.
+    fn index(self) -> usize {
+        2 * self.var + usize::from(self.negated)
+    }
+}
+
+impl Not for Literal {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self {
+            var: self.var,
+            negated: !self.negated,
+        }
+    }
+}
+
+/// The error returned when a [`TwoSat`] problem has no solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("unsatisfiable: variable {var} implies its own negation and vice versa")]
+pub struct Unsatisfiable {
+    /// A variable which is in the same strongly connected component of the
+    /// implication graph as its negation.
+    pub var: usize,
+}
+
+/// A 2-SAT problem.  See the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct TwoSat {
+    num_vars: usize,
+    clauses: Vec<(Literal, Literal)>,
+}
+
+impl TwoSat {
+    /// Creates a problem with `num_vars` variables and no clauses.
+    pub fn new(num_vars: usize) -> Self {
+        Self {
+            num_vars,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// Returns the number of variables.
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Adds a variable, returning its index.
+    pub fn add_var(&mut self) -> usize {
+        self.num_vars += 1;
+        self.num_vars - 1
+    }
+
+    /// Returns the clauses, in the order they were added.
+    pub fn clauses(&self) -> &[(Literal, Literal)] {
+        &self.clauses
+    }
+
+    /// Adds the clause `a ∨ b`.  Panics if either literal refers to a
+    /// variable which does not exist.
+    pub fn add_clause(&mut self, a: Literal, b: Literal) {
+        assert!(
+            a.var < self.num_vars && b.var < self.num_vars,
+            "clause refers to a variable which does not exist"
+        );
+        self.clauses.push((a, b));
+    }
+
+    /// Requires `a` to be true.
+    pub fn add_unit(&mut self, a: Literal) {
+        self.add_clause(a, a);
+    }
+
+    /// Requires `b` to be true whenever `a` is.
+    pub fn add_implication(&mut self, a: Literal, b: Literal) {
+        self.add_clause(!a, b);
+    }
+
+    /// Requires exactly one of `a` and `b` to be true.
+    pub fn add_xor(&mut self, a: Literal, b: Literal) {
+        self.add_clause(a, b);
+        self.add_clause(!a, !b);
+    }
+
+    /// Requires `a` and `b` to have the same value.
+    pub fn add_equal(&mut self, a: Literal, b: Literal) {
+        self.add_implication(a, b);
+        self.add_implication(b, a);
+    }
+
+    /// Returns true if `assignment` satisfies every clause.
+    pub fn is_satisfied_by(&self, assignment: &[bool]) -> bool {
+        self.clauses
+            .iter()
+            .all(|&(a, b)| a.eval(assignment) || b.eval(assignment))
+    }
+
+    /// Constructs the implication graph, with a node for each literal of
+    /// each variable and an edge from `¬a` to `b` and from `¬b` to `a` for
+    /// each clause `a ∨ b`.  Returns the graph and the node of each literal,
+    /// indexed by `2 * var` for positive literals and `2 * var + 1` for
+    /// negated ones.
+    pub fn implication_graph<G>(&self) -> (G, Vec<G::NodeId>)
+    where
+        G: GraphMut<NodeData = Literal, EdgeData = (), Directedness = Directed> + Default,
+    {
+        let mut graph = G::default();
+        graph.reserve(2 * self.num_vars, 2 * self.clauses.len());
+        let nodes: Vec<_> = (0..self.num_vars)
+            .flat_map(|var| [Literal::pos(var), Literal::neg(var)])
+            .map(|literal| graph.add_node(literal))
+            .collect();
+        for &(a, b) in &self.clauses {
+            graph.add_edge(&nodes[(!a).index()], &nodes[b.index()], ());
+            if a != b {
+                graph.add_edge(&nodes[(!b).index()], &nodes[a.index()], ());
+            }
+        }
+        (graph, nodes)
+    }
+
+    /// Finds an assignment which satisfies every clause, indexed by
+    /// variable, or returns an error if there is none.
+    pub fn solve(&self) -> Result<Vec<bool>, Unsatisfiable> {
+        let (graph, _) = self.implication_graph::<BagGraph<Literal, (), Directed>>();
+        // Components are in reverse topological order, so a literal whose
+        // component has a lower index is implied by, rather than implies,
+        // the literals in components with higher indices.
+        let mut component = vec![0; 2 * self.num_vars];
+        for (i, nodes) in scc::strongly_connected_components(&graph)
+            .into_iter()
+            .enumerate()
+        {
+            for nid in nodes {
+                component[graph.node_data(&nid).index()] = i;
+            }
+        }
+        (0..self.num_vars)
+            .map(|var| {
+                let (pos, neg) = (
+                    component[Literal::pos(var).index()],
+                    component[Literal::neg(var).index()],
+                );
+                if pos == neg {
+                    Err(Unsatisfiable { var })
+                } else {
+                    Ok(pos < neg)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+    use crate::LinkedGraph;
+
+    use super::*;
+
+    #[test]
+    fn test_unsatisfiable() {
+        let mut problem = TwoSat::new(2);
+        problem.add_xor(Literal::pos(0), Literal::pos(1));
+        problem.add_equal(Literal::pos(0), Literal::pos(1));
+        assert!(problem.solve().is_err());
+
+        let mut problem = TwoSat::new(1);
+        problem.add_unit(Literal::pos(0));
+        assert_eq!(problem.solve(), Ok(vec![true]));
+        problem.add_unit(Literal::neg(0));
+        assert_eq!(problem.solve(), Err(Unsatisfiable { var: 0 }));
+    }
+
+    #[test]
+    fn test_implication_graph() {
+        let mut problem = TwoSat::new(2);
+        problem.add_implication(Literal::pos(0), Literal::neg(1));
+        let (graph, nodes) = problem.implication_graph::<LinkedGraph<_, _, Directed>>();
+        assert_eq!(graph.num_nodes(), 4);
+        assert_eq!(graph.num_edges(), 2);
+        assert!(graph.has_edge_from_into(&nodes[0], &nodes[3]));
+        assert!(graph.has_edge_from_into(&nodes[2], &nodes[1]));
+        assert_eq!(*graph.node_data(&nodes[3]), Literal::neg(1));
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        for _ in 0..200 {
+            let num_vars = rng.random_range(1..=6);
+            let mut problem = TwoSat::new(num_vars);
+            for _ in 0..rng.random_range(0..=12) {
+                let mut literal = || Literal {
+                    var: rng.random_range(0..num_vars),
+                    negated: rng.random(),
+                };
+                let (a, b) = (literal(), literal());
+                problem.add_clause(a, b);
+            }
+            let satisfiable = (0..1u32 << num_vars).any(|bits| {
+                let assignment: Vec<_> = (0..num_vars).map(|i| bits & (1 << i) != 0).collect();
+                problem.is_satisfied_by(&assignment)
+            });
+            match problem.solve() {
+                Ok(assignment) => assert!(problem.is_satisfied_by(&assignment)),
+                Err(_) => assert!(!satisfiable),
+            }
+        }
+    }
+}
@@ -0,0 +1,887 @@
+//! Undo and redo for graph mutations.
+//!
+//! [`UndoableGraph`] wraps a graph and records each change made through
+//! [`GraphMut`] along with the data needed to reverse it, so that
+//! [`UndoableGraph::undo`] and [`UndoableGraph::redo`] can step backwards and
+//! forwards through the history.  Changes made between
+//! [`UndoableGraph::begin_action`] and [`UndoableGraph::end_action`] are
+//! undone and redone together.
+//!
+//! Removing a node records the edges removed with it, and undoing the
+//! removal restores the node and then its edges, in the order in which the
+//! graph listed them.  A restored node or edge may not get back its old ID,
+//! since the wrapped graph assigns IDs when elements are added, but the
+//! history follows the new IDs, so later undos and redos still apply to the
+//! right elements.  IDs of elements which were never removed do not change.
+//!
+//! A [`Savepoint`] marks a point in the history which the graph can be
+//! returned to later with [`UndoableGraph::rollback_to`], undoing or redoing
+//! as many steps as needed.  This gives transactional editing without
+//! copying the graph: take a savepoint, make changes, and roll back if they
+//! should be abandoned.
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash, mem};
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    map_collector::MapCollector,
+    prelude::*,
+    stable_ids::{StableEdgeIds, StableNodeIds},
+};
+
+/// Assigns each node or edge mentioned in the history a slot which stays the
+/// same while it is removed and restored.
+struct Slots<I> {
+    /// The current ID of the element in each slot, or `None` if it has been
+    /// removed.
+    ids: Vec<Option<I>>,
+    /// The slot of each element which has not been removed.
+    index: FastHashMap<I, usize>,
+}
+
+impl<I> Slots<I>
+where
+    I: Clone + Eq + Hash,
+{
+    fn new() -> Self {
+        Self {
+            ids: Vec::new(),
+            index: FastHashMap::default(),
+        }
+    }
+
+    /// Returns the slot of `id`, assigning a new one if necessary.
+    fn slot(&mut self, id: &I) -> usize {
+        if let Some(&slot) = self.index.get(id) {
+            return slot;
+        }
+        let slot = self.ids.len();
+        self.ids.push(Some(id.clone()));
+        self.index.insert(id.clone(), slot);
+        slot
+    }
+
+    /// Returns the current ID of the element in `slot`.
+    fn id(&self, slot: usize) -> &I {
+        self.ids[slot]
+            .as_ref()
+            .expect("history refers to a removed element")
+    }
+
+    /// Marks the element in `slot` as removed, since the graph may give its
+    /// ID to another element.
+    fn remove(&mut self, slot: usize) {
+        if let Some(id) = self.ids[slot].take() {
+            self.index.remove(&id);
+        }
+    }
+
+    /// Records that the element in `slot` has been restored with the ID
+    /// `id`.
+    fn restore(&mut self, slot: usize, id: I) {
+        self.index.insert(id.clone(), slot);
+        self.ids[slot] = Some(id);
+    }
+
+    /// Updates the IDs of elements after compaction.
+    fn remap(&mut self, map: &HashMap<I, I>) {
+        self.index.clear();
+        for (slot, id) in self.ids.iter_mut().enumerate() {
+            if let Some(id) = id {
+                if let Some(new_id) = map.get(id) {
+                    *id = new_id.clone();
+                }
+                self.index.insert(id.clone(), slot);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.ids.clear();
+        self.index.clear();
+    }
+}
+
+/// An edge removed from the graph, with its ends given as node slots.
+struct RemovedEdge<E> {
+    edge: usize,
+    from: usize,
+    into: usize,
+    data: E,
+}
+
+/// A change to the graph.  Applying a change reverses it, and returns the
+/// change which reverses that in turn.
+enum Change<N, E> {
+    /// A node was added.
+    AddNode(usize),
+    /// A node was removed along with its edges.
+    RemoveNode {
+        node: usize,
+        data: N,
+        edges: Vec<RemovedEdge<E>>,
+    },
+    /// An edge was added.
+    AddEdge(usize),
+    /// An edge was removed.
+    RemoveEdge(RemovedEdge<E>),
+    /// The data of a node was changed from the given value.
+    NodeData(usize, N),
+    /// The data of an edge was changed from the given value.
+    EdgeData(usize, E),
+}
+
+/// A group of changes which are undone and redone together.
+struct Step<N, E> {
+    /// Identifies the step for [`Savepoint`]s.
+    id: u64,
+    changes: Vec<Change<N, E>>,
+}
+
+/// A point in the history of an [`UndoableGraph`].  See
+/// [`UndoableGraph::savepoint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Savepoint {
+    /// The generation of the history when the savepoint was taken.
+    generation: u64,
+    /// The ID of the last step which had been done, if any.
+    step: Option<u64>,
+}
+
+/// A wrapper around a graph which records changes so that they can be
+/// undone and redone.  See the [module documentation](self).
+///
+/// Since [`GraphMut::node_data_mut`] and [`GraphMut::edge_data_mut`] cannot
+/// tell what the caller changes, each call records a copy of the old data,
+/// so node and edge data must implement [`Clone`].
+pub struct UndoableGraph<G>
+where
+    G: Graph,
+{
+    inner: G,
+    nodes: Slots<G::NodeId>,
+    edges: Slots<G::EdgeId>,
+    undo_stack: Vec<Step<G::NodeData, G::EdgeData>>,
+    redo_stack: Vec<Step<G::NodeData, G::EdgeData>>,
+    /// The changes made since the outermost open action began.
+    action: Vec<Change<G::NodeData, G::EdgeData>>,
+    /// The number of actions which have begun but not ended.
+    action_depth: usize,
+    /// The ID of the next step.
+    next_step: u64,
+    /// Incremented when the history is cleared, which invalidates all
+    /// savepoints.
+    generation: u64,
+}
+
+impl<G> UndoableGraph<G>
+where
+    G: GraphMut,
+{
+    /// Wraps an existing graph, with an empty history.
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            nodes: Slots::new(),
+            edges: Slots::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            action: Vec::new(),
+            action_depth: 0,
+            next_step: 0,
+            generation: 0,
+        }
+    }
+
+    /// Gets a reference to the wrapped graph.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Unwraps the graph, discarding the history.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    /// Begins an action.  Changes made until the matching call to
+    /// [`Self::end_action`] are undone and redone as a single step.  Actions
+    /// may be nested, in which case the outermost action forms the step.
+    pub fn begin_action(&mut self) {
+        self.action_depth += 1;
+    }
+
+    /// Ends the action begun by the last unmatched call to
+    /// [`Self::begin_action`].  An action with no changes does not add a step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no action has begun.
+    pub fn end_action(&mut self) {
+        assert!(self.action_depth > 0, "end_action called outside an action");
+        self.action_depth -= 1;
+        if self.action_depth == 0 && !self.action.is_empty() {
+            let changes = mem::take(&mut self.action);
+            self.push_step(changes);
+        }
+    }
+
+    /// Returns true if an action has begun but not ended.
+    pub fn in_action(&self) -> bool {
+        self.action_depth > 0
+    }
+
+    /// Returns true if there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Returns true if there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Returns the number of steps which can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Returns the number of steps which can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    /// Undoes the last step which has not been undone, returning false if
+    /// there is none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an action is in progress.
+    pub fn undo(&mut self) -> bool {
+        assert!(!self.in_action(), "cannot undo during an action");
+        let Some(step) = self.undo_stack.pop() else {
+            return false;
+        };
+        let step = self.apply_step(step);
+        self.redo_stack.push(step);
+        true
+    }
+
+    /// Redoes the last step which was undone, returning false if there is
+    /// none.  Making any other change discards the steps which can be
+    /// redone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an action is in progress.
+    pub fn redo(&mut self) -> bool {
+        assert!(!self.in_action(), "cannot redo during an action");
+        let Some(step) = self.redo_stack.pop() else {
+            return false;
+        };
+        let step = self.apply_step(step);
+        self.undo_stack.push(step);
+        true
+    }
+
+    /// Discards the history, keeping the graph as it is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an action is in progress.
+    pub fn clear_history(&mut self) {
+        assert!(!self.in_action(), "cannot clear history during an action");
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.nodes.clear();
+        self.edges.clear();
+        self.generation += 1;
+    }
+
+    /// Returns a savepoint for the current point in the history.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an action is in progress.
+    pub fn savepoint(&self) -> Savepoint {
+        assert!(
+            !self.in_action(),
+            "cannot take a savepoint during an action"
+        );
+        Savepoint {
+            generation: self.generation,
+            step: self.undo_stack.last().map(|step| step.id),
+        }
+    }
+
+    /// Returns true if the graph is at the point in the history marked by
+    /// `savepoint`, i.e. no steps have been done or undone since it was
+    /// taken, or they have all been reversed.
+    pub fn is_at(&self, savepoint: Savepoint) -> bool {
+        !self.in_action()
+            && savepoint.generation == self.generation
+            && savepoint.step == self.undo_stack.last().map(|step| step.id)
+    }
+
+    /// Undoes or redoes steps until the graph is at the point in the history
+    /// marked by `savepoint`.  Returns false, without changing the graph, if
+    /// that point can no longer be reached, because the steps after it were
+    /// undone and then discarded by another change, or because the history
+    /// was cleared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an action is in progress.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) -> bool {
+        assert!(!self.in_action(), "cannot roll back during an action");
+        if savepoint.generation != self.generation {
+            return false;
+        }
+        let Some(target) = savepoint.step else {
+            while self.undo() {}
+            return true;
+        };
+        if self.undo_stack.iter().any(|step| step.id == target) {
+            while self.undo_stack.last().is_some_and(|step| step.id != target) {
+                self.undo();
+            }
+            true
+        } else if self.redo_stack.iter().any(|step| step.id == target) {
+            while self.undo_stack.last().is_none_or(|step| step.id != target) {
+                self.redo();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds a step to the undo stack.
+    fn push_step(&mut self, changes: Vec<Change<G::NodeData, G::EdgeData>>) {
+        let id = self.next_step;
+        self.next_step += 1;
+        self.undo_stack.push(Step { id, changes });
+    }
+
+    /// Adds a change to the history.
+    fn record(&mut self, change: Change<G::NodeData, G::EdgeData>) {
+        self.redo_stack.clear();
+        if self.in_action() {
+            self.action.push(change);
+        } else {
+            self.push_step(vec![change]);
+        }
+    }
+
+    /// Applies the changes of a step in reverse order, returning the step
+    /// which reverses it.
+    fn apply_step(
+        &mut self,
+        step: Step<G::NodeData, G::EdgeData>,
+    ) -> Step<G::NodeData, G::EdgeData> {
+        let changes = step
+            .changes
+            .into_iter()
+            .rev()
+            .map(|change| self.apply(change))
+            .collect();
+        Step {
+            id: step.id,
+            changes,
+        }
+    }
+
+    /// Reverses a change, returning the change which reverses that.
+    fn apply(
+        &mut self,
+        change: Change<G::NodeData, G::EdgeData>,
+    ) -> Change<G::NodeData, G::EdgeData> {
+        match change {
+            Change::AddNode(node) => {
+                let nid = self.nodes.id(node).clone();
+                let (data, edges) = self.remove_node_and_edges(&nid);
+                Change::RemoveNode { node, data, edges }
+            }
+            Change::RemoveNode { node, data, edges } => {
+                let nid = self.inner.add_node(data);
+                self.nodes.restore(node, nid);
+                for edge in edges {
+                    self.restore_edge(edge);
+                }
+                Change::AddNode(node)
+            }
+            Change::AddEdge(edge) => {
+                let eid = self.edges.id(edge).clone();
+                Change::RemoveEdge(self.remove_edge_only(&eid))
+            }
+            Change::RemoveEdge(edge) => Change::AddEdge(self.restore_edge(edge)),
+            Change::NodeData(node, data) => {
+                let nid = self.nodes.id(node);
+                let data = mem::replace(self.inner.node_data_mut(nid), data);
+                Change::NodeData(node, data)
+            }
+            Change::EdgeData(edge, data) => {
+                let eid = self.edges.id(edge);
+                let data = mem::replace(self.inner.edge_data_mut(eid), data);
+                Change::EdgeData(edge, data)
+            }
+        }
+    }
+
+    /// Removes a node from the wrapped graph, returning its data and the
+    /// edges removed with it.
+    fn remove_node_and_edges(
+        &mut self,
+        id: &G::NodeId,
+    ) -> (G::NodeData, Vec<RemovedEdge<G::EdgeData>>) {
+        let mut seen = FastHashSet::default();
+        let incident: Vec<_> = self
+            .inner
+            .edges_from(id)
+            .chain(self.inner.edges_into(id))
+            .filter(|eid| seen.insert(eid.clone()))
+            .collect();
+        let edges = incident
+            .iter()
+            .map(|eid| self.remove_edge_only(eid))
+            .collect();
+        let node = self.nodes.slot(id);
+        self.nodes.remove(node);
+        (self.inner.remove_node(id), edges)
+    }
+
+    /// Removes an edge from the wrapped graph.
+    fn remove_edge_only(&mut self, id: &G::EdgeId) -> RemovedEdge<G::EdgeData> {
+        let (from, into) = self.inner.edge_ends(id).into_values();
+        let (from, into) = (self.nodes.slot(&from), self.nodes.slot(&into));
+        let edge = self.edges.slot(id);
+        self.edges.remove(edge);
+        let data = self.inner.remove_edge(id);
+        RemovedEdge {
+            edge,
+            from,
+            into,
+            data,
+        }
+    }
+
+    /// Adds a removed edge back to the wrapped graph, returning its slot.
+    fn restore_edge(&mut self, edge: RemovedEdge<G::EdgeData>) -> usize {
+        let (from, into) = (self.nodes.id(edge.from), self.nodes.id(edge.into));
+        let result = self.inner.add_edge(from, into, edge.data);
+        debug_assert!(result.is_added(), "restored edge replaced another");
+        self.edges.restore(edge.edge, result.into_edge_id());
+        edge.edge
+    }
+}
+
+impl<G> Default for UndoableGraph<G>
+where
+    G: GraphMut + Default,
+{
+    fn default() -> Self {
+        Self::new(G::default())
+    }
+}
+
+impl<G> Debug for UndoableGraph<G>
+where
+    G: Graph + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UndoableGraph")
+            .field("inner", &self.inner)
+            .field("undo_len", &self.undo_stack.len())
+            .field("redo_len", &self.redo_stack.len())
+            .finish()
+    }
+}
+
+impl<G> Graph for UndoableGraph<G>
+where
+    G: Graph,
+{
+    type Directedness = G::Directedness;
+    type EdgeMultiplicity = G::EdgeMultiplicity;
+    type NodeData = G::NodeData;
+    type EdgeData = G::EdgeData;
+    type NodeId = G::NodeId;
+    type EdgeId = G::EdgeId;
+
+    fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+        self.inner.node_ids()
+    }
+
+    fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+        self.inner.node_data(id)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.inner.num_nodes()
+    }
+
+    fn check_node_id(&self, id: &Self::NodeId) -> Result<(), GraphError> {
+        self.inner.check_node_id(id)
+    }
+
+    fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+        self.inner.edge_data(id)
+    }
+
+    fn check_edge_id(&self, id: &Self::EdgeId) -> Result<(), GraphError> {
+        self.inner.check_edge_id(id)
+    }
+
+    fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+        self.inner.edge_ids()
+    }
+
+    fn edge_ends(
+        &self,
+        id: &Self::EdgeId,
+    ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+        self.inner.edge_ends(id)
+    }
+
+    fn edges_from<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from(from)
+    }
+
+    fn edges_into<'a, 'b: 'a>(
+        &'a self,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_into(into)
+    }
+
+    fn edges_from_into<'a, 'b: 'a>(
+        &'a self,
+        from: &'b Self::NodeId,
+        into: &'b Self::NodeId,
+    ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+        self.inner.edges_from_into(from, into)
+    }
+
+    fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+        self.inner.has_edge_from_into(from, into)
+    }
+
+    fn num_edges(&self) -> usize {
+        self.inner.num_edges()
+    }
+
+    fn num_edges_into(&self, into: &Self::NodeId) -> usize {
+        self.inner.num_edges_into(into)
+    }
+
+    fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+        self.inner.num_edges_from(from)
+    }
+
+    fn is_very_slow(&self) -> bool {
+        self.inner.is_very_slow()
+    }
+}
+
+impl<G> GraphMut for UndoableGraph<G>
+where
+    G: GraphMut,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+{
+    fn node_data_mut(&mut self, id: &Self::NodeId) -> &mut Self::NodeData {
+        let node = self.nodes.slot(id);
+        let data = self.inner.node_data(id).clone();
+        self.record(Change::NodeData(node, data));
+        self.inner.node_data_mut(id)
+    }
+
+    fn edge_data_mut(&mut self, id: &Self::EdgeId) -> &mut Self::EdgeData {
+        let edge = self.edges.slot(id);
+        let data = self.inner.edge_data(id).clone();
+        self.record(Change::EdgeData(edge, data));
+        self.inner.edge_data_mut(id)
+    }
+
+    fn add_node(&mut self, data: Self::NodeData) -> Self::NodeId {
+        let nid = self.inner.add_node(data);
+        let node = self.nodes.slot(&nid);
+        self.record(Change::AddNode(node));
+        nid
+    }
+
+    fn remove_node(&mut self, id: &Self::NodeId) -> Self::NodeData {
+        let node = self.nodes.slot(id);
+        let (data, edges) = self.remove_node_and_edges(id);
+        self.record(Change::RemoveNode {
+            node,
+            data: data.clone(),
+            edges,
+        });
+        data
+    }
+
+    fn add_edge(
+        &mut self,
+        from: &Self::NodeId,
+        into: &Self::NodeId,
+        data: Self::EdgeData,
+    ) -> AddEdgeResult<Self::EdgeId, Self::EdgeData> {
+        // Remove any edge which would be replaced first, so that it is
+        // recorded with its ends, and so that its ID is forgotten before the
+        // new edge can reuse it.
+        self.begin_action();
+        let replaced = if self.inner.allows_parallel_edges() {
+            None
+        } else {
+            self.inner.edges_from_into(from, into).next()
+        };
+        let replaced = replaced.map(|eid| {
+            let removed = self.remove_edge_only(&eid);
+            let data = removed.data.clone();
+            self.record(Change::RemoveEdge(removed));
+            (eid, data)
+        });
+        let eid = self.inner.add_edge(from, into, data).into_edge_id();
+        let edge = self.edges.slot(&eid);
+        self.record(Change::AddEdge(edge));
+        self.end_action();
+        (eid, replaced).into()
+    }
+
+    fn remove_edge(&mut self, id: &Self::EdgeId) -> Self::EdgeData {
+        let removed = self.remove_edge_only(id);
+        let data = removed.data.clone();
+        self.record(Change::RemoveEdge(removed));
+        data
+    }
+
+    fn reserve(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve(additional_nodes, additional_edges);
+    }
+
+    fn reserve_exact(&mut self, additional_nodes: usize, additional_edges: usize) {
+        self.inner.reserve_exact(additional_nodes, additional_edges);
+    }
+
+    fn compact(
+        &mut self,
+        node_map_collector: Option<&mut dyn MapCollector<Self::NodeId>>,
+        edge_map_collector: Option<&mut dyn MapCollector<Self::EdgeId>>,
+    ) {
+        let mut node_map = HashMap::new();
+        let mut edge_map = HashMap::new();
+        self.inner.compact(Some(&mut node_map), Some(&mut edge_map));
+        self.nodes.remap(&node_map);
+        self.edges.remap(&edge_map);
+        if let Some(collector) = node_map_collector {
+            for (old, new) in node_map {
+                collector.insert(old, new);
+            }
+        }
+        if let Some(collector) = edge_map_collector {
+            for (old, new) in edge_map {
+                collector.insert(old, new);
+            }
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+}
+
+impl<G> StableNodeIds for UndoableGraph<G>
+where
+    G: StableNodeIds + GraphMut,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+{
+}
+
+impl<G> StableEdgeIds for UndoableGraph<G>
+where
+    G: StableEdgeIds + GraphMut,
+    G::NodeData: Clone,
+    G::EdgeData: Clone,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{BagGraph, LinkedGraph};
+
+    use super::*;
+
+    type Contents<N, E> = (BTreeSet<N>, BTreeSet<(N, N, E)>);
+
+    /// Describes a graph by the data of its nodes and of its edges and their
+    /// ends, which do not depend on IDs.
+    fn contents<G>(graph: &G) -> Contents<G::NodeData, G::EdgeData>
+    where
+        G: Graph,
+        G::NodeData: Ord + Clone,
+        G::EdgeData: Ord + Clone,
+    {
+        let nodes = graph
+            .node_ids()
+            .map(|nid| graph.node_data(&nid).clone())
+            .collect();
+        let edges = graph
+            .edge_ids()
+            .map(|eid| {
+                let (from, into) = graph.edge_ends(&eid).into_values();
+                (
+                    graph.node_data(&from).clone(),
+                    graph.node_data(&into).clone(),
+                    graph.edge_data(&eid).clone(),
+                )
+            })
+            .collect();
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_undo_redo_remove_node() {
+        let mut graph = UndoableGraph::new(LinkedGraph::<&str, i32, Directed>::default());
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_new_edge(&a, &b, 1);
+        graph.add_new_edge(&b, &c, 2);
+        graph.add_new_edge(&b, &b, 3);
+        graph.add_new_edge(&c, &b, 4);
+        let before = contents(&graph);
+        assert_eq!(graph.undo_len(), 7);
+
+        assert_eq!(graph.remove_node(&b), "b");
+        assert_eq!(graph.num_edges(), 0);
+        let after = contents(&graph);
+
+        assert!(graph.undo());
+        assert_eq!(contents(&graph), before);
+        assert!(graph.redo());
+        assert_eq!(contents(&graph), after);
+        assert!(!graph.redo());
+
+        // Undoing everything leaves an empty graph, and redoing everything
+        // restores it, even though the restored IDs may differ.
+        while graph.undo() {}
+        assert_eq!(graph.num_nodes(), 0);
+        assert!(!graph.can_undo());
+        while graph.redo() {}
+        assert_eq!(contents(&graph), after);
+        assert_eq!(graph.undo_len(), 8);
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut graph = UndoableGraph::new(BagGraph::<&str, (), Undirected>::default());
+        let a = graph.add_node("a");
+        graph.begin_action();
+        let b = graph.add_node("b");
+        graph.begin_action();
+        graph.add_new_edge(&a, &b, ());
+        graph.end_action();
+        assert!(graph.in_action());
+        graph.end_action();
+        assert_eq!(graph.undo_len(), 2);
+
+        // An empty action adds no step.
+        graph.begin_action();
+        graph.end_action();
+        assert_eq!(graph.undo_len(), 2);
+
+        assert!(graph.undo());
+        assert_eq!(graph.num_nodes(), 1);
+        assert_eq!(graph.num_edges(), 0);
+        assert!(graph.redo());
+        assert_eq!(graph.num_nodes(), 2);
+        assert_eq!(graph.num_edges(), 1);
+
+        // A new change discards the steps which could be redone.
+        assert!(graph.undo());
+        graph.add_node("c");
+        assert!(!graph.can_redo());
+    }
+
+    #[test]
+    fn test_data_changes_and_replaced_edges() {
+        let mut graph =
+            UndoableGraph::new(LinkedGraph::<&str, i32, Directed, SingleEdge>::default());
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.add_edge(&a, &b, 1);
+        let result = graph.add_edge(&a, &b, 2);
+        let eid = result.edge_id().clone();
+        assert_eq!(result.updated().map(|(_, data)| data), Some(1));
+        *graph.node_data_mut(&a) = "A";
+        *graph.edge_data_mut(&eid) = 3;
+        let after = contents(&graph);
+
+        assert_eq!(graph.undo_len(), 6);
+        graph.undo();
+        assert_eq!(*graph.edge_data(&eid), 2);
+        graph.undo();
+        assert_eq!(*graph.node_data(&a), "a");
+        graph.undo();
+        let eid = graph.edge_ids().next().unwrap();
+        assert_eq!(*graph.edge_data(&eid), 1);
+        graph.undo();
+        assert_eq!(graph.num_edges(), 0);
+
+        while graph.redo() {}
+        assert_eq!(contents(&graph), after);
+    }
+
+    #[test]
+    fn test_savepoints() {
+        let mut graph = UndoableGraph::new(BagGraph::<&str, i32, Directed>::default());
+        let start = graph.savepoint();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let middle = graph.savepoint();
+        let middle_contents = contents(&graph);
+        graph.begin_action();
+        graph.add_new_edge(&a, &b, 1);
+        graph.add_node("c");
+        graph.end_action();
+        let end = graph.savepoint();
+        let end_contents = contents(&graph);
+        assert!(graph.is_at(end));
+        assert!(!graph.is_at(middle));
+
+        assert!(graph.rollback_to(middle));
+        assert!(graph.is_at(middle));
+        assert_eq!(contents(&graph), middle_contents);
+        assert!(graph.rollback_to(start));
+        assert_eq!(graph.num_nodes(), 0);
+        assert!(graph.rollback_to(end));
+        assert_eq!(contents(&graph), end_contents);
+
+        // Changing the graph after rolling back discards the steps after the
+        // savepoint, so `end` can no longer be reached.
+        assert!(graph.rollback_to(middle));
+        graph.add_node("d");
+        assert!(!graph.rollback_to(end));
+        assert!(graph.rollback_to(middle));
+        assert_eq!(contents(&graph), middle_contents);
+
+        graph.clear_history();
+        assert!(!graph.rollback_to(start));
+        assert!(!graph.is_at(middle));
+        assert!(graph.is_at(graph.savepoint()));
+    }
+
+    #[test]
+    #[should_panic(expected = "end_action called outside an action")]
+    fn test_unmatched_end_action() {
+        let mut graph = UndoableGraph::new(BagGraph::<(), (), Directed>::default());
+        graph.end_action();
+    }
+}
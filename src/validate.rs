@@ -0,0 +1,468 @@
+//! Checking that a graph is internally consistent.
+//!
+//! [`Graph::validate`] cross-checks the answers a graph gives through the
+//! [`Graph`] trait: that every listed ID is valid and listed once, that every
+//! edge connects nodes of the graph, is listed at both of its ends and is
+//! found by [`Graph::has_edge_from_into`], that a graph without parallel
+//! edges has none, and that the counting methods agree with the iterators.  Instead of stopping at the first problem, it
+//! returns every [`Violation`] found, so it can be used to report on graphs
+//! from untrusted sources, such as files, as well as in tests.
+//!
+//! Only the methods of [`Graph`] are used, so a graph whose internal state is
+//! corrupt enough for those methods to panic will still panic.  Validation
+//! takes time proportional to the sum of the degrees of the nodes, plus the
+//! number of edges times the number of edges between each pair of nodes.
+
+use crate::{
+    Direction,
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+};
+
+/// An inconsistency found by [`Graph::validate`].
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Violation<N, E> {
+    /// A node ID is returned more than once by [`Graph::node_ids`].
+    #[error("node {0:?} is listed more than once")]
+    DuplicateNodeId(N),
+    /// An edge ID is returned more than once by [`Graph::edge_ids`], or by
+    /// [`Graph::edges_from_into`] for its ends.
+    #[error("edge {0:?} is listed more than once")]
+    DuplicateEdgeId(E),
+    /// A node ID returned by [`Graph::node_ids`] is rejected by
+    /// [`Graph::check_node_id`].
+    #[error("node {0:?} is listed but not valid")]
+    InvalidNodeId(N),
+    /// An edge ID returned by [`Graph::edge_ids`] is rejected by
+    /// [`Graph::check_edge_id`].
+    #[error("edge {0:?} is listed but not valid")]
+    InvalidEdgeId(E),
+    /// An end of an edge is not a node of the graph.
+    #[error("edge {edge:?} has an end {node:?} which is not a node of the graph")]
+    DanglingEdge {
+        /// The edge.
+        edge: E,
+        /// The end which is not a node of the graph.
+        node: N,
+    },
+    /// An edge is missing from the edges of one of its ends: from
+    /// [`Graph::edges_from`] of its source for [`Direction::Out`], from
+    /// [`Graph::edges_into`] of its target for [`Direction::In`], or from
+    /// [`Graph::edges_from_into`] of its ends for [`Direction::Both`].
+    #[error("edge {edge:?} is not listed at its end {node:?} ({direction:?})")]
+    UnlistedEdge {
+        /// The edge.
+        edge: E,
+        /// The end at which the edge is missing.
+        node: N,
+        /// Which edges of the node were searched.
+        direction: Direction,
+    },
+    /// [`Graph::edges_from`] or [`Graph::edges_into`] of a node returns an
+    /// edge which is not an edge of the graph, or which does not have the
+    /// node at the corresponding end.
+    #[error("edge {edge:?} is listed at {node:?} ({direction:?}) but does not end there")]
+    StrayEdge {
+        /// The edge.
+        edge: E,
+        /// The node whose edges include it.
+        node: N,
+        /// Which edges of the node include it.
+        direction: Direction,
+    },
+    /// A graph which does not allow parallel edges has more than one edge
+    /// between two nodes.
+    #[error("{count} parallel edges from {from:?} into {into:?}")]
+    ParallelEdges {
+        /// The source of the edges.
+        from: N,
+        /// The target of the edges.
+        into: N,
+        /// The number of edges.
+        count: usize,
+    },
+    /// [`Graph::has_edge_from_into`] returns false for the ends of an edge,
+    /// or, in an undirected graph, for its ends in reverse order.
+    #[error("edge {edge:?} is not found by has_edge_from_into({from:?}, {into:?})")]
+    UndetectedEdge {
+        /// The edge.
+        edge: E,
+        /// The first node passed to `has_edge_from_into`.
+        from: N,
+        /// The second node passed to `has_edge_from_into`.
+        into: N,
+    },
+    /// [`Graph::num_edges_from_into`] of two nodes disagrees with the number
+    /// of edges listed by [`Graph::edges_from_into`].
+    #[error("{reported} edges reported from {from:?} into {into:?} but {actual} listed")]
+    EdgeCountBetweenMismatch {
+        /// The source of the edges.
+        from: N,
+        /// The target of the edges.
+        into: N,
+        /// The count reported.
+        reported: usize,
+        /// The number of edges listed.
+        actual: usize,
+    },
+    /// [`Graph::num_edges_from`] or [`Graph::num_edges_into`] of a node, or
+    /// [`Graph::has_edge_from`] or [`Graph::has_edge_into`] as a count of 0
+    /// or 1, disagrees with the number of edges listed.
+    #[error("node {node:?} reports {reported} edges ({direction:?}) but lists {actual}")]
+    DegreeMismatch {
+        /// The node.
+        node: N,
+        /// Which edges of the node were counted.
+        direction: Direction,
+        /// The count reported.
+        reported: usize,
+        /// The number of edges listed, or for `has_edge_*`, whether any are.
+        actual: usize,
+    },
+    /// [`Graph::num_nodes`] disagrees with the number of nodes listed.
+    #[error("graph reports {reported} nodes but lists {actual}")]
+    NodeCountMismatch {
+        /// The count reported.
+        reported: usize,
+        /// The number of nodes listed.
+        actual: usize,
+    },
+    /// [`Graph::num_edges`] disagrees with the number of edges listed.
+    #[error("graph reports {reported} edges but lists {actual}")]
+    EdgeCountMismatch {
+        /// The count reported.
+        reported: usize,
+        /// The number of edges listed.
+        actual: usize,
+    },
+}
+
+/// Checks that `graph` is internally consistent.  See [`Graph::validate`].
+pub fn validate<G>(graph: &G) -> Vec<Violation<G::NodeId, G::EdgeId>>
+where
+    G: Graph + ?Sized,
+{
+    let mut violations = Vec::new();
+
+    let mut nodes = FastHashSet::default();
+    let mut num_listed_nodes = 0;
+    for nid in graph.node_ids() {
+        num_listed_nodes += 1;
+        if graph.check_node_id(&nid).is_err() {
+            violations.push(Violation::InvalidNodeId(nid.clone()));
+        }
+        if !nodes.insert(nid.clone()) {
+            violations.push(Violation::DuplicateNodeId(nid));
+        }
+    }
+    if num_listed_nodes != graph.num_nodes() {
+        violations.push(Violation::NodeCountMismatch {
+            reported: graph.num_nodes(),
+            actual: num_listed_nodes,
+        });
+    }
+
+    let mut edges = FastHashMap::default();
+    let mut num_listed_edges = 0;
+    for eid in graph.edge_ids() {
+        num_listed_edges += 1;
+        if graph.check_edge_id(&eid).is_err() {
+            violations.push(Violation::InvalidEdgeId(eid.clone()));
+            continue;
+        }
+        let (from, into) = graph.edge_ends(&eid).into_values();
+        if edges
+            .insert(eid.clone(), (from.clone(), into.clone()))
+            .is_some()
+        {
+            violations.push(Violation::DuplicateEdgeId(eid));
+            continue;
+        }
+        let mut dangling = false;
+        for end in [&from, &into] {
+            if !nodes.contains(end) {
+                violations.push(Violation::DanglingEdge {
+                    edge: eid.clone(),
+                    node: end.clone(),
+                });
+                dangling = true;
+            }
+        }
+        if dangling {
+            continue;
+        }
+        for (node, direction, found) in [
+            (
+                &from,
+                Direction::Out,
+                graph.edges_from(&from).any(|e| e == eid),
+            ),
+            (
+                &into,
+                Direction::In,
+                graph.edges_into(&into).any(|e| e == eid),
+            ),
+        ] {
+            if !found {
+                violations.push(Violation::UnlistedEdge {
+                    edge: eid.clone(),
+                    node: node.clone(),
+                    direction,
+                });
+            }
+        }
+        let between: Vec<_> = graph.edges_from_into(&from, &into).collect();
+        if !between.contains(&eid) {
+            violations.push(Violation::UnlistedEdge {
+                edge: eid.clone(),
+                node: from.clone(),
+                direction: Direction::Both,
+            });
+        }
+        if between.iter().filter(|&e| *e == eid).count() > 1 {
+            violations.push(Violation::DuplicateEdgeId(eid.clone()));
+        }
+        let reversed = (!G::Directedness::IS_DIRECTED).then_some((&into, &from));
+        for (n1, n2) in std::iter::once((&from, &into)).chain(reversed) {
+            if !graph.has_edge_from_into(n1, n2) {
+                violations.push(Violation::UndetectedEdge {
+                    edge: eid.clone(),
+                    from: n1.clone(),
+                    into: n2.clone(),
+                });
+            }
+        }
+        // Report each pair of nodes once, from its first edge.
+        let first_between = between.iter().min() == Some(&eid);
+        let num_between = graph.num_edges_from_into(&from, &into);
+        if first_between && num_between != between.len() {
+            violations.push(Violation::EdgeCountBetweenMismatch {
+                from: from.clone(),
+                into: into.clone(),
+                reported: num_between,
+                actual: between.len(),
+            });
+        }
+        if !graph.allows_parallel_edges() && between.len() > 1 && first_between {
+            violations.push(Violation::ParallelEdges {
+                from,
+                into,
+                count: between.len(),
+            });
+        }
+    }
+    if num_listed_edges != graph.num_edges() {
+        violations.push(Violation::EdgeCountMismatch {
+            reported: graph.num_edges(),
+            actual: num_listed_edges,
+        });
+    }
+
+    for nid in &nodes {
+        for direction in [Direction::Out, Direction::In] {
+            let (listed, reported, has_edge): (Vec<_>, _, _) = match direction {
+                Direction::Out => (
+                    graph.edges_from(nid).collect(),
+                    graph.num_edges_from(nid),
+                    graph.has_edge_from(nid),
+                ),
+                _ => (
+                    graph.edges_into(nid).collect(),
+                    graph.num_edges_into(nid),
+                    graph.has_edge_into(nid),
+                ),
+            };
+            for eid in &listed {
+                let ends_here = edges.get(eid).is_some_and(|(from, into)| {
+                    match (G::Directedness::IS_DIRECTED, direction) {
+                        (true, Direction::Out) => from == nid,
+                        (true, _) => into == nid,
+                        (false, _) => from == nid || into == nid,
+                    }
+                });
+                if !ends_here {
+                    violations.push(Violation::StrayEdge {
+                        edge: eid.clone(),
+                        node: nid.clone(),
+                        direction,
+                    });
+                }
+            }
+            if reported != listed.len() {
+                violations.push(Violation::DegreeMismatch {
+                    node: nid.clone(),
+                    direction,
+                    reported,
+                    actual: listed.len(),
+                });
+            }
+            let any_listed = !listed.is_empty();
+            if has_edge != any_listed {
+                violations.push(Violation::DegreeMismatch {
+                    node: nid.clone(),
+                    direction,
+                    reported: usize::from(has_edge),
+                    actual: usize::from(any_listed),
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LinkedGraph;
+
+    use super::*;
+
+    /// A graph which misreports some of the answers of a correct graph.
+    struct Faulty {
+        inner: LinkedGraph<(), (), Directed>,
+        extra_edges: usize,
+        hide_from: bool,
+        hide_between: bool,
+    }
+
+    impl Graph for Faulty {
+        type Directedness = Directed;
+        type EdgeMultiplicity = SingleEdge;
+        type NodeData = ();
+        type EdgeData = ();
+        type NodeId = <LinkedGraph<(), (), Directed> as Graph>::NodeId;
+        type EdgeId = <LinkedGraph<(), (), Directed> as Graph>::EdgeId;
+
+        fn node_ids(&self) -> impl Iterator<Item = Self::NodeId> {
+            self.inner.node_ids()
+        }
+
+        fn node_data(&self, id: &Self::NodeId) -> &Self::NodeData {
+            self.inner.node_data(id)
+        }
+
+        fn edge_ids(&self) -> impl Iterator<Item = Self::EdgeId> + '_ {
+            self.inner.edge_ids()
+        }
+
+        fn edge_data(&self, id: &Self::EdgeId) -> &Self::EdgeData {
+            self.inner.edge_data(id)
+        }
+
+        fn edge_ends(
+            &self,
+            id: &Self::EdgeId,
+        ) -> <Self::Directedness as Directedness>::EndPair<Self::NodeId> {
+            self.inner.edge_ends(id)
+        }
+
+        fn edges_from<'a, 'b: 'a>(
+            &'a self,
+            from: &'b Self::NodeId,
+        ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+            self.inner.edges_from(from).filter(|_| !self.hide_from)
+        }
+
+        fn edges_from_into<'a, 'b: 'a>(
+            &'a self,
+            from: &'b Self::NodeId,
+            into: &'b Self::NodeId,
+        ) -> impl Iterator<Item = Self::EdgeId> + 'a {
+            self.inner.edges_from_into(from, into)
+        }
+
+        fn num_edges(&self) -> usize {
+            self.inner.num_edges() + self.extra_edges
+        }
+
+        fn num_edges_from(&self, from: &Self::NodeId) -> usize {
+            self.inner.num_edges_from(from)
+        }
+
+        fn has_edge_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> bool {
+            !self.hide_between && self.inner.has_edge_from_into(from, into)
+        }
+
+        fn num_edges_from_into(&self, from: &Self::NodeId, into: &Self::NodeId) -> usize {
+            self.inner.num_edges_from_into(from, into) - usize::from(self.hide_between)
+        }
+    }
+
+    #[test]
+    fn test_valid() {
+        let mut graph = LinkedGraph::<(), (), Undirected>::default();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(&a, &b, ());
+        graph.add_edge(&b, &b, ());
+        graph.add_edge(&a, &b, ());
+        assert_eq!(graph.validate(), vec![]);
+    }
+
+    #[test]
+    fn test_violations() {
+        let mut inner = LinkedGraph::default();
+        let a = inner.add_node(());
+        let b = inner.add_node(());
+        let ab = inner.add_new_edge(&a, &b, ());
+        let ab2 = inner.add_new_edge(&a, &b, ());
+        let mut graph = Faulty {
+            inner,
+            extra_edges: 1,
+            hide_from: false,
+            hide_between: false,
+        };
+        let violations = graph.validate();
+        assert_eq!(
+            violations,
+            vec![
+                Violation::ParallelEdges {
+                    from: a.clone(),
+                    into: b.clone(),
+                    count: 2,
+                },
+                Violation::EdgeCountMismatch {
+                    reported: 3,
+                    actual: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            violations[1].to_string(),
+            "graph reports 3 edges but lists 2"
+        );
+
+        graph.extra_edges = 0;
+        graph.hide_from = true;
+        let violations = graph.validate();
+        for edge in [&ab, &ab2] {
+            assert!(violations.contains(&Violation::UnlistedEdge {
+                edge: edge.clone(),
+                node: a.clone(),
+                direction: Direction::Out,
+            }));
+        }
+        assert!(violations.contains(&Violation::DegreeMismatch {
+            node: a.clone(),
+            direction: Direction::Out,
+            reported: 2,
+            actual: 0,
+        }));
+
+        graph.hide_from = false;
+        graph.hide_between = true;
+        let violations = graph.validate();
+        assert!(violations.contains(&Violation::UndetectedEdge {
+            edge: ab.clone(),
+            from: a.clone(),
+            into: b.clone(),
+        }));
+        assert!(violations.contains(&Violation::EdgeCountBetweenMismatch {
+            from: a,
+            into: b,
+            reported: 1,
+            actual: 2,
+        }));
+    }
+}
@@ -0,0 +1,255 @@
+//! Vertex covers and independent sets.
+//!
+//! A vertex cover is a set of nodes which includes at least one end of every
+//! edge, and an independent set is a set of nodes no two of which are
+//! adjacent.  The complement of a vertex cover is an independent set, so a
+//! minimum vertex cover gives a maximum independent set.
+//!
+//! Finding either exactly is NP-hard.  [`exact_vertex_cover`] uses branch and
+//! bound, which is practical for graphs with up to a few dozen nodes, and
+//! [`approx_vertex_cover`] finds a cover at most twice the minimum size in
+//! linear time.  [`min_vertex_cover`] and [`max_independent_set`] choose
+//! between them based on [`EXACT_LIMIT`].
+//!
+//! Edge direction and parallel edges are ignored.  A node with a self-loop
+//! is in every vertex cover and in no independent set.
+
+use std::collections::HashSet;
+
+use crate::{
+    end_pair::EndPair,
+    hashing::{FastHashMap, FastHashSet},
+    prelude::*,
+};
+
+/// The largest number of nodes for which [`min_vertex_cover`] and
+/// [`max_independent_set`] find an exact solution.
+pub const EXACT_LIMIT: usize = 32;
+
+/// Returns a minimum vertex cover if the graph has at most [`EXACT_LIMIT`]
+/// nodes, or a 2-approximation otherwise.
+pub fn min_vertex_cover<G>(graph: &G) -> HashSet<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    if graph.num_nodes() <= EXACT_LIMIT {
+        exact_vertex_cover(graph)
+    } else {
+        approx_vertex_cover(graph)
+    }
+}
+
+/// Returns a maximum independent set if the graph has at most
+/// [`EXACT_LIMIT`] nodes.  Otherwise returns a maximal independent set
+/// containing the complement of [`approx_vertex_cover`].
+pub fn max_independent_set<G>(graph: &G) -> HashSet<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    let cover = min_vertex_cover(graph);
+    let mut set: HashSet<_> = graph
+        .node_ids()
+        .filter(|nid| !cover.contains(nid))
+        .collect();
+    // An approximate cover may leave room for more nodes.
+    for nid in graph.node_ids() {
+        if cover.contains(&nid) {
+            let blocked = graph
+                .successors(&nid)
+                .chain(graph.predecessors(&nid))
+                .any(|other| other == nid || set.contains(&other));
+            if !blocked {
+                set.insert(nid);
+            }
+        }
+    }
+    set
+}
+
+/// Returns a vertex cover at most twice the size of a minimum one, made of
+/// the ends of a maximal matching.
+pub fn approx_vertex_cover<G>(graph: &G) -> HashSet<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    let mut cover = HashSet::new();
+    for eid in graph.edge_ids() {
+        let (a, b) = graph.edge_ends(&eid).into_values();
+        if !cover.contains(&a) && !cover.contains(&b) {
+            cover.insert(a);
+            cover.insert(b);
+        }
+    }
+    cover
+}
+
+/// Returns a minimum vertex cover, using branch and bound.  The running time
+/// is exponential in the number of nodes in the worst case.
+pub fn exact_vertex_cover<G>(graph: &G) -> HashSet<G::NodeId>
+where
+    G: Graph + ?Sized,
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let index: FastHashMap<_, _> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+    let mut neighbors = vec![Vec::new(); nodes.len()];
+    let mut alive = vec![true; nodes.len()];
+    let mut cover = Vec::new();
+    for eid in graph.edge_ids() {
+        let (a, b) = graph.edge_ends(&eid).into_values();
+        let (a, b) = (index[&a], index[&b]);
+        if a == b {
+            // A self-loop can only be covered by its node.
+            if alive[a] {
+                alive[a] = false;
+                cover.push(a);
+            }
+        } else if !neighbors[a].contains(&b) {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+    }
+
+    let initial: FastHashSet<_> = approx_vertex_cover(graph)
+        .iter()
+        .map(|nid| index[nid])
+        .collect();
+    let mut search = CoverSearch {
+        neighbors: &neighbors,
+        best: initial.into_iter().collect(),
+    };
+    search.run(&mut alive, &mut cover);
+    search.best.into_iter().map(|i| nodes[i].clone()).collect()
+}
+
+/// The state of the branch and bound search in [`exact_vertex_cover`].
+/// Nodes are represented by indices, and only the nodes which are alive and
+/// the edges between them remain to be covered.
+struct CoverSearch<'a> {
+    neighbors: &'a [Vec<usize>],
+    best: Vec<usize>,
+}
+
+impl CoverSearch<'_> {
+    fn degree(&self, alive: &[bool], node: usize) -> usize {
+        self.neighbors[node].iter().filter(|&&n| alive[n]).count()
+    }
+
+    fn run(&mut self, alive: &mut [bool], cover: &mut Vec<usize>) {
+        let mut max_degree = 0;
+        let mut max_node = 0;
+        let mut total_degree = 0;
+        for node in (0..alive.len()).filter(|&n| alive[n]) {
+            let degree = self.degree(alive, node);
+            total_degree += degree;
+            if degree > max_degree {
+                max_degree = degree;
+                max_node = node;
+            }
+        }
+        if max_degree == 0 {
+            if cover.len() < self.best.len() {
+                self.best = cover.clone();
+            }
+            return;
+        }
+        // Each node covers at most `max_degree` of the remaining edges.
+        let num_edges = total_degree / 2;
+        if cover.len() + num_edges.div_ceil(max_degree) >= self.best.len() {
+            return;
+        }
+
+        // Either the node is in the cover...
+        alive[max_node] = false;
+        cover.push(max_node);
+        self.run(alive, cover);
+        cover.pop();
+
+        // ...or all of its neighbors are.
+        let taken: Vec<_> = self.neighbors[max_node]
+            .iter()
+            .copied()
+            .filter(|&n| alive[n])
+            .collect();
+        for &n in &taken {
+            alive[n] = false;
+        }
+        cover.extend(&taken);
+        self.run(alive, cover);
+        cover.truncate(cover.len() - taken.len());
+        for &n in &taken {
+            alive[n] = true;
+        }
+        alive[max_node] = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagGraph, LinkedGraph, generators};
+
+    use super::*;
+
+    fn is_cover<G: Graph>(graph: &G, cover: &HashSet<G::NodeId>) -> bool {
+        graph.edge_ids().all(|eid| {
+            let (a, b) = graph.edge_ends(&eid).into_values();
+            cover.contains(&a) || cover.contains(&b)
+        })
+    }
+
+    fn is_independent<G: Graph>(graph: &G, set: &HashSet<G::NodeId>) -> bool {
+        graph.edge_ids().all(|eid| {
+            let (a, b) = graph.edge_ends(&eid).into_values();
+            !set.contains(&a) || !set.contains(&b)
+        })
+    }
+
+    #[test]
+    fn test_exact_vertex_cover() {
+        // The Petersen graph has a minimum vertex cover of size 6.
+        let mut graph = BagGraph::<usize, (), Undirected>::default();
+        let nodes: Vec<_> = (0..10).map(|i| graph.add_node(i)).collect();
+        for i in 0..5 {
+            graph.add_edge(&nodes[i], &nodes[(i + 1) % 5], ());
+            graph.add_edge(&nodes[i], &nodes[i + 5], ());
+            graph.add_edge(&nodes[i + 5], &nodes[(i + 2) % 5 + 5], ());
+        }
+        let cover = graph.min_vertex_cover();
+        assert!(is_cover(&graph, &cover));
+        assert_eq!(cover.len(), 6);
+
+        let set = graph.max_independent_set();
+        assert!(is_independent(&graph, &set));
+        assert_eq!(set.len(), 4);
+    }
+
+    #[test]
+    fn test_self_loops_and_direction() {
+        let mut graph = LinkedGraph::<char, (), Directed>::default();
+        let a = graph.add_node('a');
+        let b = graph.add_node('b');
+        let c = graph.add_node('c');
+        graph.add_edge(&a, &a, ());
+        graph.add_edge(&b, &a, ());
+        graph.add_edge(&c, &a, ());
+        assert_eq!(graph.min_vertex_cover(), HashSet::from([a.clone()]));
+        assert_eq!(graph.max_independent_set(), HashSet::from([b, c]));
+    }
+
+    #[test]
+    fn test_approx_vertex_cover() {
+        let graph: BagGraph<usize, (), Undirected> =
+            generators::cycle_graph(EXACT_LIMIT + 2, |i| i, |_, _| ());
+        let exact = exact_vertex_cover(&graph);
+        let approx = min_vertex_cover(&graph);
+        assert_eq!(exact.len(), EXACT_LIMIT / 2 + 1);
+        assert!(is_cover(&graph, &approx));
+        assert!(approx.len() <= 2 * exact.len());
+
+        let set = max_independent_set(&graph);
+        assert!(is_independent(&graph, &set));
+        // The set is maximal.
+        assert!(graph.node_ids().all(|nid| {
+            set.contains(&nid) || graph.successors(&nid).any(|other| set.contains(&other))
+        }));
+    }
+}
@@ -26,9 +26,43 @@ mod linked {
          GraphTests<LinkedGraph<String, String, Undirected, SingleEdge>>);
 }
 
+mod slab {
+    pub use graphitude::{SlabGraph, prelude::*};
+    use graphitude::{graph_test_suite, graph_tests::GraphTests};
+
+    graph_test_suite!(directed_multiple:
+        GraphTests<SlabGraph<String, String, Directed, MultipleEdges>>);
+
+    graph_test_suite!(directed_single:
+        GraphTests<SlabGraph<String, String, Directed, SingleEdge>>);
+
+    graph_test_suite!(undirected_multiple:
+        GraphTests<SlabGraph<String, String, Undirected, MultipleEdges>>);
+
+    graph_test_suite!(undirected_single:
+         GraphTests<SlabGraph<String, String, Undirected, SingleEdge>>);
+}
+
+mod persistent {
+    pub use graphitude::{PersistentGraph, prelude::*};
+    use graphitude::{graph_test_suite, graph_tests::GraphTests};
+
+    graph_test_suite!(directed_multiple:
+        GraphTests<PersistentGraph<String, String, Directed, MultipleEdges>>);
+
+    graph_test_suite!(directed_single:
+        GraphTests<PersistentGraph<String, String, Directed, SingleEdge>>);
+
+    graph_test_suite!(undirected_multiple:
+        GraphTests<PersistentGraph<String, String, Undirected, MultipleEdges>>);
+
+    graph_test_suite!(undirected_single:
+         GraphTests<PersistentGraph<String, String, Undirected, SingleEdge>>);
+}
+
 mod adjacency {
     use graphitude::{AdjacencyGraph, graph_test_suite};
-    use graphitude::{BitvecStorage, HashStorage, graph_tests::GraphTests, prelude::*};
+    use graphitude::{BitvecStorage, CsrStorage, HashStorage, graph_tests::GraphTests, prelude::*};
 
     graph_test_suite!(directed_single_bitvec:
         GraphTests<AdjacencyGraph<String, String, Directed, SingleEdge, BitvecStorage>>);
@@ -53,4 +87,16 @@ mod adjacency {
 
     graph_test_suite!(undirected_multiple_hash:
         GraphTests<AdjacencyGraph<String, String, Undirected, MultipleEdges, HashStorage>>);
+
+    graph_test_suite!(directed_single_csr:
+        GraphTests<AdjacencyGraph<String, String, Directed, SingleEdge, CsrStorage>>);
+
+    graph_test_suite!(undirected_single_csr:
+        GraphTests<AdjacencyGraph<String, String, Undirected, SingleEdge, CsrStorage>>);
+
+    graph_test_suite!(directed_multiple_csr:
+        GraphTests<AdjacencyGraph<String, String, Directed, MultipleEdges, CsrStorage>>);
+
+    graph_test_suite!(undirected_multiple_csr:
+        GraphTests<AdjacencyGraph<String, String, Undirected, MultipleEdges, CsrStorage>>);
 }